@@ -0,0 +1,134 @@
+#!/usr/bin/env python3
+"""faster-whisper sidecar for voxtype ([whisper] mode = "faster-whisper").
+
+voxtype starts this script, loads a model through it and sends it the
+recordings to transcribe. Requests come in on stdin and responses go out on
+stdout, one JSON object per line; the audio of a transcribe request follows
+its line as raw little-endian f32 samples (16 kHz mono). Anything printed
+by faster-whisper or CTranslate2 goes to stderr, which ends up in voxtype's
+log. The sidecar exits when stdin is closed.
+
+See docs/FASTER_WHISPER.md for the protocol.
+"""
+
+import json
+import os
+import sys
+
+PROTOCOL = 1
+
+
+def main():
+    requests = sys.stdin.buffer
+    # Keep stdout for responses; everything else printed goes to stderr
+    responses = os.fdopen(os.dup(1), "w", encoding="utf-8")
+    os.dup2(2, 1)
+    sys.stdout = sys.stderr
+
+    def send(message):
+        responses.write(json.dumps(message) + "\n")
+        responses.flush()
+
+    model = None
+    while True:
+        line = requests.readline()
+        if not line:
+            return
+        try:
+            request = json.loads(line)
+        except ValueError as e:
+            send({"type": "error", "message": "Invalid request: %s" % e})
+            continue
+
+        kind = request.get("type")
+        if kind == "ping":
+            send({"type": "pong"})
+        elif kind == "load":
+            if request.get("protocol") != PROTOCOL:
+                send({
+                    "type": "error",
+                    "message": "voxtype speaks sidecar protocol %s, this script %d"
+                    % (request.get("protocol"), PROTOCOL),
+                })
+                continue
+            try:
+                model = load(request)
+            except Exception as e:  # noqa: BLE001 - reported to voxtype
+                send({"type": "error", "message": "Failed to load model: %s" % e})
+                continue
+            send({"type": "ready", "protocol": PROTOCOL})
+        elif kind == "transcribe":
+            count = int(request.get("samples", 0))
+            audio = read_exactly(requests, count * 4)
+            if audio is None:
+                return
+            if model is None:
+                send({"type": "error", "message": "No model loaded"})
+                continue
+            try:
+                send(transcribe(model, request, audio))
+            except Exception as e:  # noqa: BLE001 - reported to voxtype
+                send({"type": "error", "message": str(e)})
+        else:
+            send({"type": "error", "message": "Unknown request type %r" % kind})
+
+
+def load(request):
+    try:
+        from faster_whisper import WhisperModel
+    except ImportError as e:
+        raise RuntimeError(
+            "%s (install it with: pip install faster-whisper)" % e
+        ) from e
+
+    return WhisperModel(
+        request["model"],
+        device=request.get("device") or "auto",
+        compute_type=request.get("compute_type") or "default",
+        cpu_threads=request.get("threads") or 0,
+        download_root=request.get("download_root"),
+    )
+
+
+def read_exactly(stream, size):
+    """Read the audio of a request, None if stdin closed before its end."""
+    chunks = []
+    while size > 0:
+        chunk = stream.read(size)
+        if not chunk:
+            return None
+        chunks.append(chunk)
+        size -= len(chunk)
+    return b"".join(chunks)
+
+
+def transcribe(model, request, audio):
+    import numpy as np
+
+    samples = np.frombuffer(audio, dtype="<f4")
+    language = request.get("language")
+    languages = request.get("languages") or []
+    if language is None and len(languages) > 1:
+        # Detect among the configured languages only
+        _, _, probabilities = model.detect_language(samples)
+        allowed = [(p, code) for code, p in probabilities if code in languages]
+        if allowed:
+            language = max(allowed)[1]
+
+    segments, info = model.transcribe(
+        samples,
+        language=language,
+        task=request.get("task") or "transcribe",
+        initial_prompt=request.get("initial_prompt"),
+    )
+    return {
+        "type": "result",
+        "language": info.language,
+        "segments": [
+            {"start": s.start, "end": s.end, "text": s.text} for s in segments
+        ],
+    }
+
+
+if __name__ == "__main__":
+    main()
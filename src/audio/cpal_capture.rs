@@ -1,11 +1,13 @@
 //! cpal-based audio capture
 //!
 //! Uses the cpal crate for cross-platform audio input.
-//! Works with PipeWire, PulseAudio, and ALSA backends.
+//! Works with PipeWire, PulseAudio, and ALSA backends, with CoreAudio on
+//! macOS and with WASAPI (shared mode) on Windows.
 //!
 //! Note: cpal::Stream is not Send, so we run the audio capture in a
 //! dedicated thread and communicate via channels.
 
+use super::filter::HighPass;
 use super::AudioCapture;
 use crate::config::AudioConfig;
 use crate::error::AudioError;
@@ -27,6 +29,8 @@ struct StreamBuildParams {
     source_rate: u32,
     target_rate: u32,
     source_channels: usize,
+    /// Cutoff of the high-pass filter in Hz, 0 for none
+    high_pass_hz: u32,
 }
 
 /// cpal-based audio capture implementation
@@ -50,80 +54,128 @@ impl CpalCapture {
     }
 }
 
-/// Find an audio input device by name with flexible matching.
+/// Prefix that switches device selection to pattern matching
+const MATCH_PREFIX: &str = "match:";
+
+/// Select a device name from the list of available input devices.
 ///
 /// Matching strategy (in order):
-/// 1. Exact match (case-sensitive)
-/// 2. Exact match (case-insensitive)
-/// 3. Substring match: device name contains the search term (case-insensitive)
+/// 1. `match:<pattern>`: first device whose name matches the pattern as a
+///    case-insensitive regex (falls back to a plain substring if the pattern
+///    is not a valid regex)
+/// 2. Exact match (case-sensitive)
+/// 3. Exact match (case-insensitive)
+/// 4. Substring match: device name contains the search term (case-insensitive)
 ///
 /// This allows users to specify either:
 /// - Full cpal device names: "alsa_input.pci-0000_00_1f.3.analog-stereo"
 /// - PipeWire/PulseAudio short names: "vox_buffer"
 /// - Partial device names: "analog-stereo"
-fn find_audio_device(host: &cpal::Host, device_name: &str) -> Result<cpal::Device, AudioError> {
-    use cpal::traits::{DeviceTrait, HostTrait};
-
-    let devices: Vec<cpal::Device> = host
-        .input_devices()
-        .map_err(|e| AudioError::Connection(e.to_string()))?
-        .collect();
-
-    // Collect device names for error message
-    let device_names: Vec<String> = devices.iter().filter_map(|d| d.name().ok()).collect();
+/// - Patterns that survive reboots and port changes: "match:Jabra"
+pub(super) fn select_device_name<'a>(
+    device_names: &'a [String],
+    device_name: &str,
+) -> Option<&'a str> {
+    if let Some(pattern) = device_name.strip_prefix(MATCH_PREFIX) {
+        let pattern = pattern.trim();
+        let matched = match regex::RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+        {
+            Ok(re) => device_names.iter().find(|n| re.is_match(n)),
+            Err(e) => {
+                tracing::warn!(
+                    "Invalid audio device pattern '{}' ({}), using substring match",
+                    pattern,
+                    e
+                );
+                let pattern_lower = pattern.to_lowercase();
+                device_names
+                    .iter()
+                    .find(|n| n.to_lowercase().contains(&pattern_lower))
+            }
+        };
+        if let Some(name) = matched {
+            tracing::debug!(
+                "Found audio device by pattern match: {} (pattern: {})",
+                name,
+                pattern
+            );
+        }
+        return matched.map(|n| n.as_str());
+    }
 
     let search_lower = device_name.to_lowercase();
 
     // 1. Try exact match (case-sensitive)
-    for device in &devices {
-        if let Ok(name) = device.name() {
-            if name == device_name {
-                tracing::debug!("Found audio device by exact match: {}", name);
-                return host
-                    .input_devices()
-                    .map_err(|e| AudioError::Connection(e.to_string()))?
-                    .find(|d| d.name().map(|n| n == device_name).unwrap_or(false))
-                    .ok_or_else(|| AudioError::DeviceNotFound(device_name.to_string()));
-            }
-        }
+    if let Some(name) = device_names.iter().find(|n| *n == device_name) {
+        tracing::debug!("Found audio device by exact match: {}", name);
+        return Some(name);
     }
 
     // 2. Try exact match (case-insensitive)
-    for device in &devices {
-        if let Ok(name) = device.name() {
-            if name.to_lowercase() == search_lower {
-                tracing::debug!(
-                    "Found audio device by case-insensitive match: {} (searched for: {})",
-                    name,
-                    device_name
-                );
-                let matched_name = name.clone();
-                return host
-                    .input_devices()
-                    .map_err(|e| AudioError::Connection(e.to_string()))?
-                    .find(|d| d.name().map(|n| n == matched_name).unwrap_or(false))
-                    .ok_or_else(|| AudioError::DeviceNotFound(device_name.to_string()));
-            }
-        }
+    if let Some(name) = device_names
+        .iter()
+        .find(|n| n.to_lowercase() == search_lower)
+    {
+        tracing::debug!(
+            "Found audio device by case-insensitive match: {} (searched for: {})",
+            name,
+            device_name
+        );
+        return Some(name);
     }
 
     // 3. Try substring match (case-insensitive)
-    for device in &devices {
-        if let Ok(name) = device.name() {
-            if name.to_lowercase().contains(&search_lower) {
-                tracing::debug!(
-                    "Found audio device by substring match: {} (searched for: {})",
-                    name,
-                    device_name
-                );
-                let matched_name = name.clone();
-                return host
-                    .input_devices()
-                    .map_err(|e| AudioError::Connection(e.to_string()))?
-                    .find(|d| d.name().map(|n| n == matched_name).unwrap_or(false))
-                    .ok_or_else(|| AudioError::DeviceNotFound(device_name.to_string()));
-            }
-        }
+    if let Some(name) = device_names
+        .iter()
+        .find(|n| n.to_lowercase().contains(&search_lower))
+    {
+        tracing::debug!(
+            "Found audio device by substring match: {} (searched for: {})",
+            name,
+            device_name
+        );
+        return Some(name);
+    }
+
+    None
+}
+
+/// List the names of all input devices, with the default device first
+pub fn list_input_devices() -> Result<(Option<String>, Vec<String>), AudioError> {
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    let host = cpal::default_host();
+    let default = host.default_input_device().and_then(|d| d.name().ok());
+    let names = host
+        .input_devices()
+        .map_err(|e| AudioError::Connection(e.to_string()))?
+        .filter_map(|d| d.name().ok())
+        .collect();
+
+    Ok((default, names))
+}
+
+/// Find an audio input device by name with flexible matching.
+///
+/// See [`select_device_name`] for the matching rules.
+fn find_audio_device(host: &cpal::Host, device_name: &str) -> Result<cpal::Device, AudioError> {
+    use cpal::traits::{DeviceTrait, HostTrait};
+
+    let devices: Vec<cpal::Device> = host
+        .input_devices()
+        .map_err(|e| AudioError::Connection(e.to_string()))?
+        .collect();
+
+    // Collect device names for matching and the error message
+    let device_names: Vec<String> = devices.iter().filter_map(|d| d.name().ok()).collect();
+
+    if let Some(matched_name) = select_device_name(&device_names, device_name) {
+        return devices
+            .into_iter()
+            .find(|d| d.name().map(|n| n == matched_name).unwrap_or(false))
+            .ok_or_else(|| AudioError::DeviceNotFound(device_name.to_string()));
     }
 
     // No match found - provide helpful error with available devices
@@ -162,7 +214,15 @@ impl AudioCapture for CpalCapture {
         };
 
         let device_name = device.name().unwrap_or_else(|_| "unknown".to_string());
-        tracing::info!("Using audio device: {}", device_name);
+        if self.config.device.starts_with(MATCH_PREFIX) {
+            tracing::info!(
+                "Using audio device: {} (resolved from '{}')",
+                device_name,
+                self.config.device
+            );
+        } else {
+            tracing::info!("Using audio device: {}", device_name);
+        }
 
         // Get supported config
         let supported_config = device
@@ -172,6 +232,7 @@ impl AudioCapture for CpalCapture {
         let source_sample_rate = supported_config.sample_rate().0;
         let source_channels = supported_config.channels() as usize;
         let target_sample_rate = self.config.sample_rate;
+        let high_pass_hz = self.config.high_pass_hz;
         let sample_format = supported_config.sample_format();
 
         tracing::debug!(
@@ -207,6 +268,7 @@ impl AudioCapture for CpalCapture {
                 source_rate: source_sample_rate,
                 target_rate: target_sample_rate,
                 source_channels,
+                high_pass_hz,
             };
 
             let stream_result = match sample_format {
@@ -364,8 +426,12 @@ where
         source_rate,
         target_rate,
         source_channels,
+        high_pass_hz,
     } = params;
 
+    // Filtered here so the streamed chunks match the recording
+    let mut high_pass = (high_pass_hz > 0).then(|| HighPass::new(high_pass_hz, target_rate));
+
     let stream = device
         .build_input_stream(
             config,
@@ -383,11 +449,14 @@ where
                     .collect();
 
                 // Resample if needed
-                let resampled = if source_rate != target_rate {
+                let mut resampled = if source_rate != target_rate {
                     resample(&mono_f32, source_rate, target_rate)
                 } else {
                     mono_f32
                 };
+                if let Some(ref mut high_pass) = high_pass {
+                    high_pass.process(&mut resampled);
+                }
 
                 // Store samples
                 if let Ok(mut guard) = samples.lock() {
@@ -407,7 +476,7 @@ where
 
 /// Linear interpolation resampling
 /// For better quality, consider using the `rubato` crate
-fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+pub(crate) fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
     if from_rate == to_rate || samples.is_empty() {
         return samples.to_vec();
     }
@@ -437,6 +506,46 @@ fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
 mod tests {
     use super::*;
 
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_select_device_exact_before_substring() {
+        let devices = names(&["pulse-mic", "pulse"]);
+        assert_eq!(select_device_name(&devices, "pulse"), Some("pulse"));
+        assert_eq!(select_device_name(&devices, "PULSE"), Some("pulse"));
+        assert_eq!(select_device_name(&devices, "mic"), Some("pulse-mic"));
+        assert_eq!(select_device_name(&devices, "jabra"), None);
+    }
+
+    #[test]
+    fn test_select_device_match_prefix() {
+        let devices = names(&[
+            "sysdefault:CARD=PCH",
+            "sysdefault:CARD=Jabra",
+            "front:CARD=Jabra,DEV=0",
+        ]);
+        assert_eq!(
+            select_device_name(&devices, "match:jabra"),
+            Some("sysdefault:CARD=Jabra")
+        );
+        assert_eq!(
+            select_device_name(&devices, "match:^front:.*jabra"),
+            Some("front:CARD=Jabra,DEV=0")
+        );
+        assert_eq!(select_device_name(&devices, "match:Logitech"), None);
+    }
+
+    #[test]
+    fn test_select_device_invalid_pattern_falls_back_to_substring() {
+        let devices = names(&["Jabra (USB", "PCH"]);
+        assert_eq!(
+            select_device_name(&devices, "match:jabra (usb"),
+            Some("Jabra (USB")
+        );
+    }
+
     #[test]
     fn test_resample_same_rate() {
         let samples = vec![1.0, 2.0, 3.0, 4.0];
@@ -8,11 +8,10 @@
 //! layer and can access monitor sources that aren't visible to ALSA.
 
 use super::cpal_capture::CpalCapture;
+use super::monitor_capture::{find_monitor_source, ParecLoopback};
 use super::AudioCapture;
 use crate::config::AudioConfig;
 use crate::error::AudioError;
-use std::process::Stdio;
-use std::sync::{Arc, Mutex};
 
 /// Audio source identifier
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,112 +33,6 @@ pub struct SourcedSample {
     pub timestamp: u64,
 }
 
-/// Loopback capture via parec subprocess
-struct ParecLoopback {
-    /// Source name (PulseAudio/PipeWire source)
-    source: String,
-    /// Child process
-    child: Option<std::process::Child>,
-    /// Shared buffer for received samples
-    buffer: Arc<Mutex<Vec<f32>>>,
-    /// Reader thread handle
-    reader_thread: Option<std::thread::JoinHandle<()>>,
-}
-
-impl ParecLoopback {
-    fn new(source: String) -> Self {
-        Self {
-            source,
-            child: None,
-            buffer: Arc::new(Mutex::new(Vec::new())),
-            reader_thread: None,
-        }
-    }
-
-    fn start(&mut self) -> Result<(), AudioError> {
-        let mut child = std::process::Command::new("parec")
-            .args([
-                "--device", &self.source,
-                "--format=float32le",
-                "--channels=1",
-                "--rate=16000",
-                "--raw",
-            ])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::null())
-            .spawn()
-            .map_err(|e| AudioError::Connection(format!("Failed to start parec: {}", e)))?;
-
-        let mut stdout = child.stdout.take()
-            .ok_or_else(|| AudioError::Connection("Failed to capture parec stdout".to_string()))?;
-
-        self.child = Some(child);
-        tracing::info!("Loopback capture started via parec: {}", self.source);
-
-        // Spawn reader thread
-        let buffer = Arc::clone(&self.buffer);
-        self.reader_thread = Some(std::thread::spawn(move || {
-            use std::io::Read;
-            let mut raw_buf = [0u8; 4096]; // 1024 f32 samples
-            loop {
-                match stdout.read(&mut raw_buf) {
-                    Ok(0) => break, // EOF
-                    Ok(n) => {
-                        // Convert raw bytes to f32 samples
-                        let sample_count = n / 4;
-                        let mut samples = Vec::with_capacity(sample_count);
-                        for i in 0..sample_count {
-                            let offset = i * 4;
-                            if offset + 4 <= n {
-                                let sample = f32::from_le_bytes([
-                                    raw_buf[offset],
-                                    raw_buf[offset + 1],
-                                    raw_buf[offset + 2],
-                                    raw_buf[offset + 3],
-                                ]);
-                                samples.push(sample);
-                            }
-                        }
-                        if let Ok(mut buf) = buffer.lock() {
-                            buf.extend(samples);
-                        }
-                    }
-                    Err(_) => break,
-                }
-            }
-            tracing::debug!("Loopback reader thread stopped");
-        }));
-
-        Ok(())
-    }
-
-    fn get_samples(&self) -> Vec<f32> {
-        if let Ok(mut buf) = self.buffer.lock() {
-            std::mem::take(&mut *buf)
-        } else {
-            Vec::new()
-        }
-    }
-
-    fn stop(&mut self) {
-        if let Some(ref mut child) = self.child {
-            let _ = child.kill();
-            let _ = child.wait();
-        }
-        self.child = None;
-        if let Some(thread) = self.reader_thread.take() {
-            let _ = thread.join();
-        }
-        tracing::debug!("Loopback capture stopped");
-    }
-}
-
-impl Drop for ParecLoopback {
-    fn drop(&mut self) {
-        self.stop();
-    }
-}
-
 /// Dual audio capture for mic + loopback
 pub struct DualCapture {
     /// Microphone capture (via cpal/ALSA)
@@ -160,21 +53,22 @@ impl DualCapture {
 
         let loopback = match loopback_device {
             Some("disabled") | Some("") | None => None,
-            Some("auto") => {
-                match Self::find_monitor_source() {
-                    Some(source) => {
-                        tracing::info!("Auto-detected loopback source: {}", source);
-                        Some(ParecLoopback::new(source))
-                    }
-                    None => {
-                        tracing::warn!("No monitor source found, using mic only");
-                        None
-                    }
+            Some("auto") => match find_monitor_source() {
+                Some(source) => {
+                    tracing::info!("Auto-detected loopback source: {}", source);
+                    Some(ParecLoopback::new(source, mic_config.sample_rate))
                 }
-            }
+                None => {
+                    tracing::warn!("No monitor source found, using mic only");
+                    None
+                }
+            },
             Some(device) => {
                 tracing::info!("Using configured loopback source: {}", device);
-                Some(ParecLoopback::new(device.to_string()))
+                Some(ParecLoopback::new(
+                    device.to_string(),
+                    mic_config.sample_rate,
+                ))
             }
         };
 
@@ -185,43 +79,6 @@ impl DualCapture {
         })
     }
 
-    /// Find a PipeWire/PulseAudio monitor source via pactl
-    fn find_monitor_source() -> Option<String> {
-        // pactl list short sources output format:
-        // ID\tNAME\tDRIVER\tFORMAT\tSTATUS
-        let output = std::process::Command::new("pactl")
-            .args(["list", "short", "sources"])
-            .output()
-            .ok()?;
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-
-        // First pass: prefer RUNNING monitor sources (active audio output)
-        for line in stdout.lines() {
-            let fields: Vec<&str> = line.split('\t').collect();
-            if fields.len() >= 5 {
-                let name = fields[1];
-                let status = fields[4];
-                if name.contains(".monitor") && status == "RUNNING" {
-                    tracing::debug!("Found running monitor source: {}", name);
-                    return Some(name.to_string());
-                }
-            }
-        }
-        // Fall back to any monitor source
-        for line in stdout.lines() {
-            let fields: Vec<&str> = line.split('\t').collect();
-            if fields.len() >= 2 {
-                let name = fields[1];
-                if name.contains(".monitor") {
-                    tracing::debug!("Found monitor source: {}", name);
-                    return Some(name.to_string());
-                }
-            }
-        }
-        None
-    }
-
     /// Check if loopback capture is active
     pub fn has_loopback(&self) -> bool {
         self.loopback.is_some()
@@ -232,7 +89,7 @@ impl DualCapture {
         let _mic_rx = self.mic_capture.start().await?;
 
         if let Some(ref mut loopback) = self.loopback {
-            if let Err(e) = loopback.start() {
+            if let Err(e) = loopback.start(None) {
                 tracing::warn!("Loopback capture failed, continuing with mic only: {}", e);
                 self.loopback = None;
             }
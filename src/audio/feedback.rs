@@ -2,11 +2,34 @@
 //!
 //! Provides audio cues (beeps/sounds) for recording start/stop events.
 //! Supports multiple sound themes and custom sound files.
+//!
+//! The "xdg" theme uses the desktop's sound theme (freedesktop sound theme
+//! and naming specifications), so the cues match the rest of the desktop.
+//! Theme sounds are usually Ogg Vorbis, which are played with pw-play or
+//! paplay; events without a theme sound get the built-in default tones.
 
 use crate::config::AudioFeedbackConfig;
 use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use std::collections::HashSet;
 use std::io::Cursor;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Theme setting for the desktop's XDG sound theme (`xdg:<name>` picks one)
+const XDG_THEME: &str = "xdg";
+
+/// Players for theme sounds that rodio can't decode, in order of preference
+const PLAYERS: &[&str] = &["pw-play", "paplay"];
+
+/// Extensions of sound theme files, in order of preference
+const SOUND_EXTENSIONS: &[&str] = &["oga", "ogg", "wav"];
+
+/// Sound names (freedesktop naming specification) for each event, in order
+/// of preference
+const XDG_START: &[&str] = &["message-new-instant", "bell"];
+const XDG_STOP: &[&str] = &["complete", "message"];
+const XDG_CANCEL: &[&str] = &["window-close", "dialog-warning"];
+const XDG_ERROR: &[&str] = &["dialog-error", "dialog-warning"];
 
 /// Sound event types
 #[derive(Debug, Clone, Copy)]
@@ -27,14 +50,36 @@ pub struct AudioFeedback {
     stream_handle: OutputStreamHandle,
     config: AudioFeedbackConfig,
     theme: SoundTheme,
+    /// Player for [`Sound::File`] sounds
+    player: Option<&'static str>,
 }
 
 /// A sound theme containing audio data for different events
 struct SoundTheme {
-    start: Vec<u8>,
-    stop: Vec<u8>,
-    cancel: Vec<u8>,
-    error: Vec<u8>,
+    start: Sound,
+    stop: Sound,
+    cancel: Sound,
+    error: Sound,
+}
+
+/// The sound for one event
+enum Sound {
+    /// WAV data, played with rodio (empty for no sound)
+    Wav(Vec<u8>),
+    /// A sound theme file, played with an external player
+    File(PathBuf),
+}
+
+impl Sound {
+    fn is_empty(&self) -> bool {
+        matches!(self, Sound::Wav(data) if data.is_empty())
+    }
+}
+
+impl From<Vec<u8>> for Sound {
+    fn from(data: Vec<u8>) -> Self {
+        Sound::Wav(data)
+    }
 }
 
 impl AudioFeedback {
@@ -47,13 +92,25 @@ impl AudioFeedback {
         let (stream, stream_handle) = OutputStream::try_default()
             .map_err(|e| format!("Failed to open audio output: {}", e))?;
 
-        let theme = load_theme(&config.theme)?;
+        let mut theme = load_theme(&config.theme)?;
+
+        let uses_player = [&theme.start, &theme.stop, &theme.cancel, &theme.error]
+            .iter()
+            .any(|sound| matches!(sound, Sound::File(_)));
+        let player = if uses_player { find_player() } else { None };
+        if uses_player && player.is_none() {
+            tracing::warn!(
+                "Neither pw-play nor paplay found to play the sound theme, using built-in tones"
+            );
+            theme = generate_default_theme();
+        }
 
         Ok(Self {
             _stream: stream,
             stream_handle,
             config: config.clone(),
             theme,
+            player,
         })
     }
 
@@ -70,11 +127,37 @@ impl AudioFeedback {
             return;
         }
 
-        if let Err(e) = self.play_wav(sound_data) {
+        let result = match sound_data {
+            Sound::Wav(data) => self.play_wav(data),
+            Sound::File(path) => self.play_file(path),
+        };
+        if let Err(e) = result {
             tracing::warn!("Failed to play feedback sound: {}", e);
         }
     }
 
+    /// Play a sound theme file with the external player, in the background
+    fn play_file(&self, path: &Path) -> Result<(), String> {
+        let player = self.player.ok_or("No sound player")?;
+        let volume = self.config.volume.clamp(0.0, 1.0);
+        // paplay takes the volume as 0-65536, pw-play as 0.0-1.0
+        let volume = match player {
+            "paplay" => ((volume * 65536.0) as u32).to_string(),
+            _ => volume.to_string(),
+        };
+        let mut child = Command::new(player)
+            .arg("--volume")
+            .arg(volume)
+            .arg(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", player, e))?;
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+
     fn play_wav(&self, data: &[u8]) -> Result<(), String> {
         let cursor = Cursor::new(data.to_vec());
         let source = Decoder::new(cursor).map_err(|e| format!("Failed to decode audio: {}", e))?;
@@ -98,7 +181,11 @@ fn load_theme(theme_name: &str) -> Result<SoundTheme, String> {
         "default" => Ok(generate_default_theme()),
         "subtle" => Ok(generate_subtle_theme()),
         "mechanical" => Ok(generate_mechanical_theme()),
-        path => load_custom_theme(path),
+        XDG_THEME => Ok(load_xdg_theme(&desktop_sound_theme())),
+        name => match name.strip_prefix("xdg:") {
+            Some(xdg_theme) => Ok(load_xdg_theme(xdg_theme)),
+            None => load_custom_theme(name),
+        },
     }
 }
 
@@ -115,10 +202,176 @@ fn load_custom_theme(path: &str) -> Result<SoundTheme, String> {
     };
 
     Ok(SoundTheme {
-        start: load_file("start.wav"),
-        stop: load_file("stop.wav"),
-        cancel: load_file("cancel.wav"),
-        error: load_file("error.wav"),
+        start: load_file("start.wav").into(),
+        stop: load_file("stop.wav").into(),
+        cancel: load_file("cancel.wav").into(),
+        error: load_file("error.wav").into(),
+    })
+}
+
+/// Load the sounds of an XDG sound theme, with the default tones for events
+/// the theme has no sound for
+fn load_xdg_theme(theme: &str) -> SoundTheme {
+    let dirs = sound_dirs();
+    let fallback = generate_default_theme();
+    let find = |names: &[&str], fallback: Sound| {
+        find_theme_sound(&dirs, theme, names).map_or(fallback, Sound::File)
+    };
+    let sounds = SoundTheme {
+        start: find(XDG_START, fallback.start),
+        stop: find(XDG_STOP, fallback.stop),
+        cancel: find(XDG_CANCEL, fallback.cancel),
+        error: find(XDG_ERROR, fallback.error),
+    };
+    tracing::debug!(
+        "Sound theme {:?}: start {}, stop {}, cancel {}, error {}",
+        theme,
+        sounds.start,
+        sounds.stop,
+        sounds.cancel,
+        sounds.error
+    );
+    sounds
+}
+
+impl std::fmt::Display for Sound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sound::Wav(_) => write!(f, "built-in"),
+            Sound::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// The desktop's sound theme: GNOME (and derivatives) or KDE settings, or
+/// the "freedesktop" theme every other theme falls back to
+fn desktop_sound_theme() -> String {
+    let gsettings = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.sound", "theme-name"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .trim_matches('\'')
+                .to_string()
+        })
+        .filter(|name| !name.is_empty());
+    gsettings
+        .or_else(|| {
+            let kdeglobals = dirs::config_dir()?.join("kdeglobals");
+            ini_value(
+                &std::fs::read_to_string(kdeglobals).ok()?,
+                "Sounds",
+                "Theme",
+            )
+        })
+        .unwrap_or_else(|| "freedesktop".to_string())
+}
+
+/// Directories holding sound themes: `$XDG_DATA_HOME/sounds`, then
+/// `sounds` in each of `$XDG_DATA_DIRS`
+fn sound_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    dirs.extend(std::env::split_paths(&data_dirs));
+    dirs.into_iter().map(|dir| dir.join("sounds")).collect()
+}
+
+/// Find the first of `names` in a sound theme or the themes it inherits,
+/// ending with "freedesktop"
+///
+/// As the naming specification says, a name is looked for in all themes
+/// before the next name is tried.
+fn find_theme_sound(dirs: &[PathBuf], theme: &str, names: &[&str]) -> Option<PathBuf> {
+    let chain = theme_chain(dirs, theme);
+    names.iter().find_map(|name| {
+        chain.iter().find_map(|(theme, subdirs)| {
+            dirs.iter().find_map(|dir| {
+                subdirs.iter().find_map(|subdir| {
+                    SOUND_EXTENSIONS.iter().find_map(|extension| {
+                        let path = dir
+                            .join(theme)
+                            .join(subdir)
+                            .join(format!("{}.{}", name, extension));
+                        path.is_file().then_some(path)
+                    })
+                })
+            })
+        })
+    })
+}
+
+/// A theme and the themes it inherits, each with the directories of its
+/// stereo sounds (from `index.theme`)
+fn theme_chain(dirs: &[PathBuf], theme: &str) -> Vec<(String, Vec<String>)> {
+    let mut chain = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![theme.to_string()];
+    while let Some(theme) = pending.pop() {
+        if !seen.insert(theme.clone()) {
+            continue;
+        }
+        let index = dirs
+            .iter()
+            .find_map(|dir| std::fs::read_to_string(dir.join(&theme).join("index.theme")).ok())
+            .unwrap_or_default();
+        let list = |key: &str| -> Vec<String> {
+            ini_value(&index, "Sound Theme", key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(|item| item.trim().to_string())
+                        .filter(|item| !item.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let mut subdirs = list("Directories");
+        if subdirs.is_empty() {
+            subdirs.push("stereo".to_string());
+        }
+        // Inherited themes are searched in order, before their own parents
+        pending.extend(list("Inherits").into_iter().rev());
+        chain.push((theme, subdirs));
+    }
+    if !seen.contains("freedesktop") {
+        chain.push(("freedesktop".to_string(), vec!["stereo".to_string()]));
+    }
+    chain
+}
+
+/// Value of a key in a section of an INI-style file
+fn ini_value(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name == section;
+        } else if in_section {
+            if let Some((k, value)) = line.split_once('=') {
+                if k.trim() == key {
+                    return Some(value.trim().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// The first sound player on PATH
+fn find_player() -> Option<&'static str> {
+    PLAYERS.iter().copied().find(|player| {
+        Command::new("which")
+            .arg(player)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
     })
 }
 
@@ -231,13 +484,13 @@ fn encode_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
 fn generate_default_theme() -> SoundTheme {
     SoundTheme {
         // Rising two-tone: 440Hz -> 880Hz (musical, energizing)
-        start: generate_two_tone_wav(440.0, 880.0, 150, 20),
+        start: generate_two_tone_wav(440.0, 880.0, 150, 20).into(),
         // Falling two-tone: 880Hz -> 440Hz (completion)
-        stop: generate_two_tone_wav(880.0, 440.0, 150, 20),
+        stop: generate_two_tone_wav(880.0, 440.0, 150, 20).into(),
         // Quick descending triple-beep for cancel (distinct from stop)
-        cancel: generate_tone_wav(600.0, 80, 10),
+        cancel: generate_tone_wav(600.0, 80, 10).into(),
         // Low warning tone
-        error: generate_two_tone_wav(300.0, 200.0, 200, 30),
+        error: generate_two_tone_wav(300.0, 200.0, 200, 30).into(),
     }
 }
 
@@ -245,13 +498,13 @@ fn generate_default_theme() -> SoundTheme {
 fn generate_subtle_theme() -> SoundTheme {
     SoundTheme {
         // Soft high click
-        start: generate_tone_wav(1200.0, 50, 10),
+        start: generate_tone_wav(1200.0, 50, 10).into(),
         // Soft low click
-        stop: generate_tone_wav(800.0, 50, 10),
+        stop: generate_tone_wav(800.0, 50, 10).into(),
         // Quick mid-tone for cancel
-        cancel: generate_tone_wav(600.0, 40, 8),
+        cancel: generate_tone_wav(600.0, 40, 8).into(),
         // Double low click
-        error: generate_two_tone_wav(400.0, 300.0, 100, 15),
+        error: generate_two_tone_wav(400.0, 300.0, 100, 15).into(),
     }
 }
 
@@ -259,13 +512,13 @@ fn generate_subtle_theme() -> SoundTheme {
 fn generate_mechanical_theme() -> SoundTheme {
     SoundTheme {
         // Sharp click
-        start: generate_click_wav(30),
+        start: generate_click_wav(30).into(),
         // Softer click
-        stop: generate_click_wav(20),
+        stop: generate_click_wav(20).into(),
         // Double click for cancel
-        cancel: generate_click_wav(15),
+        cancel: generate_click_wav(15).into(),
         // Buzzer
-        error: generate_tone_wav(150.0, 150, 20),
+        error: generate_tone_wav(150.0, 150, 20).into(),
     }
 }
 
@@ -298,4 +551,41 @@ mod tests {
         assert!(!mechanical.start.is_empty());
         assert!(!mechanical.cancel.is_empty());
     }
+
+    #[test]
+    fn test_find_theme_sound() {
+        let dir = std::env::temp_dir().join(format!("voxtype-sounds-test-{}", std::process::id()));
+        let write = |path: &str, content: &str| {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "custom/index.theme",
+            "[Sound Theme]\nName=Custom\nInherits=base\nDirectories=stereo, 5.1\n",
+        );
+        write("custom/5.1/bell.wav", "");
+        write("base/stereo/complete.oga", "");
+        write("freedesktop/stereo/complete.oga", "");
+        write("freedesktop/stereo/message-new-instant.oga", "");
+        let dirs = vec![dir.clone()];
+
+        // A theme's own sound, then an inherited one before freedesktop's
+        assert_eq!(
+            find_theme_sound(&dirs, "custom", &["bell"]),
+            Some(dir.join("custom/5.1/bell.wav"))
+        );
+        assert_eq!(
+            find_theme_sound(&dirs, "custom", XDG_STOP),
+            Some(dir.join("base/stereo/complete.oga"))
+        );
+        // The first name is looked for in all themes before the next one
+        assert_eq!(
+            find_theme_sound(&dirs, "custom", XDG_START),
+            Some(dir.join("freedesktop/stereo/message-new-instant.oga"))
+        );
+        assert_eq!(find_theme_sound(&dirs, "custom", XDG_ERROR), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -0,0 +1,193 @@
+//! Loudness normalization (`[audio] normalize_loudness`)
+//!
+//! Measures the integrated loudness of a recording the way EBU R 128 does
+//! (ITU-R BS.1770: K-weighting, 400 ms blocks overlapping by 75%, an absolute
+//! gate at -70 LUFS and a relative gate 10 LU below the ungated loudness),
+//! then scales the recording to the target loudness. A quiet headset mic and
+//! a loud desk mic reach the transcriber at the same level.
+//!
+//! The gain is one factor for the whole recording, so the dynamics of speech
+//! are kept. It's limited so that faint noise isn't amplified into speech-like
+//! levels and so that no sample clips.
+
+use super::filter::Biquad;
+
+/// Sample rate of recordings
+const SAMPLE_RATE: f64 = 16000.0;
+
+/// Gating block of 400 ms
+const BLOCK: usize = 6400;
+
+/// Blocks start every 100 ms (75% overlap)
+const STEP: usize = 1600;
+
+/// Blocks quieter than this are silence (LUFS)
+const ABSOLUTE_GATE: f64 = -70.0;
+
+/// Blocks this far below the ungated loudness don't count (LU)
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Most a recording is amplified (dB)
+const MAX_GAIN_DB: f64 = 30.0;
+
+/// The two K-weighting stages of BS.1770 for the sample rate: a high shelf
+/// modelling the head, then a high-pass
+///
+/// The standard gives coefficients for 48 kHz only; these are derived from
+/// the same analog prototypes, as libebur128 does.
+fn k_weighting() -> [Biquad; 2] {
+    // High shelf: +4 dB above ~1.7 kHz
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (std::f64::consts::PI * 1_681.974_450_955_533 / SAMPLE_RATE).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    // High-pass at ~38 Hz
+    let q = 0.500_327_037_323_877_3;
+    let k = (std::f64::consts::PI * 38.135_470_876_024_44 / SAMPLE_RATE).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new(
+        [1.0, -2.0, 1.0],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    [shelf, high_pass]
+}
+
+/// Loudness (LUFS) of a mean square of K-weighted samples
+fn loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+/// Integrated loudness of 16 kHz mono audio in LUFS, None when it's silent
+///
+/// Audio shorter than a block is measured as one block.
+pub fn integrated_loudness(samples: &[f32]) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+
+    let [mut shelf, mut high_pass] = k_weighting();
+    let squared: Vec<f64> = samples
+        .iter()
+        .map(|&x| high_pass.process(shelf.process(x as f64)).powi(2))
+        .collect();
+
+    // Mean square of each block, from running sums
+    let mut sums = Vec::with_capacity(squared.len() + 1);
+    sums.push(0.0);
+    for (i, x) in squared.iter().enumerate() {
+        sums.push(sums[i] + x);
+    }
+    let block = BLOCK.min(squared.len());
+    let blocks: Vec<f64> = (0..=squared.len() - block)
+        .step_by(STEP)
+        .map(|start| (sums[start + block] - sums[start]) / block as f64)
+        .filter(|&power| power > 0.0 && loudness(power) > ABSOLUTE_GATE)
+        .collect();
+    if blocks.is_empty() {
+        return None;
+    }
+
+    let ungated = blocks.iter().sum::<f64>() / blocks.len() as f64;
+    let gate = loudness(ungated) + RELATIVE_GATE;
+    let gated: Vec<f64> = blocks
+        .into_iter()
+        .filter(|&power| loudness(power) > gate)
+        .collect();
+    Some(loudness(gated.iter().sum::<f64>() / gated.len() as f64))
+}
+
+/// Scale audio to the target loudness in LUFS
+///
+/// Returns the gain applied in dB, None when the audio is silent and was
+/// left alone.
+pub fn normalize(samples: &mut [f32], target_lufs: f32) -> Option<f32> {
+    let measured = integrated_loudness(samples)?;
+    let peak = samples.iter().fold(0.0f32, |peak, x| peak.max(x.abs())) as f64;
+
+    // At most MAX_GAIN_DB up, and never past full scale
+    let mut gain_db = (target_lufs as f64 - measured).min(MAX_GAIN_DB);
+    if peak > 0.0 {
+        gain_db = gain_db.min(-20.0 * peak.log10());
+    }
+    let gain = 10f64.powf(gain_db / 20.0) as f32;
+    for sample in samples.iter_mut() {
+        *sample *= gain;
+    }
+
+    tracing::debug!(
+        "Loudness {:.1} LUFS, applied {:+.1} dB for {:.1} LUFS",
+        measured,
+        gain_db,
+        target_lufs
+    );
+    Some(gain_db as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A second of a 1 kHz sine wave
+    fn sine(amplitude: f32) -> Vec<f32> {
+        (0..16000)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 16000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_integrated_loudness() {
+        // BS.1770 is calibrated so a full-scale 1 kHz sine reads -3.01 LUFS
+        let measured = integrated_loudness(&sine(1.0)).unwrap();
+        assert!((measured + 3.01).abs() < 0.1, "measured {}", measured);
+
+        // 20 dB quieter reads 20 LU lower
+        let quieter = integrated_loudness(&sine(0.1)).unwrap();
+        assert!((measured - quieter - 20.0).abs() < 0.1);
+
+        assert_eq!(integrated_loudness(&[]), None);
+        assert_eq!(integrated_loudness(&[0.0; 16000]), None);
+
+        // Silence around speech is gated out; only the blocks straddling
+        // its edges lower the loudness a little (ungated: ~4.8 LU)
+        let mut padded = vec![0.0; 16000];
+        padded.extend(sine(0.1));
+        padded.extend(vec![0.0; 16000]);
+        let padded = integrated_loudness(&padded).unwrap();
+        assert!((quieter - padded) < 1.5, "padded {}", padded);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut quiet = sine(0.01);
+        let gain = normalize(&mut quiet, -23.0).unwrap();
+        assert!((gain - 20.0).abs() < 0.2, "gain {}", gain);
+        let measured = integrated_loudness(&quiet).unwrap();
+        assert!((measured + 23.0).abs() < 0.1, "measured {}", measured);
+
+        // Loud audio is turned down
+        let mut loud = sine(0.9);
+        assert!(normalize(&mut loud, -23.0).unwrap() < 0.0);
+
+        // Amplification stops short of clipping
+        let mut peaky = sine(0.01);
+        peaky[100] = 0.5;
+        normalize(&mut peaky, -3.0).unwrap();
+        assert!(peaky.iter().all(|x| x.abs() <= 1.0));
+
+        // Silence is left alone
+        let mut silence = vec![0.0; 8000];
+        assert_eq!(normalize(&mut silence, -23.0), None);
+    }
+}
@@ -8,13 +8,20 @@ pub mod dual_capture;
 #[cfg(feature = "onnx-common")]
 pub mod enhance;
 pub mod feedback;
+pub mod filter;
+pub mod loudness;
+pub mod monitor_capture;
+pub mod pre_roll;
 
 pub use dual_capture::{AudioSourceType, DualCapture, DualSamples, SourcedSample};
 
-use crate::config::AudioConfig;
+use crate::config::{AudioConfig, AudioSource};
 use crate::error::AudioError;
 use tokio::sync::mpsc;
 
+/// Input level shown as silence (dBFS)
+const SILENCE_DB: f32 = -60.0;
+
 /// Trait for audio capture implementations
 #[async_trait::async_trait]
 pub trait AudioCapture: Send + Sync {
@@ -33,5 +40,40 @@ pub trait AudioCapture: Send + Sync {
 
 /// Factory function to create audio capture
 pub fn create_capture(config: &AudioConfig) -> Result<Box<dyn AudioCapture>, AudioError> {
-    Ok(Box::new(cpal_capture::CpalCapture::new(config)?))
+    match config.source {
+        AudioSource::Microphone => Ok(Box::new(cpal_capture::CpalCapture::new(config)?)),
+        AudioSource::Monitor => Ok(Box::new(monitor_capture::MonitorCapture::new(config)?)),
+    }
+}
+
+/// RMS level of the samples on a 0.0..=1.0 dB scale, for level meters
+pub fn rms_level(samples: &[f32]) -> f32 {
+    let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+    db_scale(rms)
+}
+
+/// Peak level of the samples on the same scale as [`rms_level`]
+pub fn peak_level(samples: &[f32]) -> f32 {
+    db_scale(samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs())))
+}
+
+/// Map an amplitude from -60 dBFS (0.0) to full scale (1.0)
+fn db_scale(amplitude: f32) -> f32 {
+    let db = 20.0 * amplitude.max(1e-6).log10();
+    (1.0 - db / SILENCE_DB).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert_eq!(rms_level(&[0.0; 160]), 0.0);
+        assert_eq!(rms_level(&[1.0, -1.0]), 1.0);
+        // -30 dBFS is half way
+        let quiet = 10f32.powf(-30.0 / 20.0);
+        assert!((rms_level(&[quiet, -quiet]) - 0.5).abs() < 1e-4);
+        assert!((peak_level(&[0.0, quiet, 0.0]) - 0.5).abs() < 1e-4);
+    }
 }
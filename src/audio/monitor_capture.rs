@@ -0,0 +1,347 @@
+//! System audio (monitor source) capture
+//!
+//! Records what is currently playing on the desktop instead of the
+//! microphone, so a hotkey press can transcribe a meeting or video.
+//!
+//! Monitor sources are not visible to ALSA, so capture goes through `parec`
+//! (PulseAudio recording client), which also works with PipeWire's
+//! PulseAudio compatibility layer. Sources are enumerated with `pactl`.
+
+use super::cpal_capture::select_device_name;
+use super::AudioCapture;
+use crate::config::AudioConfig;
+use crate::error::AudioError;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+/// A PulseAudio/PipeWire monitor source as reported by `pactl`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorSource {
+    /// Source name (pass to `audio.device` or `parec --device`)
+    pub name: String,
+    /// Whether the source is currently running (audio is playing)
+    pub running: bool,
+}
+
+/// Parse `pactl list short sources` output into monitor sources
+///
+/// Output format: `ID\tNAME\tDRIVER\tFORMAT\tSTATUS`
+fn parse_monitor_sources(output: &str) -> Vec<MonitorSource> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let name = *fields.get(1)?;
+            if !name.contains(".monitor") {
+                return None;
+            }
+            Some(MonitorSource {
+                name: name.to_string(),
+                running: fields.get(4).map(|s| *s == "RUNNING").unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+/// List available monitor sources via pactl
+///
+/// Returns an empty list if pactl is missing or fails.
+pub fn list_monitor_sources() -> Vec<MonitorSource> {
+    let output = match std::process::Command::new("pactl")
+        .args(["list", "short", "sources"])
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::debug!("Failed to run pactl: {}", e);
+            return Vec::new();
+        }
+    };
+
+    parse_monitor_sources(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Pick the best monitor source, preferring one that is currently running
+fn pick_monitor_source(sources: &[MonitorSource]) -> Option<&MonitorSource> {
+    sources
+        .iter()
+        .find(|s| s.running)
+        .or_else(|| sources.first())
+}
+
+/// Find a PipeWire/PulseAudio monitor source, preferring running ones
+pub fn find_monitor_source() -> Option<String> {
+    let sources = list_monitor_sources();
+    let source = pick_monitor_source(&sources)?;
+    tracing::debug!(
+        "Found {}monitor source: {}",
+        if source.running { "running " } else { "" },
+        source.name
+    );
+    Some(source.name.clone())
+}
+
+/// Resolve the configured device to a concrete monitor source name
+///
+/// "default" auto-detects, other values use the same matching rules as
+/// microphone devices (exact, case-insensitive, substring, `match:`).
+/// Names that don't match any listed source are passed through to parec
+/// unchanged, since pactl may hide sources that parec can still open.
+fn resolve_monitor_source(device: &str) -> Result<String, AudioError> {
+    if device == "default" {
+        return find_monitor_source().ok_or_else(|| {
+            AudioError::DeviceNotFound(
+                "monitor source (no .monitor source found, is PipeWire/PulseAudio running?)"
+                    .to_string(),
+            )
+        });
+    }
+
+    let names: Vec<String> = list_monitor_sources().into_iter().map(|s| s.name).collect();
+    match select_device_name(&names, device) {
+        Some(name) => Ok(name.to_string()),
+        None if device.starts_with("match:") => Err(AudioError::DeviceNotFoundWithList {
+            requested: device.to_string(),
+            available: if names.is_empty() {
+                "No monitor sources found.".to_string()
+            } else {
+                format!(
+                    "Available monitor sources:\n{}",
+                    names
+                        .iter()
+                        .map(|n| format!("  - {}", n))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )
+            },
+        }),
+        None => Ok(device.to_string()),
+    }
+}
+
+/// Raw audio capture via a parec subprocess
+pub(super) struct ParecLoopback {
+    /// Source name (PulseAudio/PipeWire source)
+    source: String,
+    /// Sample rate requested from parec
+    sample_rate: u32,
+    /// Child process
+    child: Option<std::process::Child>,
+    /// Shared buffer for received samples
+    buffer: Arc<Mutex<Vec<f32>>>,
+    /// Reader thread handle
+    reader_thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl ParecLoopback {
+    pub(super) fn new(source: String, sample_rate: u32) -> Self {
+        Self {
+            source,
+            sample_rate,
+            child: None,
+            buffer: Arc::new(Mutex::new(Vec::new())),
+            reader_thread: None,
+        }
+    }
+
+    /// Start parec, optionally streaming chunks to `chunk_tx`
+    pub(super) fn start(
+        &mut self,
+        chunk_tx: Option<mpsc::Sender<Vec<f32>>>,
+    ) -> Result<(), AudioError> {
+        let rate_arg = format!("--rate={}", self.sample_rate);
+        let mut child = std::process::Command::new("parec")
+            .args([
+                "--device",
+                &self.source,
+                "--format=float32le",
+                "--channels=1",
+                &rate_arg,
+                "--raw",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| AudioError::Connection(format!("Failed to start parec: {}", e)))?;
+
+        let mut stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| AudioError::Connection("Failed to capture parec stdout".to_string()))?;
+
+        self.child = Some(child);
+        tracing::info!("Loopback capture started via parec: {}", self.source);
+
+        // Spawn reader thread
+        let buffer = Arc::clone(&self.buffer);
+        self.reader_thread = Some(std::thread::spawn(move || {
+            use std::io::Read;
+            let mut raw_buf = [0u8; 4096]; // 1024 f32 samples
+            loop {
+                match stdout.read(&mut raw_buf) {
+                    Ok(0) => break, // EOF
+                    Ok(n) => {
+                        // Convert raw bytes to f32 samples
+                        let samples: Vec<f32> = raw_buf[..n - n % 4]
+                            .chunks_exact(4)
+                            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                            .collect();
+                        if let Ok(mut buf) = buffer.lock() {
+                            buf.extend_from_slice(&samples);
+                        }
+                        if let Some(ref tx) = chunk_tx {
+                            // Ignore errors - receiver might be gone
+                            let _ = tx.try_send(samples);
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+            tracing::debug!("Loopback reader thread stopped");
+        }));
+
+        Ok(())
+    }
+
+    pub(super) fn get_samples(&self) -> Vec<f32> {
+        if let Ok(mut buf) = self.buffer.lock() {
+            std::mem::take(&mut *buf)
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub(super) fn stop(&mut self) {
+        if let Some(ref mut child) = self.child {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        self.child = None;
+        if let Some(thread) = self.reader_thread.take() {
+            let _ = thread.join();
+        }
+        tracing::debug!("Loopback capture stopped");
+    }
+}
+
+impl Drop for ParecLoopback {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Monitor source capture implementation
+pub struct MonitorCapture {
+    /// Audio configuration
+    config: AudioConfig,
+    /// Active parec capture
+    parec: Option<ParecLoopback>,
+}
+
+impl MonitorCapture {
+    /// Create a new monitor capture instance
+    pub fn new(config: &AudioConfig) -> Result<Self, AudioError> {
+        Ok(Self {
+            config: config.clone(),
+            parec: None,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl AudioCapture for MonitorCapture {
+    async fn start(&mut self) -> Result<mpsc::Receiver<Vec<f32>>, AudioError> {
+        let source = resolve_monitor_source(&self.config.device)?;
+        if source == self.config.device {
+            tracing::info!("Using monitor source: {}", source);
+        } else {
+            tracing::info!(
+                "Using monitor source: {} (resolved from '{}')",
+                source,
+                self.config.device
+            );
+        }
+
+        let (chunk_tx, chunk_rx) = mpsc::channel(64);
+        let mut parec = ParecLoopback::new(source, self.config.sample_rate);
+        parec.start(Some(chunk_tx))?;
+        self.parec = Some(parec);
+
+        Ok(chunk_rx)
+    }
+
+    async fn stop(&mut self) -> Result<Vec<f32>, AudioError> {
+        let samples = match self.parec.take() {
+            Some(mut parec) => {
+                parec.stop();
+                parec.get_samples()
+            }
+            None => Vec::new(),
+        };
+
+        let duration_secs = samples.len() as f32 / self.config.sample_rate as f32;
+        tracing::debug!(
+            "Monitor capture stopped: {} samples ({:.2}s)",
+            samples.len(),
+            duration_secs
+        );
+
+        if samples.is_empty() {
+            return Err(AudioError::EmptyRecording);
+        }
+
+        Ok(samples)
+    }
+
+    async fn get_samples(&mut self) -> Vec<f32> {
+        self.parec
+            .as_ref()
+            .map(|p| p.get_samples())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACTL_OUTPUT: &str = "\
+49\talsa_output.pci-0000_00_1f.3.analog-stereo.monitor\tPipeWire\ts32le 2ch 48000Hz\tSUSPENDED
+50\talsa_input.pci-0000_00_1f.3.analog-stereo\tPipeWire\ts32le 2ch 48000Hz\tRUNNING
+51\tbluez_output.AA_BB_CC.1.monitor\tPipeWire\ts16le 2ch 48000Hz\tRUNNING
+";
+
+    #[test]
+    fn test_parse_monitor_sources_skips_inputs() {
+        let sources = parse_monitor_sources(PACTL_OUTPUT);
+        assert_eq!(sources.len(), 2);
+        assert_eq!(
+            sources[0].name,
+            "alsa_output.pci-0000_00_1f.3.analog-stereo.monitor"
+        );
+        assert!(!sources[0].running);
+        assert!(sources[1].running);
+    }
+
+    #[test]
+    fn test_pick_monitor_source_prefers_running() {
+        let sources = parse_monitor_sources(PACTL_OUTPUT);
+        assert_eq!(
+            pick_monitor_source(&sources).map(|s| s.name.as_str()),
+            Some("bluez_output.AA_BB_CC.1.monitor")
+        );
+    }
+
+    #[test]
+    fn test_pick_monitor_source_falls_back_to_first() {
+        let sources = parse_monitor_sources(
+            "49\talsa_output.analog-stereo.monitor\tPipeWire\ts32le 2ch 48000Hz\tIDLE\n",
+        );
+        assert_eq!(
+            pick_monitor_source(&sources).map(|s| s.name.as_str()),
+            Some("alsa_output.analog-stereo.monitor")
+        );
+        assert!(pick_monitor_source(&[]).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+//! Pre-roll buffer
+//!
+//! Keeps the last moment of microphone audio while idle, so a recording
+//! starts with the words said just before the hotkey press. Opt-in with
+//! `[audio] pre_roll_ms`: the microphone stays open, but its audio only ever
+//! lives in a small in-memory ring buffer that is overwritten continuously.
+//! It is never written to disk, and reaches transcription only as the start
+//! of a recording. The buffer is emptied when a recording takes it and again
+//! when the recording stops, so no audio is used twice.
+
+use super::{create_capture, AudioCapture};
+use crate::config::AudioConfig;
+use crate::error::AudioError;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Longest pre-roll kept, whatever the configuration says
+pub const MAX_PRE_ROLL_MS: u32 = 2000;
+
+/// The most recent samples, up to a fixed capacity
+pub struct RingBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl RingBuffer {
+    /// Keep at most `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Add samples, dropping the oldest beyond the capacity
+    pub fn push(&mut self, samples: &[f32]) {
+        let samples = &samples[samples.len().saturating_sub(self.capacity)..];
+        let excess = (self.samples.len() + samples.len()).saturating_sub(self.capacity);
+        self.samples.drain(..excess);
+        self.samples.extend(samples);
+    }
+
+    /// Take the buffered samples, leaving the buffer empty
+    pub fn take(&mut self) -> Vec<f32> {
+        self.samples.drain(..).collect()
+    }
+
+    /// Drop the buffered samples
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/// Microphone stream feeding the pre-roll buffer
+pub struct PreRoll {
+    buffer: Arc<Mutex<RingBuffer>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl PreRoll {
+    /// Open the audio input and start buffering `[audio] pre_roll_ms`
+    pub async fn start(config: &AudioConfig) -> Result<Self, AudioError> {
+        let millis = config.pre_roll_ms.min(MAX_PRE_ROLL_MS) as usize;
+        let capacity = millis * config.sample_rate as usize / 1000;
+        let buffer = Arc::new(Mutex::new(RingBuffer::new(capacity)));
+
+        let mut capture = create_capture(config)?;
+        let chunks = capture.start().await?;
+        let task = tokio::spawn(run(capture, chunks, buffer.clone()));
+
+        Ok(Self { buffer, task })
+    }
+
+    /// Put the buffered audio in front of a recording that just started
+    ///
+    /// Without `with_prefix` the buffered audio is dropped instead, but the
+    /// buffer is still emptied when the recording stops.
+    pub fn wrap(&self, capture: Box<dyn AudioCapture>, with_prefix: bool) -> Box<dyn AudioCapture> {
+        let mut prefix = self
+            .buffer
+            .lock()
+            .map(|mut buffer| buffer.take())
+            .unwrap_or_default();
+        if !with_prefix {
+            prefix.clear();
+        }
+        tracing::debug!(
+            "Pre-roll: {:.2}s before the recording",
+            prefix.len() as f32 / 16000.0
+        );
+        Box::new(PreRolled {
+            prefix,
+            inner: capture,
+            buffer: self.buffer.clone(),
+        })
+    }
+}
+
+impl Drop for PreRoll {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Keep the newest audio in the buffer until the stream ends
+async fn run(
+    mut capture: Box<dyn AudioCapture>,
+    mut chunks: mpsc::Receiver<Vec<f32>>,
+    buffer: Arc<Mutex<RingBuffer>>,
+) {
+    // The capture also keeps everything it recorded; drop that regularly
+    let mut drain = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            chunk = chunks.recv() => {
+                let Some(chunk) = chunk else { break };
+                if let Ok(mut buffer) = buffer.lock() {
+                    buffer.push(&chunk);
+                }
+            }
+            _ = drain.tick() => {
+                capture.get_samples().await;
+            }
+        }
+    }
+    let _ = capture.stop().await;
+}
+
+/// A recording with the pre-roll audio in front
+struct PreRolled {
+    /// Pre-roll audio, until handed out
+    prefix: Vec<f32>,
+    inner: Box<dyn AudioCapture>,
+    /// Emptied when the recording stops, so the next one doesn't repeat its end
+    buffer: Arc<Mutex<RingBuffer>>,
+}
+
+#[async_trait::async_trait]
+impl AudioCapture for PreRolled {
+    async fn start(&mut self) -> Result<mpsc::Receiver<Vec<f32>>, AudioError> {
+        self.inner.start().await
+    }
+
+    async fn stop(&mut self) -> Result<Vec<f32>, AudioError> {
+        let recorded = self.inner.stop().await;
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.clear();
+        }
+        let mut samples = std::mem::take(&mut self.prefix);
+        samples.extend(recorded?);
+        Ok(samples)
+    }
+
+    async fn get_samples(&mut self) -> Vec<f32> {
+        let mut samples = std::mem::take(&mut self.prefix);
+        samples.extend(self.inner.get_samples().await);
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut buffer = RingBuffer::new(4);
+        buffer.push(&[1.0, 2.0]);
+        buffer.push(&[3.0, 4.0, 5.0]);
+        assert_eq!(buffer.take(), vec![2.0, 3.0, 4.0, 5.0]);
+        assert!(buffer.take().is_empty());
+
+        // A chunk longer than the buffer keeps only its end
+        buffer.push(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(buffer.take(), vec![3.0, 4.0, 5.0, 6.0]);
+
+        buffer.push(&[1.0]);
+        buffer.clear();
+        assert!(buffer.take().is_empty());
+    }
+
+    #[test]
+    fn test_ring_buffer_zero_capacity() {
+        let mut buffer = RingBuffer::new(0);
+        buffer.push(&[1.0, 2.0]);
+        assert!(buffer.take().is_empty());
+    }
+}
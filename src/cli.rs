@@ -21,13 +21,16 @@ COMMANDS:
   voxtype status           Show daemon status (integrates with Waybar)
   voxtype setup            Check dependencies and download models
   voxtype config           Show current configuration
+  voxtype devices          List audio input devices and monitor sources
+  voxtype monitor          Test the microphone with a live level meter
 
 EXAMPLES:
   voxtype setup model      Interactive model selection (Whisper, Parakeet, or Moonshine)
   voxtype setup waybar     Show Waybar integration config
   voxtype setup gpu        Manage GPU acceleration (Vulkan/CUDA/ROCm)
   voxtype setup onnx       Switch between Whisper and ONNX engines
-  voxtype status --follow --format json   Waybar integration
+  voxtype model use small.en  Switch the running daemon to another model
+  voxtype waybar           Waybar integration (custom module exec)
 
 See 'voxtype <command> --help' for more info on a command.
 See 'man voxtype' or docs/INSTALL.md for setup instructions.
@@ -37,6 +40,11 @@ pub struct Cli {
     #[arg(short, long, value_name = "FILE")]
     pub config: Option<std::path::PathBuf>,
 
+    /// Named instance, with its own config (instances/NAME.toml), state and log
+    /// Lets several daemons run at once; pass it to record/status to target one
+    #[arg(long, global = true, value_name = "NAME")]
+    pub instance: Option<String>,
+
     /// Increase verbosity (-v = debug, -vv = trace)
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
@@ -45,6 +53,10 @@ pub struct Cli {
     #[arg(short, long)]
     pub quiet: bool,
 
+    /// Replace a running daemon: ask it to shut down, then take over
+    #[arg(long)]
+    pub replace: bool,
+
     /// Force clipboard mode (don't try to type)
     #[arg(long)]
     pub clipboard: bool,
@@ -63,12 +75,13 @@ pub struct Cli {
     pub restore_clipboard_delay_ms: Option<u32>,
 
     /// Override model for transcription.
-    /// Whisper: tiny, base, small, medium, large-v3, large-v3-turbo (and .en variants).
+    /// Whisper: tiny, base, small, medium, large-v3, large-v3-turbo (and .en variants),
+    /// distil-small.en, distil-large-v3 and more (see docs/CONFIGURATION.md).
     /// Parakeet: parakeet-tdt-0.6b-v3, parakeet-tdt-0.6b-v3-int8
     #[arg(long, value_name = "MODEL")]
     pub model: Option<String>,
 
-    /// Override transcription engine: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual
+    /// Override transcription engine: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual, onnx
     #[arg(long, value_name = "ENGINE")]
     pub engine: Option<String>,
 
@@ -88,10 +101,18 @@ pub struct Cli {
     #[arg(long, value_name = "KEY", help_heading = "Hotkey")]
     pub cancel_key: Option<String>,
 
+    /// Undo key for erasing the last transcription (e.g., F10, PAUSE)
+    #[arg(long, value_name = "KEY", help_heading = "Hotkey")]
+    pub undo_key: Option<String>,
+
     /// Modifier key for secondary model selection (e.g., LEFTSHIFT)
     #[arg(long, value_name = "KEY", help_heading = "Hotkey")]
     pub model_modifier: Option<String>,
 
+    /// Key that switches to the next model while idle (e.g., F9)
+    #[arg(long, value_name = "KEY", help_heading = "Hotkey")]
+    pub model_cycle_key: Option<String>,
+
     // -- Whisper --
 
     /// Disable context window optimization for short recordings
@@ -103,6 +124,18 @@ pub struct Cli {
     #[arg(long, value_name = "PROMPT", help_heading = "Whisper")]
     pub initial_prompt: Option<String>,
 
+    /// Minimum transcription confidence (0.0-1.0); below it nothing is typed
+    #[arg(long, value_name = "CONFIDENCE", help_heading = "Whisper")]
+    pub min_confidence: Option<f32>,
+
+    /// Drop segments whose no-speech probability (0.0-1.0) is above this
+    #[arg(long, value_name = "PROBABILITY", help_heading = "Whisper")]
+    pub no_speech_threshold: Option<f32>,
+
+    /// Keep non-speech tokens such as "(laughs)" or "[music]" in the output
+    #[arg(long, help_heading = "Whisper")]
+    pub keep_non_speech_tokens: bool,
+
     /// Language for transcription (e.g., en, fr, auto, or comma-separated: en,fr,de)
     #[arg(long, value_name = "LANG", help_heading = "Whisper")]
     pub language: Option<String>,
@@ -123,7 +156,7 @@ pub struct Cli {
     #[arg(long, help_heading = "Whisper")]
     pub on_demand_loading: bool,
 
-    /// Whisper execution mode: local, remote, or cli
+    /// Whisper execution mode: local, remote, worker, cli, or faster-whisper
     #[arg(long, value_name = "MODE", help_heading = "Whisper")]
     pub whisper_mode: Option<String>,
 
@@ -153,6 +186,11 @@ pub struct Cli {
     #[arg(long, value_name = "DEVICE", help_heading = "Audio")]
     pub audio_device: Option<String>,
 
+    /// What to record: microphone (default) or monitor (system audio).
+    /// Use monitor to transcribe what's playing, e.g. a meeting or video.
+    #[arg(long, value_name = "SOURCE", help_heading = "Audio")]
+    pub audio_source: Option<String>,
+
     /// Maximum recording duration in seconds (safety limit)
     #[arg(long, value_name = "SECS", help_heading = "Audio")]
     pub max_duration: Option<u32>,
@@ -181,7 +219,7 @@ pub struct Cli {
     pub append_text: Option<String>,
 
     /// Output driver order for type mode (comma-separated).
-    /// Available: wtype, dotool, ydotool, clipboard.
+    /// Available: virtual-keyboard, wtype, eitype, dotool, ydotool, clipboard, xclip, atspi, ibus, neovim, tmux, terminal, cgevent, sendinput.
     /// Example: --driver=ydotool,wtype,clipboard
     #[arg(long, value_name = "DRIVERS", help_heading = "Output")]
     pub driver: Option<String>,
@@ -218,6 +256,10 @@ pub struct Cli {
     #[arg(long, help_heading = "Output")]
     pub spoken_punctuation: bool,
 
+    /// Convert spoken numbers to digits (e.g., say "twenty three" to get "23")
+    #[arg(long, help_heading = "Output")]
+    pub spoken_numbers: bool,
+
     /// Keystroke for paste mode (e.g., ctrl+v, shift+insert, ctrl+shift+v)
     #[arg(long, value_name = "KEYS", help_heading = "Output")]
     pub paste_keys: Option<String>,
@@ -283,13 +325,26 @@ pub enum Commands {
         /// Path to audio file
         file: std::path::PathBuf,
 
-        /// Override transcription engine: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual
+        /// Override transcription engine: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual, onnx
         #[arg(long, value_name = "ENGINE")]
         engine: Option<String>,
+
+        /// Output format: text (default), timestamps ("[00:12] text" lines), srt, vtt,
+        /// or json (segments with word timings).
+        /// Timestamped formats print only the transcript to stdout, e.g.:
+        /// voxtype transcribe talk.wav --format srt > talk.srt
+        #[arg(long, value_name = "FORMAT", default_value = "text")]
+        format: String,
+
+        /// Prefix each line with the detected speaker (Speaker 1, Speaker 2, ...).
+        /// Requires a build with the ml-diarization feature and the speaker
+        /// embedding model (see [meeting.diarization] model_path)
+        #[arg(long)]
+        diarize: bool,
     },
 
     /// Internal: Worker process for GPU-isolated transcription
-    /// Reads audio from stdin, writes transcription result to stdout
+    /// Reads audio from, and writes the result to, a socket from the daemon
     #[command(hide = true)]
     TranscribeWorker {
         /// Model name or path (passed from parent process)
@@ -307,6 +362,58 @@ pub enum Commands {
         /// Number of threads for inference (passed from parent process)
         #[arg(long)]
         threads: Option<usize>,
+
+        /// No-speech probability threshold (passed from parent process)
+        #[arg(long)]
+        no_speech_threshold: Option<f32>,
+
+        /// Don't suppress non-speech tokens (passed from parent process)
+        #[arg(long)]
+        keep_non_speech_tokens: bool,
+
+        /// Run on the CPU rather than the GPU (passed from parent process)
+        #[arg(long)]
+        cpu: bool,
+
+        /// File descriptor of the socket to the parent process
+        #[arg(long)]
+        socket_fd: i32,
+    },
+
+    /// Transcribe for voxtype on other machines (their mode = "worker")
+    ///
+    /// Loads the configured Whisper model and transcribes the recordings
+    /// daemons send, one at a time. Clients need the worker_token from this
+    /// machine's config, if one is set. With --http, tools speaking the
+    /// OpenAI transcription API can use the model too, and with --wyoming,
+    /// Home Assistant.
+    Serve {
+        /// Address to listen on (port 7840 if not given)
+        #[arg(long, value_name = "ADDR", default_value = "0.0.0.0:7840")]
+        listen: String,
+
+        /// TLS certificate chain (PEM file), to serve over TLS
+        #[arg(long, value_name = "FILE", requires = "tls_key")]
+        tls_cert: Option<std::path::PathBuf>,
+
+        /// TLS private key (PEM file)
+        #[arg(long, value_name = "FILE", requires = "tls_cert")]
+        tls_key: Option<std::path::PathBuf>,
+
+        /// Also serve an HTTP transcription API, OpenAI-compatible, on this
+        /// address (e.g., 0.0.0.0:8080)
+        #[arg(long, value_name = "ADDR")]
+        http: Option<String>,
+
+        /// Also be a Wyoming speech-to-text service for Home Assistant, on
+        /// this address (default: 0.0.0.0:10300)
+        #[arg(
+            long,
+            value_name = "ADDR",
+            num_args = 0..=1,
+            default_missing_value = "0.0.0.0:10300"
+        )]
+        wyoming: Option<String>,
     },
 
     /// Setup and installation utilities
@@ -319,7 +426,8 @@ pub enum Commands {
         download: bool,
 
         /// Specify which model to download (use with --download).
-        /// Whisper: tiny, base, small, medium, large-v3, large-v3-turbo (and .en variants).
+        /// Whisper: tiny, base, small, medium, large-v3, large-v3-turbo (and .en variants),
+        /// distil-small.en, distil-large-v3 and more (see docs/CONFIGURATION.md).
         /// Parakeet: parakeet-tdt-0.6b-v3, parakeet-tdt-0.6b-v3-int8
         #[arg(long, value_name = "NAME")]
         model: Option<String>,
@@ -334,7 +442,24 @@ pub enum Commands {
     },
 
     /// Show current configuration
-    Config,
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+
+    /// List audio input devices and monitor (system audio) sources
+    Devices,
+
+    /// Test the microphone: show a live level meter and warn about clipping
+    ///
+    /// Records from the configured device (or --audio-device) for a few
+    /// seconds without transcribing, then tells whether the level is too
+    /// quiet, clipping or fine. Nothing is saved.
+    Monitor {
+        /// How long to listen, in seconds
+        #[arg(long, default_value_t = 10, value_name = "SECONDS")]
+        duration: u64,
+    },
 
     /// Show daemon status (for Waybar/polybar integration)
     Status {
@@ -346,6 +471,10 @@ pub enum Commands {
         #[arg(long, default_value = "text")]
         format: String,
 
+        /// Shorthand for --format json
+        #[arg(long)]
+        json: bool,
+
         /// Include extended info in JSON (model, device, backend)
         #[arg(long)]
         extended: bool,
@@ -353,6 +482,38 @@ pub enum Commands {
         /// Icon theme for JSON output (emoji, nerd-font, material, phosphor, codicons, omarchy, minimal, dots, arrows, text, or path to custom theme)
         #[arg(long, value_name = "THEME")]
         icon_theme: Option<String>,
+
+        /// Show uptime, model, output method, last dictation and remote usage of the
+        /// running daemon
+        #[arg(long, conflicts_with = "follow")]
+        detail: bool,
+
+        /// Show the timing breakdown of the last dictation
+        #[arg(long)]
+        report: bool,
+
+        /// Include the live input level while recording: "recording:0.63" as
+        /// text, "percentage" in JSON (with --follow, updated ten times a second)
+        #[arg(long)]
+        level: bool,
+    },
+
+    /// Print Waybar JSON on every state change (custom module "exec")
+    ///
+    /// Same as `status --follow --format json`, plus the recording time in
+    /// the tooltip, updated every second while recording.
+    Waybar {
+        /// Include extended info (model, device, backend)
+        #[arg(long)]
+        extended: bool,
+
+        /// Icon theme (same values as `status --icon-theme`)
+        #[arg(long, value_name = "THEME")]
+        icon_theme: Option<String>,
+
+        /// Include the live input level as "percentage" while recording
+        #[arg(long)]
+        level: bool,
     },
 
     /// Control recording from external sources (compositor keybindings, scripts)
@@ -369,6 +530,12 @@ pub enum Commands {
         #[command(subcommand)]
         action: MeetingAction,
     },
+
+    /// Manage downloaded models
+    Model {
+        #[command(subcommand)]
+        action: ModelAction,
+    },
 }
 
 /// Output mode override for record commands
@@ -486,6 +653,38 @@ pub enum RecordAction {
     },
     /// Cancel current recording or transcription (discard without output)
     Cancel,
+    /// Erase the last transcription that was typed (or clear the clipboard)
+    Undo,
+}
+
+/// Model management actions
+#[derive(Subcommand)]
+pub enum ModelAction {
+    /// Remove models not used for a number of days
+    ///
+    /// Models the config refers to are always kept.
+    Prune {
+        /// Remove models unused for at least this many days
+        #[arg(long, default_value = "30")]
+        days: u64,
+
+        /// List what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Switch the running daemon to another Whisper model
+    ///
+    /// The switch lasts until the daemon restarts. To change the configured
+    /// model, use 'voxtype setup model'.
+    Use {
+        /// Model name (e.g., small.en, large-v3-turbo) or path to a model file
+        model: String,
+    },
+
+    /// Switch the running daemon to the next model: [whisper] model, then
+    /// each of available_models
+    Next,
 }
 
 /// Meeting mode actions
@@ -606,7 +805,7 @@ impl RecordAction {
                 file,
                 ..
             } => (*type_mode, *clipboard, *paste, file.as_ref()),
-            RecordAction::Cancel => return None,
+            RecordAction::Cancel | RecordAction::Undo => return None,
         };
 
         if type_mode {
@@ -629,7 +828,7 @@ impl RecordAction {
     pub fn file_path(&self) -> Option<&str> {
         match self {
             RecordAction::Start { file, .. } | RecordAction::Toggle { file, .. } => file.as_deref(),
-            RecordAction::Stop { .. } | RecordAction::Cancel => None,
+            RecordAction::Stop { .. } | RecordAction::Cancel | RecordAction::Undo => None,
         }
     }
 
@@ -639,7 +838,7 @@ impl RecordAction {
         match self {
             RecordAction::Start { model, .. } => model.as_deref(),
             RecordAction::Toggle { model, .. } => model.as_deref(),
-            RecordAction::Stop { .. } | RecordAction::Cancel => None,
+            RecordAction::Stop { .. } | RecordAction::Cancel | RecordAction::Undo => None,
         }
     }
 
@@ -649,7 +848,7 @@ impl RecordAction {
         match self {
             RecordAction::Start { profile, .. } => profile.as_deref(),
             RecordAction::Toggle { profile, .. } => profile.as_deref(),
-            RecordAction::Stop { .. } | RecordAction::Cancel => None,
+            RecordAction::Stop { .. } | RecordAction::Cancel | RecordAction::Undo => None,
         }
     }
 
@@ -667,7 +866,7 @@ impl RecordAction {
                 no_auto_submit,
                 ..
             } => (*auto_submit, *no_auto_submit),
-            RecordAction::Stop { .. } | RecordAction::Cancel => return None,
+            RecordAction::Stop { .. } | RecordAction::Cancel | RecordAction::Undo => return None,
         };
 
         if auto_submit {
@@ -693,7 +892,7 @@ impl RecordAction {
                 no_shift_enter_newlines,
                 ..
             } => (*shift_enter_newlines, *no_shift_enter_newlines),
-            RecordAction::Stop { .. } | RecordAction::Cancel => return None,
+            RecordAction::Stop { .. } | RecordAction::Cancel | RecordAction::Undo => return None,
         };
 
         if shift_enter {
@@ -706,6 +905,21 @@ impl RecordAction {
     }
 }
 
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Validate the config file: syntax, unknown keys, hotkey names, model
+    /// files and conflicting options (exits non-zero on errors)
+    Check,
+
+    /// Upgrade an older config file to the current schema (renamed options,
+    /// version); shows the changes unless --write is given
+    Migrate {
+        /// Rewrite the file, keeping a backup as config.toml.bak
+        #[arg(long)]
+        write: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum SetupAction {
     /// Check system configuration and dependencies
@@ -826,6 +1040,16 @@ pub enum SetupAction {
         #[arg(long)]
         status: bool,
     },
+
+    /// Download the openWakeWord models for [wake_word]
+    ///
+    /// Downloads the shared feature models and the configured wake phrase
+    /// model (`[wake_word] model`, default "hey_jarvis").
+    WakeWord {
+        /// Show wake word model status
+        #[arg(long)]
+        status: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -909,6 +1133,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_config_check() {
+        let cli = Cli::parse_from(["voxtype", "config", "check"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config {
+                action: Some(ConfigAction::Check)
+            })
+        ));
+
+        let cli = Cli::parse_from(["voxtype", "config"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config { action: None })
+        ));
+
+        let cli = Cli::parse_from(["voxtype", "config", "migrate", "--write"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Config {
+                action: Some(ConfigAction::Migrate { write: true })
+            })
+        ));
+    }
+
     #[test]
     fn test_setup_without_flags() {
         let cli = Cli::parse_from(["voxtype", "setup"]);
@@ -1084,6 +1333,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_waybar() {
+        let cli = Cli::parse_from(["voxtype", "waybar", "--icon-theme", "nerd-font"]);
+        match cli.command {
+            Some(Commands::Waybar {
+                extended,
+                icon_theme,
+                level,
+            }) => {
+                assert!(!extended);
+                assert!(!level);
+                assert_eq!(icon_theme.as_deref(), Some("nerd-font"));
+            }
+            _ => panic!("Expected Waybar command"),
+        }
+    }
+
     #[test]
     fn test_record_cancel() {
         let cli = Cli::parse_from(["voxtype", "record", "cancel"]);
@@ -1097,6 +1363,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_record_undo() {
+        let cli = Cli::parse_from(["voxtype", "record", "undo"]);
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Record {
+                action: RecordAction::Undo,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_model_use() {
+        let cli = Cli::parse_from(["voxtype", "model", "use", "small.en"]);
+        match cli.command {
+            Some(Commands::Model {
+                action: ModelAction::Use { model },
+            }) => assert_eq!(model, "small.en"),
+            _ => panic!("Expected Model Use command"),
+        }
+        assert!(matches!(
+            Cli::parse_from(["voxtype", "model", "next"]).command,
+            Some(Commands::Model {
+                action: ModelAction::Next,
+            })
+        ));
+    }
+
     #[test]
     fn test_record_start_no_override() {
         let cli = Cli::parse_from(["voxtype", "record", "start"]);
@@ -1597,7 +1891,7 @@ mod tests {
     fn test_transcribe_engine_flag() {
         let cli = Cli::parse_from(["voxtype", "transcribe", "test.wav", "--engine", "moonshine"]);
         match cli.command {
-            Some(Commands::Transcribe { file, engine }) => {
+            Some(Commands::Transcribe { file, engine, .. }) => {
                 assert_eq!(file, std::path::PathBuf::from("test.wav"));
                 assert_eq!(engine, Some("moonshine".to_string()));
             }
@@ -1616,6 +1910,15 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transcribe_diarize_flag() {
+        let cli = Cli::parse_from(["voxtype", "transcribe", "call.wav", "--diarize"]);
+        match cli.command {
+            Some(Commands::Transcribe { diarize, .. }) => assert!(diarize),
+            _ => panic!("Expected Transcribe command"),
+        }
+    }
+
     #[test]
     fn test_transcribe_engine_whisper() {
         let cli = Cli::parse_from(["voxtype", "transcribe", "test.wav", "--engine", "whisper"]);
@@ -1627,6 +1930,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_serve_command() {
+        let cli = Cli::parse_from(["voxtype", "serve"]);
+        match cli.command {
+            Some(Commands::Serve {
+                listen,
+                tls_cert,
+                http,
+                ..
+            }) => {
+                assert_eq!(listen, "0.0.0.0:7840");
+                assert_eq!(tls_cert, None);
+                assert_eq!(http, None);
+            }
+            _ => panic!("Expected Serve command"),
+        }
+
+        // A certificate needs its key
+        assert!(Cli::try_parse_from(["voxtype", "serve", "--tls-cert", "cert.pem"]).is_err());
+        let cli = Cli::parse_from([
+            "voxtype",
+            "serve",
+            "--listen",
+            "192.168.1.10",
+            "--tls-cert",
+            "cert.pem",
+            "--tls-key",
+            "key.pem",
+            "--http",
+            "0.0.0.0:8080",
+            "--wyoming",
+        ]);
+        match cli.command {
+            Some(Commands::Serve {
+                listen,
+                tls_key,
+                http,
+                wyoming,
+                ..
+            }) => {
+                assert_eq!(listen, "192.168.1.10");
+                assert_eq!(tls_key, Some(std::path::PathBuf::from("key.pem")));
+                assert_eq!(http.as_deref(), Some("0.0.0.0:8080"));
+                assert_eq!(wyoming.as_deref(), Some("0.0.0.0:10300"));
+            }
+            _ => panic!("Expected Serve command"),
+        }
+    }
+
     #[test]
     fn test_record_start_auto_submit() {
         let cli = Cli::parse_from(["voxtype", "record", "start", "--auto-submit"]);
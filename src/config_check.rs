@@ -0,0 +1,666 @@
+//! Config validation
+//!
+//! `voxtype config check` and daemon startup run the same checks: TOML syntax
+//! and value types (located by the TOML parser), unknown keys, hotkey names,
+//! missing model files, options that contradict each other and renamed
+//! options from older config versions. Errors stop the daemon before it
+//! starts; warnings are only reported.
+
+use crate::config::{self, Config, OutputMode, OutputSink, TranscriptionEngine, WhisperMode};
+use crate::config_migrate::{self, CURRENT_VERSION};
+use crate::error::{HotkeyError, TranscribeError};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Keys accepted under another name (alias, canonical name)
+const KEY_ALIASES: &[(&str, &str)] = &[("delay_ms", "type_delay_ms")];
+
+/// How serious a problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The daemon refuses to start
+    Error,
+    /// Reported, but the daemon starts
+    Warning,
+}
+
+/// A problem found in the config
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    /// Line and column in the config file (1-based)
+    pub location: Option<(usize, usize)>,
+    pub message: String,
+}
+
+impl Issue {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            location: None,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            location: None,
+            message: message.into(),
+        }
+    }
+
+    fn at(mut self, location: Option<(usize, usize)>) -> Self {
+        self.location = location;
+        self
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match self.location {
+            Some((line, column)) => {
+                write!(f, "{}:{}: {}: {}", line, column, severity, self.message)
+            }
+            None => write!(f, "{}: {}", severity, self.message),
+        }
+    }
+}
+
+/// Outcome of checking a config file
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// The file that was checked (None when running on defaults)
+    pub path: Option<PathBuf>,
+    pub issues: Vec<Issue>,
+}
+
+impl CheckReport {
+    /// Whether the daemon would refuse to start
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    /// Issues prefixed with the file name, then a count line
+    pub fn summary(&self) -> String {
+        let name = self
+            .path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "config".to_string());
+        let mut summary = String::new();
+        for issue in &self.issues {
+            let separator = if issue.location.is_some() { ":" } else { ": " };
+            summary.push_str(&format!("{}{}{}\n", name, separator, issue));
+        }
+        let errors = self
+            .issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .count();
+        let warnings = self.issues.len() - errors;
+        summary.push_str(&format!(
+            "{} error{}, {} warning{}",
+            errors,
+            if errors == 1 { "" } else { "s" },
+            warnings,
+            if warnings == 1 { "" } else { "s" }
+        ));
+        summary
+    }
+}
+
+/// Check the config file at `path` (or the default location): syntax first,
+/// then everything `check` looks at in the config it loads to
+pub fn check_file(path: Option<&Path>) -> CheckReport {
+    let path = path.map(PathBuf::from).or_else(Config::default_path);
+    let mut report = CheckReport {
+        path: path.clone().filter(|p| p.exists()),
+        issues: Vec::new(),
+    };
+
+    let contents = match report.path {
+        Some(ref path) => match std::fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(e) => {
+                report
+                    .issues
+                    .push(Issue::error(format!("Failed to read config: {}", e)));
+                return report;
+            }
+        },
+        None => None,
+    };
+
+    if let Some(ref contents) = contents {
+        if let Err(e) = toml::from_str::<Config>(contents) {
+            let location = e.span().map(|span| line_col(contents, span.start));
+            report
+                .issues
+                .push(Issue::error(e.message().trim().to_string()).at(location));
+            return report;
+        }
+    }
+
+    match config::load_config(path.as_deref()) {
+        Ok(config) => report.issues = check(&config, contents.as_deref()),
+        Err(e) => report.issues.push(Issue::error(e.to_string())),
+    }
+    report
+}
+
+/// Check a loaded config; `contents` is the file it was read from, used to
+/// find unknown keys and to locate problems
+pub fn check(config: &Config, contents: Option<&str>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if let Some(contents) = contents {
+        issues.extend(unknown_keys(contents));
+        issues.extend(migrations(contents));
+    }
+    let locate = |path: &[&str]| contents.and_then(|c| locate_key(c, path));
+
+    if config.version > CURRENT_VERSION {
+        issues.push(
+            Issue::warning(format!(
+                "config version {} is newer than this voxtype supports ({}); \
+                 newer options are ignored",
+                config.version, CURRENT_VERSION
+            ))
+            .at(locate(&["version"])),
+        );
+    }
+
+    if config.hotkey.enabled {
+        if let Err(e) = crate::hotkey::validate_config(&config.hotkey) {
+            let field = hotkey_field(config, &e);
+            issues.push(
+                Issue::error(format!("hotkey.{}: {}", field, e)).at(locate(&["hotkey", field])),
+            );
+        }
+    } else if config.hotkey.exclusive {
+        issues.push(
+            Issue::warning("hotkey.exclusive has no effect while hotkey.enabled = false")
+                .at(locate(&["hotkey", "exclusive"])),
+        );
+    }
+
+    if let Some(issue) = check_model(config) {
+        let section = engine_section(config.engine);
+        issues.push(issue.at(locate(&[section, "model"]).or_else(|| locate(&["engine"]))));
+    }
+
+    let mut profiles: Vec<_> = config.profiles.iter().collect();
+    profiles.sort_by_key(|(name, _)| name.as_str());
+    for (name, profile) in profiles {
+        let Some(engine) = profile.engine else {
+            continue;
+        };
+        if config
+            .engine_model_name(engine)
+            .ends_with("(not configured)")
+        {
+            let section = engine_section(engine);
+            issues.push(
+                Issue::error(format!(
+                    "profiles.{}.engine = \"{}\" needs a [{}] section with a model",
+                    name, section, section
+                ))
+                .at(locate(&["profiles", name, "engine"])),
+            );
+        }
+    }
+
+    if let Some(Err(e)) = config
+        .text
+        .locale
+        .as_deref()
+        .map(str::parse::<crate::text::numbers::Locale>)
+    {
+        issues.push(Issue::warning(format!("text.locale: {}", e)).at(locate(&["text", "locale"])));
+    }
+
+    if let Err(e) = crate::script::Hooks::load(config) {
+        issues.push(Issue::error(e).at(locate(&["scripting", "script"])));
+    }
+
+    if config.output.file_path.is_none() {
+        if config.output.mode == OutputMode::File {
+            issues.push(
+                Issue::warning(
+                    "output.mode = \"file\" needs output.file_path; text will be typed instead",
+                )
+                .at(locate(&["output", "mode"])),
+            );
+        }
+        if config.output.also.contains(&OutputSink::File) {
+            issues.push(
+                Issue::warning("output.also includes \"file\" but output.file_path is not set")
+                    .at(locate(&["output", "also"])),
+            );
+        }
+    }
+    issues
+}
+
+/// Keys in the file that no config field accepted
+///
+/// The file is parsed into a `Config` and serialized back; keys that didn't
+/// survive the round trip were ignored by serde.
+fn unknown_keys(contents: &str) -> Vec<Issue> {
+    let (Ok(raw), Ok(config)) = (
+        toml::from_str::<toml::Table>(contents),
+        toml::from_str::<Config>(contents),
+    ) else {
+        return Vec::new();
+    };
+    let Ok(serde_json::Value::Object(known)) = serde_json::to_value(&config) else {
+        return Vec::new();
+    };
+
+    let mut unknown = Vec::new();
+    collect_unknown(&raw, &known, &mut Vec::new(), &mut unknown);
+    let mut issues: Vec<Issue> = unknown
+        .into_iter()
+        .map(|path| {
+            let keys: Vec<&str> = path.iter().map(String::as_str).collect();
+            Issue::warning(format!("unknown key `{}` (ignored)", path.join(".")))
+                .at(locate_key(contents, &keys))
+        })
+        .collect();
+    issues.sort_by_key(|issue| issue.location);
+    issues
+}
+
+/// Options from an older config version that `load_config` upgraded
+fn migrations(contents: &str) -> Vec<Issue> {
+    let Ok(mut raw) = toml::from_str::<toml::Table>(contents) else {
+        return Vec::new();
+    };
+    config_migrate::migrate(&mut raw)
+        .into_iter()
+        .map(|migration| {
+            Issue::warning(format!(
+                "{} (update the file with: voxtype config migrate --write)",
+                migration
+            ))
+            .at(locate_key(contents, &[migration.section, migration.from]))
+        })
+        .collect()
+}
+
+fn collect_unknown(
+    raw: &toml::Table,
+    known: &serde_json::Map<String, serde_json::Value>,
+    path: &mut Vec<String>,
+    unknown: &mut Vec<Vec<String>>,
+) {
+    for (key, value) in raw {
+        let known_value = known.get(key).or_else(|| {
+            KEY_ALIASES
+                .iter()
+                .find(|(alias, _)| alias == key)
+                .and_then(|(_, canonical)| known.get(*canonical))
+        });
+        path.push(key.clone());
+        match (value, known_value) {
+            (_, None) => unknown.push(path.clone()),
+            (toml::Value::Table(table), Some(serde_json::Value::Object(fields))) => {
+                collect_unknown(table, fields, path, unknown)
+            }
+            (toml::Value::Array(items), Some(serde_json::Value::Array(known_items))) => {
+                for (item, known_item) in items.iter().zip(known_items) {
+                    if let (toml::Value::Table(table), serde_json::Value::Object(fields)) =
+                        (item, known_item)
+                    {
+                        collect_unknown(table, fields, path, unknown);
+                    }
+                }
+            }
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
+/// Which hotkey field a hotkey error is about
+fn hotkey_field(config: &Config, error: &HotkeyError) -> &'static str {
+    let hotkey = &config.hotkey;
+    match error {
+        HotkeyError::KeyConflict(message) if message.starts_with("cancel_key") => "cancel_key",
+        HotkeyError::KeyConflict(message) if message.starts_with("undo_key") => "undo_key",
+        HotkeyError::UnknownKey(message) => {
+            let name = message.split('.').next().unwrap_or_default().trim();
+            let matches = |value: &Option<String>| {
+                value
+                    .as_deref()
+                    .is_some_and(|v| v.eq_ignore_ascii_case(name))
+            };
+            if matches(&hotkey.cancel_key) {
+                "cancel_key"
+            } else if matches(&hotkey.undo_key) {
+                "undo_key"
+            } else if matches(&hotkey.model_modifier) {
+                "model_modifier"
+            } else if matches(&hotkey.spell_modifier) {
+                "spell_modifier"
+            } else if hotkey
+                .modifiers
+                .iter()
+                .any(|m| m.eq_ignore_ascii_case(name))
+            {
+                "modifiers"
+            } else {
+                "key"
+            }
+        }
+        _ => "key",
+    }
+}
+
+/// Config section of an engine
+fn engine_section(engine: TranscriptionEngine) -> &'static str {
+    match engine {
+        TranscriptionEngine::Whisper => "whisper",
+        TranscriptionEngine::Parakeet => "parakeet",
+        TranscriptionEngine::Moonshine => "moonshine",
+        TranscriptionEngine::SenseVoice => "sensevoice",
+        TranscriptionEngine::Paraformer => "paraformer",
+        TranscriptionEngine::Dolphin => "dolphin",
+        TranscriptionEngine::Omnilingual => "omnilingual",
+        TranscriptionEngine::Onnx => "onnx",
+    }
+}
+
+/// Check that the active engine is configured and its model is on disk
+///
+/// Whisper model names map to exactly one file, so a missing one is an
+/// error. The ONNX engines also accept several directory layouts, so for
+/// them only a warning is given.
+fn check_model(config: &Config) -> Option<Issue> {
+    let section = engine_section(config.engine);
+    let model = config.model_name();
+    let (candidates, severity) = match config.engine {
+        TranscriptionEngine::Whisper => {
+            // faster-whisper downloads models of its own
+            if matches!(
+                config.whisper.effective_mode(),
+                WhisperMode::Remote | WhisperMode::Worker | WhisperMode::FasterWhisper
+            ) {
+                return None;
+            }
+            if crate::transcribe::models::is_path(model) {
+                let path = config::expand_home(model);
+                return crate::transcribe::models::check_model_file(&path)
+                    .err()
+                    .map(|e| match e {
+                        TranscribeError::ModelNotFound(reason)
+                        | TranscribeError::InitFailed(reason) => reason,
+                        other => other.to_string(),
+                    })
+                    .map(|reason| Issue::error(format!("whisper.model: {}", reason)));
+            }
+            (
+                vec![crate::transcribe::models::model_filename(model)],
+                Severity::Error,
+            )
+        }
+        _ => {
+            if model.ends_with("(not configured)") {
+                return Some(Issue::error(format!(
+                    "engine = \"{}\" needs a [{}] section with a model",
+                    section, section
+                )));
+            }
+            let dir_name = if model.starts_with(&format!("{}-", section)) {
+                model.to_string()
+            } else {
+                format!("{}-{}", section, model)
+            };
+            (
+                vec![
+                    model.to_string(),
+                    format!("sherpa-onnx-{}", dir_name),
+                    dir_name,
+                ],
+                Severity::Warning,
+            )
+        }
+    };
+
+    if candidates.iter().any(|name| model_exists(name)) {
+        return None;
+    }
+    Some(Issue {
+        severity,
+        location: None,
+        message: format!(
+            "{}.model: model '{}' not found in {} (download it with: voxtype setup model)",
+            section,
+            model,
+            Config::models_dir().display()
+        ),
+    })
+}
+
+/// Whether a model file or directory exists where the engines look for it
+fn model_exists(name: &str) -> bool {
+    let path = Path::new(name);
+    if path.is_absolute() {
+        return path.exists();
+    }
+    Config::models_dir().join(name).exists()
+        || path.exists()
+        || Path::new("models").join(name).exists()
+}
+
+/// 1-based line and column of a byte offset
+fn line_col(contents: &str, offset: usize) -> (usize, usize) {
+    let before = &contents[..offset.min(contents.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
+
+/// Find where a key is set: its `key = ...` line or its `[table]` header
+///
+/// Falls back to the closest enclosing key or table that is present.
+fn locate_key(contents: &str, path: &[&str]) -> Option<(usize, usize)> {
+    let mut table: Vec<String> = Vec::new();
+    let mut best: Option<(usize, (usize, usize))> = None;
+
+    for (index, line) in contents.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let column = line.len() - trimmed.len() + 1;
+        let location = (index + 1, column);
+
+        let full = if let Some(header) = trimmed.strip_prefix('[') {
+            let header = header.trim_start_matches('[');
+            let Some(end) = header.find(']') else {
+                continue;
+            };
+            table = split_key(&header[..end]);
+            table.clone()
+        } else if let Some((key, _)) = trimmed.split_once('=') {
+            if trimmed.starts_with('#') {
+                continue;
+            }
+            let mut full = table.clone();
+            full.extend(split_key(key));
+            full
+        } else {
+            continue;
+        };
+
+        let common = full.iter().zip(path).take_while(|(a, b)| a == *b).count();
+        if common == path.len() {
+            return Some(location);
+        }
+        if common == full.len() && best.is_none_or(|(len, _)| common > len) {
+            best = Some((common, location));
+        }
+    }
+    best.map(|(_, location)| location)
+}
+
+/// Split a dotted TOML key into its parts, removing quotes
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|part| part.trim().trim_matches('"').trim_matches('\'').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[hotkey]
+key = "SCROLLLOCK"
+cancel_key = "ESC"
+
+[output]
+mode = "type"
+typo_option = true
+
+[output.notification]
+on_transcription = true
+on_transcripton = false
+
+[[output.driver_order]]
+backend = "ydotool"
+delay_ms = 5
+
+[audio]
+device = "default"
+sample_rate = 16000
+max_duration_secs = 60
+"#;
+
+    #[test]
+    fn test_line_col() {
+        assert_eq!(line_col("a = 1\nb = 2\n", 0), (1, 1));
+        assert_eq!(line_col("a = 1\nb = 2\n", 10), (2, 5));
+    }
+
+    #[test]
+    fn test_locate_key() {
+        assert_eq!(locate_key(CONFIG, &["hotkey", "cancel_key"]), Some((4, 1)));
+        assert_eq!(
+            locate_key(CONFIG, &["output", "notification", "on_transcription"]),
+            Some((11, 1))
+        );
+        // Not set: the enclosing table
+        assert_eq!(locate_key(CONFIG, &["hotkey", "undo_key"]), Some((2, 1)));
+        assert_eq!(locate_key(CONFIG, &["meeting", "enabled"]), None);
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let issues = unknown_keys(CONFIG);
+        let messages: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "8:1: warning: unknown key `output.typo_option` (ignored)",
+                "12:1: warning: unknown key `output.notification.on_transcripton` (ignored)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrations() {
+        let contents = CONFIG.replace("mode = \"type\"", "wtype_delay_ms = 50");
+        let messages: Vec<String> = migrations(&contents)
+            .iter()
+            .map(|i| i.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "7:1: warning: output.wtype_delay_ms was renamed to output.pre_type_delay_ms \
+                 (update the file with: voxtype config migrate --write)"
+            ]
+        );
+        assert!(migrations(&format!("version = 2\n{}", contents)).is_empty());
+    }
+
+    #[test]
+    fn test_check_reports_conflicts() {
+        let mut config: Config = toml::from_str(CONFIG).unwrap();
+        config.hotkey.undo_key = Some("esc".to_string());
+        config.output.mode = OutputMode::File;
+
+        let issues = check(&config, None);
+        assert!(issues
+            .iter()
+            .any(|i| i.severity == Severity::Error && i.message.starts_with("hotkey.cancel_key:")));
+        assert!(issues
+            .iter()
+            .any(|i| i.severity == Severity::Warning && i.message.contains("file_path")));
+    }
+
+    #[test]
+    fn test_check_model_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("legal-finetune.bin");
+        let mut config = Config::default();
+        config.whisper.model = path.to_str().unwrap().to_string();
+        let issue = check_model(&config).unwrap();
+        assert_eq!(issue.severity, Severity::Error);
+        assert!(issue.message.contains("does not exist"));
+
+        std::fs::write(&path, b"lmgg\0\0\0\0").unwrap();
+        assert!(check_model(&config).is_none());
+    }
+
+    #[test]
+    fn test_check_profile_engine() {
+        let mut config = Config::default();
+        config.profiles.insert(
+            "npu".to_string(),
+            crate::config::Profile {
+                engine: Some(TranscriptionEngine::Onnx),
+                ..Default::default()
+            },
+        );
+        let issues = check(&config, None);
+        assert!(
+            issues
+                .iter()
+                .any(|i| i.severity == Severity::Error
+                    && i.message.starts_with("profiles.npu.engine"))
+        );
+
+        config.onnx = Some(Default::default());
+        let issues = check(&config, None);
+        assert!(!issues.iter().any(|i| i.message.starts_with("profiles.")));
+    }
+
+    #[test]
+    fn test_report_summary() {
+        let report = CheckReport {
+            path: Some(PathBuf::from("config.toml")),
+            issues: vec![
+                Issue::error("hotkey.key: bad").at(Some((3, 1))),
+                Issue::warning("unknown key `x` (ignored)"),
+            ],
+        };
+        assert!(report.has_errors());
+        assert_eq!(
+            report.summary(),
+            "config.toml:3:1: error: hotkey.key: bad\n\
+             config.toml: warning: unknown key `x` (ignored)\n\
+             1 error, 1 warning"
+        );
+    }
+}
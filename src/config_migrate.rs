@@ -0,0 +1,335 @@
+//! Config versioning and migration
+//!
+//! The config file carries a top-level `version`; files written before it
+//! existed are version 1. Options that were renamed are listed in `RENAMES`.
+//! `load_config` upgrades the parsed file in memory, so old configs keep
+//! working, and `voxtype config check` and daemon startup warn about each
+//! upgraded key. `voxtype config migrate --write` applies the same changes
+//! to the file itself, editing only the affected lines so comments survive.
+
+use std::fmt;
+
+/// Config schema version written by this release
+pub const CURRENT_VERSION: u32 = 2;
+
+/// An option that was renamed
+struct Rename {
+    /// First version that uses the new name
+    version: u32,
+    section: &'static str,
+    from: &'static str,
+    to: &'static str,
+    /// Value of the new key that means it isn't set, e.g. the
+    /// `pre_type_delay_ms = 0` of the default config
+    unset: Option<i64>,
+}
+
+const RENAMES: &[Rename] = &[
+    Rename {
+        version: 2,
+        section: "whisper",
+        from: "backend",
+        to: "mode",
+        unset: None,
+    },
+    Rename {
+        version: 2,
+        section: "output",
+        from: "wtype_delay_ms",
+        to: "pre_type_delay_ms",
+        unset: Some(0),
+    },
+];
+
+/// One change made while upgrading a config
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    /// Table of the renamed key
+    pub section: &'static str,
+    /// Old name
+    pub from: &'static str,
+    /// New name
+    pub to: &'static str,
+    /// The new key was already set, so the old one was dropped
+    pub dropped: bool,
+    /// The new key held its unset value, which the old one replaced
+    pub replaced: bool,
+}
+
+impl fmt::Display for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dropped {
+            write!(
+                f,
+                "{0}.{1} is obsolete and ignored because {0}.{2} is set; remove it",
+                self.section, self.from, self.to
+            )
+        } else {
+            write!(
+                f,
+                "{0}.{1} was renamed to {0}.{2}",
+                self.section, self.from, self.to
+            )
+        }
+    }
+}
+
+/// Schema version of a parsed config file
+pub fn file_version(table: &toml::Table) -> u32 {
+    table
+        .get("version")
+        .and_then(toml::Value::as_integer)
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(1)
+}
+
+/// Upgrade a parsed config file to `CURRENT_VERSION`
+///
+/// When both the old and the new key are set, the old one is dropped,
+/// since the new one takes precedence. A new key that only holds its unset
+/// value (`pre_type_delay_ms = 0`) doesn't count: the old value replaces it,
+/// as `wtype_delay_ms` was used whenever `pre_type_delay_ms` was 0.
+pub fn migrate(table: &mut toml::Table) -> Vec<Migration> {
+    let version = file_version(table);
+    if version >= CURRENT_VERSION {
+        return Vec::new();
+    }
+
+    let mut migrations = Vec::new();
+    for rename in RENAMES.iter().filter(|r| r.version > version) {
+        let Some(section) = table
+            .get_mut(rename.section)
+            .and_then(toml::Value::as_table_mut)
+        else {
+            continue;
+        };
+        let Some(value) = section.remove(rename.from) else {
+            continue;
+        };
+        let replaced = match section.get(rename.to) {
+            Some(existing) => rename.unset.is_some() && existing.as_integer() == rename.unset,
+            None => false,
+        };
+        let dropped = section.contains_key(rename.to) && !replaced;
+        if !dropped {
+            section.insert(rename.to.to_string(), value);
+        }
+        migrations.push(Migration {
+            section: rename.section,
+            from: rename.from,
+            to: rename.to,
+            dropped,
+            replaced,
+        });
+    }
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(CURRENT_VERSION.into()),
+    );
+    migrations
+}
+
+/// Rewrite a config file in the current schema
+///
+/// Renamed keys are renamed in place (or commented out when the new key is
+/// already set, and the new key is commented out when it was unset) and `version` is added or updated; everything else,
+/// comments included, is kept as written. Returns the new contents and the
+/// changes made, or an error when the file can't be upgraded line by line
+/// (e.g. an old key set with dotted syntax), so it has to be edited by hand.
+pub fn migrate_text(contents: &str) -> Result<(String, Vec<Migration>), String> {
+    let mut table: toml::Table = toml::from_str(contents).map_err(|e| e.to_string())?;
+    let version = file_version(&table);
+    if version >= CURRENT_VERSION {
+        return Ok((contents.to_string(), Vec::new()));
+    }
+    let has_version = table.contains_key("version");
+    let migrations = migrate(&mut table);
+
+    let mut section = String::new();
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if let Some(header) = trimmed.strip_prefix('[') {
+            section = header
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+        } else if let Some((key, rest)) = trimmed.split_once('=') {
+            let key = key.trim();
+            if section.is_empty() && key == "version" && !trimmed.starts_with('#') {
+                lines.push(format!("{}version = {}", indent, CURRENT_VERSION));
+                continue;
+            }
+            let migration = migrations
+                .iter()
+                .find(|m| m.section == section && m.from == key);
+            let replaced = migrations
+                .iter()
+                .find(|m| m.replaced && m.section == section && m.to == key);
+            if let Some(migration) = replaced {
+                lines.push(format!(
+                    "{}# {} (unset, replaced by {})",
+                    indent, trimmed, migration.from
+                ));
+                continue;
+            }
+            if let Some(migration) = migration {
+                if migration.dropped {
+                    lines.push(format!(
+                        "{}# {} (obsolete, replaced by {})",
+                        indent, trimmed, migration.to
+                    ));
+                } else {
+                    lines.push(format!("{}{} ={}", indent, migration.to, rest));
+                }
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+
+    if !has_version {
+        // After the comment block that opens the file
+        let at = lines
+            .iter()
+            .position(|line| !line.trim_start().starts_with('#'))
+            .unwrap_or(lines.len());
+        let mut block = vec![
+            "# Config schema version, updated by `voxtype config migrate --write`".to_string(),
+            format!("version = {}", CURRENT_VERSION),
+        ];
+        if at == lines.len() {
+            if at > 0 {
+                block.insert(0, String::new());
+            }
+            lines.extend(block);
+        } else if lines[at].trim().is_empty() {
+            block.push(String::new());
+            lines.splice(at + 1..at + 1, block);
+        } else {
+            if at > 0 {
+                block.insert(0, String::new());
+            }
+            block.push(String::new());
+            lines.splice(at..at, block);
+        }
+    }
+
+    let mut migrated = lines.join("\n");
+    if contents.ends_with('\n') || contents.is_empty() {
+        migrated.push('\n');
+    }
+
+    // The line edits must produce the same result as the in-memory upgrade
+    let rewritten: toml::Table = toml::from_str(&migrated).map_err(|e| e.to_string())?;
+    if rewritten != table {
+        return Err(format!(
+            "could not rewrite {} automatically; please edit the file by hand",
+            migrations
+                .iter()
+                .map(|m| format!("{}.{}", m.section, m.from))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok((migrated, migrations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD: &str = r#"# My voxtype config
+
+[whisper]
+# Transcribe on a server
+backend = "remote"
+model = "base.en"
+
+[output]
+mode = "type"
+wtype_delay_ms = 50
+pre_type_delay_ms = 20
+"#;
+
+    #[test]
+    fn test_migrate_table() {
+        let mut table: toml::Table = toml::from_str(OLD).unwrap();
+        let migrations = migrate(&mut table);
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(
+            migrations[0].to_string(),
+            "whisper.backend was renamed to whisper.mode"
+        );
+        assert!(migrations[1].dropped);
+        assert_eq!(table["whisper"]["mode"].as_str(), Some("remote"));
+        assert!(table["output"].get("wtype_delay_ms").is_none());
+        assert_eq!(file_version(&table), CURRENT_VERSION);
+
+        // Already current: nothing to do
+        assert!(migrate(&mut table).is_empty());
+    }
+
+    #[test]
+    fn test_migrate_text() {
+        let (migrated, migrations) = migrate_text(OLD).unwrap();
+        assert_eq!(migrations.len(), 2);
+        assert_eq!(
+            migrated,
+            r#"# My voxtype config
+
+# Config schema version, updated by `voxtype config migrate --write`
+version = 2
+
+[whisper]
+# Transcribe on a server
+mode = "remote"
+model = "base.en"
+
+[output]
+mode = "type"
+# wtype_delay_ms = 50 (obsolete, replaced by pre_type_delay_ms)
+pre_type_delay_ms = 20
+"#
+        );
+
+        let (unchanged, migrations) = migrate_text(&migrated).unwrap();
+        assert_eq!(unchanged, migrated);
+        assert!(migrations.is_empty());
+
+        // Dotted keys aren't rewritten line by line
+        assert!(migrate_text("whisper.backend = \"remote\"\n").is_err());
+    }
+
+    #[test]
+    fn test_migrate_unset_new_key() {
+        // The default config ships pre_type_delay_ms = 0, which isn't a setting
+        let old = "[output]\nwtype_delay_ms = 50\npre_type_delay_ms = 0\n";
+        let mut table: toml::Table = toml::from_str(old).unwrap();
+        let migrations = migrate(&mut table);
+        assert_eq!(migrations.len(), 1);
+        assert!(migrations[0].replaced);
+        assert!(!migrations[0].dropped);
+        assert_eq!(
+            migrations[0].to_string(),
+            "output.wtype_delay_ms was renamed to output.pre_type_delay_ms"
+        );
+        assert_eq!(table["output"]["pre_type_delay_ms"].as_integer(), Some(50));
+        assert!(table["output"].get("wtype_delay_ms").is_none());
+
+        let (migrated, _) = migrate_text(old).unwrap();
+        assert_eq!(
+            migrated,
+            "# Config schema version, updated by `voxtype config migrate --write`\n\
+             version = 2\n\
+             \n\
+             [output]\n\
+             pre_type_delay_ms = 50\n\
+             # pre_type_delay_ms = 0 (unset, replaced by wtype_delay_ms)\n"
+        );
+    }
+}
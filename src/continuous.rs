@@ -0,0 +1,241 @@
+//! Continuous (hands-free) mode
+//!
+//! With no hotkey at all, the daemon listens on its own audio stream and
+//! cuts it into utterances: speech starts one, and a pause of
+//! `silence_secs` ends it. Each utterance is transcribed and typed like a
+//! recording.
+//!
+//! Saying the sleep phrase ("stop listening") pauses dictation: utterances
+//! are still transcribed, but only to hear the wake phrase ("start
+//! listening"). While dictation is on, the recording indicator stays on so
+//! the open microphone is never forgotten.
+
+use crate::audio::cpal_capture::CpalCapture;
+use crate::audio::AudioCapture;
+use crate::config::{Config, ContinuousConfig};
+use crate::error::AudioError;
+use crate::wake::{is_speech, FRAME_SIZE};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Sample rate of the listening stream and of the utterances
+const SAMPLE_RATE: usize = 16000;
+
+/// Audio kept from before the speech started, so the first syllable isn't cut
+const PRE_ROLL_SECS: f32 = 0.3;
+
+/// Speech shorter than this (a cough, a click) is dropped
+const MIN_SPEECH_SECS: f32 = 0.2;
+
+/// Cuts a stream of audio into utterances at pauses
+pub struct Segmenter {
+    /// Silent samples that end an utterance
+    silence_samples: usize,
+    /// Utterances are cut at this length even without a pause
+    max_samples: usize,
+    /// Samples of pre-roll kept while nobody speaks
+    pre_roll: usize,
+    /// The utterance so far, or the pre-roll before one
+    segment: Vec<f32>,
+    /// Whether an utterance is in progress
+    in_speech: bool,
+    /// Speech samples in the utterance
+    speech: usize,
+    /// Silent samples since the last speech
+    silent: usize,
+    /// Samples not yet checked, less than a frame
+    pending: Vec<f32>,
+}
+
+impl Segmenter {
+    /// Segment 16kHz audio, ending utterances after `silence_secs` of
+    /// silence or `max_secs` of audio
+    pub fn new(silence_secs: f32, max_secs: u32) -> Self {
+        Self {
+            silence_samples: (silence_secs.max(0.1) * SAMPLE_RATE as f32) as usize,
+            max_samples: max_secs.max(1) as usize * SAMPLE_RATE,
+            pre_roll: (PRE_ROLL_SECS * SAMPLE_RATE as f32) as usize,
+            segment: Vec::new(),
+            in_speech: false,
+            speech: 0,
+            silent: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add captured audio; returns the utterances it completed
+    pub fn feed(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
+        self.pending.extend_from_slice(samples);
+        let frames = self.pending.len() / FRAME_SIZE;
+        let mut finished = Vec::new();
+
+        for i in 0..frames {
+            let frame = &self.pending[i * FRAME_SIZE..(i + 1) * FRAME_SIZE];
+            let speech = is_speech(frame);
+            self.segment.extend_from_slice(frame);
+
+            if !self.in_speech {
+                if speech {
+                    self.in_speech = true;
+                    self.speech = FRAME_SIZE;
+                    self.silent = 0;
+                } else if self.segment.len() > self.pre_roll {
+                    let excess = self.segment.len() - self.pre_roll;
+                    self.segment.drain(..excess);
+                }
+                continue;
+            }
+
+            if speech {
+                self.speech += FRAME_SIZE;
+                self.silent = 0;
+            } else {
+                self.silent += FRAME_SIZE;
+            }
+            if self.silent >= self.silence_samples || self.segment.len() >= self.max_samples {
+                let segment = std::mem::take(&mut self.segment);
+                if self.speech as f32 >= MIN_SPEECH_SECS * SAMPLE_RATE as f32 {
+                    finished.push(segment);
+                }
+                self.in_speech = false;
+            }
+        }
+        self.pending.drain(..frames * FRAME_SIZE);
+
+        finished
+    }
+}
+
+/// Voice commands understood in continuous mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceCommand {
+    /// Pause dictation
+    Sleep,
+    /// Resume dictation
+    Wake,
+}
+
+/// The voice command an utterance consists of, if any
+///
+/// The whole utterance must be the phrase; case and punctuation are ignored.
+pub fn voice_command(text: &str, config: &ContinuousConfig) -> Option<VoiceCommand> {
+    let spoken = normalize(text);
+    if spoken.is_empty() {
+        None
+    } else if spoken == normalize(&config.sleep_phrase) {
+        Some(VoiceCommand::Sleep)
+    } else if spoken == normalize(&config.wake_phrase) {
+        Some(VoiceCommand::Wake)
+    } else {
+        None
+    }
+}
+
+/// Lowercase words without punctuation, separated by single spaces
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Start listening
+///
+/// Returns a channel that receives each utterance (16kHz audio) as soon as
+/// the speaker pauses. Listening runs until the daemon exits.
+pub async fn listen(config: &Config) -> Result<mpsc::Receiver<Vec<f32>>, AudioError> {
+    let mut audio_config = config.audio.clone();
+    if let Some(ref device) = config.continuous.device {
+        audio_config.device = device.clone();
+    }
+    audio_config.sample_rate = SAMPLE_RATE as u32;
+    let mut capture = CpalCapture::new(&audio_config)?;
+    let mut chunks = capture.start().await?;
+
+    let mut segmenter = Segmenter::new(
+        config.continuous.silence_secs,
+        config.audio.max_duration_secs,
+    );
+    let (tx, rx) = mpsc::channel(4);
+
+    tokio::spawn(async move {
+        // The capture also keeps everything it recorded; drop that regularly
+        let mut drain = tokio::time::interval(Duration::from_secs(1));
+        'listen: loop {
+            tokio::select! {
+                chunk = chunks.recv() => {
+                    let Some(chunk) = chunk else { break };
+                    for segment in segmenter.feed(&chunk) {
+                        if tx.send(segment).await.is_err() {
+                            break 'listen;
+                        }
+                    }
+                }
+                _ = drain.tick() => {
+                    capture.get_samples().await;
+                }
+            }
+        }
+        let _ = capture.stop().await;
+    });
+
+    Ok(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segmenter() {
+        let speech = [0.1; 1600];
+        let silence = [0.0; 1600];
+        let mut segmenter = Segmenter::new(0.5, 60);
+
+        // Silence alone produces nothing
+        for _ in 0..20 {
+            assert!(segmenter.feed(&silence).is_empty());
+        }
+        // Speech, a short pause, more speech, then a long pause
+        for _ in 0..5 {
+            assert!(segmenter.feed(&speech).is_empty());
+        }
+        assert!(segmenter.feed(&silence).is_empty());
+        assert!(segmenter.feed(&speech).is_empty());
+        for _ in 0..4 {
+            assert!(segmenter.feed(&silence).is_empty());
+        }
+        let segments = segmenter.feed(&silence);
+        assert_eq!(segments.len(), 1);
+        // Pre-roll, speech with the short pause, and the final pause
+        assert_eq!(segments[0].len(), 4800 + 7 * 1600 + 5 * 1600);
+    }
+
+    #[test]
+    fn test_segmenter_limits() {
+        let mut segmenter = Segmenter::new(0.5, 1);
+        // A click is too short to be an utterance
+        assert!(segmenter.feed(&[0.1; 320]).is_empty());
+        assert!(segmenter.feed(&[0.0; 16000]).is_empty());
+        // Speech without a pause is cut at the maximum length
+        let segments = segmenter.feed(&[0.1; 20000]);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].len(), 16000);
+    }
+
+    #[test]
+    fn test_voice_command() {
+        let config = ContinuousConfig::default();
+        assert_eq!(
+            voice_command("Stop listening.", &config),
+            Some(VoiceCommand::Sleep)
+        );
+        assert_eq!(
+            voice_command("start, listening!", &config),
+            Some(VoiceCommand::Wake)
+        );
+        assert_eq!(voice_command("Please stop listening to me", &config), None);
+        assert_eq!(voice_command("", &config), None);
+    }
+}
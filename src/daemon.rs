@@ -4,45 +4,55 @@
 //! and text output components.
 
 use crate::audio::feedback::{AudioFeedback, SoundEvent};
+use crate::audio::pre_roll::PreRoll;
 use crate::audio::{self, AudioCapture};
-use crate::config::{ActivationMode, Config, FileMode, OutputMode};
+use crate::config::{
+    ActivationMode, Config, FileMode, NotificationConfig, NotificationUrgency, OutputMode,
+    OutputSink, RefineOutput,
+};
+use crate::continuous::{self, VoiceCommand};
+use crate::daemon_info::DaemonInfo;
 use crate::eager::{self, EagerConfig};
 use crate::error::Result;
 use crate::hotkey::{self, HotkeyEvent};
 use crate::meeting::{self, MeetingDaemon, MeetingEvent, StorageConfig};
 use crate::model_manager::ModelManager;
 use crate::output;
+use crate::output::llm_cleanup::LlmCleanup;
 use crate::output::post_process::PostProcessor;
+use crate::perf::{PerfReport, PerfTimer};
+use crate::power::{self, PowerSource};
+use crate::script::{Hooks, TranscriptContext};
 use crate::state::{ChunkResult, State};
+use crate::text::rules::RuleSet;
+use crate::text::snippets::SnippetSet;
+use crate::text::spell;
 use crate::text::TextProcessor;
-use crate::transcribe::Transcriber;
+use crate::transcribe::chain::Fallbacks;
+use crate::transcribe::{Progress, Transcriber};
+use crate::wake::{self, SilenceStop};
 use pidlock::Pidlock;
-use std::path::PathBuf;
-use std::process::Stdio;
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::process::Command;
 use tokio::signal::unix::{signal, SignalKind};
 
-/// Send a desktop notification with optional engine icon
+/// Send a desktop notification, with the engine's icon in the title if
+/// `engine` is given and `show_engine_icon` is on
 async fn send_notification(
     title: &str,
     body: &str,
-    show_engine_icon: bool,
-    engine: crate::config::TranscriptionEngine,
+    config: &NotificationConfig,
+    engine: Option<crate::config::TranscriptionEngine>,
 ) {
-    let title = if show_engine_icon {
-        format!("{} {}", crate::output::engine_icon(engine), title)
-    } else {
-        title.to_string()
+    let title = match engine {
+        Some(engine) if config.show_engine_icon => {
+            format!("{} {}", crate::output::engine_icon(engine), title)
+        }
+        _ => title.to_string(),
     };
-
-    let _ = Command::new("notify-send")
-        .args(["--app-name=Voxtype", "--expire-time=2000", &title, body])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .await;
+    output::send_notification(config, &title, body, NotificationUrgency::Normal, 2000).await;
 }
 
 /// Write state to file for external integrations (e.g., Waybar)
@@ -102,6 +112,73 @@ fn cleanup_pid_file(path: &PathBuf) {
     }
 }
 
+/// How long `--replace` waits for the running daemon to exit
+const REPLACE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Recordings longer than this report transcription progress
+const PROGRESS_MIN_SECS: f32 = 20.0;
+
+/// Longest `[audio] release_tail_ms`
+const MAX_RELEASE_TAIL_MS: u32 = 1000;
+
+/// Take the single-instance lock
+///
+/// If another daemon holds it, fail with its PID, or with `replace` ask it
+/// to shut down (SIGTERM) and take the lock once it has exited.
+async fn acquire_lock(lock_path: &Path, replace: bool) -> Result<Pidlock> {
+    use nix::sys::signal::{kill, Signal};
+    use nix::unistd::Pid;
+
+    if let Some(parent) = lock_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut pidlock = Pidlock::new(&lock_path.to_string_lossy());
+    if pidlock.acquire().is_ok() {
+        return Ok(pidlock);
+    }
+
+    // get_owner() also clears a lock left behind by a dead process
+    let Some(owner) = pidlock.get_owner() else {
+        return match pidlock.acquire() {
+            Ok(()) => Ok(pidlock),
+            Err(e) => Err(crate::error::VoxtypeError::Config(format!(
+                "Failed to acquire lock {:?}: {:?}",
+                lock_path, e
+            ))),
+        };
+    };
+
+    if !replace {
+        return Err(crate::error::VoxtypeError::Config(format!(
+            "Another voxtype daemon is already running (PID {}). \
+             Stop it first, or start with --replace to take over",
+            owner
+        )));
+    }
+
+    tracing::info!("Asking running daemon (PID {}) to shut down", owner);
+    if let Err(e) = kill(Pid::from_raw(owner as i32), Signal::SIGTERM) {
+        return Err(crate::error::VoxtypeError::Config(format!(
+            "Failed to stop running daemon (PID {}): {}",
+            owner, e
+        )));
+    }
+
+    let deadline = std::time::Instant::now() + REPLACE_TIMEOUT;
+    while std::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        if pidlock.acquire().is_ok() {
+            tracing::info!("Took over from daemon (PID {})", owner);
+            return Ok(pidlock);
+        }
+    }
+    Err(crate::error::VoxtypeError::Config(format!(
+        "Running daemon (PID {}) did not exit within {}s",
+        owner,
+        REPLACE_TIMEOUT.as_secs()
+    )))
+}
+
 /// Check if cancel has been requested (via file trigger)
 fn check_cancel_requested() -> bool {
     let cancel_file = Config::runtime_dir().join("cancel");
@@ -122,6 +199,29 @@ fn cleanup_cancel_file() {
     }
 }
 
+/// Check if undo of the last output has been requested (via file trigger)
+fn check_undo_requested() -> bool {
+    let undo_file = Config::runtime_dir().join("undo");
+    if undo_file.exists() {
+        let _ = std::fs::remove_file(&undo_file);
+        true
+    } else {
+        false
+    }
+}
+
+/// Read and consume a model switch request (`voxtype model use/next`)
+///
+/// The file holds the model to switch to; an empty one asks for the next
+/// model, returned as `Some(None)`.
+fn check_model_switch_requested() -> Option<Option<String>> {
+    let switch_file = Config::runtime_dir().join("model_switch");
+    let content = std::fs::read_to_string(&switch_file).ok()?;
+    let _ = std::fs::remove_file(&switch_file);
+    let model = content.trim();
+    Some((!model.is_empty()).then(|| model.to_string()))
+}
+
 /// Read and consume the output mode override file
 /// Returns the override mode if the file exists and is valid, None otherwise
 /// Output mode override result, which may include a file path for file mode
@@ -152,8 +252,12 @@ fn read_output_mode_override() -> Option<OutputOverride> {
         tracing::warn!("Failed to remove output mode override file: {}", e);
     }
 
-    let trimmed = content.trim();
+    parse_output_mode_override(content.trim())
+}
 
+/// Parse an output mode override: "type", "clipboard", "paste", "file",
+/// "file:/path/to/file.txt" or "notify"
+fn parse_output_mode_override(trimmed: &str) -> Option<OutputOverride> {
     // Check for file mode with path: "file:/path/to/file.txt"
     if let Some(path) = trimmed.strip_prefix("file:") {
         let path = path.trim();
@@ -182,6 +286,10 @@ fn read_output_mode_override() -> Option<OutputOverride> {
             tracing::info!("Using output mode override: file (using config path)");
             Some(OutputOverride::Mode(OutputMode::File))
         }
+        "notify" => {
+            tracing::info!("Using output mode override: notify");
+            Some(OutputOverride::Mode(OutputMode::Notify))
+        }
         other => {
             tracing::warn!("Invalid output mode override: {:?}", other);
             None
@@ -388,45 +496,6 @@ fn write_meeting_state_file(path: &PathBuf, state: &str, meeting_id: Option<&str
     }
 }
 
-/// Write transcription to a file, respecting file_mode (overwrite or append)
-async fn write_transcription_to_file(
-    path: &std::path::Path,
-    text: &str,
-    file_mode: &FileMode,
-) -> std::io::Result<()> {
-    use tokio::io::AsyncWriteExt;
-
-    // Create parent directories if needed
-    if let Some(parent) = path.parent() {
-        if !parent.as_os_str().is_empty() {
-            tokio::fs::create_dir_all(parent).await?;
-        }
-    }
-
-    // Ensure text ends with newline
-    let output_text = if text.ends_with('\n') {
-        text.to_string()
-    } else {
-        format!("{}\n", text)
-    };
-
-    match file_mode {
-        FileMode::Overwrite => {
-            tokio::fs::write(path, output_text).await?;
-        }
-        FileMode::Append => {
-            let mut file = tokio::fs::OpenOptions::new()
-                .create(true)
-                .append(true)
-                .open(path)
-                .await?;
-            file.write_all(output_text.as_bytes()).await?;
-        }
-    }
-
-    Ok(())
-}
-
 /// Read and consume the model override file
 /// Returns the model name if the file exists, None otherwise
 fn read_model_override() -> Option<String> {
@@ -466,25 +535,200 @@ fn cleanup_model_override() {
 /// Result type for transcription task
 type TranscriptionResult = std::result::Result<String, crate::error::TranscribeError>;
 
+/// Per-recording settings from `voxtype record` flags and the focused app
+#[derive(Debug, Default)]
+struct RecordingOverrides {
+    output_mode: Option<OutputOverride>,
+    profile: Option<String>,
+    auto_submit: Option<bool>,
+    shift_enter: Option<bool>,
+    /// App focused when the recording started
+    app: Option<String>,
+    /// `[[whisper.app_prompts]]` text for the focused app
+    prompt_context: Option<String>,
+    /// Spell out the transcription (spell modifier held)
+    spell: bool,
+    /// GBNF grammar constraining the transcription
+    grammar: Option<PathBuf>,
+    /// An utterance from continuous mode, which may be a voice command
+    continuous: bool,
+}
+
+impl RecordingOverrides {
+    /// Read and consume the override files
+    ///
+    /// Done when the recording stops, so a recording queued behind another
+    /// transcription keeps its own flags. `app` is the app focused when the
+    /// recording started: its `[[profile_rules]]` profile applies when no
+    /// `--profile` was given, and its `[[whisper.app_prompts]]` are added to
+    /// the initial prompt. `spell` is whether the spell modifier was held,
+    /// which selects `[whisper] spell_grammar` over the profile's grammar.
+    fn take(config: &Config, app: Option<&str>, spell: bool) -> Self {
+        let auto_profile = app.and_then(|app| config.profile_for_app(app));
+        if let (Some(app), Some(profile)) = (app, auto_profile) {
+            tracing::info!("Focused app {:?}: using profile {}", app, profile);
+        }
+        let profile = read_profile_override().or_else(|| auto_profile.map(String::from));
+        let grammar = if spell {
+            config.whisper.spell_grammar.as_deref()
+        } else {
+            profile
+                .as_deref()
+                .and_then(|name| config.get_profile(name))
+                .and_then(|profile| profile.grammar.as_deref())
+        };
+        Self {
+            output_mode: read_output_mode_override(),
+            profile,
+            auto_submit: read_bool_override("auto_submit"),
+            shift_enter: read_bool_override("shift_enter"),
+            app: app.map(String::from),
+            prompt_context: app.and_then(|app| config.whisper.app_prompt(app)),
+            spell,
+            grammar: grammar.map(Config::grammar_path),
+            continuous: false,
+        }
+    }
+}
+
+/// A stopped recording waiting for its transcription and output
+struct PendingTranscription {
+    /// Audio, moved into the task when the transcription starts
+    samples: Vec<f32>,
+    /// How long the recording lasted
+    capture: Duration,
+    transcriber: Arc<dyn Transcriber>,
+    overrides: RecordingOverrides,
+    /// Running transcription (None while waiting in the queue)
+    task: Option<tokio::task::JoinHandle<TranscriptionResult>>,
+    /// Progress (percent) of the transcription, for long recordings
+    progress: Option<tokio::sync::mpsc::UnboundedReceiver<u8>>,
+    /// Timing for the performance report, from when the transcription starts
+    perf: Option<PerfTimer>,
+    /// Copy of the audio for `[whisper] refine_model`, after the draft is output
+    refine_audio: Option<Vec<f32>>,
+}
+
+/// A dictation being transcribed again with `[whisper] refine_model`
+struct Refinement {
+    task: tokio::task::JoinHandle<TranscriptionResult>,
+    transcriber: Arc<dyn Transcriber>,
+    /// The draft text as it was output
+    draft: String,
+    /// Profile and spell mode of the recording, to process the result alike
+    overrides: RecordingOverrides,
+    /// Whether a sentence had ended before the draft, to case the result alike
+    sentence_ended: bool,
+}
+
+/// Which transcription of a dictation is being processed
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pass {
+    /// The first one, which is output right away
+    Draft,
+    /// `[whisper] refine_model`'s, processed like the draft was
+    Refined { sentence_ended: bool },
+}
+
+/// Something that happened to a running transcription
+enum TranscriptionEvent {
+    /// Percent done, or None when the transcriber stops reporting
+    Progress(Option<u8>),
+    Finished(std::result::Result<TranscriptionResult, tokio::task::JoinError>),
+}
+
+impl PendingTranscription {
+    /// Start the transcription on a blocking thread
+    fn start(&mut self) {
+        let samples = std::mem::take(&mut self.samples);
+        let t = self.transcriber.clone();
+        self.perf = Some(PerfTimer::start(self.capture, samples.len(), t.clone()));
+        let progress = (samples.len() as f32 / 16000.0 > PROGRESS_MIN_SECS).then(|| {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            self.progress = Some(rx);
+            let progress: Progress = Arc::new(move |percent: u8| {
+                let _ = tx.send(percent);
+            });
+            progress
+        });
+        let context = self.overrides.prompt_context.clone();
+        let grammar = self.overrides.grammar.clone();
+        self.task = Some(tokio::task::spawn_blocking(move || {
+            if let Some(grammar) = grammar {
+                return t.transcribe_with_grammar(&samples, &grammar);
+            }
+            match (context, progress) {
+                (Some(context), progress) => {
+                    t.transcribe_with_context(&samples, progress, &context)
+                }
+                (None, Some(progress)) => t.transcribe_with_progress(&samples, progress),
+                (None, None) => t.transcribe(&samples),
+            }
+        }));
+    }
+
+    /// Wait for the next progress report or the result
+    async fn next_event(&mut self) -> TranscriptionEvent {
+        let Some(task) = self.task.as_mut() else {
+            return std::future::pending().await;
+        };
+        match self.progress.as_mut() {
+            Some(rx) => tokio::select! {
+                biased;
+                percent = rx.recv() => TranscriptionEvent::Progress(percent),
+                result = task => TranscriptionEvent::Finished(result),
+            },
+            None => TranscriptionEvent::Finished(task.await),
+        }
+    }
+}
+
 /// Main daemon that orchestrates all components
 pub struct Daemon {
     config: Config,
     config_path: Option<PathBuf>,
     state_file_path: Option<PathBuf>,
     pid_file_path: Option<PathBuf>,
+    // Take over from a running daemon instead of refusing to start
+    replace: bool,
     audio_feedback: Option<AudioFeedback>,
     text_processor: TextProcessor,
     post_processor: Option<PostProcessor>,
+    // LLM cleanup client, used when enabled in config or by the active profile
+    llm_cleanup: LlmCleanup,
+    // Regex [[postprocess.rules]], applied after the post-processing command
+    postprocess_rules: RuleSet,
+    // Voice-triggered [[snippets]], checked before any processing
+    snippets: SnippetSet,
+    // Last delivered output, kept so it can be undone
+    last_output: Option<output::LastOutput>,
+    // Output methods that failed their last probe, tried after the others
+    unavailable_outputs: Vec<&'static str>,
+    // Snapshot for `voxtype status --detail`, rewritten on every change
+    info: Mutex<DaemonInfo>,
     // Model manager for multi-model support
     model_manager: Option<ModelManager>,
+    // Power source the Whisper settings were last chosen for ([power])
+    power_source: Option<PowerSource>,
+    // [whisper] as configured, used on AC power
+    ac_whisper: crate::config::WhisperConfig,
     // Background task for loading model on-demand
     model_load_task: Option<
         tokio::task::JoinHandle<
             std::result::Result<Arc<dyn Transcriber>, crate::error::TranscribeError>,
         >,
     >,
-    // Background task for transcription (allows cancel during transcription)
-    transcription_task: Option<tokio::task::JoinHandle<TranscriptionResult>>,
+    // Transcribers for profiles with their own engine, loaded on first use
+    profile_transcribers: HashMap<crate::config::TranscriptionEngine, Arc<dyn Transcriber>>,
+    // [fallback] transcribers for recordings the configured one fails on
+    fallbacks: Arc<Fallbacks>,
+    // [scripting] hooks, loaded at startup
+    hooks: Option<Hooks>,
+    // Stopped recordings, oldest first; the first one is being transcribed
+    // and the rest wait their turn, so outputs keep the recording order
+    transcriptions: VecDeque<PendingTranscription>,
+    // Re-transcription of the last dictation with the refine model
+    refinement: Option<Refinement>,
     // Background tasks for eager chunk transcriptions (chunk_index, task)
     eager_chunk_tasks: Vec<(
         usize,
@@ -506,6 +750,23 @@ pub struct Daemon {
     // GTCRN speech enhancer for mic echo cancellation
     #[cfg(feature = "onnx-common")]
     speech_enhancer: Option<std::sync::Arc<audio::enhance::GtcrnEnhancer>>,
+    // On-screen captions ([overlay] enabled)
+    #[cfg(target_os = "linux")]
+    overlay: Option<crate::overlay::Overlay>,
+    // On-screen recording indicator ([indicator] enabled)
+    #[cfg(target_os = "linux")]
+    indicator: Option<crate::indicator::Indicator>,
+    // App focused when the recording started, for [[profile_rules]] and
+    // [[whisper.app_prompts]]
+    recording_app: Option<String>,
+    // Spell modifier held when the recording started
+    recording_spell: bool,
+    // Ends the recording at a pause when the wake word started it
+    silence_stop: Option<SilenceStop>,
+    // Continuous mode is dictating (on and not put to sleep)
+    listening: bool,
+    // Idle microphone audio put in front of recordings ([audio] pre_roll_ms)
+    pre_roll: Option<PreRoll>,
 }
 
 impl Daemon {
@@ -535,6 +796,17 @@ impl Daemon {
 
         // Initialize text processor
         let text_processor = TextProcessor::new(&config.text);
+        let postprocess_rules = RuleSet::new(&config.postprocess.rules);
+        if !postprocess_rules.is_empty() {
+            tracing::info!(
+                "Post-processing rules enabled: {} rule(s)",
+                config.postprocess.rules.len()
+            );
+        }
+        let snippets = SnippetSet::new(&config.snippets);
+        if !snippets.is_empty() {
+            tracing::info!("Snippets configured: {}", config.snippets.len());
+        }
         if config.text.spoken_punctuation {
             tracing::info!("Spoken punctuation enabled");
         }
@@ -546,15 +818,24 @@ impl Daemon {
         }
 
         // Initialize post-processor if configured
-        let post_processor = config.output.post_process.as_ref().map(|cfg| {
+        let post_processor = config.output.effective_post_process().map(|cfg| {
             tracing::info!(
                 "Post-processing enabled: command={:?}, timeout={}ms",
                 cfg.command,
                 cfg.timeout_ms
             );
-            PostProcessor::new(cfg)
+            PostProcessor::new(&cfg)
         });
 
+        let llm_cleanup = LlmCleanup::new(&config.output.llm_cleanup);
+        if config.output.llm_cleanup.enabled {
+            tracing::info!(
+                "LLM cleanup enabled: endpoint={:?}, model={:?}",
+                config.output.llm_cleanup.endpoint,
+                config.output.llm_cleanup.model
+            );
+        }
+
         // Initialize Voice Activity Detection if enabled
         let vad = match crate::vad::create_vad(&config) {
             Ok(Some(vad)) => {
@@ -580,17 +861,54 @@ impl Daemon {
             None
         };
 
+        #[cfg(target_os = "linux")]
+        let overlay = config.overlay.enabled.then(|| {
+            tracing::info!("Caption overlay enabled ({:?})", config.overlay.position);
+            crate::overlay::Overlay::spawn(&config.overlay)
+        });
+        #[cfg(target_os = "linux")]
+        let indicator = config.indicator.enabled.then(|| {
+            tracing::info!("Recording indicator enabled ({:?})", config.indicator.style);
+            crate::indicator::Indicator::spawn(&config.indicator)
+        });
+        #[cfg(not(target_os = "linux"))]
+        if config.overlay.enabled {
+            tracing::warn!("The caption overlay needs a Wayland compositor; ignoring [overlay]");
+        }
+        #[cfg(not(target_os = "linux"))]
+        if config.indicator.enabled {
+            tracing::warn!(
+                "The recording indicator needs a Wayland compositor; ignoring [indicator]"
+            );
+        }
+
+        let info = Mutex::new(DaemonInfo::new(&config));
+        let ac_whisper = config.whisper.clone();
+
         Self {
             config,
             config_path,
             state_file_path,
             pid_file_path: None,
+            replace: false,
             audio_feedback,
             text_processor,
             post_processor,
+            llm_cleanup,
+            postprocess_rules,
+            snippets,
+            last_output: None,
+            unavailable_outputs: Vec::new(),
+            info,
             model_manager: None,
+            power_source: None,
+            ac_whisper,
             model_load_task: None,
-            transcription_task: None,
+            profile_transcribers: HashMap::new(),
+            fallbacks: Arc::default(),
+            hooks: None,
+            transcriptions: VecDeque::new(),
+            refinement: None,
             eager_chunk_tasks: Vec::new(),
             vad,
             meeting_daemon: None,
@@ -601,9 +919,100 @@ impl Daemon {
             meeting_event_rx: None,
             #[cfg(feature = "onnx-common")]
             speech_enhancer: None,
+            #[cfg(target_os = "linux")]
+            overlay,
+            #[cfg(target_os = "linux")]
+            indicator,
+            recording_app: None,
+            recording_spell: false,
+            silence_stop: None,
+            listening: false,
+            pre_roll: None,
+        }
+    }
+
+    /// Shut down an already running daemon and take over, instead of failing
+    pub fn with_replace(mut self, replace: bool) -> Self {
+        self.replace = replace;
+        self
+    }
+
+    /// Put the pre-roll audio (if enabled) in front of a recording that
+    /// just started
+    ///
+    /// Not for wake-word recordings, whose pre-roll is the wake phrase.
+    fn with_pre_roll(&self, capture: Box<dyn AudioCapture>, woken: bool) -> Box<dyn AudioCapture> {
+        match self.pre_roll {
+            Some(ref pre_roll) => pre_roll.wrap(capture, !woken),
+            None => capture,
+        }
+    }
+
+    /// Overrides for the recording that just stopped
+    fn take_overrides(&mut self) -> RecordingOverrides {
+        let app = self.recording_app.take();
+        let spell = std::mem::take(&mut self.recording_spell);
+        RecordingOverrides::take(&self.config, app.as_deref(), spell)
+    }
+
+    /// The transcriber for a profile that sets its own `engine`
+    ///
+    /// Loaded on first use and kept. None when the profile uses the
+    /// configured engine, or when its engine fails to load, in which case
+    /// the configured engine transcribes.
+    async fn profile_transcriber(
+        &mut self,
+        profile: Option<&str>,
+    ) -> Option<Arc<dyn Transcriber>> {
+        let engine = profile
+            .and_then(|name| self.config.get_profile(name))
+            .and_then(|profile| profile.engine)
+            .filter(|&engine| engine != self.config.engine)?;
+        if let Some(transcriber) = self.profile_transcribers.get(&engine) {
+            return Some(transcriber.clone());
+        }
+
+        tracing::info!(
+            "Loading {:?} engine for profile {}",
+            engine,
+            profile.unwrap_or_default()
+        );
+        let mut config = self.config.clone();
+        config.engine = engine;
+        let loaded =
+            tokio::task::spawn_blocking(move || crate::transcribe::create_transcriber(&config))
+                .await;
+        match loaded {
+            Ok(Ok(transcriber)) => {
+                self.profile_transcribers.insert(engine, transcriber.clone());
+                Some(transcriber)
+            }
+            Ok(Err(e)) => {
+                tracing::error!(
+                    "Failed to load {:?} engine, using {:?}: {}",
+                    engine,
+                    self.config.engine,
+                    e
+                );
+                None
+            }
+            Err(e) => {
+                tracing::error!("Engine loading task panicked: {}", e);
+                None
+            }
         }
     }
 
+    /// The focused app, looked up only when a per-app setting needs it
+    async fn focused_app_for_recording(&self) -> Option<String> {
+        if self.config.profile_rules.is_empty() && self.config.whisper.app_prompts.is_empty() {
+            return None;
+        }
+        let app = output::active_window::focused_app().await;
+        tracing::debug!("Focused app at recording start: {:?}", app);
+        app
+    }
+
     /// Play audio feedback sound if enabled
     fn play_feedback(&self, event: SoundEvent) {
         if let Some(ref feedback) = self.audio_feedback {
@@ -611,23 +1020,99 @@ impl Daemon {
         }
     }
 
+    /// Send a critical notification about a failure, if `on_error` is enabled
+    async fn notify_error(&self, title: &str, error: &str) {
+        let config = &self.config.output.notification;
+        if config.on_error {
+            output::send_notification(config, title, error, NotificationUrgency::Critical, 5000)
+                .await;
+        }
+    }
+
     /// Update the state file if configured
     fn update_state(&self, state_name: &str) {
         if let Some(ref path) = self.state_file_path {
             write_state_file(path, state_name);
         }
+        #[cfg(target_os = "linux")]
+        if let Some(ref overlay) = self.overlay {
+            overlay.set_state(state_name);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(ref indicator) = self.indicator {
+            indicator.set_recording(state_name == "recording" || self.listening);
+        }
+        self.update_info(|info| info.set_state(state_name, chrono::Local::now()));
+        if let Some(ref hooks) = self.hooks {
+            hooks.on_state_change(state_name);
+        }
+    }
+
+    /// Publish transcription progress in the status snapshot
+    ///
+    /// Notifies at each quarter when recording-stop notifications are on,
+    /// so a long transcription doesn't look like a hung daemon.
+    async fn report_progress(&self, percent: u8) {
+        let previous = self
+            .info
+            .lock()
+            .ok()
+            .and_then(|info| info.progress)
+            .unwrap_or(0);
+        if percent <= previous {
+            return;
+        }
+        tracing::debug!("Transcription progress: {}%", percent);
+        self.update_info(|info| info.progress = Some(percent));
+
+        if self.config.output.notification.on_recording_stop
+            && percent < 100
+            && percent / 25 > previous / 25
+        {
+            send_notification(
+                "Transcribing...",
+                &format!("{}% done", percent / 25 * 25),
+                &self.config.output.notification,
+                Some(self.config.engine),
+            )
+            .await;
+        }
+    }
+
+    /// Apply a change to the daemon status snapshot and save it
+    fn update_info(&self, change: impl FnOnce(&mut DaemonInfo)) {
+        let Ok(mut info) = self.info.lock() else {
+            return;
+        };
+        change(&mut info);
+        if let Err(e) = info.write() {
+            tracing::debug!("Failed to write {:?}: {}", DaemonInfo::path(), e);
+        }
     }
 
     /// Get the transcriber for the current recording session
     ///
     /// For on-demand loading: waits for the background model load task to complete
     /// For preloaded models: returns the preloaded transcriber (Parakeet) or gets from model manager (Whisper)
+    /// Either way it falls back to the `[fallback]` transcribers.
     ///
     /// Returns Ok(transcriber) on success, Err(()) if an error occurred and caller should skip to next iteration
     async fn get_transcriber_for_recording(
         &mut self,
         model_override: Option<&str>,
         transcriber_preloaded: &Option<Arc<dyn Transcriber>>,
+    ) -> std::result::Result<Arc<dyn Transcriber>, ()> {
+        let transcriber = self
+            .configured_transcriber(model_override, transcriber_preloaded)
+            .await?;
+        Ok(self.fallbacks.wrap(transcriber))
+    }
+
+    /// The configured transcriber for the current recording session
+    async fn configured_transcriber(
+        &mut self,
+        model_override: Option<&str>,
+        transcriber_preloaded: &Option<Arc<dyn Transcriber>>,
     ) -> std::result::Result<Arc<dyn Transcriber>, ()> {
         if self.config.on_demand_loading() {
             // Wait for background model load task
@@ -661,7 +1146,8 @@ impl Daemon {
                 | crate::config::TranscriptionEngine::SenseVoice
                 | crate::config::TranscriptionEngine::Paraformer
                 | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
+                | crate::config::TranscriptionEngine::Omnilingual
+                | crate::config::TranscriptionEngine::Onnx => {
                     if let Some(ref t) = transcriber_preloaded {
                         Ok(t.clone())
                     } else {
@@ -690,6 +1176,156 @@ impl Daemon {
         }
     }
 
+    /// Switch the Whisper settings between AC and battery (`[power]`)
+    ///
+    /// Models loaded with the old settings are dropped; the new ones load
+    /// on next use.
+    fn follow_power_source(&mut self) {
+        if !self.config.power.is_enabled()
+            || !matches!(
+                self.config.engine,
+                crate::config::TranscriptionEngine::Whisper
+            )
+        {
+            return;
+        }
+        let Some(source) = power::power_source() else {
+            return;
+        };
+        if self.power_source == Some(source) {
+            return;
+        }
+        self.power_source = Some(source);
+
+        self.config.whisper = match source {
+            PowerSource::Ac => self.ac_whisper.clone(),
+            PowerSource::Battery => self.config.power.on_battery(&self.ac_whisper),
+        };
+        tracing::info!(
+            "On {}: model '{}', gpu_backend = {}",
+            source,
+            self.config.whisper.model,
+            self.config.whisper.gpu_backend
+        );
+        if self.model_manager.is_some() {
+            self.model_manager = Some(ModelManager::new(
+                &self.config.whisper,
+                self.config_path.clone(),
+            ));
+        }
+    }
+
+    /// Switch to another Whisper model until the daemon restarts (`voxtype
+    /// model use/next`, `[hotkey] model_cycle_key`); None is the next model
+    ///
+    /// Loaded models are dropped, the subprocess worker included, and the
+    /// new model is preloaded as at startup. If it fails to load, the
+    /// previous model stays.
+    async fn switch_model(&mut self, model: Option<String>) {
+        if !matches!(
+            self.config.engine,
+            crate::config::TranscriptionEngine::Whisper
+        ) {
+            tracing::warn!(
+                "Model switching needs the Whisper engine (engine = {:?})",
+                self.config.engine
+            );
+            self.play_feedback(SoundEvent::Error);
+            return;
+        }
+        let Some(model) = model.or_else(|| {
+            self.ac_whisper
+                .next_model(&self.config.whisper.model)
+                .map(String::from)
+        }) else {
+            tracing::info!("No other model to switch to; list some in [whisper] available_models");
+            return;
+        };
+        if model == self.config.whisper.model {
+            tracing::info!("Already using model '{}'", model);
+            return;
+        }
+
+        // Free the current model first, so both don't have to fit in memory
+        self.model_manager = None;
+        let mut whisper = self.config.whisper.clone();
+        whisper.model = model.clone();
+        let mut model_manager = ModelManager::new(&whisper, self.config_path.clone());
+        if let Err(e) = model_manager.preload_primary() {
+            tracing::error!("Failed to switch to model '{}': {}", model, e);
+            self.play_feedback(SoundEvent::Error);
+            self.notify_error("Model Switch Failed", &e.to_string())
+                .await;
+            self.model_manager = Some(ModelManager::new(
+                &self.config.whisper,
+                self.config_path.clone(),
+            ));
+            return;
+        }
+
+        tracing::info!(
+            "Switched from model '{}' to '{}'",
+            self.config.whisper.model,
+            model
+        );
+        self.config.whisper = whisper;
+        self.ac_whisper.model = model.clone();
+        self.model_manager = Some(model_manager);
+        self.update_info(|info| info.model = model.clone());
+        send_notification(
+            "Model Switched",
+            &format!("Now using {}", model),
+            &self.config.output.notification,
+            Some(self.config.engine),
+        )
+        .await;
+    }
+
+    /// Model for a recording being stopped: the one requested when it
+    /// started, else the `[[whisper.length_models]]` rule matching its length
+    ///
+    /// With on-demand loading, a model picked by length replaces the one
+    /// that has been loading since the hotkey was pressed.
+    fn model_at_release(&mut self, state: &State) -> Option<String> {
+        let State::Recording {
+            started_at,
+            model_override: None,
+        } = state
+        else {
+            return state.model_override().map(String::from);
+        };
+        if !matches!(
+            self.config.engine,
+            crate::config::TranscriptionEngine::Whisper
+        ) {
+            return None;
+        }
+        let secs = started_at.elapsed().as_secs_f32();
+        let model = self.config.whisper.model_for_length(secs)?.to_string();
+        if model == self.config.whisper.model {
+            return None;
+        }
+        tracing::info!(
+            "{:.1}s recording, transcribing with model '{}'",
+            secs,
+            model
+        );
+
+        if self.config.on_demand_loading() {
+            if let Some(task) = self.model_load_task.take() {
+                task.abort();
+            }
+            let config = self.config.whisper.clone();
+            let config_path = self.config_path.clone();
+            let model_to_load = model.clone();
+            self.model_load_task = Some(tokio::task::spawn_blocking(move || {
+                let mut temp_manager = ModelManager::new(&config, config_path);
+                temp_manager.get_transcriber(Some(&model_to_load))
+            }));
+        }
+        Some(model)
+    }
+
     /// Update the meeting state file if configured
     fn update_meeting_state(&self, state_name: &str, meeting_id: Option<&str>) {
         if let Some(ref path) = self.meeting_state_file_path {
@@ -719,6 +1355,8 @@ impl Daemon {
             },
             retain_audio: self.config.meeting.retain_audio,
             max_duration_mins: self.config.meeting.max_duration_mins,
+            notes_file: self.config.meeting.notes_file.clone(),
+            diarization: (&self.config.meeting.diarization).into(),
         };
 
         // Create event channel
@@ -793,8 +1431,8 @@ impl Daemon {
                             send_notification(
                                 "Meeting Started",
                                 &format!("ID: {}", meeting_id),
-                                false,
-                                self.config.engine,
+                                &self.config.output.notification,
+                                None,
                             )
                             .await;
                         }
@@ -833,8 +1471,8 @@ impl Daemon {
                         send_notification(
                             "Meeting Ended",
                             &format!("ID: {}", meeting_id),
-                            false,
-                            self.config.engine,
+                            &self.config.output.notification,
+                            None,
                         )
                         .await;
                     }
@@ -864,8 +1502,8 @@ impl Daemon {
                 send_notification(
                     "Meeting Paused",
                     "Recording paused",
-                    false,
-                    self.config.engine,
+                    &self.config.output.notification,
+                    None,
                 )
                 .await;
             }
@@ -885,8 +1523,8 @@ impl Daemon {
                 send_notification(
                     "Meeting Resumed",
                     "Recording resumed",
-                    false,
-                    self.config.engine,
+                    &self.config.output.notification,
+                    None,
                 )
                 .await;
             }
@@ -907,7 +1545,8 @@ impl Daemon {
         16000 * self.config.meeting.chunk_duration_secs as usize
     }
 
-    /// Reset state to idle and run post_output_command to reset compositor submap
+    /// Reset state to idle (or transcribing, while recordings are queued) and run
+    /// post_output_command to reset compositor submap
     /// Call this when exiting from recording/transcribing without normal output flow
     async fn reset_to_idle(&self, state: &mut State) {
         cleanup_output_mode_override();
@@ -916,7 +1555,7 @@ impl Daemon {
         cleanup_bool_override("auto_submit");
         cleanup_bool_override("shift_enter");
         *state = State::Idle;
-        self.update_state("idle");
+        self.settle_state(state);
 
         // Run post_output_command to reset compositor submap
         if let Some(cmd) = &self.config.output.post_output_command {
@@ -930,7 +1569,7 @@ impl Daemon {
     fn spawn_chunk_transcription(
         &mut self,
         chunk_index: usize,
-        chunk_audio: Vec<f32>,
+        mut chunk_audio: Vec<f32>,
         transcriber: Arc<dyn Transcriber>,
     ) {
         tracing::debug!(
@@ -939,7 +1578,13 @@ impl Daemon {
             chunk_audio.len() as f32 / 16000.0
         );
 
-        let task = tokio::task::spawn_blocking(move || transcriber.transcribe(&chunk_audio));
+        let target_lufs = self.loudness_target();
+        let task = tokio::task::spawn_blocking(move || {
+            if let Some(target_lufs) = target_lufs {
+                audio::loudness::normalize(&mut chunk_audio, target_lufs);
+            }
+            transcriber.transcribe(&chunk_audio)
+        });
 
         self.eager_chunk_tasks.push((chunk_index, task));
     }
@@ -1047,11 +1692,13 @@ impl Daemon {
     }
 
     /// Finish eager recording: wait for all chunks, transcribe tail, combine results
+    ///
+    /// Returns the text with the timing for its performance report.
     async fn finish_eager_recording(
         &mut self,
         state: &mut State,
         transcriber: Arc<dyn Transcriber>,
-    ) -> Option<String> {
+    ) -> Option<(String, PerfTimer)> {
         // Extract state data
         let (accumulated_audio, mut chunk_results) = match state {
             State::EagerRecording {
@@ -1063,6 +1710,11 @@ impl Daemon {
         };
 
         let audio_duration = accumulated_audio.len() as f32 / 16000.0;
+        let perf = PerfTimer::start(
+            state.recording_duration().unwrap_or_default(),
+            accumulated_audio.len(),
+            transcriber.clone(),
+        );
         tracing::info!(
             "Finishing eager recording: {:.1}s of audio, {} chunks already transcribed",
             audio_duration,
@@ -1084,7 +1736,7 @@ impl Daemon {
         let tail_start = chunks_sent * eager_config.stride_samples();
 
         if tail_start < accumulated_audio.len() {
-            let tail_audio = accumulated_audio[tail_start..].to_vec();
+            let mut tail_audio = accumulated_audio[tail_start..].to_vec();
             let tail_duration = tail_audio.len() as f32 / 16000.0;
 
             if tail_duration >= 0.3 {
@@ -1095,8 +1747,14 @@ impl Daemon {
                 );
 
                 let tail_transcriber = transcriber.clone();
-                match tokio::task::spawn_blocking(move || tail_transcriber.transcribe(&tail_audio))
-                    .await
+                let target_lufs = self.loudness_target();
+                match tokio::task::spawn_blocking(move || {
+                    if let Some(target_lufs) = target_lufs {
+                        audio::loudness::normalize(&mut tail_audio, target_lufs);
+                    }
+                    tail_transcriber.transcribe(&tail_audio)
+                })
+                .await
                 {
                     Ok(Ok(text)) => {
                         tracing::debug!("Tail transcription: {:?}", text);
@@ -1122,7 +1780,7 @@ impl Daemon {
         if combined.is_empty() {
             None
         } else {
-            Some(combined)
+            Some((combined, perf))
         }
     }
 
@@ -1145,8 +1803,8 @@ impl Daemon {
             send_notification(
                 "Recording Stopped",
                 "Transcribing...",
-                self.config.output.notification.show_engine_icon,
-                self.config.engine,
+                &self.config.output.notification,
+                Some(self.config.engine),
             )
             .await;
         }
@@ -1154,7 +1812,7 @@ impl Daemon {
         // Stop recording and get samples
         if let Some(mut capture) = audio_capture.take() {
             match capture.stop().await {
-                Ok(samples) => {
+                Ok(mut samples) => {
                     let audio_duration = samples.len() as f32 / 16000.0;
 
                     // Skip if too short (likely accidental press)
@@ -1165,46 +1823,66 @@ impl Daemon {
                     }
 
                     // Voice Activity Detection: skip if no speech detected
-                    if let Some(ref vad) = self.vad {
-                        match vad.detect(&samples) {
-                            Ok(result) if !result.has_speech => {
-                                tracing::debug!(
-                                    "No speech detected (speech={:.1}%, rms={:.4}), skipping transcription",
-                                    result.speech_ratio * 100.0,
-                                    result.rms_energy
-                                );
-                                self.play_feedback(SoundEvent::Cancelled);
-                                self.reset_to_idle(state).await;
-                                return false;
-                            }
-                            Ok(result) => {
-                                tracing::debug!(
-                                    "Speech detected: {:.2}s ({:.1}%)",
-                                    result.speech_duration_secs,
-                                    result.speech_ratio * 100.0
-                                );
-                            }
-                            Err(e) => {
-                                // VAD failed, proceed with transcription anyway
-                                tracing::warn!("VAD failed, proceeding anyway: {}", e);
-                            }
-                        }
+                    if !self.has_speech(&samples) {
+                        self.play_feedback(SoundEvent::Cancelled);
+                        self.reset_to_idle(state).await;
+                        return false;
+                    }
+                    if let Some(target_lufs) = self.loudness_target() {
+                        audio::loudness::normalize(&mut samples, target_lufs);
                     }
 
+                    // Keep the audio for the refine model, unless the recording
+                    // asked for a model of its own
+                    let refine_audio = (self.config.whisper.refine_model.is_some()
+                        && matches!(
+                            self.config.engine,
+                            crate::config::TranscriptionEngine::Whisper
+                        )
+                        && state.model_override().is_none())
+                    .then(|| samples.clone());
+
                     tracing::info!("Transcribing {:.1}s of audio...", audio_duration);
                     *state = State::Transcribing {
                         audio: samples.clone(),
                     };
                     self.update_state("transcribing");
 
-                    // Spawn transcription task (non-blocking)
+                    // Queue the transcription (non-blocking); it starts right away
+                    // unless earlier recordings use all the transcriber's workers
                     if let Some(t) = transcriber {
-                        self.transcription_task =
-                            Some(tokio::task::spawn_blocking(move || t.transcribe(&samples)));
+                        let overrides = self.take_overrides();
+                        let t = self
+                            .profile_transcriber(overrides.profile.as_deref())
+                            .await
+                            .unwrap_or(t);
+                        self.transcriptions.push_back(PendingTranscription {
+                            samples,
+                            capture: duration,
+                            transcriber: t,
+                            overrides,
+                            task: None,
+                            progress: None,
+                            perf: None,
+                            refine_audio,
+                        });
+                        self.start_queued_transcriptions();
+                        if self
+                            .transcriptions
+                            .back()
+                            .is_some_and(|job| job.task.is_none())
+                        {
+                            tracing::info!(
+                                "Queued behind {} transcription(s) in progress",
+                                self.transcriptions.len() - 1
+                            );
+                        }
                         true
                     } else {
                         tracing::error!("No transcriber available");
                         self.play_feedback(SoundEvent::Error);
+                        self.notify_error("Transcription Failed", "No transcriber available")
+                            .await;
                         self.reset_to_idle(state).await;
                         false
                     }
@@ -1221,31 +1899,449 @@ impl Daemon {
         }
     }
 
-    /// Handle transcription completion (called when transcription_task completes)
-    async fn handle_transcription_result(
+    /// Whether VAD (if enabled) finds speech in the audio
+    fn has_speech(&self, samples: &[f32]) -> bool {
+        let Some(ref vad) = self.vad else {
+            return true;
+        };
+        match vad.detect(samples) {
+            Ok(result) if !result.has_speech => {
+                tracing::debug!(
+                    "No speech detected (speech={:.1}%, rms={:.4}), skipping transcription",
+                    result.speech_ratio * 100.0,
+                    result.rms_energy
+                );
+                false
+            }
+            Ok(result) => {
+                tracing::debug!(
+                    "Speech detected: {:.2}s ({:.1}%)",
+                    result.speech_duration_secs,
+                    result.speech_ratio * 100.0
+                );
+                true
+            }
+            Err(e) => {
+                // VAD failed, proceed with transcription anyway
+                tracing::warn!("VAD failed, proceeding anyway: {}", e);
+                true
+            }
+        }
+    }
+
+    /// Target loudness recordings are normalized to, if `[audio]
+    /// normalize_loudness` is on
+    ///
+    /// Normalization runs after VAD, whose energy check would be fooled by
+    /// amplified background noise.
+    fn loudness_target(&self) -> Option<f32> {
+        self.config
+            .audio
+            .normalize_loudness
+            .then_some(self.config.audio.target_lufs)
+    }
+
+    /// Queue an utterance from continuous mode for transcription
+    async fn queue_utterance(
+        &mut self,
+        state: &mut State,
+        mut samples: Vec<f32>,
+        transcriber: Arc<dyn Transcriber>,
+    ) {
+        if !self.has_speech(&samples) {
+            return;
+        }
+        if let Some(target_lufs) = self.loudness_target() {
+            audio::loudness::normalize(&mut samples, target_lufs);
+        }
+        let audio_duration = samples.len() as f32 / 16000.0;
+        tracing::info!("Transcribing {:.1}s utterance...", audio_duration);
+        *state = State::Transcribing {
+            audio: samples.clone(),
+        };
+        self.update_state("transcribing");
+
+        self.recording_app = self.focused_app_for_recording().await;
+        let mut overrides = self.take_overrides();
+        overrides.continuous = true;
+        self.transcriptions.push_back(PendingTranscription {
+            samples,
+            capture: Duration::from_secs_f32(audio_duration),
+            transcriber,
+            overrides,
+            task: None,
+            progress: None,
+            perf: None,
+            refine_audio: None,
+        });
+        self.start_queued_transcriptions();
+    }
+
+    /// Act on a continuous-mode voice command; true when the utterance
+    /// must not be output (a command, or anything said while asleep)
+    async fn handle_voice_command(&mut self, text: &str) -> bool {
+        match continuous::voice_command(text, &self.config.continuous) {
+            Some(VoiceCommand::Sleep) if self.listening => {
+                tracing::info!("Continuous mode: stopped listening");
+                self.listening = false;
+                self.play_feedback(SoundEvent::RecordingStop);
+                send_notification(
+                    "Stopped listening",
+                    &format!("Say \"{}\" to resume", self.config.continuous.wake_phrase),
+                    &self.config.output.notification,
+                    Some(self.config.engine),
+                )
+                .await;
+                true
+            }
+            Some(VoiceCommand::Wake) if !self.listening => {
+                tracing::info!("Continuous mode: listening");
+                self.listening = true;
+                self.play_feedback(SoundEvent::RecordingStart);
+                send_notification(
+                    "Listening",
+                    &format!("Say \"{}\" to pause", self.config.continuous.sleep_phrase),
+                    &self.config.output.notification,
+                    Some(self.config.engine),
+                )
+                .await;
+                true
+            }
+            Some(_) => true,
+            None => {
+                if !self.listening {
+                    tracing::debug!("Not listening, ignoring {:?}", text);
+                }
+                !self.listening
+            }
+        }
+    }
+
+    /// Start queued transcriptions, oldest first, as far as the
+    /// transcriber allows (one at a time unless it supports more)
+    fn start_queued_transcriptions(&mut self) {
+        let mut running = 0;
+        for job in self.transcriptions.iter_mut() {
+            if job.task.is_some() {
+                running += 1;
+                continue;
+            }
+            if running >= job.transcriber.max_concurrent() {
+                break;
+            }
+            job.start();
+            running += 1;
+        }
+    }
+
+    /// Queue the text of a finished eager recording for output
+    fn queue_eager_result(
+        &mut self,
+        text: String,
+        perf: PerfTimer,
+        transcriber: Arc<dyn Transcriber>,
+    ) {
+        let overrides = self.take_overrides();
+        self.transcriptions.push_back(PendingTranscription {
+            samples: Vec::new(),
+            capture: Duration::ZERO,
+            transcriber,
+            overrides,
+            task: Some(tokio::spawn(async move { Ok(text) })),
+            progress: None,
+            perf: Some(perf),
+            refine_audio: None,
+        });
+    }
+
+    /// Stop all transcriptions, running and queued
+    ///
+    /// Dropping a task alone would leave whisper (or the worker process)
+    /// running to the end in the background, so the transcriber is told to
+    /// stop too.
+    fn abort_transcriptions(&mut self) {
+        for job in self.transcriptions.drain(..) {
+            if let Some(task) = job.task {
+                task.abort();
+                job.transcriber.cancel();
+            }
+        }
+    }
+
+    /// Leave the transcribing state once a transcription is done
+    ///
+    /// A recording started in the meantime keeps the recording state, and
+    /// while more recordings are queued the state stays transcribing.
+    fn settle_state(&self, state: &mut State) {
+        if state.is_recording() {
+            return;
+        }
+        if self.transcriptions.is_empty() {
+            *state = State::Idle;
+            self.update_state("idle");
+        } else {
+            *state = State::Transcribing { audio: Vec::new() };
+            self.update_state("transcribing");
+        }
+    }
+
+    /// Settle the state after a transcription that output nothing
+    ///
+    /// Runs post_output_command (to reset the compositor submap) like an
+    /// output would, unless a new recording has started.
+    async fn finish_without_output(&self, state: &mut State) {
+        if state.is_recording() {
+            return;
+        }
+        self.settle_state(state);
+        if let Some(cmd) = &self.config.output.post_output_command {
+            if let Err(e) = output::run_hook(cmd, "post_output").await {
+                tracing::warn!("{}", e);
+            }
+        }
+    }
+
+    /// Erase the last output (undo key or `voxtype record undo`)
+    async fn undo_last_output(&mut self) {
+        let Some(last) = self.last_output.take() else {
+            tracing::info!("Nothing to undo");
+            return;
+        };
+
+        match last.undo().await {
+            Ok(()) => {
+                tracing::info!("Undid last output via {}", last.method());
+                self.text_processor.reset_continuation();
+                if self.config.output.notification.on_transcription {
+                    send_notification(
+                        "Undone",
+                        "Last transcription removed",
+                        &self.config.output.notification,
+                        Some(self.config.engine),
+                    )
+                    .await;
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Undo failed: {}", e);
+                self.play_feedback(SoundEvent::Error);
+            }
+        }
+    }
+
+    /// Whether LLM cleanup, translation or a post-processing command
+    /// applies to recordings with this profile
+    ///
+    /// Their output can't be compared with a second transcription, so such
+    /// dictations aren't refined.
+    fn has_external_stages(&self, active_profile: Option<&crate::config::Profile>) -> bool {
+        let llm_cleanup = active_profile
+            .and_then(|p| p.llm_cleanup)
+            .unwrap_or(self.config.output.llm_cleanup.enabled);
+        let translation = output::translate::target_language(
+            &self.config.output.translation,
+            active_profile.and_then(|p| p.translate_to.as_deref()),
+        )
+        .is_some();
+        let command = active_profile.is_some_and(|p| p.post_process_command.is_some())
+            || self.post_processor.is_some();
+        llm_cleanup || translation || command
+    }
+
+    /// Turn a transcription into the text to output
+    ///
+    /// Spelled utterances and snippet triggers are output as is. Everything
+    /// else goes through text processing, LLM cleanup, translation,
+    /// post-processing and the profanity filter, with the recording's profile.
+    /// A refined transcription is cased like its draft, without touching the
+    /// continuation state, and skips LLM cleanup, translation and
+    /// post-processing commands.
+    async fn process_transcription(
+        &self,
+        text: &str,
+        overrides: &RecordingOverrides,
+        pass: Pass,
+    ) -> String {
+        let profile_override = overrides.profile.as_deref();
+        let active_profile = profile_override.and_then(|name| self.config.get_profile(name));
+
+        let spelled = if overrides.spell {
+            Some(spell::spell(text))
+        } else {
+            self.config
+                .text
+                .spell_prefix
+                .as_deref()
+                .and_then(|prefix| spell::strip_prefix(text, prefix))
+                .map(spell::spell)
+        };
+        let verbatim = if let Some(spelled) = spelled {
+            tracing::info!("Spelled: {:?}", spelled);
+            Some(spelled)
+        } else {
+            self.snippets.expand(text, profile_override).map(|snippet| {
+                tracing::info!("Expanded snippet: {:?}", snippet);
+                snippet.to_string()
+            })
+        };
+        if let Some(verbatim) = verbatim {
+            verbatim
+        } else {
+            // Apply text processing (numbers, replacements, punctuation)
+            let processed_text = match pass {
+                Pass::Draft => self
+                    .text_processor
+                    .process_with_profile(text, active_profile),
+                Pass::Refined { sentence_ended } => {
+                    self.text_processor
+                        .process_with_state(text, active_profile, sentence_ended)
+                }
+            };
+            if processed_text != text {
+                tracing::debug!("After text processing: {:?}", processed_text);
+            }
+
+            // Refinements only run without these, see `has_external_stages`
+            let final_text = match pass {
+                Pass::Draft => {
+                    self.run_external_stages(processed_text, profile_override, active_profile)
+                        .await
+                }
+                Pass::Refined { .. } => processed_text,
+            };
+
+            // Regex rules, looking up the focused app only if a rule needs it
+            let final_text = if self.postprocess_rules.is_empty() {
+                final_text
+            } else {
+                let app = if self.postprocess_rules.needs_app() {
+                    output::active_window::focused_app().await
+                } else {
+                    None
+                };
+                let result =
+                    self.postprocess_rules
+                        .apply(&final_text, profile_override, app.as_deref());
+                if result != final_text {
+                    tracing::debug!("After postprocess rules: {:?}", result);
+                }
+                result
+            };
+
+            // Profanity filter runs last so post-processing can't reintroduce words
+            self.text_processor.filter_profanity(&final_text)
+        }
+    }
+
+    /// LLM cleanup, translation and the post-processing command
+    async fn run_external_stages(
         &self,
+        processed_text: String,
+        profile_override: Option<&str>,
+        active_profile: Option<&crate::config::Profile>,
+    ) -> String {
+        // LLM cleanup (profile can turn it on or off for this recording)
+        let llm_cleanup_enabled = active_profile
+            .and_then(|p| p.llm_cleanup)
+            .unwrap_or(self.config.output.llm_cleanup.enabled);
+        let processed_text = if llm_cleanup_enabled {
+            self.llm_cleanup.process(&processed_text).await
+        } else {
+            processed_text
+        };
+
+        // Translation (profile translate_to overrides the configured language)
+        let translation = &self.config.output.translation;
+        let processed_text = match output::translate::target_language(
+            translation,
+            active_profile.and_then(|p| p.translate_to.as_deref()),
+        ) {
+            Some(language) => {
+                let translator = output::translate::Translator::new(
+                    translation,
+                    &self.config.output.llm_cleanup,
+                    language,
+                );
+                let result = translator.process(&processed_text).await;
+                tracing::info!("Translated to {}: {:?}", language, result);
+                result
+            }
+            None => processed_text,
+        };
+
+        // Apply post-processing command (profile overrides default)
+        if let Some(profile) = active_profile {
+            if let Some(ref cmd) = profile.post_process_command {
+                let timeout_ms = profile.post_process_timeout_ms.unwrap_or(30000);
+                let profile_config = crate::config::PostProcessConfig {
+                    command: cmd.clone(),
+                    timeout_ms,
+                };
+                let profile_processor = PostProcessor::new(&profile_config);
+                tracing::info!(
+                    "Post-processing with profile: {:?}",
+                    profile_override.unwrap()
+                );
+                let result = profile_processor.process(&processed_text).await;
+                tracing::info!("Post-processed: {:?}", result);
+                result
+            } else {
+                // Profile exists but has no post_process_command, use default
+                if let Some(ref post_processor) = self.post_processor {
+                    tracing::info!("Post-processing: {:?}", processed_text);
+                    let result = post_processor.process(&processed_text).await;
+                    tracing::info!("Post-processed: {:?}", result);
+                    result
+                } else {
+                    processed_text
+                }
+            }
+        } else if let Some(ref post_processor) = self.post_processor {
+            tracing::info!("Post-processing: {:?}", processed_text);
+            let result = post_processor.process(&processed_text).await;
+            tracing::info!("Post-processed: {:?}", result);
+            result
+        } else {
+            processed_text
+        }
+    }
+
+    /// Handle transcription completion (called when a queued transcription completes)
+    ///
+    /// A new recording may be in progress; `state` is left alone then. Only
+    /// dictations that reach output get a performance report.
+    async fn handle_transcription_result(
+        &mut self,
         state: &mut State,
         result: std::result::Result<TranscriptionResult, tokio::task::JoinError>,
+        mut perf: Option<PerfTimer>,
+        mut overrides: RecordingOverrides,
+        refine_audio: Option<Vec<f32>>,
     ) {
         match result {
             Ok(Ok(text)) => {
+                if let Some(ref mut perf) = perf {
+                    perf.transcribed();
+                }
+
+                let filtered = self.text_processor.filter_hallucinations(&text);
+                if filtered != text {
+                    tracing::info!("Filtered hallucinations: {:?} -> {:?}", text, filtered);
+                }
+                let text = filtered;
+
                 if text.is_empty() {
                     tracing::debug!("Transcription was empty");
-                    self.reset_to_idle(state).await;
+                    self.finish_without_output(state).await;
+                } else if overrides.continuous && self.handle_voice_command(&text).await {
+                    self.finish_without_output(state).await;
                 } else {
                     tracing::info!("Transcribed: {:?}", text);
 
-                    // Apply text processing (replacements, punctuation)
-                    let processed_text = self.text_processor.process(&text);
-                    if processed_text != text {
-                        tracing::debug!("After text processing: {:?}", processed_text);
-                    }
-
                     // Check for profile override from CLI flags
-                    let profile_override = read_profile_override();
-                    let active_profile = profile_override
-                        .as_ref()
-                        .and_then(|name| self.config.get_profile(name));
+                    let profile_override = overrides.profile.as_deref();
+                    let active_profile =
+                        profile_override.and_then(|name| self.config.get_profile(name));
 
                     if let Some(profile_name) = &profile_override {
                         if active_profile.is_none() {
@@ -1256,44 +2352,52 @@ impl Daemon {
                         }
                     }
 
-                    // Apply post-processing command (profile overrides default)
-                    let final_text = if let Some(profile) = active_profile {
-                        if let Some(ref cmd) = profile.post_process_command {
-                            let timeout_ms = profile.post_process_timeout_ms.unwrap_or(30000);
-                            let profile_config = crate::config::PostProcessConfig {
-                                command: cmd.clone(),
-                                timeout_ms,
+                    // Before processing the draft updates it, for a refinement
+                    let sentence_ended = self.text_processor.sentence_ended();
+                    let final_text = self
+                        .process_transcription(&text, &overrides, Pass::Draft)
+                        .await;
+
+                    // The script's on_transcript hook has the last word
+                    let final_text = match self.hooks {
+                        Some(ref hooks) => {
+                            let engine = format!("{:?}", self.config.engine).to_lowercase();
+                            let context = TranscriptContext {
+                                raw: &text,
+                                app: overrides.app.as_deref(),
+                                profile: profile_override,
+                                engine: &engine,
+                                model: self.config.model_name(),
                             };
-                            let profile_processor = PostProcessor::new(&profile_config);
-                            tracing::info!(
-                                "Post-processing with profile: {:?}",
-                                profile_override.as_ref().unwrap()
-                            );
-                            let result = profile_processor.process(&processed_text).await;
-                            tracing::info!("Post-processed: {:?}", result);
-                            result
-                        } else {
-                            // Profile exists but has no post_process_command, use default
-                            if let Some(ref post_processor) = self.post_processor {
-                                tracing::info!("Post-processing: {:?}", processed_text);
-                                let result = post_processor.process(&processed_text).await;
-                                tracing::info!("Post-processed: {:?}", result);
-                                result
-                            } else {
-                                processed_text
+                            let outcome = hooks.on_transcript(&final_text, &context);
+                            if let Some(mode) = outcome
+                                .output
+                                .as_deref()
+                                .and_then(parse_output_mode_override)
+                            {
+                                overrides.output_mode = Some(mode);
+                            }
+                            if outcome.auto_submit.is_some() {
+                                overrides.auto_submit = outcome.auto_submit;
                             }
+                            if outcome.text != final_text {
+                                tracing::debug!("After on_transcript: {:?}", outcome.text);
+                            }
+                            outcome.text
                         }
-                    } else if let Some(ref post_processor) = self.post_processor {
-                        tracing::info!("Post-processing: {:?}", processed_text);
-                        let result = post_processor.process(&processed_text).await;
-                        tracing::info!("Post-processed: {:?}", result);
-                        result
-                    } else {
-                        processed_text
+                        None => final_text,
                     };
+                    if final_text.is_empty() {
+                        tracing::info!("Nothing to output after on_transcript");
+                        self.finish_without_output(state).await;
+                        return;
+                    }
+                    if let Some(ref mut perf) = perf {
+                        perf.processed();
+                    }
 
                     // Check for output mode override from CLI flags
-                    let output_override = read_output_mode_override();
+                    let output_override = overrides.output_mode;
 
                     // Check if profile specifies output mode override
                     let profile_output_mode = active_profile.and_then(|p| p.output_mode.clone());
@@ -1321,14 +2425,16 @@ impl Daemon {
                     };
 
                     if let Some(output_path) = file_output_path {
-                        *state = State::Outputting {
-                            text: final_text.clone(),
-                        };
+                        if !state.is_recording() {
+                            *state = State::Outputting {
+                                text: final_text.clone(),
+                            };
+                        }
+                        // Text written to a file can't be undone from the daemon
+                        self.last_output = None;
 
                         let file_mode = &self.config.output.file_mode;
-                        match write_transcription_to_file(&output_path, &final_text, file_mode)
-                            .await
-                        {
+                        match output::file::write_text(&output_path, &final_text, file_mode).await {
                             Ok(()) => {
                                 let mode_str = match file_mode {
                                     FileMode::Overwrite => "wrote",
@@ -1345,14 +2451,23 @@ impl Daemon {
                             }
                         }
 
-                        *state = State::Idle;
-                        self.update_state("idle");
+                        // Extra sinks still apply; a file sink would duplicate this write
+                        let mut also_config = self.config.output.clone();
+                        also_config.also.retain(|sink| *sink != OutputSink::File);
+                        let also_sinks = output::create_also_sinks(&also_config);
+                        output::output_to_also_sinks(&also_sinks, &final_text).await;
+
+                        if let Some(perf) = perf {
+                            self.finish_perf_report(perf.output(Some("file"))).await;
+                        }
+
+                        self.settle_state(state);
                         return;
                     }
 
                     // Check for per-recording boolean overrides from CLI flags
-                    let auto_submit_override = read_bool_override("auto_submit");
-                    let shift_enter_override = read_bool_override("shift_enter");
+                    let auto_submit_override = overrides.auto_submit;
+                    let shift_enter_override = overrides.shift_enter;
 
                     // Create output chain with potential mode override (for non-file modes)
                     // Priority: 1. CLI override, 2. profile output_mode, 3. config default
@@ -1381,40 +2496,120 @@ impl Daemon {
                         output_config.shift_enter_newlines = shift_enter;
                     }
 
-                    let output_chain = output::create_output_chain(&output_config);
+                    let mut output_chain = output::create_output_chain(&output_config);
+                    output::deprioritize_unavailable(&mut output_chain, &self.unavailable_outputs);
+
+                    // Output the text
+                    if !state.is_recording() {
+                        *state = State::Outputting {
+                            text: final_text.clone(),
+                        };
+                    }
+
+                    let output_options = output::OutputOptions {
+                        pre_output_command: output_config.pre_output_command.as_deref(),
+                        post_output_command: output_config.post_output_command.as_deref(),
+                    };
+
+                    let delivered_by = match output::output_with_fallback(
+                        &output_chain,
+                        &final_text,
+                        output_options,
+                    )
+                    .await
+                    {
+                        Ok(index) => {
+                            // A backend that failed its probe may have come back
+                            let name = output_chain[index].name();
+                            self.unavailable_outputs.retain(|n| *n != name);
+
+                            // Remember which method delivered the text so it can be undone
+                            self.last_output = Some(output::LastOutput::new(
+                                output_chain.swap_remove(index),
+                                &final_text,
+                                &output_config,
+                            ));
+
+                            // Notify mode already showed the text in a notification
+                            if self.config.output.notification.on_transcription
+                                && output_config.mode != OutputMode::Notify
+                            {
+                                // Send notification on successful output
+                                output::send_transcription_notification(
+                                    &final_text,
+                                    &self.config.output.notification,
+                                    self.config.engine,
+                                )
+                                .await;
+                            }
+                            Some(name)
+                        }
+                        Err(e) => {
+                            tracing::error!("Output failed: {}", e);
+                            self.notify_error("Output Failed", &e.to_string()).await;
+                            // Re-probe so the next attempt starts with whatever works now
+                            self.probe_outputs(&output_chain).await;
+                            None
+                        }
+                    };
+
+                    #[cfg(target_os = "linux")]
+                    if let Some(ref overlay) = self.overlay {
+                        overlay.show_caption(&final_text);
+                    }
 
-                    // Output the text
-                    *state = State::Outputting {
-                        text: final_text.clone(),
-                    };
+                    // Sinks in `also` get the text whatever the primary chain did
+                    let also_sinks = output::create_also_sinks(&output_config);
+                    output::output_to_also_sinks(&also_sinks, &final_text).await;
 
-                    let output_options = output::OutputOptions {
-                        pre_output_command: output_config.pre_output_command.as_deref(),
-                        post_output_command: output_config.post_output_command.as_deref(),
-                    };
+                    if let Some(perf) = perf {
+                        self.finish_perf_report(perf.output(delivered_by)).await;
+                    }
 
-                    if let Err(e) =
-                        output::output_with_fallback(&output_chain, &final_text, output_options)
-                            .await
-                    {
-                        tracing::error!("Output failed: {}", e);
-                    } else if self.config.output.notification.on_transcription {
-                        // Send notification on successful output
-                        output::send_transcription_notification(
-                            &final_text,
-                            self.config.output.notification.show_engine_icon,
-                            self.config.engine,
-                        )
-                        .await;
+                    // Two-pass transcription: the typed text was the draft
+                    if let (Some(samples), Some(_)) = (refine_audio, delivered_by) {
+                        if self.has_external_stages(active_profile) {
+                            tracing::debug!(
+                                "Not refining: LLM cleanup, translation or post-processing changed the draft"
+                            );
+                        } else {
+                            let overrides = RecordingOverrides {
+                                profile: profile_override.map(String::from),
+                                app: overrides.app,
+                                prompt_context: overrides.prompt_context,
+                                spell: overrides.spell,
+                                grammar: overrides.grammar,
+                                ..Default::default()
+                            };
+                            self.start_refinement(samples, final_text, overrides, sentence_ended);
+                        }
                     }
 
-                    *state = State::Idle;
-                    self.update_state("idle");
+                    self.settle_state(state);
                 }
             }
+            Ok(Err(crate::error::TranscribeError::LowConfidence(confidence))) => {
+                tracing::info!(
+                    "Nothing typed: confidence {:.2} below whisper.min_confidence",
+                    confidence
+                );
+                self.play_feedback(SoundEvent::Cancelled);
+                if self.config.output.notification.on_transcription {
+                    send_notification(
+                        "Didn't catch that",
+                        "Low confidence, nothing was typed",
+                        &self.config.output.notification,
+                        Some(self.config.engine),
+                    )
+                    .await;
+                }
+                self.finish_without_output(state).await;
+            }
             Ok(Err(e)) => {
                 tracing::error!("Transcription failed: {}", e);
-                self.reset_to_idle(state).await;
+                self.notify_error("Transcription Failed", &e.to_string())
+                    .await;
+                self.finish_without_output(state).await;
             }
             Err(e) => {
                 // JoinError - task was cancelled or panicked
@@ -1423,17 +2618,195 @@ impl Daemon {
                 } else {
                     tracing::error!("Transcription task panicked: {}", e);
                 }
-                self.reset_to_idle(state).await;
+                self.finish_without_output(state).await;
+            }
+        }
+    }
+
+    /// Transcribe a dictation again with `[whisper] refine_model`, in the
+    /// background; `draft` is the text that was output for it, after a text
+    /// that left `sentence_ended`
+    fn start_refinement(
+        &mut self,
+        samples: Vec<f32>,
+        draft: String,
+        overrides: RecordingOverrides,
+        sentence_ended: bool,
+    ) {
+        let Some(model) = self.config.whisper.refine_model.clone() else {
+            return;
+        };
+        let Some(ref mut mm) = self.model_manager else {
+            return;
+        };
+        let transcriber = match mm.get_transcriber(Some(&model)) {
+            Ok(transcriber) => transcriber,
+            Err(e) => {
+                tracing::warn!("Refine model '{}' unavailable: {}", model, e);
+                return;
+            }
+        };
+
+        // Only the latest dictation is refined
+        if let Some(previous) = self.refinement.take() {
+            tracing::debug!("Dropping the refinement of an earlier dictation");
+            previous.task.abort();
+            previous.transcriber.cancel();
+        }
+
+        tracing::info!("Refining the transcription with model '{}'", model);
+        let t = transcriber.clone();
+        let context = overrides.prompt_context.clone();
+        let grammar = overrides.grammar.clone();
+        let task = tokio::task::spawn_blocking(move || match (grammar, context) {
+            (Some(grammar), _) => t.transcribe_with_grammar(&samples, &grammar),
+            (None, Some(context)) => t.transcribe_with_context(&samples, None, &context),
+            (None, None) => t.transcribe(&samples),
+        });
+        self.refinement = Some(Refinement {
+            task,
+            transcriber,
+            draft,
+            overrides,
+            sentence_ended,
+        });
+    }
+
+    /// Deliver a refined transcription that differs from the draft
+    ///
+    /// With `refine_output = "replace"` the draft is erased and the refined
+    /// text typed instead, but only while the draft is still the last
+    /// output. Otherwise the refined text goes on the clipboard.
+    async fn finish_refinement(
+        &mut self,
+        refinement: Refinement,
+        result: std::result::Result<TranscriptionResult, tokio::task::JoinError>,
+    ) {
+        let text = match result {
+            Ok(Ok(text)) => self.text_processor.filter_hallucinations(&text),
+            Ok(Err(e)) => {
+                tracing::warn!("Refinement failed: {}", e);
+                return;
+            }
+            Err(e) => {
+                if !e.is_cancelled() {
+                    tracing::error!("Refinement task panicked: {}", e);
+                }
+                return;
+            }
+        };
+        if text.is_empty() {
+            return;
+        }
+        let pass = Pass::Refined {
+            sentence_ended: refinement.sentence_ended,
+        };
+        let refined = self
+            .process_transcription(&text, &refinement.overrides, pass)
+            .await;
+        // The hook sees the refined text like the draft; its output mode
+        // applies to the draft only
+        let refined = match self.hooks {
+            Some(ref hooks) => {
+                let model = self
+                    .config
+                    .whisper
+                    .refine_model
+                    .as_deref()
+                    .unwrap_or_default();
+                let context = TranscriptContext {
+                    raw: &text,
+                    app: refinement.overrides.app.as_deref(),
+                    profile: refinement.overrides.profile.as_deref(),
+                    engine: "whisper",
+                    model,
+                };
+                hooks.on_transcript(&refined, &context).text
+            }
+            None => refined,
+        };
+        if refined.is_empty() {
+            return;
+        }
+        if refined == refinement.draft {
+            tracing::debug!("Refined transcription matches the draft");
+            return;
+        }
+        tracing::info!("Refined: {:?} -> {:?}", refinement.draft, refined);
+
+        if self.config.whisper.refine_output == RefineOutput::Replace {
+            match self
+                .last_output
+                .as_mut()
+                .filter(|last| last.delivered(&refinement.draft))
+            {
+                Some(last) => match last.replace(&refined).await {
+                    Ok(()) => {
+                        tracing::info!("Replaced the draft via {}", last.method());
+                        return;
+                    }
+                    Err(e) => tracing::warn!("Could not replace the draft: {}", e),
+                },
+                None => tracing::info!("Something was output after the draft, not replacing it"),
             }
         }
+
+        let mut config = self.config.output.clone();
+        config.mode = OutputMode::Clipboard;
+        let chain = output::create_output_chain(&config);
+        let options = output::OutputOptions {
+            pre_output_command: None,
+            post_output_command: None,
+        };
+        match output::output_with_fallback(&chain, &refined, options).await {
+            Ok(_) => tracing::info!("Copied the refined transcription to the clipboard"),
+            Err(e) => tracing::warn!("Could not copy the refined transcription: {}", e),
+        }
+    }
+
+    /// Log and save the performance report of a finished dictation
+    async fn finish_perf_report(&self, report: PerfReport) {
+        tracing::info!("Performance: {}", report.summary());
+        if let Err(e) = report.write() {
+            tracing::warn!("Failed to write performance report: {}", e);
+        }
+        self.update_info(|info| info.record_dictation(&report, chrono::Local::now()));
+        if self.config.output.notification.on_performance_report {
+            send_notification(
+                "Performance",
+                &report.summary().replace(" | ", "\n"),
+                &self.config.output.notification,
+                Some(self.config.engine),
+            )
+            .await;
+        }
+    }
+
+    /// Probe an output chain, log the results and remember the dead methods
+    async fn probe_outputs(&mut self, chain: &[Box<dyn output::TextOutput>]) {
+        let results = output::probe_chain(chain).await;
+        tracing::info!("Output backends: {}", output::format_probe(&results));
+        self.unavailable_outputs = results
+            .iter()
+            .filter(|(_, ok)| !ok)
+            .map(|(name, _)| *name)
+            .collect();
     }
 
     /// Run the daemon main loop
     pub async fn run(&mut self) -> Result<()> {
         tracing::info!("Starting voxtype daemon");
 
-        // Clean up any stale cancel file from previous runs
+        // Single-instance safeguard, taken before touching any runtime files
+        // a running daemon may still be using
+        let lock_path = Config::runtime_dir().join("voxtype.lock");
+        let mut pidlock = acquire_lock(&lock_path, self.replace).await?;
+        tracing::debug!("Acquired PID lock at {:?}", lock_path);
+
+        // Clean up any stale cancel/undo/model switch files from previous runs
         cleanup_cancel_file();
+        let _ = check_undo_requested();
+        let _ = check_model_switch_requested();
 
         // Clean up any stale meeting command files
         cleanup_meeting_files();
@@ -1460,26 +2833,6 @@ impl Daemon {
             crate::error::VoxtypeError::Config(format!("Failed to create directories: {}", e))
         })?;
 
-        // Check if another instance is already running (single-instance safeguard)
-        let lock_path = Config::runtime_dir().join("voxtype.lock");
-        let lock_path_str = lock_path.to_string_lossy().to_string();
-        let mut pidlock = Pidlock::new(&lock_path_str);
-
-        match pidlock.acquire() {
-            Ok(_) => {
-                tracing::debug!("Acquired PID lock at {:?}", lock_path);
-            }
-            Err(e) => {
-                tracing::error!(
-                    "Failed to acquire lock: another voxtype instance is already running"
-                );
-                return Err(crate::error::VoxtypeError::Config(format!(
-                    "Another voxtype instance is already running (lock error: {:?})",
-                    e
-                )));
-            }
-        }
-
         tracing::info!("Output mode: {:?}", self.config.output.mode);
 
         // Log state file if configured
@@ -1502,20 +2855,43 @@ impl Daemon {
             None
         };
 
-        // Log default output chain (chain is created dynamically per-transcription to support overrides)
-        let default_chain = output::create_output_chain(&self.config.output);
-        tracing::debug!(
-            "Default output chain: {}",
-            default_chain
+        // Probe the default output chain so dead backends are reported now rather
+        // than at the first dictation (chain is created per-transcription to support overrides)
+        if self.config.output.mode != OutputMode::File {
+            let default_chain = output::create_output_chain(&self.config.output);
+            self.probe_outputs(&default_chain).await;
+            let first_usable = default_chain
                 .iter()
                 .map(|o| o.name())
-                .collect::<Vec<_>>()
-                .join(" -> ")
-        );
-        drop(default_chain); // Not used; chain is created per-transcription
+                .find(|name| !self.unavailable_outputs.contains(name));
+            self.update_info(|info| info.output_method = first_usable.map(String::from));
+            if first_usable.is_none() {
+                tracing::warn!("No output method is usable; run 'voxtype setup check' for details");
+                send_notification(
+                    "No output method available",
+                    "Run 'voxtype setup check' for details",
+                    &self.config.output.notification,
+                    Some(self.config.engine),
+                )
+                .await;
+            }
+        } else {
+            self.update_info(|info| info.output_method = Some("file".to_string()));
+        }
+
+        // Start with the settings for the current power source
+        self.follow_power_source();
 
         // Initialize model manager for multi-model support (Whisper only)
         let mut model_manager = ModelManager::new(&self.config.whisper, self.config_path.clone());
+        self.fallbacks = Fallbacks::new(&self.config)?;
+        self.hooks = Hooks::load(&self.config).map_err(crate::error::VoxtypeError::Config)?;
+        if !self.config.fallback.transcribers.is_empty() {
+            tracing::info!(
+                "{} fallback transcriber(s) configured",
+                self.config.fallback.transcribers.len()
+            );
+        }
 
         // Pre-load transcription model if on_demand_loading is disabled
         let mut transcriber_preloaded: Option<Arc<dyn Transcriber>> = None;
@@ -1534,11 +2910,11 @@ impl Daemon {
                 | crate::config::TranscriptionEngine::SenseVoice
                 | crate::config::TranscriptionEngine::Paraformer
                 | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
+                | crate::config::TranscriptionEngine::Omnilingual
+                | crate::config::TranscriptionEngine::Onnx => {
                     // Parakeet/Moonshine uses its own model loading
-                    transcriber_preloaded = Some(Arc::from(crate::transcribe::create_transcriber(
-                        &self.config,
-                    )?));
+                    transcriber_preloaded =
+                        Some(crate::transcribe::create_transcriber(&self.config)?);
                 }
             }
             tracing::info!("Model loaded, ready for voice input");
@@ -1563,15 +2939,76 @@ impl Daemon {
             None
         };
 
+        // Start the wake-word detector (if enabled)
+        let mut wake_rx = if self.config.wake_word.enabled {
+            match wake::listen(&self.config).await {
+                Ok(rx) => {
+                    tracing::info!("Listening for wake word: {}", self.config.wake_word.model);
+                    Some(rx)
+                }
+                Err(e) => {
+                    tracing::warn!("Wake word disabled: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Start continuous mode (if enabled)
+        let mut utterance_rx = if !self.config.continuous.enabled {
+            None
+        } else if self.config.on_demand_loading() {
+            tracing::warn!("Continuous mode disabled: it needs on_demand_loading = false");
+            None
+        } else {
+            match continuous::listen(&self.config).await {
+                Ok(rx) => {
+                    tracing::info!(
+                        "Continuous mode: listening, say {:?} to pause",
+                        self.config.continuous.sleep_phrase
+                    );
+                    self.listening = true;
+                    Some(rx)
+                }
+                Err(e) => {
+                    tracing::warn!("Continuous mode disabled: {}", e);
+                    None
+                }
+            }
+        };
+
+        // Start buffering audio for the pre-roll (if enabled)
+        if self.config.audio.pre_roll_ms > 0 {
+            match PreRoll::start(&self.config.audio).await {
+                Ok(pre_roll) => {
+                    tracing::info!(
+                        "Pre-roll: keeping the last {}ms of audio in memory",
+                        self.config
+                            .audio
+                            .pre_roll_ms
+                            .min(audio::pre_roll::MAX_PRE_ROLL_MS)
+                    );
+                    self.pre_roll = Some(pre_roll);
+                }
+                Err(e) => tracing::warn!("Pre-roll disabled: {}", e),
+            }
+        }
+
         // Current state
         let mut state = State::Idle;
 
         // Audio capture (created fresh for each recording)
         let mut audio_capture: Option<Box<dyn AudioCapture>> = None;
+        // Chunks of the recording in progress, as they're captured
+        let mut audio_chunks: Option<tokio::sync::mpsc::Receiver<Vec<f32>>> = None;
 
         // Recording timeout
         let max_duration = Duration::from_secs(self.config.audio.max_duration_secs as u64);
 
+        // When a push-to-talk recording stops, once the release tail is over
+        let mut release_at: Option<tokio::time::Instant> = None;
+
         let activation_mode = self.config.hotkey.mode;
         if self.config.hotkey.enabled {
             let mode_desc = match activation_mode {
@@ -1590,25 +3027,65 @@ impl Daemon {
 
         // Main event loop
         loop {
+            let release_deadline = release_at;
             tokio::select! {
-                // Handle hotkey events (only if hotkey listener is enabled)
-                Some(hotkey_event) = async {
-                    match &mut hotkey_rx {
-                        Some(rx) => rx.recv().await,
-                        None => std::future::pending().await,
+                // Handle hotkey events (only if hotkey listener is enabled),
+                // `voxtype record start/stop` (SIGUSR1/SIGUSR2) and the wake
+                // word, which act like pressing and releasing a push-to-talk
+                // key whatever the mode
+                Some((hotkey_event, event_mode, woken)) = async {
+                    tokio::select! {
+                        Some(event) = async {
+                            match &mut hotkey_rx {
+                                Some(rx) => rx.recv().await,
+                                None => std::future::pending().await,
+                            }
+                        } => Some((event, activation_mode, false)),
+                        _ = sigusr1.recv() => {
+                            tracing::debug!("Received SIGUSR1 (start recording)");
+                            // Set by `voxtype record start --model X`
+                            let model_override = read_model_override();
+                            Some((HotkeyEvent::Pressed { model_override, spell: false }, ActivationMode::PushToTalk, false))
+                        }
+                        _ = sigusr2.recv() => {
+                            tracing::debug!("Received SIGUSR2 (stop recording)");
+                            Some((HotkeyEvent::Released, ActivationMode::PushToTalk, false))
+                        }
+                        Some(()) = async {
+                            match &mut wake_rx {
+                                Some(rx) => rx.recv().await,
+                                None => std::future::pending().await,
+                            }
+                        } => {
+                            tracing::info!("Wake word detected");
+                            // A pause ends the recording, see `silence_stop`
+                            Some((HotkeyEvent::Pressed { model_override: None, spell: false }, ActivationMode::PushToTalk, true))
+                        }
+                        // The release tail is over: stop as if released now
+                        _ = async {
+                            match release_deadline {
+                                Some(at) => tokio::time::sleep_until(at).await,
+                                None => std::future::pending().await,
+                            }
+                        } => Some((HotkeyEvent::Released, ActivationMode::PushToTalk, false)),
                     }
                 } => {
-                    match (hotkey_event, activation_mode) {
+                    match (hotkey_event, event_mode) {
                         // === PUSH-TO-TALK MODE ===
-                        (HotkeyEvent::Pressed { model_override }, ActivationMode::PushToTalk) => {
-                            tracing::debug!("Received HotkeyEvent::Pressed (push-to-talk), state.is_idle() = {}, model_override = {:?}",
-                                state.is_idle(), model_override);
-                            if state.is_idle() {
+                        (HotkeyEvent::Pressed { model_override, spell }, ActivationMode::PushToTalk) => {
+                            tracing::debug!("Received HotkeyEvent::Pressed (push-to-talk), state = {}, model_override = {:?}",
+                                state, model_override);
+                            if release_at.take().is_some() && state.is_recording() {
+                                tracing::debug!("Pressed again during the release tail, still recording");
+                                continue;
+                            }
+                            if state.can_start_recording() {
                                 tracing::info!("Recording started");
+                                self.follow_power_source();
 
                                 // Send notification if enabled
                                 if self.config.output.notification.on_recording_start {
-                                    send_notification("Push to Talk Active", "Recording...", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                                    send_notification("Push to Talk Active", "Recording...", &self.config.output.notification, Some(self.config.engine)).await;
                                 }
 
                                 // Prepare model for transcription
@@ -1629,10 +3106,11 @@ impl Daemon {
                                         | crate::config::TranscriptionEngine::SenseVoice
                 | crate::config::TranscriptionEngine::Paraformer
                 | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
+                | crate::config::TranscriptionEngine::Omnilingual
+                | crate::config::TranscriptionEngine::Onnx => {
                                             let config = self.config.clone();
                                             self.model_load_task = Some(tokio::task::spawn_blocking(move || {
-                                                crate::transcribe::create_transcriber(&config).map(Arc::from)
+                                                crate::transcribe::create_transcriber(&config)
                                             }));
                                         }
                                     }
@@ -1652,7 +3130,8 @@ impl Daemon {
                                         | crate::config::TranscriptionEngine::SenseVoice
                 | crate::config::TranscriptionEngine::Paraformer
                 | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
+                | crate::config::TranscriptionEngine::Omnilingual
+                | crate::config::TranscriptionEngine::Onnx => {
                                             if let Some(ref t) = transcriber_preloaded {
                                                 let transcriber = t.clone();
                                                 tokio::task::spawn_blocking(move || {
@@ -1668,12 +3147,16 @@ impl Daemon {
                                 match audio::create_capture(&self.config.audio) {
                                     Ok(mut capture) => {
                                         tracing::debug!("Audio capture created, starting...");
-                                        if let Err(e) = capture.start().await {
-                                            tracing::error!("Failed to start audio: {}", e);
-                                            continue;
+                                        match capture.start().await {
+                                            Ok(chunks) => audio_chunks = Some(chunks),
+                                            Err(e) => {
+                                                tracing::error!("Failed to start audio: {}", e);
+                                                self.notify_error("Recording Failed", &e.to_string()).await;
+                                                continue;
+                                            }
                                         }
                                         tracing::debug!("Audio capture started successfully");
-                                        audio_capture = Some(capture);
+                                        audio_capture = Some(self.with_pre_roll(capture, woken));
 
                                         // Use EagerRecording state if eager_processing is enabled
                                         if self.config.whisper.eager_processing {
@@ -1695,6 +3178,13 @@ impl Daemon {
                                         self.update_state("recording");
                                         self.play_feedback(SoundEvent::RecordingStart);
 
+                                        // Per-app profile and prompt, for where the text will go
+                                        self.recording_app = self.focused_app_for_recording().await;
+                                        self.recording_spell = spell;
+                                        self.silence_stop = woken.then(|| {
+                                            SilenceStop::new(self.config.wake_word.silence_secs, self.config.audio.sample_rate)
+                                        });
+
                                         // Run pre-recording hook (e.g., enter compositor submap for cancel)
                                         if let Some(cmd) = &self.config.output.pre_recording_command {
                                             if let Err(e) = output::run_hook(cmd, "pre_recording").await {
@@ -1704,6 +3194,7 @@ impl Daemon {
                                     }
                                     Err(e) => {
                                         tracing::error!("Failed to create audio capture: {}", e);
+                                        self.notify_error("Recording Failed", &e.to_string()).await;
                                         self.play_feedback(SoundEvent::Error);
                                     }
                                 }
@@ -1712,15 +3203,24 @@ impl Daemon {
 
                         (HotkeyEvent::Released, ActivationMode::PushToTalk) => {
                             tracing::debug!("Received HotkeyEvent::Released (push-to-talk), state.is_recording() = {}", state.is_recording());
-                            if let State::Recording { model_override, .. } = &state {
+                            // Keep recording the end of a word said while releasing the
+                            // key; this event comes again when the tail is over
+                            let tail_over = release_at.take().is_some();
+                            let release_tail = self.config.audio.release_tail_ms.min(MAX_RELEASE_TAIL_MS);
+                            if !tail_over && state.is_recording() && release_tail > 0 {
+                                release_at = Some(tokio::time::Instant::now() + Duration::from_millis(release_tail as u64));
+                                continue;
+                            }
+                            if matches!(state, State::Recording { .. }) {
+                                let model = self.model_at_release(&state);
                                 let transcriber = match self.get_transcriber_for_recording(
-                                    model_override.as_deref(),
+                                    model.as_deref(),
                                     &transcriber_preloaded,
                                 ).await {
                                     Ok(t) => Some(t),
                                     Err(()) => {
                                         state = State::Idle;
-                                        self.update_state("idle");
+                                        self.settle_state(&mut state);
                                         continue;
                                     }
                                 };
@@ -1743,7 +3243,7 @@ impl Daemon {
                                 self.play_feedback(SoundEvent::RecordingStop);
 
                                 if self.config.output.notification.on_recording_stop {
-                                    send_notification("Recording Stopped", "Transcribing...", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                                    send_notification("Recording Stopped", "Transcribing...", &self.config.output.notification, Some(self.config.engine)).await;
                                 }
 
                                 // Stop audio capture and get remaining samples
@@ -1763,17 +3263,17 @@ impl Daemon {
                                     Ok(t) => t,
                                     Err(()) => {
                                         state = State::Idle;
-                                        self.update_state("idle");
+                                        self.settle_state(&mut state);
                                         continue;
                                     }
                                 };
 
                                 self.update_state("transcribing");
 
-                                if let Some(text) = self.finish_eager_recording(&mut state, transcriber).await {
-                                    // Move to outputting state and handle via transcription result flow
+                                if let Some((text, perf)) = self.finish_eager_recording(&mut state, transcriber.clone()).await {
+                                    // Output through the queue, after any earlier recordings
+                                    self.queue_eager_result(text, perf, transcriber);
                                     state = State::Transcribing { audio: Vec::new() };
-                                    self.handle_transcription_result(&mut state, Ok(Ok(text))).await;
                                 } else {
                                     tracing::debug!("Eager recording produced empty result");
                                     self.reset_to_idle(&mut state).await;
@@ -1782,16 +3282,17 @@ impl Daemon {
                         }
 
                         // === TOGGLE MODE ===
-                        (HotkeyEvent::Pressed { model_override }, ActivationMode::Toggle) => {
-                            tracing::debug!("Received HotkeyEvent::Pressed (toggle), state.is_idle() = {}, state.is_recording() = {}, model_override = {:?}",
-                                state.is_idle(), state.is_recording(), model_override);
+                        (HotkeyEvent::Pressed { model_override, spell }, ActivationMode::Toggle) => {
+                            tracing::debug!("Received HotkeyEvent::Pressed (toggle), state = {}, model_override = {:?}",
+                                state, model_override);
 
-                            if state.is_idle() {
+                            if state.can_start_recording() {
                                 // Start recording
                                 tracing::info!("Recording started (toggle mode)");
+                                self.follow_power_source();
 
                                 if self.config.output.notification.on_recording_start {
-                                    send_notification("Recording Started", "Press hotkey again to stop", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                                    send_notification("Recording Started", "Press hotkey again to stop", &self.config.output.notification, Some(self.config.engine)).await;
                                 }
 
                                 // Prepare model for transcription
@@ -1812,10 +3313,11 @@ impl Daemon {
                                         | crate::config::TranscriptionEngine::SenseVoice
                 | crate::config::TranscriptionEngine::Paraformer
                 | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
+                | crate::config::TranscriptionEngine::Omnilingual
+                | crate::config::TranscriptionEngine::Onnx => {
                                             let config = self.config.clone();
                                             self.model_load_task = Some(tokio::task::spawn_blocking(move || {
-                                                crate::transcribe::create_transcriber(&config).map(Arc::from)
+                                                crate::transcribe::create_transcriber(&config)
                                             }));
                                         }
                                     }
@@ -1835,7 +3337,8 @@ impl Daemon {
                                         | crate::config::TranscriptionEngine::SenseVoice
                 | crate::config::TranscriptionEngine::Paraformer
                 | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
+                | crate::config::TranscriptionEngine::Omnilingual
+                | crate::config::TranscriptionEngine::Onnx => {
                                             if let Some(ref t) = transcriber_preloaded {
                                                 let transcriber = t.clone();
                                                 tokio::task::spawn_blocking(move || {
@@ -1848,12 +3351,16 @@ impl Daemon {
 
                                 match audio::create_capture(&self.config.audio) {
                                     Ok(mut capture) => {
-                                        if let Err(e) = capture.start().await {
-                                            tracing::error!("Failed to start audio: {}", e);
-                                            self.play_feedback(SoundEvent::Error);
-                                            continue;
+                                        match capture.start().await {
+                                            Ok(chunks) => audio_chunks = Some(chunks),
+                                            Err(e) => {
+                                                tracing::error!("Failed to start audio: {}", e);
+                                                self.notify_error("Recording Failed", &e.to_string()).await;
+                                                self.play_feedback(SoundEvent::Error);
+                                                continue;
+                                            }
                                         }
-                                        audio_capture = Some(capture);
+                                        audio_capture = Some(self.with_pre_roll(capture, false));
 
                                         // Use EagerRecording state if eager_processing is enabled
                                         if self.config.whisper.eager_processing {
@@ -1875,6 +3382,11 @@ impl Daemon {
                                         self.update_state("recording");
                                         self.play_feedback(SoundEvent::RecordingStart);
 
+                                        // Per-app profile and prompt, for where the text will go
+                                        self.recording_app = self.focused_app_for_recording().await;
+                                        self.recording_spell = spell;
+                                        self.silence_stop = None;
+
                                         // Run pre-recording hook (e.g., enter compositor submap for cancel)
                                         if let Some(cmd) = &self.config.output.pre_recording_command {
                                             if let Err(e) = output::run_hook(cmd, "pre_recording").await {
@@ -1884,18 +3396,20 @@ impl Daemon {
                                     }
                                     Err(e) => {
                                         tracing::error!("Failed to create audio capture: {}", e);
+                                        self.notify_error("Recording Failed", &e.to_string()).await;
                                         self.play_feedback(SoundEvent::Error);
                                     }
                                 }
-                            } else if let State::Recording { model_override: current_model_override, .. } = &state {
+                            } else if matches!(state, State::Recording { .. }) {
+                                let model = self.model_at_release(&state);
                                 let transcriber = match self.get_transcriber_for_recording(
-                                    current_model_override.as_deref(),
+                                    model.as_deref(),
                                     &transcriber_preloaded,
                                 ).await {
                                     Ok(t) => Some(t),
                                     Err(()) => {
                                         state = State::Idle;
-                                        self.update_state("idle");
+                                        self.settle_state(&mut state);
                                         continue;
                                     }
                                 };
@@ -1919,7 +3433,7 @@ impl Daemon {
                                 self.play_feedback(SoundEvent::RecordingStop);
 
                                 if self.config.output.notification.on_recording_stop {
-                                    send_notification("Recording Stopped", "Transcribing...", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                                    send_notification("Recording Stopped", "Transcribing...", &self.config.output.notification, Some(self.config.engine)).await;
                                 }
 
                                 // Stop audio capture and get remaining samples
@@ -1938,16 +3452,16 @@ impl Daemon {
                                     Ok(t) => t,
                                     Err(()) => {
                                         state = State::Idle;
-                                        self.update_state("idle");
+                                        self.settle_state(&mut state);
                                         continue;
                                     }
                                 };
 
                                 self.update_state("transcribing");
 
-                                if let Some(text) = self.finish_eager_recording(&mut state, transcriber).await {
+                                if let Some((text, perf)) = self.finish_eager_recording(&mut state, transcriber.clone()).await {
+                                    self.queue_eager_result(text, perf, transcriber);
                                     state = State::Transcribing { audio: Vec::new() };
-                                    self.handle_transcription_result(&mut state, Ok(Ok(text))).await;
                                 } else {
                                     tracing::debug!("Eager recording produced empty result");
                                     self.reset_to_idle(&mut state).await;
@@ -1986,7 +3500,7 @@ impl Daemon {
                                 cleanup_model_override();
                                 cleanup_profile_override();
                                 state = State::Idle;
-                                self.update_state("idle");
+                                self.settle_state(&mut state);
                                 self.play_feedback(SoundEvent::Cancelled);
 
                                 // Run post_output_command to reset compositor submap
@@ -1997,21 +3511,19 @@ impl Daemon {
                                 }
 
                                 if self.config.output.notification.on_recording_stop {
-                                    send_notification("Cancelled", "Recording discarded", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                                    send_notification("Cancelled", "Recording discarded", &self.config.output.notification, Some(self.config.engine)).await;
                                 }
                             } else if matches!(state, State::Transcribing { .. }) {
                                 tracing::info!("Transcription cancelled via hotkey");
 
-                                // Abort the transcription task
-                                if let Some(task) = self.transcription_task.take() {
-                                    task.abort();
-                                }
+                                // Abort the running and queued transcriptions
+                                self.abort_transcriptions();
 
                                 cleanup_output_mode_override();
                                 cleanup_model_override();
                                 cleanup_profile_override();
                                 state = State::Idle;
-                                self.update_state("idle");
+                                self.settle_state(&mut state);
                                 self.play_feedback(SoundEvent::Cancelled);
 
                                 // Run post_output_command to reset compositor submap
@@ -2022,17 +3534,63 @@ impl Daemon {
                                 }
 
                                 if self.config.output.notification.on_recording_stop {
-                                    send_notification("Cancelled", "Transcription aborted", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                                    send_notification("Cancelled", "Transcription aborted", &self.config.output.notification, Some(self.config.engine)).await;
                                 }
                             } else {
                                 tracing::trace!("Cancel ignored - not recording or transcribing");
                             }
                         }
+
+                        // === UNDO KEY (erase last output, only while idle) ===
+                        (HotkeyEvent::Undo, _) => {
+                            tracing::debug!("Received HotkeyEvent::Undo");
+                            if matches!(state, State::Idle) {
+                                self.undo_last_output().await;
+                            } else {
+                                tracing::debug!("Undo ignored - not idle");
+                            }
+                        }
+
+                        // === MODEL CYCLE KEY (switch to the next model, only while idle) ===
+                        (HotkeyEvent::CycleModel, _) => {
+                            tracing::debug!("Received HotkeyEvent::CycleModel");
+                            if matches!(state, State::Idle) {
+                                self.switch_model(None).await;
+                            } else {
+                                tracing::debug!("Model switch ignored - not idle");
+                            }
+                        }
                     }
                 }
 
                 // Check for recording timeout and cancel requests
                 _ = tokio::time::sleep(Duration::from_millis(100)), if state.is_recording() => {
+                    // Stream the audio captured so far to the prepared worker,
+                    // the level indicator and meter and the wake-word pause check
+                    let mut paused = false;
+                    if let Some(chunks) = audio_chunks.as_mut() {
+                        let mut captured = Vec::new();
+                        while let Ok(chunk) = chunks.try_recv() {
+                            captured.extend(chunk);
+                        }
+                        // A prepared worker transcribes the audio it was fed, so
+                        // it gets none when recordings are normalized afterwards
+                        let feed = !captured.is_empty() && !self.config.audio.normalize_loudness;
+                        if let Some(mm) = self.model_manager.as_ref().filter(|_| feed) {
+                            mm.feed_prepared(state.model_override(), &captured);
+                        }
+                        #[cfg(target_os = "linux")]
+                        if let Some(ref indicator) = self.indicator {
+                            indicator.feed(&captured);
+                        }
+                        if !captured.is_empty() {
+                            self.update_info(|info| info.set_input_level(&captured));
+                        }
+                        if let Some(ref mut stop) = self.silence_stop {
+                            paused = stop.feed(&captured);
+                        }
+                    }
+
                     // Check for cancel request first
                     if check_cancel_requested() {
                         tracing::info!("Recording cancelled");
@@ -2056,7 +3614,7 @@ impl Daemon {
                         cleanup_model_override();
                         cleanup_profile_override();
                         state = State::Idle;
-                        self.update_state("idle");
+                        self.settle_state(&mut state);
                         self.play_feedback(SoundEvent::Cancelled);
 
                         // Run post_output_command to reset compositor submap
@@ -2067,19 +3625,25 @@ impl Daemon {
                         }
 
                         if self.config.output.notification.on_recording_stop {
-                            send_notification("Cancelled", "Recording discarded", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                            send_notification("Cancelled", "Recording discarded", &self.config.output.notification, Some(self.config.engine)).await;
                         }
 
                         continue;
                     }
 
-                    // Check for recording timeout
+                    // Check for recording timeout, or the pause ending a
+                    // wake-word recording
                     if let Some(duration) = state.recording_duration() {
-                        if duration > max_duration {
-                            tracing::warn!(
-                                "Recording timeout ({:.0}s limit), transcribing captured audio",
-                                max_duration.as_secs_f32()
-                            );
+                        if duration > max_duration || paused {
+                            if paused {
+                                tracing::info!("Pause after wake word, transcribing");
+                                self.silence_stop = None;
+                            } else {
+                                tracing::warn!(
+                                    "Recording timeout ({:.0}s limit), transcribing captured audio",
+                                    max_duration.as_secs_f32()
+                                );
+                            }
 
                             // Cancel any pending eager chunk tasks
                             for (_, task) in self.eager_chunk_tasks.drain(..) {
@@ -2090,22 +3654,18 @@ impl Daemon {
                             cleanup_model_override();
                             cleanup_profile_override();
 
-                            // Get model override from state before transitioning
-                            let model_override = match &state {
-                                State::Recording { model_override, .. } => model_override.as_deref(),
-                                State::EagerRecording { model_override, .. } => model_override.as_deref(),
-                                _ => None,
-                            };
+                            // Pick the model before transitioning
+                            let model = self.model_at_release(&state);
 
                             // Get transcriber for this recording
                             let transcriber = match self.get_transcriber_for_recording(
-                                model_override,
+                                model.as_deref(),
                                 &transcriber_preloaded,
                             ).await {
                                 Ok(t) => Some(t),
                                 Err(()) => {
                                     state = State::Idle;
-                                    self.update_state("idle");
+                                    self.settle_state(&mut state);
                                     continue;
                                 }
                             };
@@ -2120,191 +3680,62 @@ impl Daemon {
                     }
                 }
 
-                // Handle SIGUSR1 - start recording (for compositor keybindings)
-                _ = sigusr1.recv() => {
-                    tracing::debug!("Received SIGUSR1 (start recording)");
-                    if state.is_idle() {
-                        // Read model override from file (set by `voxtype record start --model X`)
-                        let model_override = read_model_override();
-                        tracing::info!("Recording started (external trigger), model_override = {:?}", model_override);
-
-                        if self.config.output.notification.on_recording_start {
-                            send_notification("Recording Started", "External trigger", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                // Handle progress and completion of the oldest transcription;
+                // the next one starts before this one is output
+                event = async {
+                    match self.transcriptions.front_mut() {
+                        Some(job) => job.next_event().await,
+                        None => std::future::pending().await,
+                    }
+                }, if !self.transcriptions.is_empty() => {
+                    match event {
+                        TranscriptionEvent::Progress(Some(percent)) => {
+                            self.report_progress(percent).await;
                         }
-
-                        // Prepare model for transcription
-                        if self.config.on_demand_loading() {
-                            // Start model loading in background
-                            match self.config.engine {
-                                crate::config::TranscriptionEngine::Whisper => {
-                                    let config = self.config.whisper.clone();
-                                    let config_path = self.config_path.clone();
-                                    let model_to_load = model_override.clone();
-                                    self.model_load_task = Some(tokio::task::spawn_blocking(move || {
-                                        let mut temp_manager = ModelManager::new(&config, config_path);
-                                        temp_manager.get_transcriber(model_to_load.as_deref())
-                                    }));
-                                }
-                                crate::config::TranscriptionEngine::Parakeet
-                                | crate::config::TranscriptionEngine::Moonshine
-                                | crate::config::TranscriptionEngine::SenseVoice
-                | crate::config::TranscriptionEngine::Paraformer
-                | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
-                                    let config = self.config.clone();
-                                    self.model_load_task = Some(tokio::task::spawn_blocking(move || {
-                                        crate::transcribe::create_transcriber(&config).map(Arc::from)
-                                    }));
-                                }
-                            }
-                        } else {
-                            // Prepare model (spawns subprocess for gpu_isolation mode)
-                            match self.config.engine {
-                                crate::config::TranscriptionEngine::Whisper => {
-                                    if let Some(ref mut mm) = self.model_manager {
-                                        if let Err(e) = mm.prepare_model(model_override.as_deref()) {
-                                            tracing::warn!("Failed to prepare model: {}", e);
-                                        }
-                                    }
-                                }
-                                crate::config::TranscriptionEngine::Parakeet
-                                | crate::config::TranscriptionEngine::Moonshine
-                                | crate::config::TranscriptionEngine::SenseVoice
-                | crate::config::TranscriptionEngine::Paraformer
-                | crate::config::TranscriptionEngine::Dolphin
-                | crate::config::TranscriptionEngine::Omnilingual => {
-                                    if let Some(ref t) = transcriber_preloaded {
-                                        let transcriber = t.clone();
-                                        tokio::task::spawn_blocking(move || {
-                                            transcriber.prepare();
-                                        });
-                                    }
-                                }
+                        TranscriptionEvent::Progress(None) => {
+                            if let Some(job) = self.transcriptions.front_mut() {
+                                job.progress = None;
                             }
                         }
-
-                        match audio::create_capture(&self.config.audio) {
-                            Ok(mut capture) => {
-                                if let Err(e) = capture.start().await {
-                                    tracing::error!("Failed to start audio: {}", e);
-                                } else {
-                                    audio_capture = Some(capture);
-
-                                    // Use EagerRecording state if eager_processing is enabled
-                                    if self.config.whisper.eager_processing {
-                                        tracing::info!("Using eager input processing");
-                                        state = State::EagerRecording {
-                                            started_at: std::time::Instant::now(),
-                                            model_override,
-                                            accumulated_audio: Vec::new(),
-                                            chunks_sent: 0,
-                                            chunk_results: Vec::new(),
-                                            tasks_in_flight: 0,
-                                        };
-                                    } else {
-                                        state = State::Recording {
-                                            started_at: std::time::Instant::now(),
-                                            model_override,
-                                        };
-                                    }
-                                    self.update_state("recording");
-                                    self.play_feedback(SoundEvent::RecordingStart);
-
-                                    // Run pre-recording hook (e.g., enter compositor submap for cancel)
-                                    if let Some(cmd) = &self.config.output.pre_recording_command {
-                                        if let Err(e) = output::run_hook(cmd, "pre_recording").await {
-                                            tracing::warn!("{}", e);
-                                        }
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                tracing::error!("Failed to create audio capture: {}", e);
-                                self.play_feedback(SoundEvent::Error);
+                        TranscriptionEvent::Finished(result) => {
+                            if let Some(job) = self.transcriptions.pop_front() {
+                                // Progress shown is the oldest transcription's
+                                self.update_info(|info| info.progress = None);
+                                self.start_queued_transcriptions();
+                                self.handle_transcription_result(&mut state, result, job.perf, job.overrides, job.refine_audio).await;
                             }
                         }
                     }
                 }
 
-                // Handle SIGUSR2 - stop recording (for compositor keybindings)
-                _ = sigusr2.recv() => {
-                    tracing::debug!("Received SIGUSR2 (stop recording)");
-                    if let State::Recording { model_override, .. } = &state {
-                        let transcriber = match self.get_transcriber_for_recording(
-                            model_override.as_deref(),
-                            &transcriber_preloaded,
-                        ).await {
-                            Ok(t) => Some(t),
-                            Err(()) => {
-                                state = State::Idle;
-                                self.update_state("idle");
-                                continue;
-                            }
-                        };
-
-                        self.start_transcription_task(
-                            &mut state,
-                            &mut audio_capture,
-                            transcriber,
-                        ).await;
-                    } else if state.is_eager_recording() {
-                        // Handle eager recording stop via external trigger - extract model_override first
-                        let model_override = match &state {
-                            State::EagerRecording { model_override, .. } => model_override.clone(),
-                            _ => None,
-                        };
-
-                        let duration = state.recording_duration().unwrap_or_default();
-                        tracing::info!("Eager recording stopped ({:.1}s)", duration.as_secs_f32());
-
-                        self.play_feedback(SoundEvent::RecordingStop);
-
-                        if self.config.output.notification.on_recording_stop {
-                            send_notification("Recording Stopped", "Transcribing...", self.config.output.notification.show_engine_icon, self.config.engine).await;
-                        }
-
-                        // Stop audio capture and get remaining samples
-                        if let Some(mut capture) = audio_capture.take() {
-                            if let Ok(final_samples) = capture.stop().await {
-                                if let State::EagerRecording { accumulated_audio, .. } = &mut state {
-                                    accumulated_audio.extend(final_samples);
-                                }
-                            }
-                        }
-
-                        let transcriber = match self.get_transcriber_for_recording(
-                            model_override.as_deref(),
-                            &transcriber_preloaded,
-                        ).await {
-                            Ok(t) => t,
-                            Err(()) => {
-                                state = State::Idle;
-                                self.update_state("idle");
-                                continue;
-                            }
-                        };
-
-                        self.update_state("transcribing");
-
-                        if let Some(text) = self.finish_eager_recording(&mut state, transcriber).await {
-                            state = State::Transcribing { audio: Vec::new() };
-                            self.handle_transcription_result(&mut state, Ok(Ok(text))).await;
-                        } else {
-                            tracing::debug!("Eager recording produced empty result");
-                            self.reset_to_idle(&mut state).await;
-                        }
+                // A refined transcription of the last dictation
+                result = async {
+                    match self.refinement.as_mut() {
+                        Some(refinement) => (&mut refinement.task).await,
+                        None => std::future::pending().await,
+                    }
+                }, if self.refinement.is_some() => {
+                    if let Some(refinement) = self.refinement.take() {
+                        self.finish_refinement(refinement, result).await;
                     }
                 }
 
-                // Handle transcription task completion
-                result = async {
-                    match self.transcription_task.as_mut() {
-                        Some(task) => task.await,
+                // Utterances from continuous mode
+                Some(samples) = async {
+                    match &mut utterance_rx {
+                        Some(rx) => rx.recv().await,
                         None => std::future::pending().await,
                     }
-                }, if self.transcription_task.is_some() => {
-                    self.transcription_task = None;
-                    self.handle_transcription_result(&mut state, result).await;
+                } => {
+                    if state.is_recording() {
+                        // The recording captures the same speech
+                        tracing::debug!("Recording in progress, dropping utterance");
+                    } else if let Ok(transcriber) = self
+                        .get_transcriber_for_recording(None, &transcriber_preloaded)
+                        .await
+                    {
+                        self.queue_utterance(&mut state, samples, transcriber).await;
+                    }
                 }
 
                 // Check for cancel during transcription
@@ -2312,16 +3743,14 @@ impl Daemon {
                     if check_cancel_requested() {
                         tracing::info!("Transcription cancelled");
 
-                        // Abort the transcription task
-                        if let Some(task) = self.transcription_task.take() {
-                            task.abort();
-                        }
+                        // Abort the running and queued transcriptions
+                        self.abort_transcriptions();
 
                         cleanup_output_mode_override();
                         cleanup_model_override();
                         cleanup_profile_override();
                         state = State::Idle;
-                        self.update_state("idle");
+                        self.settle_state(&mut state);
                         self.play_feedback(SoundEvent::Cancelled);
 
                         // Run post_output_command to reset compositor submap
@@ -2332,7 +3761,7 @@ impl Daemon {
                         }
 
                         if self.config.output.notification.on_recording_stop {
-                            send_notification("Cancelled", "Transcription aborted", self.config.output.notification.show_engine_icon, self.config.engine).await;
+                            send_notification("Cancelled", "Transcription aborted", &self.config.output.notification, Some(self.config.engine)).await;
                         }
                     }
                 }
@@ -2342,6 +3771,16 @@ impl Daemon {
                     // Silently consume any stale cancel request
                     let _ = check_cancel_requested();
 
+                    // Undo requested via `voxtype record undo`
+                    if check_undo_requested() {
+                        self.undo_last_output().await;
+                    }
+
+                    // Model switch requested via `voxtype model use/next`
+                    if let Some(model) = check_model_switch_requested() {
+                        self.switch_model(model).await;
+                    }
+
                     // Periodically evict idle models (every ~60s when idle)
                     // The check interval is 500ms, so we use a counter to approximate 60s
                     static EVICTION_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
@@ -2460,33 +3899,18 @@ impl Daemon {
                             };
 
                             if let Some(ref mut daemon) = self.meeting_daemon {
-                                // Process mic chunk
-                                let mut had_loopback = false;
-                                match daemon.process_chunk_with_source(mic_chunk, meeting::data::AudioSource::Microphone).await {
+                                // Process both tracks on a shared timeline
+                                let had_loopback = match daemon.process_dual_chunk(mic_chunk, loopback_chunk).await {
                                     Ok(Some(segments)) => {
-                                        tracing::debug!("Processed mic chunk with {} segments", segments.len());
+                                        tracing::debug!("Processed meeting chunk with {} segments", segments.len());
+                                        segments.iter().any(|s| s.source == meeting::data::AudioSource::Loopback)
                                     }
-                                    Ok(None) => {}
+                                    Ok(None) => false,
                                     Err(e) => {
-                                        tracing::error!("Error processing mic chunk: {}", e);
-                                    }
-                                }
-
-                                // Process loopback chunk if non-empty
-                                if !loopback_chunk.is_empty() {
-                                    match daemon.process_chunk_with_source(loopback_chunk, meeting::data::AudioSource::Loopback).await {
-                                        Ok(Some(segments)) => {
-                                            tracing::debug!("Processed loopback chunk with {} segments", segments.len());
-                                            if !segments.is_empty() {
-                                                had_loopback = true;
-                                            }
-                                        }
-                                        Ok(None) => {}
-                                        Err(e) => {
-                                            tracing::error!("Error processing loopback chunk: {}", e);
-                                        }
+                                        tracing::error!("Error processing meeting chunk: {}", e);
+                                        false
                                     }
-                                }
+                                };
 
                                 // Dedup bleed-through: strip echoed phrases from mic segments
                                 if had_loopback {
@@ -2575,9 +3999,7 @@ impl Daemon {
         }
 
         // Abort any pending transcription task
-        if let Some(task) = self.transcription_task.take() {
-            task.abort();
-        }
+        self.abort_transcriptions();
 
         // Abort any pending eager chunk tasks
         for (_, task) in self.eager_chunk_tasks.drain(..) {
@@ -2604,6 +4026,8 @@ impl Daemon {
         if let Some(ref path) = self.pid_file_path {
             cleanup_pid_file(path);
         }
+        DaemonInfo::remove();
+        let _ = pidlock.release();
 
         tracing::info!("Daemon stopped");
 
@@ -2826,6 +4250,18 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_acquire_lock_reports_running_daemon() {
+        let dir = TempDir::new().unwrap();
+        let lock_path = dir.path().join("voxtype.lock");
+
+        let _held = acquire_lock(&lock_path, false).await.unwrap();
+        let err = acquire_lock(&lock_path, false).await.err().unwrap();
+        let message = err.to_string();
+        assert!(message.contains(&format!("PID {}", std::process::id())));
+        assert!(message.contains("--replace"));
+    }
+
     #[test]
     fn test_pidlock_released_on_drop() {
         with_test_runtime_dir(|dir| {
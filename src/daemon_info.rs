@@ -0,0 +1,236 @@
+//! Daemon status snapshot
+//!
+//! The daemon keeps `$XDG_RUNTIME_DIR/voxtype/daemon.json` up to date with
+//! its state, start time, model and the outcome of the last dictation, and
+//! removes it on shutdown. `voxtype status --detail` reads it, so scripts
+//! get more than the single word in the state file, including the progress
+//! of long transcriptions.
+
+use crate::config::{Config, TranscriptionEngine};
+use crate::perf::PerfReport;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Snapshot of the running daemon
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonInfo {
+    /// Process ID of the daemon
+    pub pid: u32,
+    /// Current state (idle, recording, transcribing, ...)
+    pub state: String,
+    /// Percent of the audio transcribed, while transcribing a long recording
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<u8>,
+    /// Input level while recording, 0.0 (-60 dBFS or below) to 1.0 (full
+    /// scale), RMS of the last 100 ms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_level: Option<f32>,
+    /// Peak input level of the last 100 ms while recording, same scale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_peak: Option<f32>,
+    /// When the current state was entered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_since: Option<DateTime<Local>>,
+    /// When the daemon started
+    pub started_at: DateTime<Local>,
+    /// Transcription engine
+    pub engine: TranscriptionEngine,
+    /// Primary model of the engine
+    pub model: String,
+    /// Audio input device
+    pub audio_device: String,
+    /// Output method that delivered the last text, or the first usable one
+    pub output_method: Option<String>,
+    /// When the last dictation was delivered
+    pub last_transcription_at: Option<DateTime<Local>>,
+    /// Time from end of recording to delivered text for the last dictation
+    pub last_latency_ms: Option<u64>,
+}
+
+impl DaemonInfo {
+    /// Snapshot for a daemon starting now with this config
+    pub fn new(config: &Config) -> Self {
+        let now = Local::now();
+        Self {
+            pid: std::process::id(),
+            state: "idle".to_string(),
+            progress: None,
+            input_level: None,
+            input_peak: None,
+            state_since: Some(now),
+            started_at: now,
+            engine: config.engine,
+            model: config.model_name().to_string(),
+            audio_device: config.audio.device.clone(),
+            output_method: None,
+            last_transcription_at: None,
+            last_latency_ms: None,
+        }
+    }
+
+    /// Path of the snapshot file
+    pub fn path() -> PathBuf {
+        Config::runtime_dir().join("daemon.json")
+    }
+
+    /// Save the snapshot, replacing the previous one atomically
+    pub fn write(&self) -> std::io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// Load the snapshot, if the daemon wrote one
+    pub fn read() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Remove the snapshot (on shutdown)
+    pub fn remove() {
+        let _ = std::fs::remove_file(Self::path());
+    }
+
+    /// Seconds since the daemon started
+    pub fn uptime_secs(&self, now: DateTime<Local>) -> u64 {
+        (now - self.started_at).num_seconds().max(0) as u64
+    }
+
+    /// Enter a state, keeping `state_since` if it is the current one
+    pub fn set_state(&mut self, state: &str, now: DateTime<Local>) {
+        if self.state != state {
+            self.state = state.to_string();
+            self.state_since = Some(now);
+        }
+        self.progress = None;
+        self.input_level = None;
+        self.input_peak = None;
+    }
+
+    /// Publish the level of audio just captured
+    pub fn set_input_level(&mut self, samples: &[f32]) {
+        // Two decimals are plenty for a meter and keep the JSON short
+        let round = |level: f32| (level * 100.0).round() / 100.0;
+        self.input_level = Some(round(crate::audio::rms_level(samples)));
+        self.input_peak = Some(round(crate::audio::peak_level(samples)));
+    }
+
+    /// Seconds spent in the current state
+    pub fn state_secs(&self, now: DateTime<Local>) -> Option<u64> {
+        self.state_since
+            .map(|since| (now - since).num_seconds().max(0) as u64)
+    }
+
+    /// Record a delivered dictation
+    pub fn record_dictation(&mut self, report: &PerfReport, at: DateTime<Local>) {
+        self.last_transcription_at = Some(at);
+        self.last_latency_ms = Some(report.total_ms());
+        if report.output_method.is_some() {
+            self.output_method = report.output_method.clone();
+        }
+    }
+}
+
+/// Format seconds as "45s", "3m 5s", "2h 13m" or "1d 4h"
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {}s", mins, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(185), "3m 5s");
+        assert_eq!(format_duration(2 * 3600 + 13 * 60 + 9), "2h 13m");
+        assert_eq!(format_duration(86400 + 4 * 3600), "1d 4h");
+    }
+
+    #[test]
+    fn test_record_dictation() {
+        let mut info = DaemonInfo::new(&Config::default());
+        info.output_method = Some("wtype".to_string());
+        let now = Local::now();
+
+        let report = PerfReport {
+            inference_ms: 900,
+            processing_ms: 50,
+            output_ms: 100,
+            ..Default::default()
+        };
+        info.record_dictation(&report, now);
+        assert_eq!(info.last_latency_ms, Some(1050));
+        assert_eq!(info.last_transcription_at, Some(now));
+        // Failed output keeps the last known method
+        assert_eq!(info.output_method.as_deref(), Some("wtype"));
+
+        let report = PerfReport {
+            output_method: Some("clipboard".to_string()),
+            ..Default::default()
+        };
+        info.record_dictation(&report, now);
+        assert_eq!(info.output_method.as_deref(), Some("clipboard"));
+
+        let json = serde_json::to_string(&info).unwrap();
+        let parsed: DaemonInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, info);
+    }
+
+    #[test]
+    fn test_set_state() {
+        let mut info = DaemonInfo::new(&Config::default());
+        let start = Local::now();
+        info.set_state("recording", start);
+        assert_eq!(
+            info.state_secs(start + chrono::Duration::seconds(12)),
+            Some(12)
+        );
+
+        // Repeating the state keeps the start time
+        info.progress = Some(10);
+        info.set_state("recording", start + chrono::Duration::seconds(5));
+        assert_eq!(info.state_since, Some(start));
+        assert_eq!(info.progress, None);
+
+        info.set_input_level(&[0.5, -0.25]);
+        assert_eq!(info.input_level, Some(0.87));
+        assert_eq!(info.input_peak, Some(0.9));
+
+        info.set_state("idle", start + chrono::Duration::seconds(20));
+        assert_eq!(info.input_level, None);
+        assert_eq!(
+            info.state_secs(start + chrono::Duration::seconds(21)),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_progress_only_while_set() {
+        let mut info = DaemonInfo::new(&Config::default());
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(!json.contains("progress"));
+
+        info.state = "transcribing".to_string();
+        info.progress = Some(40);
+        let json = serde_json::to_string(&info).unwrap();
+        let parsed: DaemonInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.progress, Some(40));
+    }
+}
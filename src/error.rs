@@ -44,6 +44,18 @@ pub enum HotkeyError {
 
     #[error("evdev error: {0}")]
     Evdev(String),
+
+    #[error("Conflicting hotkey configuration: {0}")]
+    KeyConflict(String),
+
+    #[error("Global shortcuts portal: {0}")]
+    Portal(String),
+
+    #[error("Cannot listen for the hotkey: {0}\n  Allow voxtype in System Settings > Privacy & Security > Input Monitoring, then restart it.")]
+    EventTap(String),
+
+    #[error("Failed to install the keyboard hook: {0}")]
+    Hook(String),
 }
 
 /// Errors related to audio capture
@@ -52,7 +64,7 @@ pub enum AudioError {
     #[error("Audio connection failed: {0}")]
     Connection(String),
 
-    #[error("Audio device not found: '{0}'. List devices with: pactl list sources short")]
+    #[error("Audio device not found: '{0}'. List devices with: voxtype devices")]
     DeviceNotFound(String),
 
     #[error("Audio device not found: '{requested}'.\n{available}")]
@@ -94,6 +106,12 @@ pub enum TranscribeError {
 
     #[error("Remote server error: {0}")]
     RemoteError(String),
+
+    #[error("Low confidence transcription ({0:.2}), discarded")]
+    LowConfidence(f32),
+
+    #[error("Transcription cancelled")]
+    Cancelled,
 }
 
 /// Errors related to Voice Activity Detection
@@ -109,6 +127,19 @@ pub enum VadError {
     DetectionFailed(String),
 }
 
+/// Errors related to wake-word activation
+#[derive(Error, Debug)]
+pub enum WakeWordError {
+    #[error("Wake word model not found: {0}\n  Run 'voxtype setup wake-word' to download.")]
+    ModelNotFound(String),
+
+    #[error("Wake word support not compiled in. Rebuild with: cargo build --features wake-word")]
+    NotCompiled,
+
+    #[error("Wake word initialization failed: {0}")]
+    InitFailed(String),
+}
+
 /// Errors related to text output
 #[derive(Error, Debug)]
 pub enum OutputError {
@@ -142,6 +173,13 @@ pub enum OutputError {
     #[error("Ctrl+V simulation failed: {0}")]
     CtrlVFailed(String),
 
+    #[error("Nothing to undo: {0}")]
+    NothingToUndo(String),
+
+    /// The method only outputs to certain windows, and the focused one isn't one
+    #[error("Focused window is {0}")]
+    WrongWindow(String),
+
     #[error(
         "All output methods failed. Ensure wtype, dotool, ydotool, wl-copy, or xclip is available."
     )]
@@ -173,6 +211,7 @@ pub enum MeetingError {
 /// Result type alias using VoxtypeError
 pub type Result<T> = std::result::Result<T, VoxtypeError>;
 
+#[cfg(target_os = "linux")]
 impl From<evdev::Error> for HotkeyError {
     fn from(e: evdev::Error) -> Self {
         HotkeyError::Evdev(e.to_string())
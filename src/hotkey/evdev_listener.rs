@@ -8,10 +8,11 @@
 //!
 //! The user must be in the 'input' group to access /dev/input/* devices.
 
-use super::{HotkeyEvent, HotkeyListener};
+use super::{Debouncer, HotkeyEvent, HotkeyListener};
 use crate::config::HotkeyConfig;
 use crate::error::HotkeyError;
-use evdev::{Device, InputEventKind, Key};
+use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use evdev::{Device, EventType, InputEvent, InputEventKind, Key, Synchronization};
 use inotify::{Inotify, WatchMask};
 use std::collections::{HashMap, HashSet};
 use std::os::unix::io::AsRawFd;
@@ -21,16 +22,24 @@ use tokio::sync::{mpsc, oneshot};
 
 /// evdev-based hotkey listener
 pub struct EvdevListener {
-    /// The key to listen for
-    target_key: Key,
-    /// Modifier keys that must be held
-    modifier_keys: HashSet<Key>,
+    /// The key and modifiers to listen for
+    chord: Chord,
     /// Optional cancel key
     cancel_key: Option<Key>,
+    /// Optional undo key (erase the last output)
+    undo_key: Option<Key>,
+    /// Optional key that switches to the next model
+    model_cycle_key: Option<Key>,
     /// Optional model modifier key (when held, use secondary model)
     model_modifier: Option<Key>,
     /// Secondary model to use when model_modifier is held
     secondary_model: Option<String>,
+    /// Optional spell modifier key (when held, spell out the recording)
+    spell_modifier: Option<Key>,
+    /// Window for ignoring a release+press bounce
+    debounce: Duration,
+    /// Grab keyboards so the hotkey doesn't reach other applications
+    exclusive: bool,
     /// Signal to stop the listener task
     stop_signal: Option<oneshot::Sender<()>>,
 }
@@ -38,13 +47,18 @@ pub struct EvdevListener {
 impl EvdevListener {
     /// Create a new evdev listener for the configured hotkey
     pub fn new(config: &HotkeyConfig) -> Result<Self, HotkeyError> {
-        let target_key = parse_key_name(&config.key)?;
+        let listener = Self::parse(config)?;
 
-        let modifier_keys = config
-            .modifiers
-            .iter()
-            .map(|k| parse_key_name(k))
-            .collect::<Result<HashSet<_>, _>>()?;
+        // Verify we can access /dev/input (permission check)
+        std::fs::read_dir("/dev/input")
+            .map_err(|e| HotkeyError::DeviceAccess(format!("/dev/input: {}", e)))?;
+
+        Ok(listener)
+    }
+
+    /// Parse the configured keys without touching any device
+    fn parse(config: &HotkeyConfig) -> Result<Self, HotkeyError> {
+        let chord = Chord::parse(&config.key, &config.modifiers)?;
 
         // Parse optional cancel key
         let cancel_key = config
@@ -53,6 +67,29 @@ impl EvdevListener {
             .map(|k| parse_key_name(k))
             .transpose()?;
 
+        // Parse optional undo key
+        let undo_key = config
+            .undo_key
+            .as_ref()
+            .map(|k| parse_key_name(k))
+            .transpose()?;
+
+        // Parse optional model cycle key
+        let model_cycle_key = config
+            .model_cycle_key
+            .as_ref()
+            .map(|k| parse_key_name(k))
+            .transpose()?;
+
+        check_key_conflicts(
+            chord.key,
+            &[
+                ("cancel_key", cancel_key),
+                ("undo_key", undo_key),
+                ("model_cycle_key", model_cycle_key),
+            ],
+        )?;
+
         // Parse optional model modifier key
         let model_modifier = config
             .model_modifier
@@ -60,16 +97,23 @@ impl EvdevListener {
             .map(|k| parse_key_name(k))
             .transpose()?;
 
-        // Verify we can access /dev/input (permission check)
-        std::fs::read_dir("/dev/input")
-            .map_err(|e| HotkeyError::DeviceAccess(format!("/dev/input: {}", e)))?;
+        // Parse optional spell modifier key
+        let spell_modifier = config
+            .spell_modifier
+            .as_ref()
+            .map(|k| parse_key_name(k))
+            .transpose()?;
 
         Ok(Self {
-            target_key,
-            modifier_keys,
+            chord,
             cancel_key,
+            undo_key,
+            model_cycle_key,
             model_modifier,
             secondary_model: None, // Set later via set_secondary_model
+            spell_modifier,
+            debounce: Duration::from_millis(config.debounce_ms),
+            exclusive: config.exclusive,
             stop_signal: None,
         })
     }
@@ -87,20 +131,28 @@ impl HotkeyListener for EvdevListener {
         let (stop_tx, stop_rx) = oneshot::channel();
         self.stop_signal = Some(stop_tx);
 
-        let target_key = self.target_key;
-        let modifier_keys = self.modifier_keys.clone();
+        let chord = self.chord.clone();
         let cancel_key = self.cancel_key;
+        let undo_key = self.undo_key;
+        let model_cycle_key = self.model_cycle_key;
         let model_modifier = self.model_modifier;
         let secondary_model = self.secondary_model.clone();
+        let spell_modifier = self.spell_modifier;
+        let debounce = self.debounce;
+        let exclusive = self.exclusive;
 
         // Spawn the listener task
         tokio::task::spawn_blocking(move || {
             if let Err(e) = evdev_listener_loop(
-                target_key,
-                modifier_keys,
+                chord,
                 cancel_key,
+                undo_key,
+                model_cycle_key,
                 model_modifier,
                 secondary_model,
+                spell_modifier,
+                debounce,
+                exclusive,
                 tx,
                 stop_rx,
             ) {
@@ -119,10 +171,75 @@ impl HotkeyListener for EvdevListener {
     }
 }
 
+/// Check the configured key names and conflicts (for `voxtype config check`)
+pub fn validate_config(config: &HotkeyConfig) -> Result<(), HotkeyError> {
+    EvdevListener::parse(config).map(|_| ())
+}
+
+/// Name of the virtual keyboards that re-emit events from grabbed devices
+const PASSTHROUGH_NAME: &str = "voxtype passthrough";
+
+/// An opened keyboard
+struct Keyboard {
+    device: Device,
+    /// Re-emits the events voxtype doesn't consume (exclusive mode only)
+    passthrough: Option<Passthrough>,
+}
+
+/// Virtual keyboard mirroring a grabbed device
+struct Passthrough {
+    device: VirtualDevice,
+    /// Events of the current frame, emitted at its SYN_REPORT
+    frame: Vec<InputEvent>,
+}
+
+impl Passthrough {
+    /// Create a virtual device with the same keys as `source`
+    fn new(source: &Device) -> std::io::Result<Self> {
+        let mut builder = VirtualDeviceBuilder::new()?
+            .name(PASSTHROUGH_NAME)
+            .input_id(source.input_id());
+        if let Some(keys) = source.supported_keys() {
+            builder = builder.with_keys(keys)?;
+        }
+        if let Some(axes) = source.supported_relative_axes() {
+            builder = builder.with_relative_axes(axes)?;
+        }
+        Ok(Self {
+            device: builder.build()?,
+            frame: Vec::new(),
+        })
+    }
+
+    /// Queue an event, emitting the frame when it's complete
+    fn forward(&mut self, event: InputEvent) {
+        if event.event_type() == EventType::SYNCHRONIZATION
+            && event.code() == Synchronization::SYN_REPORT.0
+        {
+            self.flush();
+        } else if event.event_type() != EventType::SYNCHRONIZATION {
+            self.frame.push(event);
+        }
+    }
+
+    /// Emit queued events
+    fn flush(&mut self) {
+        if self.frame.is_empty() {
+            return;
+        }
+        if let Err(e) = self.device.emit(&self.frame) {
+            tracing::warn!("Failed to pass key events through: {}", e);
+        }
+        self.frame.clear();
+    }
+}
+
 /// Manages input devices with hotplug detection via inotify
 struct DeviceManager {
-    /// Map of device path to opened device
-    devices: HashMap<PathBuf, Device>,
+    /// Map of device path to opened keyboard
+    devices: HashMap<PathBuf, Keyboard>,
+    /// Grab keyboards and pass through the events voxtype doesn't consume
+    exclusive: bool,
     /// inotify instance watching /dev/input
     inotify: Inotify,
     /// Buffer for inotify events
@@ -133,7 +250,7 @@ struct DeviceManager {
 
 impl DeviceManager {
     /// Create a new device manager with inotify watcher
-    fn new() -> Result<Self, HotkeyError> {
+    fn new(exclusive: bool) -> Result<Self, HotkeyError> {
         let inotify = Inotify::init().map_err(|e| {
             HotkeyError::DeviceAccess(format!("Failed to initialize inotify: {}", e))
         })?;
@@ -146,6 +263,7 @@ impl DeviceManager {
 
         let mut manager = Self {
             devices: HashMap::new(),
+            exclusive,
             inotify,
             inotify_buffer: [0u8; 1024],
             last_validation: Instant::now(),
@@ -195,7 +313,12 @@ impl DeviceManager {
     /// Try to open a device and add it if it's a keyboard
     fn try_open_device(&mut self, path: &PathBuf) {
         match Device::open(path) {
-            Ok(device) => {
+            Ok(mut device) => {
+                // Our own passthrough devices would feed back into the listener
+                if device.name() == Some(PASSTHROUGH_NAME) {
+                    return;
+                }
+
                 // Check if device has keyboard capabilities
                 let has_keys = device
                     .supported_keys()
@@ -217,12 +340,29 @@ impl DeviceManager {
                         }
                     }
 
+                    let passthrough = if self.exclusive {
+                        grab_device(&mut device, path)
+                    } else {
+                        None
+                    };
+
                     tracing::info!(
-                        "Opened keyboard: {:?} ({:?})",
+                        "Opened keyboard: {:?} ({:?}){}",
                         path,
-                        device.name().unwrap_or("unknown")
+                        device.name().unwrap_or("unknown"),
+                        if passthrough.is_some() {
+                            ", grabbed"
+                        } else {
+                            ""
+                        }
+                    );
+                    self.devices.insert(
+                        path.clone(),
+                        Keyboard {
+                            device,
+                            passthrough,
+                        },
                     );
-                    self.devices.insert(path.clone(), device);
                 }
             }
             Err(e) => {
@@ -296,8 +436,8 @@ impl DeviceManager {
     fn validate_devices(&mut self) -> bool {
         let mut stale_paths = Vec::new();
 
-        for (path, device) in &self.devices {
-            let fd = device.as_raw_fd();
+        for (path, keyboard) in &self.devices {
+            let fd = keyboard.device.as_raw_fd();
             let link_path = format!("/proc/self/fd/{}", fd);
 
             // Check if the symlink still points to a valid device
@@ -319,18 +459,14 @@ impl DeviceManager {
     }
 
     /// Poll all devices for events, handling errors gracefully
-    fn poll_events(&mut self) -> Vec<(Key, i32)> {
+    fn poll_events(&mut self) -> Vec<(PathBuf, InputEvent)> {
         let mut events = Vec::new();
         let mut error_paths = Vec::new();
 
-        for (path, device) in &mut self.devices {
-            match device.fetch_events() {
+        for (path, keyboard) in &mut self.devices {
+            match keyboard.device.fetch_events() {
                 Ok(device_events) => {
-                    for event in device_events {
-                        if let InputEventKind::Key(key) = event.kind() {
-                            events.push((key, event.value()));
-                        }
-                    }
+                    events.extend(device_events.map(|event| (path.clone(), event)));
                 }
                 Err(ref e) if e.raw_os_error() == Some(libc::ENODEV) => {
                     tracing::debug!("Device gone (ENODEV): {:?}", path);
@@ -353,23 +489,191 @@ impl DeviceManager {
         events
     }
 
+    /// Pass an event through to applications if its device is grabbed
+    fn forward(&mut self, path: &PathBuf, event: InputEvent) {
+        if let Some(passthrough) = self
+            .devices
+            .get_mut(path)
+            .and_then(|keyboard| keyboard.passthrough.as_mut())
+        {
+            passthrough.forward(event);
+        }
+    }
+
+    /// Emit any partial frames left after a poll
+    fn flush_passthrough(&mut self) {
+        for keyboard in self.devices.values_mut() {
+            if let Some(passthrough) = keyboard.passthrough.as_mut() {
+                passthrough.flush();
+            }
+        }
+    }
+
     /// Check if we have any devices
     fn has_devices(&self) -> bool {
         !self.devices.is_empty()
     }
 }
 
+/// Grab a keyboard and create the virtual device that passes its other keys through
+///
+/// Returns None (device not grabbed) if either step fails, so a missing
+/// /dev/uinput permission never leaves the keyboard dead.
+fn grab_device(device: &mut Device, path: &PathBuf) -> Option<Passthrough> {
+    let passthrough = match Passthrough::new(device) {
+        Ok(passthrough) => passthrough,
+        Err(e) => {
+            tracing::warn!(
+                "hotkey.exclusive: can't create a virtual keyboard for {:?} ({}); \
+                 the hotkey will still reach other applications. Check write access to /dev/uinput",
+                path,
+                e
+            );
+            return None;
+        }
+    };
+
+    // Grabbing while a key is down (e.g. Enter that started the daemon) would
+    // swallow its release and leave it stuck in applications
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while device
+        .get_key_state()
+        .map(|keys| keys.iter().next().is_some())
+        .unwrap_or(false)
+        && Instant::now() < deadline
+    {
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    match device.grab() {
+        Ok(()) => Some(passthrough),
+        Err(e) => {
+            tracing::warn!("hotkey.exclusive: failed to grab {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Modifier keys tracked for chord matching
+const MODIFIER_KEYS: [Key; 8] = [
+    Key::KEY_LEFTCTRL,
+    Key::KEY_RIGHTCTRL,
+    Key::KEY_LEFTALT,
+    Key::KEY_RIGHTALT,
+    Key::KEY_LEFTSHIFT,
+    Key::KEY_RIGHTSHIFT,
+    Key::KEY_LEFTMETA,
+    Key::KEY_RIGHTMETA,
+];
+
+/// A hotkey: a key plus the modifiers that must be held with it
+#[derive(Debug, Clone, PartialEq)]
+struct Chord {
+    key: Key,
+    /// Each modifier is satisfied by any of its keys (ALT matches either Alt key)
+    modifiers: Vec<Vec<Key>>,
+}
+
+impl Chord {
+    /// Parse `key` ("SCROLLLOCK" or a chord like "SUPER+ALT+D") and the
+    /// separate `modifiers` list
+    fn parse(key: &str, modifiers: &[String]) -> Result<Self, HotkeyError> {
+        let mut parts: Vec<&str> = key.split('+').map(str::trim).collect();
+        let key_name = parts.pop().unwrap_or_default();
+        if key_name.is_empty() {
+            return Err(HotkeyError::UnknownKey(format!(
+                "{}. A chord must end with a key, e.g. SUPER+ALT+D",
+                key
+            )));
+        }
+
+        Ok(Self {
+            key: parse_key_name(key_name)?,
+            modifiers: parts
+                .into_iter()
+                .chain(modifiers.iter().map(String::as_str))
+                .map(parse_modifier)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// Whether the listener needs to track this key's held state
+    fn tracks(&self, key: Key) -> bool {
+        MODIFIER_KEYS.contains(&key) || self.modifiers.iter().any(|m| m.contains(&key))
+    }
+
+    /// Whether the held modifiers complete the chord
+    ///
+    /// When the chord has modifiers, no others may be held (except `allowed`,
+    /// the model and spell modifiers), so SUPER+D doesn't fire on
+    /// SUPER+SHIFT+D.
+    fn modifiers_match(&self, held: &HashSet<Key>, allowed: &[Key]) -> bool {
+        let required_held = self
+            .modifiers
+            .iter()
+            .all(|any| any.iter().any(|k| held.contains(k)));
+        if !required_held || self.modifiers.is_empty() {
+            return required_held;
+        }
+        held.iter().all(|k| {
+            *k == self.key || allowed.contains(k) || self.modifiers.iter().any(|m| m.contains(k))
+        })
+    }
+}
+
+/// Parse a modifier name; ALT, CTRL, SHIFT and SUPER match either side
+fn parse_modifier(name: &str) -> Result<Vec<Key>, HotkeyError> {
+    let normalized = name.trim().to_ascii_uppercase();
+    let keys = match normalized.strip_prefix("KEY_").unwrap_or(&normalized) {
+        "ALT" => vec![Key::KEY_LEFTALT, Key::KEY_RIGHTALT],
+        "CTRL" | "CONTROL" => vec![Key::KEY_LEFTCTRL, Key::KEY_RIGHTCTRL],
+        "SHIFT" => vec![Key::KEY_LEFTSHIFT, Key::KEY_RIGHTSHIFT],
+        "SUPER" | "META" | "WIN" | "LOGO" => vec![Key::KEY_LEFTMETA, Key::KEY_RIGHTMETA],
+        _ => vec![parse_key_name(name)?],
+    };
+    Ok(keys)
+}
+
+/// Reject cancel/undo/model cycle keys that clash with the hotkey or each
+/// other
+///
+/// The listener checks these keys before the hotkey, and in that order, so a
+/// clash would make the hotkey (or the later key) unreachable.
+fn check_key_conflicts(target_key: Key, keys: &[(&str, Option<Key>)]) -> Result<(), HotkeyError> {
+    for (i, &(name, key)) in keys.iter().enumerate() {
+        let Some(key) = key else { continue };
+        if key == target_key {
+            return Err(HotkeyError::KeyConflict(format!(
+                "{} is the same key as the hotkey ({:?})",
+                name, target_key
+            )));
+        }
+        if let Some((other, _)) = keys[i + 1..].iter().find(|(_, k)| *k == Some(key)) {
+            return Err(HotkeyError::KeyConflict(format!(
+                "{} and {} are the same key ({:?})",
+                name, other, key
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// Main listener loop running in a blocking task
+#[allow(clippy::too_many_arguments)]
 fn evdev_listener_loop(
-    target_key: Key,
-    modifier_keys: HashSet<Key>,
+    chord: Chord,
     cancel_key: Option<Key>,
+    undo_key: Option<Key>,
+    model_cycle_key: Option<Key>,
     model_modifier: Option<Key>,
     secondary_model: Option<String>,
+    spell_modifier: Option<Key>,
+    debounce: Duration,
+    exclusive: bool,
     tx: mpsc::Sender<HotkeyEvent>,
     mut stop_rx: oneshot::Receiver<()>,
 ) -> Result<(), HotkeyError> {
-    let mut manager = DeviceManager::new()?;
+    let mut manager = DeviceManager::new(exclusive)?;
 
     // Track currently held modifier keys
     let mut active_modifiers: HashSet<Key> = HashSet::new();
@@ -377,26 +681,46 @@ fn evdev_listener_loop(
     // Track if model modifier is currently held
     let mut model_modifier_held = false;
 
+    // Track if spell modifier is currently held
+    let mut spell_modifier_held = false;
+
+    // Modifiers that may be held besides the chord's own
+    let allowed: Vec<Key> = [model_modifier, spell_modifier]
+        .into_iter()
+        .flatten()
+        .collect();
+
     // Track if we're currently "pressed" (to handle repeat events)
     let mut is_pressed = false;
 
+    // Releases are held back briefly in case the key bounces
+    let mut debouncer = Debouncer::new(debounce);
+
     if let Some(cancel) = cancel_key {
         tracing::info!(
             "Listening for {:?} (with modifiers: {:?}) and cancel key {:?} on {} device(s)",
-            target_key,
-            modifier_keys,
+            chord.key,
+            chord.modifiers,
             cancel,
             manager.devices.len()
         );
     } else {
         tracing::info!(
             "Listening for {:?} (with modifiers: {:?}) on {} device(s)",
-            target_key,
-            modifier_keys,
+            chord.key,
+            chord.modifiers,
             manager.devices.len()
         );
     }
 
+    if let Some(undo) = undo_key {
+        tracing::info!("Undo key {:?} configured", undo);
+    }
+
+    if let Some(cycle) = model_cycle_key {
+        tracing::info!("Model cycle key {:?} configured", cycle);
+    }
+
     if let Some(mm) = model_modifier {
         if let Some(ref model) = secondary_model {
             tracing::info!(
@@ -407,6 +731,10 @@ fn evdev_listener_loop(
         }
     }
 
+    if let Some(sm) = spell_modifier {
+        tracing::info!("Spell modifier {:?} configured", sm);
+    }
+
     loop {
         // Check for stop signal (non-blocking)
         match stop_rx.try_recv() {
@@ -422,6 +750,7 @@ fn evdev_listener_loop(
             // Clear state when devices change
             active_modifiers.clear();
             model_modifier_held = false;
+            spell_modifier_held = false;
             is_pressed = false;
             manager.handle_device_changes();
         }
@@ -432,6 +761,7 @@ fn evdev_listener_loop(
                 // Devices were removed, clear state
                 active_modifiers.clear();
                 model_modifier_held = false;
+                spell_modifier_held = false;
                 is_pressed = false;
                 tracing::debug!("Stale devices removed during validation");
             }
@@ -449,9 +779,22 @@ fn evdev_listener_loop(
         }
 
         // Poll all devices for events
-        for (key, value) in manager.poll_events() {
-            // Track modifier state
-            if modifier_keys.contains(&key) {
+        for (path, event) in manager.poll_events() {
+            let InputEventKind::Key(key) = event.kind() else {
+                manager.forward(&path, event);
+                continue;
+            };
+            let value = event.value();
+
+            // The hotkey's own events are consumed while it's active; everything
+            // else reaches applications (only matters for grabbed devices)
+            let was_pressed = is_pressed;
+            if key != chord.key {
+                manager.forward(&path, event);
+            }
+
+            // Track modifier state (all modifiers, so extra ones can be rejected)
+            if chord.tracks(key) {
                 match value {
                     1 => {
                         active_modifiers.insert(key);
@@ -474,6 +817,15 @@ fn evdev_listener_loop(
                 }
             }
 
+            // Track spell modifier state
+            if spell_modifier == Some(key) {
+                match value {
+                    1 => spell_modifier_held = true,
+                    0 => spell_modifier_held = false,
+                    _ => {}
+                }
+            }
+
             // Check cancel key first (if configured)
             if let Some(cancel) = cancel_key {
                 if key == cancel && value == 1 {
@@ -486,54 +838,95 @@ fn evdev_listener_loop(
                 }
             }
 
+            // Check undo key (if configured)
+            if let Some(undo) = undo_key {
+                if key == undo && value == 1 {
+                    tracing::debug!("Undo key pressed");
+                    if tx.blocking_send(HotkeyEvent::Undo).is_err() {
+                        return Ok(()); // Channel closed
+                    }
+                    continue;
+                }
+            }
+
+            // Check model cycle key (if configured)
+            if model_cycle_key == Some(key) && value == 1 {
+                tracing::debug!("Model cycle key pressed");
+                if tx.blocking_send(HotkeyEvent::CycleModel).is_err() {
+                    return Ok(()); // Channel closed
+                }
+                continue;
+            }
+
             // Check target key
-            if key == target_key {
-                let modifiers_satisfied =
-                    modifier_keys.iter().all(|m| active_modifiers.contains(m));
+            if key == chord.key {
+                let now = Instant::now();
+                if debouncer.release_due(now) {
+                    tracing::debug!("Hotkey released");
+                    if tx.blocking_send(HotkeyEvent::Released).is_err() {
+                        return Ok(()); // Channel closed
+                    }
+                }
 
-                if modifiers_satisfied {
-                    match value {
-                        1 if !is_pressed => {
-                            // Key press (not repeat)
-                            is_pressed = true;
-
-                            // Determine model override based on model_modifier state
-                            let model_override = if model_modifier_held {
-                                secondary_model.clone()
-                            } else {
-                                None
-                            };
-
-                            if model_override.is_some() {
-                                tracing::debug!(
-                                    "Hotkey pressed with model override: {:?}",
-                                    model_override
-                                );
-                            } else {
-                                tracing::debug!("Hotkey pressed");
-                            }
-
-                            if tx
-                                .blocking_send(HotkeyEvent::Pressed { model_override })
-                                .is_err()
-                            {
-                                return Ok(()); // Channel closed
-                            }
-                        }
-                        0 if is_pressed => {
-                            // Key release
-                            is_pressed = false;
-                            tracing::debug!("Hotkey released");
-                            if tx.blocking_send(HotkeyEvent::Released).is_err() {
-                                return Ok(()); // Channel closed
-                            }
+                match value {
+                    1 if !is_pressed && debouncer.press(now) => {
+                        // Pressed again right after a release: bounce, keep recording
+                        is_pressed = true;
+                        tracing::debug!("Ignoring hotkey bounce");
+                    }
+                    1 if !is_pressed && chord.modifiers_match(&active_modifiers, &allowed) => {
+                        // Key press (not repeat)
+                        is_pressed = true;
+
+                        // Determine model override based on model_modifier state
+                        let model_override = if model_modifier_held {
+                            secondary_model.clone()
+                        } else {
+                            None
+                        };
+
+                        if model_override.is_some() {
+                            tracing::debug!(
+                                "Hotkey pressed with model override: {:?}",
+                                model_override
+                            );
+                        } else {
+                            tracing::debug!("Hotkey pressed");
                         }
-                        2 => {
-                            // Key repeat - ignore
+
+                        let spell = spell_modifier_held;
+                        if tx
+                            .blocking_send(HotkeyEvent::Pressed {
+                                model_override,
+                                spell,
+                            })
+                            .is_err()
+                        {
+                            return Ok(()); // Channel closed
                         }
-                        _ => {}
+                    }
+                    0 if is_pressed => {
+                        // Key release, even if a modifier was let go first.
+                        // Sent once the debounce window passes without a press
+                        is_pressed = false;
+                        debouncer.release(now);
+                    }
+                    _ => {
+                        // Key repeat, or pressed without its modifiers - ignore
                     }
                 }
+
+                if !was_pressed && !is_pressed {
+                    manager.forward(&path, event);
+                }
+            }
+        }
+        manager.flush_passthrough();
+
+        if debouncer.release_due(Instant::now()) {
+            tracing::debug!("Hotkey released");
+            if tx.blocking_send(HotkeyEvent::Released).is_err() {
+                return Ok(()); // Channel closed
             }
         }
 
@@ -621,6 +1014,44 @@ fn parse_key_name(name: &str) -> Result<Key, HotkeyError> {
         "KEY_F23" => Key::KEY_F23,
         "KEY_F24" => Key::KEY_F24,
 
+        // Letter and number keys (for chords like SUPER+ALT+D)
+        "KEY_A" => Key::KEY_A,
+        "KEY_B" => Key::KEY_B,
+        "KEY_C" => Key::KEY_C,
+        "KEY_D" => Key::KEY_D,
+        "KEY_E" => Key::KEY_E,
+        "KEY_F" => Key::KEY_F,
+        "KEY_G" => Key::KEY_G,
+        "KEY_H" => Key::KEY_H,
+        "KEY_I" => Key::KEY_I,
+        "KEY_J" => Key::KEY_J,
+        "KEY_K" => Key::KEY_K,
+        "KEY_L" => Key::KEY_L,
+        "KEY_M" => Key::KEY_M,
+        "KEY_N" => Key::KEY_N,
+        "KEY_O" => Key::KEY_O,
+        "KEY_P" => Key::KEY_P,
+        "KEY_Q" => Key::KEY_Q,
+        "KEY_R" => Key::KEY_R,
+        "KEY_S" => Key::KEY_S,
+        "KEY_T" => Key::KEY_T,
+        "KEY_U" => Key::KEY_U,
+        "KEY_V" => Key::KEY_V,
+        "KEY_W" => Key::KEY_W,
+        "KEY_X" => Key::KEY_X,
+        "KEY_Y" => Key::KEY_Y,
+        "KEY_Z" => Key::KEY_Z,
+        "KEY_0" => Key::KEY_0,
+        "KEY_1" => Key::KEY_1,
+        "KEY_2" => Key::KEY_2,
+        "KEY_3" => Key::KEY_3,
+        "KEY_4" => Key::KEY_4,
+        "KEY_5" => Key::KEY_5,
+        "KEY_6" => Key::KEY_6,
+        "KEY_7" => Key::KEY_7,
+        "KEY_8" => Key::KEY_8,
+        "KEY_9" => Key::KEY_9,
+
         // Navigation keys
         "KEY_HOME" => Key::KEY_HOME,
         "KEY_END" => Key::KEY_END,
@@ -784,4 +1215,64 @@ mod tests {
     fn test_parse_key_name_error() {
         assert!(parse_key_name("INVALID_KEY_NAME").is_err());
     }
+
+    #[test]
+    fn test_parse_chord() {
+        let chord = Chord::parse("Super+Alt+D", &[]).unwrap();
+        assert_eq!(chord.key, Key::KEY_D);
+        assert_eq!(
+            chord.modifiers,
+            vec![
+                vec![Key::KEY_LEFTMETA, Key::KEY_RIGHTMETA],
+                vec![Key::KEY_LEFTALT, Key::KEY_RIGHTALT]
+            ]
+        );
+
+        let chord = Chord::parse("SCROLLLOCK", &["LEFTCTRL".to_string()]).unwrap();
+        assert_eq!(chord.key, Key::KEY_SCROLLLOCK);
+        assert_eq!(chord.modifiers, vec![vec![Key::KEY_LEFTCTRL]]);
+
+        assert!(Chord::parse("SUPER+", &[]).is_err());
+        assert!(Chord::parse("HYPER+D", &[]).is_err());
+    }
+
+    #[test]
+    fn test_chord_modifiers_match() {
+        let chord = Chord::parse("SUPER+ALT+D", &[]).unwrap();
+        let held = |keys: &[Key]| keys.iter().copied().collect::<HashSet<_>>();
+
+        assert!(chord.modifiers_match(&held(&[Key::KEY_LEFTMETA, Key::KEY_RIGHTALT]), &[]));
+        assert!(!chord.modifiers_match(&held(&[Key::KEY_LEFTMETA]), &[]));
+        // Extra modifiers don't match, unless it's the model or spell modifier
+        let with_shift = held(&[Key::KEY_LEFTMETA, Key::KEY_LEFTALT, Key::KEY_LEFTSHIFT]);
+        assert!(!chord.modifiers_match(&with_shift, &[]));
+        assert!(chord.modifiers_match(&with_shift, &[Key::KEY_LEFTSHIFT]));
+        assert!(chord.modifiers_match(&with_shift, &[Key::KEY_F1, Key::KEY_LEFTSHIFT]));
+
+        // A single key fires whatever else is held
+        let single = Chord::parse("SCROLLLOCK", &[]).unwrap();
+        assert!(single.modifiers_match(&held(&[Key::KEY_LEFTCTRL]), &[]));
+    }
+
+    #[test]
+    fn test_check_key_conflicts() {
+        let hotkey = Key::KEY_SCROLLLOCK;
+        let check = |cancel, undo, cycle| {
+            check_key_conflicts(
+                hotkey,
+                &[
+                    ("cancel_key", cancel),
+                    ("undo_key", undo),
+                    ("model_cycle_key", cycle),
+                ],
+            )
+        };
+        assert!(check(Some(Key::KEY_ESC), Some(Key::KEY_F12), Some(Key::KEY_F9)).is_ok());
+        assert!(check(None, None, None).is_ok());
+        assert!(check(Some(hotkey), None, None).is_err());
+        assert!(check(None, Some(hotkey), None).is_err());
+        assert!(check(None, None, Some(hotkey)).is_err());
+        assert!(check(Some(Key::KEY_ESC), Some(Key::KEY_ESC), None).is_err());
+        assert!(check(None, Some(Key::KEY_F9), Some(Key::KEY_F9)).is_err());
+    }
 }
@@ -0,0 +1,373 @@
+//! Hotkey chords for the macOS and Windows listeners
+//!
+//! Both platforms hand the listener raw key codes one event at a time. This
+//! module parses the configured evdev key names through the platform's
+//! [`Layout`] and turns key events into [`HotkeyEvent`]s the same way the
+//! evdev listener does: chord modifiers, cancel, undo and model cycle keys,
+//! the model and spell modifiers and release debouncing.
+
+use super::{Debouncer, HotkeyEvent};
+use crate::config::HotkeyConfig;
+use crate::error::HotkeyError;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Platform key code (macOS kVK_* or Windows VK_*)
+pub(crate) type KeyCode = u16;
+
+/// How a platform names its keys
+#[derive(Debug)]
+pub(crate) struct Layout {
+    /// Parse an evdev key name (without the KEY_ prefix)
+    pub key: fn(&str) -> Result<KeyCode, HotkeyError>,
+    /// Parse a modifier name to the keys that satisfy it (either side)
+    pub modifier: fn(&str) -> Result<Vec<KeyCode>, HotkeyError>,
+    /// Whether a key is a modifier
+    pub is_modifier: fn(KeyCode) -> bool,
+}
+
+/// The configured keys
+#[derive(Debug, Clone)]
+pub(crate) struct Keys {
+    pub key: KeyCode,
+    /// Each modifier is satisfied by any of its keys (ALT matches either Alt key)
+    pub modifiers: Vec<Vec<KeyCode>>,
+    pub cancel_key: Option<KeyCode>,
+    pub undo_key: Option<KeyCode>,
+    pub model_cycle_key: Option<KeyCode>,
+    pub model_modifier: Option<KeyCode>,
+    pub spell_modifier: Option<KeyCode>,
+    is_modifier: fn(KeyCode) -> bool,
+}
+
+impl Keys {
+    pub fn parse(config: &HotkeyConfig, layout: &Layout) -> Result<Self, HotkeyError> {
+        let mut parts: Vec<&str> = config.key.split('+').map(str::trim).collect();
+        let key_name = parts.pop().unwrap_or_default();
+        if key_name.is_empty() {
+            return Err(HotkeyError::UnknownKey(format!(
+                "{}. A chord must end with a key, e.g. SUPER+ALT+D",
+                config.key
+            )));
+        }
+        let key = (layout.key)(key_name)?;
+        let modifiers = parts
+            .into_iter()
+            .chain(config.modifiers.iter().map(String::as_str))
+            .map(layout.modifier)
+            .collect::<Result<_, _>>()?;
+
+        let parse = |name: &Option<String>| name.as_deref().map(layout.key).transpose();
+        let cancel_key = parse(&config.cancel_key)?;
+        let undo_key = parse(&config.undo_key)?;
+        let model_cycle_key = parse(&config.model_cycle_key)?;
+        let named = [
+            ("cancel_key", cancel_key),
+            ("undo_key", undo_key),
+            ("model_cycle_key", model_cycle_key),
+        ];
+        for (i, &(name, other)) in named.iter().enumerate() {
+            if other.is_none() {
+                continue;
+            }
+            if other == Some(key) {
+                return Err(HotkeyError::KeyConflict(format!(
+                    "{} is the same key as the hotkey ({})",
+                    name, key_name
+                )));
+            }
+            if let Some((second, _)) = named[i + 1..].iter().find(|(_, k)| *k == other) {
+                return Err(HotkeyError::KeyConflict(format!(
+                    "{} and {} are the same key",
+                    name, second
+                )));
+            }
+        }
+
+        Ok(Self {
+            key,
+            modifiers,
+            cancel_key,
+            undo_key,
+            model_cycle_key,
+            model_modifier: parse(&config.model_modifier)?,
+            spell_modifier: parse(&config.spell_modifier)?,
+            is_modifier: layout.is_modifier,
+        })
+    }
+
+    /// Whether the held modifiers complete the chord
+    ///
+    /// When the chord has modifiers, no others may be held (except the model
+    /// and spell modifiers), so SUPER+D doesn't fire on SUPER+SHIFT+D.
+    fn modifiers_match(&self, held: &HashSet<KeyCode>) -> bool {
+        let required_held = self
+            .modifiers
+            .iter()
+            .all(|any| any.iter().any(|k| held.contains(k)));
+        if !required_held || self.modifiers.is_empty() {
+            return required_held;
+        }
+        held.iter().all(|k| {
+            *k == self.key
+                || Some(*k) == self.model_modifier
+                || Some(*k) == self.spell_modifier
+                || self.modifiers.iter().any(|m| m.contains(k))
+        })
+    }
+
+    /// Hotkey description for the log
+    pub fn label(&self) -> String {
+        if self.modifiers.is_empty() {
+            format!("key code {}", self.key)
+        } else {
+            format!("key code {} with modifiers {:?}", self.key, self.modifiers)
+        }
+    }
+}
+
+/// Key press state, fed one key event at a time
+pub(crate) struct KeyState {
+    keys: Keys,
+    secondary_model: Option<String>,
+    /// Modifier keys currently held
+    held: HashSet<KeyCode>,
+    /// Whether the hotkey is down (to ignore autorepeat)
+    is_pressed: bool,
+    debouncer: Debouncer,
+}
+
+impl KeyState {
+    pub fn new(keys: Keys, secondary_model: Option<String>, debounce: Duration) -> Self {
+        Self {
+            keys,
+            secondary_model,
+            held: HashSet::new(),
+            is_pressed: false,
+            debouncer: Debouncer::new(debounce),
+        }
+    }
+
+    /// The configured keys
+    pub fn keys(&self) -> &Keys {
+        &self.keys
+    }
+
+    /// Whether the hotkey is held down
+    pub fn is_pressed(&self) -> bool {
+        self.is_pressed
+    }
+
+    /// Handle a key press (1), release (0) or autorepeat (2)
+    pub fn key(&mut self, key: KeyCode, value: i32, now: Instant) -> Vec<HotkeyEvent> {
+        let mut events = Vec::new();
+
+        if (self.keys.is_modifier)(key)
+            || Some(key) == self.keys.model_modifier
+            || Some(key) == self.keys.spell_modifier
+        {
+            match value {
+                1 => {
+                    self.held.insert(key);
+                }
+                0 => {
+                    self.held.remove(&key);
+                }
+                _ => {}
+            }
+        }
+
+        if value == 1 && Some(key) == self.keys.cancel_key {
+            events.push(HotkeyEvent::Cancel);
+            return events;
+        }
+        if value == 1 && Some(key) == self.keys.undo_key {
+            events.push(HotkeyEvent::Undo);
+            return events;
+        }
+        if value == 1 && Some(key) == self.keys.model_cycle_key {
+            events.push(HotkeyEvent::CycleModel);
+            return events;
+        }
+        if key != self.keys.key {
+            return events;
+        }
+
+        events.extend(self.poll(now));
+        match value {
+            1 if !self.is_pressed && self.debouncer.press(now) => {
+                // Pressed again right after a release: bounce, keep recording
+                self.is_pressed = true;
+                tracing::debug!("Ignoring hotkey bounce");
+            }
+            1 if !self.is_pressed && self.keys.modifiers_match(&self.held) => {
+                self.is_pressed = true;
+                let model_override = self
+                    .keys
+                    .model_modifier
+                    .filter(|mm| self.held.contains(mm))
+                    .and(self.secondary_model.clone());
+                let spell = self
+                    .keys
+                    .spell_modifier
+                    .is_some_and(|sm| self.held.contains(&sm));
+                tracing::debug!(
+                    "Hotkey pressed (model override: {:?}, spell: {})",
+                    model_override,
+                    spell
+                );
+                events.push(HotkeyEvent::Pressed {
+                    model_override,
+                    spell,
+                });
+            }
+            0 if self.is_pressed => {
+                // Sent once the debounce window passes without a press
+                self.is_pressed = false;
+                self.debouncer.release(now);
+            }
+            _ => {}
+        }
+        events
+    }
+
+    /// A hotkey release whose debounce window has passed
+    pub fn poll(&mut self, now: Instant) -> Option<HotkeyEvent> {
+        self.debouncer.release_due(now).then(|| {
+            tracing::debug!("Hotkey released");
+            HotkeyEvent::Released
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Letters are their ASCII codes; SHIFT is 1/2, SUPER is 3/4, ALT is 5/6
+    const TEST_LAYOUT: Layout = Layout {
+        key: |name| match name.as_bytes() {
+            [c @ b'A'..=b'Z'] => Ok(*c as KeyCode),
+            _ => Err(HotkeyError::UnknownKey(name.to_string())),
+        },
+        modifier: |name| match name {
+            "SHIFT" => Ok(vec![1, 2]),
+            "SUPER" => Ok(vec![3, 4]),
+            "ALT" => Ok(vec![5, 6]),
+            _ => Err(HotkeyError::UnknownKey(name.to_string())),
+        },
+        is_modifier: |key| (1..=6).contains(&key),
+    };
+
+    fn config(key: &str) -> HotkeyConfig {
+        toml::from_str(&format!("key = \"{}\"", key)).unwrap()
+    }
+
+    #[test]
+    fn test_keys_parse() {
+        let keys = Keys::parse(&config("SUPER+ALT+D"), &TEST_LAYOUT).unwrap();
+        assert_eq!(keys.key, b'D' as KeyCode);
+        assert_eq!(keys.modifiers, vec![vec![3, 4], vec![5, 6]]);
+        assert_eq!(keys.label(), "key code 68 with modifiers [[3, 4], [5, 6]]");
+
+        let mut conflict = config("X");
+        conflict.undo_key = Some("X".to_string());
+        assert!(Keys::parse(&conflict, &TEST_LAYOUT).is_err());
+        conflict.undo_key = Some("U".to_string());
+        conflict.model_cycle_key = Some("U".to_string());
+        assert!(Keys::parse(&conflict, &TEST_LAYOUT).is_err());
+        assert!(Keys::parse(&config("SUPER+"), &TEST_LAYOUT).is_err());
+    }
+
+    #[test]
+    fn test_key_state_chord() {
+        let now = Instant::now();
+        let d = b'D' as KeyCode;
+        let keys = Keys::parse(&config("SUPER+D"), &TEST_LAYOUT).unwrap();
+        let mut state = KeyState::new(keys, None, Duration::ZERO);
+        assert_eq!(state.keys().key, d);
+
+        // Without the modifier nothing happens
+        assert!(state.key(d, 1, now).is_empty());
+        assert!(state.key(d, 0, now).is_empty());
+
+        // An extra modifier doesn't match either
+        state.key(3, 1, now);
+        state.key(1, 1, now);
+        assert!(state.key(d, 1, now).is_empty());
+        state.key(d, 0, now);
+        state.key(1, 0, now);
+
+        assert_eq!(
+            state.key(d, 1, now),
+            vec![HotkeyEvent::Pressed {
+                model_override: None,
+                spell: false,
+            }]
+        );
+        assert!(state.is_pressed());
+        assert!(state.key(d, 2, now).is_empty());
+        assert!(state.key(d, 0, now).is_empty());
+        assert_eq!(state.poll(now), Some(HotkeyEvent::Released));
+    }
+
+    #[test]
+    fn test_key_state_model_modifier() {
+        let now = Instant::now();
+        let mut config = config("D");
+        config.model_modifier = Some("M".to_string());
+        config.cancel_key = Some("C".to_string());
+        config.model_cycle_key = Some("N".to_string());
+        let keys = Keys::parse(&config, &TEST_LAYOUT).unwrap();
+        let mut state = KeyState::new(keys, Some("large-v3".to_string()), Duration::ZERO);
+
+        assert_eq!(
+            state.key(b'C' as KeyCode, 1, now),
+            vec![HotkeyEvent::Cancel]
+        );
+        assert_eq!(
+            state.key(b'N' as KeyCode, 1, now),
+            vec![HotkeyEvent::CycleModel]
+        );
+        state.key(b'M' as KeyCode, 1, now);
+        assert_eq!(
+            state.key(b'D' as KeyCode, 1, now),
+            vec![HotkeyEvent::Pressed {
+                model_override: Some("large-v3".to_string()),
+                spell: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_key_state_spell_modifier() {
+        let now = Instant::now();
+        let mut config = config("SUPER+D");
+        config.spell_modifier = Some("S".to_string());
+        let keys = Keys::parse(&config, &TEST_LAYOUT).unwrap();
+        let mut state = KeyState::new(keys, None, Duration::ZERO);
+        let d = b'D' as KeyCode;
+
+        // Held with the chord, it doesn't count as an extra modifier
+        state.key(3, 1, now);
+        state.key(b'S' as KeyCode, 1, now);
+        assert_eq!(
+            state.key(d, 1, now),
+            vec![HotkeyEvent::Pressed {
+                model_override: None,
+                spell: true,
+            }]
+        );
+        state.key(d, 0, now);
+        assert_eq!(state.poll(now), Some(HotkeyEvent::Released));
+
+        state.key(b'S' as KeyCode, 0, now);
+        assert_eq!(
+            state.key(d, 1, now),
+            vec![HotkeyEvent::Pressed {
+                model_override: None,
+                spell: false,
+            }]
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! macOS hotkey listener using a Quartz event tap
+//!
+//! A listen-only CGEventTap on the login session sees key events for every
+//! application, like evdev does on Linux. macOS only delivers them once the
+//! user allows voxtype under System Settings > Privacy & Security > Input
+//! Monitoring; until then the listener asks for the permission and fails.
+//!
+//! Key names are the evdev names used on Linux, mapped to macOS virtual key
+//! codes, so configs can be shared. PC keys missing from Mac keyboards map to
+//! the keys macOS reports for them: SYSRQ is F13, SCROLLLOCK is F14 and
+//! PAUSE is F15.
+
+use super::keymap::{KeyCode, KeyState, Keys, Layout};
+use super::{HotkeyEvent, HotkeyListener};
+use crate::config::HotkeyConfig;
+use crate::error::HotkeyError;
+use core_foundation::runloop::{kCFRunLoopCommonModes, kCFRunLoopDefaultMode, CFRunLoop};
+use core_graphics::event::{
+    CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType, EventField,
+};
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+
+/// macOS virtual key codes (kVK_*)
+const LAYOUT: Layout = Layout {
+    key: parse_key_name,
+    modifier: parse_modifier,
+    is_modifier: |key| modifier_mask(key).is_some(),
+};
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightListenEventAccess() -> bool;
+    fn CGRequestListenEventAccess() -> bool;
+}
+
+/// Event tap hotkey listener
+pub struct EventTapListener {
+    /// Hotkey, cancel/undo keys and model modifier
+    keys: Keys,
+    /// Secondary model to use when model_modifier is held
+    secondary_model: Option<String>,
+    /// Window for ignoring a release+press bounce
+    debounce: Duration,
+    /// Signal to stop the listener task
+    stop_signal: Option<oneshot::Sender<()>>,
+}
+
+impl EventTapListener {
+    /// Create a new listener for the configured hotkey
+    pub fn new(config: &HotkeyConfig) -> Result<Self, HotkeyError> {
+        let keys = Keys::parse(config, &LAYOUT)?;
+
+        if config.exclusive {
+            tracing::warn!(
+                "hotkey.exclusive is not supported on macOS; the hotkey still reaches applications"
+            );
+        }
+
+        // Input Monitoring permission; asking shows the system prompt once
+        if !unsafe { CGPreflightListenEventAccess() } {
+            unsafe { CGRequestListenEventAccess() };
+            return Err(HotkeyError::EventTap(
+                "Input Monitoring permission not granted".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            keys,
+            secondary_model: None,
+            debounce: Duration::from_millis(config.debounce_ms),
+            stop_signal: None,
+        })
+    }
+
+    /// Set the secondary model to use when model_modifier is held
+    pub fn set_secondary_model(&mut self, model: Option<String>) {
+        self.secondary_model = model;
+    }
+}
+
+#[async_trait::async_trait]
+impl HotkeyListener for EventTapListener {
+    async fn start(&mut self) -> Result<mpsc::Receiver<HotkeyEvent>, HotkeyError> {
+        let (tx, rx) = mpsc::channel(32);
+        let (stop_tx, stop_rx) = oneshot::channel();
+        self.stop_signal = Some(stop_tx);
+
+        let state = KeyState::new(
+            self.keys.clone(),
+            self.secondary_model.clone(),
+            self.debounce,
+        );
+
+        // The tap delivers events through a run loop on this thread
+        tokio::task::spawn_blocking(move || {
+            if let Err(e) = event_tap_loop(state, tx, stop_rx) {
+                tracing::error!("Hotkey listener error: {}", e);
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn stop(&mut self) -> Result<(), HotkeyError> {
+        if let Some(stop) = self.stop_signal.take() {
+            let _ = stop.send(());
+        }
+        Ok(())
+    }
+}
+
+/// Check the configured key names and conflicts (for `voxtype config check`)
+pub fn validate_config(config: &HotkeyConfig) -> Result<(), HotkeyError> {
+    Keys::parse(config, &LAYOUT).map(|_| ())
+}
+
+/// Run the event tap until stopped or the daemon goes away
+fn event_tap_loop(
+    state: KeyState,
+    tx: mpsc::Sender<HotkeyEvent>,
+    mut stop_rx: oneshot::Receiver<()>,
+) -> Result<(), HotkeyError> {
+    let state = RefCell::new(state);
+
+    let tap = CGEventTap::new(
+        CGEventTapLocation::Session,
+        CGEventTapPlacement::HeadInsertEventTap,
+        CGEventTapOptions::ListenOnly,
+        vec![
+            CGEventType::KeyDown,
+            CGEventType::KeyUp,
+            CGEventType::FlagsChanged,
+        ],
+        |_proxy, event_type, event| {
+            let key = event.get_integer_value_field(EventField::KEYBOARD_EVENT_KEYCODE) as KeyCode;
+            let value = match event_type {
+                CGEventType::KeyDown
+                    if event.get_integer_value_field(EventField::KEYBOARD_EVENT_AUTOREPEAT)
+                        != 0 =>
+                {
+                    2
+                }
+                CGEventType::KeyDown => 1,
+                CGEventType::KeyUp => 0,
+                // Modifiers only report the new flags; the key's own bit says
+                // whether it went down or up
+                CGEventType::FlagsChanged => match modifier_mask(key) {
+                    Some(mask) => i32::from(event.get_flags().bits() & mask != 0),
+                    None => return None,
+                },
+                _ => return None,
+            };
+            for hotkey_event in state.borrow_mut().key(key, value, Instant::now()) {
+                let _ = tx.try_send(hotkey_event);
+            }
+            None
+        },
+    )
+    .map_err(|_| HotkeyError::EventTap("failed to create the keyboard event tap".to_string()))?;
+
+    let source = tap
+        .mach_port
+        .create_runloop_source(0)
+        .map_err(|_| HotkeyError::EventTap("failed to create a run loop source".to_string()))?;
+    CFRunLoop::get_current().add_source(&source, unsafe { kCFRunLoopCommonModes });
+    tap.enable();
+
+    tracing::info!(
+        "Listening for {} via the macOS event tap",
+        state.borrow().keys().label()
+    );
+
+    loop {
+        match stop_rx.try_recv() {
+            Ok(_) | Err(oneshot::error::TryRecvError::Closed) => {
+                tracing::debug!("Hotkey listener stopping");
+                return Ok(());
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+        }
+        if tx.is_closed() {
+            return Ok(());
+        }
+
+        CFRunLoop::run_in_mode(
+            unsafe { kCFRunLoopDefaultMode },
+            Duration::from_millis(20),
+            true,
+        );
+
+        if let Some(event) = state.borrow_mut().poll(Instant::now()) {
+            let _ = tx.try_send(event);
+        }
+
+        // macOS disables taps whose callback was too slow; turn it back on
+        tap.enable();
+    }
+}
+
+/// Device-dependent flag bit (NX_DEVICE*KEYMASK) set while a modifier is down
+fn modifier_mask(key: KeyCode) -> Option<u64> {
+    let mask = match key {
+        59 => 0x0000_0001, // Left Control
+        56 => 0x0000_0002, // Left Shift
+        60 => 0x0000_0004, // Right Shift
+        55 => 0x0000_0008, // Left Command
+        54 => 0x0000_0010, // Right Command
+        58 => 0x0000_0020, // Left Option
+        61 => 0x0000_0040, // Right Option
+        62 => 0x0000_2000, // Right Control
+        57 => 0x0001_0000, // Caps Lock
+        63 => 0x0080_0000, // Fn
+        _ => return None,
+    };
+    Some(mask)
+}
+
+/// Parse a modifier name; ALT, CTRL, SHIFT and SUPER match either side
+fn parse_modifier(name: &str) -> Result<Vec<KeyCode>, HotkeyError> {
+    let normalized = name.trim().to_ascii_uppercase();
+    let keys = match normalized.strip_prefix("KEY_").unwrap_or(&normalized) {
+        "ALT" | "OPTION" => vec![58, 61],
+        "CTRL" | "CONTROL" => vec![59, 62],
+        "SHIFT" => vec![56, 60],
+        "SUPER" | "META" | "WIN" | "LOGO" | "CMD" | "COMMAND" => vec![55, 54],
+        _ => vec![parse_key_name(name)?],
+    };
+    Ok(keys)
+}
+
+/// Parse an evdev key name (without the KEY_ prefix) to a macOS key code
+fn parse_key_name(name: &str) -> Result<KeyCode, HotkeyError> {
+    let normalized = name.trim().to_ascii_uppercase();
+    let code = match normalized.strip_prefix("KEY_").unwrap_or(&normalized) {
+        "A" => 0,
+        "S" => 1,
+        "D" => 2,
+        "F" => 3,
+        "H" => 4,
+        "G" => 5,
+        "Z" => 6,
+        "X" => 7,
+        "C" => 8,
+        "V" => 9,
+        "B" => 11,
+        "Q" => 12,
+        "W" => 13,
+        "E" => 14,
+        "R" => 15,
+        "Y" => 16,
+        "T" => 17,
+        "1" => 18,
+        "2" => 19,
+        "3" => 20,
+        "4" => 21,
+        "6" => 22,
+        "5" => 23,
+        "EQUAL" => 24,
+        "9" => 25,
+        "7" => 26,
+        "MINUS" => 27,
+        "8" => 28,
+        "0" => 29,
+        "RIGHTBRACE" => 30,
+        "O" => 31,
+        "U" => 32,
+        "LEFTBRACE" => 33,
+        "I" => 34,
+        "P" => 35,
+        "ENTER" => 36,
+        "L" => 37,
+        "J" => 38,
+        "APOSTROPHE" => 39,
+        "K" => 40,
+        "SEMICOLON" => 41,
+        "BACKSLASH" => 42,
+        "COMMA" => 43,
+        "SLASH" => 44,
+        "N" => 45,
+        "M" => 46,
+        "DOT" => 47,
+        "TAB" => 48,
+        "SPACE" => 49,
+        "GRAVE" => 50,
+        "BACKSPACE" => 51,
+        "ESC" => 53,
+        "RIGHTMETA" | "RIGHTCMD" => 54,
+        "LEFTMETA" | "LEFTCMD" => 55,
+        "LEFTSHIFT" => 56,
+        "CAPSLOCK" => 57,
+        "LEFTALT" | "LEFTOPTION" => 58,
+        "LEFTCTRL" => 59,
+        "RIGHTSHIFT" => 60,
+        "RIGHTALT" | "RIGHTOPTION" => 61,
+        "RIGHTCTRL" => 62,
+        "FN" => 63,
+        "F17" => 64,
+        "KPDOT" => 65,
+        "KPASTERISK" => 67,
+        "KPPLUS" => 69,
+        "NUMLOCK" => 71,
+        "VOLUMEUP" => 72,
+        "VOLUMEDOWN" => 73,
+        "MUTE" => 74,
+        "KPSLASH" => 75,
+        "KPENTER" => 76,
+        "KPMINUS" => 78,
+        "F18" => 79,
+        "F19" => 80,
+        "KPEQUAL" => 81,
+        "KP0" => 82,
+        "KP1" => 83,
+        "KP2" => 84,
+        "KP3" => 85,
+        "KP4" => 86,
+        "KP5" => 87,
+        "KP6" => 88,
+        "KP7" => 89,
+        "F20" => 90,
+        "KP8" => 91,
+        "KP9" => 92,
+        "F5" => 96,
+        "F6" => 97,
+        "F7" => 98,
+        "F3" => 99,
+        "F8" => 100,
+        "F9" => 101,
+        "F11" => 103,
+        "F13" | "SYSRQ" | "PRINT" => 105,
+        "F16" => 106,
+        "F14" | "SCROLLLOCK" => 107,
+        "F10" => 109,
+        "COMPOSE" | "MENU" => 110,
+        "F12" => 111,
+        "F15" | "PAUSE" => 113,
+        "INSERT" | "HELP" => 114,
+        "HOME" => 115,
+        "PAGEUP" => 116,
+        "DELETE" => 117,
+        "F4" => 118,
+        "END" => 119,
+        "F2" => 120,
+        "PAGEDOWN" => 121,
+        "F1" => 122,
+        "LEFT" => 123,
+        "RIGHT" => 124,
+        "DOWN" => 125,
+        "UP" => 126,
+        _ => return Err(HotkeyError::UnknownKey(name.to_string())),
+    };
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(key: &str) -> HotkeyConfig {
+        toml::from_str(&format!("key = \"{}\"", key)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_key_name() {
+        assert_eq!(parse_key_name("SCROLLLOCK").unwrap(), 107);
+        assert_eq!(parse_key_name("key_rightalt").unwrap(), 61);
+        assert_eq!(parse_key_name("F13").unwrap(), 105);
+        assert!(parse_key_name("NOTAKEY").is_err());
+        assert_eq!(parse_modifier("SUPER").unwrap(), vec![55, 54]);
+    }
+
+    #[test]
+    fn test_keys_parse() {
+        let keys = Keys::parse(&config("SUPER+ALT+D"), &LAYOUT).unwrap();
+        assert_eq!(keys.key, 2);
+        assert_eq!(keys.modifiers, vec![vec![55, 54], vec![58, 61]]);
+
+        let mut conflict = config("F13");
+        conflict.cancel_key = Some("SYSRQ".to_string());
+        assert!(Keys::parse(&conflict, &LAYOUT).is_err());
+    }
+}
@@ -4,12 +4,24 @@
 //! This approach works on all Wayland compositors because it
 //! operates at the Linux input subsystem level.
 //!
-//! Requires the user to be in the 'input' group.
+//! Requires the user to be in the 'input' group. Inside a Flatpak sandbox
+//! (or with `backend = "portal"`) the XDG GlobalShortcuts portal is used
+//! instead. On macOS a Quartz event tap takes the place of evdev, and on
+//! Windows a low-level keyboard hook.
 
+#[cfg(target_os = "linux")]
 pub mod evdev_listener;
+#[cfg(any(target_os = "macos", target_os = "windows", test))]
+mod keymap;
+#[cfg(target_os = "macos")]
+pub mod macos_listener;
+pub mod portal_listener;
+#[cfg(target_os = "windows")]
+pub mod windows_listener;
 
-use crate::config::HotkeyConfig;
+use crate::config::{HotkeyBackend, HotkeyConfig};
 use crate::error::HotkeyError;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 /// Events emitted by the hotkey listener
@@ -19,11 +31,17 @@ pub enum HotkeyEvent {
     Pressed {
         /// Model to use for this transcription (None = use default)
         model_override: Option<String>,
+        /// Spell out the recording (spell modifier held)
+        spell: bool,
     },
     /// The hotkey was released
     Released,
     /// The cancel key was pressed (abort recording/transcription)
     Cancel,
+    /// The undo key was pressed (erase the last output)
+    Undo,
+    /// The model cycle key was pressed (switch to the next model)
+    CycleModel,
 }
 
 /// Trait for hotkey detection implementations
@@ -42,7 +60,152 @@ pub fn create_listener(
     config: &HotkeyConfig,
     secondary_model: Option<String>,
 ) -> Result<Box<dyn HotkeyListener>, HotkeyError> {
-    let mut listener = evdev_listener::EvdevListener::new(config)?;
-    listener.set_secondary_model(secondary_model);
-    Ok(Box::new(listener))
+    #[cfg(target_os = "macos")]
+    {
+        if config.backend != HotkeyBackend::Auto {
+            tracing::warn!(
+                "hotkey.backend = {:?} is not available on macOS, using the event tap",
+                config.backend
+            );
+        }
+        let mut listener = macos_listener::EventTapListener::new(config)?;
+        listener.set_secondary_model(secondary_model);
+        Ok(Box::new(listener))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if config.backend != HotkeyBackend::Auto {
+            tracing::warn!(
+                "hotkey.backend = {:?} is not available on Windows, using the keyboard hook",
+                config.backend
+            );
+        }
+        let mut listener = windows_listener::HookListener::new(config)?;
+        listener.set_secondary_model(secondary_model);
+        Ok(Box::new(listener))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if uses_portal(config) {
+            return Ok(Box::new(portal_listener::PortalListener::new(config)?));
+        }
+
+        let mut listener = evdev_listener::EvdevListener::new(config)?;
+        listener.set_secondary_model(secondary_model);
+        Ok(Box::new(listener))
+    }
+}
+
+/// Whether the hotkey goes through the GlobalShortcuts portal
+#[cfg(target_os = "linux")]
+fn uses_portal(config: &HotkeyConfig) -> bool {
+    match config.backend {
+        HotkeyBackend::Auto => crate::sandbox::is_flatpak(),
+        HotkeyBackend::Evdev => false,
+        HotkeyBackend::Portal => true,
+    }
+}
+
+/// Check the configured key names without starting a listener
+///
+/// Portal shortcuts are only suggestions to the desktop, so only the evdev,
+/// event tap and keyboard hook backends are checked.
+pub fn validate_config(config: &HotkeyConfig) -> Result<(), HotkeyError> {
+    #[cfg(target_os = "macos")]
+    {
+        macos_listener::validate_config(config)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_listener::validate_config(config)
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if uses_portal(config) {
+            return Ok(());
+        }
+        evdev_listener::validate_config(config)
+    }
+}
+
+/// Holds back hotkey releases so a quick release+press (switch bounce,
+/// synthetic autorepeat) doesn't stop and restart the recording
+pub(crate) struct Debouncer {
+    window: Duration,
+    /// When the key was released, if that release hasn't been sent yet
+    pending_release: Option<Instant>,
+}
+
+impl Debouncer {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending_release: None,
+        }
+    }
+
+    /// The key was released at `now`
+    pub(crate) fn release(&mut self, now: Instant) {
+        self.pending_release = Some(now);
+    }
+
+    /// The key was pressed at `now`; true if it cancels a pending release
+    pub(crate) fn press(&mut self, now: Instant) -> bool {
+        match self.pending_release {
+            Some(released) if now.duration_since(released) < self.window => {
+                self.pending_release = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the pending release has outlived the window and should be sent
+    pub(crate) fn release_due(&mut self, now: Instant) -> bool {
+        match self.pending_release {
+            Some(released) if now.duration_since(released) >= self.window => {
+                self.pending_release = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debouncer_coalesces_bounce() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut debouncer = Debouncer::new(ms(30));
+
+        // Release then press 10ms later: bounce, no events
+        debouncer.release(start);
+        assert!(!debouncer.release_due(start + ms(5)));
+        assert!(debouncer.press(start + ms(10)));
+        assert!(!debouncer.release_due(start + ms(100)));
+
+        // Release with no press: sent once the window passes
+        debouncer.release(start + ms(200));
+        assert!(!debouncer.release_due(start + ms(220)));
+        assert!(debouncer.release_due(start + ms(230)));
+        assert!(!debouncer.release_due(start + ms(240)));
+        assert!(!debouncer.press(start + ms(250)));
+    }
+
+    #[test]
+    fn test_debouncer_disabled() {
+        let now = Instant::now();
+        let mut debouncer = Debouncer::new(Duration::ZERO);
+        debouncer.release(now);
+        assert!(!debouncer.press(now));
+        assert!(debouncer.release_due(now));
+    }
 }
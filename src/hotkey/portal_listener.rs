@@ -0,0 +1,347 @@
+//! Hotkey listener using the XDG GlobalShortcuts portal
+//!
+//! Registers the hotkey (and the cancel/undo keys, if configured) with the
+//! desktop through `org.freedesktop.portal.GlobalShortcuts`. The desktop asks
+//! the user to confirm or change the shortcuts the first time, then reports
+//! presses and releases. No access to /dev/input is needed, so this is the
+//! hotkey backend inside a Flatpak sandbox.
+//!
+//! The D-Bus calls go through a small Python helper using the Gio GObject
+//! introspection bindings, which prints one line per event.
+//!
+//! Requires:
+//! - python3 with PyGObject (python3-gi / python-gobject)
+//! - A portal backend implementing GlobalShortcuts (KDE Plasma 5.27+,
+//!   GNOME 48+, Hyprland via xdg-desktop-portal-hyprland)
+
+use super::{HotkeyEvent, HotkeyListener};
+use crate::config::HotkeyConfig;
+use crate::error::HotkeyError;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{mpsc, oneshot};
+
+/// Shortcut ID of the push-to-talk hotkey
+const DICTATE: &str = "dictate";
+/// Shortcut ID of the cancel key
+const CANCEL: &str = "cancel";
+/// Shortcut ID of the undo key
+const UNDO: &str = "undo";
+/// Shortcut ID of the model cycle key
+const CYCLE_MODEL: &str = "cycle-model";
+
+/// Helper script: argv[1] is a JSON list of [id, description, trigger].
+/// Prints "activated <id>", "deactivated <id>", "bound <id> <trigger>",
+/// "ready" or "error <message>".
+const HELPER: &str = r#"
+import json
+import sys
+import gi
+gi.require_version("Gio", "2.0")
+from gi.repository import Gio, GLib
+
+DESKTOP = "org.freedesktop.portal.Desktop"
+DESKTOP_PATH = "/org/freedesktop/portal/desktop"
+SHORTCUTS = "org.freedesktop.portal.GlobalShortcuts"
+
+shortcuts = json.loads(sys.argv[1])
+bus = Gio.bus_get_sync(Gio.BusType.SESSION, None)
+sender = bus.get_unique_name()[1:].replace(".", "_")
+loop = GLib.MainLoop()
+counter = [0]
+
+def emit(line):
+    print(line, flush=True)
+
+def fail(message):
+    emit("error " + message)
+    loop.quit()
+
+def request(method, params, on_results):
+    counter[0] += 1
+    token = "voxtype%d" % counter[0]
+    path = "%s/request/%s/%s" % (DESKTOP_PATH, sender, token)
+
+    def on_response(conn, sender_name, obj, iface, signal, args):
+        bus.signal_unsubscribe(subscription)
+        code, results = args.unpack()
+        if code == 0:
+            on_results(results)
+        else:
+            fail("%s was cancelled or denied (response %d)" % (method, code))
+
+    subscription = bus.signal_subscribe(DESKTOP, "org.freedesktop.portal.Request",
+        "Response", path, None, Gio.DBusSignalFlags.NONE, on_response)
+    try:
+        bus.call_sync(DESKTOP, DESKTOP_PATH, SHORTCUTS, method, params(token),
+            None, Gio.DBusCallFlags.NONE, -1, None)
+    except GLib.Error as e:
+        fail("%s failed: %s" % (method, e.message))
+
+def listen(session):
+    def on_signal(conn, sender_name, obj, iface, signal, args):
+        values = args.unpack()
+        if values[0] == session:
+            emit("%s %s" % (signal.lower(), values[1]))
+
+    for signal in ("Activated", "Deactivated"):
+        bus.signal_subscribe(DESKTOP, SHORTCUTS, signal, DESKTOP_PATH, None,
+            Gio.DBusSignalFlags.NONE, on_signal)
+
+def bound(results):
+    for shortcut_id, props in results.get("shortcuts", []):
+        emit("bound %s %s" % (shortcut_id, props.get("trigger_description", "")))
+    emit("ready")
+
+def bind(results):
+    session = results["session_handle"]
+    listen(session)
+    entries = [(shortcut_id, {
+        "description": GLib.Variant("s", description),
+        "preferred_trigger": GLib.Variant("s", trigger),
+    }) for shortcut_id, description, trigger in shortcuts]
+    request("BindShortcuts", lambda token: GLib.Variant("(oa(sa{sv})sa{sv})",
+        (session, entries, "", {"handle_token": GLib.Variant("s", token)})), bound)
+
+def on_stdin(fd, condition):
+    loop.quit()
+    return False
+
+GLib.io_add_watch(sys.stdin.fileno(), GLib.PRIORITY_DEFAULT,
+    GLib.IOCondition.IN | GLib.IOCondition.HUP, on_stdin)
+request("CreateSession", lambda token: GLib.Variant("(a{sv})", ({
+    "handle_token": GLib.Variant("s", token),
+    "session_handle_token": GLib.Variant("s", "voxtype"),
+},)), bind)
+loop.run()
+"#;
+
+/// Hotkey listener backed by the GlobalShortcuts portal
+pub struct PortalListener {
+    /// Shortcuts to bind: ID, description and preferred trigger
+    shortcuts: Vec<(&'static str, &'static str, String)>,
+    /// Signal to stop the listener task
+    stop_signal: Option<oneshot::Sender<()>>,
+}
+
+impl PortalListener {
+    /// Create a portal listener for the configured hotkey
+    pub fn new(config: &HotkeyConfig) -> Result<Self, HotkeyError> {
+        if config.key.trim().is_empty() {
+            return Err(HotkeyError::UnknownKey(config.key.clone()));
+        }
+
+        let mut shortcuts = vec![(
+            DICTATE,
+            "Dictate (hold to record)",
+            preferred_trigger(&config.key, &config.modifiers),
+        )];
+        if let Some(ref key) = config.cancel_key {
+            shortcuts.push((CANCEL, "Cancel dictation", preferred_trigger(key, &[])));
+        }
+        if let Some(ref key) = config.undo_key {
+            shortcuts.push((UNDO, "Undo last dictation", preferred_trigger(key, &[])));
+        }
+        if let Some(ref key) = config.model_cycle_key {
+            shortcuts.push((
+                CYCLE_MODEL,
+                "Switch to the next model",
+                preferred_trigger(key, &[]),
+            ));
+        }
+
+        if config.model_modifier.is_some() {
+            tracing::warn!("hotkey.model_modifier is not supported with the portal backend");
+        }
+        if config.exclusive {
+            tracing::warn!("hotkey.exclusive has no effect with the portal backend");
+        }
+
+        Ok(Self {
+            shortcuts,
+            stop_signal: None,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl HotkeyListener for PortalListener {
+    async fn start(&mut self) -> Result<mpsc::Receiver<HotkeyEvent>, HotkeyError> {
+        let shortcuts = serde_json::to_string(&self.shortcuts)
+            .map_err(|e| HotkeyError::Portal(e.to_string()))?;
+        let mut child = Command::new("python3")
+            .args(["-c", HELPER, &shortcuts])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| HotkeyError::Portal(format!("failed to run python3: {}", e)))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| HotkeyError::Portal("no helper output".to_string()))?;
+
+        let (tx, rx) = mpsc::channel(32);
+        let (stop_tx, mut stop_rx) = oneshot::channel();
+        self.stop_signal = Some(stop_tx);
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            loop {
+                let line = tokio::select! {
+                    _ = &mut stop_rx => break,
+                    line = lines.next_line() => line,
+                };
+                let Ok(Some(line)) = line else {
+                    tracing::error!(
+                        "Global shortcuts helper exited; is python3 with PyGObject installed?"
+                    );
+                    break;
+                };
+
+                if let Some(event) = event_for(&line) {
+                    if tx.send(event).await.is_err() {
+                        break;
+                    }
+                } else if let Some(message) = line.strip_prefix("error ") {
+                    tracing::error!("Global shortcuts portal: {}", message);
+                } else if let Some(bound) = line.strip_prefix("bound ") {
+                    tracing::info!("Global shortcut bound: {}", bound);
+                } else if line == "ready" {
+                    tracing::info!("Listening for hotkey via the global shortcuts portal");
+                }
+            }
+            let _ = child.kill().await;
+        });
+
+        Ok(rx)
+    }
+
+    async fn stop(&mut self) -> Result<(), HotkeyError> {
+        if let Some(stop) = self.stop_signal.take() {
+            let _ = stop.send(());
+        }
+        Ok(())
+    }
+}
+
+/// Hotkey event for a helper line like "activated dictate"
+fn event_for(line: &str) -> Option<HotkeyEvent> {
+    match line.split_once(' ')? {
+        ("activated", DICTATE) => Some(HotkeyEvent::Pressed {
+            model_override: None,
+            spell: false,
+        }),
+        ("deactivated", DICTATE) => Some(HotkeyEvent::Released),
+        ("activated", CANCEL) => Some(HotkeyEvent::Cancel),
+        ("activated", UNDO) => Some(HotkeyEvent::Undo),
+        ("activated", CYCLE_MODEL) => Some(HotkeyEvent::CycleModel),
+        _ => None,
+    }
+}
+
+/// Translate a configured key or chord into the portal's shortcut syntax,
+/// e.g. "SUPER+ALT+D" becomes "LOGO+ALT+d" and "SCROLLLOCK" "Scroll_Lock"
+///
+/// This is only a suggestion; the user confirms or changes it in the
+/// desktop's shortcut dialog.
+fn preferred_trigger(key: &str, modifiers: &[String]) -> String {
+    let mut parts: Vec<&str> = key.split('+').map(str::trim).collect();
+    let key_name = parts.pop().unwrap_or_default();
+    let mut trigger: Vec<String> = parts
+        .into_iter()
+        .chain(modifiers.iter().map(String::as_str))
+        .map(portal_modifier)
+        .collect();
+    trigger.push(keysym_name(key_name));
+    trigger.join("+")
+}
+
+/// Portal name of a modifier; left and right variants are not distinguished
+fn portal_modifier(name: &str) -> String {
+    let upper = name.trim().to_ascii_uppercase();
+    let name = upper.strip_prefix("KEY_").unwrap_or(&upper);
+    let name = name
+        .strip_prefix("LEFT")
+        .or_else(|| name.strip_prefix("RIGHT"))
+        .unwrap_or(name);
+    match name {
+        "CTRL" | "CONTROL" => "CTRL",
+        "SUPER" | "META" | "WIN" | "LOGO" => "LOGO",
+        other => other,
+    }
+    .to_string()
+}
+
+/// XKB keysym name for an evdev key name
+fn keysym_name(key: &str) -> String {
+    let upper = key.trim().to_ascii_uppercase();
+    let name = upper.strip_prefix("KEY_").unwrap_or(&upper);
+    let keysym = match name {
+        "SCROLLLOCK" => "Scroll_Lock",
+        "PAUSE" => "Pause",
+        "ESC" => "Escape",
+        "SPACE" => "space",
+        "ENTER" => "Return",
+        "TAB" => "Tab",
+        "BACKSPACE" => "BackSpace",
+        "INSERT" => "Insert",
+        "DELETE" => "Delete",
+        "HOME" => "Home",
+        "END" => "End",
+        "PAGEUP" => "Prior",
+        "PAGEDOWN" => "Next",
+        "CAPSLOCK" => "Caps_Lock",
+        "NUMLOCK" => "Num_Lock",
+        "SYSRQ" | "PRINT" => "Print",
+        "COMPOSE" | "MENU" => "Menu",
+        "RIGHTALT" => "Alt_R",
+        "RIGHTCTRL" => "Control_R",
+        // Letters and digits; F-keys and anything else keep their name
+        _ if name.len() == 1 => return name.to_ascii_lowercase(),
+        _ => return name.to_string(),
+    };
+    keysym.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_trigger() {
+        assert_eq!(preferred_trigger("SCROLLLOCK", &[]), "Scroll_Lock");
+        assert_eq!(preferred_trigger("F13", &[]), "F13");
+        assert_eq!(preferred_trigger("SUPER+ALT+D", &[]), "LOGO+ALT+d");
+        assert_eq!(
+            preferred_trigger("PAUSE", &["LEFTCTRL".to_string(), "RIGHTSHIFT".to_string()]),
+            "CTRL+SHIFT+Pause"
+        );
+    }
+
+    #[test]
+    fn test_event_for() {
+        assert_eq!(
+            event_for("activated dictate"),
+            Some(HotkeyEvent::Pressed {
+                model_override: None,
+                spell: false,
+            })
+        );
+        assert_eq!(
+            event_for("deactivated dictate"),
+            Some(HotkeyEvent::Released)
+        );
+        assert_eq!(event_for("activated cancel"), Some(HotkeyEvent::Cancel));
+        assert_eq!(event_for("deactivated cancel"), None);
+        assert_eq!(event_for("activated undo"), Some(HotkeyEvent::Undo));
+        assert_eq!(
+            event_for("activated cycle-model"),
+            Some(HotkeyEvent::CycleModel)
+        );
+        assert_eq!(event_for("ready"), None);
+        assert_eq!(event_for("bound dictate Ctrl+Pause"), None);
+    }
+}
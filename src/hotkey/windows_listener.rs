@@ -0,0 +1,334 @@
+//! Windows hotkey listener using a low-level keyboard hook
+//!
+//! `RegisterHotKey` only reports presses, so push-to-talk can't see the
+//! release; a WH_KEYBOARD_LL hook sees every key down and up instead, for all
+//! applications and without extra permissions. The hook runs on a dedicated
+//! thread that pumps its message queue.
+//!
+//! Key names are the evdev names used on Linux, mapped to Windows virtual
+//! key codes, so configs can be shared. With `exclusive = true` the hotkey
+//! is swallowed instead of reaching the focused application.
+
+use super::keymap::{KeyCode, KeyState, Keys, Layout};
+use super::{HotkeyEvent, HotkeyListener};
+use crate::config::HotkeyConfig;
+use crate::error::HotkeyError;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use windows_sys::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, DispatchMessageW, MsgWaitForMultipleObjects, PeekMessageW, SetWindowsHookExW,
+    TranslateMessage, UnhookWindowsHookEx, HC_ACTION, KBDLLHOOKSTRUCT, LLKHF_INJECTED, LLKHF_UP,
+    MSG, PM_REMOVE, QS_ALLINPUT, WH_KEYBOARD_LL,
+};
+
+/// Windows virtual key codes (VK_*)
+const LAYOUT: Layout = Layout {
+    key: parse_key_name,
+    modifier: parse_modifier,
+    is_modifier,
+};
+
+/// Low-level keyboard hook listener
+pub struct HookListener {
+    /// Hotkey, cancel/undo keys and model modifier
+    keys: Keys,
+    /// Secondary model to use when model_modifier is held
+    secondary_model: Option<String>,
+    /// Window for ignoring a release+press bounce
+    debounce: Duration,
+    /// Swallow the hotkey so the focused application doesn't see it
+    exclusive: bool,
+    /// Signal to stop the listener task
+    stop_signal: Option<oneshot::Sender<()>>,
+}
+
+impl HookListener {
+    /// Create a new listener for the configured hotkey
+    pub fn new(config: &HotkeyConfig) -> Result<Self, HotkeyError> {
+        Ok(Self {
+            keys: Keys::parse(config, &LAYOUT)?,
+            secondary_model: None,
+            debounce: Duration::from_millis(config.debounce_ms),
+            exclusive: config.exclusive,
+            stop_signal: None,
+        })
+    }
+
+    /// Set the secondary model to use when model_modifier is held
+    pub fn set_secondary_model(&mut self, model: Option<String>) {
+        self.secondary_model = model;
+    }
+}
+
+#[async_trait::async_trait]
+impl HotkeyListener for HookListener {
+    async fn start(&mut self) -> Result<mpsc::Receiver<HotkeyEvent>, HotkeyError> {
+        let (tx, rx) = mpsc::channel(32);
+        let (stop_tx, stop_rx) = oneshot::channel();
+        self.stop_signal = Some(stop_tx);
+
+        let state = KeyState::new(
+            self.keys.clone(),
+            self.secondary_model.clone(),
+            self.debounce,
+        );
+        let exclusive = self.exclusive;
+
+        // The hook is called on the thread that installed it, while that
+        // thread reads its message queue
+        std::thread::spawn(move || {
+            if let Err(e) = hook_loop(state, exclusive, tx, stop_rx) {
+                tracing::error!("Hotkey listener error: {}", e);
+            }
+        });
+
+        Ok(rx)
+    }
+
+    async fn stop(&mut self) -> Result<(), HotkeyError> {
+        if let Some(stop) = self.stop_signal.take() {
+            let _ = stop.send(());
+        }
+        Ok(())
+    }
+}
+
+/// Check the configured key names and conflicts (for `voxtype config check`)
+pub fn validate_config(config: &HotkeyConfig) -> Result<(), HotkeyError> {
+    Keys::parse(config, &LAYOUT).map(|_| ())
+}
+
+/// State the hook procedure reads; it gets no user data pointer
+struct Hook {
+    state: KeyState,
+    exclusive: bool,
+    /// Keys currently down, to tell autorepeat from presses
+    down: HashSet<KeyCode>,
+    tx: mpsc::Sender<HotkeyEvent>,
+}
+
+thread_local! {
+    static HOOK: RefCell<Option<Hook>> = const { RefCell::new(None) };
+}
+
+/// Run the hook until stopped or the daemon goes away
+fn hook_loop(
+    state: KeyState,
+    exclusive: bool,
+    tx: mpsc::Sender<HotkeyEvent>,
+    mut stop_rx: oneshot::Receiver<()>,
+) -> Result<(), HotkeyError> {
+    tracing::info!(
+        "Listening for {} via a low-level keyboard hook",
+        state.keys().label()
+    );
+    HOOK.with(|hook| {
+        *hook.borrow_mut() = Some(Hook {
+            state,
+            exclusive,
+            down: HashSet::new(),
+            tx: tx.clone(),
+        })
+    });
+
+    let hook =
+        unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(hook_proc), std::ptr::null_mut(), 0) };
+    if hook.is_null() {
+        return Err(HotkeyError::Hook(
+            std::io::Error::last_os_error().to_string(),
+        ));
+    }
+
+    let mut msg: MSG = unsafe { std::mem::zeroed() };
+    loop {
+        match stop_rx.try_recv() {
+            Ok(_) | Err(oneshot::error::TryRecvError::Closed) => {
+                tracing::debug!("Hotkey listener stopping");
+                break;
+            }
+            Err(oneshot::error::TryRecvError::Empty) => {}
+        }
+        if tx.is_closed() {
+            break;
+        }
+
+        unsafe {
+            MsgWaitForMultipleObjects(0, std::ptr::null(), 0, 20, QS_ALLINPUT);
+            while PeekMessageW(&mut msg, std::ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+                TranslateMessage(&msg);
+                DispatchMessageW(&msg);
+            }
+        }
+
+        HOOK.with(|hook| {
+            if let Some(hook) = hook.borrow_mut().as_mut() {
+                if let Some(event) = hook.state.poll(Instant::now()) {
+                    let _ = hook.tx.try_send(event);
+                }
+            }
+        });
+    }
+
+    unsafe { UnhookWindowsHookEx(hook) };
+    HOOK.with(|hook| hook.borrow_mut().take());
+    Ok(())
+}
+
+unsafe extern "system" fn hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 {
+        let info = &*(lparam as *const KBDLLHOOKSTRUCT);
+        // Text typed by voxtype itself (SendInput) is flagged as injected
+        if info.flags & LLKHF_INJECTED == 0 {
+            let up = info.flags & LLKHF_UP != 0;
+            let swallow = HOOK.with(|hook| {
+                hook.borrow_mut()
+                    .as_mut()
+                    .is_some_and(|hook| hook.key(info.vkCode as KeyCode, up))
+            });
+            if swallow {
+                return 1;
+            }
+        }
+    }
+    CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+}
+
+impl Hook {
+    /// Feed a key event to the state; returns whether to swallow it
+    fn key(&mut self, key: KeyCode, up: bool) -> bool {
+        let value = if up {
+            self.down.remove(&key);
+            0
+        } else if self.down.insert(key) {
+            1
+        } else {
+            2
+        };
+
+        let was_pressed = self.state.is_pressed();
+        for event in self.state.key(key, value, Instant::now()) {
+            let _ = self.tx.try_send(event);
+        }
+        self.exclusive && key == self.state.keys().key && (was_pressed || self.state.is_pressed())
+    }
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    matches!(key, 0x14 | 0x5B | 0x5C | 0xA0..=0xA5)
+}
+
+/// Parse a modifier name; ALT, CTRL, SHIFT and SUPER match either side
+fn parse_modifier(name: &str) -> Result<Vec<KeyCode>, HotkeyError> {
+    let normalized = name.trim().to_ascii_uppercase();
+    let keys = match normalized.strip_prefix("KEY_").unwrap_or(&normalized) {
+        "ALT" => vec![0xA4, 0xA5],
+        "CTRL" | "CONTROL" => vec![0xA2, 0xA3],
+        "SHIFT" => vec![0xA0, 0xA1],
+        "SUPER" | "META" | "WIN" | "LOGO" => vec![0x5B, 0x5C],
+        _ => vec![parse_key_name(name)?],
+    };
+    Ok(keys)
+}
+
+/// Parse an evdev key name (without the KEY_ prefix) to a Windows virtual key
+fn parse_key_name(name: &str) -> Result<KeyCode, HotkeyError> {
+    let normalized = name.trim().to_ascii_uppercase();
+    let name_part = normalized.strip_prefix("KEY_").unwrap_or(&normalized);
+
+    // Letters and digits are their ASCII codes, F1-F24 are consecutive
+    if let [c @ (b'A'..=b'Z' | b'0'..=b'9')] = name_part.as_bytes() {
+        return Ok(*c as KeyCode);
+    }
+    if let Some(n) = name_part
+        .strip_prefix('F')
+        .and_then(|n| n.parse::<KeyCode>().ok())
+        .filter(|n| (1..=24).contains(n))
+    {
+        return Ok(0x6F + n);
+    }
+    if let Some(n) = name_part
+        .strip_prefix("KP")
+        .and_then(|n| n.parse::<KeyCode>().ok())
+        .filter(|n| *n <= 9)
+    {
+        return Ok(0x60 + n);
+    }
+
+    let code = match name_part {
+        "BACKSPACE" => 0x08,
+        "TAB" => 0x09,
+        "ENTER" | "KPENTER" => 0x0D,
+        "PAUSE" => 0x13,
+        "CAPSLOCK" => 0x14,
+        "ESC" => 0x1B,
+        "SPACE" => 0x20,
+        "PAGEUP" => 0x21,
+        "PAGEDOWN" => 0x22,
+        "END" => 0x23,
+        "HOME" => 0x24,
+        "LEFT" => 0x25,
+        "UP" => 0x26,
+        "RIGHT" => 0x27,
+        "DOWN" => 0x28,
+        "SYSRQ" | "PRINT" => 0x2C,
+        "INSERT" => 0x2D,
+        "DELETE" => 0x2E,
+        "LEFTMETA" | "LEFTWIN" => 0x5B,
+        "RIGHTMETA" | "RIGHTWIN" => 0x5C,
+        "COMPOSE" | "MENU" => 0x5D,
+        "KPASTERISK" => 0x6A,
+        "KPPLUS" => 0x6B,
+        "KPMINUS" => 0x6D,
+        "KPDOT" => 0x6E,
+        "KPSLASH" => 0x6F,
+        "NUMLOCK" => 0x90,
+        "SCROLLLOCK" => 0x91,
+        "LEFTSHIFT" => 0xA0,
+        "RIGHTSHIFT" => 0xA1,
+        "LEFTCTRL" => 0xA2,
+        "RIGHTCTRL" => 0xA3,
+        "LEFTALT" => 0xA4,
+        "RIGHTALT" => 0xA5,
+        "MUTE" => 0xAD,
+        "VOLUMEDOWN" => 0xAE,
+        "VOLUMEUP" => 0xAF,
+        "NEXTSONG" => 0xB0,
+        "PREVIOUSSONG" => 0xB1,
+        "PLAYPAUSE" => 0xB3,
+        "SEMICOLON" => 0xBA,
+        "EQUAL" => 0xBB,
+        "COMMA" => 0xBC,
+        "MINUS" => 0xBD,
+        "DOT" => 0xBE,
+        "SLASH" => 0xBF,
+        "GRAVE" => 0xC0,
+        "LEFTBRACE" => 0xDB,
+        "BACKSLASH" => 0xDC,
+        "RIGHTBRACE" => 0xDD,
+        "APOSTROPHE" => 0xDE,
+        _ => return Err(HotkeyError::UnknownKey(name.to_string())),
+    };
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_name() {
+        assert_eq!(parse_key_name("SCROLLLOCK").unwrap(), 0x91);
+        assert_eq!(parse_key_name("key_rightalt").unwrap(), 0xA5);
+        assert_eq!(parse_key_name("D").unwrap(), 0x44);
+        assert_eq!(parse_key_name("F1").unwrap(), 0x70);
+        assert_eq!(parse_key_name("F24").unwrap(), 0x87);
+        assert_eq!(parse_key_name("KP7").unwrap(), 0x67);
+        assert!(parse_key_name("F25").is_err());
+        assert!(parse_key_name("NOTAKEY").is_err());
+        assert_eq!(parse_modifier("SUPER").unwrap(), vec![0x5B, 0x5C]);
+        assert!(is_modifier(0xA3));
+    }
+}
@@ -0,0 +1,208 @@
+//! On-screen recording indicator
+//!
+//! A small dot, or bars following the input level, on the layer-shell
+//! overlay layer. It is shown only while recording, so the microphone is
+//! never left on by accident in toggle mode.
+//!
+//! Independent of the caption overlay: it has its own surface and thread, and
+//! either can be enabled alone.
+
+use crate::audio::rms_level;
+use crate::config::{IndicatorConfig, IndicatorStyle};
+use crate::overlay::{parse_color, Image, Surface};
+use crate::wayland::Connection;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Bars in the level style, oldest level on the left
+const BARS: usize = 5;
+
+/// Used when `color` doesn't parse
+const DEFAULT_COLOR: [u8; 4] = [0xe0, 0x1b, 0x24, 0xff];
+
+/// Messages from the daemon to the indicator thread
+enum Event {
+    Recording(bool),
+    /// Input level, 0.0 (silence) to 1.0 (full scale)
+    Level(f32),
+}
+
+/// Handle to the indicator thread
+pub struct Indicator {
+    events: Sender<Event>,
+    style: IndicatorStyle,
+}
+
+impl Indicator {
+    /// Start the indicator thread
+    pub fn spawn(config: &IndicatorConfig) -> Self {
+        let (events, receiver) = mpsc::channel();
+        let thread_config = config.clone();
+        std::thread::spawn(move || run(thread_config, receiver));
+        Self {
+            events,
+            style: config.style,
+        }
+    }
+
+    /// Show or hide the indicator
+    pub fn set_recording(&self, recording: bool) {
+        let _ = self.events.send(Event::Recording(recording));
+    }
+
+    /// Update the level bars with newly captured audio
+    pub fn feed(&self, samples: &[f32]) {
+        if self.style == IndicatorStyle::Level && !samples.is_empty() {
+            let _ = self.events.send(Event::Level(rms_level(samples)));
+        }
+    }
+}
+
+/// Indicator thread: map a surface while recording
+fn run(config: IndicatorConfig, events: Receiver<Event>) {
+    let color = parse_color(&config.color).unwrap_or_else(|| {
+        tracing::warn!(
+            "Indicator: invalid color {:?}, using the default",
+            config.color
+        );
+        DEFAULT_COLOR
+    });
+    let size = config.size.max(4);
+    let mut surface: Option<Surface> = None;
+    let mut levels = [0.0; BARS];
+    let mut recording = false;
+    // Don't reconnect for every level update after a failure
+    let mut failed = false;
+    let mut warned = false;
+
+    while let Ok(event) = events.recv() {
+        match event {
+            Event::Recording(on) => {
+                recording = on;
+                failed = false;
+                levels = [0.0; BARS];
+                if !on {
+                    // Disconnecting removes the surface
+                    surface = None;
+                    continue;
+                }
+            }
+            Event::Level(level) => {
+                levels.rotate_left(1);
+                levels[BARS - 1] = level;
+            }
+        }
+        if !recording || failed {
+            continue;
+        }
+
+        let image = match config.style {
+            IndicatorStyle::Dot => draw_dot(size, color),
+            IndicatorStyle::Level => draw_level(size, color, &levels),
+        };
+        let result = match surface.as_mut() {
+            Some(surface) => surface.draw(&image),
+            None => Connection::connect()
+                .and_then(|connection| {
+                    Surface::create(
+                        connection,
+                        config.position,
+                        config.margin,
+                        "voxtype-indicator",
+                    )
+                })
+                .and_then(|mut created| created.draw(&image).map(|()| created))
+                .map(|created| surface = Some(created)),
+        };
+        if let Err(e) = result {
+            surface = None;
+            failed = true;
+            if warned {
+                tracing::debug!("Indicator: {}", e);
+            } else {
+                tracing::warn!("Indicator: {}", e);
+                warned = true;
+            }
+        }
+    }
+}
+
+/// `color` at `coverage` as a premultiplied ARGB8888 pixel
+fn pixel([r, g, b, a]: [u8; 4], coverage: f32) -> [u8; 4] {
+    let alpha = a as f32 / 255.0 * coverage;
+    [b, g, r, 255].map(|c| (c as f32 * alpha).round() as u8)
+}
+
+/// An anti-aliased dot `size` pixels across
+fn draw_dot(size: u32, color: [u8; 4]) -> Image {
+    let radius = size as f32 / 2.0;
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = (x as f32 + 0.5 - radius).hypot(y as f32 + 0.5 - radius);
+            let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+            pixels.extend_from_slice(&pixel(color, coverage));
+        }
+    }
+    Image {
+        width: size,
+        height: size,
+        pixels,
+    }
+}
+
+/// Vertical bars, centered, as tall as the levels
+fn draw_level(size: u32, color: [u8; 4], levels: &[f32]) -> Image {
+    let bar = (size / 4).max(1);
+    let width = bar * (2 * levels.len() as u32 - 1);
+    let mut pixels = vec![0; (width * size * 4) as usize];
+    for (i, level) in levels.iter().enumerate() {
+        let height = ((level * size as f32).round() as u32).clamp(bar, size);
+        let top = (size - height) / 2;
+        for y in top..top + height {
+            for x in 2 * i as u32 * bar..(2 * i as u32 + 1) * bar {
+                let offset = ((y * width + x) * 4) as usize;
+                pixels[offset..offset + 4].copy_from_slice(&pixel(color, 1.0));
+            }
+        }
+    }
+    Image {
+        width,
+        height: size,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_dot() {
+        let image = draw_dot(16, [0xff, 0x00, 0x00, 0xff]);
+        assert_eq!((image.width, image.height), (16, 16));
+        let at = |x: u32, y: u32| {
+            let offset = ((y * 16 + x) * 4) as usize;
+            &image.pixels[offset..offset + 4]
+        };
+        // Red in BGRA order, transparent corners
+        assert_eq!(at(8, 8), [0, 0, 255, 255]);
+        assert_eq!(at(0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel([0xff, 0xff, 0xff, 0x80], 1.0), [128, 128, 128, 128]);
+    }
+
+    #[test]
+    fn test_draw_level() {
+        let image = draw_level(16, [0xff, 0xff, 0xff, 0xff], &[0.0, 1.0, 0.5]);
+        // Three 4px bars with 4px gaps
+        assert_eq!((image.width, image.height), (20, 16));
+        let column = |x: u32| {
+            (0..16)
+                .filter(|y| image.pixels[((y * 20 + x) * 4 + 3) as usize] == 255)
+                .count()
+        };
+        assert_eq!(column(0), 4);
+        assert_eq!(column(4), 0);
+        assert_eq!(column(8), 16);
+        assert_eq!(column(16), 8);
+    }
+}
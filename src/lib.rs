@@ -7,6 +7,11 @@
 //! - Processing text (punctuation, replacements, optional LLM post-processing)
 //! - Outputting text via wtype/dotool/ydotool/clipboard fallback chain
 //!
+//! On macOS a Quartz event tap, CoreAudio and Quartz keyboard events (or
+//! pbcopy) take the place of evdev, PipeWire and the typing tools.
+//! On Windows a low-level keyboard hook, WASAPI and SendInput (or the Win32
+//! clipboard) do.
+//!
 //! # Architecture
 //!
 //! ```text
@@ -71,22 +76,43 @@
 pub mod audio;
 pub mod cli;
 pub mod config;
+pub mod config_check;
+pub mod config_migrate;
+pub mod continuous;
 pub mod cpu;
 pub mod daemon;
+pub mod daemon_info;
 pub mod eager;
 pub mod error;
 pub mod hotkey;
+#[cfg(target_os = "linux")]
+pub mod indicator;
+pub mod log_file;
 pub mod meeting;
+pub mod model_cache;
 pub mod model_manager;
 pub mod output;
+#[cfg(target_os = "linux")]
+pub mod overlay;
+pub mod perf;
+pub mod power;
+pub mod priority;
+pub mod proxy;
+pub mod sandbox;
+pub mod script;
+pub mod secret;
 pub mod setup;
 pub mod state;
 pub mod text;
 pub mod transcribe;
 pub mod vad;
+pub mod wake;
+#[cfg(target_os = "linux")]
+mod wayland;
 
 pub use cli::{
-    Cli, Commands, CompositorType, MeetingAction, OutputModeOverride, RecordAction, SetupAction,
+    Cli, Commands, CompositorType, ConfigAction, MeetingAction, ModelAction, OutputModeOverride,
+    RecordAction, SetupAction,
 };
 pub use config::Config;
 pub use daemon::Daemon;
@@ -0,0 +1,226 @@
+//! Log file with rotation
+//!
+//! The daemon can mirror its logs to a file (`[log] file = true`) so they
+//! survive when it's started from a compositor autostart with stderr going
+//! nowhere. The file is rotated by size or by day: `voxtype.log` is renamed
+//! to `voxtype.log.1`, older files shift up, and only `keep` are retained.
+//!
+//! The tracing layer is installed before the config is loaded and writes
+//! through [`writer`]; nothing is written until [`init`] opens the file.
+
+use crate::config::{LogConfig, LogRotation};
+use chrono::{DateTime, Local, NaiveDate};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// The open log file, set once by `init`
+static LOG_FILE: OnceLock<Mutex<RotatingFile>> = OnceLock::new();
+
+/// Open the configured log file; later log lines are written to it
+///
+/// Returns the path of the log file.
+pub fn init(config: &LogConfig) -> io::Result<PathBuf> {
+    let path = config.resolved_path();
+    let file = RotatingFile::open(&path, config)?;
+    if LOG_FILE.set(Mutex::new(file)).is_err() {
+        tracing::warn!("Log file already initialized");
+    }
+    Ok(path)
+}
+
+/// `MakeWriter` for the tracing file layer
+pub fn writer() -> LogFileWriter {
+    LogFileWriter
+}
+
+/// Writes to the log file if one is open, otherwise discards
+pub struct LogFileWriter;
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match LOG_FILE.get() {
+            Some(file) => match file.lock() {
+                Ok(mut file) => file.write(buf),
+                Err(_) => Ok(buf.len()),
+            },
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match LOG_FILE.get().map(|file| file.lock()) {
+            Some(Ok(mut file)) => file.flush(),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Append-only file that rotates itself before a write
+struct RotatingFile {
+    path: PathBuf,
+    rotation: LogRotation,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    /// Bytes in the current file
+    size: u64,
+    /// Day of the last write to the current file
+    day: NaiveDate,
+}
+
+impl RotatingFile {
+    fn open(path: &Path, config: &LogConfig) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let metadata = file.metadata()?;
+        let day = metadata
+            .modified()
+            .map(|time| DateTime::<Local>::from(time).date_naive())
+            .unwrap_or_else(|_| Local::now().date_naive());
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            rotation: config.rotation,
+            max_bytes: config.max_size_mb.saturating_mul(1024 * 1024),
+            keep: config.keep,
+            file,
+            size: metadata.len(),
+            day,
+        })
+    }
+
+    /// Whether writing `incoming` bytes on `today` should start a new file
+    fn needs_rotation(&self, incoming: usize, today: NaiveDate) -> bool {
+        if self.size == 0 {
+            return false;
+        }
+        match self.rotation {
+            LogRotation::Size => self.size + incoming as u64 > self.max_bytes,
+            LogRotation::Daily => today != self.day,
+            LogRotation::Never => false,
+        }
+    }
+
+    /// Shift voxtype.log.N to .N+1 (dropping the oldest) and start a new file
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, self.keep));
+            for n in (1..self.keep).rev() {
+                let from = rotated_path(&self.path, n);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let today = Local::now().date_naive();
+        if self.needs_rotation(buf.len(), today) {
+            // Keep logging to the current file if rotation fails
+            if let Err(e) = self.rotate() {
+                eprintln!("voxtype: failed to rotate {:?}: {}", self.path, e);
+            }
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        self.day = today;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Path of the n-th rotated file, e.g. voxtype.log.2
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(rotation: LogRotation, keep: usize) -> LogConfig {
+        LogConfig {
+            file: true,
+            rotation,
+            max_size_mb: 1,
+            keep,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_size_rotation_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voxtype.log");
+        let mut file = RotatingFile::open(&path, &config(LogRotation::Size, 2)).unwrap();
+        file.max_bytes = 10;
+
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "third\n"
+        );
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 2)).unwrap(),
+            "second\n"
+        );
+        assert!(!rotated_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_daily_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("voxtype.log");
+        let mut file = RotatingFile::open(&path, &config(LogRotation::Daily, 5)).unwrap();
+        file.write_all(b"today\n").unwrap();
+
+        let today = Local::now().date_naive();
+        assert!(!file.needs_rotation(100, today));
+        assert!(file.needs_rotation(1, today.succ_opt().unwrap()));
+
+        file.day = today.pred_opt().unwrap();
+        file.write_all(b"tomorrow\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "tomorrow\n");
+        assert_eq!(
+            fs::read_to_string(rotated_path(&path, 1)).unwrap(),
+            "today\n"
+        );
+    }
+
+    #[test]
+    fn test_never_rotates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("voxtype.log");
+        let mut file = RotatingFile::open(&path, &config(LogRotation::Never, 5)).unwrap();
+        file.max_bytes = 1;
+        file.write_all(b"one\n").unwrap();
+        file.write_all(b"two\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+    }
+}
@@ -7,16 +7,18 @@
 use clap::Parser;
 use std::path::PathBuf;
 use std::process::Command;
+use tracing_subscriber::prelude::*;
 use tracing_subscriber::EnvFilter;
 use voxtype::{
-    config, cpu, daemon, meeting, setup, transcribe, vad, Cli, Commands, MeetingAction,
-    RecordAction, SetupAction,
+    audio, config, config_check, cpu, daemon, log_file, meeting, model_cache, priority, proxy,
+    sandbox, secret, setup, transcribe, vad, Cli, Commands, ConfigAction, MeetingAction,
+    ModelAction, RecordAction, SetupAction,
 };
 
-/// Parse a comma-separated list of driver names into OutputDriver vec
-fn parse_driver_order(s: &str) -> Result<Vec<config::OutputDriver>, String> {
+/// Parse a comma-separated list of driver names into driver_order entries
+fn parse_driver_order(s: &str) -> Result<Vec<config::DriverEntry>, String> {
     s.split(',')
-        .map(|d| d.trim().parse::<config::OutputDriver>())
+        .map(|d| d.trim().parse::<config::OutputDriver>().map(Into::into))
         .collect()
 }
 
@@ -69,7 +71,7 @@ async fn main() -> anyhow::Result<()> {
     };
 
     if is_worker {
-        // Worker uses stderr for logging (stdout is reserved for IPC protocol)
+        // Worker logs to stderr, which the daemon passes through to its own
         tracing_subscriber::fmt()
             .with_env_filter(
                 EnvFilter::try_from_default_env()
@@ -79,18 +81,62 @@ async fn main() -> anyhow::Result<()> {
             .with_writer(std::io::stderr)
             .init();
     } else {
-        tracing_subscriber::fmt()
-            .with_env_filter(
+        // The file layer stays silent until the daemon opens the log file ([log] file = true)
+        tracing_subscriber::registry()
+            .with(
                 EnvFilter::try_from_default_env()
                     .unwrap_or_else(|_| EnvFilter::new(format!("voxtype={},warn", log_level))),
             )
-            .with_target(false)
+            .with(tracing_subscriber::fmt::layer().with_target(false))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_target(false)
+                    .with_ansi(false)
+                    .with_writer(log_file::writer),
+            )
             .init();
     }
 
+    // Select the instance before any config or runtime path is resolved
+    if let Some(name) = cli
+        .instance
+        .clone()
+        .or_else(|| std::env::var("VOXTYPE_INSTANCE").ok())
+    {
+        config::Config::set_instance(&name)?;
+    }
+
+    // `config check` reports problems in the file itself, so it runs before
+    // loading, which would stop at the first one
+    if let Some(Commands::Config {
+        action: Some(ConfigAction::Check),
+    }) = cli.command
+    {
+        let report = config_check::check_file(cli.config.as_deref());
+        match report.path {
+            Some(ref path) => println!("Checking {}", path.display()),
+            None => println!("No config file found, checking built-in defaults"),
+        }
+        println!("{}", report.summary());
+        std::process::exit(if report.has_errors() { 1 } else { 0 });
+    }
+
+    // `config migrate` upgrades files that may not load as they are
+    if let Some(Commands::Config {
+        action: Some(ConfigAction::Migrate { write }),
+    }) = cli.command
+    {
+        return migrate_config(cli.config.as_deref(), write);
+    }
+
     // Load configuration
     let config_path = cli.config.clone().or_else(config::Config::default_path);
     let mut config = config::load_config(cli.config.as_deref())?;
+    if let Some(ref dir) = config.models_dir {
+        config::Config::set_models_dir(dir);
+    }
+    priority::init(&config.inference);
+    proxy::init(&config.network);
 
     // Apply CLI overrides
     if cli.clipboard {
@@ -107,7 +153,7 @@ async fn main() -> anyhow::Result<()> {
     }
     let top_level_model = cli.model.clone();
     if let Some(model) = cli.model {
-        if setup::model::is_valid_model(&model) {
+        if setup::model::is_valid_model(&model) || transcribe::models::is_path(&model) {
             config.whisper.model = model;
         } else {
             let default_model = &config.whisper.model;
@@ -116,14 +162,20 @@ async fn main() -> anyhow::Result<()> {
                 model,
                 default_model
             );
-            let _ = Command::new("notify-send")
-                .args([
-                    "--app-name=Voxtype",
-                    "--expire-time=5000",
-                    "Voxtype: Invalid Model",
-                    &format!("Unknown model '{}', using '{}'", model, default_model),
-                ])
-                .spawn();
+            let message = format!("Unknown model '{}', using '{}'", model, default_model);
+            if sandbox::is_flatpak() {
+                sandbox::send_notification("Voxtype: Invalid Model", &message, "normal")
+                    .await;
+            } else {
+                let _ = Command::new("notify-send")
+                    .args([
+                        "--app-name=Voxtype",
+                        "--expire-time=5000",
+                        "Voxtype: Invalid Model",
+                        &message,
+                    ])
+                    .spawn();
+            }
         }
     }
     if let Some(engine) = cli.engine {
@@ -135,9 +187,10 @@ async fn main() -> anyhow::Result<()> {
             "paraformer" => config.engine = config::TranscriptionEngine::Paraformer,
             "dolphin" => config.engine = config::TranscriptionEngine::Dolphin,
             "omnilingual" => config.engine = config::TranscriptionEngine::Omnilingual,
+            "onnx" => config.engine = config::TranscriptionEngine::Onnx,
             _ => {
                 eprintln!(
-                    "Error: Invalid engine '{}'. Valid options: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual",
+                    "Error: Invalid engine '{}'. Valid options: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual, onnx",
                     engine
                 );
                 std::process::exit(1);
@@ -158,9 +211,15 @@ async fn main() -> anyhow::Result<()> {
     if let Some(cancel_key) = cli.cancel_key {
         config.hotkey.cancel_key = Some(cancel_key);
     }
+    if let Some(undo_key) = cli.undo_key {
+        config.hotkey.undo_key = Some(undo_key);
+    }
     if let Some(model_modifier) = cli.model_modifier {
         config.hotkey.model_modifier = Some(model_modifier);
     }
+    if let Some(model_cycle_key) = cli.model_cycle_key {
+        config.hotkey.model_cycle_key = Some(model_cycle_key);
+    }
 
     // Whisper overrides
     if let Some(delay) = cli.pre_type_delay {
@@ -176,6 +235,15 @@ async fn main() -> anyhow::Result<()> {
     if let Some(prompt) = cli.initial_prompt {
         config.whisper.initial_prompt = Some(prompt);
     }
+    if let Some(min) = cli.min_confidence {
+        config.whisper.min_confidence = min;
+    }
+    if let Some(threshold) = cli.no_speech_threshold {
+        config.whisper.no_speech_threshold = Some(threshold);
+    }
+    if cli.keep_non_speech_tokens {
+        config.whisper.suppress_non_speech_tokens = false;
+    }
     if let Some(lang) = cli.language {
         config.whisper.language = config::LanguageConfig::from_comma_separated(&lang);
     }
@@ -195,10 +263,12 @@ async fn main() -> anyhow::Result<()> {
         match mode.to_lowercase().as_str() {
             "local" => config.whisper.mode = Some(config::WhisperMode::Local),
             "remote" => config.whisper.mode = Some(config::WhisperMode::Remote),
+            "worker" => config.whisper.mode = Some(config::WhisperMode::Worker),
             "cli" => config.whisper.mode = Some(config::WhisperMode::Cli),
+            "faster-whisper" => config.whisper.mode = Some(config::WhisperMode::FasterWhisper),
             _ => {
                 eprintln!(
-                    "Error: Invalid whisper mode '{}'. Valid options: local, remote, cli",
+                    "Error: Invalid whisper mode '{}'. Valid options: local, remote, worker, cli, faster-whisper",
                     mode
                 );
                 std::process::exit(1);
@@ -225,6 +295,15 @@ async fn main() -> anyhow::Result<()> {
     if let Some(device) = cli.audio_device {
         config.audio.device = device;
     }
+    if let Some(source) = cli.audio_source {
+        match source.parse() {
+            Ok(source) => config.audio.source = source,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
     if let Some(max_dur) = cli.max_duration {
         config.audio.max_duration_secs = max_dur;
     }
@@ -274,6 +353,9 @@ async fn main() -> anyhow::Result<()> {
     if cli.spoken_punctuation {
         config.text.spoken_punctuation = true;
     }
+    if cli.spoken_numbers {
+        config.text.spoken_numbers = true;
+    }
     if let Some(keys) = cli.paste_keys {
         config.output.paste_keys = Some(keys);
     }
@@ -337,11 +419,44 @@ async fn main() -> anyhow::Result<()> {
     // Run the appropriate command
     match cli.command.unwrap_or(Commands::Daemon) {
         Commands::Daemon => {
-            let mut daemon = daemon::Daemon::new(config, config_path);
+            if config.log.file {
+                match log_file::init(&config.log) {
+                    Ok(path) => tracing::info!("Logging to {:?}", path),
+                    Err(e) => tracing::warn!("Failed to open log file: {}", e),
+                }
+            }
+            // Refuse to start on a config that would fail mid-run
+            let contents = config_path
+                .as_ref()
+                .and_then(|path| std::fs::read_to_string(path).ok());
+            let report = config_check::CheckReport {
+                path: config_path.clone().filter(|path| path.exists()),
+                issues: config_check::check(&config, contents.as_deref()),
+            };
+            if report.has_errors() {
+                anyhow::bail!("Invalid configuration:\n{}", report.summary());
+            }
+            for issue in &report.issues {
+                tracing::warn!("Config: {}", issue);
+            }
+
+            let mut daemon = daemon::Daemon::new(config, config_path).with_replace(cli.replace);
             daemon.run().await?;
         }
 
-        Commands::Transcribe { file, engine } => {
+        Commands::Transcribe {
+            file,
+            engine,
+            format,
+            diarize,
+        } => {
+            let format: transcribe::format::SegmentFormat = match format.parse() {
+                Ok(format) => format,
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            };
             if let Some(engine_name) = engine {
                 match engine_name.to_lowercase().as_str() {
                     "whisper" => config.engine = config::TranscriptionEngine::Whisper,
@@ -351,13 +466,14 @@ async fn main() -> anyhow::Result<()> {
                     "paraformer" => config.engine = config::TranscriptionEngine::Paraformer,
                     "dolphin" => config.engine = config::TranscriptionEngine::Dolphin,
                     "omnilingual" => config.engine = config::TranscriptionEngine::Omnilingual,
+                    "onnx" => config.engine = config::TranscriptionEngine::Onnx,
                     _ => {
-                        eprintln!("Error: Invalid engine '{}'. Valid options: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual", engine_name);
+                        eprintln!("Error: Invalid engine '{}'. Valid options: whisper, parakeet, moonshine, sensevoice, paraformer, dolphin, omnilingual, onnx", engine_name);
                         std::process::exit(1);
                     }
                 }
             }
-            transcribe_file(&config, &file)?;
+            transcribe_file(&config, &file, format, diarize)?;
         }
 
         Commands::TranscribeWorker {
@@ -365,6 +481,10 @@ async fn main() -> anyhow::Result<()> {
             language,
             translate,
             threads,
+            no_speech_threshold,
+            keep_non_speech_tokens,
+            cpu,
+            socket_fd,
         } => {
             // Internal command: run transcription worker process
             // This is spawned by the daemon when gpu_isolation is enabled
@@ -383,7 +503,36 @@ async fn main() -> anyhow::Result<()> {
             if let Some(t) = threads {
                 whisper_config.threads = Some(t);
             }
-            transcribe::worker::run_worker(&whisper_config)?;
+            if let Some(threshold) = no_speech_threshold {
+                whisper_config.no_speech_threshold = Some(threshold);
+            }
+            if keep_non_speech_tokens {
+                whisper_config.suppress_non_speech_tokens = false;
+            }
+            if cpu {
+                whisper_config.gpu_backend = config::GpuBackend::Cpu;
+            }
+            // The worker is a process of its own, so all of it can run at the
+            // lower priority
+            priority::apply_to_current_thread(&config.inference);
+            transcribe::worker::run_worker(&whisper_config, socket_fd)?;
+        }
+
+        Commands::Serve {
+            listen,
+            tls_cert,
+            tls_key,
+            http,
+            wyoming,
+        } => {
+            let tls = tls_cert.as_deref().zip(tls_key.as_deref());
+            transcribe::remote_worker::serve(
+                &config.whisper,
+                &listen,
+                tls,
+                http.as_deref(),
+                wyoming.as_deref(),
+            )?;
         }
 
         Commands::Setup {
@@ -461,7 +610,7 @@ async fn main() -> anyhow::Result<()> {
                     if status {
                         setup::gpu::show_status();
                     } else if enable {
-                        setup::gpu::enable()?;
+                        setup::gpu::enable(config.whisper.gpu_backend)?;
                     } else if disable {
                         setup::gpu::disable()?;
                     } else {
@@ -503,6 +652,14 @@ async fn main() -> anyhow::Result<()> {
                         setup::vad::download_model()?;
                     }
                 }
+                Some(SetupAction::WakeWord { status }) => {
+                    warn_if_root("wake-word");
+                    if status {
+                        setup::wake_word::show_status(&config);
+                    } else {
+                        setup::wake_word::download_models(&config)?;
+                    }
+                }
                 None => {
                     // Default: run setup (non-blocking)
                     warn_if_root("");
@@ -512,17 +669,44 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
-        Commands::Config => {
+        Commands::Config { .. } => {
             show_config(&config).await?;
         }
 
+        Commands::Devices => {
+            list_devices(&config)?;
+        }
+
+        Commands::Monitor { duration } => {
+            run_monitor(&config, duration).await?;
+        }
+
         Commands::Status {
             follow,
             format,
+            json,
             extended,
             icon_theme,
+            detail,
+            report,
+            level,
         } => {
-            run_status(&config, follow, &format, extended, icon_theme).await?;
+            let format = if json { "json".to_string() } else { format };
+            if report {
+                print_perf_report(&format)?;
+            } else if detail {
+                print_daemon_detail(&config, &format)?;
+            } else {
+                run_status(&config, follow, &format, extended, icon_theme, false, level).await?;
+            }
+        }
+
+        Commands::Waybar {
+            extended,
+            icon_theme,
+            level,
+        } => {
+            run_status(&config, true, "json", extended, icon_theme, true, level).await?;
         }
 
         Commands::Record { action } => {
@@ -532,11 +716,79 @@ async fn main() -> anyhow::Result<()> {
         Commands::Meeting { action } => {
             run_meeting_command(&config, action).await?;
         }
+
+        Commands::Model { action } => match action {
+            ModelAction::Prune { days, dry_run } => prune_models(&config, days, dry_run)?,
+            ModelAction::Use { model } => switch_model(Some(&model))?,
+            ModelAction::Next => switch_model(None)?,
+        },
     }
 
     Ok(())
 }
 
+/// Remove models unused for `days`, keeping those the config refers to
+fn prune_models(config: &config::Config, days: u64, dry_run: bool) -> anyhow::Result<()> {
+    let dir = config::Config::models_dir();
+    let max_age = std::time::Duration::from_secs(days * 24 * 60 * 60);
+    let keep = model_cache::configured_models(config);
+    let stale = model_cache::stale_models(
+        model_cache::cached_models(&dir),
+        max_age,
+        std::time::SystemTime::now(),
+        &keep,
+    );
+
+    if stale.is_empty() {
+        println!("No models in {:?} unused for {} days.", dir, days);
+        return Ok(());
+    }
+
+    let mut freed = 0;
+    for model in &stale {
+        let size_mb = model.size as f64 / 1_048_576.0;
+        if dry_run {
+            println!("Would remove {} ({:.0} MB)", model.name, size_mb);
+        } else if let Err(e) = model_cache::remove(model) {
+            eprintln!("Could not remove {}: {}", model.name, e);
+            continue;
+        } else {
+            println!("Removed {} ({:.0} MB)", model.name, size_mb);
+        }
+        freed += model.size;
+    }
+
+    let verb = if dry_run { "Would free" } else { "Freed" };
+    println!("{} {:.0} MB.", verb, freed as f64 / 1_048_576.0);
+    Ok(())
+}
+
+/// Ask the running daemon to switch to `model`, or to the next model
+///
+/// The request is a file trigger (the model name, empty for the next model)
+/// that the daemon picks up once it's idle.
+fn switch_model(model: Option<&str>) -> anyhow::Result<()> {
+    if let Some(model) = model {
+        if !setup::model::is_valid_model(model) && !transcribe::models::is_path(model) {
+            anyhow::bail!(
+                "Unknown model '{}'. Valid models: {}",
+                model,
+                setup::model::valid_model_names().join(", ")
+            );
+        }
+    }
+    check_daemon_running()?;
+
+    let switch_file = config::Config::runtime_dir().join("model_switch");
+    std::fs::write(&switch_file, model.unwrap_or_default())
+        .map_err(|e| anyhow::anyhow!("Failed to write model switch file: {}", e))?;
+    match model {
+        Some(model) => println!("Switching to model '{}'.", model),
+        None => println!("Switching to the next model."),
+    }
+    Ok(())
+}
+
 /// Check if the daemon is running, exit with error if not
 fn check_daemon_running() -> anyhow::Result<()> {
     use nix::sys::signal::kill;
@@ -614,6 +866,14 @@ fn send_record_command(
         return Ok(());
     }
 
+    // Undo also uses a file trigger, picked up by the daemon while idle
+    if matches!(action, RecordAction::Undo) {
+        let undo_file = config::Config::runtime_dir().join("undo");
+        std::fs::write(&undo_file, "undo")
+            .map_err(|e| anyhow::anyhow!("Failed to write undo file: {}", e))?;
+        return Ok(());
+    }
+
     // Write output mode override file if specified
     // For file mode, format is "file" or "file:/path/to/file"
     if let Some(mode_override) = action.output_mode_override() {
@@ -693,24 +953,15 @@ fn send_record_command(
         RecordAction::Start { .. } => Signal::SIGUSR1,
         RecordAction::Stop { .. } => Signal::SIGUSR2,
         RecordAction::Toggle { .. } => {
-            // Read current state to determine action
-            let state_file = match config.resolve_state_file() {
-                Some(path) => path,
-                None => {
-                    eprintln!("Error: Cannot toggle recording without state_file configured.");
-                    eprintln!();
-                    eprintln!("Add to your config.toml:");
-                    eprintln!("  state_file = \"auto\"");
-                    eprintln!();
-                    eprintln!("Or use explicit start/stop commands:");
-                    eprintln!("  voxtype record start");
-                    eprintln!("  voxtype record stop");
-                    std::process::exit(1);
-                }
-            };
-
-            let current_state =
-                std::fs::read_to_string(&state_file).unwrap_or_else(|_| "idle".to_string());
+            // The daemon keeps its state in daemon.json whether or not state_file is set
+            let current_state = voxtype::daemon_info::DaemonInfo::read()
+                .map(|info| info.state)
+                .or_else(|| {
+                    config
+                        .resolve_state_file()
+                        .and_then(|path| std::fs::read_to_string(path).ok())
+                })
+                .unwrap_or_else(|| "idle".to_string());
 
             if current_state.trim() == "recording" {
                 Signal::SIGUSR2 // Stop
@@ -718,7 +969,7 @@ fn send_record_command(
                 Signal::SIGUSR1 // Start
             }
         }
-        RecordAction::Cancel => unreachable!(), // Handled above
+        RecordAction::Cancel | RecordAction::Undo => unreachable!(), // Handled above
     };
 
     kill(Pid::from_raw(pid), signal)
@@ -728,15 +979,44 @@ fn send_record_command(
 }
 
 /// Transcribe an audio file
-fn transcribe_file(config: &config::Config, path: &PathBuf) -> anyhow::Result<()> {
+fn transcribe_file(
+    config: &config::Config,
+    path: &PathBuf,
+    format: transcribe::format::SegmentFormat,
+    diarize: bool,
+) -> anyhow::Result<()> {
     use hound::WavReader;
+    use transcribe::format::{format_segments, SegmentFormat};
+
+    // Load the speaker model up front so a missing model fails fast
+    let diarizer = if diarize {
+        let diarization: meeting::DiarizationConfig = (&config.meeting.diarization).into();
+        match meeting::diarization::create_ml_diarizer(&diarization) {
+            Ok(diarizer) => Some(diarizer),
+            Err(e) => anyhow::bail!("Speaker diarization unavailable: {}", e),
+        }
+    } else {
+        None
+    };
 
-    println!("Loading audio file: {:?}", path);
+    // Keep stdout clean for subtitle/timestamp output so it can be redirected
+    let plain = format == SegmentFormat::Text;
+    macro_rules! progress {
+        ($($arg:tt)*) => {
+            if plain {
+                println!($($arg)*);
+            } else {
+                eprintln!($($arg)*);
+            }
+        };
+    }
+
+    progress!("Loading audio file: {:?}", path);
 
     let reader = WavReader::open(path)?;
     let spec = reader.spec();
 
-    println!(
+    progress!(
         "Audio format: {} Hz, {} channel(s), {:?}",
         spec.sample_rate, spec.channels, spec.sample_format
     );
@@ -769,13 +1049,13 @@ fn transcribe_file(config: &config::Config, path: &PathBuf) -> anyhow::Result<()
 
     // Resample to 16kHz if needed
     let final_samples = if spec.sample_rate != 16000 {
-        println!("Resampling from {} Hz to 16000 Hz...", spec.sample_rate);
+        progress!("Resampling from {} Hz to 16000 Hz...", spec.sample_rate);
         resample(&mono_samples, spec.sample_rate, 16000)
     } else {
         mono_samples
     };
 
-    println!(
+    progress!(
         "Processing {} samples ({:.2}s)...",
         final_samples.len(),
         final_samples.len() as f32 / 16000.0
@@ -785,13 +1065,13 @@ fn transcribe_file(config: &config::Config, path: &PathBuf) -> anyhow::Result<()
     if let Ok(Some(vad)) = vad::create_vad(config) {
         match vad.detect(&final_samples) {
             Ok(result) => {
-                println!(
+                progress!(
                     "VAD: {:.2}s speech ({:.1}% of audio)",
                     result.speech_duration_secs,
                     result.speech_ratio * 100.0
                 );
                 if !result.has_speech {
-                    println!("No speech detected, skipping transcription.");
+                    progress!("No speech detected, skipping transcription.");
                     return Ok(());
                 }
             }
@@ -802,11 +1082,35 @@ fn transcribe_file(config: &config::Config, path: &PathBuf) -> anyhow::Result<()
         }
     }
 
-    // Create transcriber and transcribe
-    let transcriber = transcribe::create_transcriber(config)?;
-    let text = transcriber.transcribe(&final_samples)?;
+    // Create transcriber and transcribe, timed for comparing models
+    let load_start = std::time::Instant::now();
+    let transcriber = transcribe::create_transcriber_chain(config)?;
+    progress!("Model loaded in {:.2}s", load_start.elapsed().as_secs_f32());
+
+    let start = std::time::Instant::now();
+    let output = if let Some(diarizer) = diarizer {
+        let mut segments = transcriber.transcribe_segments(&final_samples)?;
+        progress!("Identifying speakers...");
+        meeting::diarization::label_segments(diarizer.as_ref(), &final_samples, &mut segments);
+        format_segments(&segments, format)
+    } else if plain {
+        transcriber.transcribe(&final_samples)?
+    } else {
+        format_segments(&transcriber.transcribe_segments(&final_samples)?, format)
+    };
+    let elapsed = start.elapsed().as_secs_f32();
+    let audio_secs = final_samples.len() as f32 / 16000.0;
+    progress!(
+        "Transcribed in {:.2}s ({:.1}x realtime)",
+        elapsed,
+        audio_secs / elapsed.max(0.001)
+    );
 
-    println!("\n{}", text);
+    if plain {
+        println!("\n{}", output);
+    } else {
+        print!("{}", output);
+    }
     Ok(())
 }
 
@@ -846,22 +1150,27 @@ struct ExtendedStatusInfo {
 
 impl ExtendedStatusInfo {
     fn from_config(config: &config::Config) -> Self {
-        let backend = setup::gpu::detect_current_backend()
+        Self {
+            model: config.whisper.model.clone(),
+            device: config.audio.device.clone(),
+            backend: Self::detect_backend(),
+        }
+    }
+
+    /// Human-readable name of the installed compute backend
+    fn detect_backend() -> String {
+        setup::gpu::detect_current_backend()
             .map(|b| match b {
                 setup::gpu::Backend::Cpu => "CPU (legacy)",
                 setup::gpu::Backend::Native => "CPU (native)",
                 setup::gpu::Backend::Avx2 => "CPU (AVX2)",
                 setup::gpu::Backend::Avx512 => "CPU (AVX-512)",
                 setup::gpu::Backend::Vulkan => "GPU (Vulkan)",
+                setup::gpu::Backend::Cuda => "GPU (CUDA)",
+                setup::gpu::Backend::Rocm => "GPU (ROCm)",
             })
             .unwrap_or("unknown")
-            .to_string();
-
-        Self {
-            model: config.whisper.model.clone(),
-            device: config.audio.device.clone(),
-            backend,
-        }
+            .to_string()
     }
 }
 
@@ -884,6 +1193,144 @@ fn is_daemon_running() -> bool {
     std::path::Path::new(&format!("/proc/{}", pid)).exists()
 }
 
+/// Upgrade the config file to the current schema (`voxtype config migrate`)
+fn migrate_config(path: Option<&std::path::Path>, write: bool) -> anyhow::Result<()> {
+    use voxtype::config_migrate::{self, CURRENT_VERSION};
+
+    let Some(path) = path
+        .map(PathBuf::from)
+        .or_else(config::Config::default_path)
+    else {
+        anyhow::bail!("Could not determine config file location");
+    };
+    if !path.exists() {
+        anyhow::bail!("No config file at {}", path.display());
+    }
+    let contents = std::fs::read_to_string(&path)?;
+    let (migrated, migrations) = config_migrate::migrate_text(&contents)
+        .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+
+    if migrated == contents {
+        println!(
+            "{} is up to date (version {})",
+            path.display(),
+            CURRENT_VERSION
+        );
+        return Ok(());
+    }
+    for migration in &migrations {
+        println!("  {}", migration);
+    }
+    println!("  version set to {}", CURRENT_VERSION);
+
+    if !write {
+        println!();
+        println!(
+            "Run 'voxtype config migrate --write' to update {}",
+            path.display()
+        );
+        return Ok(());
+    }
+    let backup = path.with_extension("toml.bak");
+    std::fs::copy(&path, &backup)?;
+    std::fs::write(&path, migrated)?;
+    println!();
+    println!("Updated {} (backup: {})", path.display(), backup.display());
+    Ok(())
+}
+
+/// Print the performance report of the last dictation
+fn print_perf_report(format: &str) -> anyhow::Result<()> {
+    let Some(report) = voxtype::perf::PerfReport::read() else {
+        println!("No performance report yet. Dictate something with the daemon running.");
+        return Ok(());
+    };
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("{}", report.summary().replace(" | ", "\n"));
+    }
+    Ok(())
+}
+
+/// Print the running daemon's status snapshot
+fn print_daemon_detail(config: &config::Config, format: &str) -> anyhow::Result<()> {
+    use voxtype::daemon_info::{format_duration, DaemonInfo};
+    use voxtype::transcribe::usage::{Budget, Usage};
+
+    let info = DaemonInfo::read().filter(|_| is_daemon_running());
+    let Some(info) = info else {
+        if format == "json" {
+            println!(r#"{{"state": "stopped"}}"#);
+        } else {
+            println!("stopped");
+        }
+        return Ok(());
+    };
+
+    let now = chrono::Local::now();
+    let backend = ExtendedStatusInfo::detect_backend();
+    // Audio sent to a paid API counts against [whisper] remote_*_limit_mins
+    let today = now.date_naive();
+    let remote_usage = (config.engine == config::TranscriptionEngine::Whisper
+        && config.whisper.effective_mode() == config::WhisperMode::Remote)
+        .then(|| (Usage::read(), Budget::from_config(&config.whisper)));
+    if format == "json" {
+        let mut value = serde_json::to_value(&info)?;
+        value["uptime_secs"] = info.uptime_secs(now).into();
+        value["backend"] = backend.into();
+        if let Some((usage, budget)) = remote_usage {
+            value["remote_usage"] = serde_json::json!({
+                "today_secs": usage.today_secs(today).round(),
+                "month_secs": usage.month_secs(today).round(),
+                "daily_limit_secs": budget.daily_secs,
+                "monthly_limit_secs": budget.monthly_secs,
+            });
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    match info.progress {
+        Some(percent) => println!("State:    {} ({}%)", info.state, percent),
+        None => println!("State:    {}", info.state),
+    }
+    println!("PID:      {}", info.pid);
+    println!("Uptime:   {}", format_duration(info.uptime_secs(now)));
+    println!("Engine:   {:?}", info.engine);
+    println!("Model:    {}", info.model);
+    println!("Audio:    {}", info.audio_device);
+    println!("Backend:  {}", backend);
+    if let Some((usage, budget)) = remote_usage {
+        let used = |secs: f64, limit: Option<f64>| match limit {
+            Some(limit) => format!(
+                "{} of {}",
+                format_duration(secs as u64),
+                format_duration(limit as u64)
+            ),
+            None => format_duration(secs as u64),
+        };
+        println!(
+            "Remote:   {} today, {} this month",
+            used(usage.today_secs(today), budget.daily_secs),
+            used(usage.month_secs(today), budget.monthly_secs)
+        );
+    }
+    println!(
+        "Output:   {}",
+        info.output_method.as_deref().unwrap_or("none available")
+    );
+    match (info.last_transcription_at, info.last_latency_ms) {
+        (Some(at), Some(latency_ms)) => println!(
+            "Last:     {} ago, {:.2}s latency",
+            format_duration((now - at).num_seconds().max(0) as u64),
+            latency_ms as f64 / 1000.0
+        ),
+        _ => println!("Last:     no dictation yet"),
+    }
+    Ok(())
+}
+
 /// Run the status command - show current daemon state
 async fn run_status(
     config: &config::Config,
@@ -891,6 +1338,8 @@ async fn run_status(
     format: &str,
     extended: bool,
     icon_theme_override: Option<String>,
+    show_elapsed: bool,
+    show_level: bool,
 ) -> anyhow::Result<()> {
     let state_file = config.resolve_state_file();
 
@@ -930,11 +1379,20 @@ async fn run_status(
             std::fs::read_to_string(&state_path).unwrap_or_else(|_| "stopped".to_string())
         };
         let state = state.trim();
+        let level = if show_level {
+            recording_level(state)
+        } else {
+            None
+        };
 
         if format == "json" {
-            println!("{}", format_state_json(state, &icons, ext_info.as_ref()));
+            let progress = transcribing_progress(state);
+            println!(
+                "{}",
+                format_state_json(state, progress, None, level, &icons, ext_info.as_ref())
+            );
         } else {
-            println!("{}", state);
+            println!("{}", format_state_text(state, level));
         }
         return Ok(());
     }
@@ -951,10 +1409,37 @@ async fn run_status(
         std::fs::read_to_string(&state_path).unwrap_or_else(|_| "stopped".to_string())
     };
     let state = state.trim();
+    let mut last_progress = transcribing_progress(state);
+    let elapsed = |state: &str| {
+        if show_elapsed {
+            recording_secs(state)
+        } else {
+            None
+        }
+    };
+    let mut last_elapsed = elapsed(state);
+    let level = |state: &str| {
+        if show_level {
+            recording_level(state)
+        } else {
+            None
+        }
+    };
+    let mut last_level = level(state);
     if format == "json" {
-        println!("{}", format_state_json(state, &icons, ext_info.as_ref()));
+        println!(
+            "{}",
+            format_state_json(
+                state,
+                last_progress,
+                last_elapsed,
+                last_level,
+                &icons,
+                ext_info.as_ref()
+            )
+        );
     } else {
-        println!("{}", state);
+        println!("{}", format_state_text(state, last_level));
     }
 
     // Set up file watcher
@@ -966,7 +1451,8 @@ async fn run_status(
         NotifyConfig::default().with_poll_interval(Duration::from_millis(100)),
     )?;
 
-    // Watch the state file's parent directory (file may not exist yet)
+    // Watch the state file's parent directory (file may not exist yet). With
+    // the default location this also sees daemon.json, which has the progress.
     if let Some(parent) = state_path.parent() {
         std::fs::create_dir_all(parent)?;
         watcher.watch(parent, RecursiveMode::NonRecursive)?;
@@ -985,17 +1471,32 @@ async fn run_status(
                 // File changed, read new state
                 if let Ok(new_state) = std::fs::read_to_string(&state_path) {
                     let new_state = new_state.trim().to_string();
-                    if new_state != last_state {
-                        if format == "json" {
-                            println!(
-                                "{}",
-                                format_state_json(&new_state, &icons, ext_info.as_ref())
+                    let progress = transcribing_progress(&new_state);
+                    let secs = elapsed(&new_state);
+                    let new_level = level(&new_state);
+                    if format == "json" {
+                        if new_state != last_state
+                            || progress != last_progress
+                            || secs != last_elapsed
+                            || new_level != last_level
+                        {
+                            let json = format_state_json(
+                                &new_state,
+                                progress,
+                                secs,
+                                new_level,
+                                &icons,
+                                ext_info.as_ref(),
                             );
-                        } else {
-                            println!("{}", new_state);
+                            println!("{}", json);
                         }
-                        last_state = new_state;
+                    } else if new_state != last_state || new_level != last_level {
+                        println!("{}", format_state_text(&new_state, new_level));
                     }
+                    last_state = new_state;
+                    last_progress = progress;
+                    last_elapsed = secs;
+                    last_level = new_level;
                 }
             }
             Ok(Err(e)) => {
@@ -1007,12 +1508,37 @@ async fn run_status(
                     if format == "json" {
                         println!(
                             "{}",
-                            format_state_json("stopped", &icons, ext_info.as_ref())
+                            format_state_json(
+                                "stopped",
+                                None,
+                                None,
+                                None,
+                                &icons,
+                                ext_info.as_ref()
+                            )
                         );
                     } else {
                         println!("stopped");
                     }
                     last_state = "stopped".to_string();
+                    last_progress = None;
+                    last_elapsed = None;
+                    last_level = None;
+                } else if show_elapsed && last_state == "recording" {
+                    // Tick the recording time
+                    let secs = elapsed(&last_state);
+                    if secs != last_elapsed {
+                        let json = format_state_json(
+                            &last_state,
+                            last_progress,
+                            secs,
+                            last_level,
+                            &icons,
+                            ext_info.as_ref(),
+                        );
+                        println!("{}", json);
+                        last_elapsed = secs;
+                    }
                 }
             }
             Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
@@ -1024,10 +1550,52 @@ async fn run_status(
     Ok(())
 }
 
+/// Progress of the running transcription, from the daemon's status snapshot
+fn transcribing_progress(state: &str) -> Option<u8> {
+    if state != "transcribing" {
+        return None;
+    }
+    voxtype::daemon_info::DaemonInfo::read().and_then(|info| info.progress)
+}
+
+/// Seconds since recording started, from the daemon's status snapshot
+fn recording_secs(state: &str) -> Option<u64> {
+    if state != "recording" {
+        return None;
+    }
+    voxtype::daemon_info::DaemonInfo::read()
+        .filter(|info| info.state == "recording")
+        .and_then(|info| info.state_secs(chrono::Local::now()))
+}
+
+/// Input level while recording, from the daemon's status snapshot
+fn recording_level(state: &str) -> Option<f32> {
+    if state != "recording" {
+        return None;
+    }
+    voxtype::daemon_info::DaemonInfo::read()
+        .filter(|info| info.state == "recording")
+        .and_then(|info| info.input_level)
+}
+
+/// Format state as a line of text, "recording:0.63" with the input level
+fn format_state_text(state: &str, level: Option<f32>) -> String {
+    match level {
+        Some(level) => format!("{}:{:.2}", state, level),
+        None => state.to_string(),
+    }
+}
+
 /// Format state as JSON for Waybar consumption
 /// The `alt` field enables Waybar's format-icons feature for custom icon mapping
+/// `progress` (long transcriptions only) goes in the tooltip and `percentage`,
+/// `elapsed` (recording seconds, `voxtype waybar`) in the tooltip,
+/// `level` (input level while recording, `--level`) in `percentage`
 fn format_state_json(
     state: &str,
+    progress: Option<u8>,
+    elapsed: Option<u64>,
+    level: Option<f32>,
     icons: &config::ResolvedIcons,
     extended: Option<&ExtendedStatusInfo>,
 ) -> String {
@@ -1044,6 +1612,26 @@ fn format_state_json(
     let alt = state;
     let class = state;
 
+    let (base_tooltip, percentage) = match (progress, level) {
+        (Some(percent), _) => (
+            format!("{} {}%", base_tooltip, percent),
+            format!(r#", "percentage": {}"#, percent),
+        ),
+        (None, Some(level)) => (
+            base_tooltip.to_string(),
+            format!(r#", "percentage": {}"#, (level * 100.0).round()),
+        ),
+        (None, None) => (base_tooltip.to_string(), String::new()),
+    };
+    let base_tooltip = match elapsed {
+        Some(secs) => format!(
+            "{} {}",
+            base_tooltip,
+            voxtype::daemon_info::format_duration(secs)
+        ),
+        None => base_tooltip,
+    };
+
     match extended {
         Some(info) => {
             // Extended format includes model, device, backend
@@ -1052,19 +1640,162 @@ fn format_state_json(
                 base_tooltip, info.model, info.device, info.backend
             );
             format!(
-                r#"{{"text": "{}", "alt": "{}", "class": "{}", "tooltip": "{}", "model": "{}", "device": "{}", "backend": "{}"}}"#,
-                text, alt, class, tooltip, info.model, info.device, info.backend
+                r#"{{"text": "{}", "alt": "{}", "class": "{}", "tooltip": "{}", "model": "{}", "device": "{}", "backend": "{}"{}}}"#,
+                text, alt, class, tooltip, info.model, info.device, info.backend, percentage
             )
         }
         None => {
             format!(
-                r#"{{"text": "{}", "alt": "{}", "class": "{}", "tooltip": "{}"}}"#,
-                text, alt, class, base_tooltip
+                r#"{{"text": "{}", "alt": "{}", "class": "{}", "tooltip": "{}"{}}}"#,
+                text, alt, class, base_tooltip, percentage
             )
         }
     }
 }
 
+/// List audio input devices and monitor sources
+fn list_devices(config: &config::Config) -> anyhow::Result<()> {
+    println!("Input devices (audio.source = \"microphone\"):\n");
+    match audio::cpal_capture::list_input_devices() {
+        Ok((default, names)) if !names.is_empty() => {
+            for name in &names {
+                let marker = if default.as_deref() == Some(name.as_str()) {
+                    " (default)"
+                } else {
+                    ""
+                };
+                println!("  {}{}", name, marker);
+            }
+        }
+        Ok(_) => println!("  (none found)"),
+        Err(e) => println!("  Failed to list input devices: {}", e),
+    }
+
+    println!("\nMonitor sources (audio.source = \"monitor\"):\n");
+    let monitors = audio::monitor_capture::list_monitor_sources();
+    if monitors.is_empty() {
+        println!("  (none found; requires pactl from PipeWire/PulseAudio)");
+    }
+    for source in &monitors {
+        let marker = if source.running { " (running)" } else { "" };
+        println!("  {}{}", source.name, marker);
+    }
+
+    println!(
+        "\nCurrent: source = {:?}, device = {:?}",
+        config.audio.source, config.audio.device
+    );
+    println!("Set [audio] device to a name above, or use \"match:<pattern>\" to match by regex.");
+
+    Ok(())
+}
+
+/// Samples at or above this are counted as clipped
+const CLIP_LEVEL: f32 = 0.99;
+
+/// Width of the level meter in characters
+const METER_WIDTH: usize = 40;
+
+/// Amplitude in dBFS
+fn dbfs(amplitude: f32) -> f32 {
+    20.0 * amplitude.max(1e-6).log10()
+}
+
+/// Show a live level meter for the configured audio device, then judge the
+/// input gain
+async fn run_monitor(config: &config::Config, duration: u64) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    println!(
+        "Listening to {:?} ({:?}) for {}s. Speak as you would when dictating.\n",
+        config.audio.device, config.audio.source, duration
+    );
+
+    let mut capture = audio::create_capture(&config.audio)?;
+    let mut chunks = capture.start().await?;
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_millis(100));
+    let mut peak = 0.0f32;
+    let mut loudest_rms = 0.0f32;
+    let mut clipped = 0usize;
+    let mut received = 0usize;
+    for _ in 0..duration * 10 {
+        interval.tick().await;
+        let mut window = Vec::new();
+        while let Ok(chunk) = chunks.try_recv() {
+            window.extend(chunk);
+        }
+        if window.is_empty() {
+            continue;
+        }
+
+        let rms = (window.iter().map(|s| s * s).sum::<f32>() / window.len() as f32).sqrt();
+        let window_peak = window.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        let window_clipped = window.iter().filter(|s| s.abs() >= CLIP_LEVEL).count();
+        peak = peak.max(window_peak);
+        loudest_rms = loudest_rms.max(rms);
+        clipped += window_clipped;
+        received += window.len();
+
+        // Bar for the RMS level, '|' at the peak, on a -60..0 dBFS scale
+        let filled = (audio::rms_level(&window) * METER_WIDTH as f32).round() as usize;
+        let peak_at = (audio::peak_level(&window) * METER_WIDTH as f32).round() as usize;
+        let meter: String = (0..METER_WIDTH)
+            .map(|i| {
+                if i < filled {
+                    '#'
+                } else if i + 1 == peak_at {
+                    '|'
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        // Padded so a shorter line overwrites the previous one
+        let warning = if window_clipped > 0 { "CLIPPING" } else { "" };
+        print!(
+            "\r  [{}] {:>6.1} dBFS  peak {:>6.1} dBFS  {:<8}",
+            meter,
+            dbfs(rms),
+            dbfs(window_peak),
+            warning
+        );
+        let _ = std::io::stdout().flush();
+    }
+    println!("\n");
+    let _ = capture.stop().await;
+
+    if received == 0 {
+        println!("No audio was received from the device.");
+        println!("Check the device name (voxtype devices) and that it isn't muted.");
+        anyhow::bail!("no audio received");
+    }
+
+    println!("Peak:          {:.1} dBFS", dbfs(peak));
+    println!("Loudest 100ms: {:.1} dBFS (RMS)", dbfs(loudest_rms));
+    println!(
+        "Clipped:       {} sample(s) ({:.2}%)\n",
+        clipped,
+        clipped as f32 * 100.0 / received as f32
+    );
+
+    if clipped > 0 {
+        println!("The input clips, which distorts speech and hurts transcription.");
+        println!("Lower the input gain, e.g. in pavucontrol or with");
+        println!("wpctl set-volume @DEFAULT_AUDIO_SOURCE@ 70%");
+    } else if dbfs(loudest_rms) < -40.0 {
+        println!("The input is very quiet; was anything said?");
+        println!("Raise the input gain, move closer to the microphone, or check");
+        println!("that the right device is selected (voxtype devices).");
+    } else if dbfs(peak) > -3.0 {
+        println!("The level is close to clipping; consider lowering the input gain a little.");
+    } else {
+        println!("The level looks good.");
+    }
+
+    Ok(())
+}
+
 /// Show current configuration
 async fn show_config(config: &config::Config) -> anyhow::Result<()> {
     println!("Current Configuration\n");
@@ -1077,8 +1808,14 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
 
     println!("\n[audio]");
     println!("  device = {:?}", config.audio.device);
+    println!("  source = {:?}", config.audio.source);
     println!("  sample_rate = {}", config.audio.sample_rate);
     println!("  max_duration_secs = {}", config.audio.max_duration_secs);
+    println!("  pre_roll_ms = {}", config.audio.pre_roll_ms);
+    println!("  release_tail_ms = {}", config.audio.release_tail_ms);
+    println!("  high_pass_hz = {}", config.audio.high_pass_hz);
+    println!("  normalize_loudness = {}", config.audio.normalize_loudness);
+    println!("  target_lufs = {}", config.audio.target_lufs);
 
     println!("\n[audio.feedback]");
     println!("  enabled = {}", config.audio.feedback.enabled);
@@ -1231,7 +1968,7 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
             "  driver_order = [{}]",
             driver_order
                 .iter()
-                .map(|d| format!("{:?}", d))
+                .map(|d| d.to_string())
                 .collect::<Vec<_>>()
                 .join(", ")
         );
@@ -1259,6 +1996,17 @@ async fn show_config(config: &config::Config) -> anyhow::Result<()> {
         "  on_transcription = {}",
         config.output.notification.on_transcription
     );
+    println!("  on_error = {}", config.output.notification.on_error);
+    println!("  show_text = {}", config.output.notification.show_text);
+    if let Some(urgency) = config.output.notification.urgency {
+        println!("  urgency = {:?}", urgency.as_str());
+    }
+    if let Some(timeout_ms) = config.output.notification.timeout_ms {
+        println!("  timeout_ms = {}", timeout_ms);
+    }
+    if let Some(ref icon) = config.output.notification.icon {
+        println!("  icon = {:?}", icon);
+    }
 
     println!("\n[status]");
     println!("  icon_theme = {:?}", config.status.icon_theme);
@@ -1326,6 +2074,8 @@ async fn run_meeting_command(config: &config::Config, action: MeetingAction) ->
         },
         retain_audio: config.meeting.retain_audio,
         max_duration_mins: config.meeting.max_duration_mins,
+        notes_file: config.meeting.notes_file.clone(),
+        diarization: (&config.meeting.diarization).into(),
     };
 
     match action {
@@ -1659,7 +2409,12 @@ async fn run_meeting_command(config: &config::Config, action: MeetingAction) ->
                 ollama_url: config.meeting.summary.ollama_url.clone(),
                 ollama_model: config.meeting.summary.ollama_model.clone(),
                 remote_endpoint: config.meeting.summary.remote_endpoint.clone(),
-                remote_api_key: config.meeting.summary.remote_api_key.clone(),
+                remote_api_key: secret::resolve(
+                    config.meeting.summary.remote_api_key.as_deref(),
+                    config.meeting.summary.remote_api_key_cmd.as_deref(),
+                    config.meeting.summary.remote_api_key_keyring.as_deref(),
+                )
+                .map_err(|e| anyhow::anyhow!(e))?,
                 timeout_secs: config.meeting.summary.timeout_secs,
             };
 
@@ -276,20 +276,19 @@ impl ChunkProcessor {
             samples.len() as f32 / 16000.0
         );
 
-        let text = self.transcriber.transcribe(&samples)?;
+        let timed = self.transcriber.transcribe_segments(&samples)?;
 
+        // Map chunk-relative timestamps onto the meeting timeline
         let mut segments = vec![];
-        if !text.is_empty() && !text.trim().is_empty() {
-            // Create a single segment for the whole chunk
-            // Phase 3 will add proper sentence segmentation based on whisper timestamps
+        for timed_segment in timed {
             let segment_id = self.next_segment_id;
             self.next_segment_id += 1;
 
             let mut segment = TranscriptSegment::new(
                 segment_id,
-                start_offset_ms,
-                start_offset_ms + audio_duration_ms,
-                text.trim().to_string(),
+                start_offset_ms + timed_segment.start_ms,
+                start_offset_ms + timed_segment.end_ms.min(audio_duration_ms),
+                timed_segment.text,
                 chunk_id,
             );
             segment.source = source;
@@ -155,9 +155,16 @@ impl Transcript {
         Self::default()
     }
 
-    /// Add a segment to the transcript
+    /// Add a segment to the transcript, keeping segments in time order
+    ///
+    /// Mic and loopback tracks are transcribed separately for the same span
+    /// of time, so a segment may start before ones already added. Segments
+    /// with equal start times keep their insertion order.
     pub fn add_segment(&mut self, segment: TranscriptSegment) {
-        self.segments.push(segment);
+        let pos = self
+            .segments
+            .partition_point(|s| s.start_ms <= segment.start_ms);
+        self.segments.insert(pos, segment);
     }
 
     /// Remove echoed phrases from mic segments that match loopback transcripts.
@@ -696,6 +703,36 @@ mod tests {
         assert!(text.contains("**Remote**: Hi there"));
     }
 
+    #[test]
+    fn test_transcript_add_segment_interleaves_sources() {
+        let mut transcript = Transcript::new();
+        let mut mic1 = TranscriptSegment::new(0, 0, 2000, "Hi".to_string(), 0);
+        mic1.source = AudioSource::Microphone;
+        let mut mic2 = TranscriptSegment::new(1, 4000, 6000, "Sounds good".to_string(), 0);
+        mic2.source = AudioSource::Microphone;
+        let mut remote = TranscriptSegment::new(2, 2000, 4000, "Hello".to_string(), 0);
+        remote.source = AudioSource::Loopback;
+        transcript.add_segment(mic1);
+        transcript.add_segment(mic2);
+        transcript.add_segment(remote);
+
+        let texts: Vec<&str> = transcript
+            .segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["Hi", "Hello", "Sounds good"]);
+    }
+
+    #[test]
+    fn test_transcript_add_segment_same_start_keeps_order() {
+        let mut transcript = Transcript::new();
+        transcript.add_segment(TranscriptSegment::new(0, 0, 1000, "first".to_string(), 0));
+        transcript.add_segment(TranscriptSegment::new(1, 0, 1000, "second".to_string(), 0));
+        assert_eq!(transcript.segments[0].text, "first");
+        assert_eq!(transcript.segments[1].text, "second");
+    }
+
     #[test]
     fn test_transcript_text_with_speakers_merges_consecutive() {
         let mut transcript = Transcript::new();
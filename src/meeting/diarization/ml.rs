@@ -10,6 +10,7 @@ use crate::meeting::data::AudioSource;
 use crate::meeting::TranscriptSegment;
 use std::collections::HashMap;
 use std::path::PathBuf;
+#[cfg(any(feature = "ml-diarization", test))]
 use std::sync::Mutex;
 
 #[cfg(feature = "ml-diarization")]
@@ -51,6 +52,16 @@ impl SpeakerEmbedding {
     }
 }
 
+/// Speakers identified so far, shared across `diarize` calls
+#[cfg(any(feature = "ml-diarization", test))]
+#[derive(Debug, Default)]
+struct SpeakerClusters {
+    /// One reference embedding per known speaker
+    embeddings: Vec<SpeakerEmbedding>,
+    /// Next speaker ID to hand out
+    next_id: u32,
+}
+
 /// ML-based speaker diarizer
 #[allow(dead_code)]
 pub struct MlDiarizer {
@@ -59,12 +70,11 @@ pub struct MlDiarizer {
     /// ONNX session (lazy loaded)
     #[cfg(feature = "ml-diarization")]
     session: Option<Mutex<Session>>,
-    /// Known speaker embeddings
-    speaker_embeddings: Vec<SpeakerEmbedding>,
+    /// Known speakers (behind a mutex since `diarize` takes `&self`)
+    #[cfg(any(feature = "ml-diarization", test))]
+    clusters: Mutex<SpeakerClusters>,
     /// Speaker labels (auto ID -> human label)
     speaker_labels: HashMap<u32, String>,
-    /// Next speaker ID
-    next_speaker_id: u32,
     /// Similarity threshold for matching speakers
     similarity_threshold: f32,
     /// Maximum number of speakers to detect
@@ -82,9 +92,9 @@ impl MlDiarizer {
             model_path: config.model_path.as_ref().map(PathBuf::from),
             #[cfg(feature = "ml-diarization")]
             session: None,
-            speaker_embeddings: Vec::new(),
+            #[cfg(any(feature = "ml-diarization", test))]
+            clusters: Mutex::new(SpeakerClusters::default()),
             speaker_labels: HashMap::new(),
-            next_speaker_id: 0,
             similarity_threshold: 0.75,
             max_speakers: config.max_speakers,
             min_segment_ms: config.min_segment_ms,
@@ -166,16 +176,20 @@ impl MlDiarizer {
     }
 
     /// Find or create speaker ID for an embedding
-    #[allow(dead_code)]
-    fn find_or_create_speaker(&mut self, embedding: &[f32]) -> SpeakerId {
+    #[cfg(any(feature = "ml-diarization", test))]
+    fn find_or_create_speaker(&self, embedding: &[f32]) -> SpeakerId {
+        let Ok(mut clusters) = self.clusters.lock() else {
+            return SpeakerId::Unknown;
+        };
+
         let new_embedding = SpeakerEmbedding {
             vector: embedding.to_vec(),
-            speaker_id: SpeakerId::Auto(self.next_speaker_id),
+            speaker_id: SpeakerId::Auto(clusters.next_id),
         };
 
         // Find best matching existing speaker
         let mut best_match: Option<(usize, f32)> = None;
-        for (i, existing) in self.speaker_embeddings.iter().enumerate() {
+        for (i, existing) in clusters.embeddings.iter().enumerate() {
             let similarity = new_embedding.cosine_similarity(existing);
             if similarity > self.similarity_threshold {
                 match best_match {
@@ -190,15 +204,12 @@ impl MlDiarizer {
 
         if let Some((idx, _)) = best_match {
             // Return existing speaker
-            self.speaker_embeddings[idx].speaker_id.clone()
-        } else if self.next_speaker_id < self.max_speakers {
+            clusters.embeddings[idx].speaker_id.clone()
+        } else if clusters.next_id < self.max_speakers {
             // Create new speaker
-            let speaker_id = SpeakerId::Auto(self.next_speaker_id);
-            self.speaker_embeddings.push(SpeakerEmbedding {
-                vector: embedding.to_vec(),
-                speaker_id: speaker_id.clone(),
-            });
-            self.next_speaker_id += 1;
+            let speaker_id = new_embedding.speaker_id.clone();
+            clusters.embeddings.push(new_embedding);
+            clusters.next_id += 1;
             speaker_id
         } else {
             // Too many speakers, return unknown
@@ -305,11 +316,8 @@ impl Diarizer for MlDiarizer {
                 // Extract embedding
                 match self.extract_embedding(segment_samples) {
                     Ok(embedding) => {
-                        // Note: find_or_create_speaker needs mutable self, but diarize takes &self
-                        // In a real implementation, we'd need interior mutability or a different pattern
-                        // For now, return with unknown speaker and let caller handle labeling
                         results.push(DiarizedSegment {
-                            speaker: SpeakerId::Unknown, // Would be find_or_create_speaker result
+                            speaker: self.find_or_create_speaker(&embedding),
                             start_ms: seg.start_ms,
                             end_ms: seg.end_ms,
                             text: seg.text.clone(),
@@ -398,6 +406,40 @@ mod tests {
         assert_eq!(diarizer.get_label(&SpeakerId::Auto(2)), None);
     }
 
+    #[test]
+    fn test_find_or_create_speaker_clusters_embeddings() {
+        let diarizer = MlDiarizer::default();
+        assert_eq!(
+            diarizer.find_or_create_speaker(&[1.0, 0.0, 0.0]),
+            SpeakerId::Auto(0)
+        );
+        assert_eq!(
+            diarizer.find_or_create_speaker(&[0.0, 1.0, 0.0]),
+            SpeakerId::Auto(1)
+        );
+        // Close to the first speaker's voice
+        assert_eq!(
+            diarizer.find_or_create_speaker(&[0.95, 0.1, 0.0]),
+            SpeakerId::Auto(0)
+        );
+    }
+
+    #[test]
+    fn test_find_or_create_speaker_respects_max_speakers() {
+        let diarizer = MlDiarizer::new(&DiarizationConfig {
+            max_speakers: 1,
+            ..Default::default()
+        });
+        assert_eq!(
+            diarizer.find_or_create_speaker(&[1.0, 0.0]),
+            SpeakerId::Auto(0)
+        );
+        assert_eq!(
+            diarizer.find_or_create_speaker(&[0.0, 1.0]),
+            SpeakerId::Unknown
+        );
+    }
+
     #[test]
     fn test_default_model_path() {
         let path = MlDiarizer::default_model_path();
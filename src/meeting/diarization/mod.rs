@@ -13,6 +13,7 @@ pub mod simple;
 pub mod subprocess;
 
 use crate::meeting::data::AudioSource;
+use crate::transcribe::Segment;
 use std::collections::HashMap;
 
 /// Speaker identifier
@@ -108,35 +109,54 @@ impl Default for DiarizationConfig {
     }
 }
 
+impl From<&crate::config::MeetingDiarizationConfig> for DiarizationConfig {
+    fn from(config: &crate::config::MeetingDiarizationConfig) -> Self {
+        Self {
+            enabled: config.enabled,
+            backend: config.backend.clone(),
+            max_speakers: config.max_speakers,
+            model_path: config
+                .model_path
+                .as_ref()
+                .map(|p| p.to_string_lossy().into_owned()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Create the ML diarizer, or explain why it is unavailable
+///
+/// Unlike [`create_diarizer`], this does not fall back to source-based
+/// attribution, so callers that need real speaker separation can report
+/// the problem to the user.
+pub fn create_ml_diarizer(config: &DiarizationConfig) -> Result<Box<dyn Diarizer>, String> {
+    #[cfg(feature = "ml-diarization")]
+    {
+        let mut diarizer = ml::MlDiarizer::new(config);
+        diarizer.load_model()?;
+        Ok(Box::new(diarizer))
+    }
+    #[cfg(not(feature = "ml-diarization"))]
+    {
+        let _ = config;
+        Err("ML diarization requires voxtype built with the 'ml-diarization' feature".to_string())
+    }
+}
+
 /// Create a diarizer based on configuration
 pub fn create_diarizer(config: &DiarizationConfig) -> Box<dyn Diarizer> {
     match config.backend.as_str() {
         "simple" => Box::new(simple::SimpleDiarizer::new()),
-        "ml" => {
-            #[cfg(feature = "ml-diarization")]
-            {
-                let mut diarizer = ml::MlDiarizer::new(config);
-                if diarizer.model_exists() {
-                    if let Err(e) = diarizer.load_model() {
-                        tracing::warn!("Failed to load ML diarization model: {}", e);
-                        tracing::info!("Falling back to simple diarization");
-                        return Box::new(simple::SimpleDiarizer::new());
-                    }
-                    tracing::info!("Using ML diarization with ONNX");
-                    return Box::new(diarizer);
-                } else {
-                    tracing::warn!("ML diarization model not found, falling back to simple");
-                    return Box::new(simple::SimpleDiarizer::new());
-                }
+        "ml" => match create_ml_diarizer(config) {
+            Ok(diarizer) => {
+                tracing::info!("Using ML diarization with ONNX");
+                diarizer
             }
-            #[cfg(not(feature = "ml-diarization"))]
-            {
-                tracing::warn!(
-                    "ML diarization requires the 'ml-diarization' feature, falling back to simple"
-                );
+            Err(e) => {
+                tracing::warn!("{}, falling back to simple diarization", e);
                 Box::new(simple::SimpleDiarizer::new())
             }
-        }
+        },
         "subprocess" => {
             // Subprocess diarizer for memory-isolated ML diarization
             Box::new(subprocess::SubprocessDiarizer::new(config.clone()))
@@ -151,10 +171,82 @@ pub fn create_diarizer(config: &DiarizationConfig) -> Box<dyn Diarizer> {
     }
 }
 
+/// Display label for a speaker in file transcription output
+///
+/// Auto-detected speakers are numbered from 1 ("Speaker 1", "Speaker 2").
+/// Returns `None` for speakers the diarizer could not attribute.
+pub fn file_speaker_label(speaker: &SpeakerId) -> Option<String> {
+    match speaker {
+        SpeakerId::Auto(id) => Some(format!("Speaker {}", id + 1)),
+        SpeakerId::Named(name) => Some(name.clone()),
+        SpeakerId::You | SpeakerId::Remote | SpeakerId::Unknown => None,
+    }
+}
+
+/// Attribute file transcription segments to speakers
+///
+/// `samples` is the full 16kHz clip the segments were transcribed from.
+/// Each segment takes the speaker of the diarized span it starts in.
+pub fn label_segments(diarizer: &dyn Diarizer, samples: &[f32], segments: &mut [Segment]) {
+    let transcript: Vec<crate::meeting::TranscriptSegment> = segments
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            crate::meeting::TranscriptSegment::new(
+                i as u32,
+                s.start_ms,
+                s.end_ms,
+                s.text.clone(),
+                0,
+            )
+        })
+        .collect();
+
+    let diarized = diarizer.diarize(samples, AudioSource::Unknown, &transcript);
+    for segment in segments.iter_mut() {
+        segment.speaker = diarized
+            .iter()
+            .rfind(|d| d.start_ms <= segment.start_ms)
+            .and_then(|d| file_speaker_label(&d.speaker));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Diarizer that alternates speakers per segment
+    struct AlternatingDiarizer;
+
+    impl Diarizer for AlternatingDiarizer {
+        fn diarize(
+            &self,
+            _samples: &[f32],
+            _source: AudioSource,
+            transcript_segments: &[crate::meeting::TranscriptSegment],
+        ) -> Vec<DiarizedSegment> {
+            transcript_segments
+                .iter()
+                .enumerate()
+                .map(|(i, seg)| DiarizedSegment {
+                    speaker: if i == 2 {
+                        SpeakerId::Unknown
+                    } else {
+                        SpeakerId::Auto(i as u32 % 2)
+                    },
+                    start_ms: seg.start_ms,
+                    end_ms: seg.end_ms,
+                    text: seg.text.clone(),
+                    confidence: 1.0,
+                })
+                .collect()
+        }
+
+        fn name(&self) -> &'static str {
+            "alternating"
+        }
+    }
+
     #[test]
     fn test_speaker_id_display() {
         assert_eq!(SpeakerId::You.display_name(), "You");
@@ -174,4 +266,42 @@ mod tests {
         assert_eq!(config.backend, "simple");
         assert_eq!(config.max_speakers, 10);
     }
+
+    #[test]
+    fn test_file_speaker_label() {
+        assert_eq!(
+            file_speaker_label(&SpeakerId::Auto(0)),
+            Some("Speaker 1".to_string())
+        );
+        assert_eq!(file_speaker_label(&SpeakerId::Unknown), None);
+    }
+
+    #[test]
+    fn test_label_segments() {
+        let mut segments: Vec<Segment> = ["Hi.", "Hello.", "Mm."]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Segment {
+                start_ms: i as u64 * 1000,
+                end_ms: i as u64 * 1000 + 900,
+                text: text.to_string(),
+                speaker: None,
+                words: Vec::new(),
+            })
+            .collect();
+
+        label_segments(&AlternatingDiarizer, &[], &mut segments);
+
+        assert_eq!(segments[0].speaker.as_deref(), Some("Speaker 1"));
+        assert_eq!(segments[1].speaker.as_deref(), Some("Speaker 2"));
+        assert_eq!(segments[2].speaker, None);
+    }
+
+    #[test]
+    fn test_ml_diarizer_without_feature_reports_error() {
+        if cfg!(not(feature = "ml-diarization")) {
+            let err = create_ml_diarizer(&DiarizationConfig::default()).err();
+            assert!(err.unwrap().contains("ml-diarization"));
+        }
+    }
 }
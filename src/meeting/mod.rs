@@ -26,6 +26,7 @@ pub mod chunk;
 pub mod data;
 pub mod diarization;
 pub mod export;
+pub mod notes;
 pub mod state;
 pub mod storage;
 pub mod summary;
@@ -35,12 +36,15 @@ pub use data::{
     ActionItem, AudioSource, MeetingData, MeetingId, MeetingMetadata, MeetingStatus,
     MeetingSummary, Transcript, TranscriptSegment,
 };
+pub use diarization::{DiarizationConfig, Diarizer};
 pub use export::{export_meeting, export_meeting_to_file, ExportFormat, ExportOptions};
+pub use notes::NotesFile;
 pub use state::{ChunkState, MeetingState};
 pub use storage::{MeetingStorage, StorageConfig, StorageError};
 
 use crate::error::{MeetingError, Result};
 use crate::transcribe::{self, Transcriber};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
@@ -57,6 +61,10 @@ pub struct MeetingConfig {
     pub retain_audio: bool,
     /// Maximum meeting duration in minutes (0 = unlimited)
     pub max_duration_mins: u32,
+    /// File to append timestamped notes to while the meeting runs
+    pub notes_file: Option<PathBuf>,
+    /// Speaker diarization settings
+    pub diarization: DiarizationConfig,
 }
 
 impl Default for MeetingConfig {
@@ -67,6 +75,8 @@ impl Default for MeetingConfig {
             storage: StorageConfig::default(),
             retain_audio: false,
             max_duration_mins: 180,
+            notes_file: None,
+            diarization: DiarizationConfig::default(),
         }
     }
 }
@@ -100,6 +110,12 @@ pub struct MeetingDaemon {
     transcriber: Option<Arc<dyn Transcriber>>,
     engine_name: String,
     event_tx: mpsc::Sender<MeetingEvent>,
+    /// Position of the next chunk on the meeting timeline (milliseconds)
+    audio_position_ms: u64,
+    /// Live notes file for the current meeting
+    notes: Option<NotesFile>,
+    /// Speaker diarizer for the current meeting (None for source-based attribution)
+    diarizer: Option<Box<dyn Diarizer>>,
 }
 
 impl MeetingDaemon {
@@ -112,8 +128,7 @@ impl MeetingDaemon {
         let storage = MeetingStorage::open(config.storage.clone())
             .map_err(|e| MeetingError::Storage(e.to_string()))?;
 
-        let transcriber: Arc<dyn Transcriber> =
-            Arc::from(transcribe::create_transcriber(app_config)?);
+        let transcriber = transcribe::create_transcriber_chain(app_config)?;
         let engine_name = format!("{:?}", app_config.engine).to_lowercase();
 
         Ok(Self {
@@ -124,6 +139,9 @@ impl MeetingDaemon {
             transcriber: Some(transcriber),
             engine_name,
             event_tx,
+            audio_position_ms: 0,
+            notes: None,
+            diarizer: None,
         })
     }
 
@@ -144,9 +162,24 @@ impl MeetingDaemon {
             .map_err(|e| MeetingError::Storage(e.to_string()))?;
         meeting.metadata.storage_path = Some(storage_path);
 
+        self.notes = self.config.notes_file.as_ref().and_then(|path| {
+            NotesFile::open(path, &meeting.metadata)
+                .map_err(|e| tracing::warn!("Failed to open notes file {:?}: {}", path, e))
+                .ok()
+        });
+
+        // Fresh diarizer per meeting so speaker numbering starts at SPEAKER_00.
+        // The simple backend needs no diarizer: segments are already
+        // attributed by audio source.
+        let diarization = &self.config.diarization;
+        self.diarizer = (diarization.enabled && diarization.backend != "simple")
+            .then(|| diarization::create_diarizer(diarization))
+            .filter(|d| d.name() != "simple");
+
         let meeting_id = meeting.metadata.id;
         self.current_meeting = Some(meeting);
         self.state = MeetingState::start();
+        self.audio_position_ms = 0;
 
         let _ = self
             .event_tx
@@ -222,6 +255,8 @@ impl MeetingDaemon {
         // Clean up
         self.state = std::mem::take(&mut self.state).finalize();
         self.current_meeting = None;
+        self.notes = None;
+        self.diarizer = None;
 
         Ok(meeting_id)
     }
@@ -259,6 +294,43 @@ impl MeetingDaemon {
             return Ok(None);
         }
 
+        let duration_ms = samples_to_ms(samples.len());
+        let segments = self.transcribe_track(samples, source, true)?;
+        self.commit_chunk(segments, duration_ms).await
+    }
+
+    /// Process one window of dual-source audio (mic + loopback)
+    ///
+    /// Both tracks cover the same span of wall-clock time, so their segments
+    /// share a start offset and are merged into the transcript in time order.
+    /// Only the loopback track is diarized; the microphone is always "You".
+    pub async fn process_dual_chunk(
+        &mut self,
+        mic: Vec<f32>,
+        loopback: Vec<f32>,
+    ) -> Result<Option<Vec<TranscriptSegment>>> {
+        if !self.state.is_active() {
+            return Ok(None);
+        }
+
+        let duration_ms = samples_to_ms(mic.len().max(loopback.len()));
+        let mut segments = self.transcribe_track(mic, AudioSource::Microphone, false)?;
+        if !loopback.is_empty() {
+            segments.extend(self.transcribe_track(loopback, AudioSource::Loopback, true)?);
+        }
+        self.commit_chunk(segments, duration_ms).await
+    }
+
+    /// Transcribe one track of the current chunk at the current timeline position
+    ///
+    /// With `diarize` set and an ML diarizer active, segments are attributed
+    /// to individual speakers instead of just the track's source.
+    fn transcribe_track(
+        &self,
+        samples: Vec<f32>,
+        source: AudioSource,
+        diarize: bool,
+    ) -> Result<Vec<TranscriptSegment>> {
         let Some(ref transcriber) = self.transcriber else {
             return Err(MeetingError::TranscriberNotInitialized.into());
         };
@@ -269,30 +341,50 @@ impl MeetingDaemon {
             ..Default::default()
         };
 
-        // Calculate start offset
-        let start_offset_ms = if let Some(ref meeting) = self.current_meeting {
-            meeting.transcript.duration_ms()
-        } else {
-            0
-        };
-
         let mut processor = ChunkProcessor::new(chunk_config, transcriber.clone());
-        let mut buffer = processor.new_buffer(chunk_id, source, start_offset_ms);
+        let mut buffer = processor.new_buffer(chunk_id, source, self.audio_position_ms);
         buffer.add_samples(&samples);
 
         let result = processor
             .process_chunk(buffer)
             .map_err(crate::error::VoxtypeError::Transcribe)?;
 
-        // Add segments to transcript
+        let mut segments = result.segments;
+        if let Some(diarizer) = self.diarizer.as_deref().filter(|_| diarize) {
+            assign_speakers(
+                diarizer,
+                &samples,
+                source,
+                self.audio_position_ms,
+                &mut segments,
+            );
+        }
+        Ok(segments)
+    }
+
+    /// Add a chunk's segments to the transcript and advance the timeline
+    async fn commit_chunk(
+        &mut self,
+        segments: Vec<TranscriptSegment>,
+        duration_ms: u64,
+    ) -> Result<Option<Vec<TranscriptSegment>>> {
+        let chunk_id = self.state.chunks_processed();
+
         if let Some(ref mut meeting) = self.current_meeting {
-            for segment in &result.segments {
+            for segment in &segments {
                 meeting.transcript.add_segment(segment.clone());
             }
             meeting.transcript.total_chunks = chunk_id + 1;
         }
 
-        // Advance state
+        if let Some(ref mut notes) = self.notes {
+            if let Err(e) = notes.append(&segments) {
+                tracing::warn!("Failed to append to notes file: {}", e);
+            }
+        }
+
+        // Advance timeline and state
+        self.audio_position_ms += duration_ms;
         self.state = std::mem::take(&mut self.state).next_chunk();
 
         // Send event
@@ -300,11 +392,11 @@ impl MeetingDaemon {
             .event_tx
             .send(MeetingEvent::ChunkProcessed {
                 chunk_id,
-                segments: result.segments.clone(),
+                segments: segments.clone(),
             })
             .await;
 
-        Ok(Some(result.segments))
+        Ok(Some(segments))
     }
 
     /// Get storage access
@@ -313,6 +405,45 @@ impl MeetingDaemon {
     }
 }
 
+/// Set `speaker_id` on a chunk's segments from the diarizer
+///
+/// Segments carry meeting-timeline timestamps while the diarizer works on
+/// the chunk's own samples, so times are shifted by `offset_ms` for the
+/// diarizer and matched back by position.
+fn assign_speakers(
+    diarizer: &dyn Diarizer,
+    samples: &[f32],
+    source: AudioSource,
+    offset_ms: u64,
+    segments: &mut [TranscriptSegment],
+) {
+    let relative: Vec<TranscriptSegment> = segments
+        .iter()
+        .map(|s| {
+            let mut seg = s.clone();
+            seg.start_ms = s.start_ms.saturating_sub(offset_ms);
+            seg.end_ms = s.end_ms.saturating_sub(offset_ms);
+            seg
+        })
+        .collect();
+
+    let diarized = diarizer.diarize(samples, source, &relative);
+    for (segment, rel) in segments.iter_mut().zip(&relative) {
+        let speaker = diarized
+            .iter()
+            .rfind(|d| d.start_ms <= rel.start_ms)
+            .map(|d| &d.speaker);
+        if let Some(speaker) = speaker.filter(|s| **s != diarization::SpeakerId::Unknown) {
+            segment.speaker_id = Some(speaker.display_name());
+        }
+    }
+}
+
+/// Convert a 16kHz sample count to milliseconds
+fn samples_to_ms(samples: usize) -> u64 {
+    (samples as f64 / 16000.0 * 1000.0) as u64
+}
+
 /// List meetings from storage
 pub fn list_meetings(
     config: &MeetingConfig,
@@ -356,6 +487,53 @@ mod tests {
         assert_eq!(config.max_duration_mins, 180);
     }
 
+    /// Diarizer that hears a second speaker from 1s into every chunk
+    struct SplitDiarizer;
+
+    impl Diarizer for SplitDiarizer {
+        fn diarize(
+            &self,
+            _samples: &[f32],
+            _source: AudioSource,
+            transcript_segments: &[TranscriptSegment],
+        ) -> Vec<diarization::DiarizedSegment> {
+            transcript_segments
+                .iter()
+                .map(|seg| diarization::DiarizedSegment {
+                    speaker: diarization::SpeakerId::Auto((seg.start_ms >= 1000) as u32),
+                    start_ms: seg.start_ms,
+                    end_ms: seg.end_ms,
+                    text: seg.text.clone(),
+                    confidence: 1.0,
+                })
+                .collect()
+        }
+
+        fn name(&self) -> &'static str {
+            "split"
+        }
+    }
+
+    #[test]
+    fn test_assign_speakers_uses_chunk_relative_times() {
+        let mut segments = vec![
+            TranscriptSegment::new(0, 30_000, 30_800, "Hi".to_string(), 1),
+            TranscriptSegment::new(1, 31_500, 32_000, "Hello".to_string(), 1),
+        ];
+
+        assign_speakers(
+            &SplitDiarizer,
+            &[],
+            AudioSource::Loopback,
+            30_000,
+            &mut segments,
+        );
+
+        assert_eq!(segments[0].speaker_display(), "SPEAKER_00");
+        assert_eq!(segments[1].speaker_display(), "SPEAKER_01");
+        assert_eq!(segments[1].start_ms, 31_500);
+    }
+
     #[test]
     fn test_meeting_state_transitions() {
         let state = MeetingState::Idle;
@@ -376,4 +554,11 @@ mod tests {
         let state = state.finalize();
         assert!(state.is_idle());
     }
+
+    #[test]
+    fn test_samples_to_ms() {
+        assert_eq!(samples_to_ms(0), 0);
+        assert_eq!(samples_to_ms(16000), 1000);
+        assert_eq!(samples_to_ms(480_000), 30_000);
+    }
 }
@@ -0,0 +1,148 @@
+//! Live meeting notes file
+//!
+//! Appends each transcribed segment to a text or markdown file as soon as
+//! its chunk is processed, so notes can be followed (or tailed) while the
+//! meeting is still running. The full transcript is still saved to meeting
+//! storage on stop.
+
+use crate::meeting::data::{MeetingMetadata, TranscriptSegment};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// Append-only notes file for the current meeting
+pub struct NotesFile {
+    file: File,
+    markdown: bool,
+}
+
+impl NotesFile {
+    /// Open (or create) the notes file and write a header for this meeting
+    ///
+    /// Existing content is kept, so a single file can collect notes from
+    /// several meetings. Files ending in `.md` get markdown formatting.
+    pub fn open(path: &Path, metadata: &MeetingMetadata) -> std::io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let markdown = path
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"))
+            .unwrap_or(false);
+
+        let mut notes = Self { file, markdown };
+        notes.write(&format_header(metadata, markdown))?;
+
+        tracing::info!("Writing meeting notes to {:?}", path);
+        Ok(notes)
+    }
+
+    /// Append transcribed segments
+    pub fn append(&mut self, segments: &[TranscriptSegment]) -> std::io::Result<()> {
+        if segments.is_empty() {
+            return Ok(());
+        }
+        let lines: String = segments
+            .iter()
+            .map(|s| format_line(s, self.markdown))
+            .collect();
+        self.write(&lines)
+    }
+
+    fn write(&mut self, text: &str) -> std::io::Result<()> {
+        self.file.write_all(text.as_bytes())?;
+        self.file.flush()
+    }
+}
+
+/// Format the per-meeting header
+fn format_header(metadata: &MeetingMetadata, markdown: bool) -> String {
+    let date = metadata.started_at.format("%Y-%m-%d %H:%M");
+    if markdown {
+        format!(
+            "\n## {}\n\n_Started {}_\n\n",
+            metadata.display_title(),
+            date
+        )
+    } else {
+        format!(
+            "\n{}\nStarted {}\n{}\n\n",
+            metadata.display_title(),
+            date,
+            "=".repeat(60)
+        )
+    }
+}
+
+/// Format one segment as a timestamped line
+fn format_line(segment: &TranscriptSegment, markdown: bool) -> String {
+    if markdown {
+        format!(
+            "- `[{}]` **{}**: {}\n",
+            segment.format_timestamp(),
+            segment.speaker_display(),
+            segment.text
+        )
+    } else {
+        format!(
+            "[{}] {}: {}\n",
+            segment.format_timestamp(),
+            segment.speaker_display(),
+            segment.text
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meeting::data::AudioSource;
+
+    fn segment(start_ms: u64, text: &str, source: AudioSource) -> TranscriptSegment {
+        let mut seg = TranscriptSegment::new(0, start_ms, start_ms + 1000, text.to_string(), 0);
+        seg.source = source;
+        seg
+    }
+
+    #[test]
+    fn test_format_line_text() {
+        let seg = segment(75_000, "Let's start.", AudioSource::Microphone);
+        assert_eq!(format_line(&seg, false), "[01:15] You: Let's start.\n");
+    }
+
+    #[test]
+    fn test_format_line_markdown() {
+        let seg = segment(0, "Hi all", AudioSource::Loopback);
+        assert_eq!(format_line(&seg, true), "- `[00:00]` **Remote**: Hi all\n");
+    }
+
+    #[test]
+    fn test_notes_file_appends_across_meetings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes").join("standup.md");
+
+        let first = MeetingMetadata::new(Some("Monday".to_string()));
+        let mut notes = NotesFile::open(&path, &first).unwrap();
+        notes
+            .append(&[segment(0, "Hello", AudioSource::Microphone)])
+            .unwrap();
+        drop(notes);
+
+        let second = MeetingMetadata::new(Some("Tuesday".to_string()));
+        let mut notes = NotesFile::open(&path, &second).unwrap();
+        notes
+            .append(&[segment(2000, "Morning", AudioSource::Loopback)])
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## Monday"));
+        assert!(content.contains("**You**: Hello"));
+        assert!(content.contains("## Tuesday"));
+        assert!(content.contains("**Remote**: Morning"));
+        assert!(content.find("Monday").unwrap() < content.find("Tuesday").unwrap());
+    }
+}
@@ -30,7 +30,7 @@ impl OllamaSummarizer {
 
     /// Check if Ollama is running and the model is available
     pub fn check_availability(&self) -> Result<(), SummaryError> {
-        let client = ureq::AgentBuilder::new()
+        let client = crate::proxy::agent(&self.url)
             .timeout(Duration::from_secs(5))
             .build();
 
@@ -79,7 +79,7 @@ impl OllamaSummarizer {
 
     /// Call Ollama generate API
     fn generate(&self, prompt: &str) -> Result<String, SummaryError> {
-        let client = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        let client = crate::proxy::agent(&self.url).timeout(self.timeout).build();
 
         let generate_url = format!("{}/api/generate", self.url);
 
@@ -33,7 +33,9 @@ impl RemoteSummarizer {
 
     /// Call the remote summarization API
     fn call_api(&self, prompt: &str) -> Result<String, SummaryError> {
-        let client = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        let client = crate::proxy::agent(&self.endpoint)
+            .timeout(self.timeout)
+            .build();
 
         #[derive(Serialize)]
         struct SummarizeRequest<'a> {
@@ -120,7 +122,7 @@ impl Summarizer for RemoteSummarizer {
 
     fn is_available(&self) -> bool {
         // Try a simple health check
-        let client = ureq::AgentBuilder::new()
+        let client = crate::proxy::agent(&self.endpoint)
             .timeout(Duration::from_secs(5))
             .build();
 
@@ -0,0 +1,265 @@
+//! Models directory housekeeping
+//!
+//! Loading a model from the models directory records the time in
+//! `.last_used/<entry>`, where the entry is the model's file or directory
+//! name. `voxtype model prune` removes entries whose last use is older than
+//! a number of days, so trying several model sizes doesn't fill the disk for
+//! good. Models never loaded since tracking began count from their file's
+//! modification time, and models the config refers to are always kept.
+
+use crate::config::Config;
+use crate::transcribe::models;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Directory of last-use timestamps, inside the models directory
+const LAST_USED_DIR: &str = ".last_used";
+
+/// Record that a model was just loaded
+///
+/// Paths outside the models directory are ignored.
+pub fn mark_used(path: &Path) {
+    mark_used_in(&Config::models_dir(), path, SystemTime::now());
+}
+
+fn mark_used_in(dir: &Path, path: &Path, now: SystemTime) {
+    let Some(entry) = path
+        .strip_prefix(dir)
+        .ok()
+        .and_then(|rest| rest.components().next())
+    else {
+        return;
+    };
+    let markers = dir.join(LAST_USED_DIR);
+    let secs = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if let Err(e) = fs::create_dir_all(&markers)
+        .and_then(|()| fs::write(markers.join(entry), format!("{}\n", secs)))
+    {
+        tracing::debug!("Could not record use of {:?}: {}", path, e);
+    }
+}
+
+/// A model file or directory in the models directory
+pub struct CachedModel {
+    pub path: PathBuf,
+    /// File or directory name
+    pub name: String,
+    /// Size in bytes, including everything in a directory
+    pub size: u64,
+    /// Last load, or the modification time if never recorded
+    pub last_used: SystemTime,
+}
+
+/// Everything in a models directory, with when each was last used
+pub fn cached_models(dir: &Path) -> Vec<CachedModel> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut models: Vec<CachedModel> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            let path = entry.path();
+            let recorded = fs::read_to_string(dir.join(LAST_USED_DIR).join(&name))
+                .ok()
+                .and_then(|secs| secs.trim().parse().ok())
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+            let last_used = recorded
+                .or_else(|| entry.metadata().and_then(|m| m.modified()).ok())
+                .unwrap_or(UNIX_EPOCH);
+            Some(CachedModel {
+                size: disk_size(&path),
+                path,
+                name,
+                last_used,
+            })
+        })
+        .collect();
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    models
+}
+
+/// Models unused for `max_age`, except those named in `keep`
+pub fn stale_models(
+    models: Vec<CachedModel>,
+    max_age: Duration,
+    now: SystemTime,
+    keep: &[String],
+) -> Vec<CachedModel> {
+    models
+        .into_iter()
+        .filter(|model| !keep.contains(&model.name))
+        .filter(|model| {
+            now.duration_since(model.last_used)
+                .is_ok_and(|age| age >= max_age)
+        })
+        .collect()
+}
+
+/// Delete a model and its last-use record
+pub fn remove(model: &CachedModel) -> std::io::Result<()> {
+    if model.path.is_dir() {
+        fs::remove_dir_all(&model.path)?;
+    } else {
+        fs::remove_file(&model.path)?;
+    }
+    if let Some(dir) = model.path.parent() {
+        let _ = fs::remove_file(dir.join(LAST_USED_DIR).join(&model.name));
+    }
+    Ok(())
+}
+
+/// Entries in the models directory the config refers to
+pub fn configured_models(config: &Config) -> Vec<String> {
+    let whisper = &config.whisper;
+    let whisper_models = [&whisper.model]
+        .into_iter()
+        .chain(&whisper.secondary_model)
+        .chain(&whisper.refine_model)
+        .chain(&whisper.available_models)
+        .chain(whisper.length_models.iter().map(|rule| &rule.model))
+        .chain(&config.power.battery_model)
+        .map(|name| models::model_filename(name));
+
+    // Other engines and VAD may name a path; the entry is its last component
+    let entry = |model: &str| {
+        Path::new(model)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(model)
+            .to_string()
+    };
+
+    let mut keep: Vec<String> = whisper_models.collect();
+    // Short names of other engines live in "<engine>-<name>" directories;
+    // profiles may use engines of their own
+    let engines = std::iter::once(config.engine).chain(
+        config
+            .profiles
+            .values()
+            .filter_map(|profile| profile.engine),
+    );
+    for engine in engines {
+        let name = format!("{:?}", engine).to_lowercase();
+        let engine_model = entry(config.engine_model_name(engine));
+        keep.push(format!("{}-{}", name, engine_model));
+        keep.push(engine_model);
+    }
+    keep.extend(config.vad.model.as_deref().map(entry));
+    keep.push(crate::vad::get_whisper_vad_model_filename().to_string());
+    keep.push("gtcrn_simple.onnx".to_string());
+    keep.push("openwakeword".to_string());
+    // faster-whisper keeps its downloads in a directory of its own
+    if config.whisper.effective_mode() == crate::config::WhisperMode::FasterWhisper {
+        keep.push("faster-whisper".to_string());
+    }
+    keep
+}
+
+/// Bytes used by a file, or by everything in a directory
+fn disk_size(path: &Path) -> u64 {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| disk_size(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    #[test]
+    fn test_mark_used() {
+        let dir = TempDir::new().unwrap();
+        let now = UNIX_EPOCH + 400 * DAY;
+        fs::write(dir.path().join("ggml-base.en.bin"), [0u8; 10]).unwrap();
+        fs::create_dir(dir.path().join("parakeet-tdt")).unwrap();
+        fs::write(dir.path().join("parakeet-tdt/encoder.onnx"), [0u8; 5]).unwrap();
+
+        mark_used_in(dir.path(), &dir.path().join("ggml-base.en.bin"), now);
+        mark_used_in(dir.path(), &dir.path().join("parakeet-tdt"), now - 40 * DAY);
+        // Elsewhere: nothing recorded
+        mark_used_in(dir.path(), Path::new("/opt/models/custom.bin"), now);
+
+        let models = cached_models(dir.path());
+        let names: Vec<&str> = models.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["ggml-base.en.bin", "parakeet-tdt"]);
+        assert_eq!(models[0].last_used, now);
+        assert_eq!(models[0].size, 10);
+        assert_eq!(models[1].size, 5);
+        assert_eq!(models[1].last_used, now - 40 * DAY);
+    }
+
+    #[test]
+    fn test_stale_models() {
+        let dir = TempDir::new().unwrap();
+        let now = UNIX_EPOCH + 400 * DAY;
+        for name in ["ggml-small.en.bin", "ggml-medium.bin", "ggml-tiny.bin"] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        mark_used_in(
+            dir.path(),
+            &dir.path().join("ggml-small.en.bin"),
+            now - 2 * DAY,
+        );
+        mark_used_in(
+            dir.path(),
+            &dir.path().join("ggml-medium.bin"),
+            now - 60 * DAY,
+        );
+        mark_used_in(
+            dir.path(),
+            &dir.path().join("ggml-tiny.bin"),
+            now - 90 * DAY,
+        );
+
+        let keep = vec!["ggml-tiny.bin".to_string()];
+        let stale = stale_models(cached_models(dir.path()), 30 * DAY, now, &keep);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].name, "ggml-medium.bin");
+
+        remove(&stale[0]).unwrap();
+        assert!(!dir.path().join("ggml-medium.bin").exists());
+        assert!(!dir.path().join(".last_used/ggml-medium.bin").exists());
+        assert_eq!(cached_models(dir.path()).len(), 2);
+    }
+
+    #[test]
+    fn test_configured_models() {
+        let mut config = Config::default();
+        config.whisper.secondary_model = Some("large-v3-turbo".to_string());
+        config.vad.model = Some("/home/user/models/silero.bin".to_string());
+        let keep = configured_models(&config);
+        assert!(keep.contains(&"ggml-base.en.bin".to_string()));
+        assert!(keep.contains(&"ggml-large-v3-turbo.bin".to_string()));
+        assert!(keep.contains(&"silero.bin".to_string()));
+        assert!(!keep.contains(&"faster-whisper".to_string()));
+
+        config.engine = crate::config::TranscriptionEngine::Moonshine;
+        config.moonshine = Some(Default::default());
+        let moonshine = config.moonshine.as_ref().unwrap().model.clone();
+        let keep = configured_models(&config);
+        assert!(keep.contains(&format!("moonshine-{}", moonshine)));
+
+        config.onnx = Some(Default::default());
+        config.profiles.insert(
+            "npu".to_string(),
+            crate::config::Profile {
+                engine: Some(crate::config::TranscriptionEngine::Onnx),
+                ..Default::default()
+            },
+        );
+        let keep = configured_models(&config);
+        assert!(keep.contains(&"onnx-whisper-base".to_string()));
+    }
+}
@@ -3,8 +3,10 @@
 //! Manages the lifecycle of Whisper models, supporting:
 //! - LRU caching of loaded models (when gpu_isolation = false)
 //! - On-demand loading with automatic eviction
+//! - Freeing idle models in place (`unload_after_secs`), reloaded on next use
 //! - Fresh subprocess per model (when gpu_isolation = true)
 //! - Remote backend model selection
+//! - A faster-whisper sidecar per model, restarted when it stops responding
 
 use crate::config::{WhisperConfig, WhisperMode};
 use crate::error::TranscribeError;
@@ -47,7 +49,8 @@ impl ModelManager {
         }
     }
 
-    /// Check if a model is available (configured as primary, secondary, or in available_models)
+    /// Check if a model is available (configured as primary, secondary,
+    /// refine model, or in available_models)
     pub fn is_model_available(&self, model: &str) -> bool {
         if model == self.config.model {
             return true;
@@ -57,6 +60,17 @@ impl ModelManager {
                 return true;
             }
         }
+        if self.config.refine_model.as_deref() == Some(model) {
+            return true;
+        }
+        if self
+            .config
+            .length_models
+            .iter()
+            .any(|rule| rule.model == model)
+        {
+            return true;
+        }
         self.config.available_models.contains(&model.to_string())
     }
 
@@ -64,9 +78,17 @@ impl ModelManager {
     ///
     /// For GPU isolation mode, creates a fresh subprocess transcriber each time.
     /// For non-isolation mode, returns cached transcriber or loads on demand.
+    /// It transcribes with the `[inference]` priority.
     pub fn get_transcriber(
         &mut self,
         model: Option<&str>,
+    ) -> Result<Arc<dyn Transcriber>, TranscribeError> {
+        self.load_transcriber(model).map(crate::priority::wrap)
+    }
+
+    fn load_transcriber(
+        &mut self,
+        model: Option<&str>,
     ) -> Result<Arc<dyn Transcriber>, TranscribeError> {
         // Clone the model name to avoid borrow issues
         let model_name = model
@@ -80,7 +102,7 @@ impl ModelManager {
                 model_name,
                 self.config.model
             );
-            return self.get_transcriber(None);
+            return self.load_transcriber(None);
         }
 
         // For remote backend, create transcriber with model override
@@ -93,6 +115,19 @@ impl ModelManager {
             return self.create_cli_transcriber(&model_name);
         }
 
+        // The remote worker transcribes with the model it has loaded
+        if self.config.effective_mode() == WhisperMode::Worker {
+            let transcriber =
+                transcribe::remote_worker::RemoteWorkerTranscriber::new(&self.config)?;
+            return Ok(Arc::new(transcriber));
+        }
+
+        // The faster-whisper sidecar keeps its model loaded, so it's cached
+        // like a local model
+        if self.config.effective_mode() == WhisperMode::FasterWhisper {
+            return self.get_or_load_cached(&model_name);
+        }
+
         // For GPU isolation, always create fresh subprocess
         if self.config.gpu_isolation {
             return self.create_subprocess_transcriber(&model_name);
@@ -110,7 +145,8 @@ impl ModelManager {
         let mut config = self.config.clone();
         // Override remote_model with requested model
         config.remote_model = Some(model.to_string());
-        let transcriber = transcribe::remote::RemoteTranscriber::new(&config)?;
+        let transcriber =
+            transcribe::usage::MeteredTranscriber::from_config(&config, self.config_path.clone())?;
         Ok(Arc::new(transcriber))
     }
 
@@ -154,13 +190,21 @@ impl ModelManager {
         let mut config = self.config.clone();
         config.model = model.to_string();
 
-        let transcriber = transcribe::whisper::WhisperTranscriber::new(&config)?;
+        let transcriber: Arc<dyn Transcriber> =
+            if self.config.effective_mode() == WhisperMode::FasterWhisper {
+                // The sidecar starts here and keeps the priority it starts with
+                Arc::new(crate::priority::run(|| {
+                    transcribe::faster_whisper::FasterWhisperTranscriber::new(&config)
+                })?)
+            } else {
+                Arc::new(transcribe::whisper::WhisperTranscriber::new(&config)?)
+            };
         let is_primary = model == self.config.model;
 
         self.loaded_models.insert(
             model.to_string(),
             LoadedModel {
-                transcriber: Arc::new(transcriber),
+                transcriber,
                 last_used: Instant::now(),
                 is_primary,
             },
@@ -192,6 +236,11 @@ impl ModelManager {
     /// Call this periodically (e.g., every 60 seconds) to free memory
     /// from models that are no longer being actively used.
     pub fn evict_idle_models(&mut self) {
+        // Cached models stay cached but free their memory (unload_after_secs)
+        for loaded in self.loaded_models.values() {
+            loaded.transcriber.unload_if_idle();
+        }
+
         if self.cold_timeout.is_zero() {
             return; // Auto-eviction disabled
         }
@@ -221,7 +270,7 @@ impl ModelManager {
             return Ok(());
         }
 
-        if self.config.gpu_isolation {
+        if self.config.gpu_isolation && self.config.effective_mode() == WhisperMode::Local {
             tracing::debug!("Skipping primary model preload (gpu_isolation=true)");
             return Ok(());
         }
@@ -236,6 +285,11 @@ impl ModelManager {
             return Ok(());
         }
 
+        if self.config.effective_mode() == WhisperMode::Worker {
+            tracing::debug!("Skipping primary model preload (worker backend)");
+            return Ok(());
+        }
+
         let model = self.config.model.clone();
         tracing::info!("Preloading primary model '{}'", model);
         let _ = self.get_or_load_cached(&model)?;
@@ -274,11 +328,28 @@ impl ModelManager {
                     is_primary: false,
                 },
             );
+        } else if self.config.unload_after_secs > 0
+            || self.config.effective_mode() == WhisperMode::FasterWhisper
+        {
+            // Reload a model that was freed while idle, or restart a sidecar
+            // that stopped responding, behind the recording
+            if let Some(loaded) = self.loaded_models.get(&model_name) {
+                let transcriber = Arc::clone(&loaded.transcriber);
+                std::thread::spawn(move || transcriber.prepare());
+            }
         }
 
         Ok(())
     }
 
+    /// Pass audio being recorded to the prepared transcriber, if any
+    pub fn feed_prepared(&self, model: Option<&str>, samples: &[f32]) {
+        let model_name = model.unwrap_or(&self.config.model);
+        if let Some(prepared) = self.loaded_models.get(&format!("_prepared_{}", model_name)) {
+            prepared.transcriber.feed(samples);
+        }
+    }
+
     /// Get a prepared transcriber (if available) or create one
     ///
     /// This checks for a prepared subprocess transcriber first,
@@ -292,6 +363,11 @@ impl ModelManager {
             .unwrap_or_else(|| self.config.model.clone());
         let prepared_key = format!("_prepared_{}", model_name);
 
+        // A worker prepared for another model (the length rules picked a
+        // different one at release) is no longer needed
+        self.loaded_models
+            .retain(|key, _| !key.starts_with("_prepared_") || *key == prepared_key);
+
         // Check for prepared transcriber
         if let Some(prepared) = self.loaded_models.remove(&prepared_key) {
             tracing::debug!("Using prepared transcriber for model '{}'", model_name);
@@ -345,6 +421,23 @@ mod tests {
 
         // Unknown model is not available
         assert!(!manager.is_model_available("tiny.en"));
+
+        // The refine model is available
+        let config = WhisperConfig {
+            refine_model: Some("small.en".to_string()),
+            ..test_config()
+        };
+        assert!(ModelManager::new(&config, None).is_model_available("small.en"));
+
+        // So are the models of the length rules
+        let config = WhisperConfig {
+            length_models: vec![crate::config::LengthModel {
+                max_secs: Some(5.0),
+                model: "tiny.en".to_string(),
+            }],
+            ..test_config()
+        };
+        assert!(ModelManager::new(&config, None).is_model_available("tiny.en"));
     }
 
     #[test]
@@ -356,4 +449,42 @@ mod tests {
         assert_eq!(manager.cold_timeout, Duration::from_secs(300));
         assert!(manager.loaded_models.is_empty());
     }
+
+    /// Counts idle checks
+    #[derive(Default)]
+    struct IdleCounter(std::sync::atomic::AtomicUsize);
+
+    impl Transcriber for IdleCounter {
+        fn transcribe(&self, _samples: &[f32]) -> Result<String, TranscribeError> {
+            Ok(String::new())
+        }
+
+        fn unload_if_idle(&self) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_idle_check_reaches_primary_model() {
+        let config = WhisperConfig {
+            cold_model_timeout_secs: 0,
+            unload_after_secs: 600,
+            ..test_config()
+        };
+        let mut manager = ModelManager::new(&config, None);
+        let counter = Arc::new(IdleCounter::default());
+        manager.loaded_models.insert(
+            "base.en".to_string(),
+            LoadedModel {
+                transcriber: counter.clone(),
+                last_used: Instant::now(),
+                is_primary: true,
+            },
+        );
+
+        // Runs even with cold eviction disabled, and keeps the model cached
+        manager.evict_idle_models();
+        assert_eq!(counter.0.load(std::sync::atomic::Ordering::Relaxed), 1);
+        assert!(manager.loaded_models.contains_key("base.en"));
+    }
 }
@@ -0,0 +1,193 @@
+//! Focused application detection
+//!
+//! Looks up the app id / window class (or the process) of the focused window
+//! so output can depend on where the text is going. Supported: Hyprland
+//! (hyprctl), Sway (swaymsg), niri (niri msg) and X11 (xdotool). Returns None
+//! elsewhere.
+
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Get the app id (Wayland) or window class (X11) of the focused window
+pub async fn focused_app() -> Option<String> {
+    let app = if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        let json = run_json("hyprctl", &["activewindow", "-j"]).await?;
+        json.get("class")?.as_str().map(String::from)
+    } else if std::env::var_os("SWAYSOCK").is_some() {
+        let tree = run_json("swaymsg", &["-t", "get_tree"]).await?;
+        find_focused_sway_app(&tree)
+    } else if std::env::var_os("NIRI_SOCKET").is_some() {
+        let json = run_json("niri", &["msg", "--json", "focused-window"]).await?;
+        json.get("app_id")?.as_str().map(String::from)
+    } else if std::env::var_os("DISPLAY").is_some() {
+        let output = Command::new("xdotool")
+            .args(["getactivewindow", "getwindowclassname"])
+            .stderr(Stdio::null())
+            .output()
+            .await
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    };
+
+    let app = app.filter(|a| !a.is_empty());
+    tracing::debug!("Focused app: {:?}", app);
+    app
+}
+
+/// Get the process id of the focused window's client
+pub async fn focused_pid() -> Option<u32> {
+    let pid = if std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        let json = run_json("hyprctl", &["activewindow", "-j"]).await?;
+        json.get("pid")?.as_u64()
+    } else if std::env::var_os("SWAYSOCK").is_some() {
+        let tree = run_json("swaymsg", &["-t", "get_tree"]).await?;
+        find_focused_sway_node(&tree)?.get("pid")?.as_u64()
+    } else if std::env::var_os("NIRI_SOCKET").is_some() {
+        let json = run_json("niri", &["msg", "--json", "focused-window"]).await?;
+        json.get("pid")?.as_u64()
+    } else if std::env::var_os("DISPLAY").is_some() {
+        let output = Command::new("xdotool")
+            .args(["getactivewindow", "getwindowpid"])
+            .stderr(Stdio::null())
+            .output()
+            .await
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    } else {
+        None
+    };
+
+    let pid = pid
+        .and_then(|pid| u32::try_from(pid).ok())
+        .filter(|pid| *pid > 0);
+    tracing::debug!("Focused window pid: {:?}", pid);
+    pid
+}
+
+/// Parent of a process, from /proc
+pub fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name in parentheses may contain spaces and parentheses
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Whether `pid` is `ancestor` or runs under it, going up with `parent`
+/// (e.g. whether a program runs in the terminal of the focused window)
+pub fn descends_from(pid: u32, ancestor: u32, parent: impl Fn(u32) -> Option<u32>) -> bool {
+    let mut pid = pid;
+    for _ in 0..64 {
+        if pid == ancestor {
+            return true;
+        }
+        match parent(pid) {
+            Some(ppid) if ppid > 1 => pid = ppid,
+            _ => return false,
+        }
+    }
+    false
+}
+
+/// Run a command and parse its stdout as JSON
+async fn run_json(program: &str, args: &[&str]) -> Option<serde_json::Value> {
+    let output = Command::new(program)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| tracing::debug!("Failed to run {}: {}", program, e))
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+/// Find the focused node in a `swaymsg -t get_tree` tree
+fn find_focused_sway_node(node: &serde_json::Value) -> Option<&serde_json::Value> {
+    if node.get("focused").and_then(|f| f.as_bool()) == Some(true) {
+        return Some(node);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node.get(key)?.as_array())
+        .flatten()
+        .find_map(find_focused_sway_node)
+}
+
+/// App id of the focused node in a `swaymsg -t get_tree` tree
+fn find_focused_sway_app(tree: &serde_json::Value) -> Option<String> {
+    let node = find_focused_sway_node(tree)?;
+    // Native Wayland windows have app_id, XWayland windows a class
+    node.get("app_id")
+        .and_then(|a| a.as_str())
+        .or_else(|| node.pointer("/window_properties/class")?.as_str())
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_focused_sway_app() {
+        let tree = serde_json::json!({
+            "focused": false,
+            "nodes": [{
+                "focused": false,
+                "nodes": [
+                    {"focused": false, "app_id": "foot", "nodes": []},
+                    {"focused": true, "app_id": "firefox", "nodes": []}
+                ]
+            }],
+            "floating_nodes": []
+        });
+        assert_eq!(find_focused_sway_app(&tree), Some("firefox".to_string()));
+    }
+
+    #[test]
+    fn test_descends_from() {
+        // terminal 100 → shell 200 → nvim 300; nvim 400 elsewhere
+        let parent = |pid| match pid {
+            300 => Some(200),
+            200 => Some(100),
+            100 | 400 => Some(1),
+            _ => None,
+        };
+        assert!(descends_from(300, 100, parent));
+        assert!(descends_from(100, 100, parent));
+        assert!(!descends_from(400, 100, parent));
+        assert!(!descends_from(999, 100, parent));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parent_pid() {
+        assert_eq!(
+            parent_pid(std::process::id()),
+            Some(std::os::unix::process::parent_id())
+        );
+    }
+
+    #[test]
+    fn test_find_focused_sway_xwayland_class() {
+        let tree = serde_json::json!({
+            "focused": false,
+            "nodes": [],
+            "floating_nodes": [{
+                "focused": true,
+                "app_id": null,
+                "window_properties": {"class": "Slack"}
+            }]
+        });
+        assert_eq!(find_focused_sway_app(&tree), Some("Slack".to_string()));
+    }
+}
@@ -0,0 +1,211 @@
+//! AT-SPI accessibility text insertion
+//!
+//! Inserts text through the AT-SPI2 EditableText interface of the focused
+//! widget instead of synthesizing key events. This keeps the input method's
+//! state intact and works in sessions where virtual keyboards are blocked.
+//! If the focused widget isn't editable (or AT-SPI is unavailable), the
+//! output fails and the next driver in the chain is used.
+//!
+//! The AT-SPI calls go through a small Python helper using the Atspi GObject
+//! introspection bindings.
+//!
+//! Requires:
+//! - python3 with PyGObject (python3-gi / python-gobject)
+//! - libatspi / at-spi2-core (the Atspi-2.0 typelib)
+//! - Applications with accessibility enabled (GTK and Qt apps by default;
+//!   Chromium/Electron need --force-renderer-accessibility)
+
+use super::TextOutput;
+use crate::error::OutputError;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Exit code the helper uses when the focused widget is not editable
+const NOT_EDITABLE: i32 = 3;
+
+/// Helper script: `insert` reads text from stdin, `erase <count>` deletes
+/// before the caret, `enter` presses Return
+const HELPER: &str = r#"
+import sys
+import gi
+gi.require_version("Atspi", "2.0")
+from gi.repository import Atspi
+
+def focused(node, depth=0):
+    if node is None or depth > 64:
+        return None
+    if node.get_state_set().contains(Atspi.StateType.FOCUSED):
+        return node
+    for i in range(node.get_child_count()):
+        found = focused(node.get_child_at_index(i), depth + 1)
+        if found is not None:
+            return found
+    return None
+
+def focused_editable():
+    desktop = Atspi.get_desktop(0)
+    for i in range(desktop.get_child_count()):
+        app = desktop.get_child_at_index(i)
+        if app is None:
+            continue
+        for j in range(app.get_child_count()):
+            window = app.get_child_at_index(j)
+            if window is None or not window.get_state_set().contains(Atspi.StateType.ACTIVE):
+                continue
+            node = focused(window)
+            if node is None:
+                continue
+            if (node.get_state_set().contains(Atspi.StateType.EDITABLE)
+                    and node.get_editable_text_iface() is not None
+                    and node.get_text_iface() is not None):
+                return node
+            return None
+    return None
+
+mode = sys.argv[1]
+if mode == "enter":
+    Atspi.generate_keyboard_event(0xff0d, None, Atspi.KeySynthType.SYM)
+    sys.exit(0)
+
+node = focused_editable()
+if node is None:
+    sys.exit(3)
+caret = Atspi.Text.get_caret_offset(node)
+if caret < 0:
+    caret = Atspi.Text.get_character_count(node)
+
+if mode == "insert":
+    text = sys.stdin.read()
+    if not Atspi.EditableText.insert_text(node, caret, text, len(text.encode("utf-8"))):
+        sys.exit("insert_text failed")
+    Atspi.Text.set_caret_offset(node, caret + len(text))
+elif mode == "erase":
+    start = max(0, caret - int(sys.argv[2]))
+    if not Atspi.EditableText.delete_text(node, start, caret):
+        sys.exit("delete_text failed")
+"#;
+
+/// AT-SPI EditableText output
+pub struct AtspiOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+    /// Delay before inserting (ms)
+    pre_type_delay_ms: u32,
+}
+
+impl AtspiOutput {
+    /// Create a new AT-SPI output
+    pub fn new(auto_submit: bool, append_text: Option<String>, pre_type_delay_ms: u32) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+            pre_type_delay_ms,
+        }
+    }
+
+    /// Run the helper in `mode`, writing `stdin` to it
+    async fn run_helper(&self, args: &[&str], stdin: &str) -> Result<(), OutputError> {
+        let mut child = Command::new("python3")
+            .args(["-c", HELPER])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| OutputError::InjectionFailed(format!("python3: {}", e)))?;
+
+        if let Some(mut pipe) = child.stdin.take() {
+            pipe.write_all(stdin.as_bytes())
+                .await
+                .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+        }
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+
+        match output.status.code() {
+            Some(0) => Ok(()),
+            Some(NOT_EDITABLE) => Err(OutputError::InjectionFailed(
+                "focused widget is not editable via AT-SPI".to_string(),
+            )),
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                Err(OutputError::InjectionFailed(format!(
+                    "AT-SPI helper failed: {}",
+                    stderr.trim()
+                )))
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for AtspiOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        if self.pre_type_delay_ms > 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(
+                self.pre_type_delay_ms as u64,
+            ))
+            .await;
+        }
+
+        let text = match self.append_text {
+            Some(ref append) => format!("{}{}", text, append),
+            None => text.to_string(),
+        };
+        self.run_helper(&["insert"], &text).await?;
+
+        if self.auto_submit {
+            if let Err(e) = self.run_helper(&["enter"], "").await {
+                tracing::warn!("Failed to send Enter key: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        self.run_helper(&["erase", &count.to_string()], "").await
+    }
+
+    async fn is_available(&self) -> bool {
+        Command::new("python3")
+            .args([
+                "-c",
+                "import gi; gi.require_version('Atspi', '2.0'); from gi.repository import Atspi",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    fn name(&self) -> &'static str {
+        "atspi"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let output = AtspiOutput::new(true, Some(" ".to_string()), 50);
+        assert!(output.auto_submit);
+        assert_eq!(output.append_text.as_deref(), Some(" "));
+        assert_eq!(output.pre_type_delay_ms, 50);
+        assert_eq!(output.name(), "atspi");
+    }
+}
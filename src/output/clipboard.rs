@@ -112,6 +112,31 @@ impl TextOutput for ClipboardOutput {
         Ok(())
     }
 
+    async fn erase(&self, _count: usize) -> Result<(), OutputError> {
+        let status = Command::new("wl-copy")
+            .arg("--clear")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    OutputError::WlCopyNotFound
+                } else {
+                    OutputError::InjectionFailed(e.to_string())
+                }
+            })?;
+
+        if !status.success() {
+            return Err(OutputError::InjectionFailed(
+                "wl-copy --clear exited with error".to_string(),
+            ));
+        }
+
+        tracing::info!("Clipboard cleared");
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         Command::new("which")
             .arg("wl-copy")
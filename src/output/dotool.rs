@@ -107,30 +107,9 @@ impl DotoolOutput {
 
         commands
     }
-}
-
-#[async_trait::async_trait]
-impl TextOutput for DotoolOutput {
-    async fn output(&self, text: &str) -> Result<(), OutputError> {
-        if text.is_empty() {
-            return Ok(());
-        }
-
-        // Pre-typing delay if configured
-        if self.pre_type_delay_ms > 0 {
-            tracing::debug!(
-                "dotool: sleeping {}ms before typing",
-                self.pre_type_delay_ms
-            );
-            tokio::time::sleep(Duration::from_millis(self.pre_type_delay_ms as u64)).await;
-        }
-
-        let commands = self.build_commands(text);
-        tracing::debug!(
-            "dotool: sending commands for text: \"{}\"",
-            text.chars().take(20).collect::<String>()
-        );
 
+    /// Run dotool with the given commands on stdin
+    async fn run_commands(&self, commands: &str) -> Result<(), OutputError> {
         // Spawn dotool with stdin pipe
         let mut cmd = Command::new("dotool");
         cmd.stdin(Stdio::piped())
@@ -183,6 +162,46 @@ impl TextOutput for DotoolOutput {
             )));
         }
 
+        Ok(())
+    }
+
+    /// Build the dotool commands that erase `count` characters
+    fn build_erase_commands(&self, count: usize) -> String {
+        let mut commands = String::new();
+        if self.type_delay_ms > 0 {
+            commands.push_str(&format!("keydelay {}\n", self.type_delay_ms));
+        }
+        for _ in 0..count {
+            commands.push_str("key backspace\n");
+        }
+        commands
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for DotoolOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        // Pre-typing delay if configured
+        if self.pre_type_delay_ms > 0 {
+            tracing::debug!(
+                "dotool: sleeping {}ms before typing",
+                self.pre_type_delay_ms
+            );
+            tokio::time::sleep(Duration::from_millis(self.pre_type_delay_ms as u64)).await;
+        }
+
+        let commands = self.build_commands(text);
+        tracing::debug!(
+            "dotool: sending commands for text: \"{}\"",
+            text.chars().take(20).collect::<String>()
+        );
+
+        self.run_commands(&commands).await?;
+
         tracing::info!("Text typed via dotool ({} chars)", text.len());
 
         // Send notification if enabled
@@ -193,6 +212,13 @@ impl TextOutput for DotoolOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        self.run_commands(&self.build_erase_commands(count)).await
+    }
+
     async fn is_available(&self) -> bool {
         // Check if dotool exists in PATH
         Command::new("which")
@@ -247,4 +273,13 @@ mod tests {
         assert!(cmds.contains("type Test"));
         assert!(cmds.contains("key enter"));
     }
+
+    #[test]
+    fn test_build_erase_commands() {
+        let output = DotoolOutput::new(0, 0, false, false, None, None, None);
+        assert_eq!(
+            output.build_erase_commands(2),
+            "key backspace\nkey backspace\n"
+        );
+    }
 }
@@ -181,6 +181,44 @@ impl TextOutput for EitypeOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        let mut cmd = Command::new("eitype");
+        if self.type_delay_ms > 0 {
+            cmd.arg("-d").arg(self.type_delay_ms.to_string());
+        }
+        for _ in 0..count {
+            cmd.args(["-k", "backspace"]);
+        }
+        tracing::debug!("Running: eitype -k backspace (x{})", count);
+
+        let output = cmd
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    OutputError::EitypeNotFound
+                } else {
+                    OutputError::InjectionFailed(e.to_string())
+                }
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(OutputError::InjectionFailed(format!(
+                "eitype failed: {}",
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         // Check if eitype exists in PATH
         Command::new("which")
@@ -0,0 +1,104 @@
+//! File output
+//!
+//! Writes transcriptions to a file, either replacing its contents or
+//! appending to it. Used for `mode = "file"`, the `--file` CLI flag and the
+//! `also = ["file"]` sink.
+
+use super::TextOutput;
+use crate::config::FileMode;
+use crate::error::OutputError;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// Write text to a file, respecting file_mode (overwrite or append)
+pub async fn write_text(path: &Path, text: &str, file_mode: &FileMode) -> std::io::Result<()> {
+    // Create parent directories if needed
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+    }
+
+    // Ensure text ends with newline
+    let output_text = if text.ends_with('\n') {
+        text.to_string()
+    } else {
+        format!("{}\n", text)
+    };
+
+    match file_mode {
+        FileMode::Overwrite => {
+            tokio::fs::write(path, output_text).await?;
+        }
+        FileMode::Append => {
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .await?;
+            file.write_all(output_text.as_bytes()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// File output
+pub struct FileOutput {
+    path: PathBuf,
+    mode: FileMode,
+}
+
+impl FileOutput {
+    /// Create a new file output
+    pub fn new(path: PathBuf, mode: FileMode) -> Self {
+        Self { path, mode }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for FileOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        write_text(&self.path, text, &self.mode)
+            .await
+            .map_err(|e| OutputError::InjectionFailed(format!("writing {:?}: {}", self.path, e)))?;
+        tracing::debug!("Wrote transcription to {:?}", self.path);
+        Ok(())
+    }
+
+    async fn erase(&self, _count: usize) -> Result<(), OutputError> {
+        Err(OutputError::InjectionFailed(
+            "text written to a file can't be erased".to_string(),
+        ))
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "file"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_append_and_overwrite() {
+        let dir = std::env::temp_dir().join(format!("voxtype-file-test-{}", std::process::id()));
+        let path = dir.join("nested").join("log.txt");
+
+        let append = FileOutput::new(path.clone(), FileMode::Append);
+        append.output("first").await.unwrap();
+        append.output("second\n").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+
+        let overwrite = FileOutput::new(path.clone(), FileMode::Overwrite);
+        overwrite.output("third").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "third\n");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,273 @@
+//! IBus text commit
+//!
+//! Commits text through IBus, the way an input method engine commits a
+//! finished composition, instead of synthesizing key events. The focused
+//! application receives whole Unicode strings, so CJK and other non-Latin
+//! text comes out right whatever the keyboard layout, and the text never
+//! lands in the input method's own composition.
+//!
+//! IBus only accepts commits from an engine, so a small Python helper
+//! registers a temporary "voxtype" engine, switches to it, commits the text
+//! into the focused input context and switches back to the previous engine.
+//! The previous engine is restored when the helper exits, also on SIGTERM,
+//! and the helper is killed if IBus doesn't answer in time. When
+//! IBus isn't running, or no text field has the focus, the output fails and
+//! the next driver in the chain is used.
+//!
+//! This driver is IBus-only. fcitx5 has no such interface: its D-Bus API can
+//! switch input methods but not commit text from another program, and
+//! engines are C++ addons loaded into fcitx5 itself. Under fcitx5 (including
+//! its IBus compatibility frontend) this driver fails and the chain moves on.
+//!
+//! Requires:
+//! - ibus-daemon running as the input method
+//! - python3 with PyGObject (python3-gi / python-gobject) and the IBus-1.0
+//!   typelib (gir1.2-ibus-1.0 / ibus)
+
+use super::TextOutput;
+use crate::error::OutputError;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Exit code the helper uses when it can't reach ibus-daemon
+const NO_IBUS: i32 = 3;
+
+/// Exit code the helper uses when no input context took the text
+const NO_INPUT_CONTEXT: i32 = 4;
+
+/// How long the helper may run: its 2 second wait for an input context, the
+/// engine switches (1 second each at most) and starting Python
+const HELPER_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Helper script: `commit [--enter]` commits text from stdin, `erase <count>`
+/// presses BackSpace
+const HELPER: &str = r#"
+import signal
+import sys
+import gi
+gi.require_version("IBus", "1.0")
+from gi.repository import GLib, GObject, IBus
+
+mode = sys.argv[1]
+text = sys.stdin.read() if mode == "commit" else ""
+enter = "--enter" in sys.argv[2:]
+count = int(sys.argv[2]) if mode == "erase" else 0
+
+IBus.init()
+bus = IBus.Bus()
+if not bus.is_connected():
+    sys.exit(3)
+
+# Milliseconds to wait for ibus-daemon to switch engines
+SWITCH_TIMEOUT = 1000
+
+loop = GLib.MainLoop()
+state = {"delivered": False, "restoring": False, "restored": False}
+previous = bus.get_global_engine()
+previous = previous.get_name() if previous is not None else None
+if previous == "voxtype":
+    previous = None
+
+def press(engine, keyval):
+    engine.forward_key_event(keyval, 0, 0)
+    engine.forward_key_event(keyval, 0, IBus.ModifierType.RELEASE_MASK)
+
+def restored(bus, result, _data):
+    try:
+        bus.set_global_engine_async_finish(result)
+        state["restored"] = True
+    except GLib.Error as e:
+        print("restoring the input method: " + e.message, file=sys.stderr)
+    loop.quit()
+
+def restore():
+    if state["restoring"]:
+        return False
+    state["restoring"] = True
+    if previous is not None:
+        bus.set_global_engine_async(previous, SWITCH_TIMEOUT, None, restored, None)
+    else:
+        loop.quit()
+    return False
+
+def stop():
+    loop.quit()
+    return False
+
+for signum in (signal.SIGTERM, signal.SIGINT, signal.SIGHUP):
+    GLib.unix_signal_add(GLib.PRIORITY_HIGH, signum, stop)
+
+class VoxtypeEngine(IBus.Engine):
+    __gtype_name__ = "VoxtypeEngine"
+
+    def deliver(self):
+        if state["delivered"]:
+            return
+        state["delivered"] = True
+        if mode == "commit":
+            self.commit_text(IBus.Text.new_from_string(text))
+            if enter:
+                press(self, IBus.KEY_Return)
+        else:
+            for _ in range(count):
+                press(self, IBus.KEY_BackSpace)
+        GLib.idle_add(restore)
+
+    def do_enable(self):
+        self.deliver()
+
+    def do_focus_in(self):
+        self.deliver()
+
+factory = IBus.Factory.new(bus.get_connection())
+factory.add_engine("voxtype", GObject.type_from_name("VoxtypeEngine"))
+component = IBus.Component.new(
+    "org.voxtype.Dictation", "Voxtype dictation", "1.0", "MIT", "voxtype", "", "", "voxtype")
+component.add_engine(IBus.EngineDesc.new(
+    "voxtype", "Voxtype", "Voxtype dictation", "", "MIT", "voxtype", "", "default"))
+if not bus.register_component(component):
+    sys.exit("ibus-daemon refused the voxtype engine")
+
+def switched(bus, result, _data):
+    try:
+        bus.set_global_engine_async_finish(result)
+    except GLib.Error as e:
+        print("switching the input method: " + e.message, file=sys.stderr)
+        loop.quit()
+
+# Async, so the main loop can answer ibus-daemon creating the engine
+bus.set_global_engine_async("voxtype", SWITCH_TIMEOUT, None, switched, None)
+GLib.timeout_add(2000, restore)
+try:
+    loop.run()
+finally:
+    # Never leave the user on the voxtype engine, which exits with us
+    if previous is not None and not state["restored"]:
+        try:
+            bus.set_global_engine(previous)
+        except GLib.Error as e:
+            print("restoring the input method: " + e.message, file=sys.stderr)
+sys.exit(0 if state["delivered"] else 4)
+"#;
+
+/// IBus commit output
+pub struct IbusOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+}
+
+impl IbusOutput {
+    /// Create a new IBus output
+    pub fn new(auto_submit: bool, append_text: Option<String>) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+        }
+    }
+
+    /// Run the helper with `args`, writing `stdin` to it
+    async fn run_helper(&self, args: &[&str], stdin: &str) -> Result<(), OutputError> {
+        let mut child = Command::new("python3")
+            .args(["-c", HELPER])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            // A helper stuck on a hung ibus-daemon is killed on timeout
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| OutputError::InjectionFailed(format!("python3: {}", e)))?;
+
+        if let Some(mut pipe) = child.stdin.take() {
+            pipe.write_all(stdin.as_bytes())
+                .await
+                .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+        }
+
+        let output = tokio::time::timeout(HELPER_TIMEOUT, child.wait_with_output())
+            .await
+            .map_err(|_| {
+                OutputError::InjectionFailed("IBus didn't answer, helper killed".to_string())
+            })?
+            .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+
+        match output.status.code() {
+            Some(0) => Ok(()),
+            Some(NO_IBUS) => Err(OutputError::InjectionFailed(
+                "IBus is not running (fcitx5 can't commit text from other programs)".to_string(),
+            )),
+            Some(NO_INPUT_CONTEXT) => Err(OutputError::InjectionFailed(
+                "no IBus input field has the focus".to_string(),
+            )),
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                Err(OutputError::InjectionFailed(format!(
+                    "IBus helper failed: {}",
+                    stderr.trim()
+                )))
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for IbusOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let text = match self.append_text {
+            Some(ref append) => format!("{}{}", text, append),
+            None => text.to_string(),
+        };
+        let args: &[&str] = if self.auto_submit {
+            &["commit", "--enter"]
+        } else {
+            &["commit"]
+        };
+        self.run_helper(args, &text).await
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        self.run_helper(&["erase", &count.to_string()], "").await
+    }
+
+    async fn is_available(&self) -> bool {
+        Command::new("python3")
+            .args([
+                "-c",
+                "import gi; gi.require_version('IBus', '1.0'); from gi.repository import IBus",
+            ])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    fn name(&self) -> &'static str {
+        "ibus"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        let output = IbusOutput::new(true, Some(" ".to_string()));
+        assert!(output.auto_submit);
+        assert_eq!(output.append_text.as_deref(), Some(" "));
+        assert_eq!(output.name(), "ibus");
+    }
+}
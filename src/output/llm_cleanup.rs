@@ -0,0 +1,267 @@
+//! Built-in LLM cleanup
+//!
+//! Sends transcribed text to an OpenAI-compatible chat completions endpoint
+//! with a configurable system prompt and uses the reply as the output text.
+//! Works with Ollama, llama.cpp server, LM Studio, vLLM or OpenAI itself.
+//!
+//! # Example Configuration
+//!
+//! ```toml
+//! [output.llm_cleanup]
+//! enabled = true
+//! endpoint = "http://localhost:11434"
+//! model = "llama3.2:1b"
+//! ```
+//!
+//! The API key can come from a command or the keyring (`api_key_cmd`,
+//! `api_key_keyring`, see [`crate::secret`]); it's fetched on first use.
+//!
+//! Like [`super::post_process`], any failure falls back to the original text.
+
+use crate::config::LlmCleanupConfig;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// LLM client that rewrites transcribed text
+pub struct LlmCleanup {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    api_key_cmd: Option<String>,
+    api_key_keyring: Option<String>,
+    system_prompt: String,
+    timeout: Duration,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: [ChatMessage<'a>; 2],
+    temperature: f32,
+    stream: bool,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatReply,
+}
+
+#[derive(Deserialize)]
+struct ChatReply {
+    content: Option<String>,
+}
+
+impl LlmCleanup {
+    /// Create a new LLM cleanup client from configuration
+    pub fn new(config: &LlmCleanupConfig) -> Self {
+        let url = format!(
+            "{}/v1/chat/completions",
+            config
+                .endpoint
+                .trim_end_matches('/')
+                .trim_end_matches("/v1")
+        );
+        Self {
+            url,
+            model: config.model.clone(),
+            api_key: config
+                .api_key
+                .clone()
+                .or_else(|| std::env::var("VOXTYPE_LLM_API_KEY").ok()),
+            api_key_cmd: config.api_key_cmd.clone(),
+            api_key_keyring: config.api_key_keyring.clone(),
+            system_prompt: config.system_prompt.clone(),
+            timeout: Duration::from_millis(config.timeout_ms),
+        }
+    }
+
+    /// Clean up text with the LLM
+    ///
+    /// Returns the model's reply, or the original text on any failure.
+    pub async fn process(&self, text: &str) -> String {
+        let request = self.build_request(text);
+        let url = self.url.clone();
+        let api_key = self.api_key.clone();
+        let api_key_cmd = self.api_key_cmd.clone();
+        let api_key_keyring = self.api_key_keyring.clone();
+        let timeout = self.timeout;
+
+        let result = tokio::task::spawn_blocking(move || {
+            let api_key = crate::secret::resolve(
+                api_key.as_deref(),
+                api_key_cmd.as_deref(),
+                api_key_keyring.as_deref(),
+            )?;
+            send_request(&url, api_key, timeout, &request)
+        })
+        .await;
+
+        match result {
+            Ok(Ok(reply)) if !reply.is_empty() => {
+                tracing::info!("LLM cleanup: {:?} -> {:?}", text, reply);
+                reply
+            }
+            Ok(Ok(_)) => {
+                tracing::warn!("LLM cleanup returned empty output, using original text");
+                text.to_string()
+            }
+            Ok(Err(e)) => {
+                tracing::warn!("LLM cleanup failed: {}, using original text", e);
+                text.to_string()
+            }
+            Err(e) => {
+                tracing::warn!("LLM cleanup task failed: {}, using original text", e);
+                text.to_string()
+            }
+        }
+    }
+
+    /// Serialize the chat completions request body
+    fn build_request(&self, text: &str) -> String {
+        let request = ChatRequest {
+            model: &self.model,
+            messages: [
+                ChatMessage {
+                    role: "system",
+                    content: &self.system_prompt,
+                },
+                ChatMessage {
+                    role: "user",
+                    content: text,
+                },
+            ],
+            temperature: 0.1,
+            stream: false,
+        };
+        serde_json::to_string(&request).unwrap_or_default()
+    }
+}
+
+/// Send the request (blocking) and extract the reply text
+fn send_request(
+    url: &str,
+    api_key: Option<String>,
+    timeout: Duration,
+    body: &str,
+) -> Result<String, String> {
+    let mut request = crate::proxy::agent(url)
+        .build()
+        .post(url)
+        .timeout(timeout)
+        .set("Content-Type", "application/json");
+    if let Some(key) = api_key {
+        request = request.set("Authorization", &format!("Bearer {}", key));
+    }
+
+    let response = request.send_string(body).map_err(|e| match e {
+        ureq::Error::Status(code, resp) => {
+            let body = resp.into_string().unwrap_or_default();
+            format!("server returned {}: {}", code, body.trim())
+        }
+        ureq::Error::Transport(t) => format!("request failed: {}", t),
+    })?;
+
+    let body = response
+        .into_string()
+        .map_err(|e| format!("failed to read response: {}", e))?;
+    parse_reply(&body)
+}
+
+/// Extract the assistant message from a chat completions response
+fn parse_reply(body: &str) -> Result<String, String> {
+    let response: ChatResponse =
+        serde_json::from_str(body).map_err(|e| format!("invalid response: {}", e))?;
+    let content = response
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|c| c.message.content)
+        .ok_or_else(|| "response has no message content".to_string())?;
+    Ok(clean_reply(&content))
+}
+
+/// Strip reasoning blocks and wrapping quotes some models add
+fn clean_reply(content: &str) -> String {
+    let mut text = content;
+    if let Some(end) = text.find("</think>") {
+        text = &text[end + "</think>".len()..];
+    }
+    let text = text.trim();
+    let unquoted = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .filter(|t| !t.contains('"'));
+    unquoted.unwrap_or(text).trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_config(endpoint: &str) -> LlmCleanupConfig {
+        LlmCleanupConfig {
+            enabled: true,
+            endpoint: endpoint.to_string(),
+            api_key: Some("test-key".to_string()),
+            timeout_ms: 2000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_url_from_endpoint() {
+        let cleanup = LlmCleanup::new(&make_config("http://localhost:11434/"));
+        assert_eq!(cleanup.url, "http://localhost:11434/v1/chat/completions");
+        let cleanup = LlmCleanup::new(&make_config("https://api.openai.com/v1"));
+        assert_eq!(cleanup.url, "https://api.openai.com/v1/chat/completions");
+    }
+
+    #[test]
+    fn test_build_request() {
+        let cleanup = LlmCleanup::new(&make_config("http://localhost:11434"));
+        let body: serde_json::Value =
+            serde_json::from_str(&cleanup.build_request("helo world")).unwrap();
+        assert_eq!(body["model"], "llama3.2:1b");
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["content"], "helo world");
+        assert_eq!(body["stream"], false);
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        let body = r#"{"choices":[{"index":0,"message":{"role":"assistant","content":" Hello, world. "}}]}"#;
+        assert_eq!(parse_reply(body), Ok("Hello, world.".to_string()));
+        assert!(parse_reply(r#"{"choices":[]}"#).is_err());
+        assert!(parse_reply("not json").is_err());
+    }
+
+    #[test]
+    fn test_clean_reply() {
+        assert_eq!(
+            clean_reply("<think>user wants grammar</think>\n\nHello there."),
+            "Hello there."
+        );
+        assert_eq!(clean_reply("\"Hello there.\""), "Hello there.");
+        assert_eq!(
+            clean_reply("\"Hi\" she said, \"bye\""),
+            "\"Hi\" she said, \"bye\""
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_endpoint_fallback() {
+        let cleanup = LlmCleanup::new(&make_config("http://127.0.0.1:1"));
+        assert_eq!(cleanup.process("original text").await, "original text");
+    }
+}
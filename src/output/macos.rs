@@ -0,0 +1,422 @@
+//! macOS text output
+//!
+//! The `cgevent` driver types text by posting Quartz keyboard events that
+//! carry Unicode strings, so it doesn't depend on the keyboard layout or a
+//! helper tool. On macOS the `clipboard` driver uses `pbcopy`, and paste mode
+//! copies the text and presses Cmd+V.
+//!
+//! Posting keyboard events requires the Accessibility permission
+//! (System Settings > Privacy & Security > Accessibility). Without it macOS
+//! drops the events silently, so the driver reports itself unavailable and
+//! the chain falls back to the clipboard.
+
+use super::TextOutput;
+use crate::error::OutputError;
+use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation, CGKeyCode};
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// kVK_Return
+const KEY_RETURN: CGKeyCode = 36;
+/// kVK_Delete (Backspace)
+const KEY_DELETE: CGKeyCode = 51;
+/// kVK_ANSI_V
+const KEY_V: CGKeyCode = 9;
+
+/// Most UTF-16 units a single keyboard event can carry
+const MAX_EVENT_UTF16: usize = 20;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+}
+
+/// Whether voxtype may post keyboard events (Accessibility permission)
+fn accessibility_granted() -> bool {
+    unsafe { AXIsProcessTrusted() }
+}
+
+/// One step of typing
+#[derive(Debug, PartialEq)]
+enum Stroke<'a> {
+    /// Text carried by a single keyboard event
+    Text(&'a str),
+    /// A key press, with Shift held if set
+    Key(CGKeyCode, bool),
+}
+
+/// Split text into keyboard events: Unicode chunks of whole graphemes, and
+/// Return (or Shift+Return) for newlines
+///
+/// With `one_per_event`, every grapheme gets its own event so the typing
+/// delay applies between characters.
+fn strokes(text: &str, shift_enter_newlines: bool, one_per_event: bool) -> Vec<Stroke<'_>> {
+    let mut strokes = Vec::new();
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            strokes.push(Stroke::Key(KEY_RETURN, shift_enter_newlines));
+        }
+        let mut start = 0;
+        let mut units = 0;
+        for (offset, grapheme) in line.grapheme_indices(true) {
+            let len = grapheme.encode_utf16().count();
+            if offset > start && (one_per_event || units + len > MAX_EVENT_UTF16) {
+                strokes.push(Stroke::Text(&line[start..offset]));
+                start = offset;
+                units = 0;
+            }
+            units += len;
+        }
+        if start < line.len() {
+            strokes.push(Stroke::Text(&line[start..]));
+        }
+    }
+    strokes
+}
+
+/// Post the strokes as keyboard events (blocking)
+fn post_strokes(strokes: &[Stroke<'_>], delay: Duration) -> Result<(), OutputError> {
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState).map_err(|_| {
+        OutputError::InjectionFailed("failed to create a Quartz event source".to_string())
+    })?;
+    let event = |key: CGKeyCode, down: bool| {
+        CGEvent::new_keyboard_event(source.clone(), key, down).map_err(|_| {
+            OutputError::InjectionFailed("failed to create a keyboard event".to_string())
+        })
+    };
+
+    for (i, stroke) in strokes.iter().enumerate() {
+        if i > 0 && !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        for down in [true, false] {
+            match stroke {
+                Stroke::Text(text) => {
+                    let e = event(0, down)?;
+                    e.set_string(text);
+                    e.post(CGEventTapLocation::HID);
+                }
+                Stroke::Key(key, shift) => {
+                    let e = event(*key, down)?;
+                    e.set_flags(if *shift {
+                        CGEventFlags::CGEventFlagShift
+                    } else {
+                        CGEventFlags::CGEventFlagNull
+                    });
+                    e.post(CGEventTapLocation::HID);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Press a key `count` times, with `flags` held (blocking)
+fn post_key(key: CGKeyCode, flags: CGEventFlags, count: usize) -> Result<(), OutputError> {
+    let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState).map_err(|_| {
+        OutputError::InjectionFailed("failed to create a Quartz event source".to_string())
+    })?;
+    for _ in 0..count {
+        for down in [true, false] {
+            let e = CGEvent::new_keyboard_event(source.clone(), key, down).map_err(|_| {
+                OutputError::InjectionFailed("failed to create a keyboard event".to_string())
+            })?;
+            e.set_flags(flags);
+            e.post(CGEventTapLocation::HID);
+        }
+    }
+    Ok(())
+}
+
+/// Run a blocking event-posting function off the async runtime
+async fn blocking<F>(f: F) -> Result<(), OutputError>
+where
+    F: FnOnce() -> Result<(), OutputError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| OutputError::InjectionFailed(e.to_string()))?
+}
+
+/// Typing through Quartz keyboard events
+pub struct CgEventOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+    /// Delay between keystrokes in milliseconds
+    type_delay_ms: u32,
+    /// Delay before typing starts (ms)
+    pre_type_delay_ms: u32,
+    /// Convert newlines to Shift+Enter (for apps where Enter submits)
+    shift_enter_newlines: bool,
+}
+
+impl CgEventOutput {
+    /// Create a new CGEvent output
+    pub fn new(
+        auto_submit: bool,
+        append_text: Option<String>,
+        type_delay_ms: u32,
+        pre_type_delay_ms: u32,
+        shift_enter_newlines: bool,
+    ) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+            type_delay_ms,
+            pre_type_delay_ms,
+            shift_enter_newlines,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for CgEventOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let mut text = text.to_string();
+        if let Some(ref append) = self.append_text {
+            text.push_str(append);
+        }
+
+        if self.pre_type_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(self.pre_type_delay_ms.into())).await;
+        }
+
+        let delay = Duration::from_millis(self.type_delay_ms.into());
+        let shift_enter_newlines = self.shift_enter_newlines;
+        let auto_submit = self.auto_submit;
+        blocking(move || {
+            let mut strokes = strokes(&text, shift_enter_newlines, !delay.is_zero());
+            if auto_submit {
+                strokes.push(Stroke::Key(KEY_RETURN, false));
+            }
+            post_strokes(&strokes, delay)
+        })
+        .await?;
+
+        tracing::info!("Text typed via CGEvent");
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        blocking(move || post_key(KEY_DELETE, CGEventFlags::CGEventFlagNull, count)).await
+    }
+
+    async fn is_available(&self) -> bool {
+        accessibility_granted()
+    }
+
+    fn name(&self) -> &'static str {
+        "cgevent"
+    }
+}
+
+/// Clipboard output through `pbcopy`, optionally pasting with Cmd+V
+pub struct PasteboardOutput {
+    /// Whether to show a notification after copying
+    notify: bool,
+    /// Text to append after transcription
+    append_text: Option<String>,
+    /// Press Cmd+V after copying (paste mode)
+    paste: bool,
+    /// Whether to send Enter key after pasting
+    auto_submit: bool,
+    /// Delay before pasting (ms)
+    pre_type_delay_ms: u32,
+}
+
+impl PasteboardOutput {
+    /// Copy to the clipboard only
+    pub fn clipboard(notify: bool, append_text: Option<String>) -> Self {
+        Self {
+            notify,
+            append_text,
+            paste: false,
+            auto_submit: false,
+            pre_type_delay_ms: 0,
+        }
+    }
+
+    /// Copy to the clipboard and press Cmd+V
+    pub fn paste(auto_submit: bool, append_text: Option<String>, pre_type_delay_ms: u32) -> Self {
+        Self {
+            notify: false,
+            append_text,
+            paste: true,
+            auto_submit,
+            pre_type_delay_ms,
+        }
+    }
+
+    /// Replace the clipboard contents
+    async fn copy(&self, text: &str) -> Result<(), OutputError> {
+        let mut child = Command::new("pbcopy")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| OutputError::InjectionFailed(format!("pbcopy: {}", e)))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .await
+                .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+        }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+        if !status.success() {
+            return Err(OutputError::InjectionFailed(
+                "pbcopy exited with error".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Show a notification through Notification Center
+    async fn send_notification(&self, text: &str) {
+        let preview = if text.chars().count() > 80 {
+            format!("{}...", text.chars().take(80).collect::<String>())
+        } else {
+            text.to_string()
+        };
+        let quoted = preview.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let _ = Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification \"{}\" with title \"Copied to clipboard\"",
+                quoted
+            ))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await;
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for PasteboardOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let text = match self.append_text {
+            Some(ref append) => format!("{}{}", text, append),
+            None => text.to_string(),
+        };
+        self.copy(&text).await?;
+
+        if !self.paste {
+            if self.notify {
+                self.send_notification(&text).await;
+            }
+            tracing::info!("Text copied to clipboard ({} chars)", text.len());
+            return Ok(());
+        }
+
+        if self.pre_type_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(self.pre_type_delay_ms.into())).await;
+        }
+        let auto_submit = self.auto_submit;
+        blocking(move || {
+            post_key(KEY_V, CGEventFlags::CGEventFlagCommand, 1)?;
+            if auto_submit {
+                post_key(KEY_RETURN, CGEventFlags::CGEventFlagNull, 1)?;
+            }
+            Ok(())
+        })
+        .await?;
+
+        tracing::info!("Text pasted via Cmd+V ({} chars)", text.len());
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if self.paste {
+            if count == 0 {
+                return Ok(());
+            }
+            return blocking(move || post_key(KEY_DELETE, CGEventFlags::CGEventFlagNull, count))
+                .await;
+        }
+        self.copy("").await?;
+        tracing::info!("Clipboard cleared");
+        Ok(())
+    }
+
+    async fn is_available(&self) -> bool {
+        let pbcopy = Command::new("which")
+            .arg("pbcopy")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map(|s| s.success())
+            .unwrap_or(false);
+        pbcopy && (!self.paste || accessibility_granted())
+    }
+
+    fn name(&self) -> &'static str {
+        if self.paste {
+            "paste (pbcopy)"
+        } else {
+            "clipboard (pbcopy)"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strokes() {
+        assert_eq!(
+            strokes("hi\nthere", false, false),
+            vec![
+                Stroke::Text("hi"),
+                Stroke::Key(KEY_RETURN, false),
+                Stroke::Text("there"),
+            ]
+        );
+        assert_eq!(
+            strokes("a\n", true, false),
+            vec![Stroke::Text("a"), Stroke::Key(KEY_RETURN, true)]
+        );
+        assert_eq!(
+            strokes("né", false, true),
+            vec![Stroke::Text("n"), Stroke::Text("é")]
+        );
+    }
+
+    #[test]
+    fn test_strokes_chunk_limit() {
+        // 25 emoji (2 UTF-16 units each) split into events of at most 20 units
+        let text = "😀".repeat(25);
+        let chunks = strokes(&text, false, false);
+        assert_eq!(chunks.len(), 3);
+        for stroke in &chunks {
+            let Stroke::Text(chunk) = stroke else {
+                panic!("unexpected key stroke");
+            };
+            assert!(chunk.encode_utf16().count() <= MAX_EVENT_UTF16);
+        }
+    }
+}
@@ -3,30 +3,74 @@
 //! Provides text output via keyboard simulation or clipboard.
 //!
 //! Fallback chain for `mode = "type"`:
-//! 1. wtype - Wayland-native via virtual-keyboard protocol, best Unicode/CJK support, no daemon needed
-//! 2. eitype - Wayland via libei/EI protocol, works on GNOME/KDE (no virtual-keyboard support)
-//! 3. dotool - Works on X11/Wayland/TTY, supports keyboard layouts, no daemon needed
-//! 4. ydotool - Works on X11/Wayland/TTY, requires daemon
-//! 5. clipboard (wl-copy) - Wayland clipboard fallback
-//! 6. xclip - X11 clipboard fallback
+//! 0. terminal - kitty/WezTerm remote control, only when one of them has the focus
+//! 1. virtual-keyboard - Built-in zwp_virtual_keyboard_v1 client, no external tool needed
+//! 2. wtype - Wayland-native via virtual-keyboard protocol, best Unicode/CJK support, no daemon needed
+//! 3. eitype - Wayland via libei/EI protocol, works on GNOME/KDE (no virtual-keyboard support)
+//! 4. dotool - Works on X11/Wayland/TTY, supports keyboard layouts, no daemon needed
+//! 5. ydotool - Works on X11/Wayland/TTY, requires daemon
+//! 6. clipboard (wl-copy) - Wayland clipboard fallback
+//! 7. xclip - X11 clipboard fallback
+//!
+//! The opt-in `atspi` driver inserts text through the focused widget's AT-SPI
+//! EditableText interface, and the opt-in `ibus` driver commits it through
+//! IBus like an input method engine; list them first in `driver_order` to
+//! use them. The opt-in `neovim` driver does the same for a Neovim in the
+//! focused window, over its RPC socket, and the opt-in `tmux` driver for the
+//! focused tmux pane with `tmux send-keys`.
+//!
+//! Notify mode only shows the text in a notification with a Copy action,
+//! never typing it (preview while tuning, or during screen sharing).
 //!
 //! Paste mode (clipboard + Ctrl+V) helps with system with non US keyboard layouts.
+//!
+//! On macOS the default chain is cgevent (Quartz keyboard events) then the
+//! clipboard through pbcopy, and paste mode presses Cmd+V. On Windows it is
+//! sendinput then the Win32 clipboard, and paste mode presses Ctrl+V.
+//!
+//! Sinks listed in `also` (clipboard, file, obs) receive the text as well,
+//! each independently of the primary chain and of each other.
+//!
+//! The daemon keeps the last delivered output as a [`LastOutput`] so it can be
+//! undone: typed text is erased with BackSpace through the same method, and
+//! clipboard output is cleared.
 
+pub mod active_window;
+pub mod atspi;
 pub mod clipboard;
 pub mod dotool;
 pub mod eitype;
+pub mod file;
+pub mod ibus;
+pub mod llm_cleanup;
+#[cfg(target_os = "macos")]
+pub mod macos;
+#[cfg(target_os = "linux")]
+pub mod neovim;
+pub mod notify;
+pub mod obs;
 pub mod paste;
 pub mod post_process;
+pub mod terminal;
+pub mod tmux;
+pub mod translate;
+#[cfg(target_os = "linux")]
+pub mod virtual_keyboard;
+#[cfg(target_os = "windows")]
+pub mod windows;
 pub mod wtype;
 pub mod xclip;
 pub mod ydotool;
 
-use crate::config::{OutputConfig, OutputDriver};
+use crate::config::{
+    DriverEntry, NotificationConfig, NotificationUrgency, OutputConfig, OutputDriver, OutputSink,
+};
 use crate::error::OutputError;
 use std::borrow::Cow;
 use std::fs;
 use std::process::Stdio;
 use tokio::process::Command;
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Normalize Unicode curly quotes to ASCII equivalents.
 ///
@@ -100,40 +144,76 @@ pub fn engine_icon(engine: crate::config::TranscriptionEngine) -> &'static str {
         crate::config::TranscriptionEngine::Paraformer => "\u{1F4AC}",   // 💬
         crate::config::TranscriptionEngine::Dolphin => "\u{1F42C}",      // 🐬
         crate::config::TranscriptionEngine::Omnilingual => "\u{1F30D}",  // 🌍
+        crate::config::TranscriptionEngine::Onnx => "\u{26A1}",         // ⚡
     }
 }
 
+/// Show a desktop notification in the `[output.notification]` style
+///
+/// `urgency` and `timeout_ms` apply unless the configuration sets them.
+pub async fn send_notification(
+    config: &NotificationConfig,
+    title: &str,
+    body: &str,
+    urgency: NotificationUrgency,
+    timeout_ms: u32,
+) {
+    let urgency = config.urgency.unwrap_or(urgency);
+    let timeout_ms = config.timeout_ms.unwrap_or(timeout_ms);
+
+    if crate::sandbox::is_flatpak() {
+        // The portal has no timeout or icon for sandboxed apps
+        let priority = match urgency {
+            NotificationUrgency::Low => "low",
+            NotificationUrgency::Normal => "normal",
+            NotificationUrgency::Critical => "urgent",
+        };
+        crate::sandbox::send_notification(title, body, priority).await;
+        return;
+    }
+
+    let mut command = Command::new("notify-send");
+    command
+        .arg("--app-name=Voxtype")
+        .arg(format!("--urgency={}", urgency.as_str()))
+        .arg(format!("--expire-time={}", timeout_ms));
+    if let Some(ref icon) = config.icon {
+        command.arg(format!("--icon={}", icon));
+    }
+    let _ = command
+        .arg(title)
+        .arg(body)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+}
+
 /// Send a transcription notification with optional engine icon
 pub async fn send_transcription_notification(
     text: &str,
-    show_engine_icon: bool,
+    config: &NotificationConfig,
     engine: crate::config::TranscriptionEngine,
 ) {
-    // Truncate preview for notification (use chars() to handle multi-byte UTF-8)
-    let preview = if text.chars().count() > 80 {
+    let preview = if !config.show_text {
+        match text.split_whitespace().count() {
+            1 => "1 word".to_string(),
+            words => format!("{} words", words),
+        }
+    } else if text.chars().count() > 80 {
+        // Truncate preview for notification (use chars() to handle multi-byte UTF-8)
         format!("{}...", text.chars().take(80).collect::<String>())
     } else {
         text.to_string()
     };
 
-    let title = if show_engine_icon {
+    let title = if config.show_engine_icon {
         format!("{} Transcribed", engine_icon(engine))
     } else {
         "Transcribed".to_string()
     };
 
-    let _ = Command::new("notify-send")
-        .args([
-            "--app-name=Voxtype",
-            "--urgency=low",
-            "--expire-time=3000",
-            &title,
-            &preview,
-        ])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .await;
+    send_notification(config, &title, &preview, NotificationUrgency::Low, 3000).await;
 }
 
 /// Trait for text output implementations
@@ -142,6 +222,13 @@ pub trait TextOutput: Send + Sync {
     /// Output text (type it or copy to clipboard)
     async fn output(&self, text: &str) -> Result<(), OutputError>;
 
+    /// Remove previously output text (undo)
+    ///
+    /// `count` is the number of grapheme clusters that were output. Typing
+    /// methods press BackSpace that many times; clipboard methods clear the
+    /// clipboard instead.
+    async fn erase(&self, count: usize) -> Result<(), OutputError>;
+
     /// Check if this output method is available
     async fn is_available(&self) -> bool;
 
@@ -150,18 +237,82 @@ pub trait TextOutput: Send + Sync {
 }
 
 /// Default driver order for type mode
-const DEFAULT_DRIVER_ORDER: &[OutputDriver] = &[
-    OutputDriver::Wtype,
-    OutputDriver::Eitype,
-    OutputDriver::Dotool,
-    OutputDriver::Ydotool,
-    OutputDriver::Clipboard,
-    OutputDriver::Xclip,
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const DEFAULT_DRIVER_ORDER: &[DriverEntry] = &[
+    DriverEntry::Name(OutputDriver::Terminal),
+    DriverEntry::Name(OutputDriver::VirtualKeyboard),
+    DriverEntry::Name(OutputDriver::Wtype),
+    DriverEntry::Name(OutputDriver::Eitype),
+    DriverEntry::Name(OutputDriver::Dotool),
+    DriverEntry::Name(OutputDriver::Ydotool),
+    DriverEntry::Name(OutputDriver::Clipboard),
+    DriverEntry::Name(OutputDriver::Xclip),
+];
+
+/// Default driver order for type mode
+#[cfg(target_os = "macos")]
+const DEFAULT_DRIVER_ORDER: &[DriverEntry] = &[
+    DriverEntry::Name(OutputDriver::Cgevent),
+    DriverEntry::Name(OutputDriver::Clipboard),
+];
+
+/// Default driver order for type mode
+#[cfg(target_os = "windows")]
+const DEFAULT_DRIVER_ORDER: &[DriverEntry] = &[
+    DriverEntry::Name(OutputDriver::Sendinput),
+    DriverEntry::Name(OutputDriver::Clipboard),
 ];
 
+/// Placeholder for a driver that doesn't exist on this platform
+struct UnsupportedOutput(&'static str);
+
+#[async_trait::async_trait]
+impl TextOutput for UnsupportedOutput {
+    async fn output(&self, _text: &str) -> Result<(), OutputError> {
+        Err(OutputError::InjectionFailed(format!(
+            "{} is not supported on this platform",
+            self.0
+        )))
+    }
+
+    async fn erase(&self, _count: usize) -> Result<(), OutputError> {
+        self.output("").await
+    }
+
+    async fn is_available(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// Clipboard output for this platform (pbcopy on macOS, the Win32
+/// clipboard on Windows, wl-copy elsewhere)
+fn clipboard_output(notify: bool, append_text: Option<String>) -> Box<dyn TextOutput> {
+    #[cfg(target_os = "macos")]
+    {
+        Box::new(macos::PasteboardOutput::clipboard(notify, append_text))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // No notification tool to call on Windows
+        let _ = notify;
+        Box::new(windows::ClipboardOutput::clipboard(append_text))
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        Box::new(clipboard::ClipboardOutput::new(notify, append_text))
+    }
+}
+
 /// Create a TextOutput implementation for a specific driver
+///
+/// Options set on the `driver_order` entry take precedence over the shared
+/// `[output]` settings.
 fn create_driver_output(
-    driver: OutputDriver,
+    entry: &DriverEntry,
     config: &OutputConfig,
     pre_type_delay_ms: u32,
     is_first: bool,
@@ -169,43 +320,112 @@ fn create_driver_output(
     // Only the first driver in the chain should show notifications
     let show_notification = is_first && config.notification.on_transcription;
 
-    match driver {
+    let options = entry.options();
+    let type_delay_ms = options
+        .and_then(|o| o.type_delay_ms)
+        .unwrap_or(config.type_delay_ms);
+    let pre_type_delay_ms = options
+        .and_then(|o| o.pre_type_delay_ms)
+        .unwrap_or(pre_type_delay_ms);
+
+    match entry.driver() {
+        #[cfg(target_os = "linux")]
+        OutputDriver::VirtualKeyboard => Box::new(virtual_keyboard::VirtualKeyboardOutput::new(
+            config.auto_submit,
+            config.append_text.clone(),
+            type_delay_ms,
+            pre_type_delay_ms,
+            config.shift_enter_newlines,
+        )),
+        #[cfg(not(target_os = "linux"))]
+        OutputDriver::VirtualKeyboard => Box::new(UnsupportedOutput("virtual-keyboard")),
+        #[cfg(target_os = "macos")]
+        OutputDriver::Cgevent => Box::new(macos::CgEventOutput::new(
+            config.auto_submit,
+            config.append_text.clone(),
+            type_delay_ms,
+            pre_type_delay_ms,
+            config.shift_enter_newlines,
+        )),
+        #[cfg(not(target_os = "macos"))]
+        OutputDriver::Cgevent => Box::new(UnsupportedOutput("cgevent")),
+        #[cfg(target_os = "windows")]
+        OutputDriver::Sendinput => Box::new(windows::SendInputOutput::new(
+            config.auto_submit,
+            config.append_text.clone(),
+            type_delay_ms,
+            pre_type_delay_ms,
+            config.shift_enter_newlines,
+        )),
+        #[cfg(not(target_os = "windows"))]
+        OutputDriver::Sendinput => Box::new(UnsupportedOutput("sendinput")),
         OutputDriver::Wtype => Box::new(wtype::WtypeOutput::new(
             config.auto_submit,
             config.append_text.clone(),
-            config.type_delay_ms,
+            type_delay_ms,
             pre_type_delay_ms,
             config.shift_enter_newlines,
         )),
         OutputDriver::Eitype => Box::new(eitype::EitypeOutput::new(
             config.auto_submit,
             config.append_text.clone(),
-            config.type_delay_ms,
+            type_delay_ms,
             pre_type_delay_ms,
             config.shift_enter_newlines,
         )),
         OutputDriver::Dotool => Box::new(dotool::DotoolOutput::new(
-            config.type_delay_ms,
+            type_delay_ms,
             pre_type_delay_ms,
             show_notification,
             config.auto_submit,
             config.append_text.clone(),
-            config.dotool_xkb_layout.clone(),
-            config.dotool_xkb_variant.clone(),
+            options
+                .and_then(|o| o.xkb_layout.clone())
+                .or_else(|| config.dotool_xkb_layout.clone()),
+            options
+                .and_then(|o| o.xkb_variant.clone())
+                .or_else(|| config.dotool_xkb_variant.clone()),
         )),
-        OutputDriver::Ydotool => Box::new(ydotool::YdotoolOutput::new(
-            config.type_delay_ms,
-            pre_type_delay_ms,
+        OutputDriver::Ydotool => Box::new(
+            ydotool::YdotoolOutput::new(
+                type_delay_ms,
+                pre_type_delay_ms,
+                show_notification,
+                config.auto_submit,
+                config.append_text.clone(),
+            )
+            .with_socket(options.and_then(|o| o.socket.clone())),
+        ),
+        OutputDriver::Clipboard => clipboard_output(show_notification, config.append_text.clone()),
+        OutputDriver::Xclip => Box::new(xclip::XclipOutput::new(
             show_notification,
+            config.append_text.clone(),
+        )),
+        OutputDriver::Atspi => Box::new(atspi::AtspiOutput::new(
             config.auto_submit,
             config.append_text.clone(),
+            pre_type_delay_ms,
         )),
-        OutputDriver::Clipboard => Box::new(clipboard::ClipboardOutput::new(
-            show_notification,
+        OutputDriver::Ibus => Box::new(ibus::IbusOutput::new(
+            config.auto_submit,
             config.append_text.clone(),
         )),
-        OutputDriver::Xclip => Box::new(xclip::XclipOutput::new(
-            show_notification,
+        #[cfg(target_os = "linux")]
+        OutputDriver::Neovim => Box::new(neovim::NeovimOutput::new(
+            config.auto_submit,
+            config.append_text.clone(),
+            options.and_then(|o| o.socket.clone()),
+        )),
+        #[cfg(not(target_os = "linux"))]
+        OutputDriver::Neovim => Box::new(UnsupportedOutput("neovim")),
+        OutputDriver::Tmux => Box::new(tmux::TmuxOutput::new(
+            config.auto_submit,
+            config.append_text.clone(),
+            options.and_then(|o| o.target.clone()),
+            options.and_then(|o| o.socket.clone()),
+        )),
+        OutputDriver::Terminal => Box::new(terminal::TerminalOutput::new(
+            config.auto_submit,
             config.append_text.clone(),
         )),
     }
@@ -219,7 +439,7 @@ pub fn create_output_chain(config: &OutputConfig) -> Vec<Box<dyn TextOutput>> {
 /// Factory function that returns a fallback chain of output methods with an optional driver override
 pub fn create_output_chain_with_override(
     config: &OutputConfig,
-    driver_override: Option<&[OutputDriver]>,
+    driver_override: Option<&[DriverEntry]>,
 ) -> Vec<Box<dyn TextOutput>> {
     let mut chain: Vec<Box<dyn TextOutput>> = Vec::new();
 
@@ -229,7 +449,7 @@ pub fn create_output_chain_with_override(
     match config.mode {
         crate::config::OutputMode::Type => {
             // Determine driver order: CLI override > config > default
-            let driver_order: &[OutputDriver] = driver_override
+            let driver_order: &[DriverEntry] = driver_override
                 .or(config.driver_order.as_deref())
                 .unwrap_or(DEFAULT_DRIVER_ORDER);
 
@@ -245,16 +465,16 @@ pub fn create_output_chain_with_override(
             }
 
             // Build chain based on driver order
-            for (i, driver) in driver_order.iter().enumerate() {
+            for (i, entry) in driver_order.iter().enumerate() {
                 // Skip clipboard if it's in the middle and fallback_to_clipboard is false
                 // (clipboard should only be added if explicitly in the order OR fallback is enabled and it's last)
                 let is_last = i == driver_order.len() - 1;
-                if *driver == OutputDriver::Clipboard && !is_last && !config.fallback_to_clipboard {
+                if *entry == OutputDriver::Clipboard && !is_last && !config.fallback_to_clipboard {
                     continue;
                 }
 
                 chain.push(create_driver_output(
-                    *driver,
+                    entry,
                     config,
                     pre_type_delay_ms,
                     i == 0,
@@ -264,23 +484,33 @@ pub fn create_output_chain_with_override(
             // If fallback_to_clipboard is true but clipboard wasn't in the custom order, add it
             if config.fallback_to_clipboard
                 && config.driver_order.is_some()
-                && !driver_order.contains(&OutputDriver::Clipboard)
+                && !driver_order.iter().any(|e| *e == OutputDriver::Clipboard)
             {
-                chain.push(Box::new(clipboard::ClipboardOutput::new(
-                    false,
-                    config.append_text.clone(),
-                )));
+                chain.push(clipboard_output(false, config.append_text.clone()));
             }
         }
         crate::config::OutputMode::Clipboard => {
             // Only clipboard
-            chain.push(Box::new(clipboard::ClipboardOutput::new(
+            chain.push(clipboard_output(
                 config.notification.on_transcription,
                 config.append_text.clone(),
-            )));
+            ));
         }
         crate::config::OutputMode::Paste => {
             // Only paste mode (no fallback as requested)
+            #[cfg(target_os = "macos")]
+            chain.push(Box::new(macos::PasteboardOutput::paste(
+                config.auto_submit,
+                config.append_text.clone(),
+                pre_type_delay_ms,
+            )));
+            #[cfg(target_os = "windows")]
+            chain.push(Box::new(windows::ClipboardOutput::paste(
+                config.auto_submit,
+                config.append_text.clone(),
+                pre_type_delay_ms,
+            )));
+            #[cfg(not(any(target_os = "macos", target_os = "windows")))]
             chain.push(Box::new(paste::PasteOutput::new(
                 config.auto_submit,
                 config.append_text.clone(),
@@ -291,22 +521,94 @@ pub fn create_output_chain_with_override(
                 config.restore_clipboard_delay_ms,
             )));
         }
+        crate::config::OutputMode::Notify => {
+            // Preview only, nothing is typed or copied unless the user clicks Copy
+            chain.push(Box::new(notify::NotifyOutput));
+        }
         crate::config::OutputMode::File => {
             // File output is handled in the daemon before reaching the output chain.
             // If we get here, it means mode = "file" but no file_path is configured.
             tracing::warn!(
                 "Output mode is 'file' but no file_path configured. Falling back to clipboard."
             );
-            chain.push(Box::new(clipboard::ClipboardOutput::new(
+            chain.push(clipboard_output(
                 config.notification.on_transcription,
                 config.append_text.clone(),
-            )));
+            ));
         }
     }
 
     chain
 }
 
+/// Probe each method in the chain, returning its name and whether it's usable
+///
+/// Uses the same `is_available` checks as the fallback loop (binary present,
+/// ydotoold reachable, compositor protocol advertised, ...).
+pub async fn probe_chain(chain: &[Box<dyn TextOutput>]) -> Vec<(&'static str, bool)> {
+    let mut results = Vec::with_capacity(chain.len());
+    for output in chain {
+        results.push((output.name(), output.is_available().await));
+    }
+    results
+}
+
+/// Format probe results for logging, e.g. "wtype ✓, ydotool ✗"
+pub fn format_probe(results: &[(&'static str, bool)]) -> String {
+    results
+        .iter()
+        .map(|(name, ok)| format!("{} {}", name, if *ok { "✓" } else { "✗" }))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Move methods known to be unavailable to the end of the chain
+///
+/// They stay in the chain so a backend that comes back (e.g. ydotoold
+/// started later) is still tried once everything else has failed.
+pub fn deprioritize_unavailable(chain: &mut [Box<dyn TextOutput>], unavailable: &[&'static str]) {
+    chain.sort_by_key(|output| unavailable.contains(&output.name()));
+}
+
+/// Create the extra sinks from `also`, each as its own small fallback chain
+///
+/// These receive the text regardless of which method in the primary chain
+/// delivered it. A `file` sink without `file_path` is skipped with a warning.
+pub fn create_also_sinks(config: &OutputConfig) -> Vec<Vec<Box<dyn TextOutput>>> {
+    let mut sinks: Vec<Vec<Box<dyn TextOutput>>> = Vec::new();
+    for sink in &config.also {
+        match sink {
+            OutputSink::Clipboard => sinks.push(vec![
+                clipboard_output(false, None),
+                Box::new(xclip::XclipOutput::new(false, None)),
+            ]),
+            OutputSink::File => match config.file_path {
+                Some(ref path) => sinks.push(vec![Box::new(file::FileOutput::new(
+                    path.clone(),
+                    config.file_mode.clone(),
+                ))]),
+                None => tracing::warn!("output.also includes \"file\" but file_path is not set"),
+            },
+            OutputSink::Obs => sinks.push(vec![Box::new(obs::ObsOutput::new(config.obs.clone()))]),
+        }
+    }
+    sinks
+}
+
+/// Deliver text to every extra sink, logging failures without stopping
+pub async fn output_to_also_sinks(sinks: &[Vec<Box<dyn TextOutput>>], text: &str) {
+    for sink in sinks {
+        let options = OutputOptions {
+            pre_output_command: None,
+            post_output_command: None,
+        };
+        match output_with_fallback(sink, text, options).await {
+            Ok(index) => tracing::debug!("Also delivered via {}", sink[index].name()),
+            Err(e) => tracing::warn!("Extra output sink failed: {}", e),
+        }
+    }
+}
+
 /// Run a shell command (for pre/post hooks)
 pub async fn run_hook(command: &str, hook_name: &str) -> Result<(), String> {
     tracing::debug!("Running {} hook: {}", hook_name, command);
@@ -337,11 +639,12 @@ pub struct OutputOptions<'a> {
 
 /// Try each output method in the chain until one succeeds
 /// Pre/post output commands are run before and after typing (for compositor integration).
+/// Returns the index in `chain` of the method that delivered the text.
 pub async fn output_with_fallback(
     chain: &[Box<dyn TextOutput>],
     text: &str,
     options: OutputOptions<'_>,
-) -> Result<(), OutputError> {
+) -> Result<usize, OutputError> {
     // Normalize curly quotes to ASCII to prevent line break issues with keyboard tools
     let normalized_text = normalize_quotes(text);
 
@@ -355,7 +658,7 @@ pub async fn output_with_fallback(
 
     // Try each output method
     let mut result = Err(OutputError::AllMethodsFailed);
-    for output in chain {
+    for (index, output) in chain.iter().enumerate() {
         if !output.is_available().await {
             tracing::debug!("{} not available, trying next", output.name());
             continue;
@@ -364,9 +667,12 @@ pub async fn output_with_fallback(
         match output.output(&normalized_text).await {
             Ok(()) => {
                 tracing::debug!("Text output via {}", output.name());
-                result = Ok(());
+                result = Ok(index);
                 break;
             }
+            Err(OutputError::WrongWindow(reason)) => {
+                tracing::debug!("{} skipped: {}", output.name(), reason);
+            }
             Err(e) => {
                 tracing::warn!("{} failed: {}, trying next", output.name(), e);
             }
@@ -384,10 +690,201 @@ pub async fn output_with_fallback(
     result
 }
 
+/// Number of user-perceived characters (grapheme clusters) in `text`
+///
+/// This is how many BackSpace presses it takes to erase typed text: an
+/// emoji with skin tone or a letter with a combining accent is deleted with
+/// a single BackSpace, even though it spans several `char`s.
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// The most recent output, kept by the daemon so it can be undone
+pub struct LastOutput {
+    /// Output method that delivered the text
+    output: Box<dyn TextOutput>,
+    /// Text as delivered, including any appended text
+    text: String,
+    /// `append_text` the output method adds
+    append: String,
+    /// Whether Enter was pressed afterwards (the text has been submitted)
+    submitted: bool,
+}
+
+impl LastOutput {
+    /// Record text delivered by `output` with the given output settings
+    pub fn new(output: Box<dyn TextOutput>, text: &str, config: &OutputConfig) -> Self {
+        let append = config.append_text.clone().unwrap_or_default();
+        let text = normalize_quotes(text).into_owned() + &append;
+        let submitted = config.auto_submit
+            && matches!(
+                config.mode,
+                crate::config::OutputMode::Type | crate::config::OutputMode::Paste
+            );
+        Self {
+            output,
+            text,
+            append,
+            submitted,
+        }
+    }
+
+    /// Whether `text` is what was delivered (before any appended text)
+    pub fn delivered(&self, text: &str) -> bool {
+        self.text == normalize_quotes(text).into_owned() + &self.append
+    }
+
+    /// Name of the output method that delivered the text
+    pub fn method(&self) -> &'static str {
+        self.output.name()
+    }
+
+    /// Erase the text through the method that delivered it
+    pub async fn undo(&self) -> Result<(), OutputError> {
+        if self.submitted {
+            return Err(OutputError::NothingToUndo(
+                "the text was already submitted with Enter".to_string(),
+            ));
+        }
+        let count = grapheme_count(&self.text);
+        tracing::debug!("Undoing {} characters via {}", count, self.method());
+        self.output.erase(count).await
+    }
+
+    /// Erase the text and deliver `text` in its place, through the same method
+    pub async fn replace(&mut self, text: &str) -> Result<(), OutputError> {
+        self.undo().await?;
+        let text = normalize_quotes(text);
+        self.output.output(&text).await?;
+        self.text = text.into_owned() + &self.append;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_grapheme_count() {
+        assert_eq!(grapheme_count("hello"), 5);
+        // e + combining acute accent is one character on screen
+        assert_eq!(grapheme_count("caf\u{65}\u{301}"), 4);
+        // Thumbs up with skin tone modifier
+        assert_eq!(grapheme_count("ok \u{1F44D}\u{1F3FD}"), 4);
+        assert_eq!(grapheme_count("日本語"), 3);
+        assert_eq!(grapheme_count(""), 0);
+    }
+
+    struct FakeOutput(&'static str, bool);
+
+    #[async_trait::async_trait]
+    impl TextOutput for FakeOutput {
+        async fn output(&self, _text: &str) -> Result<(), OutputError> {
+            Ok(())
+        }
+
+        async fn erase(&self, _count: usize) -> Result<(), OutputError> {
+            Ok(())
+        }
+
+        async fn is_available(&self) -> bool {
+            self.1
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_and_deprioritize() {
+        let mut chain: Vec<Box<dyn TextOutput>> = vec![
+            Box::new(FakeOutput("wtype", false)),
+            Box::new(FakeOutput("dotool", true)),
+            Box::new(FakeOutput("ydotool", false)),
+            Box::new(FakeOutput("clipboard", true)),
+        ];
+
+        let results = probe_chain(&chain).await;
+        assert_eq!(
+            format_probe(&results),
+            "wtype ✗, dotool ✓, ydotool ✗, clipboard ✓"
+        );
+
+        let unavailable: Vec<&'static str> = results
+            .iter()
+            .filter(|(_, ok)| !ok)
+            .map(|(name, _)| *name)
+            .collect();
+        deprioritize_unavailable(&mut chain, &unavailable);
+        let names: Vec<_> = chain.iter().map(|o| o.name()).collect();
+        assert_eq!(names, ["dotool", "clipboard", "wtype", "ydotool"]);
+    }
+
+    #[test]
+    fn test_create_also_sinks() {
+        let mut config = crate::config::Config::default().output;
+        assert!(create_also_sinks(&config).is_empty());
+
+        config.also = vec![OutputSink::Clipboard, OutputSink::File];
+        // file sink is skipped without file_path
+        let sinks = create_also_sinks(&config);
+        assert_eq!(sinks.len(), 1);
+        assert_eq!(sinks[0].len(), 2);
+
+        config.file_path = Some(std::path::PathBuf::from("/tmp/voxtype.log"));
+        let sinks = create_also_sinks(&config);
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(sinks[1][0].name(), "file");
+    }
+
+    #[test]
+    fn test_last_output_counts_appended_text() {
+        let mut config = crate::config::Config::default().output;
+        config.append_text = Some(" ".to_string());
+        let last = LastOutput::new(
+            Box::new(clipboard::ClipboardOutput::new(false, None)),
+            "It\u{2019}s done.",
+            &config,
+        );
+        assert_eq!(last.text, "It's done. ");
+        assert!(last.delivered("It\u{2019}s done."));
+        assert!(!last.submitted);
+        assert_eq!(last.method(), "clipboard (wl-copy)");
+    }
+
+    #[tokio::test]
+    async fn test_last_output_replace() {
+        let mut config = crate::config::Config::default().output;
+        config.append_text = Some(" ".to_string());
+        let mut last = LastOutput::new(Box::new(FakeOutput("wtype", true)), "draft", &config);
+        assert!(last.delivered("draft"));
+        last.replace("refined text").await.unwrap();
+        assert_eq!(last.text, "refined text ");
+        assert!(!last.delivered("draft"));
+    }
+
+    #[test]
+    fn test_last_output_submitted_only_when_typed() {
+        let mut config = crate::config::Config::default().output;
+        config.auto_submit = true;
+        let typed = LastOutput::new(
+            Box::new(clipboard::ClipboardOutput::new(false, None)),
+            "ls",
+            &config,
+        );
+        assert!(typed.submitted);
+
+        config.mode = crate::config::OutputMode::Clipboard;
+        let copied = LastOutput::new(
+            Box::new(clipboard::ClipboardOutput::new(false, None)),
+            "ls",
+            &config,
+        );
+        assert!(!copied.submitted);
+    }
+
     #[test]
     fn test_normalize_quotes_no_change() {
         let text = "Hello, world! It's a test.";
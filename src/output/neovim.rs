@@ -0,0 +1,532 @@
+//! Neovim output over msgpack-RPC
+//!
+//! Inserts text into a running Neovim through its RPC socket instead of
+//! typing it into the terminal, where simulated keys race with mappings,
+//! autoindent and completion. The text goes through `vim.paste`, the same
+//! path as a bracketed paste, so it lands at the cursor in insert mode,
+//! after the cursor in normal mode, on the command line, or in the job of a
+//! terminal buffer, as one undo step.
+//!
+//! Only a Neovim in the focused window is used: its process has to run
+//! under the focused window's client (found through Hyprland, Sway, niri or
+//! X11). Otherwise the output fails and the next driver in the chain types
+//! the text. Neovim inside tmux or over ssh doesn't run under the terminal;
+//! for those, the `socket` option of the driver entry names the instance to
+//! use, wherever the focus is (`nvim --listen /tmp/nvim.sock`).
+//!
+//! Instances are found by the sockets Neovim creates by default:
+//! `$XDG_RUNTIME_DIR/nvim.<pid>.0`, or `$TMPDIR/nvim.$USER/*/nvim.<pid>.0`
+//! without a runtime directory.
+
+use super::active_window::{descends_from, parent_pid};
+use super::TextOutput;
+use crate::error::OutputError;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+/// How long to wait for Neovim to answer a request
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Pastes the text (first argument), then presses Enter if the second
+/// argument is true and the mode takes input
+const PASTE: &str = r#"
+local text, submit = ...
+if not vim.paste(vim.split(text, "\n", { plain = true }), -1) then
+  return "paste was cancelled"
+end
+local mode = vim.api.nvim_get_mode().mode:sub(1, 1)
+if submit and (mode == "i" or mode == "R" or mode == "c" or mode == "t") then
+  vim.api.nvim_input("<CR>")
+end
+"#;
+
+/// Removes the last paste: BackSpace (first argument) times where the mode
+/// takes input, undo elsewhere
+const ERASE: &str = r#"
+local count = ...
+local mode = vim.api.nvim_get_mode().mode:sub(1, 1)
+if mode == "i" or mode == "R" or mode == "c" or mode == "t" then
+  vim.api.nvim_input(string.rep("<BS>", count))
+else
+  vim.cmd("silent undo")
+end
+"#;
+
+fn failed(message: impl Into<String>) -> OutputError {
+    OutputError::InjectionFailed(message.into())
+}
+
+/// Neovim RPC output
+pub struct NeovimOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+    /// Socket of the instance to use, instead of the focused one
+    socket: Option<PathBuf>,
+    /// Socket of the instance that got the last text, to erase it there
+    last_socket: Mutex<Option<PathBuf>>,
+}
+
+impl NeovimOutput {
+    /// Create a new Neovim output
+    pub fn new(auto_submit: bool, append_text: Option<String>, socket: Option<PathBuf>) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+            socket,
+            last_socket: Mutex::new(None),
+        }
+    }
+
+    /// The socket of the Neovim to send text to
+    async fn target(&self) -> Result<PathBuf, OutputError> {
+        if let Some(ref socket) = self.socket {
+            return Ok(socket.clone());
+        }
+        let sockets = find_sockets();
+        if sockets.is_empty() {
+            return Err(failed("no running Neovim found"));
+        }
+        let focused = super::active_window::focused_pid().await.ok_or_else(|| {
+            failed("can't tell which window has the focus; set socket to pick a Neovim")
+        })?;
+
+        let mut in_focus = sockets
+            .into_iter()
+            .filter(|(_, pid)| descends_from(*pid, focused, parent_pid));
+        match (in_focus.next(), in_focus.next()) {
+            (Some((socket, _)), None) => Ok(socket),
+            (None, _) => Err(failed("no Neovim in the focused window")),
+            (Some(_), Some(_)) => Err(failed(
+                "several Neovim instances in the focused window; set socket to pick one",
+            )),
+        }
+    }
+
+    /// Run a Lua chunk in the Neovim at `socket`; it returns an error
+    /// message or nothing
+    async fn exec_lua(socket: &Path, code: &str, args: Vec<Value>) -> Result<(), OutputError> {
+        let mut client = Client::connect(socket).await?;
+
+        // A Neovim at a prompt only answers after it, so don't wait for one
+        let mode = client.call("nvim_get_mode", Vec::new()).await?;
+        if mode.get("blocking") == Some(&Value::Bool(true)) {
+            return Err(failed("Neovim is waiting for input"));
+        }
+
+        let params = vec![Value::Str(code.to_string()), Value::Array(args)];
+        match client.call("nvim_exec_lua", params).await? {
+            Value::Str(error) => Err(failed(format!("Neovim: {}", error))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for NeovimOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let socket = self.target().await?;
+        let text = match self.append_text {
+            Some(ref append) => format!("{}{}", text, append),
+            None => text.to_string(),
+        };
+        Self::exec_lua(
+            &socket,
+            PASTE,
+            vec![Value::Str(text), Value::Bool(self.auto_submit)],
+        )
+        .await?;
+        tracing::debug!("Inserted text into Neovim at {:?}", socket);
+        *self.last_socket.lock().unwrap() = Some(socket);
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        let socket = self.last_socket.lock().unwrap().clone();
+        let socket = socket.ok_or_else(|| failed("no text was sent to Neovim"))?;
+        Self::exec_lua(&socket, ERASE, vec![Value::Int(count as i64)]).await
+    }
+
+    async fn is_available(&self) -> bool {
+        // Neovim may start after the daemon; whether one is focused is
+        // checked for each output
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "neovim"
+    }
+}
+
+/// Sockets of running Neovim instances, with the pid of each
+fn find_sockets() -> Vec<(PathBuf, u32)> {
+    let mut dirs = Vec::new();
+    if let Some(runtime) = std::env::var_os("XDG_RUNTIME_DIR") {
+        dirs.push(PathBuf::from(runtime));
+    }
+    if let Ok(user) = std::env::var("USER") {
+        let tmp = std::env::temp_dir().join(format!("nvim.{}", user));
+        if let Ok(entries) = std::fs::read_dir(tmp) {
+            dirs.extend(entries.flatten().map(|entry| entry.path()));
+        }
+    }
+
+    dirs.iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let pid = socket_pid(&entry.file_name().to_string_lossy())?;
+            Some((entry.path(), pid))
+        })
+        .collect()
+}
+
+/// Pid in the name of a default Neovim socket, "nvim.<pid>.0"
+fn socket_pid(name: &str) -> Option<u32> {
+    name.strip_prefix("nvim.")?.strip_suffix(".0")?.parse().ok()
+}
+
+/// A connection to one Neovim
+struct Client {
+    stream: UnixStream,
+    next_id: i64,
+    /// Received bytes not decoded yet
+    buffer: Vec<u8>,
+}
+
+impl Client {
+    async fn connect(socket: &Path) -> Result<Self, OutputError> {
+        let stream = UnixStream::connect(socket)
+            .await
+            .map_err(|e| failed(format!("Neovim socket {:?}: {}", socket, e)))?;
+        Ok(Self {
+            stream,
+            next_id: 1,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Call an API method and wait for its result
+    async fn call(&mut self, method: &str, params: Vec<Value>) -> Result<Value, OutputError> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut request = Vec::new();
+        Value::Array(vec![
+            Value::Int(0),
+            Value::Int(id),
+            Value::Str(method.to_string()),
+            Value::Array(params),
+        ])
+        .encode(&mut request);
+
+        tokio::time::timeout(TIMEOUT, self.exchange(id, &request))
+            .await
+            .map_err(|_| failed(format!("Neovim didn't answer {}", method)))?
+    }
+
+    async fn exchange(&mut self, id: i64, request: &[u8]) -> Result<Value, OutputError> {
+        self.stream
+            .write_all(request)
+            .await
+            .map_err(|e| failed(e.to_string()))?;
+        loop {
+            match Value::decode(&self.buffer) {
+                Ok((message, used)) => {
+                    self.buffer.drain(..used);
+                    // A response is [1, id, error, result]; skip anything else
+                    if let Value::Array(items) = message {
+                        if let [Value::Int(1), Value::Int(msg_id), error, result] = &items[..] {
+                            if *msg_id == id {
+                                return match error {
+                                    Value::Nil => Ok(result.clone()),
+                                    error => {
+                                        Err(failed(format!("Neovim: {}", error_message(error))))
+                                    }
+                                };
+                            }
+                        }
+                    }
+                    continue;
+                }
+                Err(DecodeError::Incomplete) => {}
+                Err(DecodeError::Invalid) => return Err(failed("invalid message from Neovim")),
+            }
+            let mut chunk = [0u8; 4096];
+            let read = self
+                .stream
+                .read(&mut chunk)
+                .await
+                .map_err(|e| failed(e.to_string()))?;
+            if read == 0 {
+                return Err(failed("Neovim closed the connection"));
+            }
+            self.buffer.extend_from_slice(&chunk[..read]);
+        }
+    }
+}
+
+/// Message of an RPC error, which Neovim sends as [type, message]
+fn error_message(error: &Value) -> String {
+    match error {
+        Value::Array(items) => match items.get(1) {
+            Some(Value::Str(message)) => message.clone(),
+            _ => format!("{:?}", error),
+        },
+        Value::Str(message) => message.clone(),
+        _ => format!("{:?}", error),
+    }
+}
+
+/// The msgpack values the API calls use; floats, binary and extension
+/// types (buffer and window handles) are skipped as `Other`
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>),
+    Other,
+}
+
+#[derive(Debug, PartialEq)]
+enum DecodeError {
+    /// More bytes are needed
+    Incomplete,
+    Invalid,
+}
+
+impl Value {
+    /// Value of `key` in a map
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| matches!(k, Value::Str(k) if k == key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        // Header of a string, array or map: the fix form, or the 32-bit one
+        let header = |out: &mut Vec<u8>, fix: u8, max: usize, long: u8, len: usize| {
+            if len <= max {
+                out.push(fix | len as u8);
+            } else {
+                out.push(long);
+                out.extend_from_slice(&(len as u32).to_be_bytes());
+            }
+        };
+        match self {
+            Value::Nil | Value::Other => out.push(0xc0),
+            Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+            Value::Int(n) if (0..128).contains(n) => out.push(*n as u8),
+            Value::Int(n) => {
+                out.push(0xd3);
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+            Value::Str(s) => {
+                header(out, 0xa0, 31, 0xdb, s.len());
+                out.extend_from_slice(s.as_bytes());
+            }
+            Value::Array(items) => {
+                header(out, 0x90, 15, 0xdd, items.len());
+                for item in items {
+                    item.encode(out);
+                }
+            }
+            Value::Map(entries) => {
+                header(out, 0x80, 15, 0xdf, entries.len());
+                for (key, value) in entries {
+                    key.encode(out);
+                    value.encode(out);
+                }
+            }
+        }
+    }
+
+    /// Decode the first value in `bytes`, returning it and its length
+    fn decode(bytes: &[u8]) -> Result<(Value, usize), DecodeError> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let value = reader.value()?;
+        Ok((value, reader.pos))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(n).ok_or(DecodeError::Invalid)?;
+        let bytes = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or(DecodeError::Incomplete)?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Big-endian unsigned integer of `n` bytes
+    fn uint(&mut self, n: usize) -> Result<u64, DecodeError> {
+        Ok(self
+            .take(n)?
+            .iter()
+            .fold(0, |value, byte| value << 8 | *byte as u64))
+    }
+
+    fn len(&mut self, n: usize) -> Result<usize, DecodeError> {
+        usize::try_from(self.uint(n)?).map_err(|_| DecodeError::Invalid)
+    }
+
+    fn string(&mut self, len: usize) -> Result<Value, DecodeError> {
+        let bytes = self.take(len)?;
+        Ok(Value::Str(String::from_utf8_lossy(bytes).into_owned()))
+    }
+
+    fn array(&mut self, len: usize) -> Result<Value, DecodeError> {
+        let mut items = Vec::new();
+        for _ in 0..len {
+            items.push(self.value()?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn map(&mut self, len: usize) -> Result<Value, DecodeError> {
+        let mut entries = Vec::new();
+        for _ in 0..len {
+            entries.push((self.value()?, self.value()?));
+        }
+        Ok(Value::Map(entries))
+    }
+
+    fn value(&mut self) -> Result<Value, DecodeError> {
+        let byte = self.take(1)?[0];
+        Ok(match byte {
+            0x00..=0x7f => Value::Int(byte as i64),
+            0x80..=0x8f => self.map((byte & 0x0f) as usize)?,
+            0x90..=0x9f => self.array((byte & 0x0f) as usize)?,
+            0xa0..=0xbf => self.string((byte & 0x1f) as usize)?,
+            0xc0 => Value::Nil,
+            0xc1 => return Err(DecodeError::Invalid),
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            // bin 8/16/32
+            0xc4..=0xc6 => {
+                let len = self.len(1 << (byte - 0xc4))?;
+                self.take(len)?;
+                Value::Other
+            }
+            // ext 8/16/32: length, type, data
+            0xc7..=0xc9 => {
+                let len = self.len(1 << (byte - 0xc7))?;
+                self.take(len + 1)?;
+                Value::Other
+            }
+            // float 32/64
+            0xca => {
+                self.take(4)?;
+                Value::Other
+            }
+            0xcb => {
+                self.take(8)?;
+                Value::Other
+            }
+            // uint 8/16/32/64
+            0xcc..=0xcf => {
+                let value = self.uint(1 << (byte - 0xcc))?;
+                i64::try_from(value).map_or(Value::Other, Value::Int)
+            }
+            // int 8/16/32/64, sign-extended from its size
+            0xd0..=0xd3 => {
+                let size = 1 << (byte - 0xd0);
+                let shift = 64 - 8 * size;
+                Value::Int(((self.uint(size)? << shift) as i64) >> shift)
+            }
+            // fixext 1/2/4/8/16: type, data
+            0xd4..=0xd8 => {
+                self.take(1 + (1 << (byte - 0xd4)))?;
+                Value::Other
+            }
+            // str 8/16/32
+            0xd9..=0xdb => {
+                let len = self.len(1 << (byte - 0xd9))?;
+                self.string(len)?
+            }
+            0xdc | 0xdd => {
+                let len = self.len(if byte == 0xdc { 2 } else { 4 })?;
+                self.array(len)?
+            }
+            0xde | 0xdf => {
+                let len = self.len(if byte == 0xde { 2 } else { 4 })?;
+                self.map(len)?
+            }
+            0xe0..=0xff => Value::Int(byte as i8 as i64),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_msgpack_roundtrip() {
+        let value = Value::Array(vec![
+            Value::Int(0),
+            Value::Int(-5),
+            Value::Int(1 << 40),
+            Value::Str("x".repeat(40)),
+            Value::Map(vec![(Value::Str("blocking".into()), Value::Bool(false))]),
+            Value::Nil,
+        ]);
+        let mut bytes = Vec::new();
+        value.encode(&mut bytes);
+        assert_eq!(Value::decode(&bytes), Ok((value, bytes.len())));
+        assert_eq!(
+            Value::decode(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::Incomplete)
+        );
+    }
+
+    #[test]
+    fn test_msgpack_decode_response() {
+        // [1, 7, nil, {"mode": "i", "blocking": false}] with a uint8 id, a
+        // buffer handle (fixext 1) and an int8
+        let bytes = [
+            0x94, 0x01, 0xcc, 0x07, 0xc0, 0x83, 0xa4, b'm', b'o', b'd', b'e', 0xa1, b'i', 0xa8,
+            b'b', b'l', b'o', b'c', b'k', b'i', b'n', b'g', 0xc2, 0xa3, b'b', b'u', b'f', 0xd4,
+            0x00, 0x01, 0xd0, 0xff,
+        ];
+        let (value, used) = Value::decode(&bytes).unwrap();
+        assert_eq!(used, bytes.len() - 2);
+        let Value::Array(items) = value else {
+            panic!("not an array: {:?}", value);
+        };
+        assert_eq!(items[1], Value::Int(7));
+        assert_eq!(items[3].get("mode"), Some(&Value::Str("i".into())));
+        assert_eq!(items[3].get("blocking"), Some(&Value::Bool(false)));
+        assert_eq!(items[3].get("buf"), Some(&Value::Other));
+        assert_eq!(Value::decode(&[0xd0, 0xff]), Ok((Value::Int(-1), 2)));
+        assert_eq!(Value::decode(&[0xc1]), Err(DecodeError::Invalid));
+    }
+
+    #[test]
+    fn test_socket_pid() {
+        assert_eq!(socket_pid("nvim.4242.0"), Some(4242));
+        assert_eq!(socket_pid("nvim.sock"), None);
+    }
+}
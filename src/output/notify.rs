@@ -0,0 +1,145 @@
+//! Notification-only output (preview mode)
+//!
+//! Shows the transcription in a desktop notification instead of typing it.
+//! The notification carries a "Copy" action that puts the text on the
+//! clipboard. Useful while tuning models and prompts, or as a safety mode
+//! during screen sharing.
+//!
+//! Requires: notify-send (libnotify 0.7.9+ for the Copy action)
+
+use super::clipboard::ClipboardOutput;
+use super::xclip::XclipOutput;
+use super::TextOutput;
+use crate::error::OutputError;
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// How long the preview stays on screen (ms)
+const EXPIRE_MS: &str = "10000";
+
+/// Action key reported by notify-send when Copy is clicked
+const COPY_ACTION: &str = "copy";
+
+/// Title shown above the transcription
+const TITLE: &str = "Transcription preview";
+
+/// Notification-only output
+pub struct NotifyOutput;
+
+#[async_trait::async_trait]
+impl TextOutput for NotifyOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let body = escape_markup(text);
+        let child = Command::new("notify-send")
+            .args([
+                "--app-name=Voxtype",
+                "--urgency=low",
+                &format!("--expire-time={}", EXPIRE_MS),
+                &format!("--action={}=Copy", COPY_ACTION),
+                "--wait",
+                TITLE,
+                &body,
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| OutputError::InjectionFailed(format!("notify-send: {}", e)))?;
+
+        // notify-send blocks until the notification is closed, so wait for the
+        // action in the background instead of holding up the daemon
+        let copy_text = text.to_string();
+        tokio::spawn(async move {
+            let output = match child.wait_with_output().await {
+                Ok(output) => output,
+                Err(e) => {
+                    tracing::warn!("notify-send failed: {}", e);
+                    return;
+                }
+            };
+
+            if !output.status.success() {
+                // Older libnotify has no --action/--wait; show a plain notification
+                let _ = Command::new("notify-send")
+                    .args([
+                        "--app-name=Voxtype",
+                        "--urgency=low",
+                        &format!("--expire-time={}", EXPIRE_MS),
+                        TITLE,
+                        &body,
+                    ])
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status()
+                    .await;
+                return;
+            }
+
+            if String::from_utf8_lossy(&output.stdout).trim() == COPY_ACTION {
+                copy_to_clipboard(&copy_text).await;
+            }
+        });
+
+        tracing::info!("Transcription shown in notification ({} chars)", text.len());
+        Ok(())
+    }
+
+    async fn erase(&self, _count: usize) -> Result<(), OutputError> {
+        Err(OutputError::InjectionFailed(
+            "nothing to erase: text was only shown in a notification".to_string(),
+        ))
+    }
+
+    async fn is_available(&self) -> bool {
+        Command::new("which")
+            .arg("notify-send")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    fn name(&self) -> &'static str {
+        "notify"
+    }
+}
+
+/// Copy text with wl-copy, falling back to xclip
+async fn copy_to_clipboard(text: &str) {
+    let wl_copy = ClipboardOutput::new(false, None);
+    let xclip = XclipOutput::new(false, None);
+    let result = if wl_copy.is_available().await {
+        wl_copy.output(text).await
+    } else {
+        xclip.output(text).await
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to copy transcription from notification: {}", e);
+    }
+}
+
+/// Escape characters notification servers treat as markup
+fn escape_markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_markup() {
+        assert_eq!(escape_markup("plain text"), "plain text");
+        assert_eq!(
+            escape_markup("if a < b && c > d"),
+            "if a &lt; b &amp;&amp; c &gt; d"
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! OBS live captions
+//!
+//! Sends each transcription to OBS over obs-websocket (v5, built into OBS 28
+//! and later), so streamers get captions from their push-to-talk hotkey. Used
+//! for the `also = ["obs"]` sink. Depending on [output.obs], the text goes out
+//! as a stream caption (`SendStreamCaption`, while streaming) and into a text
+//! source (`SetInputSettings`).
+//!
+//! Each transcription opens its own connection, so OBS can be restarted
+//! between dictations:
+//! 1. WebSocket handshake, then OBS sends Hello (op 0), with an
+//!    authentication challenge when a password is set
+//! 2. Identify (op 1), answered with Identified (op 2)
+//! 3. One Request (op 6) per update, each answered with a RequestResponse (op 7)
+
+use super::TextOutput;
+use crate::config::ObsConfig;
+use crate::error::OutputError;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Connect, read and write timeout
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest message accepted from OBS
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Appended to the handshake key to derive Sec-WebSocket-Accept (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// obs-websocket request status when the stream isn't running
+const OUTPUT_NOT_RUNNING: u64 = 501;
+
+/// Sec-WebSocket-Accept value the server must answer a key with
+fn accept_key(key: &str) -> String {
+    let digest = ring::digest::digest(
+        &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{}{}", key, WEBSOCKET_GUID).as_bytes(),
+    );
+    BASE64.encode(digest.as_ref())
+}
+
+/// obs-websocket authentication string for a password and the Hello challenge
+fn auth_string(password: &str, salt: &str, challenge: &str) -> String {
+    let sha256 =
+        |s: String| BASE64.encode(ring::digest::digest(&ring::digest::SHA256, s.as_bytes()));
+    let secret = sha256(format!("{}{}", password, salt));
+    sha256(format!("{}{}", secret, challenge))
+}
+
+/// A WebSocket client connection
+struct WebSocket {
+    stream: BufReader<TcpStream>,
+}
+
+impl WebSocket {
+    /// Connect and complete the handshake
+    fn connect(url: &str) -> Result<Self, String> {
+        let rest = url
+            .strip_prefix("ws://")
+            .ok_or_else(|| format!("OBS url must start with ws://, got: {}", url))?;
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let address = if host
+            .rsplit_once(':')
+            .is_some_and(|(_, p)| p.parse::<u16>().is_ok())
+        {
+            host.to_string()
+        } else {
+            format!("{}:4455", host)
+        };
+
+        let mut last_err = "address not found".to_string();
+        let mut connected = None;
+        for addr in address.to_socket_addrs().map_err(|e| e.to_string())? {
+            match TcpStream::connect_timeout(&addr, TIMEOUT) {
+                Ok(tcp) => {
+                    connected = Some(tcp);
+                    break;
+                }
+                Err(e) => last_err = e.to_string(),
+            }
+        }
+        let tcp = connected
+            .ok_or_else(|| format!("Failed to connect to OBS at {}: {}", address, last_err))?;
+        tcp.set_read_timeout(Some(TIMEOUT))
+            .and_then(|_| tcp.set_write_timeout(Some(TIMEOUT)))
+            .and_then(|_| tcp.set_nodelay(true))
+            .map_err(|e| e.to_string())?;
+
+        let key = BASE64.encode(uuid::Uuid::new_v4().as_bytes());
+        let mut stream = BufReader::new(tcp);
+        write!(
+            stream.get_mut(),
+            "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\
+             Sec-WebSocket-Protocol: obswebsocket.json\r\n\r\n",
+            path,
+            host,
+            key
+        )
+        .map_err(|e| e.to_string())?;
+
+        // Status line, then headers up to a blank line
+        let mut status = String::new();
+        stream.read_line(&mut status).map_err(|e| e.to_string())?;
+        if status.split_whitespace().nth(1) != Some("101") {
+            return Err(format!(
+                "OBS refused the WebSocket connection: {}",
+                status.trim()
+            ));
+        }
+        let mut accept = None;
+        loop {
+            let mut line = String::new();
+            if stream.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+                return Err("OBS closed the connection during the handshake".to_string());
+            }
+            let line = line.trim();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("sec-websocket-accept") {
+                    accept = Some(value.trim().to_string());
+                }
+            }
+        }
+        if accept.as_deref() != Some(accept_key(&key).as_str()) {
+            return Err("Invalid WebSocket handshake from OBS".to_string());
+        }
+        Ok(Self { stream })
+    }
+
+    /// Send a JSON message as a text frame
+    fn send(&mut self, message: &Value) -> io::Result<()> {
+        write_frame(
+            self.stream.get_mut(),
+            OP_TEXT,
+            message.to_string().as_bytes(),
+        )
+    }
+
+    /// Read the next JSON message, answering pings on the way
+    ///
+    /// Control frames may arrive between the fragments of a message.
+    fn receive(&mut self) -> io::Result<Value> {
+        let mut message = Vec::new();
+        loop {
+            let (fin, opcode, payload) = read_frame(&mut self.stream)?;
+            match opcode {
+                OP_CONTINUATION | OP_TEXT => {
+                    if message.len() + payload.len() > MAX_MESSAGE_BYTES {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "Message from OBS too large",
+                        ));
+                    }
+                    message.extend_from_slice(&payload);
+                    if fin {
+                        return serde_json::from_slice(&message)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+                    }
+                }
+                OP_PING => write_frame(self.stream.get_mut(), OP_PONG, &payload)?,
+                OP_CLOSE => {
+                    let reason = String::from_utf8_lossy(payload.get(2..).unwrap_or_default());
+                    let code = payload
+                        .get(..2)
+                        .map_or(0, |c| u16::from_be_bytes([c[0], c[1]]));
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        format!("OBS closed the connection ({}: {})", code, reason),
+                    ));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Close the connection politely
+    fn close(mut self) {
+        let _ = write_frame(self.stream.get_mut(), OP_CLOSE, &1000u16.to_be_bytes());
+    }
+}
+
+/// Write one masked frame, as clients must
+fn write_frame<W: Write>(writer: &mut W, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(0x80 | len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(0x80 | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    let mask: [u8; 4] = uuid::Uuid::new_v4().as_bytes()[..4].try_into().unwrap();
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+/// Read one frame: whether it ends its message, its opcode and its payload
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<(bool, u8, Vec<u8>)> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head)?;
+    let len = match head[1] & 0x7F {
+        126 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as usize
+        }
+        127 => {
+            let mut len = [0u8; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len) as usize
+        }
+        len => len as usize,
+    };
+    if len > MAX_MESSAGE_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Message from OBS too large",
+        ));
+    }
+    let mut mask = [0u8; 4];
+    if head[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    for (i, b) in payload.iter_mut().enumerate() {
+        *b ^= mask[i % 4];
+    }
+    Ok((head[0] & 0x80 != 0, head[0] & 0x0F, payload))
+}
+
+/// Send text to OBS as configured
+fn send_to_obs(config: &ObsConfig, text: &str) -> Result<(), String> {
+    let mut requests = Vec::new();
+    if config.captions {
+        requests.push(("SendStreamCaption", json!({ "captionText": text })));
+    }
+    if let Some(ref source) = config.text_source {
+        requests.push((
+            "SetInputSettings",
+            json!({ "inputName": source, "inputSettings": { "text": text } }),
+        ));
+    }
+    if requests.is_empty() {
+        return Ok(());
+    }
+
+    let mut ws = WebSocket::connect(&config.url)?;
+    let hello = ws.receive().map_err(|e| e.to_string())?;
+    if hello["op"] != 0 {
+        return Err(format!("Expected Hello from OBS, got: {}", hello));
+    }
+    let mut identify = json!({ "rpcVersion": 1, "eventSubscriptions": 0 });
+    if let Some(auth) = hello["d"].get("authentication") {
+        let password = config
+            .password
+            .as_deref()
+            .ok_or("OBS requires a password; set password in [output.obs]")?;
+        identify["authentication"] = json!(auth_string(
+            password,
+            auth["salt"].as_str().unwrap_or_default(),
+            auth["challenge"].as_str().unwrap_or_default(),
+        ));
+    }
+    ws.send(&json!({ "op": 1, "d": identify }))
+        .map_err(|e| e.to_string())?;
+    let identified = ws.receive().map_err(|e| match e.kind() {
+        io::ErrorKind::ConnectionAborted => format!("{} (wrong password?)", e),
+        _ => e.to_string(),
+    })?;
+    if identified["op"] != 2 {
+        return Err(format!("Expected Identified from OBS, got: {}", identified));
+    }
+
+    for (i, (request_type, data)) in requests.into_iter().enumerate() {
+        let request_id = format!("voxtype-{}", i);
+        ws.send(&json!({
+            "op": 6,
+            "d": { "requestType": request_type, "requestId": request_id, "requestData": data },
+        }))
+        .map_err(|e| e.to_string())?;
+
+        // Skip events and responses to other requests
+        let status = loop {
+            let message = ws.receive().map_err(|e| e.to_string())?;
+            if message["op"] == 7 && message["d"]["requestId"] == request_id.as_str() {
+                break message["d"]["requestStatus"].clone();
+            }
+        };
+        if status["result"] == true {
+            tracing::debug!("OBS {} succeeded", request_type);
+        } else if status["code"] == OUTPUT_NOT_RUNNING {
+            tracing::debug!("Not sending a stream caption: OBS isn't streaming");
+        } else {
+            return Err(format!(
+                "OBS {} failed: {}",
+                request_type,
+                status["comment"].as_str().unwrap_or("unknown error")
+            ));
+        }
+    }
+    ws.close();
+    Ok(())
+}
+
+/// OBS caption output
+pub struct ObsOutput {
+    config: ObsConfig,
+}
+
+impl ObsOutput {
+    /// Create a new OBS output
+    pub fn new(config: ObsConfig) -> Self {
+        Self { config }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for ObsOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        let config = self.config.clone();
+        let text = text.to_string();
+        tokio::task::spawn_blocking(move || send_to_obs(&config, &text))
+            .await
+            .map_err(|e| OutputError::InjectionFailed(e.to_string()))?
+            .map_err(OutputError::InjectionFailed)
+    }
+
+    async fn erase(&self, _count: usize) -> Result<(), OutputError> {
+        Err(OutputError::InjectionFailed(
+            "text sent to OBS can't be erased".to_string(),
+        ))
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "obs"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_handshake_hashes() {
+        // Examples from RFC 6455 and the obs-websocket protocol docs
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(
+            auth_string(
+                "supersecretpassword",
+                "lM1GncleQOaCu9lT1yeUZhFYnqhsLLP1G5lAGo3ixaI=",
+                "+IxH4CnCiqpX1rM9scsNynZzbOe4KhDeYcTNS3PDaeY="
+            ),
+            "1Ct943GAT+6YQUUX47Ia/ncufilbe6+oD6lY+5kaCu4="
+        );
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut buf = Vec::new();
+        let long = vec![b'x'; 300];
+        write_frame(&mut buf, OP_TEXT, b"hi").unwrap();
+        write_frame(&mut buf, OP_TEXT, &long).unwrap();
+
+        let mut reader = io::Cursor::new(buf);
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            (true, OP_TEXT, b"hi".to_vec())
+        );
+        assert_eq!(read_frame(&mut reader).unwrap(), (true, OP_TEXT, long));
+
+        // Unmasked server frames
+        let mut reader = io::Cursor::new([0x01, 2, b'a', b'b']);
+        assert_eq!(
+            read_frame(&mut reader).unwrap(),
+            (false, OP_TEXT, b"ab".to_vec())
+        );
+    }
+
+    /// Fake obs-websocket answering one connection, returning its requests
+    fn fake_obs(listener: TcpListener) -> Vec<Value> {
+        let (tcp, _) = listener.accept().unwrap();
+        let mut stream = BufReader::new(tcp);
+        let mut key = String::new();
+        loop {
+            let mut line = String::new();
+            stream.read_line(&mut line).unwrap();
+            if line.trim().is_empty() {
+                break;
+            }
+            if let Some(value) = line.strip_prefix("Sec-WebSocket-Key:") {
+                key = value.trim().to_string();
+            }
+        }
+        write!(
+            stream.get_mut(),
+            "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+            accept_key(&key)
+        )
+        .unwrap();
+
+        let send_frame = |stream: &mut BufReader<TcpStream>, head: u8, payload: &[u8]| {
+            let mut frame = vec![head, payload.len() as u8];
+            frame.extend_from_slice(payload);
+            stream.get_mut().write_all(&frame).unwrap();
+        };
+        let send = |stream: &mut BufReader<TcpStream>, message: Value| {
+            send_frame(stream, 0x81, message.to_string().as_bytes());
+        };
+        let hello = json!({ "op": 0, "d": { "rpcVersion": 1, "authentication": { "salt": "s", "challenge": "c" } } });
+        // In two fragments, with a ping between them
+        let hello = hello.to_string().into_bytes();
+        send_frame(&mut stream, OP_TEXT, &hello[..10]);
+        send_frame(&mut stream, 0x80 | OP_PING, b"p");
+        send_frame(&mut stream, 0x80 | OP_CONTINUATION, &hello[10..]);
+        assert_eq!(
+            read_frame(&mut stream).unwrap(),
+            (true, OP_PONG, b"p".to_vec())
+        );
+
+        let (_, _, identify) = read_frame(&mut stream).unwrap();
+        let identify: Value = serde_json::from_slice(&identify).unwrap();
+        assert_eq!(identify["d"]["authentication"], auth_string("pw", "s", "c"));
+        send(
+            &mut stream,
+            json!({ "op": 2, "d": { "negotiatedRpcVersion": 1 } }),
+        );
+
+        let mut requests = Vec::new();
+        loop {
+            let (_, opcode, payload) = read_frame(&mut stream).unwrap();
+            if opcode == OP_CLOSE {
+                return requests;
+            }
+            let request: Value = serde_json::from_slice(&payload).unwrap();
+            let status = if request["d"]["requestType"] == "SendStreamCaption" {
+                json!({ "result": false, "code": OUTPUT_NOT_RUNNING })
+            } else {
+                json!({ "result": true, "code": 100 })
+            };
+            let response = json!({ "op": 7, "d": { "requestId": request["d"]["requestId"], "requestStatus": status } });
+            send(&mut stream, response);
+            requests.push(request["d"].clone());
+        }
+    }
+
+    #[test]
+    fn test_send_to_obs() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || fake_obs(listener));
+
+        let config = ObsConfig {
+            url,
+            password: Some("pw".to_string()),
+            captions: true,
+            text_source: Some("Captions".to_string()),
+        };
+        send_to_obs(&config, "Hello stream").unwrap();
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["requestData"]["captionText"], "Hello stream");
+        assert_eq!(requests[1]["requestData"]["inputName"], "Captions");
+        assert_eq!(
+            requests[1]["requestData"]["inputSettings"]["text"],
+            "Hello stream"
+        );
+    }
+}
@@ -699,6 +699,61 @@ impl PasteOutput {
         tracing::warn!("Failed to send Enter key");
         Ok(())
     }
+
+    /// Send BackSpace `count` times, trying wtype first then ydotool
+    async fn send_backspaces(&self, count: usize) -> Result<(), OutputError> {
+        if self.is_wtype_available().await {
+            let mut cmd = Command::new("wtype");
+            for _ in 0..count {
+                cmd.args(["-k", "BackSpace"]);
+            }
+            let output = cmd
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .output()
+                .await;
+
+            match output {
+                Ok(out) if out.status.success() => return Ok(()),
+                Ok(out) => tracing::debug!(
+                    "wtype BackSpace failed: {}, trying ydotool",
+                    String::from_utf8_lossy(&out.stderr)
+                ),
+                Err(e) => tracing::debug!("wtype BackSpace failed: {}, trying ydotool", e),
+            }
+        }
+
+        if self.is_ydotool_available().await {
+            // 14 is KEY_BACKSPACE
+            let mut cmd = Command::new("ydotool");
+            cmd.arg("key");
+            if self.type_delay_ms > 0 {
+                cmd.arg("-d").arg(self.type_delay_ms.to_string());
+            }
+            for _ in 0..count {
+                cmd.args(["14:1", "14:0"]);
+            }
+            let output = cmd
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .output()
+                .await
+                .map_err(|e| OutputError::InjectionFailed(e.to_string()))?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(OutputError::InjectionFailed(format!(
+                    "ydotool failed: {}",
+                    stderr
+                )));
+            }
+            return Ok(());
+        }
+
+        Err(OutputError::InjectionFailed(
+            "Neither wtype nor ydotool available to erase pasted text".to_string(),
+        ))
+    }
 }
 
 #[async_trait::async_trait]
@@ -787,6 +842,14 @@ impl TextOutput for PasteOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        // The text was pasted into the focused window, so delete it there
+        self.send_backspaces(count).await
+    }
+
     async fn is_available(&self) -> bool {
         // Check if wl-copy exists (required for clipboard)
         let wl_copy_available = Command::new("which")
@@ -0,0 +1,283 @@
+//! Terminal remote-control output (kitty, WezTerm)
+//!
+//! When the focused window is kitty or WezTerm, the text is handed to the
+//! terminal itself (`kitten @ send-text`, `wezterm cli send-text`) instead of
+//! being typed. The terminal writes it to the shell as if it were typed, with
+//! any Unicode and regardless of keyboard layout. In any other window the
+//! driver steps aside and the next one in the chain types the text.
+//!
+//! kitty only listens for remote control when kitty.conf sets
+//! `allow_remote_control` and `listen_on`. Its address is read from the
+//! `KITTY_LISTEN_ON` variable kitty gives its shells. WezTerm needs nothing:
+//! the text goes to the focused pane of the GUI client in the focused window.
+//!
+//! Requires:
+//! - kitty (`kitten`) or WezTerm (`wezterm`)
+//! - Hyprland, Sway, niri or X11 (xdotool) to find the focused window
+
+use super::active_window::{focused_app, focused_pid, parent_pid};
+use super::TextOutput;
+use crate::error::OutputError;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+fn failed(message: impl Into<String>) -> OutputError {
+    OutputError::InjectionFailed(message.into())
+}
+
+/// Where the text goes
+#[derive(Debug, Clone, PartialEq)]
+enum Terminal {
+    /// kitty listening on this address
+    Kitty(String),
+    /// WezTerm pane with this id
+    WezTerm(u64),
+}
+
+impl Terminal {
+    /// The focused terminal
+    async fn focused() -> Result<Self, OutputError> {
+        let app = focused_app().await.unwrap_or_default();
+        let kitty = match terminal_kind(&app) {
+            Some(kitty) => kitty,
+            None => return Err(OutputError::WrongWindow("not kitty or WezTerm".to_string())),
+        };
+        let pid = focused_pid()
+            .await
+            .ok_or_else(|| failed(format!("can't tell which {} window has the focus", app)))?;
+
+        if kitty {
+            kitty_address(pid).map(Terminal::Kitty).ok_or_else(|| {
+                failed(
+                    "kitty isn't listening for remote control; \
+                     set allow_remote_control and listen_on in kitty.conf",
+                )
+            })
+        } else {
+            let args = ["cli", "list-clients", "--format", "json"];
+            let clients = run("wezterm", &args, "").await?;
+            wezterm_pane(&clients, pid).map(Terminal::WezTerm)
+        }
+    }
+
+    /// Send `text` to the terminal, which writes it to the program in it
+    async fn send(&self, text: &str) -> Result<(), OutputError> {
+        match self {
+            Terminal::Kitty(address) => {
+                // With --stdin the text is sent as it is, without escape processing
+                let args = ["@", "--to", address, "send-text", "--stdin"];
+                run("kitten", &args, text).await
+            }
+            Terminal::WezTerm(pane) => {
+                let pane = pane.to_string();
+                let args = ["cli", "send-text", "--no-paste", "--pane-id", &pane];
+                run("wezterm", &args, text).await
+            }
+        }
+        .map(|_| ())
+    }
+}
+
+/// Whether an app id or window class is kitty (true) or WezTerm (false)
+fn terminal_kind(app: &str) -> Option<bool> {
+    let app = app.to_lowercase();
+    if app == "kitty" {
+        Some(true)
+    } else if app.contains("wezterm") {
+        // "org.wezfurlong.wezterm"
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Remote control address of kitty `pid`, from the environment of its shells
+fn kitty_address(pid: u32) -> Option<String> {
+    std::fs::read_dir("/proc")
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|child| parent_pid(*child) == Some(pid))
+        .find_map(|child| {
+            let environ = std::fs::read(format!("/proc/{}/environ", child)).ok()?;
+            environ_var(&environ, "KITTY_LISTEN_ON")
+        })
+}
+
+/// Value of `name` in a NUL-separated environment block
+fn environ_var(environ: &[u8], name: &str) -> Option<String> {
+    environ.split(|b| *b == 0).find_map(|entry| {
+        let entry = std::str::from_utf8(entry).ok()?;
+        let value = entry.strip_prefix(name)?.strip_prefix('=')?;
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+/// Focused pane of the WezTerm client `pid`, from `wezterm cli list-clients`
+fn wezterm_pane(clients: &str, pid: u32) -> Result<u64, OutputError> {
+    let clients: Vec<serde_json::Value> = serde_json::from_str(clients)
+        .map_err(|e| failed(format!("wezterm cli list-clients: {}", e)))?;
+    let pane = |client: &serde_json::Value| client.get("focused_pane_id")?.as_u64();
+    let client = match clients[..] {
+        // One GUI: no need to match it up with the window
+        [ref client] => Some(client),
+        _ => clients
+            .iter()
+            .find(|c| c.get("pid").and_then(|p| p.as_u64()) == Some(pid as u64)),
+    };
+    client
+        .and_then(pane)
+        .ok_or_else(|| failed("no WezTerm pane has the focus"))
+}
+
+/// Run a terminal's command with `input` on stdin, returning stdout
+async fn run(program: &str, args: &[&str], input: &str) -> Result<String, OutputError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                failed(format!("{} not found in PATH", program))
+            } else {
+                failed(format!("{}: {}", program, e))
+            }
+        })?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .await
+            .map_err(|e| failed(format!("{}: {}", program, e)))?;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| failed(format!("{}: {}", program, e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(failed(format!("{} failed: {}", program, stderr.trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Check whether `program` is in PATH
+async fn installed(program: &str) -> bool {
+    Command::new("which")
+        .arg(program)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+/// kitty / WezTerm remote-control output
+pub struct TerminalOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+    /// Terminal that got the last text, to erase it there
+    last: Mutex<Option<Terminal>>,
+}
+
+impl TerminalOutput {
+    /// Create a new terminal output
+    pub fn new(auto_submit: bool, append_text: Option<String>) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+            last: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for TerminalOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let terminal = Terminal::focused().await?;
+        let mut text = text.to_string();
+        if let Some(ref append) = self.append_text {
+            text.push_str(append);
+        }
+        if self.auto_submit {
+            text.push('\r');
+        }
+        terminal.send(&text).await?;
+        tracing::debug!("Sent text to {:?}", terminal);
+
+        *self.last.lock().unwrap() = Some(terminal);
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        let terminal = self.last.lock().unwrap().clone();
+        let terminal = terminal.ok_or_else(|| failed("no text was sent to a terminal"))?;
+        // DEL, what the BackSpace key sends
+        terminal.send(&"\x7f".repeat(count)).await
+    }
+
+    async fn is_available(&self) -> bool {
+        installed("kitten").await || installed("wezterm").await
+    }
+
+    fn name(&self) -> &'static str {
+        "terminal"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_kind() {
+        assert_eq!(terminal_kind("kitty"), Some(true));
+        assert_eq!(terminal_kind("org.wezfurlong.wezterm"), Some(false));
+        assert_eq!(terminal_kind("WezTerm"), Some(false));
+        assert_eq!(terminal_kind("foot"), None);
+        assert_eq!(terminal_kind(""), None);
+    }
+
+    #[test]
+    fn test_environ_var() {
+        let environ = b"SHELL=/bin/zsh\0KITTY_LISTEN_ON=unix:@kitty-42\0KITTY_PID=42\0";
+        assert_eq!(
+            environ_var(environ, "KITTY_LISTEN_ON").as_deref(),
+            Some("unix:@kitty-42")
+        );
+        assert_eq!(environ_var(environ, "KITTY").as_deref(), None);
+        assert_eq!(environ_var(b"KITTY_LISTEN_ON=\0", "KITTY_LISTEN_ON"), None);
+    }
+
+    #[test]
+    fn test_wezterm_pane() {
+        let clients = r#"[
+            {"pid": 100, "focused_pane_id": 3},
+            {"pid": 200, "focused_pane_id": 7}
+        ]"#;
+        assert_eq!(wezterm_pane(clients, 200).unwrap(), 7);
+        assert!(wezterm_pane(clients, 300).is_err());
+        // A single client is the one in the focused window
+        assert_eq!(
+            wezterm_pane(r#"[{"pid": 100, "focused_pane_id": 3}]"#, 300).unwrap(),
+            3
+        );
+        assert!(wezterm_pane("[]", 100).is_err());
+        assert!(wezterm_pane("not json", 100).is_err());
+    }
+}
@@ -0,0 +1,193 @@
+//! tmux output
+//!
+//! Sends text to a tmux pane with `tmux send-keys -l`, which the program in
+//! the pane reads as if it were typed. With an SSH session in the pane, the
+//! text goes to the remote side in one write instead of a key at a time
+//! through the terminal emulator.
+//!
+//! Without a `target`, the text goes to the active pane of the tmux client
+//! in the focused window (found through Hyprland, Sway, niri or X11). When
+//! no tmux client is focused, the output fails and the next driver in the
+//! chain types the text. A `target` pane ("work:1.0", "%3") gets the text
+//! wherever the focus is.
+//!
+//! Requires:
+//! - tmux (3.1 or later to undo output)
+
+use super::active_window::{descends_from, parent_pid};
+use super::TextOutput;
+use crate::error::OutputError;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::process::Command;
+
+fn failed(message: impl Into<String>) -> OutputError {
+    OutputError::InjectionFailed(message.into())
+}
+
+/// tmux send-keys output
+pub struct TmuxOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+    /// Pane to send to instead of the focused client's (`-t`)
+    target: Option<String>,
+    /// Socket of the tmux server (`-S`), None for the default server
+    socket: Option<PathBuf>,
+    /// Pane that got the last text, to erase it there
+    last_pane: Mutex<Option<String>>,
+}
+
+impl TmuxOutput {
+    /// Create a new tmux output
+    pub fn new(
+        auto_submit: bool,
+        append_text: Option<String>,
+        target: Option<String>,
+        socket: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+            target,
+            socket,
+            last_pane: Mutex::new(None),
+        }
+    }
+
+    /// Run a tmux command against the configured server, returning stdout
+    async fn run(&self, args: &[&str]) -> Result<String, OutputError> {
+        let mut cmd = Command::new("tmux");
+        if let Some(ref socket) = self.socket {
+            cmd.arg("-S").arg(socket);
+        }
+        let output = cmd
+            .args(args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    failed("tmux not found in PATH")
+                } else {
+                    failed(e.to_string())
+                }
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(failed(format!("tmux {}: {}", args[0], stderr.trim())));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// The pane to send text to
+    async fn pane(&self) -> Result<String, OutputError> {
+        if let Some(ref target) = self.target {
+            return Ok(target.clone());
+        }
+        let clients = self
+            .run(&["list-clients", "-F", "#{client_pid} #{pane_id}"])
+            .await?;
+        let focused = super::active_window::focused_pid().await.ok_or_else(|| {
+            failed("can't tell which window has the focus; set target to pick a pane")
+        })?;
+
+        let panes = client_panes(&clients, |pid| descends_from(pid, focused, parent_pid));
+        match panes[..] {
+            [pane] => Ok(pane.to_string()),
+            [] => Err(failed("no tmux client in the focused window")),
+            _ => Err(failed(
+                "several tmux clients in the focused window; set target to pick a pane",
+            )),
+        }
+    }
+}
+
+/// Active panes of the clients in `list-clients` output whose pid
+/// `in_focus` accepts
+fn client_panes(clients: &str, in_focus: impl Fn(u32) -> bool) -> Vec<&str> {
+    let mut panes: Vec<&str> = clients
+        .lines()
+        .filter_map(|line| {
+            let (pid, pane) = line.split_once(' ')?;
+            in_focus(pid.parse().ok()?).then_some(pane)
+        })
+        .collect();
+    // Clients attached to the same session share the pane
+    panes.sort_unstable();
+    panes.dedup();
+    panes
+}
+
+#[async_trait::async_trait]
+impl TextOutput for TmuxOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let pane = self.pane().await?;
+        let text = match self.append_text {
+            Some(ref append) => format!("{}{}", text, append),
+            None => text.to_string(),
+        };
+        // -l sends the text literally; -- keeps a leading "-" from being an option
+        self.run(&["send-keys", "-t", &pane, "-l", "--", &text])
+            .await?;
+        tracing::debug!("Sent text to tmux pane {}", pane);
+
+        if self.auto_submit {
+            if let Err(e) = self.run(&["send-keys", "-t", &pane, "Enter"]).await {
+                tracing::warn!("Failed to send Enter key: {}", e);
+            }
+        }
+
+        *self.last_pane.lock().unwrap() = Some(pane);
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        let pane = self.last_pane.lock().unwrap().clone();
+        let pane = pane.ok_or_else(|| failed("no text was sent to tmux"))?;
+        self.run(&["send-keys", "-t", &pane, "-N", &count.to_string(), "BSpace"])
+            .await
+            .map(|_| ())
+    }
+
+    async fn is_available(&self) -> bool {
+        // Just check if tmux exists in PATH; the server may start later
+        Command::new("which")
+            .arg("tmux")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map(|s| s.success())
+            .unwrap_or(false)
+    }
+
+    fn name(&self) -> &'static str {
+        "tmux"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_panes() {
+        let clients = "100 %1\n200 %4\n300 %1\n";
+        assert_eq!(client_panes(clients, |pid| pid == 200), ["%4"]);
+        assert_eq!(client_panes(clients, |pid| pid != 200), ["%1"]);
+        assert_eq!(client_panes(clients, |_| true), ["%1", "%4"]);
+        assert!(client_panes("", |_| true).is_empty());
+    }
+}
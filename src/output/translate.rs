@@ -0,0 +1,115 @@
+//! Translation to arbitrary target languages
+//!
+//! Whisper can only translate to English. This stage translates the final
+//! transcript to any language, using the OpenAI-compatible LLM from
+//! `[output.llm_cleanup]` or an external command (`{lang}` is substituted).
+//!
+//! # Example Configuration
+//!
+//! ```toml
+//! [output.translation]
+//! target_language = "French"
+//! ```
+
+use super::llm_cleanup::LlmCleanup;
+use super::post_process::PostProcessor;
+use crate::config::{LlmCleanupConfig, PostProcessConfig, TranslationConfig};
+
+/// Translates text to a single target language
+pub enum Translator {
+    /// Chat completion with a translation prompt
+    Llm(LlmCleanup),
+    /// External command, text on stdin and translation on stdout
+    Command(PostProcessor),
+}
+
+impl Translator {
+    /// Create a translator for `language`
+    ///
+    /// Uses `config.command` if set, otherwise the LLM connection settings.
+    pub fn new(config: &TranslationConfig, llm: &LlmCleanupConfig, language: &str) -> Self {
+        match config.command {
+            Some(ref command) => Translator::Command(PostProcessor::new(&PostProcessConfig {
+                command: command.replace("{lang}", language),
+                timeout_ms: config.timeout_ms,
+            })),
+            None => Translator::Llm(LlmCleanup::new(&LlmCleanupConfig {
+                system_prompt: translation_prompt(language),
+                timeout_ms: config.timeout_ms,
+                ..llm.clone()
+            })),
+        }
+    }
+
+    /// Translate text, returning the original on failure
+    pub async fn process(&self, text: &str) -> String {
+        match self {
+            Translator::Llm(llm) => llm.process(text).await,
+            Translator::Command(command) => command.process(text).await,
+        }
+    }
+}
+
+/// Resolve the target language, with the profile overriding the config
+///
+/// Returns None when translation is off (unset, empty or "none").
+pub fn target_language<'a>(
+    config: &'a TranslationConfig,
+    profile_language: Option<&'a str>,
+) -> Option<&'a str> {
+    profile_language
+        .or(config.target_language.as_deref())
+        .map(str::trim)
+        .filter(|lang| !lang.is_empty() && !lang.eq_ignore_ascii_case("none"))
+}
+
+/// System prompt asking the model for a plain translation
+fn translation_prompt(language: &str) -> String {
+    format!(
+        "Translate the user's dictated text to {}. Keep the tone and formatting. \
+         Output only the translation, without quotes or explanations.",
+        language
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(target: Option<&str>) -> TranslationConfig {
+        TranslationConfig {
+            target_language: target.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_target_language() {
+        assert_eq!(
+            target_language(&config(Some("French")), None),
+            Some("French")
+        );
+        assert_eq!(
+            target_language(&config(Some("French")), Some("de")),
+            Some("de")
+        );
+        assert_eq!(target_language(&config(Some("French")), Some("none")), None);
+        assert_eq!(target_language(&config(None), None), None);
+        assert_eq!(target_language(&config(Some(" ")), None), None);
+    }
+
+    #[test]
+    fn test_translation_prompt() {
+        assert!(translation_prompt("French").contains("to French."));
+    }
+
+    #[tokio::test]
+    async fn test_command_translator() {
+        let config = TranslationConfig {
+            command: Some("sed 's/$/ ({lang})/'".to_string()),
+            ..config(Some("fr"))
+        };
+        let translator = Translator::new(&config, &LlmCleanupConfig::default(), "fr");
+        assert_eq!(translator.process("hello").await, "hello (fr)");
+    }
+}
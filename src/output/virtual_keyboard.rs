@@ -0,0 +1,447 @@
+//! Native Wayland virtual-keyboard output
+//!
+//! Types text through the zwp_virtual_keyboard_v1 protocol directly, speaking
+//! the Wayland wire protocol over the compositor socket, instead of spawning
+//! wtype for every transcription. Like wtype, it uploads an XKB keymap that
+//! maps keycodes to the exact Unicode keysyms needed, so any character can be
+//! typed regardless of the user's keyboard layout.
+//!
+//! Supported by wlroots compositors (Sway, Hyprland, river, ...). On
+//! compositors without the protocol (GNOME, KDE) the connection fails quickly
+//! and the next driver in the chain (wtype, eitype, ...) is used.
+
+use super::TextOutput;
+use crate::error::OutputError;
+use crate::wayland::{memfd, Args, Connection};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::time::{Duration, Instant};
+
+const MANAGER_INTERFACE: &str = "zwp_virtual_keyboard_manager_v1";
+
+/// zwp_virtual_keyboard_v1.keymap format: XKB v1 text keymap
+const KEYMAP_FORMAT_XKB_V1: u32 = 1;
+
+/// Keysyms per uploaded keymap. Keeps keycodes below 256 for X11 clients.
+const MAX_KEYS_PER_KEYMAP: usize = 200;
+
+/// Modifier mask for Shift with the "complete" XKB types
+const SHIFT_MASK: u32 = 1;
+
+/// A single key press to send
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stroke {
+    /// XKB keysym name, e.g. "U0041" or "Return"
+    keysym: String,
+    /// Hold Shift while pressing (Shift+Enter newlines)
+    shift: bool,
+}
+
+impl Stroke {
+    fn new(keysym: &str) -> Self {
+        Self {
+            keysym: keysym.to_string(),
+            shift: false,
+        }
+    }
+}
+
+/// Convert text into key strokes
+fn text_strokes(text: &str, shift_enter_newlines: bool) -> Vec<Stroke> {
+    text.chars()
+        .filter(|&c| c != '\r')
+        .map(|c| match c {
+            '\n' => Stroke {
+                keysym: "Return".to_string(),
+                shift: shift_enter_newlines,
+            },
+            '\t' => Stroke::new("Tab"),
+            c => Stroke::new(&format!("U{:04X}", c as u32)),
+        })
+        .collect()
+}
+
+/// Split strokes so each chunk needs at most `max_keys` distinct keysyms
+fn chunk_strokes(strokes: &[Stroke], max_keys: usize) -> Vec<&[Stroke]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut keysyms: Vec<&str> = Vec::new();
+    for (i, stroke) in strokes.iter().enumerate() {
+        if !keysyms.contains(&stroke.keysym.as_str()) {
+            if keysyms.len() == max_keys {
+                chunks.push(&strokes[start..i]);
+                start = i;
+                keysyms.clear();
+            }
+            keysyms.push(&stroke.keysym);
+        }
+    }
+    if start < strokes.len() {
+        chunks.push(&strokes[start..]);
+    }
+    chunks
+}
+
+/// Build an XKB keymap where keycode 9 + i produces `keysyms[i]`
+fn build_keymap(keysyms: &[&str]) -> String {
+    let mut keymap = String::from("xkb_keymap {\nxkb_keycodes \"(unnamed)\" {\nminimum = 8;\n");
+    keymap.push_str(&format!("maximum = {};\n", keysyms.len() + 9));
+    for i in 0..keysyms.len() {
+        keymap.push_str(&format!("<K{}> = {};\n", i + 1, i + 9));
+    }
+    keymap.push_str("};\n");
+    keymap.push_str("xkb_types \"(unnamed)\" { include \"complete\" };\n");
+    keymap.push_str("xkb_compatibility \"(unnamed)\" { include \"complete\" };\n");
+    keymap.push_str("xkb_symbols \"(unnamed)\" {\n");
+    for (i, keysym) in keysyms.iter().enumerate() {
+        keymap.push_str(&format!("key <K{}> {{[ {} ]}};\n", i + 1, keysym));
+    }
+    keymap.push_str("};\n};\n");
+    keymap
+}
+
+/// A connected zwp_virtual_keyboard_v1
+struct VirtualKeyboard {
+    connection: Connection,
+    keyboard: u32,
+    start: Instant,
+}
+
+impl VirtualKeyboard {
+    /// Bind the seat and virtual keyboard manager and create a keyboard
+    fn create(mut connection: Connection) -> io::Result<Self> {
+        let (registry, globals) = connection.globals()?;
+        let find = |interface: &str| {
+            globals
+                .iter()
+                .find(|(_, i)| i == interface)
+                .map(|(name, _)| *name)
+        };
+
+        let manager_name = find(MANAGER_INTERFACE).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compositor does not support zwp_virtual_keyboard_manager_v1",
+            )
+        })?;
+        let seat_name = find("wl_seat")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no wl_seat found"))?;
+
+        let seat = connection.bind(registry, seat_name, "wl_seat", 1)?;
+        let manager = connection.bind(registry, manager_name, MANAGER_INTERFACE, 1)?;
+
+        // zwp_virtual_keyboard_manager_v1.create_virtual_keyboard(seat, id)
+        let keyboard = connection.new_id();
+        connection.send(manager, 0, Args::default().uint(seat).uint(keyboard))?;
+        connection.roundtrip(|_, _, _| {})?;
+
+        Ok(Self {
+            connection,
+            keyboard,
+            start: Instant::now(),
+        })
+    }
+
+    /// Upload a keymap containing `keysyms`
+    fn set_keymap(&mut self, keysyms: &[&str]) -> io::Result<()> {
+        let mut keymap = build_keymap(keysyms).into_bytes();
+        keymap.push(0);
+
+        let file = memfd(c"voxtype-keymap", &keymap)?;
+
+        // zwp_virtual_keyboard_v1.keymap(format, fd, size)
+        self.connection.send_with_fd(
+            self.keyboard,
+            0,
+            Args::default()
+                .uint(KEYMAP_FORMAT_XKB_V1)
+                .uint(keymap.len() as u32),
+            file.as_raw_fd(),
+        )?;
+        self.set_modifiers(0)?;
+        self.connection.roundtrip(|_, _, _| {})
+    }
+
+    fn set_modifiers(&mut self, depressed: u32) -> io::Result<()> {
+        // zwp_virtual_keyboard_v1.modifiers(depressed, latched, locked, group)
+        self.connection.send(
+            self.keyboard,
+            2,
+            Args::default().uint(depressed).uint(0).uint(0).uint(0),
+        )
+    }
+
+    fn key(&mut self, key: u32, pressed: bool) -> io::Result<()> {
+        let time = self.start.elapsed().as_millis() as u32;
+        // zwp_virtual_keyboard_v1.key(time, key, state)
+        self.connection.send(
+            self.keyboard,
+            1,
+            Args::default().uint(time).uint(key).uint(pressed as u32),
+        )
+    }
+
+    /// Type the strokes, re-uploading the keymap as needed
+    fn type_strokes(
+        &mut self,
+        strokes: &[Stroke],
+        type_delay: Duration,
+        pre_type_delay: Duration,
+    ) -> io::Result<()> {
+        for (i, chunk) in chunk_strokes(strokes, MAX_KEYS_PER_KEYMAP)
+            .into_iter()
+            .enumerate()
+        {
+            let mut keysyms: Vec<&str> = Vec::new();
+            for stroke in chunk {
+                if !keysyms.contains(&stroke.keysym.as_str()) {
+                    keysyms.push(&stroke.keysym);
+                }
+            }
+            self.set_keymap(&keysyms)?;
+            if i == 0 && !pre_type_delay.is_zero() {
+                std::thread::sleep(pre_type_delay);
+            }
+
+            for stroke in chunk {
+                // Evdev keycode = XKB keycode - 8, and XKB keycodes start at 9
+                let index = keysyms
+                    .iter()
+                    .position(|k| *k == stroke.keysym)
+                    .unwrap_or(0);
+                let key = index as u32 + 1;
+                if stroke.shift {
+                    self.set_modifiers(SHIFT_MASK)?;
+                }
+                self.key(key, true)?;
+                self.key(key, false)?;
+                if stroke.shift {
+                    self.set_modifiers(0)?;
+                }
+                if !type_delay.is_zero() {
+                    std::thread::sleep(type_delay);
+                }
+            }
+            // Make sure the keys are handled before the next keymap replaces them
+            self.connection.roundtrip(|_, _, _| {})?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for VirtualKeyboard {
+    fn drop(&mut self) {
+        // zwp_virtual_keyboard_v1.destroy
+        let _ = self.connection.send(self.keyboard, 3, Args::default());
+    }
+}
+
+/// Connect, type the strokes and disconnect (blocking)
+fn send_strokes(
+    strokes: Vec<Stroke>,
+    type_delay: Duration,
+    pre_type_delay: Duration,
+) -> io::Result<()> {
+    let mut keyboard = VirtualKeyboard::create(Connection::connect()?)?;
+    keyboard.type_strokes(&strokes, type_delay, pre_type_delay)
+}
+
+/// Native Wayland virtual-keyboard output
+pub struct VirtualKeyboardOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+    /// Delay between keystrokes in milliseconds
+    type_delay_ms: u32,
+    /// Delay before typing starts (ms)
+    pre_type_delay_ms: u32,
+    /// Convert newlines to Shift+Enter (for apps where Enter submits)
+    shift_enter_newlines: bool,
+}
+
+impl VirtualKeyboardOutput {
+    /// Create a new virtual-keyboard output
+    pub fn new(
+        auto_submit: bool,
+        append_text: Option<String>,
+        type_delay_ms: u32,
+        pre_type_delay_ms: u32,
+        shift_enter_newlines: bool,
+    ) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+            type_delay_ms,
+            pre_type_delay_ms,
+            shift_enter_newlines,
+        }
+    }
+
+    async fn send(&self, strokes: Vec<Stroke>, pre_type_delay_ms: u32) -> Result<(), OutputError> {
+        let type_delay = Duration::from_millis(self.type_delay_ms as u64);
+        let pre_type_delay = Duration::from_millis(pre_type_delay_ms as u64);
+        tokio::task::spawn_blocking(move || send_strokes(strokes, type_delay, pre_type_delay))
+            .await
+            .map_err(|e| OutputError::InjectionFailed(e.to_string()))?
+            .map_err(|e| OutputError::InjectionFailed(format!("virtual keyboard: {}", e)))
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for VirtualKeyboardOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        let mut strokes = text_strokes(text, self.shift_enter_newlines);
+        if let Some(ref append) = self.append_text {
+            strokes.extend(text_strokes(append, self.shift_enter_newlines));
+        }
+        if self.auto_submit {
+            strokes.push(Stroke::new("Return"));
+        }
+        if strokes.is_empty() {
+            return Ok(());
+        }
+        self.send(strokes, self.pre_type_delay_ms).await
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+        self.send(vec![Stroke::new("BackSpace"); count], 0).await
+    }
+
+    async fn is_available(&self) -> bool {
+        std::env::var_os("WAYLAND_DISPLAY").is_some()
+    }
+
+    fn name(&self) -> &'static str {
+        "virtual-keyboard"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wayland::{encode_message, Reader, WL_DISPLAY};
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_text_strokes() {
+        let strokes = text_strokes("aé\n\t", true);
+        assert_eq!(strokes[0], Stroke::new("U0061"));
+        assert_eq!(strokes[1], Stroke::new("U00E9"));
+        assert_eq!(strokes[2].keysym, "Return");
+        assert!(strokes[2].shift);
+        assert_eq!(strokes[3], Stroke::new("Tab"));
+        assert_eq!(text_strokes("🎉", false)[0].keysym, "U1F389");
+    }
+
+    #[test]
+    fn test_chunk_strokes() {
+        let strokes = text_strokes("abcabd", false);
+        let chunks = chunk_strokes(&strokes, 3);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].len(), 5);
+        assert_eq!(chunks[1], &strokes[5..]);
+        assert_eq!(chunk_strokes(&strokes, 10).len(), 1);
+        assert!(chunk_strokes(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_build_keymap() {
+        let keymap = build_keymap(&["U0061", "Return"]);
+        assert!(keymap.contains("maximum = 11;"));
+        assert!(keymap.contains("<K2> = 10;"));
+        assert!(keymap.contains("key <K1> {[ U0061 ]};"));
+        assert!(keymap.contains("key <K2> {[ Return ]};"));
+    }
+
+    /// Answer the client like a compositor with the given globals would,
+    /// recording the virtual keyboard key events
+    fn fake_compositor(
+        mut stream: UnixStream,
+        globals: &'static [&'static str],
+    ) -> std::thread::JoinHandle<Vec<u32>> {
+        std::thread::spawn(move || {
+            let mut keys = Vec::new();
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let mut keyboard = 0;
+            loop {
+                let n = stream.read(&mut chunk).unwrap_or(0);
+                if n == 0 {
+                    return keys;
+                }
+                buffer.extend_from_slice(&chunk[..n]);
+                while buffer.len() >= 8 {
+                    let object = u32::from_ne_bytes(buffer[0..4].try_into().unwrap());
+                    let header = u32::from_ne_bytes(buffer[4..8].try_into().unwrap());
+                    let size = (header >> 16) as usize;
+                    if buffer.len() < size {
+                        break;
+                    }
+                    let opcode = header & 0xffff;
+                    let mut args = Reader::new(&buffer[8..size]);
+                    match (object, opcode) {
+                        (WL_DISPLAY, 0) => {
+                            let callback = args.uint().unwrap();
+                            let done = encode_message(callback, 0, Args::default().uint(0));
+                            stream.write_all(&done).unwrap();
+                        }
+                        (WL_DISPLAY, 1) => {
+                            let registry = args.uint().unwrap();
+                            for (name, interface) in globals.iter().enumerate() {
+                                let global =
+                                    Args::default().uint(name as u32).string(interface).uint(1);
+                                stream
+                                    .write_all(&encode_message(registry, 0, global))
+                                    .unwrap();
+                            }
+                        }
+                        // create_virtual_keyboard on the manager (bound as id 5)
+                        (5, 0) => {
+                            args.uint();
+                            keyboard = args.uint().unwrap();
+                        }
+                        (object, 1) if object == keyboard => {
+                            let (_, key, state) = (args.uint(), args.uint(), args.uint());
+                            if state == Some(1) {
+                                keys.push(key.unwrap());
+                            }
+                        }
+                        _ => {}
+                    }
+                    buffer.drain(..size);
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_types_through_fake_compositor() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let compositor = fake_compositor(server, &["wl_seat", MANAGER_INTERFACE]);
+
+        let connection = Connection::connect_to(client).unwrap();
+        let mut keyboard = VirtualKeyboard::create(connection).unwrap();
+        keyboard
+            .type_strokes(&text_strokes("hi!h", false), Duration::ZERO, Duration::ZERO)
+            .unwrap();
+        drop(keyboard);
+
+        // h, i, ! map to keycodes 1, 2, 3; the second h reuses 1
+        assert_eq!(compositor.join().unwrap(), vec![1, 2, 3, 1]);
+    }
+
+    #[test]
+    fn test_missing_protocol_fails() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let _compositor = fake_compositor(server, &["wl_seat", "wl_compositor"]);
+
+        let connection = Connection::connect_to(client).unwrap();
+        let err = VirtualKeyboard::create(connection).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
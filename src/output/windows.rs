@@ -0,0 +1,359 @@
+//! Windows text output
+//!
+//! The `sendinput` driver types text with SendInput Unicode key events, so it
+//! doesn't depend on the keyboard layout or a helper tool. On Windows the
+//! `clipboard` driver writes the Win32 clipboard directly, and paste mode
+//! copies the text and presses Ctrl+V.
+//!
+//! Windows doesn't let a normal process send input to an elevated window
+//! (UIPI); typing into one fails silently, like on a locked screen.
+
+use super::TextOutput;
+use crate::error::OutputError;
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+use windows_sys::Win32::Foundation::GlobalFree;
+use windows_sys::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, OpenClipboard, SetClipboardData,
+};
+use windows_sys::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+    VIRTUAL_KEY, VK_BACK, VK_CONTROL, VK_RETURN, VK_SHIFT,
+};
+
+/// Clipboard format for UTF-16 text
+const CF_UNICODETEXT: u32 = 13;
+
+/// Virtual key for V (letters are their ASCII codes)
+const VK_V: VIRTUAL_KEY = b'V' as VIRTUAL_KEY;
+
+fn key_input(vk: VIRTUAL_KEY, scan: u16, flags: u32) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: scan,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+/// Send a batch of key events
+fn send(inputs: &[INPUT]) -> Result<(), OutputError> {
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    let sent = unsafe {
+        SendInput(
+            inputs.len() as u32,
+            inputs.as_ptr(),
+            std::mem::size_of::<INPUT>() as i32,
+        )
+    };
+    if sent as usize != inputs.len() {
+        return Err(OutputError::InjectionFailed(format!(
+            "SendInput: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+    Ok(())
+}
+
+/// Key events for text: UTF-16 units as Unicode keystrokes, newlines as
+/// Enter (or Shift+Enter)
+fn text_inputs(text: &str, shift_enter_newlines: bool) -> Vec<INPUT> {
+    let mut inputs = Vec::new();
+    for c in text.chars() {
+        if c == '\n' {
+            inputs.extend(chord_inputs(
+                if shift_enter_newlines {
+                    &[VK_SHIFT]
+                } else {
+                    &[]
+                },
+                VK_RETURN,
+            ));
+            continue;
+        }
+        let mut units = [0u16; 2];
+        for unit in c.encode_utf16(&mut units) {
+            inputs.push(key_input(0, *unit, KEYEVENTF_UNICODE));
+            inputs.push(key_input(0, *unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP));
+        }
+    }
+    inputs
+}
+
+/// Key events for pressing `key` with `modifiers` held
+fn chord_inputs(modifiers: &[VIRTUAL_KEY], key: VIRTUAL_KEY) -> Vec<INPUT> {
+    let mut inputs: Vec<INPUT> = modifiers.iter().map(|m| key_input(*m, 0, 0)).collect();
+    inputs.push(key_input(key, 0, 0));
+    inputs.push(key_input(key, 0, KEYEVENTF_KEYUP));
+    inputs.extend(
+        modifiers
+            .iter()
+            .rev()
+            .map(|m| key_input(*m, 0, KEYEVENTF_KEYUP)),
+    );
+    inputs
+}
+
+/// Replace the clipboard contents (empty text just clears it)
+fn set_clipboard(text: &str) -> Result<(), OutputError> {
+    let failed = |what: &str| {
+        OutputError::InjectionFailed(format!("{}: {}", what, std::io::Error::last_os_error()))
+    };
+
+    // Another application may hold the clipboard for a moment
+    let mut opened = false;
+    for _ in 0..10 {
+        if unsafe { OpenClipboard(std::ptr::null_mut()) } != 0 {
+            opened = true;
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    if !opened {
+        return Err(failed("OpenClipboard"));
+    }
+
+    let result = unsafe {
+        if EmptyClipboard() == 0 {
+            Err(failed("EmptyClipboard"))
+        } else if text.is_empty() {
+            Ok(())
+        } else {
+            let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+            let mem = GlobalAlloc(GMEM_MOVEABLE, wide.len() * 2);
+            let ptr = if mem.is_null() {
+                std::ptr::null_mut()
+            } else {
+                GlobalLock(mem) as *mut u16
+            };
+            if ptr.is_null() {
+                if !mem.is_null() {
+                    GlobalFree(mem);
+                }
+                Err(failed("GlobalAlloc"))
+            } else {
+                std::ptr::copy_nonoverlapping(wide.as_ptr(), ptr, wide.len());
+                GlobalUnlock(mem);
+                // On success the clipboard owns the memory
+                if SetClipboardData(CF_UNICODETEXT, mem).is_null() {
+                    GlobalFree(mem);
+                    Err(failed("SetClipboardData"))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    };
+    unsafe { CloseClipboard() };
+    result
+}
+
+/// Run a blocking input function off the async runtime
+async fn blocking<F>(f: F) -> Result<(), OutputError>
+where
+    F: FnOnce() -> Result<(), OutputError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| OutputError::InjectionFailed(e.to_string()))?
+}
+
+/// Typing through SendInput
+pub struct SendInputOutput {
+    /// Whether to send Enter key after output
+    auto_submit: bool,
+    /// Text to append after transcription (before auto_submit)
+    append_text: Option<String>,
+    /// Delay between keystrokes in milliseconds
+    type_delay_ms: u32,
+    /// Delay before typing starts (ms)
+    pre_type_delay_ms: u32,
+    /// Convert newlines to Shift+Enter (for apps where Enter submits)
+    shift_enter_newlines: bool,
+}
+
+impl SendInputOutput {
+    /// Create a new SendInput output
+    pub fn new(
+        auto_submit: bool,
+        append_text: Option<String>,
+        type_delay_ms: u32,
+        pre_type_delay_ms: u32,
+        shift_enter_newlines: bool,
+    ) -> Self {
+        Self {
+            auto_submit,
+            append_text,
+            type_delay_ms,
+            pre_type_delay_ms,
+            shift_enter_newlines,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for SendInputOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let mut text = text.to_string();
+        if let Some(ref append) = self.append_text {
+            text.push_str(append);
+        }
+
+        if self.pre_type_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(self.pre_type_delay_ms.into())).await;
+        }
+
+        let delay = Duration::from_millis(self.type_delay_ms.into());
+        let shift_enter_newlines = self.shift_enter_newlines;
+        let auto_submit = self.auto_submit;
+        blocking(move || {
+            if delay.is_zero() {
+                send(&text_inputs(&text, shift_enter_newlines))?;
+            } else {
+                for (i, grapheme) in text.graphemes(true).enumerate() {
+                    if i > 0 {
+                        std::thread::sleep(delay);
+                    }
+                    send(&text_inputs(grapheme, shift_enter_newlines))?;
+                }
+            }
+            if auto_submit {
+                send(&chord_inputs(&[], VK_RETURN))?;
+            }
+            Ok(())
+        })
+        .await?;
+
+        tracing::info!("Text typed via SendInput");
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        blocking(move || {
+            let inputs: Vec<INPUT> = (0..count)
+                .flat_map(|_| chord_inputs(&[], VK_BACK))
+                .collect();
+            send(&inputs)
+        })
+        .await
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "sendinput"
+    }
+}
+
+/// Output through the Win32 clipboard, optionally pasting with Ctrl+V
+pub struct ClipboardOutput {
+    /// Text to append after transcription
+    append_text: Option<String>,
+    /// Press Ctrl+V after copying (paste mode)
+    paste: bool,
+    /// Whether to send Enter key after pasting
+    auto_submit: bool,
+    /// Delay before pasting (ms)
+    pre_type_delay_ms: u32,
+}
+
+impl ClipboardOutput {
+    /// Copy to the clipboard only
+    pub fn clipboard(append_text: Option<String>) -> Self {
+        Self {
+            append_text,
+            paste: false,
+            auto_submit: false,
+            pre_type_delay_ms: 0,
+        }
+    }
+
+    /// Copy to the clipboard and press Ctrl+V
+    pub fn paste(auto_submit: bool, append_text: Option<String>, pre_type_delay_ms: u32) -> Self {
+        Self {
+            append_text,
+            paste: true,
+            auto_submit,
+            pre_type_delay_ms,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TextOutput for ClipboardOutput {
+    async fn output(&self, text: &str) -> Result<(), OutputError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let text = match self.append_text {
+            Some(ref append) => format!("{}{}", text, append),
+            None => text.to_string(),
+        };
+        let len = text.len();
+        blocking(move || set_clipboard(&text)).await?;
+
+        if !self.paste {
+            tracing::info!("Text copied to clipboard ({} chars)", len);
+            return Ok(());
+        }
+
+        if self.pre_type_delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(self.pre_type_delay_ms.into())).await;
+        }
+        let auto_submit = self.auto_submit;
+        blocking(move || {
+            send(&chord_inputs(&[VK_CONTROL], VK_V))?;
+            if auto_submit {
+                send(&chord_inputs(&[], VK_RETURN))?;
+            }
+            Ok(())
+        })
+        .await?;
+
+        tracing::info!("Text pasted via Ctrl+V ({} chars)", len);
+        Ok(())
+    }
+
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if self.paste {
+            return blocking(move || {
+                let inputs: Vec<INPUT> = (0..count)
+                    .flat_map(|_| chord_inputs(&[], VK_BACK))
+                    .collect();
+                send(&inputs)
+            })
+            .await;
+        }
+        blocking(|| set_clipboard("")).await?;
+        tracing::info!("Clipboard cleared");
+        Ok(())
+    }
+
+    async fn is_available(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        if self.paste {
+            "paste (Win32)"
+        } else {
+            "clipboard (Win32)"
+        }
+    }
+}
@@ -182,6 +182,44 @@ impl TextOutput for WtypeOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        let mut cmd = Command::new("wtype");
+        if self.type_delay_ms > 0 {
+            cmd.arg("-d").arg(self.type_delay_ms.to_string());
+        }
+        for _ in 0..count {
+            cmd.args(["-k", "BackSpace"]);
+        }
+        tracing::debug!("Running: wtype -k BackSpace (x{})", count);
+
+        let output = cmd
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    OutputError::WtypeNotFound
+                } else {
+                    OutputError::InjectionFailed(e.to_string())
+                }
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(OutputError::InjectionFailed(format!(
+                "wtype failed: {}",
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         // Just check if wtype exists in PATH
         // Don't check WAYLAND_DISPLAY - systemd services may not have it
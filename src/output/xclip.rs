@@ -113,6 +113,32 @@ impl TextOutput for XclipOutput {
         Ok(())
     }
 
+    async fn erase(&self, _count: usize) -> Result<(), OutputError> {
+        // Replace the clipboard selection with empty content
+        let status = Command::new("xclip")
+            .args(["-selection", "clipboard", "-i", "/dev/null"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    OutputError::XclipNotFound
+                } else {
+                    OutputError::InjectionFailed(e.to_string())
+                }
+            })?;
+
+        if !status.success() {
+            return Err(OutputError::InjectionFailed(
+                "xclip exited with error".to_string(),
+            ));
+        }
+
+        tracing::info!("X11 clipboard cleared");
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         // Check if xclip is installed and DISPLAY is set (X11 environment)
         let xclip_installed = Command::new("which")
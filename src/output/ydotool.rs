@@ -10,6 +10,7 @@
 
 use super::TextOutput;
 use crate::error::OutputError;
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::time::Duration;
 use tokio::process::Command;
@@ -28,6 +29,8 @@ pub struct YdotoolOutput {
     auto_submit: bool,
     /// Text to append after transcription (before auto_submit)
     append_text: Option<String>,
+    /// ydotoold socket path (YDOTOOL_SOCKET), None for ydotool's default
+    socket: Option<PathBuf>,
 }
 
 impl YdotoolOutput {
@@ -54,9 +57,25 @@ impl YdotoolOutput {
             supports_key_hold,
             auto_submit,
             append_text,
+            socket: None,
         }
     }
 
+    /// Talk to the ydotoold listening on `socket` instead of the default one
+    pub fn with_socket(mut self, socket: Option<PathBuf>) -> Self {
+        self.socket = socket;
+        self
+    }
+
+    /// A `ydotool` command pointed at the configured socket
+    fn command(&self) -> Command {
+        let mut cmd = Command::new("ydotool");
+        if let Some(ref socket) = self.socket {
+            cmd.env("YDOTOOL_SOCKET", socket);
+        }
+        cmd
+    }
+
     /// Detect if ydotool supports the --key-hold flag
     ///
     /// Older versions of ydotool don't have this flag and silently ignore it
@@ -113,7 +132,7 @@ impl TextOutput for YdotoolOutput {
             tokio::time::sleep(Duration::from_millis(self.pre_type_delay_ms as u64)).await;
         }
 
-        let mut cmd = Command::new("ydotool");
+        let mut cmd = self.command();
         cmd.arg("type");
 
         // Always set delay explicitly (ydotool defaults to 12ms if not specified)
@@ -165,7 +184,7 @@ impl TextOutput for YdotoolOutput {
 
         // Append text if configured (e.g., a space to separate sentences)
         if let Some(ref append) = self.append_text {
-            let mut append_cmd = Command::new("ydotool");
+            let mut append_cmd = self.command();
             append_cmd.arg("type");
             append_cmd
                 .arg("--key-delay")
@@ -196,7 +215,8 @@ impl TextOutput for YdotoolOutput {
         // ydotool key uses evdev key codes: 28 is KEY_ENTER
         // Format: keycode:press (1) then keycode:release (0)
         if self.auto_submit {
-            let enter_output = Command::new("ydotool")
+            let enter_output = self
+                .command()
                 .args(["key", "28:1", "28:0"])
                 .stdout(Stdio::null())
                 .stderr(Stdio::piped())
@@ -220,6 +240,46 @@ impl TextOutput for YdotoolOutput {
         Ok(())
     }
 
+    async fn erase(&self, count: usize) -> Result<(), OutputError> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        // 14 is KEY_BACKSPACE
+        let mut cmd = self.command();
+        cmd.arg("key")
+            .arg("--key-delay")
+            .arg(self.type_delay_ms.to_string());
+        for _ in 0..count {
+            cmd.args(["14:1", "14:0"]);
+        }
+        tracing::debug!("Running: ydotool key 14:1 14:0 (x{})", count);
+
+        let output = cmd
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    OutputError::YdotoolNotFound
+                } else {
+                    OutputError::InjectionFailed(e.to_string())
+                }
+            })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("socket") || stderr.contains("connect") || stderr.contains("daemon")
+            {
+                return Err(OutputError::YdotoolNotRunning);
+            }
+            return Err(OutputError::InjectionFailed(stderr.to_string()));
+        }
+
+        Ok(())
+    }
+
     async fn is_available(&self) -> bool {
         // Check if ydotool exists in PATH
         let which_result = Command::new("which")
@@ -235,7 +295,7 @@ impl TextOutput for YdotoolOutput {
 
         // Check if ydotoold is running by trying a no-op
         // ydotool type "" should succeed quickly if daemon is running
-        Command::new("ydotool")
+        self.command()
             .args(["type", ""])
             .stdout(Stdio::null())
             .stderr(Stdio::null())
@@ -280,6 +340,18 @@ mod tests {
         assert_eq!(output.pre_type_delay_ms, 200);
     }
 
+    #[test]
+    fn test_with_socket() {
+        let output = YdotoolOutput::new(0, 0, false, false, None);
+        assert!(output.socket.is_none());
+
+        let output = output.with_socket(Some(PathBuf::from("/run/user/1000/.ydotool_socket")));
+        assert_eq!(
+            output.socket,
+            Some(PathBuf::from("/run/user/1000/.ydotool_socket"))
+        );
+    }
+
     #[test]
     fn test_detect_key_hold_support() {
         // This test will pass regardless of ydotool version - it just shouldn't panic
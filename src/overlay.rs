@@ -0,0 +1,990 @@
+//! On-screen caption overlay
+//!
+//! A small click-through HUD on the zwlr_layer_shell_v1 overlay layer that
+//! shows "Recording" / "Transcribing" while dictating and the last
+//! transcript for a few seconds afterwards, as a lighter alternative to
+//! desktop notifications.
+//!
+//! Like the virtual-keyboard output it speaks the Wayland wire protocol
+//! directly. Text is drawn into a wl_shm buffer: glyph outlines come from the
+//! font file (found with fc-match) and are rasterized here. The overlay runs
+//! on its own thread and connects to the compositor only while something is
+//! on screen.
+//!
+//! Supported by wlroots compositors (Sway, Hyprland, river, ...) and KDE.
+
+use crate::config::{OverlayConfig, OverlayPosition};
+use crate::wayland::{memfd, Args, Connection, Reader};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+
+const LAYER_SHELL_INTERFACE: &str = "zwlr_layer_shell_v1";
+
+/// zwlr_layer_shell_v1.layer: above fullscreen windows
+const LAYER_OVERLAY: u32 = 3;
+
+/// zwlr_layer_surface_v1.anchor bits
+const ANCHOR_TOP: u32 = 1;
+const ANCHOR_BOTTOM: u32 = 2;
+const ANCHOR_LEFT: u32 = 4;
+const ANCHOR_RIGHT: u32 = 8;
+
+/// wl_shm.format: 32-bit premultiplied ARGB, little endian
+const FORMAT_ARGB8888: u32 = 0;
+
+/// Longer transcripts are cut to their last lines
+const MAX_LINES: usize = 3;
+
+/// Vertical samples per pixel row when rasterizing
+const SUBSAMPLES: usize = 4;
+
+/// Messages from the daemon to the overlay thread
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Event {
+    /// Daemon state ("recording", "transcribing", "idle")
+    State(String),
+    /// A transcript was delivered
+    Caption(String),
+}
+
+/// Handle to the overlay thread
+pub struct Overlay {
+    events: Sender<Event>,
+}
+
+impl Overlay {
+    /// Start the overlay thread
+    pub fn spawn(config: &OverlayConfig) -> Self {
+        let (events, receiver) = mpsc::channel();
+        let config = config.clone();
+        std::thread::spawn(move || run(config, receiver));
+        Self { events }
+    }
+
+    /// Show the daemon state
+    pub fn set_state(&self, state: &str) {
+        let _ = self.events.send(Event::State(state.to_string()));
+    }
+
+    /// Show a transcript for `duration_ms`
+    pub fn show_caption(&self, text: &str) {
+        let _ = self.events.send(Event::Caption(text.to_string()));
+    }
+}
+
+/// What the overlay shows, given the events so far
+struct Captions {
+    show_state: bool,
+    duration: Duration,
+    /// Label for the current state and when it was entered
+    state: Option<(&'static str, Instant)>,
+    /// Last transcript and when it arrived
+    caption: Option<(String, Instant)>,
+}
+
+impl Captions {
+    fn new(config: &OverlayConfig) -> Self {
+        Self {
+            show_state: config.show_state,
+            duration: Duration::from_millis(config.duration_ms),
+            state: None,
+            caption: None,
+        }
+    }
+
+    fn apply(&mut self, event: Event, now: Instant) {
+        match event {
+            Event::State(state) => {
+                let label = match state.as_str() {
+                    "recording" => Some("● Recording"),
+                    "transcribing" => Some("Transcribing…"),
+                    _ => None,
+                };
+                self.state = label.filter(|_| self.show_state).map(|label| (label, now));
+            }
+            Event::Caption(text) => {
+                let text = text.trim();
+                if !text.is_empty() {
+                    self.caption = Some((text.to_string(), now));
+                }
+            }
+        }
+    }
+
+    /// The caption while it is fresh and newer than the state
+    fn current_caption(&self, now: Instant) -> Option<(&str, Instant)> {
+        let (text, since) = self.caption.as_ref()?;
+        let until = *since + self.duration;
+        let newer = self
+            .state
+            .is_none_or(|(_, state_since)| *since >= state_since);
+        (now < until && newer).then_some((text.as_str(), until))
+    }
+
+    /// Text to show now, if any
+    fn text(&self, now: Instant) -> Option<&str> {
+        match self.current_caption(now) {
+            Some((text, _)) => Some(text),
+            None => self.state.map(|(label, _)| label),
+        }
+    }
+
+    /// When the text changes without a new event
+    fn deadline(&self, now: Instant) -> Option<Instant> {
+        self.current_caption(now).map(|(_, until)| until)
+    }
+}
+
+/// Overlay thread: follow the events and keep the surface up to date
+fn run(config: OverlayConfig, events: Receiver<Event>) {
+    let font = match Font::load(&config.font) {
+        Ok(font) => font,
+        Err(e) => {
+            tracing::warn!("Overlay disabled: can't load font {:?}: {}", config.font, e);
+            return;
+        }
+    };
+    let style = Style::new(&config);
+    let mut captions = Captions::new(&config);
+    let mut surface: Option<Surface> = None;
+    let mut shown: Option<String> = None;
+    let mut warned = false;
+
+    loop {
+        let event = match captions.deadline(Instant::now()) {
+            Some(deadline) => {
+                events.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => events.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match event {
+            Ok(event) => captions.apply(event, Instant::now()),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let text = captions.text(Instant::now()).map(str::to_string);
+        if text == shown {
+            continue;
+        }
+        shown = text;
+
+        let Some(ref text) = shown else {
+            // Disconnecting removes the surface
+            surface = None;
+            continue;
+        };
+        let image = render(&font, &style, text);
+        let result = match surface.as_mut() {
+            Some(surface) => surface.draw(&image),
+            None => Connection::connect()
+                .and_then(|connection| {
+                    Surface::create(connection, config.position, config.margin, "voxtype")
+                })
+                .and_then(|mut created| created.draw(&image).map(|()| created))
+                .map(|created| surface = Some(created)),
+        };
+        if let Err(e) = result {
+            surface = None;
+            if warned {
+                tracing::debug!("Overlay: {}", e);
+            } else {
+                tracing::warn!("Overlay: {}", e);
+                warned = true;
+            }
+        }
+    }
+}
+
+/// Parse "#rrggbb" or "#rrggbbaa" into [r, g, b, a]
+pub(crate) fn parse_color(color: &str) -> Option<[u8; 4]> {
+    let hex = color.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+    Some([channel(0)?, channel(2)?, channel(4)?, alpha])
+}
+
+/// Colors and sizes from the config
+struct Style {
+    font_size: f32,
+    text: [f32; 4],
+    background: [f32; 4],
+    padding: u32,
+    max_width: u32,
+}
+
+impl Style {
+    fn new(config: &OverlayConfig) -> Self {
+        let color = |value: &str, fallback: [u8; 4]| {
+            let [r, g, b, a] = parse_color(value).unwrap_or_else(|| {
+                tracing::warn!("Overlay: invalid color {:?}, using the default", value);
+                fallback
+            });
+            [r, g, b, a].map(|c| c as f32 / 255.0)
+        };
+        Self {
+            font_size: config.font_size.max(1.0),
+            text: color(&config.text_color, [255, 255, 255, 255]),
+            background: color(&config.background_color, [0, 0, 0, 179]),
+            padding: config.padding,
+            max_width: config.max_width,
+        }
+    }
+}
+
+/// A TrueType/OpenType font file
+struct Font {
+    data: Vec<u8>,
+}
+
+impl Font {
+    /// Load a font file, or the best match for a fontconfig pattern
+    fn load(font: &str) -> io::Result<Self> {
+        let path = if Path::new(font).is_file() {
+            font.into()
+        } else {
+            let output = Command::new("fc-match")
+                .args(["--format=%{file}", font])
+                .output()?;
+            if !output.status.success() || output.stdout.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "fc-match found no font",
+                ));
+            }
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        };
+        let data = std::fs::read(&path)?;
+        ttf_parser::Face::parse(&data, 0)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path, e)))?;
+        Ok(Self { data })
+    }
+
+    fn face(&self) -> ttf_parser::Face<'_> {
+        // Checked in load()
+        ttf_parser::Face::parse(&self.data, 0).expect("font parsed before")
+    }
+}
+
+/// Break text into lines no wider than `max_width`
+///
+/// Lines break between words; a word wider than a line is split.
+fn wrap(text: &str, max_width: f32, width_of: impl Fn(&str) -> f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if width_of(&candidate) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for c in word.chars() {
+                line.push(c);
+                if line.chars().count() > 1 && width_of(&line) > max_width {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// A line segment of a glyph outline, in pixels
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+}
+
+/// Collects glyph outlines as edges, flattening curves
+struct Outline {
+    edges: Vec<Edge>,
+    scale: f32,
+    /// Pen position and baseline in pixels
+    origin: (f32, f32),
+    start: (f32, f32),
+    last: (f32, f32),
+}
+
+impl Outline {
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        // Font units point up, pixels down
+        (
+            self.origin.0 + x * self.scale,
+            self.origin.1 - y * self.scale,
+        )
+    }
+
+    fn line(&mut self, to: (f32, f32)) {
+        let (x0, y0) = self.last;
+        self.edges.push(Edge {
+            x0,
+            y0,
+            x1: to.0,
+            y1: to.1,
+        });
+        self.last = to;
+    }
+
+    /// Flatten a curve into line segments of about two pixels
+    fn curve(&mut self, to: (f32, f32), point_at: impl Fn(f32) -> (f32, f32)) {
+        let (x0, y0) = self.last;
+        let length = (to.0 - x0).hypot(to.1 - y0);
+        let steps = (length / 2.0).ceil().clamp(2.0, 16.0) as usize;
+        for i in 1..steps {
+            self.line(point_at(i as f32 / steps as f32));
+        }
+        self.line(to);
+    }
+}
+
+impl ttf_parser::OutlineBuilder for Outline {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.start = self.point(x, y);
+        self.last = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.line(self.point(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (p0, p1, p2) = (self.last, self.point(x1, y1), self.point(x, y));
+        self.curve(p2, |t| {
+            let u = 1.0 - t;
+            (
+                u * u * p0.0 + 2.0 * u * t * p1.0 + t * t * p2.0,
+                u * u * p0.1 + 2.0 * u * t * p1.1 + t * t * p2.1,
+            )
+        });
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (p0, p1, p2, p3) = (
+            self.last,
+            self.point(x1, y1),
+            self.point(x2, y2),
+            self.point(x, y),
+        );
+        self.curve(p3, |t| {
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            (
+                a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+            )
+        });
+    }
+
+    fn close(&mut self) {
+        if self.last != self.start {
+            self.line(self.start);
+        }
+    }
+}
+
+/// Anti-aliased coverage (0.0..=1.0) of the shape, non-zero winding rule
+fn rasterize(edges: &[Edge], width: usize, height: usize) -> Vec<f32> {
+    let mut coverage = vec![0.0; width * height];
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    let weight = 1.0 / SUBSAMPLES as f32;
+
+    for row in 0..height {
+        let line = &mut coverage[row * width..(row + 1) * width];
+        for sub in 0..SUBSAMPLES {
+            let y = row as f32 + (sub as f32 + 0.5) * weight;
+            crossings.clear();
+            for edge in edges {
+                let (top, bottom) = (edge.y0.min(edge.y1), edge.y0.max(edge.y1));
+                if y < top || y >= bottom {
+                    continue;
+                }
+                let x = edge.x0 + (y - edge.y0) / (edge.y1 - edge.y0) * (edge.x1 - edge.x0);
+                crossings.push((x, if edge.y1 > edge.y0 { 1 } else { -1 }));
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                if winding != 0 {
+                    fill_span(line, pair[0].0, pair[1].0, weight);
+                }
+            }
+        }
+    }
+
+    for value in &mut coverage {
+        *value = value.min(1.0);
+    }
+    coverage
+}
+
+/// Add `weight` times the horizontal coverage of x0..x1 to each pixel
+fn fill_span(line: &mut [f32], x0: f32, x1: f32, weight: f32) {
+    let end = line.len() as f32;
+    let (x0, x1) = (x0.clamp(0.0, end), x1.clamp(0.0, end));
+    if x1 <= x0 {
+        return;
+    }
+    let start = x0.floor() as usize;
+    for (i, value) in line[start..x1.ceil() as usize].iter_mut().enumerate() {
+        let px = (start + i) as f32;
+        *value += (x1.min(px + 1.0) - x0.max(px)) * weight;
+    }
+}
+
+/// An image in wl_shm ARGB8888 (premultiplied, little endian)
+pub(crate) struct Image {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) pixels: Vec<u8>,
+}
+
+/// Draw the text on its background
+fn render(font: &Font, style: &Style, text: &str) -> Image {
+    let face = font.face();
+    let scale = style.font_size / face.units_per_em() as f32;
+    let advance = |c: char| {
+        let glyph = face.glyph_index(c).unwrap_or_default();
+        face.glyph_hor_advance(glyph).unwrap_or(0) as f32 * scale
+    };
+    let width_of = |line: &str| line.chars().map(advance).sum::<f32>();
+
+    let padding = style.padding as f32;
+    let max_text_width = (style.max_width as f32 - 2.0 * padding).max(style.font_size);
+    let mut lines = wrap(text, max_text_width, width_of);
+    if lines.len() > MAX_LINES {
+        lines.drain(..lines.len() - MAX_LINES);
+        // Mark the cut, dropping characters so the line still fits
+        let mut first: String = lines[0].clone();
+        while !first.is_empty() && width_of(&format!("…{}", first)) > max_text_width {
+            first.remove(0);
+        }
+        lines[0] = format!("…{}", first.trim_start());
+    }
+
+    let ascent = face.ascender() as f32 * scale;
+    let descent = face.descender() as f32 * scale;
+    let line_height = ascent - descent + face.line_gap() as f32 * scale;
+    let text_width = lines.iter().map(|line| width_of(line)).fold(0.0, f32::max);
+    let width = (text_width + 2.0 * padding).ceil().max(1.0) as usize;
+    let height = (lines.len() as f32 * line_height - (line_height - (ascent - descent))
+        + 2.0 * padding)
+        .ceil()
+        .max(1.0) as usize;
+
+    let mut outline = Outline {
+        edges: Vec::new(),
+        scale,
+        origin: (0.0, 0.0),
+        start: (0.0, 0.0),
+        last: (0.0, 0.0),
+    };
+    for (i, line) in lines.iter().enumerate() {
+        let baseline = padding + ascent + i as f32 * line_height;
+        // Center each line like subtitles
+        let mut pen = padding + (text_width - width_of(line)) / 2.0;
+        for c in line.chars() {
+            outline.origin = (pen, baseline);
+            face.outline_glyph(face.glyph_index(c).unwrap_or_default(), &mut outline);
+            pen += advance(c);
+        }
+    }
+    let coverage = rasterize(&outline.edges, width, height);
+
+    let premultiply = |[r, g, b, a]: [f32; 4]| [b * a, g * a, r * a, a];
+    let (text_color, background) = (premultiply(style.text), premultiply(style.background));
+    let mut pixels = Vec::with_capacity(width * height * 4);
+    for value in coverage {
+        for channel in 0..4 {
+            let blended =
+                text_color[channel] * value + background[channel] * (1.0 - text_color[3] * value);
+            pixels.push((blended * 255.0).round() as u8);
+        }
+    }
+
+    Image {
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    }
+}
+
+/// Anchor bits for the configured position
+fn anchor(position: OverlayPosition) -> u32 {
+    match position {
+        OverlayPosition::Top => ANCHOR_TOP,
+        OverlayPosition::Bottom => ANCHOR_BOTTOM,
+        OverlayPosition::TopLeft => ANCHOR_TOP | ANCHOR_LEFT,
+        OverlayPosition::TopRight => ANCHOR_TOP | ANCHOR_RIGHT,
+        OverlayPosition::BottomLeft => ANCHOR_BOTTOM | ANCHOR_LEFT,
+        OverlayPosition::BottomRight => ANCHOR_BOTTOM | ANCHOR_RIGHT,
+    }
+}
+
+/// A mapped zwlr_layer_surface_v1
+pub(crate) struct Surface {
+    connection: Connection,
+    shm: u32,
+    surface: u32,
+    layer_surface: u32,
+    /// Buffer currently attached, destroyed once replaced
+    buffer: Option<u32>,
+}
+
+impl Surface {
+    /// Create a click-through layer surface and wait for its first configure
+    ///
+    /// `namespace` lets users write compositor rules for the surface.
+    pub(crate) fn create(
+        mut connection: Connection,
+        position: OverlayPosition,
+        margin: u32,
+        namespace: &str,
+    ) -> io::Result<Self> {
+        let (registry, globals) = connection.globals()?;
+        let find = |interface: &str| {
+            globals
+                .iter()
+                .find(|(_, i)| i == interface)
+                .map(|(name, _)| *name)
+        };
+        let layer_shell_name = find(LAYER_SHELL_INTERFACE).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compositor does not support zwlr_layer_shell_v1",
+            )
+        })?;
+        let (Some(compositor_name), Some(shm_name)) = (find("wl_compositor"), find("wl_shm"))
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "no wl_compositor or wl_shm found",
+            ));
+        };
+
+        let compositor = connection.bind(registry, compositor_name, "wl_compositor", 1)?;
+        let shm = connection.bind(registry, shm_name, "wl_shm", 1)?;
+        let layer_shell = connection.bind(registry, layer_shell_name, LAYER_SHELL_INTERFACE, 1)?;
+
+        // wl_compositor.create_surface(id)
+        let surface = connection.new_id();
+        connection.send(compositor, 0, Args::default().uint(surface))?;
+
+        // An empty input region lets clicks through to the windows below:
+        // wl_compositor.create_region(id), wl_surface.set_input_region(region),
+        // wl_region.destroy
+        let region = connection.new_id();
+        connection.send(compositor, 1, Args::default().uint(region))?;
+        connection.send(surface, 5, Args::default().uint(region))?;
+        connection.send(region, 0, Args::default())?;
+
+        // zwlr_layer_shell_v1.get_layer_surface(id, surface, output, layer, namespace)
+        let layer_surface = connection.new_id();
+        connection.send(
+            layer_shell,
+            0,
+            Args::default()
+                .uint(layer_surface)
+                .uint(surface)
+                .uint(0)
+                .uint(LAYER_OVERLAY)
+                .string(namespace),
+        )?;
+        // zwlr_layer_surface_v1.set_anchor(anchor)
+        connection.send(layer_surface, 1, Args::default().uint(anchor(position)))?;
+        // zwlr_layer_surface_v1.set_margin(top, right, bottom, left)
+        let margin = margin as i32;
+        connection.send(
+            layer_surface,
+            3,
+            Args::default()
+                .int(margin)
+                .int(margin)
+                .int(margin)
+                .int(margin),
+        )?;
+        // Placeholder size until the first draw; wl_surface.commit
+        connection.send(layer_surface, 0, Args::default().uint(1).uint(1))?;
+        connection.send(surface, 6, Args::default())?;
+
+        let mut created = Self {
+            connection,
+            shm,
+            surface,
+            layer_surface,
+            buffer: None,
+        };
+        if !created.sync()? {
+            return Err(io::Error::other("compositor did not configure the overlay"));
+        }
+        Ok(created)
+    }
+
+    /// Handle pending events, acknowledging a configure
+    ///
+    /// Returns whether a configure was acknowledged.
+    fn sync(&mut self) -> io::Result<bool> {
+        let layer_surface = self.layer_surface;
+        let mut serial = None;
+        let mut closed = false;
+        self.connection.roundtrip(|object, opcode, payload| {
+            if object != layer_surface {
+                return;
+            }
+            match opcode {
+                // zwlr_layer_surface_v1.configure(serial, width, height)
+                0 => serial = Reader::new(payload).uint(),
+                // zwlr_layer_surface_v1.closed
+                1 => closed = true,
+                _ => {}
+            }
+        })?;
+        if closed {
+            return Err(io::Error::other("compositor closed the overlay"));
+        }
+        if let Some(serial) = serial {
+            // zwlr_layer_surface_v1.ack_configure(serial)
+            self.connection
+                .send(layer_surface, 6, Args::default().uint(serial))?;
+        }
+        Ok(serial.is_some())
+    }
+
+    /// Show the image, resizing the surface to fit
+    pub(crate) fn draw(&mut self, image: &Image) -> io::Result<()> {
+        let (width, height) = (image.width, image.height);
+        let file = memfd(c"voxtype-overlay", &image.pixels)?;
+
+        // wl_shm.create_pool(id, fd, size)
+        let pool = self.connection.new_id();
+        self.connection.send_with_fd(
+            self.shm,
+            0,
+            Args::default().uint(pool).uint(image.pixels.len() as u32),
+            file.as_raw_fd(),
+        )?;
+        // wl_shm_pool.create_buffer(id, offset, width, height, stride, format)
+        let buffer = self.connection.new_id();
+        self.connection.send(
+            pool,
+            0,
+            Args::default()
+                .uint(buffer)
+                .int(0)
+                .uint(width)
+                .uint(height)
+                .uint(width * 4)
+                .uint(FORMAT_ARGB8888),
+        )?;
+        // wl_shm_pool.destroy; the buffer keeps the memory alive
+        self.connection.send(pool, 1, Args::default())?;
+
+        // zwlr_layer_surface_v1.set_size(width, height)
+        self.connection.send(
+            self.layer_surface,
+            0,
+            Args::default().uint(width).uint(height),
+        )?;
+        // wl_surface.attach(buffer, x, y), damage(x, y, width, height), commit
+        self.connection
+            .send(self.surface, 1, Args::default().uint(buffer).int(0).int(0))?;
+        self.connection.send(
+            self.surface,
+            2,
+            Args::default().int(0).int(0).uint(width).uint(height),
+        )?;
+        self.connection.send(self.surface, 6, Args::default())?;
+
+        if let Some(old) = self.buffer.replace(buffer) {
+            // wl_buffer.destroy
+            self.connection.send(old, 0, Args::default())?;
+        }
+
+        // The new size usually comes with a configure that needs a commit
+        if self.sync()? {
+            self.connection.send(self.surface, 6, Args::default())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wayland::{encode_message, WL_DISPLAY};
+    use std::io::{Read, Write};
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff8000"), Some([255, 128, 0, 255]));
+        assert_eq!(parse_color("#000000b3"), Some([0, 0, 0, 179]));
+        assert_eq!(parse_color("ff8000"), None);
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("#gg0000"), None);
+    }
+
+    #[test]
+    fn test_captions() {
+        let mut captions = Captions::new(&OverlayConfig::default());
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        assert_eq!(captions.text(start), None);
+        captions.apply(Event::State("recording".into()), at(0));
+        assert_eq!(captions.text(at(0)), Some("● Recording"));
+        assert_eq!(captions.deadline(at(0)), None);
+        captions.apply(Event::State("transcribing".into()), at(100));
+        captions.apply(Event::Caption(" hello world ".into()), at(200));
+        captions.apply(Event::State("idle".into()), at(210));
+
+        // The transcript stays up for duration_ms
+        assert_eq!(captions.text(at(300)), Some("hello world"));
+        assert_eq!(captions.deadline(at(300)), Some(at(3200)));
+        assert_eq!(captions.text(at(3200)), None);
+
+        // A new recording replaces it
+        captions.apply(Event::Caption("again".into()), at(4000));
+        captions.apply(Event::State("recording".into()), at(4100));
+        assert_eq!(captions.text(at(4200)), Some("● Recording"));
+
+        let mut captions = Captions::new(&OverlayConfig {
+            show_state: false,
+            ..Default::default()
+        });
+        captions.apply(Event::State("recording".into()), at(0));
+        assert_eq!(captions.text(at(0)), None);
+    }
+
+    #[test]
+    fn test_wrap() {
+        // One unit per character
+        let width_of = |s: &str| s.chars().count() as f32;
+        assert_eq!(
+            wrap("the quick brown fox", 10.0, width_of),
+            ["the quick", "brown fox"]
+        );
+        assert_eq!(
+            wrap("abcdefghij kl", 4.0, width_of),
+            ["abcd", "efgh", "ij", "kl"]
+        );
+        assert_eq!(wrap("one\ntwo", 10.0, width_of), ["one", "two"]);
+        assert!(wrap("  ", 10.0, width_of).is_empty());
+    }
+
+    fn square(x0: f32, y0: f32, x1: f32, y1: f32) -> Vec<Edge> {
+        let corners = [(x0, y0), (x1, y0), (x1, y1), (x0, y1), (x0, y0)];
+        corners
+            .windows(2)
+            .map(|pair| Edge {
+                x0: pair[0].0,
+                y0: pair[0].1,
+                x1: pair[1].0,
+                y1: pair[1].1,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let coverage = rasterize(&square(1.0, 1.0, 3.0, 3.0), 4, 4);
+        for (i, value) in coverage.iter().enumerate() {
+            let inside = (1..3).contains(&(i % 4)) && (1..3).contains(&(i / 4));
+            assert_eq!(*value, if inside { 1.0 } else { 0.0 }, "pixel {}", i);
+        }
+
+        // Half a pixel each way
+        let coverage = rasterize(&square(0.5, 0.5, 1.5, 1.5), 2, 2);
+        assert_eq!(coverage, [0.25; 4]);
+
+        // Overlapping shapes don't add up; an opposite winding cuts a hole
+        let mut edges = square(0.0, 0.0, 3.0, 3.0);
+        edges.extend(square(0.0, 0.0, 3.0, 3.0));
+        let coverage = rasterize(&edges, 3, 3);
+        assert!(coverage.iter().all(|v| *v == 1.0));
+        let mut edges = square(0.0, 0.0, 3.0, 3.0);
+        edges.extend(square(2.0, 1.0, 1.0, 2.0));
+        let coverage = rasterize(&edges, 3, 3);
+        assert_eq!(coverage[4], 0.0);
+        assert_eq!(coverage[0], 1.0);
+    }
+
+    #[test]
+    fn test_render() {
+        // Needs a system font
+        let Ok(font) = Font::load("sans-serif") else {
+            return;
+        };
+        let style = Style::new(&OverlayConfig::default());
+        let image = render(&font, &style, "Hello");
+        assert!(image.width > 2 * style.padding);
+        assert!(image.height > 2 * style.padding);
+        assert_eq!(
+            image.pixels.len(),
+            (image.width * image.height * 4) as usize
+        );
+        // The corner is background (premultiplied black, alpha 0xb3), the
+        // text is white
+        assert_eq!(&image.pixels[..4], &[0, 0, 0, 179]);
+        assert!(image.pixels.chunks(4).any(|p| p == [255, 255, 255, 255]));
+
+        // Long transcripts keep their last lines
+        let long = "word ".repeat(200);
+        let image = render(&font, &style, &long);
+        assert!(image.width <= 800);
+        assert!(image.height < 5 * style.font_size as u32 + 2 * style.padding);
+    }
+
+    /// Acknowledged configure serials and (width, height) of created buffers
+    type Recorded = (Vec<u32>, Vec<(u32, u32)>);
+
+    /// Answer the client like a compositor with layer-shell would
+    fn fake_compositor(mut stream: UnixStream) -> std::thread::JoinHandle<Recorded> {
+        std::thread::spawn(move || {
+            let globals = ["wl_compositor", "wl_shm", LAYER_SHELL_INTERFACE];
+            let (mut acks, mut buffers) = (Vec::new(), Vec::new());
+            let mut buffer = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let mut bound: Vec<(u32, String)> = Vec::new();
+            let mut layer_surface = 0;
+            let mut serial = 0;
+            loop {
+                let n = stream.read(&mut chunk).unwrap_or(0);
+                if n == 0 {
+                    return (acks, buffers);
+                }
+                buffer.extend_from_slice(&chunk[..n]);
+                while buffer.len() >= 8 {
+                    let object = u32::from_ne_bytes(buffer[0..4].try_into().unwrap());
+                    let header = u32::from_ne_bytes(buffer[4..8].try_into().unwrap());
+                    let size = (header >> 16) as usize;
+                    if buffer.len() < size {
+                        break;
+                    }
+                    let opcode = header & 0xffff;
+                    let mut args = Reader::new(&buffer[8..size]);
+                    let interface = bound
+                        .iter()
+                        .find(|(id, _)| *id == object)
+                        .map(|(_, i)| i.as_str());
+                    match (object, opcode, interface) {
+                        (WL_DISPLAY, 0, _) => {
+                            let callback = args.uint().unwrap();
+                            let done = encode_message(callback, 0, Args::default().uint(0));
+                            stream.write_all(&done).unwrap();
+                        }
+                        (WL_DISPLAY, 1, _) => {
+                            let registry = args.uint().unwrap();
+                            bound.push((registry, "wl_registry".into()));
+                            for (name, interface) in globals.iter().enumerate() {
+                                let global =
+                                    Args::default().uint(name as u32).string(interface).uint(1);
+                                stream
+                                    .write_all(&encode_message(registry, 0, global))
+                                    .unwrap();
+                            }
+                        }
+                        (_, 0, Some("wl_registry")) => {
+                            let (_, interface) = (args.uint(), args.string().unwrap());
+                            let (_, id) = (args.uint(), args.uint().unwrap());
+                            bound.push((id, interface));
+                        }
+                        (_, 0, Some("wl_compositor")) => {
+                            bound.push((args.uint().unwrap(), "wl_surface".into()));
+                        }
+                        (_, 0, Some(LAYER_SHELL_INTERFACE)) => {
+                            layer_surface = args.uint().unwrap();
+                        }
+                        (_, 0, Some("wl_shm")) => {
+                            bound.push((args.uint().unwrap(), "wl_shm_pool".into()));
+                        }
+                        (_, 0, Some("wl_shm_pool")) => {
+                            let (_, _) = (args.uint(), args.uint());
+                            buffers.push((args.uint().unwrap(), args.uint().unwrap()));
+                        }
+                        // wl_surface.commit: configure every time. The client
+                        // may already be gone after its last commit.
+                        (_, 6, Some("wl_surface")) => {
+                            serial += 1;
+                            let configure = Args::default().uint(serial).uint(0).uint(0);
+                            let _ = stream.write_all(&encode_message(layer_surface, 0, configure));
+                        }
+                        (object, 6, _) if object == layer_surface => {
+                            acks.push(args.uint().unwrap());
+                        }
+                        _ => {}
+                    }
+                    buffer.drain(..size);
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_draws_through_fake_compositor() {
+        let (client, server) = UnixStream::pair().unwrap();
+        let compositor = fake_compositor(server);
+
+        let connection = Connection::connect_to(client).unwrap();
+        let mut surface =
+            Surface::create(connection, OverlayPosition::Bottom, 48, "voxtype").unwrap();
+        let image = |width, height| Image {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        };
+        surface.draw(&image(40, 20)).unwrap();
+        surface.draw(&image(60, 20)).unwrap();
+        drop(surface);
+
+        let (acks, buffers) = compositor.join().unwrap();
+        assert_eq!(buffers, vec![(40, 20), (60, 20)]);
+        // The initial configure and one per draw
+        assert_eq!(acks, vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_missing_layer_shell_fails() {
+        let (client, mut server) = UnixStream::pair().unwrap();
+        std::thread::spawn(move || {
+            let mut chunk = [0u8; 64];
+            // get_registry, then sync
+            let _ = server.read(&mut chunk);
+            let global = Args::default().uint(0).string("wl_compositor").uint(1);
+            server.write_all(&encode_message(2, 0, global)).unwrap();
+            server
+                .write_all(&encode_message(3, 0, Args::default().uint(0)))
+                .unwrap();
+            let _ = server.read(&mut chunk);
+        });
+
+        let connection = Connection::connect_to(client).unwrap();
+        let err = Surface::create(connection, OverlayPosition::Bottom, 48, "voxtype")
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,208 @@
+//! Per-utterance performance report
+//!
+//! The daemon times each dictation from the end of recording to the end of
+//! output: inference, text processing (including post-process commands and
+//! LLM cleanup) and output. The last report is written to
+//! `$XDG_RUNTIME_DIR/voxtype/last_report.json` and shown by
+//! `voxtype status --report`, so a slow dictation can be explained after
+//! the fact.
+
+use crate::config::Config;
+use crate::transcribe::Transcriber;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Sample rate of captured audio
+const SAMPLE_RATE: f64 = 16000.0;
+
+/// Timing breakdown of one dictation
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PerfReport {
+    /// How long the hotkey was held / recording ran
+    pub capture_secs: f64,
+    /// Number of audio samples transcribed (16 kHz mono)
+    pub samples: usize,
+    /// Model load time after recording stopped (subprocess mode, loads
+    /// hidden behind recording aren't counted)
+    pub model_load_ms: Option<u64>,
+    /// Time from end of recording to transcribed text, including model load
+    pub inference_ms: u64,
+    /// Text processing, post-process command, LLM cleanup and translation
+    pub processing_ms: u64,
+    /// Delivering the text (typing, clipboard, file)
+    pub output_ms: u64,
+    /// Output method that delivered the text
+    pub output_method: Option<String>,
+}
+
+impl PerfReport {
+    /// Duration of the transcribed audio in seconds
+    pub fn audio_secs(&self) -> f64 {
+        self.samples as f64 / SAMPLE_RATE
+    }
+
+    /// Inference time divided by audio duration (below 1.0 is faster than real time)
+    pub fn real_time_factor(&self) -> f64 {
+        let audio_secs = self.audio_secs();
+        if audio_secs > 0.0 {
+            self.inference_ms as f64 / 1000.0 / audio_secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Time from end of recording until the text was delivered
+    pub fn total_ms(&self) -> u64 {
+        self.inference_ms + self.processing_ms + self.output_ms
+    }
+
+    /// One-line human-readable summary
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{:.1}s captured, {:.1}s audio ({} samples) | inference {} (RTF {:.2})",
+            self.capture_secs,
+            self.audio_secs(),
+            self.samples,
+            format_ms(self.inference_ms),
+            self.real_time_factor()
+        );
+        if let Some(load_ms) = self.model_load_ms {
+            summary.push_str(&format!(", model load {}", format_ms(load_ms)));
+        }
+        summary.push_str(&format!(
+            " | processing {} | output {}",
+            format_ms(self.processing_ms),
+            format_ms(self.output_ms)
+        ));
+        if let Some(ref method) = self.output_method {
+            summary.push_str(&format!(" via {}", method));
+        }
+        summary.push_str(&format!(" | total {}", format_ms(self.total_ms())));
+        summary
+    }
+
+    /// Path of the last report file
+    pub fn path() -> PathBuf {
+        Config::runtime_dir().join("last_report.json")
+    }
+
+    /// Save as the last report
+    pub fn write(&self) -> std::io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    /// Load the last report, if any
+    pub fn read() -> Option<Self> {
+        let content = std::fs::read_to_string(Self::path()).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
+
+/// Times the phases of the dictation in progress
+pub struct PerfTimer {
+    report: PerfReport,
+    phase_start: Instant,
+    transcriber: Option<Arc<dyn Transcriber>>,
+}
+
+impl PerfTimer {
+    /// Start timing when recording stops and transcription begins
+    pub fn start(capture: Duration, samples: usize, transcriber: Arc<dyn Transcriber>) -> Self {
+        Self {
+            report: PerfReport {
+                capture_secs: capture.as_secs_f64(),
+                samples,
+                ..Default::default()
+            },
+            phase_start: Instant::now(),
+            transcriber: Some(transcriber),
+        }
+    }
+
+    /// Milliseconds since the previous phase ended
+    fn lap(&mut self) -> u64 {
+        let elapsed = self.phase_start.elapsed().as_millis() as u64;
+        self.phase_start = Instant::now();
+        elapsed
+    }
+
+    /// Transcription finished
+    pub fn transcribed(&mut self) {
+        self.report.inference_ms = self.lap();
+        if let Some(transcriber) = self.transcriber.take() {
+            self.report.model_load_ms = transcriber
+                .last_model_load_time()
+                .map(|d| d.as_millis() as u64);
+        }
+    }
+
+    /// Text processing finished, output is about to start
+    pub fn processed(&mut self) {
+        self.report.processing_ms = self.lap();
+    }
+
+    /// Output finished via `method`
+    pub fn output(mut self, method: Option<&str>) -> PerfReport {
+        self.report.output_ms = self.lap();
+        self.report.output_method = method.map(String::from);
+        self.report
+    }
+}
+
+/// Format milliseconds as "850ms" or "1.24s"
+fn format_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.2}s", ms as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> PerfReport {
+        PerfReport {
+            capture_secs: 3.2,
+            samples: 48000,
+            model_load_ms: Some(1200),
+            inference_ms: 1500,
+            processing_ms: 40,
+            output_ms: 120,
+            output_method: Some("wtype".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_real_time_factor() {
+        let report = report();
+        assert_eq!(report.audio_secs(), 3.0);
+        assert!((report.real_time_factor() - 0.5).abs() < 1e-9);
+        assert_eq!(report.total_ms(), 1660);
+        assert_eq!(PerfReport::default().real_time_factor(), 0.0);
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            report().summary(),
+            "3.2s captured, 3.0s audio (48000 samples) | inference 1.50s (RTF 0.50), \
+             model load 1.20s | processing 40ms | output 120ms via wtype | total 1.66s"
+        );
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let json = serde_json::to_string(&report()).unwrap();
+        let parsed: PerfReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report());
+    }
+}
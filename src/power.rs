@@ -0,0 +1,122 @@
+//! AC / battery detection
+//!
+//! Reads the kernel's power supply class (`/sys/class/power_supply`), the
+//! same source upower uses, so no service needs to be running. A laptop is
+//! on battery when it has a system battery and no mains adapter is online.
+//! Peripheral batteries (mice, headsets) report `scope = Device` and are
+//! ignored. Machines without a system battery have no power source to
+//! follow, and `[power]` does nothing there.
+
+use std::fs;
+use std::path::Path;
+
+/// Where the kernel lists power supplies
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// What the machine runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+impl std::fmt::Display for PowerSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PowerSource::Ac => write!(f, "AC power"),
+            PowerSource::Battery => write!(f, "battery"),
+        }
+    }
+}
+
+/// The current power source, or None without a system battery
+pub fn power_source() -> Option<PowerSource> {
+    power_source_in(Path::new(POWER_SUPPLY_DIR))
+}
+
+/// The power source according to a power supply directory
+fn power_source_in(dir: &Path) -> Option<PowerSource> {
+    let read = |supply: &Path, attribute: &str| {
+        fs::read_to_string(supply.join(attribute))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut has_adapter = false;
+    let mut discharging = false;
+    let mut has_battery = false;
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let supply = entry.path();
+        match read(&supply, "type").as_str() {
+            "Battery" => {
+                if read(&supply, "scope") == "Device" {
+                    continue;
+                }
+                has_battery = true;
+                discharging |= read(&supply, "status") == "Discharging";
+            }
+            "Mains" | "USB" => {
+                has_adapter = true;
+                if read(&supply, "online") == "1" {
+                    return Some(PowerSource::Ac);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if !has_battery {
+        None
+    } else if has_adapter || discharging {
+        // No adapter online, or none listed and the battery drains
+        Some(PowerSource::Battery)
+    } else {
+        Some(PowerSource::Ac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn supply(dir: &TempDir, name: &str, attributes: &[(&str, &str)]) {
+        let path = dir.path().join(name);
+        fs::create_dir(&path).unwrap();
+        for (attribute, value) in attributes {
+            fs::write(path.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_power_source() {
+        let dir = TempDir::new().unwrap();
+        // A desktop with a wireless mouse has nothing to follow
+        supply(
+            &dir,
+            "hidpp_battery_0",
+            &[("type", "Battery"), ("scope", "Device")],
+        );
+        assert_eq!(power_source_in(dir.path()), None);
+
+        supply(&dir, "BAT0", &[("type", "Battery"), ("status", "Charging")]);
+        supply(&dir, "AC", &[("type", "Mains"), ("online", "1")]);
+        assert_eq!(power_source_in(dir.path()), Some(PowerSource::Ac));
+
+        fs::write(dir.path().join("AC/online"), "0\n").unwrap();
+        assert_eq!(power_source_in(dir.path()), Some(PowerSource::Battery));
+    }
+
+    #[test]
+    fn test_power_source_without_adapter() {
+        // Some laptops list no adapter; the battery status tells instead
+        let dir = TempDir::new().unwrap();
+        supply(&dir, "BAT1", &[("type", "Battery"), ("status", "Full")]);
+        assert_eq!(power_source_in(dir.path()), Some(PowerSource::Ac));
+
+        fs::write(dir.path().join("BAT1/status"), "Discharging\n").unwrap();
+        assert_eq!(power_source_in(dir.path()), Some(PowerSource::Battery));
+
+        assert_eq!(power_source_in(&dir.path().join("missing")), None);
+    }
+}
@@ -0,0 +1,247 @@
+//! Scheduling priority of transcription (`[inference]`)
+//!
+//! On Linux the nice level, I/O priority and CPU affinity belong to a thread,
+//! and a new thread inherits them from the thread that starts it. So rather
+//! than lowering the whole daemon, which would also slow down audio capture,
+//! the hotkey and typing, each transcription runs on a thread of its own
+//! with the settings applied ([`run`]). The threads whisper.cpp starts for
+//! the work inherit them, as do the whisper-cli and faster-whisper processes
+//! started from there. ONNX Runtime starts its threads when a model is
+//! loaded, so the engines are created under [`run`] as well.
+//!
+//! A nice level can't be lowered again without privileges, which is another
+//! reason not to change a thread that is used for anything else.
+//!
+//! The gpu_isolation worker is a process of its own and applies the settings
+//! to itself. Other platforms don't have per-thread priorities; the settings
+//! are ignored there.
+
+use crate::config::InferenceConfig;
+#[cfg(target_os = "linux")]
+use crate::config::IoPriority;
+use crate::error::TranscribeError;
+use crate::transcribe::{Progress, Segment, Transcriber};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// Lowest priority a nice level can ask for
+const MAX_NICE: i32 = 19;
+
+/// The configured settings, set once by `init`
+static SETTINGS: OnceLock<InferenceConfig> = OnceLock::new();
+
+/// Use these settings for transcriptions from now on
+pub fn init(config: &InferenceConfig) {
+    if SETTINGS.set(config.clone()).is_err() {
+        tracing::warn!("Inference priority already initialized");
+    }
+}
+
+/// The settings from `init`, or none at all
+fn settings() -> &'static InferenceConfig {
+    static NONE: OnceLock<InferenceConfig> = OnceLock::new();
+    SETTINGS
+        .get()
+        .unwrap_or_else(|| NONE.get_or_init(InferenceConfig::default))
+}
+
+/// Apply the settings to the calling thread and the threads it starts
+#[cfg(target_os = "linux")]
+pub fn apply_to_current_thread(config: &InferenceConfig) {
+    // SAFETY: these syscalls only change scheduling attributes of the
+    // calling thread and read nothing but the cpu_set_t passed to them
+    unsafe {
+        let tid = libc::gettid();
+
+        if config.nice > 0 {
+            let nice = config.nice.min(MAX_NICE);
+            if libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) != 0 {
+                tracing::warn!(
+                    "Failed to set nice level {}: {}",
+                    nice,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+
+        // ioprio_set(2): the class in the top bits, the level in the low ones
+        const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+        const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+        let ioprio = match config.io_priority {
+            IoPriority::Normal => None,
+            // Best effort, lowest of its 8 levels
+            IoPriority::Low => Some((2 << IOPRIO_CLASS_SHIFT) | 7),
+            IoPriority::Idle => Some(3 << IOPRIO_CLASS_SHIFT),
+        };
+        if let Some(ioprio) = ioprio {
+            if libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, ioprio) != 0 {
+                tracing::warn!(
+                    "Failed to set I/O priority {:?}: {}",
+                    config.io_priority,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+
+        if !config.cpus.is_empty() {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            for &cpu in &config.cpus {
+                if cpu < libc::CPU_SETSIZE as usize {
+                    libc::CPU_SET(cpu, &mut set);
+                }
+            }
+            if libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                tracing::warn!(
+                    "Failed to restrict transcription to CPUs {:?}: {}",
+                    config.cpus,
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+    }
+}
+
+/// Apply the settings to the calling thread and the threads it starts
+#[cfg(not(target_os = "linux"))]
+pub fn apply_to_current_thread(config: &InferenceConfig) {
+    if config.is_enabled() {
+        tracing::debug!("[inference] settings are only supported on Linux");
+    }
+}
+
+/// Run `f` on a new thread with the settings applied, and wait for it
+///
+/// Runs `f` on the calling thread when nothing is configured.
+pub fn run<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    run_with(settings(), f)
+}
+
+fn run_with<R: Send>(config: &InferenceConfig, f: impl FnOnce() -> R + Send) -> R {
+    if !config.is_enabled() {
+        return f();
+    }
+    std::thread::scope(|scope| {
+        let thread = scope.spawn(|| {
+            apply_to_current_thread(config);
+            f()
+        });
+        thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// `transcriber` running its transcriptions under the settings, or as it is
+/// when nothing is configured
+pub fn wrap(transcriber: Arc<dyn Transcriber>) -> Arc<dyn Transcriber> {
+    let config = settings();
+    if !config.is_enabled() {
+        return transcriber;
+    }
+    Arc::new(PriorityTranscriber {
+        inner: transcriber,
+        config,
+    })
+}
+
+/// A transcriber whose transcriptions [`run`] under the settings
+struct PriorityTranscriber {
+    inner: Arc<dyn Transcriber>,
+    config: &'static InferenceConfig,
+}
+
+impl Transcriber for PriorityTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        run_with(self.config, || self.inner.transcribe(samples))
+    }
+
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        run_with(self.config, || {
+            self.inner.transcribe_with_progress(samples, progress)
+        })
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        run_with(self.config, || {
+            self.inner
+                .transcribe_with_context(samples, progress, context)
+        })
+    }
+
+    fn transcribe_with_grammar(
+        &self,
+        samples: &[f32],
+        grammar: &Path,
+    ) -> Result<String, TranscribeError> {
+        run_with(self.config, || {
+            self.inner.transcribe_with_grammar(samples, grammar)
+        })
+    }
+
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        run_with(self.config, || self.inner.transcribe_segments(samples))
+    }
+
+    fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    fn max_concurrent(&self) -> usize {
+        self.inner.max_concurrent()
+    }
+
+    fn prepare(&self) {
+        self.inner.prepare();
+    }
+
+    fn feed(&self, samples: &[f32]) {
+        self.inner.feed(samples);
+    }
+
+    fn last_model_load_time(&self) -> Option<std::time::Duration> {
+        self.inner.last_model_load_time()
+    }
+
+    fn unload_if_idle(&self) {
+        self.inner.unload_if_idle();
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    /// Nice level of the calling thread
+    fn current_nice() -> i32 {
+        // SAFETY: reads the scheduling priority of the calling thread
+        unsafe { libc::getpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t) }
+    }
+
+    #[test]
+    fn test_run() {
+        let before = current_nice();
+        let config = InferenceConfig {
+            nice: (before + 5).min(MAX_NICE),
+            ..Default::default()
+        };
+        assert_eq!(run_with(&config, current_nice), config.nice);
+        // The calling thread keeps its priority
+        assert_eq!(current_nice(), before);
+
+        // Nothing configured: runs right here
+        let here = std::thread::current().id();
+        assert_eq!(
+            run_with(&InferenceConfig::default(), || std::thread::current().id()),
+            here
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Flatpak sandbox support
+//!
+//! Inside a Flatpak, evdev, notify-send and most of `$XDG_RUNTIME_DIR` are
+//! out of reach. Voxtype detects the sandbox and switches to the XDG desktop
+//! portals instead: the GlobalShortcuts portal for the hotkey (see
+//! `hotkey::portal_listener`) and the Notification portal for notifications.
+//! Runtime files move to `$XDG_RUNTIME_DIR/app/<app-id>`, the part of the
+//! runtime directory shared between the daemon and `voxtype record`.
+//!
+//! Config, models and logs need no special handling: Flatpak points
+//! `XDG_CONFIG_HOME`, `XDG_DATA_HOME` and `XDG_STATE_HOME` at
+//! `~/.var/app/<app-id>/`, which the XDG lookups already follow.
+
+use std::process::Stdio;
+use std::sync::OnceLock;
+use tokio::process::Command;
+
+/// File Flatpak places at the root of every sandbox
+const FLATPAK_INFO: &str = "/.flatpak-info";
+
+/// Notification ID, so a new notification replaces the previous one
+const NOTIFICATION_ID: &str = "voxtype";
+
+/// Flatpak application ID, if running inside a Flatpak
+pub fn flatpak_app_id() -> Option<&'static str> {
+    static APP_ID: OnceLock<Option<String>> = OnceLock::new();
+    APP_ID
+        .get_or_init(|| {
+            std::env::var("FLATPAK_ID")
+                .ok()
+                .filter(|id| !id.is_empty())
+                .or_else(|| {
+                    std::fs::read_to_string(FLATPAK_INFO)
+                        .ok()
+                        .and_then(|info| parse_app_id(&info))
+                })
+        })
+        .as_deref()
+}
+
+/// Whether voxtype runs inside a Flatpak sandbox
+pub fn is_flatpak() -> bool {
+    flatpak_app_id().is_some()
+}
+
+/// Read the application name from the `[Application]` group of .flatpak-info
+fn parse_app_id(info: &str) -> Option<String> {
+    let mut in_application = false;
+    for line in info.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_application = line == "[Application]";
+        } else if in_application {
+            if let Some(name) = line.strip_prefix("name=") {
+                return Some(name.trim().to_string()).filter(|name| !name.is_empty());
+            }
+        }
+    }
+    None
+}
+
+/// Show a notification through the Notification portal
+///
+/// `priority` is one of "low", "normal", "high" or "urgent".
+pub async fn send_notification(title: &str, body: &str, priority: &str) {
+    let notification = format!(
+        "{{'title': <{}>, 'body': <{}>, 'priority': <{}>}}",
+        gvariant_string(title),
+        gvariant_string(body),
+        gvariant_string(priority)
+    );
+    let result = Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest=org.freedesktop.portal.Desktop",
+            "--object-path=/org/freedesktop/portal/desktop",
+            "--method=org.freedesktop.portal.Notification.AddNotification",
+            NOTIFICATION_ID,
+            &notification,
+        ])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+    match result {
+        Ok(status) if status.success() => {}
+        Ok(status) => tracing::debug!("Notification portal call failed: {}", status),
+        Err(e) => tracing::debug!("Failed to run gdbus: {}", e),
+    }
+}
+
+/// Quote a string in GVariant text format
+fn gvariant_string(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('\'', "\\'")
+        .replace('\n', "\\n");
+    format!("'{}'", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_app_id() {
+        let info = "[Application]\nname=io.voxtype.Voxtype\nruntime=runtime/org.gnome.Platform/x86_64/47\n\n[Instance]\nname=other\n";
+        assert_eq!(parse_app_id(info).as_deref(), Some("io.voxtype.Voxtype"));
+        assert_eq!(parse_app_id("[Instance]\nname=other\n"), None);
+        assert_eq!(parse_app_id(""), None);
+    }
+
+    #[test]
+    fn test_gvariant_string() {
+        assert_eq!(gvariant_string("plain"), "'plain'");
+        assert_eq!(gvariant_string("it's"), "'it\\'s'");
+        assert_eq!(gvariant_string("a\\b\nc"), "'a\\\\b\\nc'");
+    }
+}
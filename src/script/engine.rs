@@ -0,0 +1,205 @@
+//! Rhai engine running the hooks
+
+use super::{Outcome, TranscriptContext};
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Most operations one hook call may take
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// A compiled script and the hooks it defines
+pub struct Script {
+    path: PathBuf,
+    engine: Engine,
+    ast: AST,
+    on_transcript: bool,
+    on_state_change: bool,
+}
+
+impl Script {
+    /// Read and compile the script at `path`
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read script {:?}: {}", path, e))?;
+        Self::compile(path, &source)
+    }
+
+    fn compile(path: &Path, source: &str) -> Result<Self, String> {
+        let engine = new_engine();
+        let ast = engine
+            .compile(source)
+            .map_err(|e| format!("Error in script {:?}: {}", path, e))?;
+        let defines = |name: &str, params: usize| {
+            ast.iter_functions()
+                .any(|f| f.name == name && f.params.len() == params)
+        };
+        let on_transcript = defines("on_transcript", 2);
+        let on_state_change = defines("on_state_change", 1);
+        if !on_transcript && !on_state_change {
+            return Err(format!(
+                "Script {:?} defines neither on_transcript(text, ctx) nor on_state_change(state)",
+                path
+            ));
+        }
+        tracing::info!("Loaded script {:?}", path);
+        Ok(Self {
+            path: path.to_path_buf(),
+            engine,
+            ast,
+            on_transcript,
+            on_state_change,
+        })
+    }
+
+    /// Call a function of the script, logging failures
+    fn call(&self, name: &str, args: impl FuncArgs) -> Option<Dynamic> {
+        // The script's top-level statements ran when it was loaded
+        let options = CallFnOptions::new().eval_ast(false);
+        match self
+            .engine
+            .call_fn_with_options(options, &mut Scope::new(), &self.ast, name, args)
+        {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::warn!("Script {:?}: {} failed: {}", self.path, name, e);
+                None
+            }
+        }
+    }
+
+    pub fn on_transcript(&self, text: &str, context: &TranscriptContext) -> Outcome {
+        if !self.on_transcript {
+            return Outcome::keep(text);
+        }
+        match self.call("on_transcript", (text.to_string(), context_map(context))) {
+            Some(result) => outcome(result, text),
+            None => Outcome::keep(text),
+        }
+    }
+
+    pub fn on_state_change(&self, state: &str) {
+        if self.on_state_change {
+            self.call("on_state_change", (state.to_string(),));
+        }
+    }
+}
+
+/// An engine limited to `MAX_OPERATIONS`, with `spawn` and `print` to the log
+fn new_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.on_print(|text| tracing::info!("Script: {}", text));
+    engine.on_debug(|text, _source, position| tracing::debug!("Script ({}): {}", position, text));
+    engine.register_fn("spawn", spawn);
+    engine
+}
+
+/// Run a shell command in the background
+fn spawn(command: &str) {
+    match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .spawn()
+    {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => tracing::warn!("Script: failed to run {:?}: {}", command, e),
+    }
+}
+
+/// `ctx` of `on_transcript`; missing values are `()`
+fn context_map(context: &TranscriptContext) -> Map {
+    let string = |value: &str| Dynamic::from(value.to_string());
+    let optional = |value: Option<&str>| value.map_or(Dynamic::UNIT, string);
+    let mut map = Map::new();
+    map.insert("raw".into(), string(context.raw));
+    map.insert("app".into(), optional(context.app));
+    map.insert("profile".into(), optional(context.profile));
+    map.insert("engine".into(), string(context.engine));
+    map.insert("model".into(), string(context.model));
+    map
+}
+
+/// The outcome of an `on_transcript` call that returned `result`
+fn outcome(result: Dynamic, text: &str) -> Outcome {
+    if result.is_unit() {
+        return Outcome::keep(text);
+    }
+    if result.is_string() {
+        return Outcome::keep(&result.into_string().unwrap_or_default());
+    }
+    let type_name = result.type_name();
+    let Some(map) = result.try_cast::<Map>() else {
+        tracing::warn!(
+            "on_transcript returned {}, expected a string or a map",
+            type_name
+        );
+        return Outcome::keep(text);
+    };
+    let string = |key: &str| map.get(key).and_then(|v| v.clone().into_string().ok());
+    Outcome {
+        text: string("text").unwrap_or_else(|| text.to_string()),
+        output: string("output"),
+        auto_submit: map.get("auto_submit").and_then(|v| v.as_bool().ok()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(source: &str) -> Result<Script, String> {
+        Script::compile(Path::new("hooks.rhai"), source)
+    }
+
+    #[test]
+    fn test_on_transcript() {
+        let context = TranscriptContext {
+            raw: "hello world",
+            app: Some("kitty"),
+            ..Default::default()
+        };
+        let hooks = script(
+            r#"
+            fn on_transcript(text, ctx) {
+                if ctx.app == "kitty" && text.starts_with("run ") {
+                    return #{ text: text.sub_string(4), auto_submit: true };
+                }
+                if text == "secret" { return ""; }
+                if text == "broken" { return 1 / 0; }
+                if text == "shout" { return ctx.raw.to_upper(); }
+            }
+            "#,
+        )
+        .unwrap();
+
+        let run = hooks.on_transcript("run ls", &context);
+        assert_eq!(run.text, "ls");
+        assert_eq!(run.auto_submit, Some(true));
+        assert_eq!(hooks.on_transcript("shout", &context).text, "HELLO WORLD");
+        assert_eq!(hooks.on_transcript("secret", &context).text, "");
+        // Returning nothing, or failing, keeps the text
+        assert_eq!(
+            hooks.on_transcript("as is", &context),
+            Outcome::keep("as is")
+        );
+        assert_eq!(
+            hooks.on_transcript("broken", &context),
+            Outcome::keep("broken")
+        );
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let err = script("fn on_transcript(text, ctx) { text +")
+            .err()
+            .unwrap();
+        assert!(err.contains("hooks.rhai"), "{}", err);
+        // A script without hooks is most likely a mistake
+        assert!(script("fn on_transcript(text) { text }").is_err());
+        assert!(script("fn on_state_change(state) { print(state); }").is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+//! Scripting hooks (`[scripting]`)
+//!
+//! A [Rhai](https://rhai.rs) script in the config directory can define two
+//! functions for the daemon to call:
+//!
+//! - `on_transcript(text, ctx)` with the text of each dictation, after all
+//!   other processing and just before it's output. `ctx` is a map with
+//!   `raw` (the text as transcribed), `app`, `profile`, `engine` and
+//!   `model`. Returning a string replaces the text, and an empty one outputs
+//!   nothing. Returning nothing keeps the text. A map can also change how
+//!   the text is output: `#{ text: "...", output: "clipboard", auto_submit:
+//!   true }`.
+//! - `on_state_change(state)` each time the daemon goes idle, starts
+//!   recording, transcribing and so on.
+//!
+//! Hooks run on the daemon's thread, so each call is stopped after a million
+//! operations. Rhai itself can't touch files or processes; the script gets
+//! `spawn(command)` to run a shell command in the background, and `print`
+//! writes to the log. A script that doesn't compile keeps the daemon from
+//! starting. A hook that fails while running is logged, and the text is
+//! output as if the hook weren't there.
+//!
+//! Needs the `scripting` build feature.
+
+#[cfg(feature = "scripting")]
+mod engine;
+
+use crate::config::Config;
+use std::path::PathBuf;
+
+/// The recording a transcript came from
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TranscriptContext<'a> {
+    /// The text as transcribed, before any processing
+    pub raw: &'a str,
+    /// App focused when the recording started
+    pub app: Option<&'a str>,
+    /// Profile of the recording
+    pub profile: Option<&'a str>,
+    /// Transcription engine
+    pub engine: &'a str,
+    /// Model of the engine
+    pub model: &'a str,
+}
+
+/// The text to output, and how, after `on_transcript`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Outcome {
+    /// Text to output; nothing is output when it's empty
+    pub text: String,
+    /// Output mode for this text ("type", "clipboard", "file:/path", ...)
+    pub output: Option<String>,
+    /// Press Enter after the text
+    pub auto_submit: Option<bool>,
+}
+
+impl Outcome {
+    /// Output `text` as usual
+    pub fn keep(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The configured script
+pub struct Hooks {
+    #[cfg(feature = "scripting")]
+    script: engine::Script,
+}
+
+impl Hooks {
+    /// Load and compile the script in `[scripting]`, None when there is none
+    pub fn load(config: &Config) -> Result<Option<Self>, String> {
+        let Some(ref script) = config.scripting.script else {
+            return Ok(None);
+        };
+        let path = script_path(script);
+        #[cfg(feature = "scripting")]
+        {
+            engine::Script::load(&path).map(|script| Some(Self { script }))
+        }
+        #[cfg(not(feature = "scripting"))]
+        {
+            Err(format!(
+                "Script {:?} needs voxtype built with the `scripting` feature",
+                path
+            ))
+        }
+    }
+
+    /// Run `on_transcript` on the text about to be output
+    pub fn on_transcript(&self, text: &str, context: &TranscriptContext) -> Outcome {
+        #[cfg(feature = "scripting")]
+        {
+            self.script.on_transcript(text, context)
+        }
+        #[cfg(not(feature = "scripting"))]
+        {
+            let _ = context;
+            Outcome::keep(text)
+        }
+    }
+
+    /// Run `on_state_change` for the state just entered
+    pub fn on_state_change(&self, state: &str) {
+        #[cfg(feature = "scripting")]
+        self.script.on_state_change(state);
+        #[cfg(not(feature = "scripting"))]
+        let _ = state;
+    }
+}
+
+/// Path of the script; relative paths are in the config directory
+fn script_path(script: &str) -> PathBuf {
+    let path = PathBuf::from(script);
+    match Config::config_dir() {
+        Some(dir) if path.is_relative() => dir.join(path),
+        _ => path,
+    }
+}
@@ -0,0 +1,111 @@
+//! API keys from a command or the system keyring
+//!
+//! Instead of a key in plain text (`api_key`), the config can name a command
+//! that prints it (`api_key_cmd = "pass show openai"`) or an entry in the
+//! Secret Service keyring (`api_key_keyring = "openai"`), looked up with
+//! `secret-tool` from libsecret. The entry is the one stored with:
+//!
+//! ```text
+//! secret-tool store --label="voxtype openai" service voxtype key openai
+//! ```
+//!
+//! A key is fetched when it's first needed and kept for the life of the
+//! process, so a password manager asks for its passphrase once at most.
+
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+/// `service` attribute of voxtype's keyring entries
+const KEYRING_SERVICE: &str = "voxtype";
+
+/// Keys fetched so far, by where they came from
+static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+/// The key from the first of: `value` itself, the output of `cmd`, the
+/// keyring entry named `keyring`
+///
+/// Returns None when none of them is set.
+pub fn resolve(
+    value: Option<&str>,
+    cmd: Option<&str>,
+    keyring: Option<&str>,
+) -> Result<Option<String>, String> {
+    if let Some(value) = value.filter(|v| !v.is_empty()) {
+        return Ok(Some(value.to_string()));
+    }
+    if let Some(cmd) = cmd.filter(|c| !c.is_empty()) {
+        return cached(&format!("cmd:{}", cmd), || {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(cmd);
+            run(command).map_err(|e| format!("API key command `{}` failed: {}", cmd, e))
+        })
+        .map(Some);
+    }
+    if let Some(name) = keyring.filter(|k| !k.is_empty()) {
+        return cached(&format!("keyring:{}", name), || {
+            let mut command = Command::new("secret-tool");
+            command.args(["lookup", "service", KEYRING_SERVICE, "key", name]);
+            run(command).map_err(|e| format!("No API key '{}' in the keyring: {}", name, e))
+        })
+        .map(Some);
+    }
+    Ok(None)
+}
+
+/// The cached key for `source`, fetched with `fetch` the first time
+fn cached(source: &str, fetch: impl FnOnce() -> Result<String, String>) -> Result<String, String> {
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(key) = cache.lock().unwrap().get(source) {
+        return Ok(key.clone());
+    }
+    let key = fetch()?;
+    cache
+        .lock()
+        .unwrap()
+        .insert(source.to_string(), key.clone());
+    Ok(key)
+}
+
+/// First line of a command's output
+///
+/// `pass show` prints the password on the first line and notes after it.
+fn run(mut command: Command) -> Result<String, String> {
+    let output = command.output().map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => format!("exited with {}", output.status),
+            stderr => stderr.to_string(),
+        });
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let key = stdout.lines().next().unwrap_or_default().trim();
+    if key.is_empty() {
+        return Err("printed nothing".to_string());
+    }
+    Ok(key.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve(None, None, None), Ok(None));
+        // A key in the config wins
+        assert_eq!(
+            resolve(Some("sk-config"), Some("echo sk-cmd"), None),
+            Ok(Some("sk-config".to_string()))
+        );
+        assert_eq!(
+            resolve(Some(""), Some("printf 'sk-cmd\\nnotes\\n'"), None),
+            Ok(Some("sk-cmd".to_string()))
+        );
+
+        let err = resolve(None, Some("echo locked >&2; exit 1"), None).unwrap_err();
+        assert!(err.contains("locked"), "{}", err);
+        assert!(resolve(None, Some("true"), None).is_err());
+    }
+}
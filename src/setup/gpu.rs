@@ -4,8 +4,12 @@
 //! 1. Tiered mode (DEB/RPM pre-built): Multiple CPU binaries (avx2, avx512) + vulkan in /usr/lib/voxtype/
 //! 2. Simple mode (AUR source build): Native CPU binary at /usr/bin/voxtype + vulkan in /usr/lib/voxtype/
 //!
+//! Either mode may also have a CUDA build (voxtype-cuda, `--features gpu-cuda`) or a
+//! ROCm build (voxtype-rocm, `--features gpu-hipblas`) in /usr/lib/voxtype/.
+//!
 //! Engine-aware: In ONNX mode, switches between onnx-cuda and onnx-avx*.
-//! In Whisper mode, switches between vulkan and avx*.
+//! In Whisper mode, switches between cuda/rocm/vulkan and avx*, following
+//! `whisper.gpu_backend` (auto prefers CUDA on NVIDIA and ROCm on AMD GPUs).
 //!
 //! GPU Selection:
 //! On systems with multiple GPUs (e.g., Intel integrated + NVIDIA discrete), the Vulkan
@@ -17,6 +21,7 @@
 //!
 //! This sets VK_LOADER_DRIVERS_SELECT internally to filter Vulkan ICDs.
 
+use crate::config::GpuBackend;
 use std::fs;
 use std::os::unix::fs::symlink;
 use std::path::Path;
@@ -81,6 +86,8 @@ pub enum Backend {
     Avx2,   // Tiered mode: AVX2 binary
     Avx512, // Tiered mode: AVX-512 binary
     Vulkan, // GPU acceleration
+    Cuda,   // GPU acceleration (NVIDIA, whisper.cpp CUDA build)
+    Rocm,   // GPU acceleration (AMD, whisper.cpp HIP build)
 }
 
 impl Backend {
@@ -91,16 +98,34 @@ impl Backend {
             Backend::Avx2 => "voxtype-avx2",
             Backend::Avx512 => "voxtype-avx512",
             Backend::Vulkan => "voxtype-vulkan",
+            Backend::Cuda => "voxtype-cuda",
+            Backend::Rocm => "voxtype-rocm",
         }
     }
 
-    fn display_name(&self) -> &'static str {
+    pub fn display_name(&self) -> &'static str {
         match self {
             Backend::Cpu => "CPU (legacy)",
             Backend::Native => "CPU (native)",
             Backend::Avx2 => "CPU (AVX2)",
             Backend::Avx512 => "CPU (AVX-512)",
             Backend::Vulkan => "GPU (Vulkan)",
+            Backend::Cuda => "GPU (CUDA)",
+            Backend::Rocm => "GPU (ROCm)",
+        }
+    }
+
+    /// Whether this is a GPU backend
+    pub fn is_gpu(&self) -> bool {
+        matches!(self, Backend::Vulkan | Backend::Cuda | Backend::Rocm)
+    }
+
+    /// Cargo feature that builds this backend, for backends packages don't ship
+    fn build_feature(&self) -> Option<&'static str> {
+        match self {
+            Backend::Cuda => Some("gpu-cuda"),
+            Backend::Rocm => Some("gpu-hipblas"),
+            _ => None,
         }
     }
 }
@@ -179,6 +204,8 @@ pub fn detect_current_backend() -> Option<Backend> {
             "voxtype-avx2" => Some(Backend::Avx2),
             "voxtype-avx512" => Some(Backend::Avx512),
             "voxtype-vulkan" => Some(Backend::Vulkan),
+            "voxtype-cuda" => Some(Backend::Cuda),
+            "voxtype-rocm" => Some(Backend::Rocm),
             _ => None,
         };
     }
@@ -197,8 +224,14 @@ pub fn detect_available_backends() -> Vec<Backend> {
     let active_bin = get_active_binary_path();
 
     if is_tiered_mode() {
-        // Tiered mode: check for avx2, avx512, vulkan
-        for backend in [Backend::Avx2, Backend::Avx512, Backend::Vulkan] {
+        // Tiered mode: check for avx2, avx512 and the GPU builds
+        for backend in [
+            Backend::Avx2,
+            Backend::Avx512,
+            Backend::Vulkan,
+            Backend::Cuda,
+            Backend::Rocm,
+        ] {
             let path = Path::new(VOXTYPE_LIB_DIR).join(backend.binary_name());
             if path.exists() {
                 available.push(backend);
@@ -220,9 +253,14 @@ pub fn detect_available_backends() -> Vec<Backend> {
             available.push(Backend::Cpu);
         }
 
-        // Check for vulkan
-        if Path::new(VOXTYPE_LIB_DIR).join("voxtype-vulkan").exists() {
-            available.push(Backend::Vulkan);
+        // Check for GPU builds
+        for backend in [Backend::Vulkan, Backend::Cuda, Backend::Rocm] {
+            if Path::new(VOXTYPE_LIB_DIR)
+                .join(backend.binary_name())
+                .exists()
+            {
+                available.push(backend);
+            }
         }
     }
 
@@ -377,23 +415,25 @@ fn switch_backend_tiered(backend: Backend) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Enable GPU in simple mode (switch symlink from native to vulkan)
-fn enable_simple_mode() -> anyhow::Result<()> {
-    let vulkan_path = Path::new(VOXTYPE_LIB_DIR).join("voxtype-vulkan");
+/// Enable GPU in simple mode (switch symlink from native to vulkan or cuda)
+fn enable_simple_mode(backend: Backend) -> anyhow::Result<()> {
+    let gpu_path = Path::new(VOXTYPE_LIB_DIR).join(backend.binary_name());
     let native_path = Path::new(VOXTYPE_NATIVE);
     let active_bin = get_active_binary_path();
 
-    if !vulkan_path.exists() {
+    if !gpu_path.exists() {
         anyhow::bail!(
-            "Vulkan backend not installed.\n\
-             The voxtype-vulkan binary was not found in {}",
+            "{} backend not installed.\n\
+             The {} binary was not found in {}",
+            backend.display_name(),
+            backend.binary_name(),
             VOXTYPE_LIB_DIR
         );
     }
 
-    // Check if already using vulkan (symlink points to vulkan)
+    // Check if already using this backend (symlink points to it)
     if let Ok(target) = fs::read_link(active_bin) {
-        if target.file_name().map(|n| n.to_str()) == Some(Some("voxtype-vulkan")) {
+        if target.file_name().map(|n| n.to_str()) == Some(Some(backend.binary_name())) {
             anyhow::bail!("GPU backend is already enabled.");
         }
     }
@@ -430,8 +470,8 @@ fn enable_simple_mode() -> anyhow::Result<()> {
         })?;
     }
 
-    // Create symlink to vulkan
-    symlink(&vulkan_path, active_bin).map_err(|e| {
+    // Create symlink to the GPU binary
+    symlink(&gpu_path, active_bin).map_err(|e| {
         // Try to restore on failure
         if native_path.exists() {
             let _ = symlink(native_path, active_bin);
@@ -470,7 +510,7 @@ fn disable_simple_mode() -> anyhow::Result<()> {
         );
     }
 
-    // Remove vulkan symlink
+    // Remove GPU symlink
     if fs::symlink_metadata(active_bin).is_ok() {
         fs::remove_file(active_bin).map_err(|e| {
             anyhow::anyhow!(
@@ -538,7 +578,7 @@ pub fn show_status() {
         match detect_current_backend() {
             Some(backend) => {
                 println!("Active backend: {}", backend.display_name());
-                if backend == Backend::Vulkan || (tiered && backend != Backend::Cpu) {
+                if backend.is_gpu() || (tiered && backend != Backend::Cpu) {
                     println!(
                         "  Binary: {}",
                         Path::new(VOXTYPE_LIB_DIR)
@@ -602,7 +642,13 @@ pub fn show_status() {
             println!("  {} - {}", display, status);
         }
     } else if tiered {
-        for backend in [Backend::Avx2, Backend::Avx512, Backend::Vulkan] {
+        for backend in [
+            Backend::Avx2,
+            Backend::Avx512,
+            Backend::Vulkan,
+            Backend::Cuda,
+            Backend::Rocm,
+        ] {
             let installed = available.contains(&backend);
             let active = current == Some(backend);
 
@@ -617,7 +663,12 @@ pub fn show_status() {
             println!("  {} - {}", backend.display_name(), status);
         }
     } else {
-        for backend in [Backend::Native, Backend::Vulkan] {
+        for backend in [
+            Backend::Native,
+            Backend::Vulkan,
+            Backend::Cuda,
+            Backend::Rocm,
+        ] {
             let installed = available.contains(&backend);
             let active = current == Some(backend);
 
@@ -652,6 +703,13 @@ pub fn show_status() {
             println!("Vulkan runtime: NOT FOUND");
             println!("  Install vulkan-icd-loader for GPU acceleration");
         }
+        if gpus.iter().any(|g| g.vendor == GpuVendor::Amd) {
+            if check_rocm_runtime() {
+                println!("ROCm runtime: installed");
+            } else {
+                println!("ROCm runtime: not installed (only needed for the ROCm backend)");
+            }
+        }
 
         // Show GPU selection status if multiple GPUs
         if gpus.len() > 1 {
@@ -695,10 +753,10 @@ pub fn show_status() {
             println!("To switch back to CPU:");
             println!("  sudo voxtype setup gpu --disable");
         }
-    } else if current != Some(Backend::Vulkan) && available.contains(&Backend::Vulkan) {
+    } else if !current.is_some_and(|b| b.is_gpu()) && available.iter().any(Backend::is_gpu) {
         println!("To enable GPU acceleration:");
         println!("  sudo voxtype setup gpu --enable");
-    } else if current == Some(Backend::Vulkan) {
+    } else if current.is_some_and(|b| b.is_gpu()) {
         println!("To switch back to CPU:");
         println!("  sudo voxtype setup gpu --disable");
     }
@@ -747,8 +805,91 @@ fn detect_best_parakeet_gpu_backend() -> Option<(&'static str, &'static str)> {
     None
 }
 
-/// Enable GPU backend (engine-aware: Vulkan for Whisper, CUDA/ROCm for Parakeet)
-pub fn enable() -> anyhow::Result<()> {
+/// Pick the Whisper GPU binary to switch to
+///
+/// An explicit `gpu_backend` wins when that binary is installed, then
+/// Vulkan; with "auto", the GPU vendor's own backend (CUDA on NVIDIA, ROCm
+/// on AMD) is preferred over Vulkan.
+fn choose_whisper_gpu_backend(
+    preference: GpuBackend,
+    available: &[Backend],
+    vendors: &[GpuVendor],
+) -> Option<Backend> {
+    let native = vendors.iter().filter_map(|vendor| match vendor {
+        GpuVendor::Nvidia => Some(Backend::Cuda),
+        GpuVendor::Amd => Some(Backend::Rocm),
+        _ => None,
+    });
+    let mut order: Vec<Backend> = match preference {
+        GpuBackend::Cuda => vec![Backend::Cuda, Backend::Vulkan],
+        GpuBackend::Vulkan => vec![Backend::Vulkan],
+        GpuBackend::Rocm => vec![Backend::Rocm, Backend::Vulkan],
+        GpuBackend::Auto | GpuBackend::Metal | GpuBackend::OpenVino | GpuBackend::Cpu => Vec::new(),
+    };
+    order.extend(native);
+    order.extend([Backend::Vulkan, Backend::Cuda, Backend::Rocm]);
+    order
+        .into_iter()
+        .find(|backend| available.contains(backend))
+}
+
+/// Directories searched for the ROCm HIP runtime
+const ROCM_LIB_DIRS: &[&str] = &[
+    "/opt/rocm/lib",
+    "/usr/lib",
+    "/usr/lib64",
+    "/usr/lib/x86_64-linux-gnu",
+];
+
+/// Check if the ROCm HIP runtime (libamdhip64.so) is installed
+pub fn check_rocm_runtime() -> bool {
+    ROCM_LIB_DIRS.iter().any(|dir| {
+        fs::read_dir(dir)
+            .map(|entries| {
+                entries.flatten().any(|e| {
+                    e.file_name()
+                        .to_string_lossy()
+                        .starts_with("libamdhip64.so")
+                })
+            })
+            .unwrap_or(false)
+    })
+}
+
+/// Check if this user can open /dev/kfd, the ROCm compute device
+/// (usually requires membership in the `render` group)
+pub fn check_kfd_access() -> bool {
+    fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/kfd")
+        .is_ok()
+}
+
+/// Print how to fix a missing ROCm runtime or /dev/kfd access
+fn print_rocm_hints() {
+    if !Path::new("/dev/kfd").exists() {
+        println!("Warning: /dev/kfd not found; the amdgpu kernel driver is not loaded.");
+        println!("The ROCm build will run on the CPU until it is.");
+        println!();
+    } else if !check_kfd_access() {
+        println!("Warning: no access to /dev/kfd.");
+        println!("Add yourself to the render and video groups, then log out and back in:");
+        println!("  sudo usermod -aG render,video $USER");
+        println!();
+    }
+    if !check_rocm_runtime() {
+        println!("Warning: ROCm runtime (libamdhip64.so) not found.");
+        println!("You may need to install the HIP runtime:");
+        println!("  Fedora: sudo dnf install rocm-hip");
+        println!("  Arch:   sudo pacman -S rocm-hip-runtime");
+        println!("  Ubuntu: see https://rocm.docs.amd.com");
+        println!();
+    }
+}
+
+/// Enable GPU backend (engine-aware: CUDA/Vulkan for Whisper, CUDA/ROCm for Parakeet)
+pub fn enable(preference: GpuBackend) -> anyhow::Result<()> {
     // Check which engine is active by looking at the current symlink
     let is_parakeet = is_parakeet_binary_active();
 
@@ -791,18 +932,40 @@ pub fn enable() -> anyhow::Result<()> {
         println!("Restart voxtype to use GPU acceleration:");
         println!("  systemctl --user restart voxtype");
     } else {
-        // Whisper mode: switch to Vulkan backend
-        let vulkan_path = Path::new(VOXTYPE_LIB_DIR).join("voxtype-vulkan");
-        if !vulkan_path.exists() {
-            anyhow::bail!(
-                "Vulkan backend not installed.\n\
-                 The voxtype-vulkan binary was not found in {}",
-                VOXTYPE_LIB_DIR
+        // Whisper mode: switch to the preferred installed GPU backend
+        let vendors: Vec<GpuVendor> = detect_gpus().iter().map(|g| g.vendor).collect();
+        let available = detect_available_backends();
+        let backend =
+            choose_whisper_gpu_backend(preference, &available, &vendors).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No GPU backend installed.\n\
+                     None of voxtype-vulkan, voxtype-cuda or voxtype-rocm was found in {}",
+                    VOXTYPE_LIB_DIR
+                )
+            })?;
+        let requested = match preference {
+            GpuBackend::Cuda => Some(Backend::Cuda),
+            GpuBackend::Vulkan => Some(Backend::Vulkan),
+            GpuBackend::Rocm => Some(Backend::Rocm),
+            GpuBackend::Auto | GpuBackend::Metal | GpuBackend::OpenVino | GpuBackend::Cpu => None,
+        };
+        if let Some(requested) = requested.filter(|&r| r != backend) {
+            println!(
+                "Note: {} is not installed, using {} instead.",
+                requested.binary_name(),
+                backend.display_name()
             );
+            if let Some(feature) = requested.build_feature() {
+                println!(
+                    "Build it with: cargo build --release --features {}",
+                    feature
+                );
+            }
+            println!();
         }
 
         // Check Vulkan runtime
-        if !check_vulkan_runtime() {
+        if backend == Backend::Vulkan && !check_vulkan_runtime() {
             println!("Warning: Vulkan runtime (libvulkan.so.1) not found.");
             println!("You may need to install vulkan-icd-loader:");
             println!("  Fedora: sudo dnf install vulkan-loader");
@@ -811,10 +974,19 @@ pub fn enable() -> anyhow::Result<()> {
             println!();
         }
 
+        if backend == Backend::Cuda && !vendors.contains(&GpuVendor::Nvidia) {
+            println!("Warning: no NVIDIA GPU detected; the CUDA build will run on the CPU.");
+            println!();
+        }
+
+        if backend == Backend::Rocm {
+            print_rocm_hints();
+        }
+
         if is_tiered_mode() {
-            switch_backend_tiered(Backend::Vulkan)?;
+            switch_backend_tiered(backend)?;
         } else {
-            enable_simple_mode()?;
+            enable_simple_mode(backend)?;
         }
 
         // Regenerate systemd service if it exists
@@ -822,7 +994,7 @@ pub fn enable() -> anyhow::Result<()> {
             println!("Updated systemd service to use GPU backend.");
         }
 
-        println!("Switched to GPU (Vulkan) backend.");
+        println!("Switched to {} backend.", backend.display_name());
         println!();
         println!("Restart voxtype to use GPU acceleration:");
         println!("  systemctl --user restart voxtype");
@@ -969,3 +1141,55 @@ fn switch_backend_tiered_parakeet(binary_name: &str) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_choose_whisper_gpu_backend() {
+        use GpuVendor::*;
+        let all = [Backend::Avx2, Backend::Vulkan, Backend::Cuda, Backend::Rocm];
+        let choose = choose_whisper_gpu_backend;
+
+        // Auto prefers the vendor's own backend, then Vulkan
+        assert_eq!(choose(GpuBackend::Auto, &all, &[Amd]), Some(Backend::Rocm));
+        assert_eq!(
+            choose(GpuBackend::Auto, &all, &[Intel, Nvidia]),
+            Some(Backend::Cuda)
+        );
+        assert_eq!(
+            choose(GpuBackend::Auto, &all, &[Intel]),
+            Some(Backend::Vulkan)
+        );
+        assert_eq!(
+            choose(GpuBackend::Auto, &[Backend::Vulkan], &[Amd]),
+            Some(Backend::Vulkan)
+        );
+
+        // An explicit preference wins, falling back to Vulkan
+        assert_eq!(
+            choose(GpuBackend::Vulkan, &all, &[Amd]),
+            Some(Backend::Vulkan)
+        );
+        assert_eq!(
+            choose(GpuBackend::Rocm, &all, &[Nvidia]),
+            Some(Backend::Rocm)
+        );
+        assert_eq!(
+            choose(
+                GpuBackend::Rocm,
+                &[Backend::Vulkan, Backend::Cuda],
+                &[Nvidia]
+            ),
+            Some(Backend::Vulkan)
+        );
+
+        // Whatever GPU build is installed, even without a detected GPU
+        assert_eq!(
+            choose(GpuBackend::Auto, &[Backend::Rocm], &[]),
+            Some(Backend::Rocm)
+        );
+        assert_eq!(choose(GpuBackend::Auto, &[Backend::Avx2], &[Amd]), None);
+    }
+}
@@ -8,6 +8,7 @@
 //! - GPU backend management
 //! - Parakeet backend management
 //! - Compositor integration (modifier key fix)
+//! - VAD and wake-word model downloads
 
 pub mod compositor;
 pub mod dms;
@@ -16,9 +17,10 @@ pub mod model;
 pub mod parakeet;
 pub mod systemd;
 pub mod vad;
+pub mod wake_word;
 pub mod waybar;
 
-use crate::config::Config;
+use crate::config::{Config, GpuBackend};
 use std::process::Stdio;
 use tokio::process::Command;
 
@@ -623,7 +625,7 @@ pub async fn run_setup(
             None => &config.whisper.model,
         };
 
-        let model_filename = crate::transcribe::whisper::get_model_filename(model_name);
+        let model_filename = crate::transcribe::models::model_filename(model_name);
         let model_path = models_dir.join(&model_filename);
 
         if model_path.exists() {
@@ -767,10 +769,31 @@ pub async fn run_checks(config: &Config) -> anyhow::Result<()> {
         }
     }
 
+    // Probe the configured chain the same way the daemon does at startup
+    if config.output.mode != crate::config::OutputMode::File {
+        println!("\nConfigured output chain ({:?} mode):", config.output.mode);
+        let chain = crate::output::create_output_chain(&config.output);
+        let results = crate::output::probe_chain(&chain).await;
+        for (name, ok) in &results {
+            if *ok {
+                print_success(name);
+            } else {
+                print_failure(&format!("{} (unavailable, tried last)", name));
+            }
+        }
+        match results.iter().find(|(_, ok)| *ok) {
+            Some((name, _)) => print_info(&format!("Text will be delivered via {}", name)),
+            None => {
+                print_failure("No configured output method is usable");
+                all_ok = false;
+            }
+        }
+    }
+
     // Check whisper model
     println!("\nWhisper Model:");
     let model_name = &config.whisper.model;
-    let model_filename = crate::transcribe::whisper::get_model_filename(model_name);
+    let model_filename = crate::transcribe::models::model_filename(model_name);
     let model_path = models_dir.join(&model_filename);
 
     if model_path.exists() {
@@ -787,6 +810,79 @@ pub async fn run_checks(config: &Config) -> anyhow::Result<()> {
         all_ok = false;
     }
 
+    // Check the runtime of the active or configured GPU backend
+    println!("\nGPU:");
+    let backend = gpu::detect_current_backend();
+    match backend {
+        Some(backend) => print_info(&format!("Active backend: {}", backend.display_name())),
+        None => print_info("Active backend: unknown (not a packaged install)"),
+    }
+    let preference = config.whisper.gpu_backend;
+    if preference != GpuBackend::Auto {
+        print_info(&format!(
+            "Preferred backend: {} (whisper.gpu_backend)",
+            preference
+        ));
+    }
+    let uses = |gpu_backend: GpuBackend, binary: gpu::Backend| {
+        backend == Some(binary)
+            || preference == gpu_backend
+            || crate::transcribe::gpu::compiled_backend() == Some(gpu_backend)
+    };
+    if uses(GpuBackend::Vulkan, gpu::Backend::Vulkan) {
+        if gpu::check_vulkan_runtime() {
+            print_success("Vulkan runtime (libvulkan.so.1) installed");
+        } else {
+            print_failure("Vulkan runtime (libvulkan.so.1) not found");
+            println!("       Install vulkan-icd-loader (Arch), vulkan-loader (Fedora) or libvulkan1 (Ubuntu)");
+            all_ok = false;
+        }
+    }
+    if uses(GpuBackend::Cuda, gpu::Backend::Cuda) {
+        if crate::transcribe::gpu::nvidia_driver_loaded() {
+            print_success("NVIDIA driver loaded");
+        } else {
+            print_failure("NVIDIA driver not loaded - CUDA will fall back to the CPU");
+            all_ok = false;
+        }
+    }
+    if uses(GpuBackend::Rocm, gpu::Backend::Rocm) {
+        if !crate::transcribe::gpu::rocm_driver_loaded() {
+            print_failure(
+                "/dev/kfd not found - amdgpu driver not loaded, ROCm will fall back to the CPU",
+            );
+            all_ok = false;
+        } else if gpu::check_kfd_access() {
+            print_success("/dev/kfd accessible");
+        } else {
+            print_failure("No access to /dev/kfd");
+            println!("       To fix: sudo usermod -aG render,video $USER && logout");
+            all_ok = false;
+        }
+        if gpu::check_rocm_runtime() {
+            print_success("ROCm runtime (libamdhip64.so) installed");
+        } else {
+            print_failure("ROCm runtime (libamdhip64.so) not found");
+            println!("       Install rocm-hip-runtime (Arch) or rocm-hip (Fedora)");
+            all_ok = false;
+        }
+    }
+    if preference == GpuBackend::OpenVino {
+        // A missing model is reported with the models above
+        if let Ok(model) = crate::transcribe::models::resolve(&config.whisper.model, false) {
+            let encoder = crate::transcribe::cli::openvino_encoder_path(&model);
+            if encoder.exists() {
+                print_success(&format!("OpenVINO encoder: {:?}", encoder));
+            } else {
+                print_failure(&format!("OpenVINO encoder not found: {:?}", encoder));
+                println!(
+                    "       Create it with whisper.cpp's models/convert-whisper-to-openvino.py"
+                );
+                all_ok = false;
+            }
+        }
+    }
+
     // Check Parakeet models (experimental)
     println!("\nParakeet Models (EXPERIMENTAL):");
 
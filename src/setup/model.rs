@@ -2,10 +2,9 @@
 
 use super::{print_failure, print_info, print_success, print_warning};
 use crate::config::{Config, TranscriptionEngine};
-use crate::transcribe::whisper::{get_model_filename, get_model_url};
+use crate::transcribe::models::{self, model_filename, model_url};
 use std::io::{self, Write};
 use std::path::Path;
-use std::process::Command;
 
 /// Model information for display
 struct ModelInfo {
@@ -81,6 +80,25 @@ const MODELS: &[ModelInfo] = &[
         description: "Fast + accurate (recommended for GPU)",
         english_only: false,
     },
+    // Distil-Whisper models: distilled, about 2x faster on CPU
+    ModelInfo {
+        name: "distil-small.en",
+        size_mb: 336,
+        description: "small.en accuracy, faster",
+        english_only: true,
+    },
+    ModelInfo {
+        name: "distil-medium.en",
+        size_mb: 789,
+        description: "medium.en accuracy, faster",
+        english_only: true,
+    },
+    ModelInfo {
+        name: "distil-large-v3",
+        size_mb: 1520,
+        description: "Near large-v3 accuracy (recommended for CPU)",
+        english_only: true,
+    },
 ];
 
 // =============================================================================
@@ -432,14 +450,14 @@ const OMNILINGUAL_MODELS: &[OmnilingualModelInfo] = &[
 // Whisper Model Functions
 // =============================================================================
 
-/// Check if a model name is valid (Whisper models)
+/// Check if a model name is valid (Whisper models from the catalog)
 pub fn is_valid_model(name: &str) -> bool {
-    MODELS.iter().any(|m| m.name == name)
+    models::is_known(name)
 }
 
 /// Get list of valid model names (for error messages)
 pub fn valid_model_names() -> Vec<&'static str> {
-    MODELS.iter().map(|m| m.name).collect()
+    models::names()
 }
 
 /// Run interactive model selection (single menu with all models)
@@ -493,7 +511,7 @@ pub async fn interactive_select() -> anyhow::Result<()> {
     println!("--- Whisper (OpenAI, 99+ languages) ---\n");
 
     for (i, model) in MODELS.iter().enumerate() {
-        let filename = get_model_filename(model.name);
+        let filename = model_filename(model.name);
         let model_path = models_dir.join(&filename);
         let installed = model_path.exists();
 
@@ -789,7 +807,7 @@ async fn handle_whisper_selection(selection: usize) -> anyhow::Result<()> {
     }
 
     let model = &MODELS[selection - 1];
-    let filename = get_model_filename(model.name);
+    let filename = model_filename(model.name);
     let model_path = models_dir.join(&filename);
 
     // Check if already installed
@@ -995,19 +1013,19 @@ async fn restart_daemon_if_running() {
 /// Download a specific Whisper model using curl
 pub fn download_model(model_name: &str) -> anyhow::Result<()> {
     let models_dir = Config::models_dir();
-    let filename = get_model_filename(model_name);
+    let filename = model_filename(model_name);
     let model_path = models_dir.join(&filename);
 
     // Ensure directory exists
     std::fs::create_dir_all(&models_dir)?;
 
-    let url = get_model_url(model_name);
+    let url = model_url(model_name);
 
     println!("\nDownloading {}...", model_name);
     println!("URL: {}", url);
 
     // Use curl for downloading - it handles progress display and redirects
-    let status = Command::new("curl")
+    let status = crate::proxy::command("curl")
         .args([
             "-L",             // Follow redirects
             "--progress-bar", // Show progress bar
@@ -1061,7 +1079,7 @@ pub fn ensure_gtcrn_model() -> Option<std::path::PathBuf> {
 
     println!("Downloading GTCRN speech enhancement model (523 KB)...");
 
-    let status = Command::new("curl")
+    let status = crate::proxy::command("curl")
         .args([
             "-L",
             "--progress-bar",
@@ -1091,7 +1109,7 @@ pub fn ensure_gtcrn_model() -> Option<std::path::PathBuf> {
 /// Set a specific model as the default (must already be downloaded)
 pub async fn set_model(model_name: &str, restart: bool) -> anyhow::Result<()> {
     let models_dir = Config::models_dir();
-    let filename = get_model_filename(model_name);
+    let filename = model_filename(model_name);
     let model_path = models_dir.join(&filename);
 
     // Verify the model exists
@@ -1147,7 +1165,7 @@ pub fn list_installed() {
     let mut found = false;
 
     for model in MODELS {
-        let filename = get_model_filename(model.name);
+        let filename = model_filename(model.name);
         let model_path = models_dir.join(&filename);
 
         if model_path.exists() {
@@ -1320,7 +1338,7 @@ fn download_parakeet_model_by_info(model: &ParakeetModelInfo) -> anyhow::Result<
 
         println!("Downloading {}...", filename);
 
-        let status = Command::new("curl")
+        let status = crate::proxy::command("curl")
             .args([
                 "-L",
                 "--progress-bar",
@@ -1595,7 +1613,7 @@ fn download_moonshine_model_by_info(model: &MoonshineModelInfo) -> anyhow::Resul
 
         println!("Downloading {}...", local_filename);
 
-        let status = Command::new("curl")
+        let status = crate::proxy::command("curl")
             .args([
                 "-L",
                 "--progress-bar",
@@ -1921,7 +1939,7 @@ fn download_sensevoice_model_by_info(model: &SenseVoiceModelInfo) -> anyhow::Res
 
         println!("Downloading {}...", local_filename);
 
-        let status = Command::new("curl")
+        let status = crate::proxy::command("curl")
             .args([
                 "-L",
                 "--progress-bar",
@@ -2217,7 +2235,7 @@ fn download_onnx_model(
 
         println!("Downloading {}...", local_filename);
 
-        let status = Command::new("curl")
+        let status = crate::proxy::command("curl")
             .args([
                 "-L",
                 "--progress-bar",
@@ -2439,6 +2457,17 @@ language = "en"
         // Large models (multilingual only)
         assert!(model_names.contains(&"large-v3"));
         assert!(model_names.contains(&"large-v3-turbo"));
+        // Distilled models
+        assert!(model_names.contains(&"distil-small.en"));
+        assert!(model_names.contains(&"distil-large-v3"));
+    }
+
+    #[test]
+    fn test_models_list_matches_catalog() {
+        for model in MODELS {
+            let known = models::find(model.name).expect(model.name);
+            assert_eq!(model.english_only, known.english_only, "{}", model.name);
+        }
     }
 
     #[test]
@@ -2484,7 +2513,11 @@ language = "en"
         let names = valid_model_names();
         assert!(names.contains(&"tiny.en"));
         assert!(names.contains(&"large-v3-turbo"));
-        assert_eq!(names.len(), MODELS.len());
+        assert!(names.contains(&"distil-small.en"));
+        // Every model in the menu can be downloaded
+        for model in MODELS {
+            assert!(names.contains(&model.name), "{} not in catalog", model.name);
+        }
     }
 
     // =========================================================================
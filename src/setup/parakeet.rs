@@ -97,6 +97,7 @@ fn detect_current_whisper_backend() -> Option<&'static str> {
             "voxtype-avx2" => Some("voxtype-avx2"),
             "voxtype-avx512" => Some("voxtype-avx512"),
             "voxtype-vulkan" => Some("voxtype-vulkan"),
+            "voxtype-cuda" => Some("voxtype-cuda"),
             "voxtype-native" => Some("voxtype-native"),
             _ => None,
         };
@@ -3,7 +3,6 @@
 use super::{print_info, print_success, print_warning};
 use crate::config::Config;
 use crate::vad::{get_whisper_vad_model_filename, get_whisper_vad_model_url};
-use std::process::Command;
 
 /// Download the Silero VAD model
 pub fn download_model() -> anyhow::Result<()> {
@@ -24,7 +23,7 @@ pub fn download_model() -> anyhow::Result<()> {
     println!("Downloading Silero VAD model...");
     println!("URL: {}", url);
 
-    let status = Command::new("curl")
+    let status = crate::proxy::command("curl")
         .args([
             "-L",
             "--progress-bar",
@@ -0,0 +1,96 @@
+//! Wake-word model download and status
+
+use super::{print_info, print_success, print_warning};
+use crate::config::Config;
+use crate::wake::{model_paths, model_url};
+use std::path::Path;
+
+/// Download the openWakeWord feature models and the configured phrase model
+pub fn download_models(config: &Config) -> anyhow::Result<()> {
+    let model = &config.wake_word.model;
+    let paths = model_paths(&config.wake_word);
+
+    for path in &paths {
+        if path.exists() {
+            print_success(&format!("Already installed: {:?}", path));
+            continue;
+        }
+        // Custom phrase models can't be downloaded
+        if model.ends_with(".onnx") && Some(path) == paths.last() {
+            anyhow::bail!("Wake word model not found: {:?}", path);
+        }
+        download(path)?;
+    }
+
+    println!();
+    print_info("Enable in config.toml:");
+    println!("  [wake_word]");
+    println!("  enabled = true");
+    println!("  model = \"{}\"", model);
+    Ok(())
+}
+
+/// Download one file from the openWakeWord release
+fn download(path: &Path) -> anyhow::Result<()> {
+    let filename = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let url = model_url(filename);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    println!("Downloading {}...", filename);
+    println!("URL: {}", url);
+
+    let status = crate::proxy::command("curl")
+        .args([
+            "-fL",
+            "--progress-bar",
+            "-o",
+            path.to_str().unwrap_or("model.onnx"),
+            &url,
+        ])
+        .status();
+
+    match status {
+        Ok(exit_status) if exit_status.success() => {
+            print_success(&format!("Saved to {:?}", path));
+            Ok(())
+        }
+        Ok(exit_status) => {
+            let _ = std::fs::remove_file(path);
+            anyhow::bail!(
+                "Download failed: curl exited with code {} (is \"{}\" an openWakeWord model name?)",
+                exit_status.code().unwrap_or(-1),
+                filename
+            )
+        }
+        Err(e) => {
+            print_info("Please ensure curl is installed (e.g., 'sudo pacman -S curl')");
+            anyhow::bail!("curl not available: {}", e)
+        }
+    }
+}
+
+/// Show wake-word model status
+pub fn show_status(config: &Config) {
+    println!("Wake Word Model Status\n");
+
+    let mut missing = false;
+    for path in model_paths(&config.wake_word) {
+        if path.exists() {
+            print_success(&format!("Installed: {:?}", path));
+        } else {
+            print_warning(&format!("Not installed: {:?}", path));
+            missing = true;
+        }
+    }
+    if missing {
+        print_info("Download with: voxtype setup wake-word");
+    }
+    if !cfg!(feature = "wake-word") {
+        print_warning("This build has no wake word support (cargo build --features wake-word)");
+    }
+}
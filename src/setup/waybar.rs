@@ -216,7 +216,7 @@ fn inject_module_into_config(content: &str) -> Result<String, VoxtypeError> {
             r#",
 
     "custom/voxtype": {
-        "exec": "voxtype status --follow --format json",
+        "exec": "voxtype waybar",
         "return-type": "json",
         "format": "{}",
         "tooltip": true,
@@ -226,7 +226,7 @@ fn inject_module_into_config(content: &str) -> Result<String, VoxtypeError> {
         } else {
             r#"
     "custom/voxtype": {
-        "exec": "voxtype status --follow --format json",
+        "exec": "voxtype waybar",
         "return-type": "json",
         "format": "{}",
         "tooltip": true,
@@ -419,7 +419,7 @@ pub fn print_config() {
     println!("   Then add this module configuration:\n");
     println!(
         r#"   "custom/voxtype": {{
-       "exec": "voxtype status --follow --format json",
+       "exec": "voxtype waybar",
        "return-type": "json",
        "format": "{{}}",
        "tooltip": true,
@@ -471,7 +471,7 @@ pub fn print_config() {
     println!("To use custom icons (e.g., Nerd Fonts), configure your Waybar module like this:\n");
     println!(
         r#"   "custom/voxtype": {{
-       "exec": "voxtype status --follow --format json",
+       "exec": "voxtype waybar",
        "return-type": "json",
        "format": "{{icon}}",
        "format-icons": {{
@@ -501,7 +501,7 @@ pub fn print_config() {
 /// Generate just the JSON config snippet (for programmatic use)
 pub fn get_json_config() -> &'static str {
     r#""custom/voxtype": {
-    "exec": "voxtype status --follow --format json",
+    "exec": "voxtype waybar",
     "return-type": "json",
     "format": "{}",
     "tooltip": true,
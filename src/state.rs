@@ -2,6 +2,9 @@
 //!
 //! Defines the states for the push-to-talk workflow:
 //! Idle → Recording → Transcribing → Outputting → Idle
+//!
+//! A new recording can start while earlier ones are still transcribing;
+//! their text is output in order while the state stays Recording.
 
 use std::time::Instant;
 
@@ -76,6 +79,11 @@ impl State {
         matches!(self, State::Recording { .. } | State::EagerRecording { .. })
     }
 
+    /// Check if a new recording can start (idle, or only transcribing)
+    pub fn can_start_recording(&self) -> bool {
+        matches!(self, State::Idle | State::Transcribing { .. })
+    }
+
     /// Check if in eager recording state specifically
     pub fn is_eager_recording(&self) -> bool {
         matches!(self, State::EagerRecording { .. })
@@ -91,6 +99,15 @@ impl State {
         }
     }
 
+    /// Get the model requested for the recording in progress, if any
+    pub fn model_override(&self) -> Option<&str> {
+        match self {
+            State::Recording { model_override, .. }
+            | State::EagerRecording { model_override, .. } => model_override.as_deref(),
+            _ => None,
+        }
+    }
+
     /// Get the number of chunks sent for transcription (eager mode only)
     pub fn eager_chunks_sent(&self) -> Option<usize> {
         match self {
@@ -175,6 +192,31 @@ mod tests {
         assert!(state.recording_duration().is_some());
     }
 
+    #[test]
+    fn test_can_start_recording_while_transcribing() {
+        assert!(State::Idle.can_start_recording());
+        assert!(State::Transcribing { audio: vec![] }.can_start_recording());
+        let state = State::Recording {
+            started_at: Instant::now(),
+            model_override: None,
+        };
+        assert!(!state.can_start_recording());
+        let state = State::Outputting {
+            text: "hi".to_string(),
+        };
+        assert!(!state.can_start_recording());
+    }
+
+    #[test]
+    fn test_model_override() {
+        let state = State::Recording {
+            started_at: Instant::now(),
+            model_override: Some("large-v3".to_string()),
+        };
+        assert_eq!(state.model_override(), Some("large-v3"));
+        assert_eq!(State::Idle.model_override(), None);
+    }
+
     #[test]
     fn test_idle_has_no_duration() {
         let state = State::Idle;
@@ -0,0 +1,244 @@
+//! Code dictation
+//!
+//! Turns spoken code into tokens: "snake case user id equals camel case get
+//! user name" becomes `user_id = getUserName`. Case commands join the words
+//! that follow into one identifier, up to the next operator, symbol or case
+//! command. Operators are spaced, symbols like "dot" and "open paren" attach
+//! to their neighbours, and nothing is capitalized or punctuated.
+
+/// Identifier casing styles
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Case {
+    /// user_id
+    Snake,
+    /// getUserName
+    Camel,
+    /// UserName
+    Pascal,
+    /// user-id
+    Kebab,
+    /// MAX_VALUE
+    Constant,
+}
+
+/// Spoken case commands, longest first
+const CASES: &[(&str, Case)] = &[
+    ("screaming snake case", Case::Constant),
+    ("snake case", Case::Snake),
+    ("camel case", Case::Camel),
+    ("pascal case", Case::Pascal),
+    ("kebab case", Case::Kebab),
+    ("constant case", Case::Constant),
+];
+
+/// How a symbol is spaced
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Spacing {
+    /// Space on both sides: `a == b`
+    Spaced,
+    /// No space on either side: `a.b`, `f(`
+    Attached,
+    /// Attached to the previous token, space after: `a, b`, `f(x) {`
+    Trailing,
+}
+
+/// Spoken operators and symbols, longest first
+const SYMBOLS: &[(&str, &str, Spacing)] = &[
+    ("greater than or equal to", ">=", Spacing::Spaced),
+    ("less than or equal to", "<=", Spacing::Spaced),
+    ("greater than or equal", ">=", Spacing::Spaced),
+    ("less than or equal", "<=", Spacing::Spaced),
+    ("equals equals equals", "===", Spacing::Spaced),
+    ("triple equals", "===", Spacing::Spaced),
+    ("equals equals", "==", Spacing::Spaced),
+    ("double equals", "==", Spacing::Spaced),
+    ("not equals", "!=", Spacing::Spaced),
+    ("not equal", "!=", Spacing::Spaced),
+    ("plus equals", "+=", Spacing::Spaced),
+    ("minus equals", "-=", Spacing::Spaced),
+    ("times equals", "*=", Spacing::Spaced),
+    ("greater than", ">", Spacing::Spaced),
+    ("less than", "<", Spacing::Spaced),
+    ("fat arrow", "=>", Spacing::Spaced),
+    ("and and", "&&", Spacing::Spaced),
+    ("or or", "||", Spacing::Spaced),
+    ("divided by", "/", Spacing::Spaced),
+    ("double colon", "::", Spacing::Attached),
+    ("open paren", "(", Spacing::Attached),
+    ("open parenthesis", "(", Spacing::Attached),
+    ("close paren", ")", Spacing::Trailing),
+    ("close parenthesis", ")", Spacing::Trailing),
+    ("open bracket", "[", Spacing::Attached),
+    ("close bracket", "]", Spacing::Trailing),
+    ("open brace", "{", Spacing::Spaced),
+    ("close brace", "}", Spacing::Spaced),
+    ("equals", "=", Spacing::Spaced),
+    ("arrow", "->", Spacing::Spaced),
+    ("plus", "+", Spacing::Spaced),
+    ("minus", "-", Spacing::Spaced),
+    ("times", "*", Spacing::Spaced),
+    ("modulo", "%", Spacing::Spaced),
+    ("dot", ".", Spacing::Attached),
+    ("underscore", "_", Spacing::Attached),
+    ("comma", ",", Spacing::Trailing),
+    ("colon", ":", Spacing::Trailing),
+    ("semicolon", ";", Spacing::Trailing),
+];
+
+/// A formatted token and how it joins its neighbours
+struct Token {
+    text: String,
+    spacing: Spacing,
+}
+
+/// Format a spoken utterance as code
+pub fn format(text: &str) -> String {
+    let words = words(text);
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i = 0;
+
+    while i < words.len() {
+        if let Some((len, case)) = match_phrase(&words[i..], CASES) {
+            i += len;
+            let start = i;
+            while i < words.len()
+                && match_phrase(&words[i..], CASES).is_none()
+                && match_symbol(&words[i..]).is_none()
+            {
+                i += 1;
+            }
+            if i > start {
+                tokens.push(Token {
+                    text: identifier(&words[start..i], case),
+                    spacing: Spacing::Spaced,
+                });
+            }
+        } else if let Some((len, symbol, spacing)) = match_symbol(&words[i..]) {
+            i += len;
+            tokens.push(Token {
+                text: symbol.to_string(),
+                spacing,
+            });
+        } else {
+            tokens.push(Token {
+                text: words[i].clone(),
+                spacing: Spacing::Spaced,
+            });
+            i += 1;
+        }
+    }
+
+    let mut result = String::new();
+    let mut previous: Option<Spacing> = None;
+    for token in tokens {
+        if previous.is_some_and(|p| p != Spacing::Attached) && token.spacing == Spacing::Spaced {
+            result.push(' ');
+        }
+        result.push_str(&token.text);
+        previous = Some(token.spacing);
+    }
+    result
+}
+
+/// Lowercase words, without the punctuation whisper adds to prose
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .map(|word| word.trim_matches(|c: char| matches!(c, '.' | ',' | '?' | '!' | ';' | ':')))
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Number of words and value of the phrase the words start with
+fn match_phrase<T: Copy>(words: &[String], phrases: &[(&str, T)]) -> Option<(usize, T)> {
+    phrases.iter().find_map(|(phrase, value)| {
+        let len = starts_with(words, phrase)?;
+        Some((len, *value))
+    })
+}
+
+/// Number of words, text and spacing of the symbol the words start with
+fn match_symbol(words: &[String]) -> Option<(usize, &'static str, Spacing)> {
+    SYMBOLS.iter().find_map(|(phrase, symbol, spacing)| {
+        let len = starts_with(words, phrase)?;
+        Some((len, *symbol, *spacing))
+    })
+}
+
+/// Number of words in the phrase, if the words start with it
+fn starts_with(words: &[String], phrase: &str) -> Option<usize> {
+    let phrase: Vec<&str> = phrase.split(' ').collect();
+    let matches =
+        words.len() >= phrase.len() && words.iter().zip(&phrase).all(|(word, p)| word == p);
+    matches.then_some(phrase.len())
+}
+
+/// Join words into one identifier
+fn identifier(words: &[String], case: Case) -> String {
+    let capitalized = |word: &String| -> String {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    };
+    match case {
+        Case::Snake => words.join("_"),
+        Case::Kebab => words.join("-"),
+        Case::Constant => words.join("_").to_uppercase(),
+        Case::Pascal => words.iter().map(capitalized).collect(),
+        Case::Camel => {
+            let mut result = words[0].clone();
+            for word in &words[1..] {
+                result.push_str(&capitalized(word));
+            }
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cases() {
+        assert_eq!(format("snake case user id"), "user_id");
+        assert_eq!(format("Camel case get user name."), "getUserName");
+        assert_eq!(format("pascal case http client"), "HttpClient");
+        assert_eq!(format("kebab case main menu"), "main-menu");
+        assert_eq!(format("constant case max value"), "MAX_VALUE");
+        assert_eq!(format("screaming snake case max value"), "MAX_VALUE");
+        assert_eq!(format("snake case"), "");
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(
+            format("snake case user id equals camel case get user name"),
+            "user_id = getUserName"
+        );
+        assert_eq!(format("x equals equals 5"), "x == 5");
+        assert_eq!(format("if a not equals b and and c"), "if a != b && c");
+        assert_eq!(format("count plus equals 1"), "count += 1");
+        assert_eq!(
+            format("fn main open paren close paren arrow result open brace"),
+            "fn main() -> result {"
+        );
+    }
+
+    #[test]
+    fn test_symbols() {
+        assert_eq!(
+            format("self dot snake case user id dot clone open paren close paren semicolon"),
+            "self.user_id.clone();"
+        );
+        assert_eq!(
+            format("Print open paren a, comma b close paren."),
+            "print(a, b)"
+        );
+        assert_eq!(format("std double colon env"), "std::env");
+        assert_eq!(format("items open bracket 0 close bracket"), "items[0]");
+    }
+}
@@ -0,0 +1,270 @@
+//! Hallucination filter
+//!
+//! Whisper tends to invent text when it only hears silence or breath noise:
+//! sound annotations ("[BLANK_AUDIO]", "(music)"), video outros ("Thanks for
+//! watching!") and loops of the same phrase. This filter strips annotations,
+//! drops sentences that match a blacklist and collapses repeat loops.
+
+use regex::Regex;
+use std::ops::Range;
+
+/// Sound annotations whisper emits in brackets, parentheses or asterisks
+const ANNOTATIONS: &str = "blank_audio|blank audio|silence|music|applause|laughter|laughs|\
+     laughing|sighs|sigh|coughs|coughing|cough|breathing|breath|noise|inaudible|clapping|\
+     static|wind|typing|clicking|no speech|sound";
+
+/// Sentences dropped wherever they appear (compared without case and punctuation)
+const DEFAULT_PHRASES: &[&str] = &[
+    "thanks for watching",
+    "thank you for watching",
+    "thank you so much for watching",
+    "thanks for watching and see you next time",
+    "please subscribe",
+    "please like and subscribe",
+    "dont forget to like and subscribe",
+    "subtitles by the amaraorg community",
+    "transcribed by esotranscribecom",
+];
+
+/// Texts dropped only when they are the whole transcription
+const WHOLE_TEXT_PHRASES: &[&str] = &["you"];
+
+/// Minimum number of consecutive copies for a phrase to count as a loop
+const MIN_REPEATS: usize = 3;
+
+/// Strips whisper hallucinations from transcribed text
+pub struct HallucinationFilter {
+    annotations: Regex,
+    phrases: Vec<String>,
+}
+
+impl HallucinationFilter {
+    /// Create a filter from the built-in blacklist plus `extra` phrases
+    pub fn new(extra: &[String]) -> Self {
+        let annotations = Regex::new(&format!(
+            r"(?i)[\[(*]\s*(?:[a-z]+ )?(?:{})\s*[\])*]|[♪♫]+",
+            ANNOTATIONS
+        ))
+        .expect("annotation pattern is valid");
+
+        let phrases = DEFAULT_PHRASES
+            .iter()
+            .map(|p| p.to_string())
+            .chain(extra.iter().map(|p| normalize(p)))
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        Self {
+            annotations,
+            phrases,
+        }
+    }
+
+    /// Apply the filter, returning an empty string if nothing real is left
+    ///
+    /// Text the filter doesn't remove keeps its spacing and line breaks.
+    pub fn apply(&self, text: &str) -> String {
+        let annotations: Vec<_> = self
+            .annotations
+            .find_iter(text)
+            .map(|m| m.range())
+            .collect();
+        let stripped = remove_spans(text, &annotations);
+
+        let blacklisted: Vec<_> = split_sentences(&stripped)
+            .into_iter()
+            .filter(|s| self.phrases.contains(&normalize(&stripped[s.clone()])))
+            .collect();
+        let kept = remove_spans(&stripped, &blacklisted);
+        let result = collapse_repeats(&kept);
+
+        if result.trim().is_empty() || WHOLE_TEXT_PHRASES.contains(&normalize(&result).as_str()) {
+            return String::new();
+        }
+        result
+    }
+}
+
+/// Lowercase and keep only letters, digits and single spaces
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Split after sentence-ending punctuation, keeping the punctuation
+///
+/// Returns the range of each sentence without surrounding whitespace.
+fn split_sentences(text: &str) -> Vec<Range<usize>> {
+    let trimmed = |range: Range<usize>| {
+        let sentence = &text[range.clone()];
+        let start = range.start + (sentence.len() - sentence.trim_start().len());
+        start..start + sentence.trim().len()
+    };
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next_is_boundary = chars.peek().is_none_or(|(_, n)| n.is_whitespace());
+        if matches!(c, '.' | '!' | '?') && next_is_boundary {
+            let end = i + c.len_utf8();
+            sentences.push(trimmed(start..end));
+            start = end;
+        }
+    }
+    sentences.push(trimmed(start..text.len()));
+    sentences.retain(|s| !s.is_empty());
+    sentences
+}
+
+/// Remove the (sorted, disjoint) `spans` from `text` with the gap each one
+/// leaves, touching nothing else
+fn remove_spans(text: &str, spans: &[Range<usize>]) -> String {
+    if spans.is_empty() {
+        return text.to_string();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut rest = 0;
+    for span in spans {
+        result.push_str(&text[rest..span.start]);
+        let mut after = &text[span.end..];
+        let line = result.rsplit('\n').next().unwrap_or_default();
+        if line.trim().is_empty() {
+            // Nothing before it on the line: drop the space after it
+            after = after.trim_start_matches([' ', '\t']);
+        } else if after.is_empty() || after.starts_with(char::is_whitespace) {
+            // "Hello [silence] world" → "Hello world"
+            result.truncate(result.trim_end_matches([' ', '\t']).len());
+        }
+        rest = text.len() - after.len();
+    }
+    result.push_str(&text[rest..]);
+    result
+}
+
+/// Collapse a phrase (two or more words) repeated back to back into one copy
+///
+/// The words that are kept keep their separators; text without a loop is
+/// returned as it is.
+fn collapse_repeats(text: &str) -> String {
+    // Each word with the whitespace that follows it
+    let mut words: Vec<&str> = Vec::new();
+    let mut first = text.len();
+    let mut start = None;
+    let mut after_space = true;
+    for (i, c) in text.char_indices() {
+        if !c.is_whitespace() && after_space {
+            match start {
+                Some(start) => words.push(&text[start..i]),
+                None => first = i,
+            }
+            start = Some(i);
+        }
+        after_space = c.is_whitespace();
+    }
+    if let Some(start) = start {
+        words.push(&text[start..]);
+    }
+    let mut changed = false;
+    let mut n = words.len() / MIN_REPEATS;
+
+    while n >= 2 {
+        let keys: Vec<String> = words.iter().map(|w| normalize(w)).collect();
+        let mut i = 0;
+        let mut collapsed = false;
+        while i + n * MIN_REPEATS <= words.len() {
+            let mut copies = 1;
+            while i + n * (copies + 1) <= words.len()
+                && keys[i..i + n] == keys[i + n * copies..i + n * (copies + 1)]
+            {
+                copies += 1;
+            }
+            if copies >= MIN_REPEATS {
+                // Keep the last copy, which usually carries the final punctuation
+                words.drain(i..i + n * (copies - 1));
+                collapsed = true;
+                changed = true;
+                break;
+            }
+            i += 1;
+        }
+        if !collapsed {
+            n -= 1;
+        }
+    }
+
+    if !changed {
+        return text.to_string();
+    }
+    let mut result = text[..first].to_string();
+    result.extend(words);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter() -> HallucinationFilter {
+        HallucinationFilter::new(&["Bye bye!".to_string()])
+    }
+
+    #[test]
+    fn test_annotations() {
+        let f = filter();
+        assert_eq!(f.apply("[BLANK_AUDIO]"), "");
+        assert_eq!(f.apply(" (upbeat music) "), "");
+        assert_eq!(f.apply("Hello [ Silence ] world *sighs*"), "Hello world");
+        assert_eq!(f.apply("♪♪ la la"), "la la");
+        assert_eq!(f.apply("call (see above)"), "call (see above)");
+    }
+
+    #[test]
+    fn test_blacklisted_sentences() {
+        let f = filter();
+        assert_eq!(f.apply("Thanks for watching!"), "");
+        assert_eq!(
+            f.apply("Send the report. Thank you for watching."),
+            "Send the report."
+        );
+        assert_eq!(f.apply("bye bye"), "");
+        assert_eq!(f.apply(" you"), "");
+        assert_eq!(f.apply("Thank you."), "Thank you.");
+        assert_eq!(f.apply("I'll see you"), "I'll see you");
+    }
+
+    #[test]
+    fn test_repeat_loops() {
+        let f = filter();
+        assert_eq!(
+            f.apply("increase the limit increase the limit increase the limit"),
+            "increase the limit"
+        );
+        assert_eq!(
+            f.apply("Okay. I'm going home. I'm going home. I'm going home."),
+            "Okay. I'm going home."
+        );
+        assert_eq!(f.apply("no no no no"), "no no no no");
+        assert_eq!(f.apply("very very good"), "very very good");
+        assert_eq!(
+            f.apply("go on\ngo on\ngo on\n\nthen stop"),
+            "go on\n\nthen stop"
+        );
+    }
+
+    #[test]
+    fn test_clean_text_unchanged() {
+        let f = filter();
+        for text in ["First line.\n\n  Indented  line ", "Done.\nNext one."] {
+            assert_eq!(f.apply(text), text);
+        }
+        assert_eq!(
+            f.apply("Done. [BLANK_AUDIO]\nNext one."),
+            "Done.\nNext one."
+        );
+    }
+}
@@ -1,19 +1,73 @@
 //! Text processing module
 //!
 //! Provides post-transcription text transformations including:
+//! - Spoken number conversion (e.g., "twenty three" → "23")
 //! - Spoken punctuation conversion (e.g., "period" → ".")
+//! - Punctuation and capitalization restoration for engines that output
+//!   neither ("can you send it" → "Can you send it?")
 //! - Custom word replacements
-
-use crate::config::TextConfig;
+//! - Sentence casing (capitalize, lowercase, continuation) and trailing
+//!   punctuation/space
+//! - Hallucination filtering ("[BLANK_AUDIO]", "Thanks for watching!", loops)
+//! - Profanity masking (applied last, after any post-processing command)
+//! - Regex `[[postprocess.rules]]` (applied by the daemon before profanity
+//!   masking, since rules may depend on the focused app)
+//! - Voice-triggered `[[snippets]]` (checked by the daemon first; a matching
+//!   utterance is replaced and skips all other processing)
+//! - Spell mode ("alpha bravo seven" → "ab7"), also checked by the daemon
+//!   first and output as is
+//! - Code dictation ("snake case user id" → "user_id"), replacing spoken
+//!   punctuation and sentence casing when enabled
+
+pub mod code;
+pub mod hallucination;
+pub mod numbers;
+pub mod profanity;
+pub mod punctuation;
+pub mod rules;
+pub mod snippets;
+pub mod spell;
+
+use crate::config::{Profile, TextConfig};
+use hallucination::HallucinationFilter;
+use numbers::{Locale, NumberLanguage};
+use profanity::ProfanityFilter;
 use regex::Regex;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Text processor that applies transformations to transcribed text
 pub struct TextProcessor {
+    /// Whether spoken number conversion is enabled
+    spoken_numbers: bool,
+    /// Language of spoken numbers
+    number_language: NumberLanguage,
+    /// Locale to write numbers, dates and units for
+    locale: Option<Locale>,
     /// Whether spoken punctuation is enabled
     spoken_punctuation: bool,
+    /// Whether to restore punctuation of unpunctuated text
+    restore_punctuation: bool,
+    /// Format as code instead of prose
+    code_mode: bool,
     /// Custom word replacements (lowercase key → replacement value)
     replacements: HashMap<String, String>,
+    /// Capitalize the first letter
+    capitalize: bool,
+    /// Lowercase everything
+    lowercase: bool,
+    /// Don't capitalize when continuing the previous sentence
+    continuation: bool,
+    /// Punctuation to add when the text doesn't end with any
+    trailing_punctuation: Option<String>,
+    /// Add a space after the text
+    trailing_space: bool,
+    /// Whether the last processed text ended a sentence (for continuation)
+    sentence_ended: AtomicBool,
+    /// Profanity filter (None when disabled)
+    profanity: Option<ProfanityFilter>,
+    /// Hallucination filter (None when disabled)
+    hallucinations: Option<HallucinationFilter>,
 }
 
 impl TextProcessor {
@@ -26,27 +80,184 @@ impl TextProcessor {
             .map(|(k, v)| (k.to_lowercase(), v.clone()))
             .collect();
 
+        let locale = config.locale.as_deref().and_then(|locale| {
+            locale
+                .parse::<Locale>()
+                .map_err(|e| tracing::warn!("{}, numbers are written as digits only", e))
+                .ok()
+        });
+        let number_language = match config.number_language.as_deref() {
+            Some(lang) => lang.parse().unwrap_or_else(|e| {
+                tracing::warn!("{}, using English", e);
+                NumberLanguage::English
+            }),
+            None => locale.and_then(|l| l.language).unwrap_or_default(),
+        };
+
         Self {
+            spoken_numbers: config.spoken_numbers,
+            number_language,
+            locale,
             spoken_punctuation: config.spoken_punctuation,
+            restore_punctuation: config.restore_punctuation,
+            code_mode: config.code_mode,
             replacements,
+            capitalize: config.capitalize,
+            lowercase: config.lowercase,
+            continuation: config.continuation,
+            trailing_punctuation: config.trailing_punctuation.clone(),
+            trailing_space: config.trailing_space,
+            sentence_ended: AtomicBool::new(true),
+            profanity: ProfanityFilter::new(
+                config.profanity_filter,
+                &config.profanity_words,
+                &config.profanity_allow,
+            ),
+            hallucinations: config
+                .hallucination_filter
+                .then(|| HallucinationFilter::new(&config.hallucination_phrases)),
+        }
+    }
+
+    /// Mask or drop profanity, if the filter is enabled
+    ///
+    /// Kept separate from [`process`](Self::process) so it can run on the
+    /// final text, after any post-processing command.
+    pub fn filter_profanity(&self, text: &str) -> String {
+        match self.profanity {
+            Some(ref filter) => filter.apply(text),
+            None => text.to_string(),
         }
     }
 
+    /// Strip whisper hallucinations, if the filter is enabled
+    ///
+    /// Runs on the raw transcription, before [`process`](Self::process).
+    /// Returns an empty string when nothing but hallucinations was heard.
+    pub fn filter_hallucinations(&self, text: &str) -> String {
+        match self.hallucinations {
+            Some(ref filter) => filter.apply(text),
+            None => text.to_string(),
+        }
+    }
+
+    /// Forget the previous text, so the next one starts a new sentence
+    ///
+    /// Called when the last output was undone.
+    pub fn reset_continuation(&self) {
+        self.sentence_ended.store(true, Ordering::Relaxed);
+    }
+
     /// Process text by applying all enabled transformations
     pub fn process(&self, text: &str) -> String {
+        self.process_with_profile(text, None)
+    }
+
+    /// Whether the last processed text ended a sentence (for continuation)
+    pub fn sentence_ended(&self) -> bool {
+        self.sentence_ended.load(Ordering::Relaxed)
+    }
+
+    /// Process text, letting the active profile override individual steps
+    pub fn process_with_profile(&self, text: &str, profile: Option<&Profile>) -> String {
+        let (result, ended) = self.process_after(text, profile, self.sentence_ended());
+        if let Some(ended) = ended {
+            self.sentence_ended.store(ended, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Process text as if the previous text left `sentence_ended`, without
+    /// changing the continuation state
+    ///
+    /// For a second transcription of the same dictation, which should be
+    /// cased like the first.
+    pub fn process_with_state(
+        &self,
+        text: &str,
+        profile: Option<&Profile>,
+        sentence_ended: bool,
+    ) -> String {
+        self.process_after(text, profile, sentence_ended).0
+    }
+
+    /// Process text following one that left `sentence_ended`, returning
+    /// whether the result ends a sentence (None for no prose)
+    fn process_after(
+        &self,
+        text: &str,
+        profile: Option<&Profile>,
+        sentence_ended: bool,
+    ) -> (String, Option<bool>) {
         let mut result = text.to_string();
 
-        // Apply spoken punctuation first (so user replacements can override if needed)
+        // Convert numbers before punctuation, which would split number phrases
+        let spoken_numbers = profile
+            .and_then(|p| p.spoken_numbers)
+            .unwrap_or(self.spoken_numbers);
+        if spoken_numbers {
+            result = numbers::convert_numbers(&result, self.number_language, self.locale.as_ref());
+        }
+
+        // Code has its own symbols and no sentences: skip spoken punctuation,
+        // casing and trailing punctuation/space
+        let code_mode = profile.and_then(|p| p.code_mode).unwrap_or(self.code_mode);
+        if code_mode {
+            result = code::format(&result);
+            if !self.replacements.is_empty() {
+                result = self.apply_replacements(&result);
+            }
+            return (result, None);
+        }
+
+        // Apply spoken punctuation before replacements (so user replacements can override if needed)
         if self.spoken_punctuation {
             result = self.apply_spoken_punctuation(&result);
         }
 
+        // After spoken punctuation, so dictated punctuation is kept as is
+        let restore_punctuation = profile
+            .and_then(|p| p.restore_punctuation)
+            .unwrap_or(self.restore_punctuation);
+        if restore_punctuation {
+            result = punctuation::restore(&result);
+        }
+
+        if self.lowercase {
+            result = result.to_lowercase();
+        }
+
         // Apply custom replacements
         if !self.replacements.is_empty() {
             result = self.apply_replacements(&result);
         }
 
-        result
+        if result.trim().is_empty() {
+            return (result, None);
+        }
+
+        // Sentence casing: continuing a sentence wins over capitalize
+        let continuing = self.continuation && !sentence_ended;
+        if continuing {
+            result = decapitalize_first_word(&result);
+        } else if self.capitalize && !self.lowercase {
+            result = capitalize_first_letter(&result);
+        }
+
+        if let Some(ref punct) = self.trailing_punctuation {
+            if !ends_with_punctuation(&result) {
+                let trimmed_len = result.trim_end().len();
+                result.insert_str(trimmed_len, punct);
+            }
+        }
+
+        let ended = ends_sentence(&result);
+
+        if self.trailing_space && !result.ends_with(char::is_whitespace) {
+            result.push(' ');
+        }
+
+        (result, Some(ended))
     }
 
     /// Apply spoken punctuation conversions
@@ -138,6 +349,66 @@ fn replace_phrase_case_insensitive(text: &str, from: &str, to: &str) -> String {
     }
 }
 
+/// Uppercase the first letter of the text
+fn capitalize_first_letter(text: &str) -> String {
+    match text.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((i, c)) if c.is_lowercase() => {
+            let mut result = String::with_capacity(text.len());
+            result.push_str(&text[..i]);
+            result.extend(c.to_uppercase());
+            result.push_str(&text[i + c.len_utf8()..]);
+            result
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Lowercase the first letter of the text, unless the first word is "I"
+/// (or a contraction of it) or looks like an acronym
+fn decapitalize_first_word(text: &str) -> String {
+    let Some(word) = text.split_whitespace().next() else {
+        return text.to_string();
+    };
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let is_i = word == "I" || word.starts_with("I'") || word.starts_with("I\u{2019}");
+    let is_acronym = letters.len() > 1 && letters.iter().all(|c| c.is_uppercase());
+    if is_i || is_acronym {
+        return text.to_string();
+    }
+
+    match text.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((i, c)) if c.is_uppercase() => {
+            let mut result = String::with_capacity(text.len());
+            result.push_str(&text[..i]);
+            result.extend(c.to_lowercase());
+            result.push_str(&text[i + c.len_utf8()..]);
+            result
+        }
+        _ => text.to_string(),
+    }
+}
+
+/// Whether the text ends with punctuation (ignoring trailing whitespace)
+fn ends_with_punctuation(text: &str) -> bool {
+    text.trim_end()
+        .chars()
+        .last()
+        .map(|c| c.is_ascii_punctuation() || matches!(c, '…' | '。' | '？' | '！'))
+        .unwrap_or(false)
+}
+
+/// Whether the text ends a sentence, so the next text should be capitalized
+fn ends_sentence(text: &str) -> bool {
+    if text.ends_with('\n') {
+        return true;
+    }
+    text.trim_end()
+        .chars()
+        .last()
+        .map(|c| matches!(c, '.' | '!' | '?' | '…' | '。' | '？' | '！'))
+        .unwrap_or(true)
+}
+
 /// Clean up spacing around punctuation marks
 fn clean_punctuation_spacing(text: &str) -> String {
     let mut result = text.to_string();
@@ -187,6 +458,7 @@ mod tests {
                 .iter()
                 .map(|(k, v)| (k.to_string(), v.to_string()))
                 .collect(),
+            ..Default::default()
         }
     }
 
@@ -282,4 +554,215 @@ mod tests {
         );
         assert_eq!(processor.process("col one tab col two"), "col one\tcol two");
     }
+
+    #[test]
+    fn test_capitalize() {
+        let config = TextConfig {
+            capitalize: true,
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+
+        assert_eq!(processor.process("hello world"), "Hello world");
+        assert_eq!(processor.process("\"quoted\" text"), "\"Quoted\" text");
+        assert_eq!(processor.process("élan"), "Élan");
+    }
+
+    #[test]
+    fn test_lowercase_keeps_replacements() {
+        let config = TextConfig {
+            lowercase: true,
+            capitalize: true,
+            replacements: [("rust".to_string(), "Rust".to_string())].into(),
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+
+        assert_eq!(processor.process("I Like RUST."), "i like Rust.");
+    }
+
+    #[test]
+    fn test_trailing_punctuation_and_space() {
+        let config = TextConfig {
+            trailing_punctuation: Some(".".to_string()),
+            trailing_space: true,
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+
+        assert_eq!(processor.process("Sounds good"), "Sounds good. ");
+        assert_eq!(processor.process("Really?"), "Really? ");
+        assert_eq!(processor.process("Trailing space "), "Trailing space. ");
+    }
+
+    #[test]
+    fn test_continuation() {
+        let config = TextConfig {
+            capitalize: true,
+            continuation: true,
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+
+        assert_eq!(processor.process("so I was thinking"), "So I was thinking");
+        // Previous text didn't end a sentence, so this continues it
+        assert_eq!(processor.process("That we could go."), "that we could go.");
+        assert_eq!(processor.process("next sentence"), "Next sentence");
+        assert_eq!(processor.process("I agree"), "I agree");
+        assert_eq!(processor.process("NASA called"), "NASA called");
+
+        processor.reset_continuation();
+        assert_eq!(processor.process("fresh start"), "Fresh start");
+    }
+
+    #[test]
+    fn test_process_with_state() {
+        let config = TextConfig {
+            capitalize: true,
+            continuation: true,
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+        assert_eq!(processor.process("so I was thinking"), "So I was thinking");
+
+        // Cased like a draft that followed a finished sentence, or not
+        let ended = processor.sentence_ended();
+        assert!(!ended);
+        assert_eq!(
+            processor.process_with_state("That we could go.", None, ended),
+            "that we could go."
+        );
+        assert_eq!(
+            processor.process_with_state("that we could go", None, true),
+            "That we could go"
+        );
+        // The state is left alone
+        assert!(!processor.sentence_ended());
+        assert_eq!(processor.process("That we go."), "that we go.");
+        assert!(processor.sentence_ended());
+    }
+
+    #[test]
+    fn test_casing_disabled_by_default() {
+        let processor = TextProcessor::new(&TextConfig::default());
+        assert_eq!(processor.process("hello world"), "hello world");
+    }
+
+    #[test]
+    fn test_spoken_numbers_with_profile_override() {
+        let config = TextConfig {
+            spoken_numbers: true,
+            spoken_punctuation: true,
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+        assert_eq!(
+            processor.process("call me at three p m period"),
+            "call me at 3 PM."
+        );
+
+        let profile = Profile {
+            spoken_numbers: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            processor.process_with_profile("twenty three", Some(&profile)),
+            "twenty three"
+        );
+    }
+
+    #[test]
+    fn test_code_mode_with_profile() {
+        let config = TextConfig {
+            spoken_numbers: true,
+            capitalize: true,
+            trailing_punctuation: Some(".".to_string()),
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+        let profile = Profile {
+            code_mode: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(
+            processor.process_with_profile("Snake case retry count equals three.", Some(&profile)),
+            "retry_count = 3"
+        );
+        assert_eq!(processor.process("snake case"), "Snake case.");
+    }
+
+    #[test]
+    fn test_restore_punctuation() {
+        let config = TextConfig {
+            restore_punctuation: true,
+            spoken_punctuation: true,
+            continuation: true,
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+        assert_eq!(
+            processor.process("okay can you send it"),
+            "Okay, can you send it?"
+        );
+        // Dictated punctuation is kept
+        assert_eq!(
+            processor.process("is it done exclamation mark"),
+            "is it done!"
+        );
+
+        let profile = Profile {
+            restore_punctuation: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(
+            processor.process_with_profile("i sent it", Some(&profile)),
+            "i sent it"
+        );
+    }
+
+    #[test]
+    fn test_spoken_numbers_german() {
+        let config = TextConfig {
+            spoken_numbers: true,
+            number_language: Some("de".to_string()),
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+        assert_eq!(processor.process("dreiundzwanzig Grad"), "23 Grad");
+    }
+
+    #[test]
+    fn test_spoken_numbers_locale() {
+        // The locale's language is used for the number words too
+        let config = TextConfig {
+            spoken_numbers: true,
+            locale: Some("de-DE".to_string()),
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+        assert_eq!(
+            processor.process("zwölftausend Komma fünf Liter"),
+            "12.000,5 l"
+        );
+    }
+
+    #[test]
+    fn test_filter_hallucinations() {
+        let processor = TextProcessor::new(&TextConfig::default());
+        assert_eq!(
+            processor.filter_hallucinations("[BLANK_AUDIO]"),
+            "[BLANK_AUDIO]"
+        );
+
+        let config = TextConfig {
+            hallucination_filter: true,
+            ..Default::default()
+        };
+        let processor = TextProcessor::new(&config);
+        assert_eq!(processor.filter_hallucinations("[BLANK_AUDIO]"), "");
+        assert_eq!(
+            processor.filter_hallucinations("Hello. Thanks for watching!"),
+            "Hello."
+        );
+    }
 }
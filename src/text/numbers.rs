@@ -0,0 +1,1302 @@
+//! Spoken number normalization
+//!
+//! Converts number words to digits: "twenty three" → "23", "twenty first"
+//! → "21st", "three point five" → "3.5", "three thirty p m" → "3:30 PM".
+//! German compounds are supported too: "dreiundzwanzig" → "23",
+//! "drei Komma fünf" → "3,5", "drei Uhr dreißig" → "3:30 Uhr".
+//!
+//! Words that are usually not numbers on their own ("one", "first",
+//! "second", German "ein"/"eine") are only converted as part of a larger
+//! number.
+//!
+//! With a [`Locale`], numbers are written the way it does: "12,345.5" in
+//! English, "12.345,5" in German. Spoken dates with a year become the
+//! locale's short date ("March fifth twenty twenty four" → "3/5/2024" or
+//! "05.03.2024"), and common units are abbreviated ("five kilometers" →
+//! "5 km", "zwanzig Prozent" → "20 %").
+
+use chrono::NaiveDate;
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Language used to recognize number words
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberLanguage {
+    #[default]
+    English,
+    German,
+}
+
+impl std::str::FromStr for NumberLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "en" | "english" => Ok(NumberLanguage::English),
+            "de" | "german" | "deutsch" => Ok(NumberLanguage::German),
+            _ => Err(format!(
+                "Unsupported number language '{}'. Valid options: en, de",
+                s
+            )),
+        }
+    }
+}
+
+/// How a locale writes numbers, dates and units (`[text] locale`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    /// Language of the locale, if its number words are supported
+    pub language: Option<NumberLanguage>,
+    /// Decimal separator
+    decimal: char,
+    /// Separator between groups of three digits, in numbers of five digits
+    /// or more
+    thousands: &'static str,
+    /// Day before month in dates
+    day_first: bool,
+    /// Separator between day, month and year
+    date_separator: char,
+    /// Zero-pad day and month ("05.03.2024")
+    date_padded: bool,
+    /// Space between a number and "%" or "°C"
+    symbol_space: bool,
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    /// Parse a locale name such as "de", "en-GB" or "fr_FR.UTF-8"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.split(['.', '@']).next().unwrap_or_default();
+        let (language, region) = match name.split_once(['-', '_']) {
+            Some((language, region)) => (language, Some(region.to_uppercase())),
+            None => (name, None),
+        };
+        let language = language.to_lowercase();
+
+        let english = Locale {
+            language: Some(NumberLanguage::English),
+            decimal: '.',
+            thousands: ",",
+            day_first: false,
+            date_separator: '/',
+            date_padded: false,
+            symbol_space: false,
+        };
+        let european = Locale {
+            language: None,
+            decimal: ',',
+            thousands: ".",
+            day_first: true,
+            date_separator: '/',
+            date_padded: true,
+            symbol_space: false,
+        };
+        Ok(match (language.as_str(), region.as_deref()) {
+            ("en", None | Some("US")) => english,
+            ("en", _) => Locale {
+                day_first: true,
+                date_padded: true,
+                ..english
+            },
+            ("de", region) => Locale {
+                language: Some(NumberLanguage::German),
+                thousands: if matches!(region, Some("CH" | "LI")) {
+                    "’"
+                } else {
+                    "."
+                },
+                decimal: if matches!(region, Some("CH" | "LI")) {
+                    '.'
+                } else {
+                    ','
+                },
+                date_separator: '.',
+                symbol_space: true,
+                ..european
+            },
+            ("fr", _) => Locale {
+                thousands: "\u{202f}",
+                symbol_space: true,
+                ..european
+            },
+            ("es", _) => Locale {
+                symbol_space: true,
+                ..european
+            },
+            ("it" | "pt", _) => european,
+            ("nl", _) => Locale {
+                date_separator: '-',
+                date_padded: false,
+                ..european
+            },
+            _ => {
+                return Err(format!(
+                "Unsupported locale '{}'. Valid options: en, en-GB, de, de-CH, fr, es, it, nl, pt",
+                s
+            ))
+            }
+        })
+    }
+}
+
+impl Locale {
+    /// `value`, followed by the `decimals` digits if there are any
+    fn number(&self, value: u64, decimals: &str) -> String {
+        let digits = value.to_string();
+        let mut number = String::with_capacity(digits.len() + decimals.len() + 4);
+        for (i, c) in digits.chars().enumerate() {
+            if digits.len() >= 5 && i > 0 && (digits.len() - i).is_multiple_of(3) {
+                number.push_str(self.thousands);
+            }
+            number.push(c);
+        }
+        if !decimals.is_empty() {
+            number.push(self.decimal);
+            number.push_str(decimals);
+        }
+        number
+    }
+
+    fn date(&self, date: NaiveDate) -> String {
+        use chrono::Datelike;
+        let (first, second) = if self.day_first {
+            (date.day(), date.month())
+        } else {
+            (date.month(), date.day())
+        };
+        let sep = self.date_separator;
+        if self.date_padded {
+            format!("{:02}{}{:02}{}{}", first, sep, second, sep, date.year())
+        } else {
+            format!("{}{}{}{}{}", first, sep, second, sep, date.year())
+        }
+    }
+
+    /// `number` followed by the abbreviation of a unit
+    fn with_unit(&self, number: &str, unit: &str) -> String {
+        if unit.starts_with(['%', '°']) && !self.symbol_space {
+            format!("{}{}", number, unit)
+        } else {
+            format!("{} {}", number, unit)
+        }
+    }
+}
+
+/// `value` with `decimals`, in `locale` or as digits with `separator`
+fn format_number(value: u64, decimals: &str, separator: char, locale: Option<&Locale>) -> String {
+    match locale {
+        Some(locale) => locale.number(value, decimals),
+        None if decimals.is_empty() => value.to_string(),
+        None => format!("{}{}{}", value, separator, decimals),
+    }
+}
+
+/// Spoken units and their abbreviations; phrases before the words they
+/// start with. Each word may also take a plural "s" or "n".
+const EN_UNITS: &[(&str, &str)] = &[
+    ("kilometer per hour", "km/h"),
+    ("kilometre per hour", "km/h"),
+    ("mile per hour", "mph"),
+    ("degree celsius", "°C"),
+    ("degree fahrenheit", "°F"),
+    ("per cent", "%"),
+    ("percent", "%"),
+    ("kilometer", "km"),
+    ("kilometre", "km"),
+    ("centimeter", "cm"),
+    ("centimetre", "cm"),
+    ("millimeter", "mm"),
+    ("millimetre", "mm"),
+    ("meter", "m"),
+    ("metre", "m"),
+    ("mile", "mi"),
+    ("foot", "ft"),
+    ("feet", "ft"),
+    ("kilogram", "kg"),
+    ("milligram", "mg"),
+    ("gram", "g"),
+    ("milliliter", "mL"),
+    ("millilitre", "mL"),
+    ("liter", "L"),
+    ("litre", "L"),
+    ("kilobyte", "KB"),
+    ("megabyte", "MB"),
+    ("gigabyte", "GB"),
+    ("terabyte", "TB"),
+];
+
+const DE_UNITS: &[(&str, &str)] = &[
+    ("kilometer pro stunde", "km/h"),
+    ("stundenkilometer", "km/h"),
+    ("grad celsius", "°C"),
+    ("grad fahrenheit", "°F"),
+    ("prozent", "%"),
+    ("kilometer", "km"),
+    ("zentimeter", "cm"),
+    ("millimeter", "mm"),
+    ("meter", "m"),
+    ("kilogramm", "kg"),
+    ("kilo", "kg"),
+    ("milligramm", "mg"),
+    ("gramm", "g"),
+    ("milliliter", "ml"),
+    ("liter", "l"),
+    ("kilobyte", "KB"),
+    ("megabyte", "MB"),
+    ("gigabyte", "GB"),
+    ("terabyte", "TB"),
+];
+
+/// Match a spoken unit at `i`, returning (abbreviation, tokens)
+fn match_unit(
+    tokens: &[Token],
+    i: usize,
+    units: &[(&str, &'static str)],
+) -> Option<(&'static str, usize)> {
+    units.iter().find_map(|(spoken, unit)| {
+        let words: Vec<&str> = spoken.split(' ').collect();
+        let matches = words.iter().enumerate().all(|(n, word)| {
+            tokens.get(i + n).is_some_and(|t| {
+                t.lead.is_empty()
+                    && (n + 1 == words.len() || t.continues())
+                    && (t.core == *word || t.core.strip_suffix(['s', 'n']) == Some(word))
+            })
+        });
+        matches.then_some((*unit, words.len()))
+    })
+}
+
+/// `number` ending at token `next`, with the unit after it if there is one
+fn with_unit(
+    tokens: &[Token],
+    next: usize,
+    number: String,
+    consumed: usize,
+    locale: Option<&Locale>,
+    units: &[(&str, &'static str)],
+) -> (String, usize) {
+    let Some(locale) = locale else {
+        return (number, consumed);
+    };
+    if !tokens[next - 1].continues() {
+        return (number, consumed);
+    }
+    match match_unit(tokens, next, units) {
+        Some((unit, n)) => (locale.with_unit(&number, unit), consumed + n),
+        None => (number, consumed),
+    }
+}
+
+/// Month number of a month name
+fn month(word: &str, language: NumberLanguage) -> Option<u32> {
+    let months: [&[&str]; 12] = match language {
+        NumberLanguage::English => [
+            &["january"],
+            &["february"],
+            &["march"],
+            &["april"],
+            &["may"],
+            &["june"],
+            &["july"],
+            &["august"],
+            &["september"],
+            &["october"],
+            &["november"],
+            &["december"],
+        ],
+        NumberLanguage::German => [
+            &["januar", "jänner"],
+            &["februar"],
+            &["märz"],
+            &["april"],
+            &["mai"],
+            &["juni"],
+            &["juli"],
+            &["august"],
+            &["september"],
+            &["oktober"],
+            &["november"],
+            &["dezember"],
+        ],
+    };
+    months
+        .iter()
+        .position(|names| names.contains(&word))
+        .map(|i| i as u32 + 1)
+}
+
+/// Convert spoken numbers in `text` to digits, written as in `locale` if
+/// there is one
+pub fn convert_numbers(text: &str, language: NumberLanguage, locale: Option<&Locale>) -> String {
+    let text = match language {
+        // "twenty-three" → "twenty three" so both forms parse the same way
+        NumberLanguage::English => english_hyphen_regex()
+            .replace_all(text, "$1 $2")
+            .into_owned(),
+        NumberLanguage::German => text.to_string(),
+    };
+
+    let tokens = tokenize(&text);
+    let mut output = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let matched = match language {
+            NumberLanguage::English => match_english(&tokens, i, locale),
+            NumberLanguage::German => match_german(&tokens, i, locale),
+        };
+        match matched {
+            Some((replacement, consumed)) => {
+                let last = &tokens[i + consumed - 1];
+                output.push_str(tokens[i].lead);
+                output.push_str(&replacement);
+                output.push_str(last.trail);
+                output.push_str(last.space);
+                i += consumed;
+            }
+            None => {
+                let token = &tokens[i];
+                output.push_str(token.text);
+                output.push_str(token.space);
+                i += 1;
+            }
+        }
+    }
+    output
+}
+
+fn english_hyphen_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r"(?i)\b(twenty|thirty|forty|fifty|sixty|seventy|eighty|ninety)-(\w+)").unwrap()
+    })
+}
+
+/// A whitespace-separated word with its surrounding punctuation split off
+#[derive(Debug)]
+struct Token<'a> {
+    /// Original text of the word, including punctuation
+    text: &'a str,
+    /// Leading punctuation (e.g. an opening quote)
+    lead: &'a str,
+    /// Lowercased word without surrounding punctuation
+    core: String,
+    /// Trailing punctuation (e.g. a comma)
+    trail: &'a str,
+    /// Whitespace following the word
+    space: &'a str,
+}
+
+impl Token<'_> {
+    /// Whether a number may continue past this token
+    fn continues(&self) -> bool {
+        self.trail.is_empty()
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    // Keep leading whitespace as an empty token so it survives the rebuild
+    let word_start = rest
+        .find(|c: char| !c.is_whitespace())
+        .unwrap_or(rest.len());
+    if word_start > 0 {
+        tokens.push(Token {
+            text: "",
+            lead: "",
+            core: String::new(),
+            trail: "",
+            space: &rest[..word_start],
+        });
+        rest = &rest[word_start..];
+    }
+
+    while !rest.is_empty() {
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..word_end];
+        let after = &rest[word_end..];
+        let space_end = after
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(after.len());
+
+        let core_start = word.find(char::is_alphanumeric).unwrap_or(word.len());
+        let core_end = word
+            .rfind(char::is_alphanumeric)
+            .map(|i| i + word[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(core_start)
+            .max(core_start);
+
+        tokens.push(Token {
+            text: word,
+            lead: &word[..core_start],
+            core: word[core_start..core_end].to_lowercase(),
+            trail: &word[core_end..],
+            space: &after[..space_end],
+        });
+        rest = &after[space_end..];
+    }
+    tokens
+}
+
+// ---------------------------------------------------------------------------
+// English
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+}
+
+fn en_cardinal(word: &str) -> Option<(u64, Kind)> {
+    let value = match word {
+        "zero" => return Some((0, Kind::Unit)),
+        "one" => 1,
+        "two" => 2,
+        "three" => 3,
+        "four" => 4,
+        "five" => 5,
+        "six" => 6,
+        "seven" => 7,
+        "eight" => 8,
+        "nine" => 9,
+        "ten" => return Some((10, Kind::Teen)),
+        "eleven" => return Some((11, Kind::Teen)),
+        "twelve" => return Some((12, Kind::Teen)),
+        "thirteen" => return Some((13, Kind::Teen)),
+        "fourteen" => return Some((14, Kind::Teen)),
+        "fifteen" => return Some((15, Kind::Teen)),
+        "sixteen" => return Some((16, Kind::Teen)),
+        "seventeen" => return Some((17, Kind::Teen)),
+        "eighteen" => return Some((18, Kind::Teen)),
+        "nineteen" => return Some((19, Kind::Teen)),
+        "twenty" => return Some((20, Kind::Tens)),
+        "thirty" => return Some((30, Kind::Tens)),
+        "forty" => return Some((40, Kind::Tens)),
+        "fifty" => return Some((50, Kind::Tens)),
+        "sixty" => return Some((60, Kind::Tens)),
+        "seventy" => return Some((70, Kind::Tens)),
+        "eighty" => return Some((80, Kind::Tens)),
+        "ninety" => return Some((90, Kind::Tens)),
+        "hundred" => return Some((100, Kind::Hundred)),
+        "thousand" => return Some((1_000, Kind::Scale)),
+        "million" => return Some((1_000_000, Kind::Scale)),
+        "billion" => return Some((1_000_000_000, Kind::Scale)),
+        _ => return None,
+    };
+    Some((value, Kind::Unit))
+}
+
+fn en_ordinal(word: &str) -> Option<(u64, Kind)> {
+    let cardinal = match word {
+        "first" => "one",
+        "second" => "two",
+        "third" => "three",
+        "fifth" => "five",
+        "eighth" => "eight",
+        "ninth" => "nine",
+        "twelfth" => "twelve",
+        _ => {
+            if let Some(stem) = word.strip_suffix("ieth") {
+                return en_cardinal(&format!("{}y", stem));
+            }
+            let stem = word.strip_suffix("th")?;
+            return en_cardinal(stem).filter(|(v, _)| *v != 0);
+        }
+    };
+    en_cardinal(cardinal)
+}
+
+/// A parsed English number
+struct EnNumber {
+    value: u64,
+    consumed: usize,
+    ordinal: bool,
+    /// Plain two-digit number (no hundred/scale), e.g. for years like "nineteen ninety"
+    two_digit: bool,
+}
+
+/// Parse a cardinal or ordinal number starting at `start`
+fn parse_en_number(tokens: &[Token], start: usize) -> Option<EnNumber> {
+    let mut total: u64 = 0;
+    let mut current: u64 = 0;
+    let mut last: Option<Kind> = None;
+    let mut last_scale = u64::MAX;
+    let mut consumed = 0;
+    let mut ordinal = false;
+
+    let mut i = start;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        // A token with leading punctuation can only start a number
+        if i > start && !token.lead.is_empty() {
+            break;
+        }
+
+        // British "one hundred and five"
+        if token.core == "and"
+            && matches!(last, Some(Kind::Hundred | Kind::Scale))
+            && token.continues()
+            && tokens
+                .get(i + 1)
+                .and_then(|t| en_cardinal(&t.core))
+                .is_some_and(|(_, k)| matches!(k, Kind::Unit | Kind::Teen | Kind::Tens))
+        {
+            i += 1;
+            continue;
+        }
+
+        let (word, is_ordinal) = match en_cardinal(&token.core) {
+            Some(word) => (word, false),
+            None => match en_ordinal(&token.core) {
+                Some(word) => (word, true),
+                None => break,
+            },
+        };
+        let (value, kind) = word;
+
+        let allowed = match kind {
+            Kind::Unit if value == 0 => last.is_none(),
+            Kind::Unit => matches!(last, None | Some(Kind::Tens | Kind::Hundred | Kind::Scale)),
+            Kind::Teen | Kind::Tens => {
+                matches!(last, None | Some(Kind::Hundred | Kind::Scale))
+            }
+            Kind::Hundred => {
+                matches!(last, Some(Kind::Unit | Kind::Teen)) && !current.is_multiple_of(100)
+            }
+            Kind::Scale => last.is_some() && last != Some(Kind::Scale) && value < last_scale,
+        };
+        if !allowed {
+            break;
+        }
+
+        match kind {
+            Kind::Unit | Kind::Teen | Kind::Tens => current += value,
+            Kind::Hundred => current *= 100,
+            Kind::Scale => {
+                total += current * value;
+                current = 0;
+                last_scale = value;
+            }
+        }
+        last = Some(kind);
+        i += 1;
+        consumed = i - start;
+
+        if is_ordinal {
+            ordinal = true;
+            break;
+        }
+        if value == 0 || !token.continues() {
+            break;
+        }
+    }
+
+    if consumed == 0 {
+        return None;
+    }
+    let value = total + current;
+    Some(EnNumber {
+        value,
+        consumed,
+        ordinal,
+        two_digit: total == 0 && (10..100).contains(&value) && last != Some(Kind::Hundred),
+    })
+}
+
+fn en_ordinal_suffix(value: u64) -> &'static str {
+    match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    }
+}
+
+/// Single digit for decimals and times ("oh" reads as zero)
+fn en_digit(word: &str) -> Option<u64> {
+    match word {
+        "oh" | "o" => Some(0),
+        _ => en_cardinal(word)
+            .filter(|(v, k)| *k == Kind::Unit && *v < 10)
+            .map(|(v, _)| v),
+    }
+}
+
+/// Match "a m" / "p m" / "am" / "pm" / "a.m." at `i`, returning (marker, tokens)
+fn match_meridiem(tokens: &[Token], i: usize) -> Option<(&'static str, usize)> {
+    let token = tokens.get(i)?;
+    if !token.lead.is_empty() {
+        return None;
+    }
+    let marker = |c: &str| match c {
+        "a" => Some("AM"),
+        "p" => Some("PM"),
+        _ => None,
+    };
+    match token.core.as_str() {
+        "am" | "a.m" => Some(("AM", 1)),
+        "pm" | "p.m" => Some(("PM", 1)),
+        c => {
+            let m = marker(c)?;
+            let next = tokens.get(i + 1)?;
+            (token.continues() && next.lead.is_empty() && next.core == "m").then_some((m, 2))
+        }
+    }
+}
+
+/// The text of `tokens` as spoken, without the first one's leading and the
+/// last one's trailing punctuation, which the caller writes around it
+fn original_words(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    for (k, token) in tokens.iter().enumerate() {
+        let word = &token.text[token.lead.len()..];
+        if k + 1 == tokens.len() {
+            text.push_str(&word[..word.len() - token.trail.len()]);
+        } else {
+            text.push_str(word);
+            text.push_str(token.space);
+        }
+    }
+    text
+}
+
+/// Match a two-digit group such as "thirty", "oh five" or "ninety nine"
+fn match_two_digits(tokens: &[Token], i: usize) -> Option<(u64, usize)> {
+    let token = tokens.get(i)?;
+    if !token.lead.is_empty() {
+        return None;
+    }
+    if token.core == "oh" && token.continues() {
+        let digit = en_digit(&tokens.get(i + 1)?.core)?;
+        return Some((digit, 2));
+    }
+    let number = parse_en_number(tokens, i)?;
+    (number.two_digit && !number.ordinal).then_some((number.value, number.consumed))
+}
+
+/// Match a year at `i`, spoken or already in digits
+fn match_en_year(tokens: &[Token], i: usize) -> Option<(i32, usize)> {
+    let token = tokens.get(i).filter(|t| t.lead.is_empty())?;
+    let (year, consumed) = match token.core.parse::<i32>() {
+        Ok(year) => (year, 1),
+        Err(_) => {
+            let (year, consumed) = match_english(tokens, i, None)?;
+            (year.parse().ok()?, consumed)
+        }
+    };
+    (1000..=2999).contains(&year).then_some((year, consumed))
+}
+
+/// Match a date with a year at `i`: "march fifth twenty twenty four",
+/// "the fifth of march nineteen ninety"
+fn match_en_date(tokens: &[Token], i: usize) -> Option<(NaiveDate, usize)> {
+    // Words after the first can't have leading punctuation, and all but the
+    // last must run on into the next
+    let plain = |j: usize| j == i || tokens.get(j).is_some_and(|t| t.lead.is_empty());
+    let word = |j: usize, word: &str| {
+        plain(j)
+            && tokens
+                .get(j)
+                .is_some_and(|t| t.core == word && t.continues())
+    };
+    let day = |j: usize| {
+        let number = parse_en_number(tokens, j)
+            .filter(|n| plain(j) && n.ordinal && (1..=31).contains(&n.value))?;
+        Some((number.value as u32, j + number.consumed))
+    };
+
+    let (day, month, next) = match month(&tokens[i].core, NumberLanguage::English) {
+        Some(month) if tokens[i].continues() => {
+            let start = if word(i + 1, "the") { i + 2 } else { i + 1 };
+            let (day, next) = day(start)?;
+            (day, month, next)
+        }
+        Some(_) => return None,
+        None => {
+            let start = if word(i, "the") { i + 1 } else { i };
+            let (day, next) = day(start)?;
+            if !tokens[next - 1].continues() || !word(next, "of") || !plain(next + 1) {
+                return None;
+            }
+            let month = month(&tokens.get(next + 1)?.core, NumberLanguage::English)?;
+            (day, month, next + 2)
+        }
+    };
+    if !tokens[next - 1].continues() {
+        return None;
+    }
+    let (year, consumed) = match_en_year(tokens, next)?;
+    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+    Some((date, next + consumed - i))
+}
+
+fn match_english(tokens: &[Token], i: usize, locale: Option<&Locale>) -> Option<(String, usize)> {
+    if let Some(locale) = locale {
+        if let Some((date, consumed)) = match_en_date(tokens, i) {
+            return Some((locale.date(date), consumed));
+        }
+    }
+
+    let number = parse_en_number(tokens, i)?;
+    let last = &tokens[i + number.consumed - 1];
+
+    if number.ordinal {
+        // "first" and "second" alone are rarely meant as numbers
+        if number.consumed == 1 && number.value <= 2 {
+            return None;
+        }
+        return Some((
+            format!("{}{}", number.value, en_ordinal_suffix(number.value)),
+            number.consumed,
+        ));
+    }
+
+    if last.continues() {
+        let next = i + number.consumed;
+
+        // Times: "three p m", "three thirty p m", "eleven oh five a m"
+        if (1..=12).contains(&number.value) {
+            if let Some((marker, n)) = match_meridiem(tokens, next) {
+                return Some((format!("{} {}", number.value, marker), number.consumed + n));
+            }
+            if let Some((minutes, m)) = match_two_digits(tokens, next).filter(|&(m, _)| m < 60) {
+                if tokens[next + m - 1].continues() {
+                    if let Some((marker, n)) = match_meridiem(tokens, next + m) {
+                        return Some((
+                            format!("{}:{:02} {}", number.value, minutes, marker),
+                            number.consumed + m + n,
+                        ));
+                    }
+                }
+                // Without a.m. or p.m. only after "at": "meet at two thirty"
+                if i > 0 && tokens[i - 1].core == "at" && tokens[i - 1].continues() {
+                    return Some((
+                        format!("{}:{:02}", number.value, minutes),
+                        number.consumed + m,
+                    ));
+                }
+            }
+        }
+
+        // Decimals: "three point one four"
+        if tokens[next..]
+            .first()
+            .is_some_and(|t| t.core == "point" && t.lead.is_empty())
+        {
+            let mut digits = String::new();
+            let mut j = next + 1;
+            while let Some(token) = tokens.get(j) {
+                if !token.lead.is_empty() {
+                    break;
+                }
+                match en_digit(&token.core) {
+                    Some(d) if token.core != "o" => digits.push_str(&d.to_string()),
+                    _ => break,
+                }
+                j += 1;
+                if !token.continues() {
+                    break;
+                }
+            }
+            if !digits.is_empty() {
+                let decimal = format_number(number.value, &digits, '.', locale);
+                return Some(with_unit(tokens, j, decimal, j - i, locale, EN_UNITS));
+            }
+        }
+
+        // Years: "nineteen ninety nine", "twenty twenty four", "nineteen oh five"
+        if number.two_digit && number.value >= 11 {
+            if let Some((rest, m)) = match_two_digits(tokens, next) {
+                if rest >= 10 || tokens[next].core == "oh" {
+                    return Some((format!("{}{:02}", number.value, rest), number.consumed + m));
+                }
+            }
+        }
+
+        // Two numbers in a row that are neither a time nor a year ("page ten
+        // eleven") are ambiguous, so they stay words; longer lists convert
+        let second = tokens[next..]
+            .first()
+            .filter(|t| t.lead.is_empty())
+            .and_then(|_| parse_en_number(tokens, next))
+            .filter(|n| !n.ordinal);
+        if let Some(second) = second {
+            let end = next + second.consumed;
+            let after = tokens[end - 1].continues() && parse_en_number(tokens, end).is_some();
+            let before =
+                i > 0 && tokens[i - 1].continues() && parse_en_number(tokens, i - 1).is_some();
+            if !after && !before {
+                return Some((original_words(&tokens[i..end]), end - i));
+            }
+        }
+    }
+
+    // A lone "one" is usually a pronoun ("the one", "one of them"),
+    // unless it's part of a list of numbers ("one two three") or a quantity
+    if number.consumed == 1 && tokens[i].core == "one" {
+        let next_is_number = last.continues()
+            && tokens
+                .get(i + 1)
+                .is_some_and(|t| t.lead.is_empty() && en_cardinal(&t.core).is_some());
+        let unit_follows =
+            locale.is_some() && last.continues() && match_unit(tokens, i + 1, EN_UNITS).is_some();
+        if !next_is_number && !unit_follows {
+            return None;
+        }
+    }
+
+    let next = i + number.consumed;
+    let value = format_number(number.value, "", '.', locale);
+    Some(with_unit(
+        tokens,
+        next,
+        value,
+        number.consumed,
+        locale,
+        EN_UNITS,
+    ))
+}
+
+// ---------------------------------------------------------------------------
+// German
+// ---------------------------------------------------------------------------
+
+fn de_simple(word: &str) -> Option<u64> {
+    Some(match word {
+        "null" => 0,
+        "eins" => 1,
+        "zwei" => 2,
+        "drei" => 3,
+        "vier" => 4,
+        "fünf" => 5,
+        "sechs" => 6,
+        "sieben" => 7,
+        "acht" => 8,
+        "neun" => 9,
+        "zehn" => 10,
+        "elf" => 11,
+        "zwölf" => 12,
+        "dreizehn" => 13,
+        "vierzehn" => 14,
+        "fünfzehn" => 15,
+        "sechzehn" => 16,
+        "siebzehn" => 17,
+        "achtzehn" => 18,
+        "neunzehn" => 19,
+        "zwanzig" => 20,
+        "dreißig" | "dreissig" => 30,
+        "vierzig" => 40,
+        "fünfzig" => 50,
+        "sechzig" => 60,
+        "siebzig" => 70,
+        "achtzig" => 80,
+        "neunzig" => 90,
+        _ => return None,
+    })
+}
+
+/// Unit inside a compound ("ein" as in "einundzwanzig", "einhundert")
+fn de_unit(word: &str) -> Option<u64> {
+    match word {
+        "ein" => Some(1),
+        _ => de_simple(word).filter(|v| (1..10).contains(v)),
+    }
+}
+
+fn de_below_hundred(word: &str) -> Option<u64> {
+    if let Some(value) = de_simple(word) {
+        return Some(value);
+    }
+    let (unit, tens) = word.split_once("und")?;
+    let tens = de_simple(tens).filter(|v| *v >= 20 && v % 10 == 0)?;
+    Some(de_unit(unit)? + tens)
+}
+
+fn de_below_thousand(word: &str) -> Option<u64> {
+    match word.split_once("hundert") {
+        Some((hundreds, rest)) => {
+            let hundreds = if hundreds.is_empty() {
+                1
+            } else {
+                de_unit(hundreds)?
+            };
+            let rest = rest.strip_prefix("und").unwrap_or(rest);
+            let rest = if rest.is_empty() {
+                0
+            } else {
+                de_below_hundred(if rest == "ein" { "eins" } else { rest })?
+            };
+            Some(hundreds * 100 + rest)
+        }
+        None => de_below_hundred(word),
+    }
+}
+
+/// Parse a German number word such as "dreitausendzweihundertfünf"
+fn de_compound(word: &str) -> Option<u64> {
+    match word.split_once("tausend") {
+        Some((thousands, rest)) => {
+            let thousands = if thousands.is_empty() {
+                1
+            } else {
+                de_below_thousand(thousands)?
+            };
+            let rest = rest.strip_prefix("und").unwrap_or(rest);
+            let rest = if rest.is_empty() {
+                0
+            } else {
+                de_below_thousand(if rest == "ein" { "eins" } else { rest })?
+            };
+            Some(thousands * 1000 + rest)
+        }
+        None => de_below_thousand(word),
+    }
+}
+
+fn de_scale(word: &str) -> Option<u64> {
+    match word {
+        "million" | "millionen" => Some(1_000_000),
+        "milliarde" | "milliarden" => Some(1_000_000_000),
+        _ => None,
+    }
+}
+
+/// German ordinal ("dritte", "dreiundzwanzigsten") → value
+fn de_ordinal(word: &str) -> Option<u64> {
+    let stem = ["en", "er", "es", "em", "e"]
+        .iter()
+        .find_map(|suffix| word.strip_suffix(suffix))?;
+    match stem {
+        "erst" => return Some(1),
+        "dritt" => return Some(3),
+        "siebt" => return Some(7),
+        "acht" => return Some(8),
+        _ => {}
+    }
+    if let Some(base) = stem.strip_suffix("st") {
+        if let Some(value) = de_compound(base).filter(|v| *v >= 20) {
+            return Some(value);
+        }
+    }
+    de_compound(stem.strip_suffix('t')?).filter(|v| (2..20).contains(v))
+}
+
+/// Words after which a German ordinal is expected ("am dritten", "der zweite")
+fn de_ordinal_context(word: &str) -> bool {
+    matches!(
+        word,
+        "der"
+            | "die"
+            | "das"
+            | "den"
+            | "dem"
+            | "des"
+            | "am"
+            | "im"
+            | "zum"
+            | "zur"
+            | "vom"
+            | "beim"
+    )
+}
+
+/// Parse a German cardinal, including "drei Millionen zweihundert"
+fn parse_de_number(tokens: &[Token], start: usize) -> Option<(u64, usize)> {
+    let first = &tokens[start];
+    let scale_follows = |i: usize| {
+        tokens[i].continues()
+            && tokens
+                .get(i + 1)
+                .is_some_and(|t| t.lead.is_empty() && de_scale(&t.core).is_some())
+    };
+
+    let value = match first.core.as_str() {
+        "ein" | "eine" if scale_follows(start) => 1,
+        core => de_compound(core)?,
+    };
+    let mut total = 0;
+    let mut current = value;
+    let mut i = start + 1;
+    let mut last_scale = u64::MAX;
+
+    while scale_follows(i - 1) {
+        let scale = de_scale(&tokens[i].core)?;
+        if scale >= last_scale || current == 0 || current >= 1000 {
+            break;
+        }
+        total += current * scale;
+        current = 0;
+        last_scale = scale;
+        i += 1;
+
+        // Optional remainder after the scale: "drei Millionen zweihundert"
+        if tokens[i - 1].continues() {
+            if let Some(rest) = tokens
+                .get(i)
+                .filter(|t| t.lead.is_empty())
+                .and_then(|t| de_compound(&t.core))
+            {
+                current = rest;
+                i += 1;
+            }
+        }
+    }
+
+    Some((total + current, i - start))
+}
+
+/// Match a date with a year at `i`: "fünften März zweitausendvierundzwanzig"
+fn match_de_date(tokens: &[Token], i: usize) -> Option<(NaiveDate, usize)> {
+    let day = de_ordinal(&tokens[i].core).filter(|d| (1..=31).contains(d))?;
+    let month_token = tokens.get(i + 1).filter(|t| t.lead.is_empty())?;
+    let month = month(&month_token.core, NumberLanguage::German)?;
+    let year_token = tokens.get(i + 2).filter(|t| t.lead.is_empty())?;
+    if !tokens[i].continues() || !month_token.continues() {
+        return None;
+    }
+    let (year, consumed) = match year_token.core.parse::<i32>() {
+        Ok(year) => (year, 1),
+        Err(_) => {
+            let (year, consumed) = parse_de_number(tokens, i + 2)?;
+            (i32::try_from(year).ok()?, consumed)
+        }
+    };
+    if !(1000..=2999).contains(&year) {
+        return None;
+    }
+    let date = NaiveDate::from_ymd_opt(year, month, day as u32)?;
+    Some((date, consumed + 2))
+}
+
+fn match_german(tokens: &[Token], i: usize, locale: Option<&Locale>) -> Option<(String, usize)> {
+    if let Some(locale) = locale {
+        if let Some((date, consumed)) = match_de_date(tokens, i) {
+            return Some((locale.date(date), consumed));
+        }
+    }
+
+    // Ordinals, only where one is expected: "am dritten Mai" → "am 3. Mai"
+    if i > 0 && de_ordinal_context(&tokens[i - 1].core) && tokens[i - 1].continues() {
+        if let Some(value) = de_ordinal(&tokens[i].core) {
+            return Some((format!("{}.", value), 1));
+        }
+    }
+
+    let (value, consumed) = parse_de_number(tokens, i)?;
+    let last = &tokens[i + consumed - 1];
+    let next = i + consumed;
+
+    if last.continues() {
+        if let Some(next_token) = tokens.get(next).filter(|t| t.lead.is_empty()) {
+            // Times: "drei Uhr", "drei Uhr dreißig"
+            if next_token.core == "uhr" && value <= 24 {
+                if next_token.continues() {
+                    if let Some(minutes) = tokens
+                        .get(next + 1)
+                        .filter(|t| t.lead.is_empty())
+                        .and_then(|t| de_compound(&t.core))
+                        .filter(|m| *m < 60)
+                    {
+                        return Some((format!("{}:{:02} Uhr", value, minutes), consumed + 2));
+                    }
+                }
+                return Some((format!("{} Uhr", value), consumed + 1));
+            }
+
+            // Decimals: "drei Komma fünf"
+            if next_token.core == "komma" && next_token.continues() {
+                let mut digits = String::new();
+                let mut j = next + 1;
+                while let Some(token) = tokens.get(j) {
+                    match de_simple(&token.core).filter(|d| *d < 10) {
+                        Some(d) if token.lead.is_empty() => digits.push_str(&d.to_string()),
+                        _ => break,
+                    }
+                    j += 1;
+                    if !token.continues() {
+                        break;
+                    }
+                }
+                if !digits.is_empty() {
+                    let decimal = format_number(value, &digits, ',', locale);
+                    return Some(with_unit(tokens, j, decimal, j - i, locale, DE_UNITS));
+                }
+            }
+        }
+    }
+
+    let number = format_number(value, "", ',', locale);
+    Some(with_unit(tokens, next, number, consumed, locale, DE_UNITS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn en(text: &str) -> String {
+        convert_numbers(text, NumberLanguage::English, None)
+    }
+
+    fn de(text: &str) -> String {
+        convert_numbers(text, NumberLanguage::German, None)
+    }
+
+    fn localized(text: &str, locale: &str) -> String {
+        let locale: Locale = locale.parse().unwrap();
+        convert_numbers(text, locale.language.unwrap(), Some(&locale))
+    }
+
+    #[test]
+    fn test_english_cardinals() {
+        assert_eq!(en("twenty three"), "23");
+        assert_eq!(en("I have twenty-three apples."), "I have 23 apples.");
+        assert_eq!(en("two hundred and five"), "205");
+        assert_eq!(en("three million four hundred thousand"), "3400000");
+        assert_eq!(en("zero"), "0");
+        assert_eq!(en("twelve, thirteen"), "12, 13");
+    }
+
+    #[test]
+    fn test_english_keeps_lone_one() {
+        assert_eq!(en("the one I like"), "the one I like");
+        assert_eq!(en("one hundred"), "100");
+        assert_eq!(en("first of all, the second"), "first of all, the second");
+        assert_eq!(en("one two three"), "1 2 3");
+    }
+
+    #[test]
+    fn test_english_ordinals() {
+        assert_eq!(en("the twenty first floor"), "the 21st floor");
+        assert_eq!(en("third"), "3rd");
+        assert_eq!(en("the twelfth"), "the 12th");
+        assert_eq!(en("twentieth century"), "20th century");
+        assert_eq!(en("one hundredth"), "100th");
+    }
+
+    #[test]
+    fn test_english_times_and_decimals() {
+        assert_eq!(en("meet at three p m"), "meet at 3 PM");
+        assert_eq!(en("three thirty pm"), "3:30 PM");
+        assert_eq!(en("eleven oh five a.m."), "11:05 AM.");
+        assert_eq!(en("three point one four"), "3.14");
+        assert_eq!(en("a point made"), "a point made");
+    }
+
+    #[test]
+    fn test_english_years() {
+        assert_eq!(en("in nineteen ninety nine"), "in 1999");
+        assert_eq!(en("twenty twenty four"), "2024");
+        assert_eq!(en("nineteen oh five"), "1905");
+    }
+
+    #[test]
+    fn test_english_clock_times() {
+        assert_eq!(en("meet at two thirty"), "meet at 2:30");
+        assert_eq!(en("at eleven oh five, please"), "at 11:05, please");
+        assert_eq!(en("two thirty p m"), "2:30 PM");
+    }
+
+    #[test]
+    fn test_english_number_pairs_stay_words() {
+        assert_eq!(en("page ten eleven"), "page ten eleven");
+        assert_eq!(en("two thirty"), "two thirty");
+        assert_eq!(en("Ten eleven."), "Ten eleven.");
+        assert_eq!(en("ten, eleven"), "10, 11");
+    }
+
+    #[test]
+    fn test_german_compounds() {
+        assert_eq!(de("dreiundzwanzig"), "23");
+        assert_eq!(de("Ich habe einhundertfünf Euro"), "Ich habe 105 Euro");
+        assert_eq!(de("zweitausendvierundzwanzig"), "2024");
+        assert_eq!(de("drei Millionen zweihundert"), "3000200");
+        assert_eq!(de("eine Million"), "1000000");
+        assert_eq!(de("ein Hund und eine Katze"), "ein Hund und eine Katze");
+    }
+
+    #[test]
+    fn test_german_ordinals_times_decimals() {
+        assert_eq!(de("am dritten Mai"), "am 3. Mai");
+        assert_eq!(de("der einundzwanzigste Juni"), "der 21. Juni");
+        assert_eq!(de("wir achten darauf"), "wir achten darauf");
+        assert_eq!(de("um drei Uhr dreißig"), "um 3:30 Uhr");
+        assert_eq!(de("drei Komma fünf"), "3,5");
+    }
+
+    #[test]
+    fn test_locale_numbers() {
+        assert_eq!(localized("three point five", "en-US"), "3.5");
+        assert_eq!(localized("drei Komma fünf", "de-DE"), "3,5");
+        assert_eq!(
+            localized("twelve thousand three hundred forty five point five", "en"),
+            "12,345.5"
+        );
+        assert_eq!(
+            localized("zwölftausenddreihundert", "de_DE.UTF-8"),
+            "12.300"
+        );
+        assert_eq!(localized("zwölftausenddreihundert", "de-CH"), "12’300");
+        // Four-digit numbers aren't grouped, so years stay as they are
+        assert_eq!(localized("two thousand five hundred", "en"), "2500");
+        assert_eq!(localized("three million", "en-GB"), "3,000,000");
+    }
+
+    #[test]
+    fn test_locale_dates() {
+        let date = "on march fifth twenty twenty four.";
+        assert_eq!(localized(date, "en-US"), "on 3/5/2024.");
+        assert_eq!(localized(date, "en-GB"), "on 05/03/2024.");
+        assert_eq!(localized("the fifth of march 2024", "en-GB"), "05/03/2024");
+        assert_eq!(
+            localized("am fünften März zweitausendvierundzwanzig", "de"),
+            "am 05.03.2024"
+        );
+        // Without a year, or without a locale, dates stay as before
+        assert_eq!(localized("march fifth", "en"), "march 5th");
+        assert_eq!(en(date), "on march 5th 2024.");
+        assert_eq!(
+            localized("february thirtieth twenty twenty four", "en"),
+            "february 30th 2024"
+        );
+    }
+
+    #[test]
+    fn test_locale_units() {
+        assert_eq!(localized("five kilometers per hour", "en"), "5 km/h");
+        assert_eq!(localized("one percent", "en"), "1%");
+        assert_eq!(localized("zwanzig Prozent", "de"), "20 %");
+        assert_eq!(localized("drei Komma fünf Kilogramm.", "de"), "3,5 kg.");
+        assert_eq!(localized("zwanzig Grad Celsius", "de-AT"), "20 °C");
+        assert_eq!(localized("in fünf Metern", "de"), "in 5 m");
+        assert_eq!(en("five kilometers"), "5 kilometers");
+    }
+
+    #[test]
+    fn test_locale_from_str() {
+        assert_eq!(
+            "fr_FR.UTF-8".parse::<Locale>().map(|l| l.language),
+            Ok(None)
+        );
+        assert!("xx".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_preserves_whitespace_and_punctuation() {
+        assert_eq!(en("  (two)\nthree"), "  (2)\n3");
+        assert_eq!(en("nothing here"), "nothing here");
+    }
+
+    #[test]
+    fn test_number_language_from_str() {
+        assert_eq!("de".parse(), Ok(NumberLanguage::German));
+        assert_eq!("English".parse(), Ok(NumberLanguage::English));
+        assert!("fr".parse::<NumberLanguage>().is_err());
+    }
+}
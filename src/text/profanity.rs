@@ -0,0 +1,178 @@
+//! Profanity filter
+//!
+//! Masks ("f***") or drops configured words before output, for dictating on
+//! shared screens or streams. A built-in English list can be extended with
+//! `[text].profanity_words` and trimmed with `[text].profanity_allow`.
+
+use crate::config::ProfanityFilterMode;
+use regex::Regex;
+
+/// Built-in word list. Common endings (s, ed, er, ing, ...) are matched too.
+const DEFAULT_WORDS: &[&str] = &[
+    "arse",
+    "arsehole",
+    "asshole",
+    "bastard",
+    "bitch",
+    "bollocks",
+    "bullshit",
+    "cock",
+    "cunt",
+    "dick",
+    "dickhead",
+    "fuck",
+    "goddamn",
+    "motherfucker",
+    "piss",
+    "prick",
+    "shit",
+    "slut",
+    "twat",
+    "wank",
+    "wanker",
+    "whore",
+];
+
+/// Endings matched after built-in words ("fucking", "shits", "pissed")
+const SUFFIXES: &str = "(?:s|es|ed|er|ers|ing|in|y)?";
+
+/// Masks or drops profanity in transcribed text
+pub struct ProfanityFilter {
+    mode: ProfanityFilterMode,
+    regex: Regex,
+}
+
+impl ProfanityFilter {
+    /// Build a filter from the built-in list plus `extra` words, minus `allow`
+    ///
+    /// Returns None when the mode is `off` or no words remain.
+    pub fn new(mode: ProfanityFilterMode, extra: &[String], allow: &[String]) -> Option<Self> {
+        if mode == ProfanityFilterMode::Off {
+            return None;
+        }
+
+        let allowed: Vec<String> = allow.iter().map(|w| w.trim().to_lowercase()).collect();
+        let is_allowed = |word: &str| allowed.iter().any(|a| a == word);
+
+        let mut alternatives: Vec<String> = DEFAULT_WORDS
+            .iter()
+            .filter(|w| !is_allowed(w))
+            .map(|w| format!("{}{}", w, SUFFIXES))
+            .collect();
+        alternatives.extend(
+            extra
+                .iter()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty() && !is_allowed(w))
+                .map(|w| regex::escape(&w)),
+        );
+        if alternatives.is_empty() {
+            return None;
+        }
+
+        let pattern = format!(r"(?i)\b(?:{})\b", alternatives.join("|"));
+        match Regex::new(&pattern) {
+            Ok(regex) => Some(Self { mode, regex }),
+            Err(e) => {
+                tracing::warn!("Invalid profanity word list, filter disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Apply the filter to `text`
+    pub fn apply(&self, text: &str) -> String {
+        match self.mode {
+            ProfanityFilterMode::Off => text.to_string(),
+            ProfanityFilterMode::Mask => self
+                .regex
+                .replace_all(text, |caps: &regex::Captures| mask_word(&caps[0]))
+                .into_owned(),
+            ProfanityFilterMode::Drop => drop_words(&self.regex, text),
+        }
+    }
+}
+
+/// Remove the words `regex` matches, with the gap each one leaves
+///
+/// Only the text around a dropped word is touched, so spacing and
+/// indentation elsewhere (and a trailing space) stay as they are.
+fn drop_words(regex: &Regex, text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = 0;
+    for m in regex.find_iter(text) {
+        result.push_str(&text[rest..m.start()]);
+        let mut after = &text[m.end()..];
+        if result.is_empty() || result.ends_with('\n') {
+            // "Shit, I forgot" → "I forgot"
+            after = after
+                .trim_start_matches([',', ';', ':'])
+                .trim_start_matches(' ');
+        } else if result.ends_with(' ')
+            && (after.is_empty() || after.starts_with([' ', '\n', ',', '.', '!', '?', ';', ':']))
+        {
+            // "this is fucking great" → "this is great", "oh shit." → "oh."
+            result.pop();
+        }
+        rest = text.len() - after.len();
+    }
+    result.push_str(&text[rest..]);
+    result
+}
+
+/// Keep the first character, replace the remaining letters with `*`
+fn mask_word(word: &str) -> String {
+    word.chars()
+        .enumerate()
+        .map(|(i, c)| if i > 0 && c.is_alphanumeric() { '*' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(mode: ProfanityFilterMode) -> ProfanityFilter {
+        ProfanityFilter::new(mode, &["frak".to_string()], &["piss".to_string()]).unwrap()
+    }
+
+    #[test]
+    fn test_mask() {
+        let f = filter(ProfanityFilterMode::Mask);
+        assert_eq!(
+            f.apply("Oh shit, that was close"),
+            "Oh s***, that was close"
+        );
+        assert_eq!(f.apply("FUCKING hell"), "F****** hell");
+        assert_eq!(f.apply("what the frak"), "what the f***");
+    }
+
+    #[test]
+    fn test_word_boundaries_and_allow_list() {
+        let f = filter(ProfanityFilterMode::Mask);
+        assert_eq!(f.apply("Scunthorpe, Dickens"), "Scunthorpe, Dickens");
+        assert_eq!(f.apply("pissed off"), "pissed off");
+    }
+
+    #[test]
+    fn test_drop() {
+        let f = filter(ProfanityFilterMode::Drop);
+        assert_eq!(f.apply("this is fucking great"), "this is great");
+        assert_eq!(f.apply("Shit, I forgot"), "I forgot");
+        assert_eq!(f.apply("oh shit.\nnext line"), "oh.\nnext line");
+        assert_eq!(f.apply("that is shit "), "that is ");
+    }
+
+    #[test]
+    fn test_drop_leaves_clean_text_alone() {
+        let f = filter(ProfanityFilterMode::Drop);
+        for text in ["  indented  with  gaps ", "line one\n\n  line two", "a , b"] {
+            assert_eq!(f.apply(text), text);
+        }
+    }
+
+    #[test]
+    fn test_off() {
+        assert!(ProfanityFilter::new(ProfanityFilterMode::Off, &[], &[]).is_none());
+    }
+}
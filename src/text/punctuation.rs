@@ -0,0 +1,237 @@
+//! Punctuation and capitalization restoration
+//!
+//! Some engines (Moonshine, Paraformer, some fine-tunes) emit lowercase text
+//! without punctuation: "okay can you send me the file i made". With
+//! `restore_punctuation` enabled, such a transcription is turned into a
+//! sentence: "Okay, can you send me the file I made?"
+//!
+//! The rules are simple and only look at the words, so the whole
+//! transcription is treated as one sentence:
+//! - "i" and its contractions are capitalized, as is the first letter
+//! - A leading interjection ("okay", "yeah", "hello", ...) gets a comma
+//! - The end gets a question mark when the text opens like a question
+//!   ("can you", "is it", "what's", "how many"), and a period otherwise
+//!
+//! Text that already has sentence punctuation is left alone, so the step is
+//! harmless with engines that punctuate, and dictated punctuation
+//! (`spoken_punctuation`) wins.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Words that open a question when followed by an auxiliary verb
+const WH_WORDS: &[&str] = &[
+    "what", "who", "whom", "whose", "where", "when", "why", "how", "which",
+];
+
+/// Auxiliary verbs, which open a question when followed by a subject
+const AUXILIARIES: &[&str] = &[
+    "is",
+    "are",
+    "am",
+    "was",
+    "were",
+    "do",
+    "does",
+    "did",
+    "can",
+    "could",
+    "would",
+    "will",
+    "should",
+    "shall",
+    "may",
+    "might",
+    "have",
+    "has",
+    "had",
+    "isn't",
+    "aren't",
+    "wasn't",
+    "weren't",
+    "don't",
+    "doesn't",
+    "didn't",
+    "can't",
+    "couldn't",
+    "wouldn't",
+    "won't",
+    "shouldn't",
+    "haven't",
+    "hasn't",
+];
+
+/// Words that start the subject of a question after an auxiliary
+const SUBJECTS: &[&str] = &[
+    "i",
+    "you",
+    "we",
+    "they",
+    "he",
+    "she",
+    "it",
+    "there",
+    "this",
+    "that",
+    "these",
+    "those",
+    "the",
+    "a",
+    "an",
+    "my",
+    "your",
+    "our",
+    "their",
+    "his",
+    "her",
+    "its",
+    "anyone",
+    "anybody",
+    "someone",
+    "somebody",
+    "everyone",
+    "everybody",
+    "anything",
+    "something",
+];
+
+/// Auxiliaries that also start commands ("do it now", "have a look"), so
+/// they only open a question before a person ("do you")
+const IMPERATIVES: &[&str] = &["do", "don't", "have"];
+
+/// Subjects after one of [`IMPERATIVES`] that make it a question
+const PERSONS: &[&str] = &["i", "you", "we", "they", "he", "she"];
+
+/// Words after "how" or "what" that make a question on their own
+/// ("how many", "what about")
+const WH_FOLLOWERS: &[&str] = &[
+    "much", "many", "long", "far", "often", "come", "about", "if",
+];
+
+/// Interjections followed by a comma when they open a sentence
+const INTERJECTIONS: &[&str] = &["okay", "ok", "yeah", "yes", "hi", "hey", "hello"];
+
+/// Restore punctuation and capitalization of unpunctuated text
+pub fn restore(text: &str) -> String {
+    let trimmed = text.trim_end();
+    if trimmed.is_empty() || has_sentence_punctuation(trimmed) {
+        return text.to_string();
+    }
+
+    let mut result = capitalize_i(trimmed);
+
+    let words: Vec<String> = result
+        .split_whitespace()
+        .take(3)
+        .map(|word| word.to_lowercase())
+        .collect();
+    let interjection = words.len() > 1 && INTERJECTIONS.contains(&words[0].as_str());
+    if interjection {
+        if let Some(end) = result.find(char::is_whitespace) {
+            result.insert(end, ',');
+        }
+    }
+
+    // "okay can you" opens a question after the comma
+    let opening = if interjection {
+        &words[1..]
+    } else {
+        &words[..]
+    };
+    let question = opening
+        .first()
+        .is_some_and(|first| is_question(first, opening.get(1).map(String::as_str)));
+    result.push(if question { '?' } else { '.' });
+
+    result = super::capitalize_first_letter(&result);
+    result.push_str(&text[trimmed.len()..]);
+    result
+}
+
+/// Whether the text has punctuation that ends or splits sentences
+fn has_sentence_punctuation(text: &str) -> bool {
+    text.chars()
+        .any(|c| matches!(c, '.' | '!' | '?' | ',' | ';' | '…' | '。' | '？' | '！'))
+}
+
+/// Whether words starting like this ask a question
+fn is_question(first: &str, second: Option<&str>) -> bool {
+    // "what's", "where'd", "how're"
+    if let Some((wh, _)) = first.split_once(['\'', '\u{2019}']) {
+        if WH_WORDS.contains(&wh) {
+            return true;
+        }
+    }
+    let Some(second) = second else {
+        return WH_WORDS.contains(&first);
+    };
+    if WH_WORDS.contains(&first) {
+        return AUXILIARIES.contains(&second)
+            || (matches!(first, "how" | "what") && WH_FOLLOWERS.contains(&second));
+    }
+    if IMPERATIVES.contains(&first) {
+        return PERSONS.contains(&second);
+    }
+    AUXILIARIES.contains(&first) && SUBJECTS.contains(&second)
+}
+
+/// Capitalize "i" and its contractions ("i'm", "i'll")
+fn capitalize_i(text: &str) -> String {
+    static I: OnceLock<Regex> = OnceLock::new();
+    let re = I.get_or_init(|| Regex::new(r"\bi\b").unwrap());
+    re.replace_all(text, "I").into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statement() {
+        assert_eq!(
+            restore("i think i'll send it tomorrow"),
+            "I think I'll send it tomorrow."
+        );
+        assert_eq!(restore("send me the file"), "Send me the file.");
+        // "when" without an auxiliary opens a clause, not a question
+        assert_eq!(
+            restore("when you get a chance call me"),
+            "When you get a chance call me."
+        );
+    }
+
+    #[test]
+    fn test_question() {
+        assert_eq!(
+            restore("can you send me the file"),
+            "Can you send me the file?"
+        );
+        assert_eq!(restore("what's the time"), "What's the time?");
+        assert_eq!(restore("how many are left"), "How many are left?");
+        assert_eq!(restore("where is it"), "Where is it?");
+        assert_eq!(restore("why"), "Why?");
+        // Commands aren't questions
+        assert_eq!(restore("do it now"), "Do it now.");
+        assert_eq!(restore("have a look"), "Have a look.");
+        assert_eq!(restore("do you have it"), "Do you have it?");
+    }
+
+    #[test]
+    fn test_interjection() {
+        assert_eq!(
+            restore("okay can you check this"),
+            "Okay, can you check this?"
+        );
+        assert_eq!(restore("hello world"), "Hello, world.");
+        assert_eq!(restore("okay"), "Okay.");
+    }
+
+    #[test]
+    fn test_punctuated_text_unchanged() {
+        assert_eq!(restore("Hello, world."), "Hello, world.");
+        assert_eq!(restore("is it done?"), "is it done?");
+        assert_eq!(restore(""), "");
+        // Trailing whitespace is kept after the added punctuation
+        assert_eq!(restore("thanks \n"), "Thanks. \n");
+    }
+}
@@ -0,0 +1,151 @@
+//! Regex post-processing rules
+//!
+//! Ordered `[[postprocess.rules]]` entries, each a regex replacement that can
+//! be limited to a profile (`only_profile`) or to the focused application
+//! (`only_app`). Rules run on the final text, after any post-processing
+//! command, so they can fix up its output too.
+
+use crate::config::PostprocessRule;
+use regex::Regex;
+
+/// A compiled rule
+struct Rule {
+    regex: Regex,
+    replace: String,
+    only_profile: Option<String>,
+    only_app: Option<String>,
+}
+
+/// Ordered set of compiled regex rules
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// Compile the configured rules, skipping (and logging) invalid patterns
+    pub fn new(rules: &[PostprocessRule]) -> Self {
+        let rules = rules
+            .iter()
+            .filter_map(|rule| match Regex::new(&rule.pattern) {
+                Ok(regex) => Some(Rule {
+                    regex,
+                    replace: rule.replace.clone(),
+                    only_profile: rule.only_profile.clone(),
+                    only_app: rule.only_app.as_ref().map(|a| a.to_lowercase()),
+                }),
+                Err(e) => {
+                    tracing::warn!("Skipping postprocess rule {:?}: {}", rule.pattern, e);
+                    None
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    /// Whether there are no rules to apply
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether any rule depends on the focused app (so it must be looked up)
+    pub fn needs_app(&self) -> bool {
+        self.rules.iter().any(|r| r.only_app.is_some())
+    }
+
+    /// Apply the matching rules in order
+    ///
+    /// `profile` is the active profile name, `app` the focused app id or
+    /// window class. Rules with a condition that isn't met are skipped.
+    pub fn apply(&self, text: &str, profile: Option<&str>, app: Option<&str>) -> String {
+        let app = app.map(|a| a.to_lowercase());
+        let mut result = text.to_string();
+
+        for rule in &self.rules {
+            if let Some(ref only_profile) = rule.only_profile {
+                if profile != Some(only_profile.as_str()) {
+                    continue;
+                }
+            }
+            if let Some(ref only_app) = rule.only_app {
+                if !app
+                    .as_deref()
+                    .is_some_and(|a| a.contains(only_app.as_str()))
+                {
+                    continue;
+                }
+            }
+            result = rule
+                .regex
+                .replace_all(&result, rule.replace.as_str())
+                .into_owned();
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, replace: &str) -> PostprocessRule {
+        PostprocessRule {
+            pattern: pattern.to_string(),
+            replace: replace.to_string(),
+            only_profile: None,
+            only_app: None,
+        }
+    }
+
+    #[test]
+    fn test_rules_apply_in_order() {
+        let rules = RuleSet::new(&[
+            rule(r"\s+", " "),
+            rule(r"(?i)\bvox type\b", "Voxtype"),
+            rule(r"\.$", ""),
+        ]);
+        assert_eq!(
+            rules.apply("I  use vox   type.", None, None),
+            "I use Voxtype"
+        );
+    }
+
+    #[test]
+    fn test_capture_groups() {
+        let rules = RuleSet::new(&[rule(r"(\w+)@(\w+) dot com", "$1@$2.com")]);
+        assert_eq!(
+            rules.apply("mail me at jo@example dot com", None, None),
+            "mail me at jo@example.com"
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        let rules = RuleSet::new(&[
+            PostprocessRule {
+                only_app: Some("Slack".to_string()),
+                ..rule(r"\.$", "")
+            },
+            PostprocessRule {
+                only_profile: Some("code".to_string()),
+                ..rule("arrow", "->")
+            },
+        ]);
+        assert!(rules.needs_app());
+
+        let text = "an arrow.";
+        assert_eq!(rules.apply(text, None, None), "an arrow.");
+        assert_eq!(rules.apply(text, None, Some("com.slack.Slack")), "an arrow");
+        assert_eq!(rules.apply(text, Some("code"), Some("foot")), "an ->.");
+    }
+
+    #[test]
+    fn test_invalid_pattern_skipped() {
+        let rules = RuleSet::new(&[rule("(unclosed", ""), rule("a", "b")]);
+        assert_eq!(rules.rules.len(), 1);
+        assert_eq!(rules.apply("aa", None, None), "bb");
+        assert!(!rules.needs_app());
+        assert!(RuleSet::new(&[]).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Voice-triggered snippets
+//!
+//! `[[snippets]]` entries map a spoken trigger ("insert signature") to text
+//! that is output verbatim instead of the transcription. The whole utterance
+//! must match the trigger, but loosely: case, punctuation and a few misheard
+//! letters are ignored, since whisper rarely transcribes a phrase the same
+//! way twice.
+
+use crate::config::Snippet;
+
+/// Minimum similarity (1.0 = identical) for an utterance to match a trigger
+const MIN_SIMILARITY: f32 = 0.8;
+
+/// A snippet with its trigger normalized for matching
+struct Entry {
+    trigger: String,
+    text: String,
+    only_profile: Option<String>,
+}
+
+/// Configured snippets
+#[derive(Default)]
+pub struct SnippetSet {
+    entries: Vec<Entry>,
+}
+
+impl SnippetSet {
+    /// Prepare the configured snippets, skipping (and logging) empty triggers
+    pub fn new(snippets: &[Snippet]) -> Self {
+        let entries = snippets
+            .iter()
+            .filter_map(|snippet| {
+                let trigger = normalize(&snippet.trigger);
+                if trigger.is_empty() {
+                    tracing::warn!("Skipping snippet with empty trigger {:?}", snippet.trigger);
+                    return None;
+                }
+                Some(Entry {
+                    trigger,
+                    text: snippet.text.clone(),
+                    only_profile: snippet.only_profile.clone(),
+                })
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Whether there are no snippets
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The snippet text if the utterance matches a trigger
+    ///
+    /// Snippets limited to another profile are skipped. When several
+    /// triggers match, the closest one wins, and on a tie the one limited to
+    /// the active profile.
+    pub fn expand(&self, text: &str, profile: Option<&str>) -> Option<&str> {
+        let spoken = normalize(text);
+        if spoken.is_empty() {
+            return None;
+        }
+
+        self.entries
+            .iter()
+            .filter(|entry| {
+                entry
+                    .only_profile
+                    .as_deref()
+                    .is_none_or(|only| profile == Some(only))
+            })
+            .map(|entry| (entry, similarity(&spoken, &entry.trigger)))
+            .filter(|(_, score)| *score >= MIN_SIMILARITY)
+            .max_by(|(a, score_a), (b, score_b)| {
+                score_a
+                    .total_cmp(score_b)
+                    .then(a.only_profile.is_some().cmp(&b.only_profile.is_some()))
+            })
+            .map(|(entry, _)| entry.text.as_str())
+    }
+}
+
+/// Lowercase words without punctuation, separated by single spaces
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 1.0 minus the edit distance relative to the longer string
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f32 / longest as f32
+}
+
+/// Levenshtein distance between two character sequences
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(trigger: &str, text: &str) -> Snippet {
+        Snippet {
+            trigger: trigger.to_string(),
+            text: text.to_string(),
+            only_profile: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("  Insert, signature! "), "insert signature");
+        assert_eq!(
+            normalize("Boilerplate-disclaimer."),
+            "boilerplate disclaimer"
+        );
+        assert_eq!(normalize("..."), "");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("same"), &chars("same")), 0);
+    }
+
+    #[test]
+    fn test_expand() {
+        let snippets = SnippetSet::new(&[
+            snippet("insert signature", "Best regards,\nJo"),
+            snippet("boilerplate disclaimer", "This message is confidential."),
+        ]);
+        assert_eq!(
+            snippets.expand("Insert signature.", None),
+            Some("Best regards,\nJo")
+        );
+        // Misheard, but close enough
+        assert_eq!(
+            snippets.expand("Insert signatures", None),
+            Some("Best regards,\nJo")
+        );
+        assert_eq!(
+            snippets.expand("Boiler plate disclaimer!", None),
+            Some("This message is confidential.")
+        );
+        // The whole utterance must match
+        assert_eq!(snippets.expand("Please insert signature here", None), None);
+        assert_eq!(snippets.expand("", None), None);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let snippets = SnippetSet::new(&[
+            Snippet {
+                only_profile: Some("work".to_string()),
+                ..snippet("insert signature", "Regards,\nJo Smith")
+            },
+            snippet("insert signature", "Cheers, Jo"),
+        ]);
+        assert_eq!(
+            snippets.expand("insert signature", Some("work")),
+            Some("Regards,\nJo Smith")
+        );
+        assert_eq!(
+            snippets.expand("insert signature", None),
+            Some("Cheers, Jo")
+        );
+    }
+
+    #[test]
+    fn test_empty_trigger_skipped() {
+        let snippets = SnippetSet::new(&[snippet("?!", "text")]);
+        assert!(snippets.is_empty());
+        assert_eq!(snippets.expand("?!", None), None);
+    }
+}
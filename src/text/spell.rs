@@ -0,0 +1,230 @@
+//! Spell mode
+//!
+//! Turns an utterance spelled out letter by letter into the characters
+//! themselves: "alpha bravo seven" becomes "ab7". Meant for passwords, codes
+//! and callsigns, which whisper would otherwise turn into words.
+//!
+//! Understands the NATO alphabet, single letters and digits, digit words
+//! ("seven", "niner"), "capital" before a letter, "double"/"triple" before a
+//! character, and names of common symbols ("dash", "underscore", "at").
+//! Anything else is kept as is, including punctuation written between
+//! letters or digits ("jo_smith@example.com"); the punctuation whisper puts
+//! between words ("Alpha, bravo.") is dropped.
+
+/// NATO alphabet, with common spelling variants
+const NATO: &[(&str, char)] = &[
+    ("alpha", 'a'),
+    ("alfa", 'a'),
+    ("bravo", 'b'),
+    ("charlie", 'c'),
+    ("delta", 'd'),
+    ("echo", 'e'),
+    ("foxtrot", 'f'),
+    ("golf", 'g'),
+    ("hotel", 'h'),
+    ("india", 'i'),
+    ("juliet", 'j'),
+    ("juliett", 'j'),
+    ("kilo", 'k'),
+    ("lima", 'l'),
+    ("mike", 'm'),
+    ("november", 'n'),
+    ("oscar", 'o'),
+    ("papa", 'p'),
+    ("quebec", 'q'),
+    ("romeo", 'r'),
+    ("sierra", 's'),
+    ("tango", 't'),
+    ("uniform", 'u'),
+    ("victor", 'v'),
+    ("whiskey", 'w'),
+    ("whisky", 'w'),
+    ("xray", 'x'),
+    ("yankee", 'y'),
+    ("zulu", 'z'),
+];
+
+/// Digit words
+const DIGITS: &[(&str, char)] = &[
+    ("zero", '0'),
+    ("one", '1'),
+    ("two", '2'),
+    ("three", '3'),
+    ("four", '4'),
+    ("five", '5'),
+    ("six", '6'),
+    ("seven", '7'),
+    ("eight", '8'),
+    ("nine", '9'),
+    ("niner", '9'),
+];
+
+/// Spoken symbol names
+const SYMBOLS: &[(&str, char)] = &[
+    ("dash", '-'),
+    ("hyphen", '-'),
+    ("minus", '-'),
+    ("underscore", '_'),
+    ("dot", '.'),
+    ("period", '.'),
+    ("point", '.'),
+    ("at", '@'),
+    ("slash", '/'),
+    ("hash", '#'),
+    ("plus", '+'),
+    ("star", '*'),
+    ("asterisk", '*'),
+    ("dollar", '$'),
+    ("exclamation", '!'),
+    ("bang", '!'),
+    ("space", ' '),
+];
+
+/// Spell out an utterance
+pub fn spell(text: &str) -> String {
+    let text = text.to_lowercase().replace("x-ray", "xray");
+    let mut result = String::new();
+    let mut capital = false;
+    let mut repeat = 1;
+
+    for word in words(&text) {
+        match word {
+            "capital" | "uppercase" | "upper" | "cap" => {
+                capital = true;
+                continue;
+            }
+            "double" => {
+                repeat = 2;
+                continue;
+            }
+            "triple" => {
+                repeat = 3;
+                continue;
+            }
+            _ => {}
+        }
+
+        let spelled = match lookup(word) {
+            Some(c) if capital => c.to_uppercase().collect(),
+            Some(c) => c.to_string(),
+            // Numbers and unknown words as they were said
+            None => word.to_string(),
+        };
+        for _ in 0..repeat {
+            result.push_str(&spelled);
+        }
+        capital = false;
+        repeat = 1;
+    }
+
+    result
+}
+
+/// Words of an utterance, with the punctuation written inside them
+///
+/// Punctuation at the edges of a word is whisper's and is dropped.
+/// Punctuation between letters or digits was written out, so each such
+/// character comes back as a word of its own.
+fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for token in text.split_whitespace() {
+        let token = token.trim_matches(|c: char| !c.is_alphanumeric());
+        let mut start = 0;
+        for (i, c) in token.char_indices() {
+            if c.is_alphanumeric() {
+                continue;
+            }
+            if start < i {
+                words.push(&token[start..i]);
+            }
+            start = i + c.len_utf8();
+            words.push(&token[i..start]);
+        }
+        if start < token.len() {
+            words.push(&token[start..]);
+        }
+    }
+    words
+}
+
+/// The character a word stands for
+fn lookup(word: &str) -> Option<char> {
+    let mut chars = word.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    [NATO, DIGITS, SYMBOLS]
+        .iter()
+        .flat_map(|table| table.iter())
+        .find(|(name, _)| *name == word)
+        .map(|(_, c)| *c)
+}
+
+/// The rest of the utterance if it starts with the prefix words
+///
+/// Case and punctuation are ignored: "Spell: alpha" matches "spell".
+pub fn strip_prefix<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let prefix = prefix.to_lowercase();
+    let mut words = prefix
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .peekable();
+    words.peek()?;
+
+    let mut rest = text;
+    for word in words {
+        rest = rest.trim_start_matches(|c: char| !c.is_alphanumeric());
+        let end = rest
+            .find(|c: char| !c.is_alphanumeric())
+            .unwrap_or(rest.len());
+        if rest[..end].to_lowercase() != word {
+            return None;
+        }
+        rest = &rest[end..];
+    }
+    Some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spell() {
+        assert_eq!(spell("Alpha bravo seven."), "ab7");
+        assert_eq!(spell("Alpha, Bravo, 7."), "ab7");
+        assert_eq!(spell("X-ray Yankee Zulu niner"), "xyz9");
+        assert_eq!(spell("A B C 1 2 3"), "abc123");
+        assert_eq!(spell("Capital Delta echo, 42"), "De42");
+    }
+
+    #[test]
+    fn test_spell_symbols_and_repeats() {
+        assert_eq!(
+            spell("jo underscore smith at example dot com"),
+            "jo_smith@example.com"
+        );
+        assert_eq!(spell("double seven triple oscar"), "77ooo");
+        assert_eq!(spell("jo_smith@example.com"), "jo_smith@example.com");
+        assert_eq!(spell("Jo_smith at example.com."), "jo_smith@example.com");
+        assert_eq!(spell("capital kilo dash 7."), "K-7");
+        assert_eq!(spell("capital double alpha"), "AA");
+        assert_eq!(spell(""), "");
+    }
+
+    #[test]
+    fn test_strip_prefix() {
+        assert_eq!(
+            strip_prefix("Spell alpha bravo", "spell"),
+            Some(" alpha bravo")
+        );
+        assert_eq!(strip_prefix("Spell: alpha", "spell"), Some(": alpha"));
+        assert_eq!(
+            strip_prefix("Spell mode, one two", "spell mode"),
+            Some(", one two")
+        );
+        assert_eq!(strip_prefix("Spelling is hard", "spell"), None);
+        assert_eq!(strip_prefix("spell", "spell"), Some(""));
+        assert_eq!(strip_prefix("alpha", ""), None);
+    }
+}
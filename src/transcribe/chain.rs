@@ -0,0 +1,357 @@
+//! Fallback transcribers (`[fallback]`)
+//!
+//! Like the output driver chain, transcription can go down a list: when the
+//! configured transcriber fails or takes longer than `timeout_secs`, the
+//! recording goes to the first of `[[fallback.transcribers]]`, and so on.
+//! The error of the last one is returned when they all fail. A cancelled
+//! transcription isn't retried, nor is one discarded for low confidence.
+//!
+//! A transcriber that times out is cancelled and left to finish on its own
+//! thread. Fallback transcribers are loaded the first time they're needed
+//! and kept from then on.
+
+use super::{Progress, Segment, Transcriber};
+use crate::config::{Config, TranscriptionEngine, WhisperMode};
+use crate::error::TranscribeError;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+/// A transcription to run on one transcriber after another
+type Job<T> = Arc<dyn Fn(&dyn Transcriber) -> Result<T, TranscribeError> + Send + Sync>;
+
+/// A `[[fallback.transcribers]]` entry, loaded on first use
+pub struct Fallback {
+    /// What it is, for the log
+    name: String,
+    config: Config,
+    timeout: Option<Duration>,
+    transcriber: Mutex<Option<Arc<dyn Transcriber>>>,
+}
+
+impl Fallback {
+    /// The transcriber, loading it the first time
+    fn transcriber(&self) -> Result<Arc<dyn Transcriber>, TranscribeError> {
+        if let Some(transcriber) = self.transcriber.lock().unwrap().clone() {
+            return Ok(transcriber);
+        }
+        tracing::info!("Loading fallback transcriber: {}", self.name);
+        let transcriber = super::create_transcriber(&self.config)?;
+        *self.transcriber.lock().unwrap() = Some(transcriber.clone());
+        Ok(transcriber)
+    }
+
+    /// The transcriber, if it's loaded
+    fn loaded(&self) -> Option<Arc<dyn Transcriber>> {
+        self.transcriber.lock().unwrap().clone()
+    }
+}
+
+/// The `[fallback]` settings, with the fallback transcribers loaded so far
+#[derive(Default)]
+pub struct Fallbacks {
+    /// How long to wait for the configured transcriber
+    timeout: Option<Duration>,
+    transcribers: Vec<Fallback>,
+}
+
+impl Fallbacks {
+    /// The fallbacks configured in `config`
+    pub fn new(config: &Config) -> Result<Arc<Self>, TranscribeError> {
+        let transcribers = config
+            .fallback
+            .transcribers
+            .iter()
+            .map(|entry| {
+                let resolved = entry.apply(config).map_err(TranscribeError::ConfigError)?;
+                Ok(Fallback {
+                    name: describe(&resolved),
+                    config: resolved,
+                    timeout: entry.timeout_secs.map(Duration::from_secs),
+                    transcriber: Mutex::new(None),
+                })
+            })
+            .collect::<Result<_, TranscribeError>>()?;
+        Ok(Arc::new(Self {
+            timeout: config.fallback.timeout_secs.map(Duration::from_secs),
+            transcribers,
+        }))
+    }
+
+    /// `primary` falling back to these transcribers, or `primary` itself
+    /// when there are none
+    pub fn wrap(self: &Arc<Self>, primary: Arc<dyn Transcriber>) -> Arc<dyn Transcriber> {
+        if self.transcribers.is_empty() && self.timeout.is_none() {
+            return primary;
+        }
+        Arc::new(ChainTranscriber {
+            primary,
+            fallbacks: self.clone(),
+        })
+    }
+}
+
+/// Short description of the transcriber a config selects
+fn describe(config: &Config) -> String {
+    if config.engine != TranscriptionEngine::Whisper {
+        return format!("{:?}", config.engine);
+    }
+    let whisper = &config.whisper;
+    match whisper.effective_mode() {
+        WhisperMode::Remote => format!(
+            "remote {}",
+            whisper.remote_endpoint.as_deref().unwrap_or_default()
+        ),
+        WhisperMode::Worker => format!(
+            "worker {}",
+            whisper.worker_address.as_deref().unwrap_or_default()
+        ),
+        mode => format!("{:?} whisper {}", mode, whisper.model),
+    }
+}
+
+/// Run `job` on `transcriber`, giving up after `timeout`
+fn attempt<T: Send + 'static>(
+    transcriber: &Arc<dyn Transcriber>,
+    timeout: Option<Duration>,
+    job: &Job<T>,
+) -> Result<T, TranscribeError> {
+    let Some(timeout) = timeout else {
+        return job(transcriber.as_ref());
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let (thread_transcriber, thread_job) = (transcriber.clone(), job.clone());
+    std::thread::spawn(move || {
+        let _ = tx.send(thread_job(thread_transcriber.as_ref()));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            transcriber.cancel();
+            Err(TranscribeError::InferenceFailed(format!(
+                "no result after {}s",
+                timeout.as_secs_f32()
+            )))
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(TranscribeError::InferenceFailed(
+            "transcription thread panicked".to_string(),
+        )),
+    }
+}
+
+/// The configured transcriber, followed by the fallbacks
+struct ChainTranscriber {
+    primary: Arc<dyn Transcriber>,
+    fallbacks: Arc<Fallbacks>,
+}
+
+impl ChainTranscriber {
+    /// Run `job` on each transcriber in turn until one succeeds
+    fn run<T: Send + 'static>(&self, job: Job<T>) -> Result<T, TranscribeError> {
+        let mut result = attempt(&self.primary, self.fallbacks.timeout, &job);
+        for fallback in &self.fallbacks.transcribers {
+            match result {
+                Err(TranscribeError::Cancelled) | Err(TranscribeError::LowConfidence(_)) => {
+                    return result
+                }
+                Err(ref e) => {
+                    tracing::warn!("Transcription failed ({}), trying {}", e, fallback.name)
+                }
+                Ok(_) => return result,
+            }
+            result = fallback
+                .transcriber()
+                .and_then(|transcriber| attempt(&transcriber, fallback.timeout, &job));
+        }
+        result
+    }
+
+    /// The loaded transcribers
+    fn loaded(&self) -> impl Iterator<Item = Arc<dyn Transcriber>> + '_ {
+        std::iter::once(self.primary.clone()).chain(
+            self.fallbacks
+                .transcribers
+                .iter()
+                .filter_map(Fallback::loaded),
+        )
+    }
+}
+
+impl Transcriber for ChainTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        let samples = samples.to_vec();
+        self.run(Arc::new(move |t| t.transcribe(&samples)))
+    }
+
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        let samples = samples.to_vec();
+        self.run(Arc::new(move |t| {
+            t.transcribe_with_progress(&samples, progress.clone())
+        }))
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        let (samples, context) = (samples.to_vec(), context.to_string());
+        self.run(Arc::new(move |t| {
+            t.transcribe_with_context(&samples, progress.clone(), &context)
+        }))
+    }
+
+    fn transcribe_with_grammar(
+        &self,
+        samples: &[f32],
+        grammar: &Path,
+    ) -> Result<String, TranscribeError> {
+        let (samples, grammar) = (samples.to_vec(), PathBuf::from(grammar));
+        self.run(Arc::new(move |t| {
+            t.transcribe_with_grammar(&samples, &grammar)
+        }))
+    }
+
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        let samples = samples.to_vec();
+        self.run(Arc::new(move |t| t.transcribe_segments(&samples)))
+    }
+
+    fn cancel(&self) {
+        for transcriber in self.loaded() {
+            transcriber.cancel();
+        }
+    }
+
+    fn max_concurrent(&self) -> usize {
+        self.primary.max_concurrent()
+    }
+
+    fn prepare(&self) {
+        self.primary.prepare();
+    }
+
+    fn feed(&self, samples: &[f32]) {
+        self.primary.feed(samples);
+    }
+
+    fn last_model_load_time(&self) -> Option<Duration> {
+        self.primary.last_model_load_time()
+    }
+
+    fn unload_if_idle(&self) {
+        for transcriber in self.loaded() {
+            transcriber.unload_if_idle();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers with its name after `delay`, or fails
+    struct Stub {
+        name: &'static str,
+        delay: Duration,
+        fail: bool,
+    }
+
+    impl Transcriber for Stub {
+        fn transcribe(&self, _samples: &[f32]) -> Result<String, TranscribeError> {
+            std::thread::sleep(self.delay);
+            if self.fail {
+                return Err(TranscribeError::NetworkError(self.name.to_string()));
+            }
+            Ok(self.name.to_string())
+        }
+    }
+
+    fn stub(name: &'static str, delay_ms: u64, fail: bool) -> Arc<dyn Transcriber> {
+        Arc::new(Stub {
+            name,
+            delay: Duration::from_millis(delay_ms),
+            fail,
+        })
+    }
+
+    fn fallback(transcriber: Arc<dyn Transcriber>, timeout_ms: Option<u64>) -> Fallback {
+        Fallback {
+            name: "stub".to_string(),
+            config: Config::default(),
+            timeout: timeout_ms.map(Duration::from_millis),
+            transcriber: Mutex::new(Some(transcriber)),
+        }
+    }
+
+    #[test]
+    fn test_chain() {
+        let fallbacks = Arc::new(Fallbacks {
+            timeout: Some(Duration::from_millis(100)),
+            transcribers: vec![
+                fallback(stub("local", 0, true), None),
+                fallback(stub("cloud", 0, false), Some(1000)),
+            ],
+        });
+        // Failures and timeouts fall through to the next transcriber
+        let chain = fallbacks.wrap(stub("gpu", 0, true));
+        assert_eq!(chain.transcribe(&[0.0; 160]).unwrap(), "cloud");
+        let chain = fallbacks.wrap(stub("gpu", 2000, false));
+        assert_eq!(chain.transcribe(&[0.0; 160]).unwrap(), "cloud");
+        let chain = fallbacks.wrap(stub("gpu", 0, false));
+        assert_eq!(chain.transcribe(&[0.0; 160]).unwrap(), "gpu");
+
+        // The last error is returned when all of them fail
+        let fallbacks = Arc::new(Fallbacks {
+            timeout: None,
+            transcribers: vec![fallback(stub("local", 0, true), None)],
+        });
+        let err = fallbacks.wrap(stub("gpu", 0, true)).transcribe(&[0.0; 160]);
+        assert!(matches!(err, Err(TranscribeError::NetworkError(name)) if name == "local"));
+    }
+
+    #[test]
+    fn test_fallbacks_from_config() {
+        let mut config = Config::default();
+        config.whisper.mode = Some(WhisperMode::Worker);
+        config.whisper.model = "large-v3".to_string();
+        config.fallback = toml::from_str(
+            r#"
+            [[transcribers]]
+            mode = "local"
+            model = "base"
+            timeout_secs = 20
+
+            [[transcribers]]
+            mode = "remote"
+            remote_endpoint = "https://api.openai.com"
+            "#,
+        )
+        .unwrap();
+        let fallbacks = Fallbacks::new(&config).unwrap();
+        let names: Vec<_> = fallbacks.transcribers.iter().map(|f| &f.name).collect();
+        assert_eq!(
+            names,
+            ["Local whisper base", "remote https://api.openai.com"]
+        );
+        assert_eq!(
+            fallbacks.transcribers[0].timeout,
+            Some(Duration::from_secs(20))
+        );
+        // Options the entry doesn't set come from [whisper]
+        assert_eq!(fallbacks.transcribers[1].config.whisper.model, "large-v3");
+
+        // Nothing configured: the transcriber is used as it is
+        let primary = stub("gpu", 0, false);
+        let wrapped = Fallbacks::new(&Config::default())
+            .unwrap()
+            .wrap(primary.clone());
+        assert!(Arc::ptr_eq(&wrapped, &primary));
+    }
+}
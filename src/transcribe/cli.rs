@@ -5,12 +5,16 @@
 //! (e.g., Ubuntu 25.10 with glibc 2.42+).
 //!
 //! The whisper-cli binary must be installed separately or built from whisper.cpp.
+//!
+//! It's also how `gpu_backend = "openvino"` runs: a whisper-cli built with
+//! `-DWHISPER_OPENVINO=1` runs the encoder on an Intel iGPU or NPU, using the
+//! converted encoder next to the model (`ggml-base.en-encoder-openvino.xml`).
 
-use super::Transcriber;
-use crate::config::{Config, WhisperConfig};
+use super::{prompt_with_context, Progress, Transcriber};
+use crate::config::{GpuBackend, WhisperConfig};
 use crate::error::TranscribeError;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 /// CLI-based transcriber using whisper-cli subprocess
@@ -27,6 +31,14 @@ pub struct CliTranscriber {
     threads: usize,
     /// Initial prompt for context
     initial_prompt: Option<String>,
+    /// No-speech probability threshold
+    no_speech_threshold: Option<f32>,
+    /// Suppress non-speech tokens
+    suppress_non_speech_tokens: bool,
+    /// Penalty for tokens outside a grammar
+    grammar_penalty: f32,
+    /// OpenVINO device for the encoder, with gpu_backend = "openvino"
+    openvino_device: Option<String>,
 }
 
 /// JSON output structure from whisper-cli
@@ -44,7 +56,8 @@ impl CliTranscriber {
     /// Create a new CLI-based transcriber
     pub fn new(config: &WhisperConfig) -> Result<Self, TranscribeError> {
         let cli_path = resolve_cli_path(config.whisper_cli_path.as_deref())?;
-        let model_path = resolve_model_path(&config.model)?;
+        let model_path = super::models::resolve(&config.model, config.auto_download)?;
+        crate::model_cache::mark_used(&model_path);
 
         tracing::info!(
             "Using whisper-cli backend: {:?} with model {:?}",
@@ -67,7 +80,24 @@ impl CliTranscriber {
         };
 
         // Get language - use primary language from config
-        let language = config.language.primary().to_string();
+        let language = super::models::model_language(&model_path, &config.language)
+            .primary()
+            .to_string();
+
+        let openvino_device =
+            (config.gpu_backend == GpuBackend::OpenVino).then(|| config.openvino_device.clone());
+        if let Some(ref device) = openvino_device {
+            let encoder = openvino_encoder_path(&model_path);
+            if encoder.exists() {
+                tracing::info!("Using OpenVINO encoder {:?} on {}", encoder, device);
+            } else {
+                tracing::warn!(
+                    "OpenVINO encoder {:?} not found, whisper-cli uses the regular encoder. \
+                     Create it with whisper.cpp's models/convert-whisper-to-openvino.py",
+                    encoder
+                );
+            }
+        }
 
         Ok(Self {
             cli_path,
@@ -76,6 +106,10 @@ impl CliTranscriber {
             translate: config.translate,
             threads,
             initial_prompt: config.initial_prompt.clone(),
+            no_speech_threshold: config.no_speech_threshold,
+            suppress_non_speech_tokens: config.suppress_non_speech_tokens,
+            grammar_penalty: config.grammar_penalty,
+            openvino_device,
         })
     }
 
@@ -115,15 +149,27 @@ impl CliTranscriber {
 
         Ok(temp_file)
     }
-}
 
-impl Transcriber for CliTranscriber {
-    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+    /// Transcribe with whisper-cli, adding `context` to the initial prompt
+    /// and constraining the output to `grammar` (its `root` rule)
+    fn run(
+        &self,
+        samples: &[f32],
+        context: Option<&str>,
+        grammar: Option<&Path>,
+    ) -> Result<String, TranscribeError> {
         if samples.is_empty() {
             return Err(TranscribeError::AudioFormat(
                 "Empty audio buffer".to_string(),
             ));
         }
+        // whisper-cli would parse a missing file as the grammar itself
+        if let Some(grammar) = grammar.filter(|path| !path.is_file()) {
+            return Err(TranscribeError::ConfigError(format!(
+                "Grammar file not found: {:?}",
+                grammar
+            )));
+        }
 
         let duration_secs = samples.len() as f32 / 16000.0;
         tracing::debug!(
@@ -164,6 +210,10 @@ impl Transcriber for CliTranscriber {
             .arg(self.threads.to_string())
             .arg("--no-prints"); // Suppress progress output
 
+        if let Some(ref device) = self.openvino_device {
+            cmd.arg("--ov-e-device").arg(device);
+        }
+
         // Set language (skip if auto-detect)
         if self.language != "auto" {
             cmd.arg("--language").arg(&self.language);
@@ -174,11 +224,29 @@ impl Transcriber for CliTranscriber {
             cmd.arg("--translate");
         }
 
-        // Initial prompt
-        if let Some(prompt) = &self.initial_prompt {
+        // Initial prompt, with this recording's context
+        if let Some(prompt) = prompt_with_context(self.initial_prompt.as_deref(), context) {
             cmd.arg("--prompt").arg(prompt);
         }
 
+        // Non-speech handling
+        if let Some(threshold) = self.no_speech_threshold {
+            cmd.arg("--no-speech-thold").arg(threshold.to_string());
+        }
+        if self.suppress_non_speech_tokens {
+            cmd.arg("--suppress-nst");
+        }
+
+        // Grammar-constrained decoding
+        if let Some(grammar) = grammar {
+            cmd.arg("--grammar")
+                .arg(grammar)
+                .arg("--grammar-rule")
+                .arg("root")
+                .arg("--grammar-penalty")
+                .arg(self.grammar_penalty.to_string());
+        }
+
         tracing::debug!("Running whisper-cli: {:?}", cmd);
 
         // Run whisper-cli
@@ -236,7 +304,38 @@ impl Transcriber for CliTranscriber {
     }
 }
 
+impl Transcriber for CliTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        self.run(samples, None, None)
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        _progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, Some(context), None)
+    }
+
+    fn transcribe_with_grammar(
+        &self,
+        samples: &[f32],
+        grammar: &Path,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, None, Some(grammar))
+    }
+}
+
 /// Resolve whisper-cli path
+/// Where whisper.cpp looks for a model's OpenVINO encoder: the model path
+/// up to its last "." ("ggml-base.en.bin" -> "ggml-base.en-encoder-openvino.xml")
+pub fn openvino_encoder_path(model_path: &Path) -> PathBuf {
+    let model = model_path.to_string_lossy();
+    let stem = model.rfind('.').map_or(&*model, |dot| &model[..dot]);
+    PathBuf::from(format!("{}-encoder-openvino.xml", stem))
+}
+
 fn resolve_cli_path(configured_path: Option<&str>) -> Result<PathBuf, TranscribeError> {
     // If explicitly configured, use that
     if let Some(path) = configured_path {
@@ -276,66 +375,6 @@ fn resolve_cli_path(configured_path: Option<&str>) -> Result<PathBuf, Transcribe
     ))
 }
 
-/// Resolve model name to file path
-fn resolve_model_path(model: &str) -> Result<PathBuf, TranscribeError> {
-    // If it's already an absolute path, use it directly
-    let path = PathBuf::from(model);
-    if path.is_absolute() && path.exists() {
-        return Ok(path);
-    }
-
-    // Map model names to file names
-    let model_filename = match model {
-        "tiny" => "ggml-tiny.bin",
-        "tiny.en" => "ggml-tiny.en.bin",
-        "base" => "ggml-base.bin",
-        "base.en" => "ggml-base.en.bin",
-        "small" => "ggml-small.bin",
-        "small.en" => "ggml-small.en.bin",
-        "medium" => "ggml-medium.bin",
-        "medium.en" => "ggml-medium.en.bin",
-        "large" | "large-v1" => "ggml-large-v1.bin",
-        "large-v2" => "ggml-large-v2.bin",
-        "large-v3" => "ggml-large-v3.bin",
-        "large-v3-turbo" => "ggml-large-v3-turbo.bin",
-        other if other.ends_with(".bin") => other,
-        other => {
-            return Err(TranscribeError::ModelNotFound(format!(
-                "Unknown model: '{}'. Valid models: tiny, base, small, medium, large-v3, large-v3-turbo",
-                other
-            )));
-        }
-    };
-
-    // Look in the data directory
-    let models_dir = Config::models_dir();
-    let model_path = models_dir.join(model_filename);
-
-    if model_path.exists() {
-        return Ok(model_path);
-    }
-
-    // Also check current directory
-    let cwd_path = PathBuf::from(model_filename);
-    if cwd_path.exists() {
-        return Ok(cwd_path);
-    }
-
-    // Also check ./models/
-    let local_models_path = PathBuf::from("models").join(model_filename);
-    if local_models_path.exists() {
-        return Ok(local_models_path);
-    }
-
-    Err(TranscribeError::ModelNotFound(format!(
-        "Model '{}' not found. Looked in:\n  - {}\n  - {}\n  - {}",
-        model,
-        model_path.display(),
-        cwd_path.display(),
-        local_models_path.display()
-    )))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -348,8 +387,33 @@ mod tests {
     }
 
     #[test]
-    fn test_resolve_model_path_unknown() {
-        let result = resolve_model_path("nonexistent-model");
-        assert!(result.is_err());
+    fn test_openvino_encoder_path() {
+        assert_eq!(
+            openvino_encoder_path(Path::new("/models/ggml-base.en.bin")),
+            PathBuf::from("/models/ggml-base.en-encoder-openvino.xml")
+        );
+        assert_eq!(
+            openvino_encoder_path(Path::new("/models/legal-finetune")),
+            PathBuf::from("/models/legal-finetune-encoder-openvino.xml")
+        );
+    }
+
+    #[test]
+    fn test_missing_grammar_file() {
+        let transcriber = CliTranscriber {
+            cli_path: PathBuf::from("/nonexistent/whisper-cli"),
+            model_path: PathBuf::from("/nonexistent/model.bin"),
+            language: "en".to_string(),
+            translate: false,
+            threads: 1,
+            initial_prompt: None,
+            no_speech_threshold: None,
+            suppress_non_speech_tokens: true,
+            grammar_penalty: 100.0,
+            openvino_device: None,
+        };
+        let result =
+            transcriber.transcribe_with_grammar(&[0.0; 160], Path::new("/nonexistent/x.gbnf"));
+        assert!(matches!(result, Err(TranscribeError::ConfigError(_))));
     }
 }
@@ -38,6 +38,7 @@ pub struct DolphinTranscriber {
 impl DolphinTranscriber {
     pub fn new(config: &DolphinConfig) -> Result<Self, TranscribeError> {
         let model_dir = resolve_model_path(&config.model)?;
+        crate::model_cache::mark_used(&model_dir);
 
         tracing::info!("Loading Dolphin model from {:?}", model_dir);
         let start = std::time::Instant::now();
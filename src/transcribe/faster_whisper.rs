@@ -0,0 +1,688 @@
+//! faster-whisper transcription through a sidecar process
+//!
+//! With `[whisper] mode = "faster-whisper"`, transcription runs in a Python
+//! process using faster-whisper (Whisper on CTranslate2), which voxtype
+//! starts, health-checks and restarts as needed. The sidecar loads the model
+//! once and keeps it loaded, so each recording only costs the transcription.
+//!
+//! The sidecar is `scripts/faster-whisper-sidecar.py`, built into voxtype and
+//! written to the runtime directory when it's started, or the command in
+//! `faster_whisper_command`. It talks to us over a Unix socket pair passed as
+//! its stdin and stdout, one JSON object per line; stderr goes to our log.
+//!
+//! Protocol (version [`PROTOCOL_VERSION`]):
+//! 1. We send `{"type":"load","protocol":1,"model":...,"device":...,
+//!    "compute_type":...,"download_root":...,"threads":N}`
+//! 2. The sidecar loads the model, downloading it first if needed, and sends
+//!    `{"type":"ready","protocol":1}`
+//! 3. For each recording we send `{"type":"transcribe","samples":N,
+//!    "language":...,"languages":[...],"task":...,"initial_prompt":...}`,
+//!    followed by N little-endian f32 samples (16 kHz mono)
+//! 4. The sidecar sends `{"type":"result","language":...,"segments":
+//!    [{"start":s,"end":s,"text":...}]}`
+//!
+//! `{"type":"ping"}` gets `{"type":"pong"}`. Any request may get
+//! `{"type":"error","message":...}` instead. The sidecar exits when its
+//! stdin is closed.
+//!
+//! A sidecar that exited is started again on the next recording, and one that
+//! crashes during a transcription is restarted and asked once more. One that
+//! stops answering is killed. Cancelling a transcription kills the sidecar,
+//! which is started again (reloading the model) when recording next starts.
+
+use super::subprocess::SocketWriter;
+use super::{prompt_with_context, Segment, Transcriber};
+use crate::config::{self, Config, WhisperConfig};
+use crate::error::TranscribeError;
+use std::io::{BufRead, BufReader, Write};
+use std::os::fd::OwnedFd;
+use std::os::unix::net::UnixStream;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use ureq::serde_json;
+
+/// Version of the sidecar protocol, bumped on incompatible changes
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The sidecar built into voxtype
+const SIDECAR_SCRIPT: &str = include_str!("../../scripts/faster-whisper-sidecar.py");
+
+/// How long loading may take; the first load of a model downloads it
+const LOAD_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// How long a health check waits for the sidecar to answer
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a transcription may take, before adding time per second of audio
+const TRANSCRIBE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Request to the sidecar, one JSON object per line
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request {
+    Load {
+        protocol: u32,
+        model: String,
+        device: String,
+        compute_type: String,
+        download_root: PathBuf,
+        /// CPU threads, 0 for the default
+        threads: usize,
+    },
+    Ping,
+    /// Followed by `samples` f32 samples
+    Transcribe {
+        samples: usize,
+        /// None to detect the language
+        language: Option<String>,
+        /// Languages to detect among, when more than one is configured
+        languages: Vec<String>,
+        task: &'static str,
+        initial_prompt: Option<String>,
+    },
+}
+
+/// Response from the sidecar, one JSON object per line
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Response {
+    Ready {
+        protocol: u32,
+    },
+    Pong,
+    Result {
+        #[serde(default)]
+        language: Option<String>,
+        segments: Vec<SidecarSegment>,
+    },
+    Error {
+        message: String,
+    },
+    /// Sent by a newer sidecar of the same protocol version
+    #[serde(other)]
+    Unknown,
+}
+
+/// A transcribed segment, with times in seconds
+#[derive(Debug, serde::Deserialize)]
+struct SidecarSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// Why a request to the sidecar failed
+#[derive(Debug)]
+enum Failure {
+    /// The sidecar answered with an error; it's still usable
+    Reported(String),
+    /// The sidecar exited or closed the connection
+    Crashed(String),
+    /// The sidecar didn't answer in time
+    TimedOut,
+}
+
+impl Failure {
+    fn into_error(self) -> TranscribeError {
+        match self {
+            Failure::Reported(message) => TranscribeError::InferenceFailed(message),
+            Failure::Crashed(reason) => TranscribeError::InferenceFailed(format!(
+                "faster-whisper sidecar failed: {} (see the log for its output)",
+                reason
+            )),
+            Failure::TimedOut => TranscribeError::InferenceFailed(
+                "faster-whisper sidecar stopped responding".to_string(),
+            ),
+        }
+    }
+}
+
+/// A running sidecar process
+struct Sidecar {
+    child: Child,
+    /// Our end of the socket, for writing requests
+    writer: SocketWriter,
+    /// Our end of the socket, for reading responses
+    reader: BufReader<UnixStream>,
+}
+
+impl Sidecar {
+    /// Start a sidecar and load the model in it
+    fn start(config: &WhisperConfig) -> Result<Self, TranscribeError> {
+        let socket_err = |e: std::io::Error| {
+            TranscribeError::InitFailed(format!("faster-whisper sidecar socket failed: {}", e))
+        };
+        let (socket, sidecar_end) = UnixStream::pair().map_err(socket_err)?;
+        let stdin = sidecar_end.try_clone().map_err(socket_err)?;
+
+        let mut cmd = sidecar_command(config)?;
+        cmd.stdin(Stdio::from(OwnedFd::from(stdin)))
+            .stdout(Stdio::from(OwnedFd::from(sidecar_end)))
+            .stderr(Stdio::inherit())
+            // Its own process group, so killing it also kills what a custom
+            // command started
+            .process_group(0);
+        let child = cmd.spawn().map_err(|e| {
+            TranscribeError::InitFailed(format!("Failed to start faster-whisper sidecar: {}", e))
+        })?;
+        tracing::debug!("Started faster-whisper sidecar (pid {})", child.id());
+
+        let writer = socket
+            .try_clone()
+            .and_then(SocketWriter::new)
+            .map_err(socket_err)?;
+        let mut sidecar = Self {
+            child,
+            writer,
+            reader: BufReader::new(socket),
+        };
+
+        let download_root = Config::models_dir().join("faster-whisper");
+        let model = if super::models::is_path(&config.model) {
+            config::expand_home(&config.model)
+                .to_string_lossy()
+                .into_owned()
+        } else {
+            crate::model_cache::mark_used(&download_root);
+            config.model.clone()
+        };
+        let load = Request::Load {
+            protocol: PROTOCOL_VERSION,
+            model,
+            device: config
+                .faster_whisper_device
+                .clone()
+                .unwrap_or_else(|| "auto".to_string()),
+            compute_type: config
+                .faster_whisper_compute_type
+                .clone()
+                .unwrap_or_else(|| "default".to_string()),
+            download_root,
+            threads: config.threads.unwrap_or(0),
+        };
+        match sidecar.request(&load, &[], LOAD_TIMEOUT) {
+            Ok(Response::Ready { protocol }) if protocol == PROTOCOL_VERSION => Ok(sidecar),
+            Ok(Response::Ready { protocol }) => Err(TranscribeError::InitFailed(format!(
+                "faster-whisper sidecar speaks protocol version {}, expected {}",
+                protocol, PROTOCOL_VERSION
+            ))),
+            Ok(other) => Err(TranscribeError::InitFailed(format!(
+                "Unexpected response from faster-whisper sidecar: {:?}",
+                other
+            ))),
+            Err(Failure::Reported(message)) => Err(TranscribeError::InitFailed(message)),
+            Err(Failure::Crashed(reason)) => Err(TranscribeError::InitFailed(format!(
+                "faster-whisper sidecar failed to load the model: {} (see the log for its output)",
+                reason
+            ))),
+            Err(Failure::TimedOut) => Err(TranscribeError::InitFailed(format!(
+                "faster-whisper sidecar didn't load the model within {} minutes",
+                LOAD_TIMEOUT.as_secs() / 60
+            ))),
+        }
+    }
+
+    /// Send a request, with its audio if any, and read the response
+    fn request(
+        &mut self,
+        request: &Request,
+        audio: &[f32],
+        timeout: Duration,
+    ) -> Result<Response, Failure> {
+        let mut message = serde_json::to_vec(request)
+            .map_err(|e| Failure::Reported(format!("Failed to encode request: {}", e)))?;
+        message.push(b'\n');
+        message.extend(audio.iter().flat_map(|sample| sample.to_le_bytes()));
+        self.writer
+            .write_all(&message)
+            .map_err(|e| Failure::Crashed(format!("failed to send request: {}", e)))?;
+
+        self.reader
+            .get_ref()
+            .set_read_timeout(Some(timeout))
+            .map_err(|e| Failure::Crashed(e.to_string()))?;
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return Err(Failure::Crashed("it exited".to_string())),
+                Ok(_) => {}
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Err(Failure::TimedOut)
+                }
+                Err(e) => return Err(Failure::Crashed(e.to_string())),
+            }
+            let response: Response = serde_json::from_str(&line).map_err(|e| {
+                Failure::Crashed(format!("unreadable response {:?}: {}", line.trim_end(), e))
+            })?;
+            match response {
+                Response::Unknown => continue,
+                Response::Error { message } => return Err(Failure::Reported(message)),
+                response => return Ok(response),
+            }
+        }
+    }
+
+    /// Whether the process has exited
+    fn has_exited(&mut self) -> bool {
+        match self.child.try_wait() {
+            Ok(Some(status)) => {
+                tracing::warn!("faster-whisper sidecar exited with {}", status);
+                true
+            }
+            Ok(None) => false,
+            Err(_) => true,
+        }
+    }
+
+    /// Ask the sidecar whether it's still responding
+    fn is_healthy(&mut self) -> bool {
+        !self.has_exited()
+            && matches!(
+                self.request(&Request::Ping, &[], PING_TIMEOUT),
+                Ok(Response::Pong)
+            )
+    }
+}
+
+impl Drop for Sidecar {
+    fn drop(&mut self) {
+        kill_group(self.child.id());
+        let _ = self.child.wait();
+    }
+}
+
+/// Kill a sidecar and everything in its process group
+fn kill_group(pid: u32) {
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+/// The command that starts the sidecar
+fn sidecar_command(config: &WhisperConfig) -> Result<Command, TranscribeError> {
+    if let Some(ref command) = config.faster_whisper_command {
+        // The sidecar may download the model, through the proxy
+        let mut cmd = crate::proxy::command("sh");
+        cmd.arg("-c").arg(command);
+        return Ok(cmd);
+    }
+
+    let dir = Config::runtime_dir();
+    let script = dir.join("faster-whisper-sidecar.py");
+    std::fs::create_dir_all(&dir)
+        .and_then(|()| std::fs::write(&script, SIDECAR_SCRIPT))
+        .map_err(|e| {
+            TranscribeError::InitFailed(format!(
+                "Failed to write faster-whisper sidecar to {:?}: {}",
+                script, e
+            ))
+        })?;
+    let python = config
+        .faster_whisper_python
+        .as_deref()
+        .map(config::expand_home)
+        .unwrap_or_else(|| PathBuf::from("python3"));
+    let mut cmd = crate::proxy::command(python);
+    cmd.arg(script);
+    Ok(cmd)
+}
+
+/// Transcriber backed by a faster-whisper sidecar process
+pub struct FasterWhisperTranscriber {
+    config: WhisperConfig,
+    /// The sidecar, None until started or after it was stopped
+    sidecar: Mutex<Option<Sidecar>>,
+    /// Pid of the sidecar while it transcribes, 0 otherwise
+    busy_pid: AtomicU32,
+    /// Set by `cancel()` for the transcription in progress
+    cancelled: AtomicBool,
+    /// When the sidecar last transcribed, for `unload_after_secs`
+    last_used: Mutex<Instant>,
+    /// Time spent starting the sidecar during the last transcription
+    last_load_time: Mutex<Option<Duration>>,
+}
+
+impl FasterWhisperTranscriber {
+    /// Start the sidecar and load the model
+    pub fn new(config: &WhisperConfig) -> Result<Self, TranscribeError> {
+        tracing::info!(
+            "Starting faster-whisper sidecar with model '{}'",
+            config.model
+        );
+        let start = Instant::now();
+        let sidecar = Sidecar::start(config)?;
+        tracing::info!(
+            "faster-whisper sidecar ready in {:.2}s",
+            start.elapsed().as_secs_f32()
+        );
+        Ok(Self {
+            config: config.clone(),
+            sidecar: Mutex::new(Some(sidecar)),
+            busy_pid: AtomicU32::new(0),
+            cancelled: AtomicBool::new(false),
+            last_used: Mutex::new(Instant::now()),
+            last_load_time: Mutex::new(None),
+        })
+    }
+
+    /// The running sidecar, started again if it has exited
+    fn running<'a>(
+        &self,
+        slot: &'a mut Option<Sidecar>,
+    ) -> Result<&'a mut Sidecar, TranscribeError> {
+        if slot.as_mut().is_some_and(Sidecar::has_exited) {
+            *slot = None;
+        }
+        if slot.is_none() {
+            tracing::info!("Starting faster-whisper sidecar");
+            let start = Instant::now();
+            *slot = Some(Sidecar::start(&self.config)?);
+            *self.last_load_time.lock().unwrap() = Some(start.elapsed());
+        }
+        Ok(slot.as_mut().unwrap())
+    }
+
+    /// The transcribe request for a recording
+    fn transcribe_request(&self, samples: usize, context: Option<&str>) -> Request {
+        let language = &self.config.language;
+        Request::Transcribe {
+            samples,
+            language: (!language.is_auto() && !language.is_multiple())
+                .then(|| language.primary().to_string()),
+            languages: if language.is_multiple() {
+                language.as_vec()
+            } else {
+                Vec::new()
+            },
+            task: if self.config.translate {
+                "translate"
+            } else {
+                "transcribe"
+            },
+            initial_prompt: prompt_with_context(self.config.initial_prompt.as_deref(), context),
+        }
+    }
+
+    /// Transcribe with the sidecar, restarting it once if it crashes
+    fn run(
+        &self,
+        samples: &[f32],
+        context: Option<&str>,
+    ) -> Result<Vec<SidecarSegment>, TranscribeError> {
+        if samples.is_empty() {
+            return Err(TranscribeError::AudioFormat(
+                "Empty audio buffer".to_string(),
+            ));
+        }
+
+        let duration = Duration::from_secs_f32(samples.len() as f32 / 16000.0);
+        let timeout = TRANSCRIBE_TIMEOUT + duration * 3;
+        let request = self.transcribe_request(samples.len(), context);
+
+        let mut slot = self.sidecar.lock().unwrap();
+        *self.last_load_time.lock().unwrap() = None;
+        self.cancelled.store(false, Ordering::SeqCst);
+        let start = Instant::now();
+        let mut retried = false;
+        loop {
+            let sidecar = self.running(&mut slot)?;
+            self.busy_pid.store(sidecar.child.id(), Ordering::SeqCst);
+            let result = sidecar.request(&request, samples, timeout);
+            self.busy_pid.store(0, Ordering::SeqCst);
+            *self.last_used.lock().unwrap() = Instant::now();
+
+            let failure = match result {
+                Ok(Response::Result { language, segments }) => {
+                    tracing::debug!(
+                        "faster-whisper transcribed {:.2}s of audio in {:.2}s (language: {})",
+                        duration.as_secs_f32(),
+                        start.elapsed().as_secs_f32(),
+                        language.as_deref().unwrap_or("unknown")
+                    );
+                    return Ok(segments);
+                }
+                Ok(other) => Failure::Crashed(format!("unexpected response {:?}", other)),
+                Err(failure) => failure,
+            };
+            if self.cancelled.load(Ordering::SeqCst) {
+                *slot = None;
+                return Err(TranscribeError::Cancelled);
+            }
+            match failure {
+                Failure::Reported(_) => return Err(failure.into_error()),
+                Failure::Crashed(ref reason) if !retried => {
+                    tracing::warn!(
+                        "faster-whisper sidecar failed ({}), restarting it and retrying",
+                        reason
+                    );
+                    *slot = None;
+                    retried = true;
+                }
+                _ => {
+                    *slot = None;
+                    return Err(failure.into_error());
+                }
+            }
+        }
+    }
+}
+
+impl Transcriber for FasterWhisperTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        let segments = self.run(samples, None)?;
+        Ok(join_text(&segments))
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        _progress: Option<super::Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        let segments = self.run(samples, Some(context))?;
+        Ok(join_text(&segments))
+    }
+
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        Ok(to_segments(self.run(samples, None)?))
+    }
+
+    fn cancel(&self) {
+        let pid = self.busy_pid.load(Ordering::SeqCst);
+        if pid == 0 {
+            return;
+        }
+        tracing::info!(
+            "Cancelling transcription, stopping faster-whisper sidecar (pid {})",
+            pid
+        );
+        self.cancelled.store(true, Ordering::SeqCst);
+        kill_group(pid);
+    }
+
+    /// Check that the sidecar still answers, restarting it if not, so the
+    /// recording about to be made doesn't wait for it
+    fn prepare(&self) {
+        let Ok(mut slot) = self.sidecar.try_lock() else {
+            return; // Busy transcribing
+        };
+        if slot.as_mut().is_some_and(|sidecar| !sidecar.is_healthy()) {
+            tracing::warn!("faster-whisper sidecar is not responding, restarting it");
+            *slot = None;
+        }
+        if let Err(e) = self.running(&mut slot) {
+            tracing::warn!("Failed to start faster-whisper sidecar: {}", e);
+        }
+    }
+
+    fn last_model_load_time(&self) -> Option<Duration> {
+        *self.last_load_time.lock().unwrap()
+    }
+
+    fn unload_if_idle(&self) {
+        if self.config.unload_after_secs == 0 {
+            return;
+        }
+        let Ok(mut slot) = self.sidecar.try_lock() else {
+            return;
+        };
+        let idle = self.last_used.lock().unwrap().elapsed();
+        if slot.is_some() && idle >= Duration::from_secs(self.config.unload_after_secs) {
+            tracing::info!(
+                "Stopping faster-whisper sidecar (unused for {}s)",
+                idle.as_secs()
+            );
+            *slot = None;
+        }
+    }
+}
+
+/// The text of the segments, as one transcription
+fn join_text(segments: &[SidecarSegment]) -> String {
+    segments
+        .iter()
+        .map(|segment| segment.text.as_str())
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Convert the sidecar's segments, dropping empty ones
+fn to_segments(segments: Vec<SidecarSegment>) -> Vec<Segment> {
+    segments
+        .into_iter()
+        .filter(|segment| !segment.text.trim().is_empty())
+        .map(|segment| Segment {
+            start_ms: (segment.start.max(0.0) * 1000.0).round() as u64,
+            end_ms: (segment.end.max(0.0) * 1000.0).round() as u64,
+            text: segment.text.trim().to_string(),
+            speaker: None,
+            words: Vec::new(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A stand-in sidecar speaking the protocol, answering every recording
+    /// with "Hello world"; it exits on its first recording if `crash_once`
+    /// names a file that doesn't exist yet, creating it
+    const FAKE_SIDECAR: &str = r#"
+read -r load
+echo '{"type":"ready","protocol":1}'
+while read -r line; do
+    case "$line" in
+        *'"ping"'*) echo '{"type":"pong"}' ;;
+        *'"transcribe"'*)
+            n=$(echo "$line" | sed 's/.*"samples":\([0-9]*\).*/\1/')
+            if [ -n "$CRASH_ONCE" ] && [ ! -e "$CRASH_ONCE" ]; then
+                touch "$CRASH_ONCE"
+                exit 1
+            fi
+            head -c $((n * 4)) > /dev/null
+            echo '{"type":"result","language":"en","segments":[{"start":0.0,"end":0.5,"text":" Hello"},{"start":0.5,"end":1.0,"text":" world"}]}'
+            ;;
+    esac
+done
+"#;
+
+    fn fake_config(dir: &TempDir, crash_once: bool) -> WhisperConfig {
+        let script = dir.path().join("sidecar.sh");
+        std::fs::write(&script, FAKE_SIDECAR).unwrap();
+        let crash = if crash_once {
+            format!("CRASH_ONCE={:?} ", dir.path().join("crashed"))
+        } else {
+            String::new()
+        };
+        WhisperConfig {
+            mode: Some(crate::config::WhisperMode::FasterWhisper),
+            model: "/nonexistent/ct2-model".to_string(),
+            faster_whisper_command: Some(format!("{}sh {:?}", crash, script)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_request_encoding() {
+        let config = WhisperConfig {
+            language: crate::config::LanguageConfig::Multiple(vec![
+                "en".to_string(),
+                "de".to_string(),
+            ]),
+            translate: true,
+            initial_prompt: Some("Voxtype.".to_string()),
+            ..Default::default()
+        };
+        let transcriber = FasterWhisperTranscriber {
+            config,
+            sidecar: Mutex::new(None),
+            busy_pid: AtomicU32::new(0),
+            cancelled: AtomicBool::new(false),
+            last_used: Mutex::new(Instant::now()),
+            last_load_time: Mutex::new(None),
+        };
+        let request = transcriber.transcribe_request(16000, Some("Slack"));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "type": "transcribe",
+                "samples": 16000,
+                "language": null,
+                "languages": ["en", "de"],
+                "task": "translate",
+                "initial_prompt": "Voxtype. Slack",
+            })
+        );
+
+        let response: Response = serde_json::from_str(
+            r#"{"type":"result","language":"en","segments":[{"start":0.25,"end":1.5,"text":" Hi. "},{"start":1.5,"end":2.0,"text":" "}]}"#,
+        )
+        .unwrap();
+        let Response::Result { segments, .. } = response else {
+            panic!("expected a result");
+        };
+        assert_eq!(join_text(&segments), "Hi.");
+        let segments = to_segments(segments);
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start_ms, segments[0].end_ms), (250, 1500));
+    }
+
+    #[test]
+    fn test_transcribe_with_sidecar() {
+        let dir = TempDir::new().unwrap();
+        let transcriber = FasterWhisperTranscriber::new(&fake_config(&dir, false)).unwrap();
+        let samples = vec![0.0f32; 16000];
+        assert_eq!(transcriber.transcribe(&samples).unwrap(), "Hello world");
+        let segments = transcriber.transcribe_segments(&samples).unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].start_ms, 500);
+
+        // A sidecar that went away is started again
+        transcriber.sidecar.lock().unwrap().take();
+        transcriber.prepare();
+        assert!(transcriber.sidecar.lock().unwrap().is_some());
+        assert_eq!(transcriber.transcribe(&samples).unwrap(), "Hello world");
+    }
+
+    #[test]
+    fn test_restart_after_crash() {
+        let dir = TempDir::new().unwrap();
+        let transcriber = FasterWhisperTranscriber::new(&fake_config(&dir, true)).unwrap();
+        let samples = vec![0.0f32; 8000];
+        assert_eq!(transcriber.transcribe(&samples).unwrap(), "Hello world");
+        assert!(dir.path().join("crashed").exists());
+        assert!(transcriber.last_model_load_time().is_some());
+    }
+}
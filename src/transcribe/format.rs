@@ -0,0 +1,321 @@
+//! Formatting of timestamped transcription segments
+//!
+//! Renders [`Segment`]s as plain text, `[00:12] text` lines, SRT, WebVTT,
+//! or JSON with word-level timing. Diarized segments are prefixed with their
+//! speaker (`Speaker 1: text`).
+
+use super::{Segment, Word};
+use serde_json::json;
+
+/// Output format for timestamped transcriptions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SegmentFormat {
+    /// Plain text without timestamps
+    #[default]
+    Text,
+    /// One `[MM:SS] text` line per segment
+    Timestamps,
+    /// SubRip subtitles
+    Srt,
+    /// WebVTT subtitles
+    Vtt,
+    /// JSON with segments and, where the engine reports them, word timings
+    Json,
+}
+
+impl std::str::FromStr for SegmentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(SegmentFormat::Text),
+            "timestamps" | "timestamped" => Ok(SegmentFormat::Timestamps),
+            "srt" => Ok(SegmentFormat::Srt),
+            "vtt" | "webvtt" => Ok(SegmentFormat::Vtt),
+            "json" => Ok(SegmentFormat::Json),
+            _ => Err(format!(
+                "Unknown format '{}'. Valid options: text, timestamps, srt, vtt, json",
+                s
+            )),
+        }
+    }
+}
+
+/// Render segments in the requested format
+pub fn format_segments(segments: &[Segment], format: SegmentFormat) -> String {
+    match format {
+        SegmentFormat::Text => format_text(segments),
+        SegmentFormat::Timestamps => segments
+            .iter()
+            .map(|s| format!("[{}] {}\n", format_short_time(s.start_ms), labelled_text(s)))
+            .collect(),
+        SegmentFormat::Srt => segments
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                format!(
+                    "{}\n{} --> {}\n{}\n\n",
+                    i + 1,
+                    format_clock_time(s.start_ms, ','),
+                    format_clock_time(s.end_ms, ','),
+                    labelled_text(s)
+                )
+            })
+            .collect(),
+        SegmentFormat::Vtt => {
+            let mut output = String::from("WEBVTT\n\n");
+            for s in segments {
+                output.push_str(&format!(
+                    "{} --> {}\n{}\n\n",
+                    format_clock_time(s.start_ms, '.'),
+                    format_clock_time(s.end_ms, '.'),
+                    labelled_text(s)
+                ));
+            }
+            output
+        }
+        SegmentFormat::Json => {
+            let json = json!({
+                "text": format_text(segments),
+                "segments": segments.iter().map(segment_json).collect::<Vec<_>>(),
+            });
+            format!("{:#}\n", json)
+        }
+    }
+}
+
+/// A segment as JSON, times in seconds
+fn segment_json(segment: &Segment) -> serde_json::Value {
+    let mut json = json!({
+        "start": seconds(segment.start_ms),
+        "end": seconds(segment.end_ms),
+        "text": segment.text,
+        "words": words_json(&segment.words),
+    });
+    if let Some(ref speaker) = segment.speaker {
+        json["speaker"] = json!(speaker);
+    }
+    json
+}
+
+/// Words as JSON in the OpenAI API's shape (`word`, `start`, `end`)
+pub fn words_json(words: &[Word]) -> Vec<serde_json::Value> {
+    words
+        .iter()
+        .map(|w| {
+            json!({
+                "word": w.text,
+                "start": seconds(w.start_ms),
+                "end": seconds(w.end_ms),
+            })
+        })
+        .collect()
+}
+
+/// Milliseconds as fractional seconds
+fn seconds(ms: u64) -> f64 {
+    ms as f64 / 1000.0
+}
+
+/// Plain text, with one line per speaker turn when segments are diarized
+fn format_text(segments: &[Segment]) -> String {
+    if segments.iter().all(|s| s.speaker.is_none()) {
+        return segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut last_speaker: Option<&str> = None;
+    for (i, segment) in segments.iter().enumerate() {
+        let speaker = segment.speaker.as_deref();
+        if i > 0 && speaker == last_speaker {
+            if let Some(line) = lines.last_mut() {
+                line.push(' ');
+                line.push_str(&segment.text);
+            }
+        } else {
+            lines.push(labelled_text(segment));
+        }
+        last_speaker = speaker;
+    }
+    lines.join("\n")
+}
+
+/// Segment text prefixed with its speaker, if known
+fn labelled_text(segment: &Segment) -> String {
+    match segment.speaker {
+        Some(ref speaker) => format!("{}: {}", speaker, segment.text),
+        None => segment.text.clone(),
+    }
+}
+
+/// Format milliseconds as `MM:SS`, or `HH:MM:SS` past the first hour
+pub fn format_short_time(ms: u64) -> String {
+    let secs = ms / 1000;
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    let seconds = secs % 60;
+    if hours > 0 {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// Format milliseconds as `HH:MM:SS<sep>mmm` (`,` for SRT, `.` for VTT)
+fn format_clock_time(ms: u64, millis_sep: char) -> String {
+    let total_secs = ms / 1000;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_secs / 3600,
+        (total_secs % 3600) / 60,
+        total_secs % 60,
+        millis_sep,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments() -> Vec<Segment> {
+        vec![
+            Segment {
+                start_ms: 0,
+                end_ms: 2500,
+                text: "Hello there.".to_string(),
+                speaker: None,
+                words: Vec::new(),
+            },
+            Segment {
+                start_ms: 12_000,
+                end_ms: 3_725_040,
+                text: "Long pause.".to_string(),
+                speaker: None,
+                words: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_format_text() {
+        assert_eq!(
+            format_segments(&segments(), SegmentFormat::Text),
+            "Hello there. Long pause."
+        );
+    }
+
+    #[test]
+    fn test_format_timestamps() {
+        assert_eq!(
+            format_segments(&segments(), SegmentFormat::Timestamps),
+            "[00:00] Hello there.\n[00:12] Long pause.\n"
+        );
+    }
+
+    #[test]
+    fn test_format_srt() {
+        assert_eq!(
+            format_segments(&segments(), SegmentFormat::Srt),
+            "1\n00:00:00,000 --> 00:00:02,500\nHello there.\n\n\
+             2\n00:00:12,000 --> 01:02:05,040\nLong pause.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_format_vtt() {
+        let vtt = format_segments(&segments(), SegmentFormat::Vtt);
+        assert!(vtt.starts_with("WEBVTT\n\n"));
+        assert!(vtt.contains("00:00:12.000 --> 01:02:05.040\nLong pause.\n"));
+    }
+
+    fn diarized() -> Vec<Segment> {
+        [
+            ("Speaker 1", "Hi."),
+            ("Speaker 1", "Ready?"),
+            ("Speaker 2", "Yes."),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, (speaker, text))| Segment {
+            start_ms: i as u64 * 1000,
+            end_ms: i as u64 * 1000 + 900,
+            text: text.to_string(),
+            speaker: Some(speaker.to_string()),
+            words: Vec::new(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_format_text_with_speakers() {
+        assert_eq!(
+            format_segments(&diarized(), SegmentFormat::Text),
+            "Speaker 1: Hi. Ready?\nSpeaker 2: Yes."
+        );
+    }
+
+    #[test]
+    fn test_format_srt_with_speakers() {
+        let srt = format_segments(&diarized(), SegmentFormat::Srt);
+        assert!(srt.contains("00:00:02,900\nSpeaker 2: Yes.\n"));
+        assert_eq!(
+            format_segments(&diarized(), SegmentFormat::Timestamps)
+                .lines()
+                .next(),
+            Some("[00:00] Speaker 1: Hi.")
+        );
+    }
+
+    #[test]
+    fn test_format_json() {
+        let mut segments = segments();
+        segments[0].words = vec![
+            Word {
+                start_ms: 0,
+                end_ms: 1200,
+                text: "Hello".to_string(),
+            },
+            Word {
+                start_ms: 1300,
+                end_ms: 2500,
+                text: "there.".to_string(),
+            },
+        ];
+        let output = format_segments(&segments, SegmentFormat::Json);
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json["text"], "Hello there. Long pause.");
+        assert_eq!(json["segments"][1]["start"], 12.0);
+        assert_eq!(json["segments"][0]["words"][1]["word"], "there.");
+        assert_eq!(json["segments"][0]["words"][1]["start"], 1.3);
+        assert_eq!(json["segments"][1]["words"], serde_json::json!([]));
+        assert!(json["segments"][0].get("speaker").is_none());
+        assert_eq!("json".parse::<SegmentFormat>(), Ok(SegmentFormat::Json));
+    }
+
+    #[test]
+    fn test_format_short_time_hours() {
+        assert_eq!(format_short_time(59_999), "00:59");
+        assert_eq!(format_short_time(3_600_000), "01:00:00");
+    }
+
+    #[test]
+    fn test_segment_format_from_str() {
+        assert_eq!("SRT".parse(), Ok(SegmentFormat::Srt));
+        assert_eq!("webvtt".parse(), Ok(SegmentFormat::Vtt));
+        assert!("docx".parse::<SegmentFormat>().is_err());
+    }
+
+    #[test]
+    fn test_whole_clip_segment() {
+        let segs = Segment::whole_clip("  hi  ".to_string(), 32_000);
+        assert_eq!(segs.len(), 1);
+        assert_eq!(segs[0].end_ms, 2000);
+        assert_eq!(segs[0].text, "hi");
+        assert!(Segment::whole_clip(" ".to_string(), 16_000).is_empty());
+    }
+}
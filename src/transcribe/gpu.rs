@@ -0,0 +1,135 @@
+//! Runtime choice between the GPU and the CPU for whisper.cpp
+//!
+//! The GPU backend is fixed at build time (`gpu-cuda`, `gpu-vulkan`,
+//! `gpu-hipblas`, ...); packages ship one binary per backend and
+//! `voxtype setup gpu` switches between them. At load time
+//! `whisper.gpu_backend` decides whether this binary's GPU backend is used:
+//! "cpu" forces the CPU, and CUDA and ROCm builds fall back to the CPU when
+//! their kernel driver isn't loaded. A preference the binary wasn't built
+//! for is logged with a hint, and the built-in backend is used.
+
+use crate::config::GpuBackend;
+use std::path::Path;
+
+/// GPU backend this binary was built with (None for CPU-only builds)
+pub fn compiled_backend() -> Option<GpuBackend> {
+    if cfg!(feature = "gpu-metal") {
+        Some(GpuBackend::Metal)
+    } else if cfg!(feature = "gpu-cuda") {
+        Some(GpuBackend::Cuda)
+    } else if cfg!(feature = "gpu-hipblas") {
+        Some(GpuBackend::Rocm)
+    } else if cfg!(feature = "gpu-vulkan") {
+        Some(GpuBackend::Vulkan)
+    } else {
+        None
+    }
+}
+
+/// Whether the NVIDIA kernel driver is loaded
+pub fn nvidia_driver_loaded() -> bool {
+    Path::new("/proc/driver/nvidia/version").exists()
+}
+
+/// Whether the AMD compute driver (amdkfd, used by ROCm) is loaded
+pub fn rocm_driver_loaded() -> bool {
+    Path::new("/dev/kfd").exists()
+}
+
+/// Whether the kernel driver `backend` needs is loaded (Vulkan goes
+/// through the graphics driver, so it's assumed present)
+fn driver_loaded(backend: Option<GpuBackend>) -> bool {
+    match backend {
+        Some(GpuBackend::Cuda) => nvidia_driver_loaded(),
+        Some(GpuBackend::Rocm) => rocm_driver_loaded(),
+        _ => true,
+    }
+}
+
+/// Whether whisper.cpp should use the GPU
+///
+/// `gpu_build` is whether whisper-rs was built with any GPU backend.
+pub fn use_gpu(preference: GpuBackend, gpu_build: bool) -> bool {
+    let compiled = compiled_backend();
+    let (use_gpu, message) = decide(preference, gpu_build, compiled, driver_loaded(compiled));
+    if let Some(message) = message {
+        tracing::warn!("{}", message);
+    }
+    tracing::debug!(
+        "whisper.cpp on {} (gpu_backend = {})",
+        if use_gpu { "GPU" } else { "CPU" },
+        preference
+    );
+    use_gpu
+}
+
+fn decide(
+    preference: GpuBackend,
+    gpu_build: bool,
+    compiled: Option<GpuBackend>,
+    driver: bool,
+) -> (bool, Option<String>) {
+    if preference == GpuBackend::Cpu || !gpu_build {
+        let message =
+            (preference != GpuBackend::Auto && preference != GpuBackend::Cpu).then(|| {
+                format!(
+                    "whisper.gpu_backend = \"{}\" but this voxtype build has no GPU support; \
+                 running on CPU (switch with: sudo voxtype setup gpu --enable)",
+                    preference
+                )
+            });
+        return (false, message);
+    }
+
+    if !driver {
+        let message = match compiled {
+            Some(GpuBackend::Rocm) => {
+                "This voxtype build uses ROCm but /dev/kfd is missing (amdgpu driver not loaded); \
+                 running on CPU"
+            }
+            _ => "This voxtype build uses CUDA but no NVIDIA driver is loaded; running on CPU",
+        };
+        return (false, Some(message.to_string()));
+    }
+
+    let message = match compiled {
+        Some(compiled) if preference != GpuBackend::Auto && preference != compiled => {
+            Some(format!(
+                "whisper.gpu_backend = \"{}\" but this voxtype build uses {}; \
+                 switch binaries with: sudo voxtype setup gpu --enable",
+                preference, compiled
+            ))
+        }
+        _ => None,
+    };
+    (true, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide() {
+        use GpuBackend::*;
+
+        // CPU builds and the cpu preference never use the GPU
+        assert_eq!(decide(Auto, false, None, true), (false, None));
+        assert_eq!(decide(Cpu, true, Some(Vulkan), true), (false, None));
+        assert!(!decide(Cuda, false, None, true).0);
+        assert!(decide(Cuda, false, None, true).1.is_some());
+
+        // CUDA and ROCm need their kernel driver
+        assert_eq!(decide(Auto, true, Some(Cuda), true), (true, None));
+        assert!(!decide(Auto, true, Some(Cuda), false).0);
+        let (gpu, message) = decide(Rocm, true, Some(Rocm), false);
+        assert!(!gpu);
+        assert!(message.unwrap().contains("/dev/kfd"));
+
+        // A preference for another backend keeps the built-in one, with a hint
+        let (gpu, message) = decide(Cuda, true, Some(Vulkan), true);
+        assert!(gpu);
+        assert!(message.unwrap().contains("uses vulkan"));
+        assert_eq!(decide(Vulkan, true, Some(Vulkan), true), (true, None));
+    }
+}
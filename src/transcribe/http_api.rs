@@ -0,0 +1,796 @@
+//! HTTP transcription API for `voxtype serve --http`
+//!
+//! Lets other tools on the network use the worker's loaded model over HTTP,
+//! including anything that speaks the OpenAI transcription API:
+//! - `POST /v1/audio/transcriptions`: multipart upload with a `file` field and
+//!   an optional `response_format` (`json`, `text`, `verbose_json`, `srt` or
+//!   `vtt`). Other fields such as `model` and `language` are ignored; the
+//!   worker's own config decides them.
+//! - `POST /transcribe`: a WAV file as the request body, returns `{"text": ...}`
+//! - `GET /health`: returns `{"status": "ok"}`
+//!
+//! With a `worker_token` configured, transcription requests need an
+//! `Authorization: Bearer <token>` header, so a daemon with `mode = "remote"`
+//! uses the token as its `remote_api_key`; it is checked before the body is
+//! read. Each connection carries one request. Connections are served
+//! concurrently, up to [`remote_worker::MAX_CONNECTIONS`], and their
+//! transcriptions take turns on the loaded model.
+//!
+//! The server is deliberately small rather than built on hyper: it shares the
+//! worker's blocking, thread-per-connection serving, TLS and connection limit
+//! instead of bringing an async HTTP stack into the worker. The request
+//! header is parsed by `httparse` (hyper's parser); what's left is kept
+//! strict: one request per connection, no keep-alive or chunked bodies, a
+//! single Content-Length, bounded header and body sizes, and a deadline for
+//! the header.
+
+use super::format::{format_segments, words_json, SegmentFormat};
+use super::remote_worker::{self, tokens_match};
+use super::{Segment, Transcriber};
+use crate::error::TranscribeError;
+use std::io::{self, Cursor, Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use ureq::serde_json::{self, json};
+
+/// Largest request header accepted
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// Time a client has to send the request header, however slowly it trickles in
+const HEADER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest request body accepted (almost an hour of 16kHz 16-bit audio)
+const MAX_BODY_BYTES: usize = 100 * 1024 * 1024;
+
+/// Sample rates accepted for uploaded audio
+const SAMPLE_RATES: std::ops::RangeInclusive<u32> = 8000..=192_000;
+
+/// Values accepted for `response_format`
+const RESPONSE_FORMATS: [&str; 5] = ["json", "text", "verbose_json", "srt", "vtt"];
+
+/// An HTTP request
+struct Request {
+    method: String,
+    /// Path without the query string
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// Value of a header, matched case-insensitively
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// An HTTP response
+#[derive(Debug)]
+struct Response {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(status: u16, value: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: value.to_string().into_bytes(),
+        }
+    }
+
+    fn text(content_type: &'static str, text: String) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body: text.into_bytes(),
+        }
+    }
+
+    /// Error in the shape the OpenAI API uses
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        let kind = if status >= 500 {
+            "server_error"
+        } else {
+            "invalid_request_error"
+        };
+        Self::json(
+            status,
+            json!({ "error": { "message": message.into(), "type": kind } }),
+        )
+    }
+}
+
+/// Reason phrase for the status codes the API returns
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
+
+/// Position of `needle` in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Serve the HTTP API until the process exits
+pub(super) fn serve(
+    listener: TcpListener,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    transcriber: Arc<dyn Transcriber>,
+    token: Option<String>,
+) {
+    let connections = remote_worker::Connections::default();
+    for tcp in listener.incoming() {
+        let tcp = match tcp {
+            Ok(tcp) => tcp,
+            Err(e) => {
+                tracing::warn!("Failed to accept HTTP connection: {}", e);
+                continue;
+            }
+        };
+        let (tls, transcriber, token) = (tls.clone(), Arc::clone(&transcriber), token.clone());
+        let spawned = connections.spawn(move || {
+            let result = remote_worker::accept(tcp, tls.as_ref()).and_then(|mut conn| {
+                handle_connection(&mut conn, transcriber.as_ref(), token.as_deref())
+            });
+            if let Err(e) = result {
+                tracing::warn!("HTTP request failed: {}", e);
+            }
+        });
+        if !spawned {
+            tracing::warn!(
+                "Refusing HTTP connection: already serving {} connections",
+                remote_worker::MAX_CONNECTIONS
+            );
+        }
+    }
+}
+
+/// Answer the request sent over one connection
+fn handle_connection<C: Read + Write>(
+    conn: &mut C,
+    transcriber: &dyn Transcriber,
+    token: Option<&str>,
+) -> io::Result<()> {
+    let response = match read_request(conn, token) {
+        Ok(request) => respond(&request, transcriber),
+        Err(response) => response,
+    };
+    write!(
+        conn,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    )?;
+    conn.write_all(&response.body)?;
+    conn.flush()
+}
+
+/// Read one request, or the error response to send instead
+///
+/// Transcription requests without the right token are refused as soon as
+/// the headers are in, without reading (or asking for) the body.
+fn read_request<C: Read + Write>(conn: &mut C, token: Option<&str>) -> Result<Request, Response> {
+    let closed = || Response::error(400, "Connection closed before the request ended");
+
+    // Read up to the end of the headers
+    let started = Instant::now();
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_len = loop {
+        if let Some(pos) = find(&buf, b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEADER_BYTES {
+            return Err(Response::error(413, "Request headers too large"));
+        }
+        if started.elapsed() > HEADER_TIMEOUT {
+            return Err(Response::error(408, "Request headers took too long"));
+        }
+        let n = conn.read(&mut chunk).map_err(|_| closed())?;
+        if n == 0 {
+            return Err(closed());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut parsed = httparse::Request::new(&mut headers);
+    match parsed.parse(&buf[..header_len]) {
+        Ok(httparse::Status::Complete(_)) => {}
+        Ok(httparse::Status::Partial) => return Err(closed()),
+        Err(e) => return Err(Response::error(400, format!("Invalid request: {}", e))),
+    }
+    let path = parsed.path.unwrap_or("/");
+    let mut request = Request {
+        method: parsed.method.unwrap_or_default().to_string(),
+        path: path
+            .split_once('?')
+            .map_or(path, |(path, _)| path)
+            .to_string(),
+        headers: parsed
+            .headers
+            .iter()
+            .map(|h| {
+                let value = String::from_utf8_lossy(h.value).into_owned();
+                (h.name.to_string(), value)
+            })
+            .collect(),
+        body: buf.split_off(header_len),
+    };
+
+    if request.header("transfer-encoding").is_some() {
+        return Err(Response::error(
+            411,
+            "Chunked requests aren't supported; send a Content-Length",
+        ));
+    }
+    let mut lengths = request
+        .headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("content-length"));
+    let length = match (lengths.next(), lengths.next()) {
+        (Some((_, value)), None) => value
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| Response::error(400, "Invalid Content-Length"))?,
+        (None, _) => 0,
+        (Some(_), Some(_)) => return Err(Response::error(400, "More than one Content-Length")),
+    };
+    if length > MAX_BODY_BYTES {
+        return Err(Response::error(413, "Request body too large"));
+    }
+    authorize(&request, token)?;
+
+    // curl waits for this before sending larger uploads
+    if request
+        .header("expect")
+        .is_some_and(|v| v.eq_ignore_ascii_case("100-continue"))
+    {
+        conn.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+            .map_err(|_| closed())?;
+    }
+    if request.body.len() < length {
+        let missing = (length - request.body.len()) as u64;
+        conn.take(missing)
+            .read_to_end(&mut request.body)
+            .map_err(|_| closed())?;
+        if request.body.len() < length {
+            return Err(closed());
+        }
+    }
+    request.body.truncate(length);
+    Ok(request)
+}
+
+/// Check the token of a transcription request
+fn authorize(request: &Request, token: Option<&str>) -> Result<(), Response> {
+    let Some(expected) = token else {
+        return Ok(());
+    };
+    if !matches!(
+        request.path.as_str(),
+        "/v1/audio/transcriptions" | "/transcribe"
+    ) {
+        return Ok(());
+    }
+    let given = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if !given.is_some_and(|given| tokens_match(expected, given.trim())) {
+        return Err(Response::error(401, "Wrong or missing API key"));
+    }
+    Ok(())
+}
+
+/// Route a request to its endpoint (already authorized by `read_request`)
+fn respond(request: &Request, transcriber: &dyn Transcriber) -> Response {
+    tracing::debug!("{} {}", request.method, request.path);
+    match request.path.as_str() {
+        "/health" if request.method == "GET" => {
+            return Response::json(200, json!({ "status": "ok" }))
+        }
+        "/health" => return Response::error(405, "Use GET"),
+        "/v1/audio/transcriptions" | "/transcribe" => {}
+        path => return Response::error(404, format!("Unknown endpoint {}", path)),
+    }
+    if request.method != "POST" {
+        return Response::error(405, "Use POST");
+    }
+
+    let result = if request.path == "/transcribe" {
+        transcribe(transcriber, &request.body, "json")
+    } else {
+        transcribe_upload(request, transcriber)
+    };
+    result.unwrap_or_else(|response| response)
+}
+
+/// Transcribe an OpenAI-style multipart upload
+fn transcribe_upload(
+    request: &Request,
+    transcriber: &dyn Transcriber,
+) -> Result<Response, Response> {
+    let boundary = request
+        .header("content-type")
+        .and_then(multipart_boundary)
+        .ok_or_else(|| Response::error(400, "Expected a multipart/form-data upload"))?;
+    let fields = multipart_fields(&request.body, &boundary).map_err(|e| Response::error(400, e))?;
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, data)| *data)
+    };
+
+    let file = field("file").ok_or_else(|| Response::error(400, "Missing the 'file' field"))?;
+    let format = field("response_format")
+        .map(|data| String::from_utf8_lossy(data).trim().to_string())
+        .unwrap_or_else(|| "json".to_string());
+    transcribe(transcriber, file, &format)
+}
+
+/// Transcribe a WAV file, answering in the requested format
+fn transcribe(
+    transcriber: &dyn Transcriber,
+    wav: &[u8],
+    format: &str,
+) -> Result<Response, Response> {
+    if !RESPONSE_FORMATS.contains(&format) {
+        return Err(Response::error(
+            400,
+            format!(
+                "Unknown response_format '{}'. Valid options: {}",
+                format,
+                RESPONSE_FORMATS.join(", ")
+            ),
+        ));
+    }
+    let samples = decode_wav(wav).map_err(|e| Response::error(400, e))?;
+    if samples.is_empty() {
+        return Err(Response::error(400, "Empty audio"));
+    }
+    tracing::info!(
+        "Transcribing {:.2}s of audio for an HTTP client",
+        samples.len() as f32 / 16000.0
+    );
+
+    let failed = |e: TranscribeError| Response::error(500, format!("Transcription failed: {}", e));
+    Ok(match format {
+        "json" => Response::json(
+            200,
+            json!({ "text": transcriber.transcribe(&samples).map_err(failed)? }),
+        ),
+        "text" => Response::text(
+            "text/plain; charset=utf-8",
+            transcriber.transcribe(&samples).map_err(failed)?,
+        ),
+        _ => {
+            let segments = transcriber.transcribe_segments(&samples).map_err(failed)?;
+            match format {
+                "srt" => Response::text(
+                    "text/plain; charset=utf-8",
+                    format_segments(&segments, SegmentFormat::Srt),
+                ),
+                "vtt" => Response::text(
+                    "text/vtt; charset=utf-8",
+                    format_segments(&segments, SegmentFormat::Vtt),
+                ),
+                _ => Response::json(200, verbose_json(&segments, samples.len())),
+            }
+        }
+    })
+}
+
+/// The OpenAI API's `verbose_json` response
+///
+/// Includes `words` when the engine reports word timing.
+fn verbose_json(all_segments: &[Segment], sample_count: usize) -> serde_json::Value {
+    let text: Vec<&str> = all_segments.iter().map(|s| s.text.as_str()).collect();
+    let segments: Vec<serde_json::Value> = all_segments
+        .iter()
+        .enumerate()
+        .map(|(id, s)| {
+            json!({
+                "id": id,
+                "start": s.start_ms as f64 / 1000.0,
+                "end": s.end_ms as f64 / 1000.0,
+                "text": s.text,
+            })
+        })
+        .collect();
+    let words: Vec<serde_json::Value> = all_segments
+        .iter()
+        .flat_map(|s| words_json(&s.words))
+        .collect();
+    let mut response = json!({
+        "task": "transcribe",
+        "duration": sample_count as f64 / 16000.0,
+        "text": text.join(" "),
+        "segments": segments,
+    });
+    if !words.is_empty() {
+        response["words"] = json!(words);
+    }
+    response
+}
+
+/// Decode a WAV file to mono 16kHz samples
+fn decode_wav(data: &[u8]) -> Result<Vec<f32>, String> {
+    let reader = hound::WavReader::new(Cursor::new(data))
+        .map_err(|e| format!("Unsupported audio, expected a WAV file: {}", e))?;
+    let spec = reader.spec();
+    if !SAMPLE_RATES.contains(&spec.sample_rate) {
+        return Err(format!(
+            "Unsupported sample rate {} Hz, expected {} to {} Hz",
+            spec.sample_rate,
+            SAMPLE_RATES.start(),
+            SAMPLE_RATES.end()
+        ));
+    }
+    if spec.channels == 0 {
+        return Err("Unsupported audio: no channels".to_string());
+    }
+
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Int => {
+            let max_val = 2f32.powi(spec.bits_per_sample as i32 - 1);
+            reader
+                .into_samples::<i32>()
+                .filter_map(|s| s.ok())
+                .map(|s| s as f32 / max_val)
+                .collect()
+        }
+        hound::SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .filter_map(|s| s.ok())
+            .collect(),
+    };
+
+    // Mix to mono if stereo
+    let mono: Vec<f32> = if spec.channels > 1 {
+        samples
+            .chunks(spec.channels as usize)
+            .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
+            .collect()
+    } else {
+        samples
+    };
+    Ok(crate::audio::cpal_capture::resample(
+        &mono,
+        spec.sample_rate,
+        16000,
+    ))
+}
+
+/// Boundary of a `multipart/form-data` content type
+fn multipart_boundary(content_type: &str) -> Option<String> {
+    let (mime, params) = content_type.split_once(';')?;
+    if !mime.trim().eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    params.split(';').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("boundary")
+            .then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Field name from a part's Content-Disposition header
+fn field_name(headers: &str) -> Option<String> {
+    let disposition = headers.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("content-disposition")
+            .then_some(value)
+    })?;
+    disposition.split(';').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        (key.trim() == "name").then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Named fields of a `multipart/form-data` body
+fn multipart_fields<'a>(body: &'a [u8], boundary: &str) -> Result<Vec<(String, &'a [u8])>, String> {
+    let malformed = || "Malformed multipart body".to_string();
+    let delimiter = format!("\r\n--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+
+    // The first delimiter comes without the line break
+    let start = find(body, &delimiter[2..]).ok_or_else(malformed)?;
+    let mut rest = &body[start + delimiter.len() - 2..];
+    let mut fields = Vec::new();
+    while !rest.starts_with(b"--") {
+        let part = rest.strip_prefix(b"\r\n").ok_or_else(malformed)?;
+        let headers_end = find(part, b"\r\n\r\n").ok_or_else(malformed)?;
+        let headers = String::from_utf8_lossy(&part[..headers_end]);
+        let content = &part[headers_end + 4..];
+        let end = find(content, delimiter).ok_or_else(malformed)?;
+        if let Some(name) = field_name(&headers) {
+            fields.push((name, &content[..end]));
+        }
+        rest = &content[end + delimiter.len()..];
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WhisperConfig;
+
+    /// Transcriber that returns the sample count
+    struct Counting;
+
+    impl Transcriber for Counting {
+        fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+            Ok(format!("{} samples", samples.len()))
+        }
+    }
+
+    fn wav(samples: usize) -> Vec<u8> {
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut buffer = Cursor::new(Vec::new());
+        let mut writer = hound::WavWriter::new(&mut buffer, spec).unwrap();
+        for _ in 0..samples {
+            writer.write_sample(1000i16).unwrap();
+        }
+        writer.finalize().unwrap();
+        buffer.into_inner()
+    }
+
+    fn request(method: &str, path: &str, headers: &[(&str, &str)], body: Vec<u8>) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: headers
+                .iter()
+                .map(|(n, v)| (n.to_string(), v.to_string()))
+                .collect(),
+            body,
+        }
+    }
+
+    fn upload(format: &str) -> Request {
+        let mut body = b"--XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.wav\"\r\nContent-Type: audio/wav\r\n\r\n".to_vec();
+        body.extend_from_slice(&wav(8000));
+        body.extend_from_slice(
+            b"\r\n--XyZ\r\nContent-Disposition: form-data; name=\"response_format\"\r\n\r\n",
+        );
+        body.extend_from_slice(format.as_bytes());
+        body.extend_from_slice(b"\r\n--XyZ--\r\n");
+        request(
+            "POST",
+            "/v1/audio/transcriptions",
+            &[("Content-Type", "multipart/form-data; boundary=XyZ")],
+            body,
+        )
+    }
+
+    #[test]
+    fn test_multipart() {
+        assert_eq!(
+            multipart_boundary("multipart/form-data; boundary=\"abc\"").as_deref(),
+            Some("abc")
+        );
+        assert_eq!(multipart_boundary("application/json"), None);
+
+        let body = b"--b\r\nContent-Disposition: form-data; name=\"model\"\r\n\r\nwhisper-1\r\n--b\r\ncontent-disposition: form-data; filename=\"x\"; name=\"file\"\r\n\r\na\r\n-b\r\n--b--\r\n";
+        let fields = multipart_fields(body, "b").unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0], ("model".to_string(), &b"whisper-1"[..]));
+        assert_eq!(fields[1], ("file".to_string(), &b"a\r\n-b"[..]));
+
+        assert!(multipart_fields(b"--b\r\nno end", "b").is_err());
+    }
+
+    #[test]
+    fn test_routes() {
+        let health = request("GET", "/health", &[], vec![]);
+        assert_eq!(respond(&health, &Counting).status, 200);
+        assert!(authorize(&health, Some("t")).is_ok());
+
+        let unknown = respond(&request("POST", "/v2/x", &[], vec![]), &Counting);
+        assert_eq!(unknown.status, 404);
+
+        let get = respond(&request("GET", "/transcribe", &[], vec![]), &Counting);
+        assert_eq!(get.status, 405);
+
+        let raw = request(
+            "POST",
+            "/transcribe",
+            &[("authorization", "Bearer t")],
+            wav(100),
+        );
+        assert!(authorize(&raw, Some("t")).is_ok());
+        assert_eq!(authorize(&raw, Some("u")).unwrap_err().status, 401);
+        let response = respond(&raw, &Counting);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, br#"{"text":"100 samples"}"#);
+
+        let not_wav = request("POST", "/transcribe", &[], b"hello".to_vec());
+        assert_eq!(authorize(&not_wav, Some("t")).unwrap_err().status, 401);
+        assert_eq!(respond(&not_wav, &Counting).status, 400);
+    }
+
+    /// Connection reading from `input`, recording what is written
+    struct Stream {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Stream {
+        fn new(input: &[u8]) -> Self {
+            Self {
+                input: Cursor::new(input.to_vec()),
+                output: Vec::new(),
+            }
+        }
+    }
+
+    impl Read for Stream {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Stream {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_token_checked_before_body() {
+        let head =
+            b"POST /transcribe HTTP/1.1\r\nContent-Length: 1000\r\nExpect: 100-continue\r\n\r\n";
+        let mut conn = Stream::new(head);
+        handle_connection(&mut conn, &Counting, Some("t")).unwrap();
+        let output = String::from_utf8(conn.output).unwrap();
+        assert!(
+            output.starts_with("HTTP/1.1 401 Unauthorized\r\n"),
+            "{}",
+            output
+        );
+        assert!(!output.contains("100 Continue"), "{}", output);
+
+        // The health check needs no token
+        let mut conn = Stream::new(b"GET /health HTTP/1.1\r\n\r\n");
+        handle_connection(&mut conn, &Counting, Some("t")).unwrap();
+        assert!(conn.output.starts_with(b"HTTP/1.1 200 OK\r\n"));
+
+        // With the token, the client is asked for the body
+        let mut input =
+            b"POST /transcribe HTTP/1.1\r\nAuthorization: Bearer t\r\nExpect: 100-continue\r\n"
+                .to_vec();
+        let body = wav(100);
+        input.extend_from_slice(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes());
+        input.extend_from_slice(&body);
+        let mut conn = Stream::new(&input);
+        handle_connection(&mut conn, &Counting, Some("t")).unwrap();
+        let output = String::from_utf8(conn.output).unwrap();
+        assert!(
+            output.starts_with("HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK"),
+            "{}",
+            output
+        );
+        assert!(output.ends_with(r#"{"text":"100 samples"}"#), "{}", output);
+    }
+
+    #[test]
+    fn test_duplicate_content_length() {
+        let mut conn = Stream::new(
+            b"POST /transcribe HTTP/1.1\r\nContent-Length: 4\r\nContent-Length: 400\r\n\r\nRIFF",
+        );
+        handle_connection(&mut conn, &Counting, None).unwrap();
+        let output = String::from_utf8(conn.output).unwrap();
+        assert!(
+            output.starts_with("HTTP/1.1 400 Bad Request\r\n"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("More than one Content-Length"),
+            "{}",
+            output
+        );
+    }
+
+    #[test]
+    fn test_decode_wav_sample_rates() {
+        let wav_at = |sample_rate| {
+            let spec = hound::WavSpec {
+                channels: 2,
+                sample_rate,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            };
+            let mut buffer = Cursor::new(Vec::new());
+            let mut writer = hound::WavWriter::new(&mut buffer, spec).unwrap();
+            for _ in 0..200 {
+                writer.write_sample(1000i16).unwrap();
+            }
+            writer.finalize().unwrap();
+            buffer.into_inner()
+        };
+        // 100 stereo frames at 8kHz are 200 samples at 16kHz
+        assert_eq!(decode_wav(&wav_at(8000)).unwrap().len(), 200);
+        assert!(decode_wav(&wav_at(48000)).is_ok());
+        assert!(decode_wav(&wav_at(192_000)).is_ok());
+        assert!(decode_wav(&wav_at(1)).unwrap_err().contains("sample rate"));
+        assert!(decode_wav(&wav_at(400_000))
+            .unwrap_err()
+            .contains("sample rate"));
+    }
+
+    #[test]
+    fn test_response_formats() {
+        let text = respond(&upload("text"), &Counting);
+        assert_eq!(text.body, b"8000 samples");
+
+        let srt = respond(&upload("srt"), &Counting);
+        assert!(String::from_utf8(srt.body)
+            .unwrap()
+            .contains("8000 samples"));
+
+        let verbose = respond(&upload("verbose_json"), &Counting);
+        let json: serde_json::Value = serde_json::from_slice(&verbose.body).unwrap();
+        assert_eq!(json["duration"], 0.5);
+        assert_eq!(json["segments"][0]["text"], "8000 samples");
+
+        let unknown = respond(&upload("xml"), &Counting);
+        assert_eq!(unknown.status, 400);
+    }
+
+    #[test]
+    fn test_remote_transcriber_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut tcp, _) = listener.accept().unwrap();
+            handle_connection(&mut tcp, &Counting, Some("secret"))
+        });
+
+        let config = WhisperConfig {
+            mode: Some(crate::config::WhisperMode::Remote),
+            remote_endpoint: Some(format!("http://{}", address)),
+            remote_api_key: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let transcriber = super::super::remote::RemoteTranscriber::new(&config).unwrap();
+        assert_eq!(
+            transcriber.transcribe(&[0.1; 1600]).unwrap(),
+            "1600 samples"
+        );
+        server.join().unwrap().unwrap();
+    }
+}
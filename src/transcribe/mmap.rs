@@ -0,0 +1,93 @@
+//! Read-only memory mapping of model files
+//!
+//! The gpu_isolation worker is a new process for every recording, so it
+//! loads the model every time. Loading from a mapping of the model file reads
+//! it straight from the page cache: after the first recording the file is
+//! cached, and the load is a copy in memory instead of a stream of reads.
+//! The kernel is asked to read the whole file ahead, which speeds up the first
+//! load too. whisper.cpp copies the model out of the mapping while loading,
+//! so the mapping is only needed until the context exists.
+
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+/// A file mapped into memory, unmapped on drop
+pub struct MappedFile {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl MappedFile {
+    /// Map the whole file read-only
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty file"));
+        }
+
+        // SAFETY: maps `len` bytes of an open file; the mapping stays valid
+        // after the file is closed and is unmapped only on drop
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        // Only a hint; loading works without it
+        // SAFETY: advises on the mapping created above
+        unsafe {
+            libc::madvise(ptr, len, libc::MADV_SEQUENTIAL);
+            libc::madvise(ptr, len, libc::MADV_WILLNEED);
+        }
+        Ok(Self { ptr, len })
+    }
+
+    /// Contents of the file
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: the mapping is `len` readable bytes for as long as self lives
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        // SAFETY: unmaps the mapping created in `open`, which no slice
+        // outlives
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+// The mapping is read-only, so it can be read from any thread
+unsafe impl Send for MappedFile {}
+unsafe impl Sync for MappedFile {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapped_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.bin");
+        let contents: Vec<u8> = (0..10000u32).map(|i| i as u8).collect();
+        std::fs::write(&path, &contents).unwrap();
+        assert_eq!(MappedFile::open(&path).unwrap().as_slice(), &contents[..]);
+
+        std::fs::write(&path, b"").unwrap();
+        assert!(MappedFile::open(&path).is_err());
+        assert!(MappedFile::open(&dir.path().join("missing.bin")).is_err());
+    }
+}
@@ -3,20 +3,35 @@
 //! Provides transcription via:
 //! - Local whisper.cpp inference (whisper-rs crate)
 //! - Remote OpenAI-compatible Whisper API (whisper.cpp server, OpenAI, etc.)
+//! - A voxtype worker on another machine (`voxtype serve`)
 //! - CLI subprocess using whisper-cli (fallback for glibc 2.42+ compatibility)
 //! - Subprocess isolation for GPU memory release
+//! - faster-whisper (CTranslate2) in a sidecar process voxtype manages
 //! - Optionally NVIDIA Parakeet via ONNX Runtime (when `parakeet` feature is enabled)
 //! - Optionally Moonshine via ONNX Runtime (when `moonshine` feature is enabled)
 //! - Optionally SenseVoice via ONNX Runtime (when `sensevoice` feature is enabled)
 //! - Optionally Paraformer via ONNX Runtime (when `paraformer` feature is enabled)
 //! - Optionally Dolphin via ONNX Runtime (when `dolphin` feature is enabled)
 //! - Optionally Omnilingual via ONNX Runtime (when `omnilingual` feature is enabled)
+//! - Optionally Whisper ONNX exports via ONNX Runtime, on CPU, GPU or NPU
+//!   (when `onnx` feature is enabled)
 
+pub mod chain;
 pub mod cli;
+pub mod faster_whisper;
+pub mod format;
+pub mod gpu;
+pub mod http_api;
+pub mod mmap;
+pub mod models;
 pub mod remote;
+pub mod remote_worker;
 pub mod subprocess;
+pub mod usage;
+pub mod vram;
 pub mod whisper;
 pub mod worker;
+pub mod wyoming;
 
 /// Shared log-mel filterbank feature extraction for ONNX-based ASR engines
 #[cfg(any(
@@ -54,9 +69,70 @@ pub mod dolphin;
 #[cfg(feature = "omnilingual")]
 pub mod omnilingual;
 
+#[cfg(feature = "onnx")]
+pub mod onnx;
+
 use crate::config::{Config, TranscriptionEngine, WhisperConfig, WhisperMode};
 use crate::error::TranscribeError;
 use crate::setup::gpu;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A transcribed span of audio with timing relative to the start of the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// Start time in milliseconds
+    pub start_ms: u64,
+    /// End time in milliseconds
+    pub end_ms: u64,
+    /// Transcribed text (trimmed)
+    pub text: String,
+    /// Speaker label from diarization (e.g. "Speaker 1")
+    pub speaker: Option<String>,
+    /// Word-level timing, empty when the engine doesn't report it
+    pub words: Vec<Word>,
+}
+
+/// A transcribed word with timing relative to the start of the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+    /// Start time in milliseconds
+    pub start_ms: u64,
+    /// End time in milliseconds
+    pub end_ms: u64,
+    /// The word, with any attached punctuation (trimmed)
+    pub text: String,
+}
+
+impl Segment {
+    /// Wrap a whole-clip transcription as a single segment
+    ///
+    /// Returns no segments for empty text.
+    pub fn whole_clip(text: String, num_samples: usize) -> Vec<Segment> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Vec::new();
+        }
+        vec![Segment {
+            start_ms: 0,
+            end_ms: (num_samples as u64 * 1000) / 16000,
+            text: text.to_string(),
+            speaker: None,
+            words: Vec::new(),
+        }]
+    }
+}
+
+/// Progress callback, called with the percent of audio processed so far
+pub type Progress = Arc<dyn Fn(u8) + Send + Sync>;
+
+/// The configured initial prompt followed by per-recording context
+pub(crate) fn prompt_with_context(prompt: Option<&str>, context: Option<&str>) -> Option<String> {
+    match (prompt, context) {
+        (Some(prompt), Some(context)) => Some(format!("{} {}", prompt.trim_end(), context)),
+        (prompt, context) => prompt.or(context).map(String::from),
+    }
+}
 
 /// Trait for speech-to-text implementations
 pub trait Transcriber: Send + Sync {
@@ -64,6 +140,86 @@ pub trait Transcriber: Send + Sync {
     /// Input: f32 samples, mono, 16kHz
     fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError>;
 
+    /// Transcribe audio samples to text, reporting progress as it goes
+    ///
+    /// Used by the daemon for long recordings so it can show that work is
+    /// still being done. Default implementation reports nothing.
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        let _ = progress;
+        self.transcribe(samples)
+    }
+
+    /// Transcribe with extra initial-prompt context for this recording
+    ///
+    /// The daemon passes the `[[whisper.app_prompts]]` text for the app
+    /// focused when recording started. Whisper transcribers add it after the
+    /// configured `initial_prompt`. Default implementation ignores it, for
+    /// engines without prompt support.
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        let _ = context;
+        match progress {
+            Some(progress) => self.transcribe_with_progress(samples, progress),
+            None => self.transcribe(samples),
+        }
+    }
+
+    /// Transcribe constrained to a GBNF grammar file
+    ///
+    /// The daemon passes `[whisper] spell_grammar` in spell mode, or the
+    /// `grammar` of the active profile. Only the whisper-cli backend supports
+    /// grammars; the default implementation warns and transcribes freely.
+    fn transcribe_with_grammar(
+        &self,
+        samples: &[f32],
+        grammar: &Path,
+    ) -> Result<String, TranscribeError> {
+        tracing::warn!(
+            "Ignoring grammar {:?}: grammars need [whisper] mode = \"cli\"",
+            grammar
+        );
+        self.transcribe(samples)
+    }
+
+    /// Transcribe audio samples into timestamped segments
+    ///
+    /// Used by file transcription (SRT/VTT output) and meeting mode.
+    /// Default implementation returns the whole transcription as a single
+    /// segment, for engines that don't report timestamps.
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        Ok(Segment::whole_clip(
+            self.transcribe(samples)?,
+            samples.len(),
+        ))
+    }
+
+    /// Stop the transcriptions in progress (optional)
+    ///
+    /// Called from the daemon when the user cancels a transcription, while
+    /// the transcription runs on another thread. Interrupted calls return
+    /// `TranscribeError::Cancelled`. Default implementation does nothing,
+    /// and the daemon discards the result when it arrives.
+    fn cancel(&self) {
+        // Default: no-op
+    }
+
+    /// How many transcriptions may run at once (default: 1)
+    ///
+    /// The daemon starts queued recordings in parallel up to this many and
+    /// still outputs their text in order. Transcribers sharing one loaded
+    /// model keep the default.
+    fn max_concurrent(&self) -> usize {
+        1
+    }
+
     /// Prepare for transcription (optional, called when recording starts)
     ///
     /// For subprocess-based transcribers, this spawns the worker process
@@ -75,10 +231,51 @@ pub trait Transcriber: Send + Sync {
     fn prepare(&self) {
         // Default: no-op
     }
+
+    /// Receive audio while it's being recorded (optional, after `prepare`)
+    ///
+    /// Called with each captured chunk, in order. The samples later passed
+    /// to `transcribe` start with this audio, so a transcriber can send it
+    /// ahead and only send the rest then. Default implementation ignores it.
+    fn feed(&self, samples: &[f32]) {
+        let _ = samples;
+    }
+
+    /// Model load time on the critical path of the last transcription
+    ///
+    /// Subprocess transcribers report how long the worker took to load the
+    /// model when it wasn't already loaded during recording. Default: None.
+    fn last_model_load_time(&self) -> Option<std::time::Duration> {
+        None
+    }
+
+    /// Free the model if it has been unused for too long (optional)
+    ///
+    /// Called periodically while the daemon is idle. The in-process
+    /// whisper transcriber drops its model here when `unload_after_secs`
+    /// has passed, and reloads it on the next `prepare` or transcription.
+    /// Default implementation does nothing.
+    fn unload_if_idle(&self) {
+        // Default: no-op
+    }
 }
 
 /// Factory function to create transcriber based on configured engine
-pub fn create_transcriber(config: &Config) -> Result<Box<dyn Transcriber>, TranscribeError> {
+///
+/// The engine is loaded and transcribes with the `[inference]` priority.
+pub fn create_transcriber(config: &Config) -> Result<Arc<dyn Transcriber>, TranscribeError> {
+    let transcriber = crate::priority::run(|| create_engine(config))?;
+    Ok(crate::priority::wrap(Arc::from(transcriber)))
+}
+
+/// Factory function to create the configured transcriber followed by the
+/// `[fallback]` transcribers, tried in order when one fails or times out
+pub fn create_transcriber_chain(config: &Config) -> Result<Arc<dyn Transcriber>, TranscribeError> {
+    let fallbacks = chain::Fallbacks::new(config)?;
+    Ok(fallbacks.wrap(create_transcriber(config)?))
+}
+
+fn create_engine(config: &Config) -> Result<Box<dyn Transcriber>, TranscribeError> {
     match config.engine {
         TranscriptionEngine::Whisper => create_whisper_transcriber(&config.whisper),
         #[cfg(feature = "parakeet")]
@@ -175,6 +372,19 @@ pub fn create_transcriber(config: &Config) -> Result<Box<dyn Transcriber>, Trans
             "Omnilingual engine requested but voxtype was not compiled with --features omnilingual"
                 .to_string(),
         )),
+        #[cfg(feature = "onnx")]
+        TranscriptionEngine::Onnx => {
+            let cfg = config.onnx.as_ref().ok_or_else(|| {
+                TranscribeError::InitFailed(
+                    "ONNX engine selected but [onnx] config section is missing".to_string(),
+                )
+            })?;
+            Ok(Box::new(onnx::OnnxTranscriber::new(cfg)?))
+        }
+        #[cfg(not(feature = "onnx"))]
+        TranscriptionEngine::Onnx => Err(TranscribeError::InitFailed(
+            "ONNX engine requested but voxtype was not compiled with --features onnx".to_string(),
+        )),
     }
 }
 
@@ -217,11 +427,26 @@ pub fn create_transcriber_with_config_path(
         }
         WhisperMode::Remote => {
             tracing::info!("Using remote whisper transcription mode");
-            Ok(Box::new(remote::RemoteTranscriber::new(config)?))
+            Ok(Box::new(usage::MeteredTranscriber::from_config(
+                config,
+                config_path,
+            )?))
+        }
+        WhisperMode::Worker => {
+            tracing::info!("Using remote voxtype worker transcription mode");
+            Ok(Box::new(remote_worker::RemoteWorkerTranscriber::new(
+                config,
+            )?))
         }
         WhisperMode::Cli => {
             tracing::info!("Using whisper-cli subprocess backend");
             Ok(Box::new(cli::CliTranscriber::new(config)?))
         }
+        WhisperMode::FasterWhisper => {
+            tracing::info!("Using faster-whisper sidecar backend");
+            Ok(Box::new(faster_whisper::FasterWhisperTranscriber::new(
+                config,
+            )?))
+        }
     }
 }
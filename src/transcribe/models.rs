@@ -0,0 +1,438 @@
+//! Whisper model catalog
+//!
+//! The one table of Whisper models voxtype knows by name: which ggml file
+//! each name stands for and where it's downloaded from. `whisper.model`,
+//! `voxtype setup model` and the download commands all resolve names here.
+//!
+//! A model missing from the models directory is downloaded on first use
+//! (`whisper.auto_download`) and kept there, so later loads are local.
+
+use crate::config::{Config, LanguageConfig};
+use crate::error::TranscribeError;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A Whisper model known by name
+pub struct WhisperModel {
+    /// Name used in config files and on the command line
+    pub name: &'static str,
+    /// Other names accepted for it
+    pub aliases: &'static [&'static str],
+    /// File name in the models directory
+    pub filename: &'static str,
+    /// Hugging Face repository holding the file
+    pub repo: &'static str,
+    /// File name in the repository, if it differs from `filename`
+    pub remote_filename: Option<&'static str>,
+    /// Transcribes English only, whatever language is configured
+    pub english_only: bool,
+}
+
+/// First bytes of a whisper.cpp model file ("ggml" as a little-endian u32)
+const GGML_MAGIC: [u8; 4] = *b"lmgg";
+
+/// Repository of the original whisper.cpp conversions
+const WHISPER_CPP_REPO: &str = "ggerganov/whisper.cpp";
+
+/// A model from the whisper.cpp repository
+const fn whisper_cpp(name: &'static str, filename: &'static str) -> WhisperModel {
+    WhisperModel {
+        name,
+        aliases: &[],
+        filename,
+        repo: WHISPER_CPP_REPO,
+        remote_filename: None,
+        english_only: false,
+    }
+}
+
+/// An English-only model from the whisper.cpp repository
+const fn whisper_cpp_en(name: &'static str, filename: &'static str) -> WhisperModel {
+    WhisperModel {
+        english_only: true,
+        ..whisper_cpp(name, filename)
+    }
+}
+
+/// A Distil-Whisper conversion: English only, about twice as fast on the CPU
+/// as the model it was distilled from
+const fn distil(
+    name: &'static str,
+    aliases: &'static [&'static str],
+    filename: &'static str,
+    repo: &'static str,
+    remote_filename: Option<&'static str>,
+) -> WhisperModel {
+    WhisperModel {
+        name,
+        aliases,
+        filename,
+        repo,
+        remote_filename,
+        english_only: true,
+    }
+}
+
+pub const WHISPER_MODELS: &[WhisperModel] = &[
+    whisper_cpp("tiny", "ggml-tiny.bin"),
+    whisper_cpp_en("tiny.en", "ggml-tiny.en.bin"),
+    whisper_cpp("base", "ggml-base.bin"),
+    whisper_cpp_en("base.en", "ggml-base.en.bin"),
+    whisper_cpp("small", "ggml-small.bin"),
+    whisper_cpp_en("small.en", "ggml-small.en.bin"),
+    whisper_cpp("medium", "ggml-medium.bin"),
+    whisper_cpp_en("medium.en", "ggml-medium.en.bin"),
+    WhisperModel {
+        aliases: &["large"],
+        ..whisper_cpp("large-v1", "ggml-large-v1.bin")
+    },
+    whisper_cpp("large-v2", "ggml-large-v2.bin"),
+    whisper_cpp("large-v3", "ggml-large-v3.bin"),
+    whisper_cpp("large-v3-turbo", "ggml-large-v3-turbo.bin"),
+    whisper_cpp("large-v3-turbo-q5_0", "ggml-large-v3-turbo-q5_0.bin"),
+    whisper_cpp("large-v3-turbo-q8_0", "ggml-large-v3-turbo-q8_0.bin"),
+    distil(
+        "distil-small.en",
+        &["distil-small"],
+        "ggml-distil-small.en.bin",
+        "distil-whisper/distil-small.en",
+        None,
+    ),
+    distil(
+        "distil-medium.en",
+        &["distil-medium"],
+        "ggml-distil-medium.en.bin",
+        "distil-whisper/distil-medium.en",
+        Some("ggml-medium-32-2.en.bin"),
+    ),
+    distil(
+        "distil-large-v2",
+        &[],
+        "ggml-distil-large-v2.bin",
+        "distil-whisper/distil-large-v2",
+        Some("ggml-large-32-2.en.bin"),
+    ),
+    distil(
+        "distil-large-v3",
+        &["distil-large"],
+        "ggml-distil-large-v3.bin",
+        "distil-whisper/distil-large-v3-ggml",
+        None,
+    ),
+];
+
+impl WhisperModel {
+    /// Download URL of the model file
+    pub fn url(&self) -> String {
+        format!(
+            "https://huggingface.co/{}/resolve/main/{}",
+            self.repo,
+            self.remote_filename.unwrap_or(self.filename)
+        )
+    }
+}
+
+/// Look up a model by name or alias
+pub fn find(name: &str) -> Option<&'static WhisperModel> {
+    WHISPER_MODELS
+        .iter()
+        .find(|model| model.name == name || model.aliases.contains(&name))
+}
+
+/// Whether `name` is the name (not an alias) of a known model
+pub fn is_known(name: &str) -> bool {
+    WHISPER_MODELS.iter().any(|model| model.name == name)
+}
+
+/// Names of all known models
+pub fn names() -> Vec<&'static str> {
+    WHISPER_MODELS.iter().map(|model| model.name).collect()
+}
+
+/// Whether a model, given by name, file name or path, transcribes English only
+///
+/// Models outside the catalog are English-only if their name says so
+/// ("small.en", "ggml-small.en-q5_1.bin").
+pub fn is_english_only(model: &str) -> bool {
+    let file = Path::new(model)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(model);
+    if let Some(known) = find(model).or_else(|| WHISPER_MODELS.iter().find(|m| m.filename == file))
+    {
+        return known.english_only;
+    }
+    let stem = file
+        .strip_prefix("ggml-")
+        .unwrap_or(file)
+        .trim_end_matches(".bin");
+    stem.ends_with(".en") || stem.contains(".en-")
+}
+
+/// The language to transcribe a model's audio in
+///
+/// English-only models get English: they can't transcribe anything else, and
+/// distilled ones produce nonsense when asked to detect the language.
+pub fn model_language(model_path: &Path, language: &LanguageConfig) -> LanguageConfig {
+    let english = LanguageConfig::Single("en".to_string());
+    if *language == english || !is_english_only(&model_path.to_string_lossy()) {
+        return language.clone();
+    }
+    tracing::warn!(
+        "Model {:?} transcribes English only, ignoring language {}",
+        model_path,
+        language.as_vec().join(",")
+    );
+    english
+}
+
+/// File name for a model; anything not in the catalog is taken as a file name
+pub fn model_filename(model: &str) -> String {
+    find(model)
+        .map(|known| known.filename)
+        .unwrap_or(model)
+        .to_string()
+}
+
+/// Download URL for a model
+pub fn model_url(model: &str) -> String {
+    match find(model) {
+        Some(known) => known.url(),
+        None => format!(
+            "https://huggingface.co/{}/resolve/main/{}",
+            WHISPER_CPP_REPO, model
+        ),
+    }
+}
+
+/// Whether a model setting is a path to a file rather than a name
+///
+/// Paths are used as they are, whatever the file is called, so fine-tuned
+/// models don't have to be renamed into the `ggml-<name>.bin` scheme.
+pub fn is_path(model: &str) -> bool {
+    model.contains('/')
+}
+
+/// Resolve a model name or path to a model file
+///
+/// A path ("~/" for the home directory) must lead to a whisper.cpp model.
+/// Names and file names are looked up in the models directory, the current
+/// directory and `./models/`. With `download`, a known model found nowhere
+/// is downloaded to the models directory.
+pub fn resolve(model: &str, download: bool) -> Result<PathBuf, TranscribeError> {
+    if is_path(model) {
+        let path = crate::config::expand_home(model);
+        // Relative paths may also be relative to the models directory
+        let in_models_dir = Config::models_dir().join(&path);
+        let path = if path.is_relative() && !path.exists() && in_models_dir.exists() {
+            in_models_dir
+        } else {
+            path
+        };
+        check_model_file(&path)?;
+        return Ok(path);
+    }
+
+    let known = find(model);
+    let model_filename = match known {
+        Some(known) => known.filename,
+        // If it looks like a filename, use it as-is
+        None if model.ends_with(".bin") => model,
+        None => {
+            return Err(TranscribeError::ModelNotFound(format!(
+                "Unknown model: '{}'. Valid models: {}",
+                model,
+                names().join(", ")
+            )));
+        }
+    };
+
+    let models_dir = Config::models_dir();
+    let model_path = models_dir.join(model_filename);
+    let cwd_path = PathBuf::from(model_filename);
+    let local_models_path = PathBuf::from("models").join(model_filename);
+    for candidate in [&model_path, &cwd_path, &local_models_path] {
+        if candidate.exists() {
+            return Ok(candidate.clone());
+        }
+    }
+
+    if let (Some(known), true) = (known, download) {
+        download_to(known, &model_path)?;
+        return Ok(model_path);
+    }
+
+    Err(TranscribeError::ModelNotFound(format!(
+        "Model '{}' not found. Looked in:\n  - {}\n  - {}\n  - {}\n\nDownload from: {}",
+        model,
+        model_path.display(),
+        cwd_path.display(),
+        local_models_path.display(),
+        model_url(model)
+    )))
+}
+
+/// Check that a file is a whisper.cpp model, to fail with a clear message
+/// rather than a load error from deep inside whisper.cpp
+pub fn check_model_file(path: &Path) -> Result<(), TranscribeError> {
+    let mut magic = [0u8; 4];
+    match File::open(path).and_then(|mut file| file.read_exact(&mut magic)) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(TranscribeError::ModelNotFound(
+            format!("Model file {} does not exist", path.display()),
+        )),
+        Err(e) => Err(TranscribeError::InitFailed(format!(
+            "Cannot read model file {}: {}",
+            path.display(),
+            e
+        ))),
+        Ok(()) if magic != GGML_MAGIC => Err(TranscribeError::InitFailed(format!(
+            "{} is not a whisper.cpp model (ggml format). Convert fine-tuned models with \
+             whisper.cpp's models/convert-h5-to-ggml.py",
+            path.display()
+        ))),
+        Ok(()) => Ok(()),
+    }
+}
+
+/// Download a model file with curl, in place only once it's complete
+fn download_to(model: &WhisperModel, path: &Path) -> Result<(), TranscribeError> {
+    let url = model.url();
+    tracing::info!("Downloading model '{}' from {}", model.name, url);
+    let failed = |reason: String| {
+        TranscribeError::ModelNotFound(format!("Model '{}' not downloaded: {}", model.name, reason))
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| failed(e.to_string()))?;
+    }
+    // Unique per process, so concurrent workers don't write the same file
+    let partial = path.with_extension(format!("part.{}", std::process::id()));
+    let status = crate::proxy::command("curl")
+        .args(["-fL", "--silent", "--show-error", "-o"])
+        .arg(&partial)
+        .arg(&url)
+        .status()
+        .map_err(|e| failed(format!("curl not available: {}", e)))?;
+    if !status.success() {
+        let _ = std::fs::remove_file(&partial);
+        return Err(failed(format!(
+            "curl exited with code {}",
+            status.code().unwrap_or(-1)
+        )));
+    }
+    std::fs::rename(&partial, path).map_err(|e| failed(e.to_string()))?;
+
+    tracing::info!("Saved model '{}' to {:?}", model.name, path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_by_name_and_alias() {
+        assert_eq!(
+            find("large-v3-turbo").unwrap().filename,
+            "ggml-large-v3-turbo.bin"
+        );
+        assert_eq!(find("large").unwrap().name, "large-v1");
+        assert!(find("nonexistent-model").is_none());
+        // Aliases resolve, but only names are listed
+        assert!(is_known("large-v1"));
+        assert!(!is_known("large"));
+        assert!(names().contains(&"distil-small.en"));
+        assert_eq!(find("distil-large").unwrap().name, "distil-large-v3");
+    }
+
+    #[test]
+    fn test_is_english_only() {
+        assert!(is_english_only("base.en"));
+        assert!(!is_english_only("base"));
+        // Distilled models are English-only without saying so
+        assert!(is_english_only("distil-large-v3"));
+        assert!(is_english_only("/models/ggml-distil-large-v3.bin"));
+        assert!(!is_english_only("/models/ggml-large-v3-turbo.bin"));
+        // Outside the catalog, the name decides
+        assert!(is_english_only("/models/ggml-small.en-q5_1.bin"));
+        assert!(!is_english_only("custom.bin"));
+    }
+
+    #[test]
+    fn test_model_language() {
+        let auto = LanguageConfig::Single("auto".to_string());
+        let english = LanguageConfig::Single("en".to_string());
+        let distil = Path::new("/models/ggml-distil-small.en.bin");
+        assert_eq!(model_language(distil, &auto), english);
+        assert_eq!(
+            model_language(
+                distil,
+                &LanguageConfig::Multiple(vec!["de".into(), "en".into()])
+            ),
+            english
+        );
+        assert_eq!(
+            model_language(Path::new("/models/ggml-base.bin"), &auto),
+            auto
+        );
+    }
+
+    #[test]
+    fn test_model_filename_and_url() {
+        assert_eq!(model_filename("base.en"), "ggml-base.en.bin");
+        assert_eq!(model_filename("custom.bin"), "custom.bin");
+
+        let url = model_url("base.en");
+        assert!(url.contains("huggingface.co"));
+        assert!(url.contains("ggml-base.en.bin"));
+        assert_eq!(
+            model_url("distil-large-v3"),
+            "https://huggingface.co/distil-whisper/distil-large-v3-ggml/resolve/main/ggml-distil-large-v3.bin"
+        );
+        // Saved under a name that says what it is, whatever the repository calls it
+        assert_eq!(
+            model_filename("distil-medium.en"),
+            "ggml-distil-medium.en.bin"
+        );
+        assert!(model_url("distil-medium.en").ends_with("/ggml-medium-32-2.en.bin"));
+    }
+
+    #[test]
+    fn test_catalog_entries_are_unique() {
+        let mut seen = std::collections::HashSet::new();
+        for model in WHISPER_MODELS {
+            assert!(seen.insert(model.name), "Duplicate name {}", model.name);
+            for alias in model.aliases {
+                assert!(seen.insert(alias), "Duplicate alias {}", alias);
+            }
+            assert!(model.filename.ends_with(".bin"));
+        }
+    }
+
+    #[test]
+    fn test_resolve_unknown_model() {
+        let err = resolve("nonexistent-model", true).unwrap_err();
+        assert!(err.to_string().contains("Unknown model"));
+    }
+
+    #[test]
+    fn test_resolve_model_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // Any file name will do for a path
+        let finetune = dir.path().join("medical-finetune-q5");
+        std::fs::write(&finetune, b"lmgg\x01\x00\x00\x00").unwrap();
+        let model = finetune.to_str().unwrap();
+        assert!(is_path(model));
+        assert_eq!(resolve(model, false).unwrap(), finetune);
+
+        let missing = dir.path().join("missing.bin");
+        let err = resolve(missing.to_str().unwrap(), true).unwrap_err();
+        assert!(matches!(err, TranscribeError::ModelNotFound(_)));
+
+        let not_ggml = dir.path().join("model.safetensors");
+        std::fs::write(&not_ggml, b"{\"__metadata__\": {}}").unwrap();
+        let err = resolve(not_ggml.to_str().unwrap(), false).unwrap_err();
+        assert!(err.to_string().contains("not a whisper.cpp model"));
+    }
+}
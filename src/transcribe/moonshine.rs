@@ -57,6 +57,7 @@ impl MoonshineTranscriber {
     /// Create a new Moonshine transcriber
     pub fn new(config: &MoonshineConfig) -> Result<Self, TranscribeError> {
         let model_dir = resolve_model_path(&config.model)?;
+        crate::model_cache::mark_used(&model_dir);
         let quantized = config.quantized;
 
         tracing::info!(
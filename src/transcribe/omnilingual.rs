@@ -33,6 +33,7 @@ pub struct OmnilingualTranscriber {
 impl OmnilingualTranscriber {
     pub fn new(config: &OmnilingualConfig) -> Result<Self, TranscribeError> {
         let model_dir = resolve_model_path(&config.model)?;
+        crate::model_cache::mark_used(&model_dir);
 
         tracing::info!("Loading Omnilingual model from {:?}", model_dir);
         let start = std::time::Instant::now();
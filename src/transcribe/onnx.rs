@@ -0,0 +1,903 @@
+//! Whisper via ONNX Runtime
+//!
+//! Runs ONNX exports of Whisper (and fine-tunes or distilled variants of it)
+//! through ONNX Runtime instead of whisper.cpp. ONNX Runtime's execution
+//! providers reach hardware whisper.cpp doesn't: Intel NPUs through OpenVINO
+//! and Qualcomm NPUs through QNN, as well as NVIDIA and AMD GPUs.
+//!
+//! Expects the Hugging Face Optimum export layout, as published by
+//! onnx-community (e.g. onnx-community/whisper-base):
+//! - encoder_model.onnx: log-mel features -> encoder hidden states
+//! - decoder_model_merged.onnx: autoregressive decoder with KV cache
+//! - tokenizer.json: token-to-text conversion and the special tokens
+//!
+//! The model files may also sit in an `onnx/` subdirectory, and quantized
+//! variants (`*_quantized.onnx`) are preferred when `quantized = true`.
+//! Audio is processed in 30-second windows, as Whisper expects.
+
+use super::Transcriber;
+use crate::config::{OnnxConfig, OnnxExecutionProvider};
+use crate::error::TranscribeError;
+use ort::ep::ExecutionProviderDispatch;
+use ort::session::Session;
+use ort::value::Tensor;
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tokenizers::Tokenizer;
+
+/// Sample rate Whisper expects
+const SAMPLE_RATE: usize = 16000;
+
+/// Samples in one 30-second window
+const WINDOW_SAMPLES: usize = 30 * SAMPLE_RATE;
+
+/// FFT size and frame shift of Whisper's log-mel frontend (25ms / 10ms)
+const N_FFT: usize = 400;
+const HOP_LENGTH: usize = 160;
+
+/// Mel frames in one window
+const WINDOW_FRAMES: usize = WINDOW_SAMPLES / HOP_LENGTH;
+
+/// Mel bands when the encoder doesn't declare them (80, or 128 for large-v3)
+const DEFAULT_NUM_MELS: usize = 80;
+
+/// Tokens to generate per window at most (Whisper's own default)
+const MAX_TOKENS_PER_WINDOW: usize = 224;
+
+/// QNN backend library for the Hexagon NPU
+const DEFAULT_QNN_BACKEND: &str = "libQnnHtp.so";
+
+/// Special tokens steering the Whisper decoder
+struct SpecialTokens {
+    start_of_transcript: i64,
+    end_of_text: i64,
+    transcribe: i64,
+    translate: i64,
+    no_timestamps: i64,
+    /// Language tokens with their codes ("en" for `<|en|>`)
+    languages: Vec<(String, i64)>,
+}
+
+impl SpecialTokens {
+    /// Look up the special tokens in a Whisper tokenizer
+    fn from_tokenizer(tokenizer: &Tokenizer) -> Result<Self, TranscribeError> {
+        let id = |token: &str| {
+            tokenizer.token_to_id(token).map(i64::from).ok_or_else(|| {
+                TranscribeError::InitFailed(format!(
+                    "tokenizer.json has no {} token; is this a Whisper model?",
+                    token
+                ))
+            })
+        };
+        let mut languages: Vec<(String, i64)> = tokenizer
+            .get_vocab(true)
+            .into_iter()
+            .filter_map(|(token, id)| {
+                language_code(&token).map(|code| (code.to_string(), i64::from(id)))
+            })
+            .collect();
+        languages.sort_by_key(|(_, id)| *id);
+
+        Ok(Self {
+            start_of_transcript: id("<|startoftranscript|>")?,
+            end_of_text: id("<|endoftext|>")?,
+            transcribe: id("<|transcribe|>")?,
+            translate: id("<|translate|>")?,
+            no_timestamps: id("<|notimestamps|>")?,
+            languages,
+        })
+    }
+
+    fn language(&self, code: &str) -> Option<i64> {
+        self.languages
+            .iter()
+            .find(|(name, _)| name == code)
+            .map(|(_, id)| *id)
+    }
+}
+
+/// The language code of a language token (`<|de|>` -> "de")
+fn language_code(token: &str) -> Option<&str> {
+    let code = token.strip_prefix("<|")?.strip_suffix("|>")?;
+    (matches!(code.len(), 2 | 3) && code.bytes().all(|b| b.is_ascii_lowercase())).then_some(code)
+}
+
+/// Whisper transcriber running ONNX exports through ONNX Runtime
+pub struct OnnxTranscriber {
+    /// ONNX session for the encoder model (Mutex because run() needs &mut)
+    encoder: Mutex<Session>,
+    /// ONNX session for the decoder model (Mutex because run() needs &mut)
+    decoder: Mutex<Session>,
+    /// BPE tokenizer for decoding token IDs to text
+    tokenizer: Tokenizer,
+    tokens: SpecialTokens,
+    /// Mel bands the encoder takes
+    num_mels: usize,
+    /// Slaney mel filterbank, `num_mels` rows of `N_FFT / 2 + 1` weights
+    mel_filters: Vec<f32>,
+    /// Decoder input names (cached from model metadata)
+    decoder_input_names: Vec<String>,
+    /// Decoder output names (cached from model metadata)
+    decoder_output_names: Vec<String>,
+    /// Number of decoder attention heads
+    num_heads: usize,
+    /// Dimension per attention head
+    head_dim: usize,
+    /// Configured language code, or None to detect it
+    language: Option<String>,
+    /// English-only model: no language or task tokens in the prompt
+    english_only: bool,
+    translate: bool,
+}
+
+impl OnnxTranscriber {
+    /// Create a new ONNX Runtime Whisper transcriber
+    pub fn new(config: &OnnxConfig) -> Result<Self, TranscribeError> {
+        let model_dir = resolve_model_path(&config.model)?;
+        crate::model_cache::mark_used(&model_dir);
+
+        tracing::info!(
+            "Loading ONNX Whisper model from {:?} (quantized={}, execution_provider={})",
+            model_dir,
+            config.quantized,
+            config.execution_provider
+        );
+        let start = std::time::Instant::now();
+
+        let (encoder_file, decoder_file) =
+            model_files(&model_dir, config.quantized).ok_or_else(|| {
+                TranscribeError::ModelNotFound(format!(
+                    "No encoder_model.onnx and decoder_model_merged.onnx in {}\n  \
+                     Download an ONNX export of Whisper, e.g. from \
+                     https://huggingface.co/onnx-community/whisper-base",
+                    model_dir.display()
+                ))
+            })?;
+
+        let tokenizer_path = model_dir.join("tokenizer.json");
+        if !tokenizer_path.exists() {
+            return Err(TranscribeError::InitFailed(format!(
+                "Whisper tokenizer not found: {}\n  \
+                 Ensure tokenizer.json is in the model directory.",
+                tokenizer_path.display()
+            )));
+        }
+        let tokenizer = Tokenizer::from_file(&tokenizer_path)
+            .map_err(|e| TranscribeError::InitFailed(format!("Failed to load tokenizer: {}", e)))?;
+        let tokens = SpecialTokens::from_tokenizer(&tokenizer)?;
+
+        let threads = config.threads.unwrap_or_else(|| num_cpus::get().min(4));
+        let provider = execution_provider(config)?;
+        let encoder = load_session(&encoder_file, threads, provider.clone())?;
+        let decoder = load_session(&decoder_file, threads, provider)?;
+
+        // Encoder input is [batch, num_mels, frames]
+        let num_mels = encoder
+            .inputs()
+            .first()
+            .and_then(|input| match *input.dtype() {
+                ort::value::ValueType::Tensor { ref shape, .. } => {
+                    let dims: &[i64] = shape;
+                    (dims.len() == 3 && dims[1] > 0).then_some(dims[1] as usize)
+                }
+                _ => None,
+            })
+            .unwrap_or(DEFAULT_NUM_MELS);
+
+        let decoder_input_names: Vec<String> = decoder
+            .inputs()
+            .iter()
+            .map(|i| i.name().to_string())
+            .collect();
+        let decoder_output_names: Vec<String> = decoder
+            .outputs()
+            .iter()
+            .map(|o| o.name().to_string())
+            .collect();
+        tracing::debug!("Decoder inputs: {:?}", decoder_input_names);
+        tracing::debug!("Decoder outputs: {:?}", decoder_output_names);
+
+        // KV cache inputs have shape [batch, num_heads, seq_len, head_dim]
+        let (num_heads, head_dim) = decoder
+            .inputs()
+            .iter()
+            .find(|i| i.name().starts_with("past_key_values"))
+            .and_then(|input| match *input.dtype() {
+                ort::value::ValueType::Tensor { ref shape, .. } => {
+                    let dims: &[i64] = shape;
+                    (dims.len() == 4 && dims[1] > 0 && dims[3] > 0)
+                        .then_some((dims[1] as usize, dims[3] as usize))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| {
+                tracing::warn!(
+                    "Could not detect KV cache dimensions from model metadata, \
+                     assuming whisper-base (num_heads=8, head_dim=64)"
+                );
+                (8, 64)
+            });
+
+        let english_only = is_english_only(&model_dir);
+        let language = match config.language.as_str() {
+            "auto" => None,
+            code if english_only && code != "en" => {
+                tracing::warn!(
+                    "{} is an English-only model; ignoring language \"{}\"",
+                    config.model,
+                    code
+                );
+                None
+            }
+            code if !english_only && tokens.language(code).is_none() => {
+                return Err(TranscribeError::ConfigError(format!(
+                    "onnx.language: the model has no language \"{}\"",
+                    code
+                )));
+            }
+            code => Some(code.to_string()),
+        };
+
+        tracing::info!(
+            "ONNX Whisper model loaded in {:.2}s ({} mel bands, num_heads={}, head_dim={})",
+            start.elapsed().as_secs_f32(),
+            num_mels,
+            num_heads,
+            head_dim,
+        );
+
+        Ok(Self {
+            encoder: Mutex::new(encoder),
+            decoder: Mutex::new(decoder),
+            tokenizer,
+            tokens,
+            num_mels,
+            mel_filters: mel_filters(num_mels),
+            decoder_input_names,
+            decoder_output_names,
+            num_heads,
+            head_dim,
+            language,
+            english_only,
+            translate: config.translate,
+        })
+    }
+
+    /// Run the encoder on one 30-second window of audio
+    fn encode(&self, samples: &[f32]) -> Result<ort::value::DynValue, TranscribeError> {
+        let features = log_mel_spectrogram(samples, self.num_mels, &self.mel_filters);
+        let input = Tensor::<f32>::from_array(([1usize, self.num_mels, WINDOW_FRAMES], features))
+            .map_err(|e| {
+            TranscribeError::InferenceFailed(format!(
+                "Failed to create encoder input tensor: {}",
+                e
+            ))
+        })?;
+
+        let mut encoder = self.encoder.lock().map_err(|e| {
+            TranscribeError::InferenceFailed(format!("Failed to lock encoder: {}", e))
+        })?;
+        let output_name = encoder
+            .outputs()
+            .first()
+            .map(|o| o.name().to_string())
+            .unwrap_or_else(|| "last_hidden_state".to_string());
+        let mut outputs = encoder.run(ort::inputs![input]).map_err(|e| {
+            TranscribeError::InferenceFailed(format!("Encoder inference failed: {}", e))
+        })?;
+        outputs
+            .remove(&output_name)
+            .ok_or_else(|| TranscribeError::InferenceFailed("Encoder produced no output".into()))
+    }
+
+    /// Generate tokens after `prompt`, greedily
+    ///
+    /// `pick` chooses the next token from the logits of the last position.
+    /// Stops at end of text or after `max_tokens`; returns only the new tokens.
+    fn decode(
+        &self,
+        encoder_hidden: &ort::value::DynValue,
+        prompt: &[i64],
+        max_tokens: usize,
+        pick: impl Fn(&[f32]) -> Option<i64>,
+    ) -> Result<Vec<i64>, TranscribeError> {
+        let mut decoder = self.decoder.lock().map_err(|e| {
+            TranscribeError::InferenceFailed(format!("Failed to lock decoder: {}", e))
+        })?;
+
+        // The merged decoder only outputs the cross-attention (encoder) KV on
+        // the first step; it is kept and passed back on every later step
+        let names = |prefix: &str, part: &str| {
+            let mut names: Vec<&str> = self
+                .decoder_input_names
+                .iter()
+                .chain(&self.decoder_output_names)
+                .map(String::as_str)
+                .filter(|n| n.starts_with(prefix) && n.contains(part))
+                .collect();
+            names.sort();
+            names
+        };
+        let decoder_kv_inputs = names("past_key_values", ".decoder.");
+        let encoder_kv_inputs = names("past_key_values", ".encoder.");
+        let decoder_kv_outputs = names("present", ".decoder.");
+        let encoder_kv_outputs = names("present", ".encoder.");
+
+        let mut decoder_kv_cache: Vec<ort::value::DynValue> = Vec::new();
+        let mut encoder_kv_cache: Vec<ort::value::DynValue> = Vec::new();
+        let mut generated: Vec<i64> = Vec::new();
+
+        for step in 0..max_tokens {
+            let input_ids = if step == 0 {
+                Tensor::<i64>::from_array(([1usize, prompt.len()], prompt.to_vec()))
+            } else {
+                Tensor::<i64>::from_array(([1usize, 1usize], vec![*generated.last().unwrap()]))
+            }
+            .map_err(|e| {
+                TranscribeError::InferenceFailed(format!(
+                    "Failed to create input_ids tensor: {}",
+                    e
+                ))
+            })?;
+
+            let mut inputs: Vec<(std::borrow::Cow<str>, ort::session::SessionInputValue)> = vec![
+                (std::borrow::Cow::Borrowed("input_ids"), input_ids.into()),
+                (
+                    std::borrow::Cow::Borrowed("encoder_hidden_states"),
+                    ort::session::SessionInputValue::from(encoder_hidden),
+                ),
+            ];
+
+            if step == 0 {
+                // Placeholders; use_cache_branch=false ignores them
+                for kv_name in decoder_kv_inputs.iter().chain(&encoder_kv_inputs) {
+                    let dummy_kv = Tensor::<f32>::from_array((
+                        [1usize, self.num_heads, 1usize, self.head_dim],
+                        vec![0.0f32; self.num_heads * self.head_dim],
+                    ))
+                    .map_err(|e| {
+                        TranscribeError::InferenceFailed(format!(
+                            "Failed to create dummy KV tensor: {}",
+                            e
+                        ))
+                    })?;
+                    inputs.push((std::borrow::Cow::Borrowed(kv_name), dummy_kv.into()));
+                }
+            } else {
+                let cached = decoder_kv_cache.iter().chain(&encoder_kv_cache);
+                for (kv_name, value) in decoder_kv_inputs
+                    .iter()
+                    .chain(&encoder_kv_inputs)
+                    .zip(cached)
+                {
+                    inputs.push((
+                        std::borrow::Cow::Borrowed(kv_name),
+                        ort::session::SessionInputValue::from(value),
+                    ));
+                }
+            }
+
+            let use_cache = Tensor::<bool>::from_array(([1], vec![step > 0])).map_err(|e| {
+                TranscribeError::InferenceFailed(format!(
+                    "Failed to create use_cache tensor: {}",
+                    e
+                ))
+            })?;
+            inputs.push((
+                std::borrow::Cow::Borrowed("use_cache_branch"),
+                use_cache.into(),
+            ));
+
+            let mut outputs = decoder.run(inputs).map_err(|e| {
+                TranscribeError::InferenceFailed(format!(
+                    "Decoder inference failed at step {}: {}",
+                    step, e
+                ))
+            })?;
+
+            // Logits are [1, seq_len, vocab]; only the last position matters
+            let (shape, logits) = outputs["logits"].try_extract_tensor::<f32>().map_err(|e| {
+                TranscribeError::InferenceFailed(format!("Failed to extract logits: {}", e))
+            })?;
+            let dims: &[i64] = shape;
+            let vocab_size = dims.last().copied().unwrap_or(0) as usize;
+            if vocab_size == 0 || logits.len() < vocab_size {
+                return Err(TranscribeError::InferenceFailed(format!(
+                    "Unexpected logits shape: {:?}",
+                    dims
+                )));
+            }
+            let next_token = pick(&logits[logits.len() - vocab_size..])
+                .ok_or_else(|| TranscribeError::InferenceFailed("Empty logits vector".into()))?;
+            if next_token == self.tokens.end_of_text {
+                break;
+            }
+            generated.push(next_token);
+
+            decoder_kv_cache = decoder_kv_outputs
+                .iter()
+                .filter_map(|name| outputs.remove(name))
+                .collect();
+            if step == 0 {
+                encoder_kv_cache = encoder_kv_outputs
+                    .iter()
+                    .filter_map(|name| outputs.remove(name))
+                    .collect();
+            }
+        }
+        Ok(generated)
+    }
+
+    /// The most likely language of a window, as its token
+    fn detect_language(
+        &self,
+        encoder_hidden: &ort::value::DynValue,
+    ) -> Result<i64, TranscribeError> {
+        let detected = self.decode(
+            encoder_hidden,
+            &[self.tokens.start_of_transcript],
+            1,
+            |logits| {
+                self.tokens
+                    .languages
+                    .iter()
+                    .filter(|(_, id)| (*id as usize) < logits.len())
+                    .max_by(|(_, a), (_, b)| logits[*a as usize].total_cmp(&logits[*b as usize]))
+                    .map(|(_, id)| *id)
+            },
+        )?;
+        let token = detected.first().copied().ok_or_else(|| {
+            TranscribeError::InferenceFailed("Language detection produced no token".into())
+        })?;
+        if let Some((code, _)) = self.tokens.languages.iter().find(|(_, id)| *id == token) {
+            tracing::debug!("Detected language: {}", code);
+        }
+        Ok(token)
+    }
+
+    /// Decoder prompt for a window: start, language, task, no timestamps
+    fn prompt(&self, language: Option<i64>) -> Vec<i64> {
+        let mut prompt = vec![self.tokens.start_of_transcript];
+        if !self.english_only {
+            prompt.extend(language);
+            prompt.push(if self.translate {
+                self.tokens.translate
+            } else {
+                self.tokens.transcribe
+            });
+        }
+        prompt.push(self.tokens.no_timestamps);
+        prompt
+    }
+}
+
+impl Transcriber for OnnxTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        if samples.is_empty() {
+            return Err(TranscribeError::AudioFormat(
+                "Empty audio buffer".to_string(),
+            ));
+        }
+
+        let duration_secs = samples.len() as f32 / SAMPLE_RATE as f32;
+        tracing::debug!(
+            "Transcribing {:.2}s of audio ({} samples) with ONNX Whisper",
+            duration_secs,
+            samples.len()
+        );
+        let start = std::time::Instant::now();
+
+        let mut language = self
+            .language
+            .as_deref()
+            .and_then(|code| self.tokens.language(code));
+        let mut texts = Vec::new();
+        for window in samples.chunks(WINDOW_SAMPLES) {
+            let encoder_hidden = self.encode(window)?;
+            if language.is_none() && !self.english_only {
+                language = Some(self.detect_language(&encoder_hidden)?);
+            }
+            let prompt = self.prompt(language);
+            let tokens = self.decode(&encoder_hidden, &prompt, MAX_TOKENS_PER_WINDOW, argmax)?;
+            // Text tokens come before the special ones (timestamps included)
+            let token_ids: Vec<u32> = tokens
+                .into_iter()
+                .filter(|&t| t < self.tokens.end_of_text)
+                .map(|t| t as u32)
+                .collect();
+            let text = self.tokenizer.decode(&token_ids, true).map_err(|e| {
+                TranscribeError::InferenceFailed(format!("Tokenizer decode failed: {}", e))
+            })?;
+            texts.push(text.trim().to_string());
+        }
+        let result = texts
+            .into_iter()
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        tracing::info!(
+            "ONNX Whisper transcription completed in {:.2}s: {:?}",
+            start.elapsed().as_secs_f32(),
+            if result.chars().count() > 50 {
+                format!("{}...", result.chars().take(50).collect::<String>())
+            } else {
+                result.clone()
+            }
+        );
+
+        Ok(result)
+    }
+}
+
+/// Index of the largest logit
+fn argmax(logits: &[f32]) -> Option<i64> {
+    logits
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(idx, _)| idx as i64)
+}
+
+/// The configured execution provider, or None for plain CPU
+///
+/// Providers need ONNX Runtime built with them, which the matching cargo
+/// feature arranges; choosing one that isn't built in is an error rather
+/// than a silent fallback to the CPU.
+fn execution_provider(
+    config: &OnnxConfig,
+) -> Result<Option<ExecutionProviderDispatch>, TranscribeError> {
+    let device = config.device.as_deref();
+    let (compiled, feature, provider) = match config.execution_provider {
+        OnnxExecutionProvider::Cpu => return Ok(None),
+        OnnxExecutionProvider::Cuda => (
+            cfg!(feature = "onnx-cuda"),
+            "onnx-cuda",
+            ort::ep::CUDA::default().build(),
+        ),
+        OnnxExecutionProvider::TensorRt => (
+            cfg!(feature = "onnx-tensorrt"),
+            "onnx-tensorrt",
+            ort::ep::TensorRT::default().build(),
+        ),
+        OnnxExecutionProvider::Rocm => (
+            cfg!(feature = "onnx-rocm"),
+            "onnx-rocm",
+            ort::ep::ROCm::default().build(),
+        ),
+        OnnxExecutionProvider::OpenVino => (
+            cfg!(feature = "onnx-openvino"),
+            "onnx-openvino",
+            match device {
+                Some(device) => ort::ep::OpenVINO::default()
+                    .with_device_type(device)
+                    .build(),
+                None => ort::ep::OpenVINO::default().build(),
+            },
+        ),
+        OnnxExecutionProvider::Qnn => (
+            cfg!(feature = "onnx-qnn"),
+            "onnx-qnn",
+            ort::ep::QNN::default()
+                .with_backend_path(device.unwrap_or(DEFAULT_QNN_BACKEND))
+                .build(),
+        ),
+    };
+    if !compiled {
+        return Err(TranscribeError::InitFailed(format!(
+            "execution_provider = \"{}\" requires voxtype compiled with --features {}",
+            config.execution_provider, feature
+        )));
+    }
+    Ok(Some(provider.error_on_failure()))
+}
+
+/// Create an ONNX Runtime session for a model file
+fn load_session(
+    file: &Path,
+    threads: usize,
+    provider: Option<ExecutionProviderDispatch>,
+) -> Result<Session, TranscribeError> {
+    let mut builder = Session::builder()
+        .map_err(|e| TranscribeError::InitFailed(format!("ONNX session builder failed: {}", e)))?
+        .with_intra_threads(threads)
+        .map_err(|e| TranscribeError::InitFailed(format!("Failed to set threads: {}", e)))?;
+    if let Some(provider) = provider {
+        builder = builder.with_execution_providers([provider]).map_err(|e| {
+            TranscribeError::InitFailed(format!("Failed to enable execution provider: {}", e))
+        })?;
+    }
+    builder
+        .commit_from_file(file)
+        .map_err(|e| TranscribeError::InitFailed(format!("Failed to load {:?}: {}", file, e)))
+}
+
+/// Encoder and decoder files in a model directory or its `onnx/` subdirectory
+///
+/// Prefers the quantized variants when asked and present.
+fn model_files(model_dir: &Path, quantized: bool) -> Option<(PathBuf, PathBuf)> {
+    let suffixes: &[&str] = if quantized {
+        &["_quantized", ""]
+    } else {
+        &[""]
+    };
+    [model_dir.to_path_buf(), model_dir.join("onnx")]
+        .iter()
+        .flat_map(|dir| {
+            suffixes.iter().map(move |suffix| {
+                (
+                    dir.join(format!("encoder_model{}.onnx", suffix)),
+                    dir.join(format!("decoder_model_merged{}.onnx", suffix)),
+                )
+            })
+        })
+        .find(|(encoder, decoder)| encoder.exists() && decoder.exists())
+}
+
+/// Whether an exported model transcribes English only
+///
+/// Optimum exports carry `is_multilingual` in generation_config.json;
+/// otherwise the directory name decides ("whisper-base.en").
+fn is_english_only(model_dir: &Path) -> bool {
+    std::fs::read_to_string(model_dir.join("generation_config.json"))
+        .ok()
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .and_then(|config| config.get("is_multilingual")?.as_bool())
+        .map(|multilingual| !multilingual)
+        .unwrap_or_else(|| super::models::is_english_only(&model_dir.to_string_lossy()))
+}
+
+/// Slaney-style mel filterbank, as librosa and Whisper compute it
+///
+/// Returns `num_mels` rows of `N_FFT / 2 + 1` weights, covering 0-8000 Hz.
+fn mel_filters(num_mels: usize) -> Vec<f32> {
+    const MIN_LOG_HZ: f64 = 1000.0;
+    const MIN_LOG_MEL: f64 = 15.0;
+    const LINEAR_HZ_PER_MEL: f64 = 200.0 / 3.0;
+    let log_step = 6.4f64.ln() / 27.0;
+    let hz_to_mel = |hz: f64| {
+        if hz < MIN_LOG_HZ {
+            hz / LINEAR_HZ_PER_MEL
+        } else {
+            MIN_LOG_MEL + (hz / MIN_LOG_HZ).ln() / log_step
+        }
+    };
+    let mel_to_hz = |mel: f64| {
+        if mel < MIN_LOG_MEL {
+            mel * LINEAR_HZ_PER_MEL
+        } else {
+            MIN_LOG_HZ * ((mel - MIN_LOG_MEL) * log_step).exp()
+        }
+    };
+
+    let num_bins = N_FFT / 2 + 1;
+    let max_mel = hz_to_mel(SAMPLE_RATE as f64 / 2.0);
+    let edges: Vec<f64> = (0..num_mels + 2)
+        .map(|i| mel_to_hz(max_mel * i as f64 / (num_mels + 1) as f64))
+        .collect();
+
+    let mut filters = vec![0.0f32; num_mels * num_bins];
+    for mel in 0..num_mels {
+        let (lower, center, upper) = (edges[mel], edges[mel + 1], edges[mel + 2]);
+        let norm = 2.0 / (upper - lower);
+        for bin in 0..num_bins {
+            let hz = (bin * SAMPLE_RATE) as f64 / N_FFT as f64;
+            let rising = (hz - lower) / (center - lower);
+            let falling = (upper - hz) / (upper - center);
+            let weight = rising.min(falling).max(0.0);
+            filters[mel * num_bins + bin] = (weight * norm) as f32;
+        }
+    }
+    filters
+}
+
+/// Whisper's log-mel spectrogram of up to 30 seconds of audio
+///
+/// Pads the audio to 30 seconds and returns `num_mels` rows of
+/// `WINDOW_FRAMES` values, scaled the way Whisper was trained.
+fn log_mel_spectrogram(samples: &[f32], num_mels: usize, filters: &[f32]) -> Vec<f32> {
+    let mut audio = samples[..samples.len().min(WINDOW_SAMPLES)].to_vec();
+    audio.resize(WINDOW_SAMPLES, 0.0);
+
+    // Centered frames: reflect-pad half a frame at both ends
+    let pad = N_FFT / 2;
+    let mut padded = Vec::with_capacity(audio.len() + 2 * pad);
+    padded.extend((1..=pad).rev().map(|i| audio[i]));
+    padded.extend_from_slice(&audio);
+    padded.extend((1..=pad).map(|i| audio[audio.len() - 1 - i]));
+
+    // Periodic Hann window
+    let window: Vec<f32> = (0..N_FFT)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / N_FFT as f32).cos())
+        .collect();
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(N_FFT);
+    let num_bins = N_FFT / 2 + 1;
+
+    let mut mel = vec![0.0f32; num_mels * WINDOW_FRAMES];
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); N_FFT];
+    let mut power = vec![0.0f32; num_bins];
+    for frame in 0..WINDOW_FRAMES {
+        let offset = frame * HOP_LENGTH;
+        for (i, value) in buffer.iter_mut().enumerate() {
+            *value = Complex::new(padded[offset + i] * window[i], 0.0);
+        }
+        fft.process(&mut buffer);
+        for (bin, p) in power.iter_mut().enumerate() {
+            *p = buffer[bin].norm_sqr();
+        }
+        for m in 0..num_mels {
+            let row = &filters[m * num_bins..(m + 1) * num_bins];
+            let energy: f32 = row.iter().zip(&power).map(|(w, p)| w * p).sum();
+            mel[m * WINDOW_FRAMES + frame] = energy.max(1e-10).log10();
+        }
+    }
+
+    // Keep 80 dB of dynamic range and scale to roughly [-1, 1]
+    let max = mel.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    for value in &mut mel {
+        *value = ((*value).max(max - 8.0) + 4.0) / 4.0;
+    }
+    mel
+}
+
+/// Resolve model name to directory path
+fn resolve_model_path(model: &str) -> Result<PathBuf, TranscribeError> {
+    let path = crate::config::expand_home(model);
+    if path.is_absolute() && path.exists() {
+        return Ok(path);
+    }
+
+    // Short names live in onnx-{name} directories
+    let model_dir_name = if model.starts_with("onnx-") {
+        model.to_string()
+    } else {
+        format!("onnx-{}", model)
+    };
+
+    let models_dir = crate::config::Config::models_dir();
+    let model_path = models_dir.join(&model_dir_name);
+    if model_path.exists() {
+        return Ok(model_path);
+    }
+
+    let cwd_path = PathBuf::from(&model_dir_name);
+    if cwd_path.exists() {
+        return Ok(cwd_path);
+    }
+
+    let local_models_path = PathBuf::from("models").join(&model_dir_name);
+    if local_models_path.exists() {
+        return Ok(local_models_path);
+    }
+
+    Err(TranscribeError::ModelNotFound(format!(
+        "ONNX model '{}' not found. Looked in:\n  \
+         - {}\n  \
+         - {}\n  \
+         - {}\n\n\
+         Download an ONNX export of Whisper into one of them, e.g.:\n  \
+         https://huggingface.co/onnx-community/whisper-base",
+        model,
+        model_path.display(),
+        cwd_path.display(),
+        local_models_path.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_language_code() {
+        assert_eq!(language_code("<|de|>"), Some("de"));
+        assert_eq!(language_code("<|haw|>"), Some("haw"));
+        assert_eq!(language_code("<|notimestamps|>"), None);
+        assert_eq!(language_code("<|0.00|>"), None);
+        assert_eq!(language_code("de"), None);
+    }
+
+    #[test]
+    fn test_model_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert_eq!(model_files(dir, true), None);
+
+        // onnx-community layout: everything under onnx/
+        fs::create_dir(dir.join("onnx")).unwrap();
+        for file in [
+            "encoder_model.onnx",
+            "decoder_model_merged.onnx",
+            "encoder_model_quantized.onnx",
+            "decoder_model_merged_quantized.onnx",
+        ] {
+            fs::write(dir.join("onnx").join(file), b"dummy").unwrap();
+        }
+        let (encoder, decoder) = model_files(dir, true).unwrap();
+        assert_eq!(encoder, dir.join("onnx/encoder_model_quantized.onnx"));
+        assert_eq!(
+            decoder,
+            dir.join("onnx/decoder_model_merged_quantized.onnx")
+        );
+        let (encoder, _) = model_files(dir, false).unwrap();
+        assert_eq!(encoder, dir.join("onnx/encoder_model.onnx"));
+    }
+
+    #[test]
+    fn test_is_english_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().join("onnx-whisper-base.en");
+        fs::create_dir(&dir).unwrap();
+        assert!(is_english_only(&dir));
+
+        fs::write(
+            dir.join("generation_config.json"),
+            r#"{"is_multilingual": true}"#,
+        )
+        .unwrap();
+        assert!(!is_english_only(&dir));
+    }
+
+    #[test]
+    fn test_mel_filters() {
+        let filters = mel_filters(80);
+        let num_bins = N_FFT / 2 + 1;
+        assert_eq!(filters.len(), 80 * num_bins);
+        // Matches the first weight of Whisper's own mel_filters.npz
+        assert!((filters[1] - 0.024_862_6).abs() < 1e-5);
+        assert!(filters.iter().all(|&w| w >= 0.0));
+        // Every band picks up some frequency
+        assert!(filters
+            .chunks(num_bins)
+            .all(|row| row.iter().any(|&w| w > 0.0)));
+    }
+
+    #[test]
+    fn test_log_mel_spectrogram() {
+        let filters = mel_filters(80);
+        let silence = log_mel_spectrogram(&[0.0; SAMPLE_RATE], 80, &filters);
+        assert_eq!(silence.len(), 80 * WINDOW_FRAMES);
+        assert!(silence.iter().all(|&v| (v - (-1.5)).abs() < 1e-6));
+
+        // A 1 kHz tone is loudest in the band around 1 kHz
+        let tone: Vec<f32> = (0..SAMPLE_RATE)
+            .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / SAMPLE_RATE as f32).sin())
+            .collect();
+        let mel = log_mel_spectrogram(&tone, 80, &filters);
+        let frame = 50;
+        let loudest = (0..80)
+            .max_by(|&a, &b| {
+                mel[a * WINDOW_FRAMES + frame].total_cmp(&mel[b * WINDOW_FRAMES + frame])
+            })
+            .unwrap();
+        assert_eq!(loudest, 26);
+        // After the tone comes padding, as quiet as the range allows
+        let floor = mel.iter().copied().fold(f32::INFINITY, f32::min);
+        assert_eq!(mel[loudest * WINDOW_FRAMES + 2000], floor);
+    }
+
+    #[test]
+    fn test_execution_provider() {
+        let mut config = OnnxConfig::default();
+        assert!(execution_provider(&config).unwrap().is_none());
+
+        config.execution_provider = OnnxExecutionProvider::Qnn;
+        let provider = execution_provider(&config);
+        if cfg!(feature = "onnx-qnn") {
+            assert!(provider.unwrap().is_some());
+        } else {
+            let err = provider.unwrap_err().to_string();
+            assert!(err.contains("--features onnx-qnn"));
+        }
+    }
+
+    #[test]
+    fn test_resolve_model_path_not_found() {
+        let result = resolve_model_path("/nonexistent/path/to/model");
+        assert!(matches!(result, Err(TranscribeError::ModelNotFound(_))));
+    }
+}
@@ -39,6 +39,7 @@ pub struct ParaformerTranscriber {
 impl ParaformerTranscriber {
     pub fn new(config: &ParaformerConfig) -> Result<Self, TranscribeError> {
         let model_dir = resolve_model_path(&config.model)?;
+        crate::model_cache::mark_used(&model_dir);
 
         tracing::info!("Loading Paraformer model from {:?}", model_dir);
         let start = std::time::Instant::now();
@@ -42,6 +42,7 @@ impl ParakeetTranscriber {
     /// Create a new Parakeet transcriber
     pub fn new(config: &ParakeetConfig) -> Result<Self, TranscribeError> {
         let model_path = resolve_model_path(&config.model)?;
+        crate::model_cache::mark_used(&model_path);
 
         // Determine model type: use config override or auto-detect from directory
         let model_type = config
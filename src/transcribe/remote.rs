@@ -62,11 +62,14 @@ impl RemoteTranscriber {
             );
         }
 
-        // Check for API key in config or environment
-        let api_key = config
-            .remote_api_key
-            .clone()
-            .or_else(|| std::env::var("VOXTYPE_WHISPER_API_KEY").ok());
+        // Check for API key in config or environment, or where they point to
+        let env_key = std::env::var("VOXTYPE_WHISPER_API_KEY").ok();
+        let api_key = crate::secret::resolve(
+            config.remote_api_key.as_deref().or(env_key.as_deref()),
+            config.remote_api_key_cmd.as_deref(),
+            config.remote_api_key_keyring.as_deref(),
+        )
+        .map_err(TranscribeError::ConfigError)?;
 
         let model = config
             .remote_model
@@ -211,10 +214,14 @@ impl Transcriber for RemoteTranscriber {
         let url = format!("{}{}", self.endpoint.trim_end_matches('/'), path);
 
         // Build request
-        let mut request = ureq::post(&url).timeout(self.timeout).set(
-            "Content-Type",
-            &format!("multipart/form-data; boundary={}", boundary),
-        );
+        let mut request = crate::proxy::agent(&url)
+            .build()
+            .post(&url)
+            .timeout(self.timeout)
+            .set(
+                "Content-Type",
+                &format!("multipart/form-data; boundary={}", boundary),
+            );
 
         // Add authorization if API key is configured
         if let Some(ref key) = self.api_key {
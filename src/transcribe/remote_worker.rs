@@ -0,0 +1,864 @@
+//! Transcription by a voxtype worker on another machine
+//!
+//! `voxtype serve` keeps a Whisper model loaded and listens on TCP, so a
+//! machine with a big GPU can transcribe for laptops on the network. The
+//! daemon on the laptop (`mode = "worker"`) sends each recording and gets the
+//! text back, getting large-model accuracy without spending its battery.
+//!
+//! The connection speaks the same protocol as the local GPU isolation worker
+//! (see [`super::worker`]), optionally over TLS:
+//! 1. Daemon sends the header and an Auth control message with its token
+//! 2. Worker sends `{"type":"ready",...}`, or a result with the error when
+//!    the token is wrong
+//! 3. Daemon sends the audio, a Prompt control message with the recording's
+//!    prompt context if any, and the End message
+//! 4. Worker sends progress messages and the result
+//!
+//! Connections are served concurrently, up to [`MAX_CONNECTIONS`], and their
+//! recordings take turns on the loaded model. The model, language and other
+//! transcription settings come from the worker's own config.
+
+use super::subprocess::{read_message, read_worker_response, WorkerMessage as DaemonMessage};
+use super::worker::{self, Control, Header, Recording, WorkerMessage, WorkerResponse};
+use super::{Progress, Transcriber};
+use crate::config::WhisperConfig;
+use crate::error::TranscribeError;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use std::io::{self, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Port `voxtype serve` listens on by default
+pub const DEFAULT_PORT: u16 = 7840;
+
+/// How long the worker waits on a silent connection
+const IO_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Connections served at once; more are closed until one finishes
+pub(super) const MAX_CONNECTIONS: usize = 16;
+
+/// Samples per Audio message (one second)
+const CHUNK_SAMPLES: usize = 16000;
+
+/// A connection, plain or over TLS
+pub(super) trait Connection: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Connection for T {}
+
+/// Add the default port to an address without one
+fn with_default_port(address: &str) -> String {
+    match address.rsplit_once(':') {
+        Some((_, port)) if port.parse::<u16>().is_ok() => address.to_string(),
+        _ => format!("{}:{}", address, DEFAULT_PORT),
+    }
+}
+
+/// Host part of a "host:port" address, for verifying the certificate
+fn host_of(address: &str) -> &str {
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// Token the worker requires, from config or the environment
+fn worker_token(config: &WhisperConfig) -> Option<String> {
+    config
+        .worker_token
+        .clone()
+        .or_else(|| std::env::var("VOXTYPE_WORKER_TOKEN").ok())
+}
+
+/// Compare tokens in constant time
+pub(super) fn tokens_match(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Transcriber sending audio to `voxtype serve` on another machine
+pub struct RemoteWorkerTranscriber {
+    /// Worker address with port (e.g., "desktop.lan:7840")
+    address: String,
+    /// Token the worker requires
+    token: Option<String>,
+    /// TLS settings, when connecting over TLS
+    tls: Option<Arc<rustls::ClientConfig>>,
+    /// Connect and read timeout
+    timeout: Duration,
+    /// Connections transcribing right now, so `cancel()` can close them
+    running: Mutex<Vec<(u64, TcpStream)>>,
+    next_id: AtomicU64,
+}
+
+impl RemoteWorkerTranscriber {
+    /// Create a new remote worker transcriber from config
+    pub fn new(config: &WhisperConfig) -> Result<Self, TranscribeError> {
+        let address = config.worker_address.as_deref().ok_or_else(|| {
+            TranscribeError::ConfigError("worker_address is required when mode = 'worker'".into())
+        })?;
+        let address = with_default_port(address);
+
+        let tls = if config.worker_tls {
+            Some(Self::tls_config(config.worker_tls_ca.as_deref())?)
+        } else {
+            let host = host_of(&address);
+            if host != "localhost" && host != "127.0.0.1" && host != "::1" {
+                tracing::warn!(
+                    "Worker connection doesn't use TLS. Audio data will be transmitted unencrypted!"
+                );
+            }
+            None
+        };
+
+        let token = worker_token(config);
+
+        let timeout = Duration::from_secs(config.remote_timeout_secs.unwrap_or(30));
+
+        tracing::info!(
+            "Configured remote worker transcriber: address={}, tls={}, timeout={}s",
+            address,
+            tls.is_some(),
+            timeout.as_secs()
+        );
+
+        Ok(Self {
+            address,
+            token,
+            tls,
+            timeout,
+            running: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(0),
+        })
+    }
+
+    /// TLS settings trusting the CA certificate, or the usual web roots
+    fn tls_config(ca: Option<&str>) -> Result<Arc<rustls::ClientConfig>, TranscribeError> {
+        let mut roots = rustls::RootCertStore::empty();
+        match ca {
+            Some(path) => {
+                let ca_err = |e: String| {
+                    TranscribeError::ConfigError(format!("Invalid worker_tls_ca {}: {}", path, e))
+                };
+                for cert in
+                    CertificateDer::pem_file_iter(path).map_err(|e| ca_err(e.to_string()))?
+                {
+                    let cert = cert.map_err(|e| ca_err(e.to_string()))?;
+                    roots.add(cert).map_err(|e| ca_err(e.to_string()))?;
+                }
+            }
+            None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        Ok(Arc::new(
+            rustls::ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth(),
+        ))
+    }
+
+    /// Connect to the worker, returning the connection and its socket
+    fn connect(&self) -> Result<(Box<dyn Connection>, TcpStream), TranscribeError> {
+        let connect_err = |e: String| {
+            TranscribeError::NetworkError(format!(
+                "Failed to connect to worker at {}: {}",
+                self.address, e
+            ))
+        };
+        let mut last_err = "address not found".to_string();
+        let mut connected = None;
+        for addr in self
+            .address
+            .to_socket_addrs()
+            .map_err(|e| connect_err(e.to_string()))?
+        {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(tcp) => {
+                    connected = Some(tcp);
+                    break;
+                }
+                Err(e) => last_err = e.to_string(),
+            }
+        }
+        let tcp = connected.ok_or_else(|| connect_err(last_err))?;
+        tcp.set_read_timeout(Some(self.timeout))
+            .and_then(|_| tcp.set_write_timeout(Some(self.timeout)))
+            .and_then(|_| tcp.set_nodelay(true))
+            .map_err(|e| connect_err(e.to_string()))?;
+        let socket = tcp.try_clone().map_err(|e| connect_err(e.to_string()))?;
+
+        let conn: Box<dyn Connection> = match &self.tls {
+            Some(tls) => {
+                let name = ServerName::try_from(host_of(&self.address).to_string())
+                    .map_err(|e| connect_err(e.to_string()))?;
+                let session = rustls::ClientConnection::new(Arc::clone(tls), name)
+                    .map_err(|e| connect_err(e.to_string()))?;
+                Box::new(rustls::StreamOwned::new(session, tcp))
+            }
+            None => Box::new(tcp),
+        };
+        Ok((conn, socket))
+    }
+
+    /// Transcribe over one connection
+    fn exchange(
+        &self,
+        conn: Box<dyn Connection>,
+        samples: &[f32],
+        progress: Option<&Progress>,
+        context: Option<&str>,
+    ) -> Result<String, TranscribeError> {
+        let send_err = |e: io::Error| {
+            TranscribeError::NetworkError(format!("Failed to send audio to worker: {}", e))
+        };
+        let mut conn = BufReader::new(conn);
+
+        // Authenticate before sending any audio
+        Header::current()
+            .write(conn.get_mut())
+            .and_then(|_| {
+                worker::write_control(
+                    conn.get_mut(),
+                    &Control::Auth {
+                        token: self.token.clone(),
+                    },
+                )
+            })
+            .map_err(send_err)?;
+        let reply = read_message(&mut conn).map_err(|e| {
+            TranscribeError::NetworkError(format!("Failed to read worker output: {}", e))
+        })?;
+        match reply {
+            Some(DaemonMessage::Ready { version }) if version == worker::PROTOCOL_VERSION => {}
+            Some(DaemonMessage::Ready { version }) => {
+                return Err(TranscribeError::InitFailed(format!(
+                    "Worker at {} speaks a different protocol (version {}, expected {}). \
+                     Run the same voxtype version on both machines.",
+                    self.address,
+                    version,
+                    worker::PROTOCOL_VERSION
+                )));
+            }
+            // Refused, with the reason
+            Some(DaemonMessage::Result(response)) => return response.into_text(),
+            Some(other) => {
+                return Err(TranscribeError::NetworkError(format!(
+                    "Unexpected message from worker: {:?}",
+                    other
+                )));
+            }
+            None => {
+                return Err(TranscribeError::NetworkError(format!(
+                    "Worker at {} closed the connection",
+                    self.address
+                )));
+            }
+        }
+
+        for chunk in samples.chunks(CHUNK_SAMPLES) {
+            worker::write_audio(conn.get_mut(), chunk).map_err(send_err)?;
+        }
+        if let Some(context) = context {
+            let prompt = Control::Prompt {
+                text: context.to_string(),
+            };
+            worker::write_control(conn.get_mut(), &prompt).map_err(send_err)?;
+        }
+        worker::write_end(conn.get_mut()).map_err(send_err)?;
+
+        read_worker_response(&mut conn, progress)?.into_text()
+    }
+
+    fn run(
+        &self,
+        samples: &[f32],
+        progress: Option<&Progress>,
+        context: Option<&str>,
+    ) -> Result<String, TranscribeError> {
+        if samples.is_empty() {
+            return Err(TranscribeError::AudioFormat(
+                "Empty audio buffer".to_string(),
+            ));
+        }
+
+        let start = std::time::Instant::now();
+        let (conn, socket) = self.connect()?;
+
+        // Transcribe, with the connection where cancel() can close it
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.running.lock().unwrap().push((id, socket));
+        let result = self.exchange(conn, samples, progress, context);
+        let cancelled = {
+            let mut running = self.running.lock().unwrap();
+            let before = running.len();
+            running.retain(|(running_id, _)| *running_id != id);
+            running.len() == before
+        };
+        if cancelled {
+            return Err(TranscribeError::Cancelled);
+        }
+
+        tracing::debug!(
+            "Remote worker transcription completed in {:.2}s",
+            start.elapsed().as_secs_f32()
+        );
+        result
+    }
+}
+
+impl Transcriber for RemoteWorkerTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        self.run(samples, None, None)
+    }
+
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, Some(&progress), None)
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, progress.as_ref(), Some(context))
+    }
+
+    fn cancel(&self) {
+        // The worker stops when it notices the connection is gone
+        for (_, socket) in self.running.lock().unwrap().drain(..) {
+            tracing::info!("Closing connection to transcription worker");
+            let _ = socket.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+/// Connections being served, each on its own thread
+#[derive(Clone, Default)]
+pub(super) struct Connections(Arc<AtomicUsize>);
+
+impl Connections {
+    /// Serve a connection with `f` on a new thread, unless
+    /// [`MAX_CONNECTIONS`] are already being served
+    pub(super) fn spawn(&self, f: impl FnOnce() + Send + 'static) -> bool {
+        if self.0.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+            return false;
+        }
+        let active = Arc::clone(&self.0);
+        std::thread::spawn(move || {
+            // Released even if `f` panics
+            struct Release(Arc<AtomicUsize>);
+            impl Drop for Release {
+                fn drop(&mut self) {
+                    self.0.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+            let _release = Release(active);
+            f();
+        });
+        true
+    }
+}
+
+/// The served transcriber, transcribing one recording at a time
+///
+/// Connections are served concurrently but share one model. Taking turns
+/// means only the caller's transcription is running while it holds its
+/// turn, so `cancel()` from a progress callback (the daemon went away)
+/// stops that transcription and no other connection's.
+struct OneAtATime {
+    inner: Arc<dyn Transcriber>,
+    turn: Mutex<()>,
+}
+
+impl OneAtATime {
+    fn run<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _turn = self.turn.lock().unwrap_or_else(|e| e.into_inner());
+        f()
+    }
+}
+
+impl Transcriber for OneAtATime {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        self.run(|| self.inner.transcribe(samples))
+    }
+
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        self.run(|| self.inner.transcribe_with_progress(samples, progress))
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        self.run(|| {
+            self.inner
+                .transcribe_with_context(samples, progress, context)
+        })
+    }
+
+    fn transcribe_with_grammar(
+        &self,
+        samples: &[f32],
+        grammar: &Path,
+    ) -> Result<String, TranscribeError> {
+        self.run(|| self.inner.transcribe_with_grammar(samples, grammar))
+    }
+
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<super::Segment>, TranscribeError> {
+        self.run(|| self.inner.transcribe_segments(samples))
+    }
+
+    /// Only call during a transcription, from its progress callback
+    fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    fn prepare(&self) {
+        self.inner.prepare();
+    }
+
+    fn last_model_load_time(&self) -> Option<Duration> {
+        self.inner.last_model_load_time()
+    }
+
+    fn unload_if_idle(&self) {
+        self.inner.unload_if_idle();
+    }
+}
+
+/// TLS settings for `voxtype serve` from PEM files
+fn server_tls_config(cert: &Path, key: &Path) -> anyhow::Result<Arc<rustls::ServerConfig>> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| anyhow::anyhow!("Invalid certificate {}: {}", cert.display(), e))?;
+    let key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|e| anyhow::anyhow!("Invalid private key {}: {}", key.display(), e))?;
+    let config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(Arc::new(config))
+}
+
+/// Run the transcription worker for other machines
+///
+/// This is the main entry point called from `voxtype serve`. It loads the
+/// model once, then serves each connection on its own thread. `tls` is the
+/// certificate and private key to serve TLS with. `http` and `wyoming` are
+/// the addresses to also serve the HTTP API (see [`super::http_api`]) and
+/// the Wyoming protocol (see [`super::wyoming`]) on.
+pub fn serve(
+    config: &WhisperConfig,
+    listen: &str,
+    tls: Option<(&Path, &Path)>,
+    http: Option<&str>,
+    wyoming: Option<&str>,
+) -> anyhow::Result<()> {
+    let tls = tls
+        .map(|(cert, key)| server_tls_config(cert, key))
+        .transpose()?;
+    let token = worker_token(config);
+    if token.is_none() {
+        tracing::warn!("No worker_token set: anyone who can reach this machine can use the worker");
+    }
+    if tls.is_none() {
+        tracing::warn!("Serving without TLS. Audio data will be transmitted unencrypted!");
+    }
+
+    tracing::info!("Loading model: {}", config.model);
+    let transcriber: Arc<dyn Transcriber> = Arc::new(OneAtATime {
+        inner: crate::priority::wrap(Arc::new(super::whisper::WhisperTranscriber::new(config)?)),
+        turn: Mutex::new(()),
+    });
+
+    let listener = TcpListener::bind(with_default_port(listen))?;
+    tracing::info!(
+        "Transcription worker listening on {}",
+        listener.local_addr()?
+    );
+
+    if let Some(http) = http {
+        let http_listener = TcpListener::bind(http)?;
+        tracing::info!(
+            "HTTP transcription API listening on {}",
+            http_listener.local_addr()?
+        );
+        let (tls, transcriber, token) = (tls.clone(), Arc::clone(&transcriber), token.clone());
+        std::thread::spawn(move || {
+            super::http_api::serve(http_listener, tls, transcriber, token);
+        });
+    }
+
+    if let Some(wyoming) = wyoming {
+        let wyoming_listener = TcpListener::bind(wyoming)?;
+        tracing::info!(
+            "Wyoming speech-to-text service listening on {}",
+            wyoming_listener.local_addr()?
+        );
+        let (config, transcriber) = (config.clone(), Arc::clone(&transcriber));
+        std::thread::spawn(move || {
+            super::wyoming::serve(wyoming_listener, &config, transcriber);
+        });
+    }
+
+    serve_workers(listener, tls, transcriber, token);
+    Ok(())
+}
+
+/// Serve daemons until the process exits, each connection on its own thread
+fn serve_workers(
+    listener: TcpListener,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    transcriber: Arc<dyn Transcriber>,
+    token: Option<String>,
+) {
+    let connections = Connections::default();
+    for tcp in listener.incoming() {
+        let tcp = match tcp {
+            Ok(tcp) => tcp,
+            Err(e) => {
+                tracing::warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let peer = tcp
+            .peer_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_else(|_| "unknown".to_string());
+        let (tls, transcriber, token) = (tls.clone(), Arc::clone(&transcriber), token.clone());
+        let peer_name = peer.clone();
+        let spawned = connections.spawn(move || {
+            tracing::info!("Transcribing for {}", peer);
+            if let Err(e) = serve_connection(tcp, tls.as_ref(), &transcriber, token.as_deref()) {
+                tracing::warn!("Connection from {} failed: {}", peer, e);
+            }
+        });
+        if !spawned {
+            tracing::warn!(
+                "Refusing connection from {}: already serving {} connections",
+                peer_name,
+                MAX_CONNECTIONS
+            );
+        }
+    }
+}
+
+/// Transcribe the recording sent over one connection
+fn serve_connection(
+    tcp: TcpStream,
+    tls: Option<&Arc<rustls::ServerConfig>>,
+    transcriber: &Arc<dyn Transcriber>,
+    token: Option<&str>,
+) -> io::Result<()> {
+    let mut conn = accept(tcp, tls)?;
+    let recording = match receive_audio(&mut conn, token) {
+        Ok(recording) => recording,
+        Err(e) => {
+            let _ = worker::write_worker_message(
+                &mut conn,
+                &WorkerMessage::Result(WorkerResponse::error(e.clone())),
+            );
+            return Err(io::Error::other(e));
+        }
+    };
+    tracing::debug!(
+        "Received {:.2}s of audio",
+        recording.samples.len() as f32 / 16000.0
+    );
+
+    // Report progress; a failed write means the daemon is gone, so stop.
+    // Progress is reported during this recording's turn on the model, so
+    // cancelling stops this transcription only (see `OneAtATime`)
+    let conn = Arc::new(Mutex::new(conn));
+    let progress_conn = Arc::clone(&conn);
+    let cancel_target = Arc::downgrade(transcriber);
+    let progress: Progress = Arc::new(move |percent| {
+        let sent = worker::write_worker_message(
+            &mut *progress_conn.lock().unwrap(),
+            &WorkerMessage::Progress { percent },
+        );
+        if sent.is_err() {
+            if let Some(transcriber) = cancel_target.upgrade() {
+                transcriber.cancel();
+            }
+        }
+    });
+    let result = recording.transcribe(transcriber.as_ref(), progress);
+    if let Err(e) = &result {
+        tracing::warn!("Transcription failed: {}", e);
+    }
+
+    let mut conn = conn.lock().unwrap();
+    worker::write_worker_message(
+        &mut *conn,
+        &WorkerMessage::Result(WorkerResponse::from_result(result)),
+    )
+}
+
+/// Set up an accepted connection, over TLS when configured
+pub(super) fn accept(
+    tcp: TcpStream,
+    tls: Option<&Arc<rustls::ServerConfig>>,
+) -> io::Result<Box<dyn Connection>> {
+    tcp.set_read_timeout(Some(IO_TIMEOUT))?;
+    tcp.set_write_timeout(Some(IO_TIMEOUT))?;
+    tcp.set_nodelay(true)?;
+    Ok(match tls {
+        Some(tls) => {
+            let session =
+                rustls::ServerConnection::new(Arc::clone(tls)).map_err(io::Error::other)?;
+            Box::new(rustls::StreamOwned::new(session, tcp))
+        }
+        None => Box::new(tcp),
+    })
+}
+
+/// Check the daemon's token, then read its audio
+fn receive_audio<C: Read + Write>(conn: &mut C, token: Option<&str>) -> Result<Recording, String> {
+    worker::read_header(conn)?;
+    match worker::read_control(conn)? {
+        Control::Auth { token: given } => {
+            if let Some(expected) = token {
+                if !given.is_some_and(|given| tokens_match(expected, &given)) {
+                    return Err("Authentication failed: wrong or missing worker_token".to_string());
+                }
+            }
+        }
+        other => return Err(format!("Expected authentication, got {:?}", other)),
+    }
+    worker::write_worker_message(
+        conn,
+        &WorkerMessage::Ready {
+            version: worker::PROTOCOL_VERSION,
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    let recording = worker::read_samples(conn)?;
+    if recording.samples.is_empty() {
+        return Err("Empty audio buffer".to_string());
+    }
+    Ok(recording)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transcriber that reports halfway and returns the sample count
+    struct Counting;
+
+    impl Transcriber for Counting {
+        fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+            Ok(format!("{} samples", samples.len()))
+        }
+
+        fn transcribe_with_progress(
+            &self,
+            samples: &[f32],
+            progress: Progress,
+        ) -> Result<String, TranscribeError> {
+            progress(50);
+            self.transcribe(samples)
+        }
+
+        fn transcribe_with_context(
+            &self,
+            samples: &[f32],
+            _progress: Option<Progress>,
+            context: &str,
+        ) -> Result<String, TranscribeError> {
+            Ok(format!("{} samples ({})", samples.len(), context))
+        }
+    }
+
+    /// Serve one connection on a local port, returning the worker address
+    fn serve_once(
+        token: Option<&'static str>,
+        tls: Option<Arc<rustls::ServerConfig>>,
+    ) -> (String, std::thread::JoinHandle<io::Result<()>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let handle = std::thread::spawn(move || {
+            let (tcp, _) = listener.accept()?;
+            let transcriber: Arc<dyn Transcriber> = Arc::new(Counting);
+            serve_connection(tcp, tls.as_ref(), &transcriber, token)
+        });
+        (address, handle)
+    }
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/tls")
+            .join(name)
+    }
+
+    fn client(address: String, token: Option<&str>) -> RemoteWorkerTranscriber {
+        let config = WhisperConfig {
+            mode: Some(crate::config::WhisperMode::Worker),
+            worker_address: Some(address),
+            worker_token: token.map(str::to_string),
+            ..Default::default()
+        };
+        RemoteWorkerTranscriber::new(&config).unwrap()
+    }
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(with_default_port("desktop.lan"), "desktop.lan:7840");
+        assert_eq!(with_default_port("desktop.lan:9000"), "desktop.lan:9000");
+        assert_eq!(with_default_port("[::1]:9000"), "[::1]:9000");
+        assert_eq!(host_of("desktop.lan:7840"), "desktop.lan");
+        assert_eq!(host_of("[::1]:7840"), "::1");
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secret", "secreT"));
+        assert!(!tokens_match("secret", "secret2"));
+        assert!(!tokens_match("secret", ""));
+    }
+
+    #[test]
+    fn test_requires_address() {
+        let config = WhisperConfig {
+            mode: Some(crate::config::WhisperMode::Worker),
+            ..Default::default()
+        };
+        let Err(err) = RemoteWorkerTranscriber::new(&config) else {
+            panic!("worker mode without worker_address accepted");
+        };
+        assert!(err.to_string().contains("worker_address"), "{}", err);
+    }
+
+    #[test]
+    fn test_transcribe_over_tcp() {
+        let (address, server) = serve_once(Some("secret"), None);
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let progress: Progress = Arc::new(move |percent| sink.lock().unwrap().push(percent));
+
+        let samples = vec![0.25; CHUNK_SAMPLES * 2 + 100];
+        let text = client(address, Some("secret"))
+            .transcribe_with_progress(&samples, progress)
+            .unwrap();
+        assert_eq!(text, "32100 samples");
+        assert_eq!(*reported.lock().unwrap(), vec![50]);
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_transcribe_with_context() {
+        let (address, server) = serve_once(None, None);
+        let text = client(address, None)
+            .transcribe_with_context(&[0.25; 100], None, "tokio, serde")
+            .unwrap();
+        assert_eq!(text, "100 samples (tokio, serde)");
+        server.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_transcribe_over_tls() {
+        let tls = server_tls_config(&fixture("cert.pem"), &fixture("key.pem")).unwrap();
+        let (address, server) = serve_once(None, Some(tls));
+        let port = address.rsplit_once(':').unwrap().1;
+        let config = WhisperConfig {
+            mode: Some(crate::config::WhisperMode::Worker),
+            worker_address: Some(format!("localhost:{}", port)),
+            worker_tls: true,
+            worker_tls_ca: Some(fixture("cert.pem").display().to_string()),
+            ..Default::default()
+        };
+        let text = RemoteWorkerTranscriber::new(&config)
+            .unwrap()
+            .transcribe(&[0.25; 100])
+            .unwrap();
+        assert_eq!(text, "100 samples");
+        server.join().unwrap().unwrap();
+
+        // The usual web roots don't trust a self-signed certificate
+        let tls = server_tls_config(&fixture("cert.pem"), &fixture("key.pem")).unwrap();
+        let (address, server) = serve_once(None, Some(tls));
+        let port = address.rsplit_once(':').unwrap().1;
+        let config = WhisperConfig {
+            worker_tls_ca: None,
+            worker_address: Some(format!("localhost:{}", port)),
+            ..config
+        };
+        let err = RemoteWorkerTranscriber::new(&config)
+            .unwrap()
+            .transcribe(&[0.25; 100])
+            .unwrap_err();
+        assert!(matches!(err, TranscribeError::NetworkError(_)), "{}", err);
+        assert!(server.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_connections_served_concurrently() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let transcriber: Arc<dyn Transcriber> = Arc::new(OneAtATime {
+            inner: Arc::new(Counting),
+            turn: Mutex::new(()),
+        });
+        std::thread::spawn(move || serve_workers(listener, None, transcriber, None));
+
+        // A connection that never sends anything doesn't hold up the next
+        let _silent = TcpStream::connect(&address).unwrap();
+        let text = client(address, None).transcribe(&[0.25; 100]).unwrap();
+        assert_eq!(text, "100 samples");
+    }
+
+    #[test]
+    fn test_connection_limit() {
+        let connections = Connections::default();
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let wait = Arc::new(Mutex::new(wait));
+        for _ in 0..MAX_CONNECTIONS {
+            let wait = Arc::clone(&wait);
+            assert!(connections.spawn(move || {
+                let _ = wait.lock().unwrap().recv();
+            }));
+        }
+        assert!(!connections.spawn(|| {}));
+
+        // A finished connection makes room for another
+        release.send(()).unwrap();
+        while connections.0.load(Ordering::SeqCst) == MAX_CONNECTIONS {
+            std::thread::yield_now();
+        }
+        assert!(connections.spawn(|| {}));
+        drop(release);
+    }
+
+    #[test]
+    fn test_wrong_token_refused() {
+        let (address, server) = serve_once(Some("secret"), None);
+        let err = client(address, Some("guess"))
+            .transcribe(&[0.25; 100])
+            .unwrap_err();
+        assert!(err.to_string().contains("Authentication failed"), "{}", err);
+        assert!(server.join().unwrap().is_err());
+
+        let (address, server) = serve_once(Some("secret"), None);
+        let err = client(address, None).transcribe(&[0.25; 100]).unwrap_err();
+        assert!(err.to_string().contains("Authentication failed"), "{}", err);
+        assert!(server.join().unwrap().is_err());
+    }
+}
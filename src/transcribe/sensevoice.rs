@@ -38,6 +38,7 @@ pub struct SenseVoiceTranscriber {
 impl SenseVoiceTranscriber {
     pub fn new(config: &SenseVoiceConfig) -> Result<Self, TranscribeError> {
         let model_dir = resolve_model_path(&config.model)?;
+        crate::model_cache::mark_used(&model_dir);
 
         tracing::info!("Loading SenseVoice model from {:?}", model_dir);
         let start = std::time::Instant::now();
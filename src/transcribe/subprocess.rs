@@ -11,35 +11,374 @@
 //! - Clean separation of concerns
 //!
 //! Eager spawning:
-//! - `prepare()` spawns the worker when recording STARTS
+//! - `prepare()` spawns the worker when recording STARTS, without waiting
 //! - Worker loads model while user is speaking
-//! - `transcribe()` sends audio to already-ready worker
+//! - Audio is streamed to the worker as it's captured (`feed()`)
+//! - `transcribe()` sends the rest of the audio to the already-ready worker
 //! - Perceived latency is just transcription time, not model load + transcription
+//!
+//! The worker talks to us over a Unix socket pair, so while it transcribes it
+//! reports progress and can be asked to stop. Cancelling a transcription asks
+//! its worker to stop, and kills it if it hasn't exited after a moment.
+//!
+//! Up to `max_workers` workers transcribe at once, so recordings made back to
+//! back don't wait for each other; further calls block until a worker exits.
 
-use super::worker::READY_SIGNAL;
-use super::Transcriber;
-use crate::config::WhisperConfig;
+use super::worker::{self, Control, Header};
+use super::{Progress, Transcriber};
+use crate::config::{GpuBackend, WhisperConfig};
 use crate::error::TranscribeError;
-use std::io::{BufRead, BufReader, Read, Write};
-use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
-use std::sync::Mutex;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io::{BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::time::Duration;
 use ureq::serde_json;
 
+/// How long a cancelled worker gets to exit before it's killed
+const CANCEL_GRACE: Duration = Duration::from_secs(1);
+
 /// Response from the transcription worker process
 #[derive(Debug, serde::Deserialize)]
-struct WorkerResponse {
+pub(super) struct WorkerResponse {
     ok: bool,
     #[serde(default)]
     text: Option<String>,
     #[serde(default)]
     error: Option<String>,
+    /// Set when the worker discarded a low-confidence transcription
+    #[serde(default)]
+    confidence: Option<f32>,
+}
+
+impl WorkerResponse {
+    /// The transcribed text, or the error the worker reported
+    pub(super) fn into_text(self) -> Result<String, TranscribeError> {
+        if self.ok {
+            self.text.ok_or_else(|| {
+                TranscribeError::InferenceFailed("Worker returned ok but no text".to_string())
+            })
+        } else if let Some(confidence) = self.confidence {
+            Err(TranscribeError::LowConfidence(confidence))
+        } else {
+            Err(TranscribeError::InferenceFailed(
+                self.error
+                    .unwrap_or_else(|| "Unknown worker error".to_string()),
+            ))
+        }
+    }
+}
+
+/// Message from the worker process, one JSON object per line
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(super) enum WorkerMessage {
+    Ready {
+        version: u16,
+    },
+    Progress {
+        percent: u8,
+    },
+    Result(WorkerResponse),
+    /// Sent by a newer worker of the same protocol version
+    #[serde(other)]
+    Unknown,
+}
+
+/// Read the next message from the worker, None once it closed the connection
+pub(super) fn read_message<R: BufRead>(
+    reader: &mut R,
+) -> Result<Option<WorkerMessage>, std::io::Error> {
+    let mut line = String::new();
+    match reader.read_line(&mut line) {
+        Ok(0) => return Ok(None),
+        Ok(_) => {}
+        // The worker exited with audio it didn't read
+        Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    serde_json::from_str(&line).map(Some).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{} (output: {:?})", e, line),
+        )
+    })
+}
+
+/// Read the worker's messages up to its response, reporting progress
+pub(super) fn read_worker_response<R: BufRead>(
+    reader: &mut R,
+    progress: Option<&Progress>,
+) -> Result<WorkerResponse, TranscribeError> {
+    loop {
+        let message = read_message(reader).map_err(|e| {
+            TranscribeError::InferenceFailed(format!("Failed to read worker output: {}", e))
+        })?;
+        match message {
+            Some(WorkerMessage::Result(response)) => return Ok(response),
+            Some(WorkerMessage::Progress { percent }) => {
+                if let Some(progress) = progress {
+                    progress(percent);
+                }
+            }
+            Some(_) => {}
+            None => {
+                return Err(TranscribeError::InferenceFailed(
+                    "Worker closed the connection without a response".to_string(),
+                ));
+            }
+        }
+    }
 }
 
 /// A prepared worker process ready to receive audio
 struct PreparedWorker {
     child: Child,
-    stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
+    /// Our end of the socket, for reading the worker's messages
+    socket: BufReader<UnixStream>,
+}
+
+/// A worker transcribing right now
+struct RunningWorker {
+    child: Child,
+    /// Our end of the socket, for sending control messages
+    control: SocketWriter,
+}
+
+/// Our end of a worker's socket, for writing
+///
+/// voxtype restores the default SIGPIPE action, so a plain write to a worker
+/// that has exited would end the daemon. This fails with EPIPE instead.
+pub(super) struct SocketWriter(UnixStream);
+
+impl SocketWriter {
+    pub(super) fn new(socket: UnixStream) -> std::io::Result<Self> {
+        #[cfg(target_os = "macos")]
+        {
+            let on: libc::c_int = 1;
+            let ret = unsafe {
+                libc::setsockopt(
+                    socket.as_raw_fd(),
+                    libc::SOL_SOCKET,
+                    libc::SO_NOSIGPIPE,
+                    &on as *const libc::c_int as *const libc::c_void,
+                    std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+                )
+            };
+            if ret == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+        }
+        Ok(Self(socket))
+    }
+}
+
+impl Write for SocketWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        #[cfg(target_os = "linux")]
+        let flags = libc::MSG_NOSIGNAL;
+        #[cfg(not(target_os = "linux"))]
+        let flags = 0;
+        let sent = unsafe {
+            libc::send(
+                self.0.as_raw_fd(),
+                buf.as_ptr() as *const libc::c_void,
+                buf.len(),
+                flags,
+            )
+        };
+        if sent < 0 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(sent as usize)
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// What the writer thread sends next
+enum Frame {
+    Audio(Vec<f32>),
+    /// Initial prompt context, just before End
+    Prompt(String),
+    End,
+}
+
+/// Audio frames on their way to a worker's socket
+///
+/// A background thread does the writing, so sending never blocks, even while
+/// the worker is still loading the model and not reading yet.
+struct AudioStream {
+    tx: mpsc::Sender<Frame>,
+    writer: std::thread::JoinHandle<std::io::Result<()>>,
+    /// Samples sent so far
+    sent: usize,
+    /// Hash of the samples sent, to check the recording starts with them
+    hash: DefaultHasher,
+}
+
+impl AudioStream {
+    fn new(mut socket: SocketWriter) -> Self {
+        let (tx, rx) = mpsc::channel::<Frame>();
+        let writer = std::thread::spawn(move || {
+            Header::current().write(&mut socket)?;
+            for frame in rx {
+                match frame {
+                    Frame::Audio(samples) => worker::write_audio(&mut socket, &samples)?,
+                    Frame::Prompt(text) => {
+                        worker::write_control(&mut socket, &Control::Prompt { text })?
+                    }
+                    Frame::End => return worker::write_end(&mut socket),
+                }
+            }
+            // Abandoned: end the stream without the End message
+            socket.0.shutdown(Shutdown::Write)
+        });
+        Self {
+            tx,
+            writer,
+            sent: 0,
+            hash: DefaultHasher::new(),
+        }
+    }
+
+    fn hash_samples(hasher: &mut DefaultHasher, samples: &[f32]) {
+        for sample in samples {
+            hasher.write_u32(sample.to_bits());
+        }
+    }
+
+    /// Queue samples for the worker
+    fn send(&mut self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        Self::hash_samples(&mut self.hash, samples);
+        self.sent += samples.len();
+        let _ = self.tx.send(Frame::Audio(samples.to_vec()));
+    }
+
+    /// Whether `samples` starts with everything sent so far
+    ///
+    /// Not the case when the capture dropped a chunk on its way here.
+    fn is_prefix_of(&self, samples: &[f32]) -> bool {
+        if samples.len() < self.sent {
+            return false;
+        }
+        let mut hasher = DefaultHasher::new();
+        Self::hash_samples(&mut hasher, &samples[..self.sent]);
+        hasher.finish() == self.hash.finish()
+    }
+
+    /// End the audio, after the prompt context if any, and wait until all
+    /// of it is written
+    ///
+    /// Dropping the stream instead ends it without the End message, and the
+    /// worker exits without transcribing.
+    fn finish(self, context: Option<&str>) -> Result<(), TranscribeError> {
+        if let Some(context) = context {
+            let _ = self.tx.send(Frame::Prompt(context.to_string()));
+        }
+        let _ = self.tx.send(Frame::End);
+        drop(self.tx);
+        self.writer
+            .join()
+            .map_err(|_| TranscribeError::InferenceFailed("Audio writer panicked".to_string()))?
+            .map_err(|e| {
+                TranscribeError::InferenceFailed(format!("Failed to write audio to worker: {}", e))
+            })
+    }
+}
+
+/// The worker `prepare()` spawns ahead of the transcription
+enum Prepared {
+    None,
+    /// Spawned, still loading the model
+    Loading,
+    Ready(PreparedWorker),
+}
+
+/// Prepared worker, shared with the thread that waits for it to load
+struct PreparedSlot {
+    worker: Mutex<Prepared>,
+    loaded: Condvar,
+}
+
+impl PreparedSlot {
+    fn new() -> Self {
+        Self {
+            worker: Mutex::new(Prepared::None),
+            loaded: Condvar::new(),
+        }
+    }
+
+    fn set(&self, prepared: Prepared) {
+        *self.worker.lock().unwrap() = prepared;
+        self.loaded.notify_all();
+    }
+
+    /// Take the prepared worker, waiting for it if it's still loading
+    ///
+    /// Also returns how long it waited, when it did.
+    fn take(&self) -> (Option<PreparedWorker>, Option<std::time::Duration>) {
+        let start = std::time::Instant::now();
+        let mut prepared = self.worker.lock().unwrap();
+        let mut waited = None;
+        while matches!(*prepared, Prepared::Loading) {
+            prepared = self.loaded.wait(prepared).unwrap();
+            waited = Some(start.elapsed());
+        }
+        match std::mem::replace(&mut *prepared, Prepared::None) {
+            Prepared::Ready(worker) => (Some(worker), waited),
+            _ => (None, None),
+        }
+    }
+}
+
+/// Counting semaphore for the workers allowed to transcribe at once
+struct WorkerSlots {
+    free: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl WorkerSlots {
+    fn new(count: usize) -> Self {
+        Self {
+            free: Mutex::new(count.max(1)),
+            freed: Condvar::new(),
+        }
+    }
+
+    /// Wait for a free slot, held until the guard is dropped
+    fn acquire(&self) -> WorkerSlot<'_> {
+        let mut free = self.free.lock().unwrap();
+        if *free == 0 {
+            tracing::debug!("All transcription workers busy, waiting for one to finish");
+        }
+        while *free == 0 {
+            free = self.freed.wait(free).unwrap();
+        }
+        *free -= 1;
+        WorkerSlot(self)
+    }
+}
+
+/// A slot taken from [`WorkerSlots`]
+struct WorkerSlot<'a>(&'a WorkerSlots);
+
+impl Drop for WorkerSlot<'_> {
+    fn drop(&mut self) {
+        *self.0.free.lock().unwrap() += 1;
+        self.0.freed.notify_one();
+    }
 }
 
 /// Subprocess-based transcriber for GPU isolation
@@ -56,7 +395,15 @@ pub struct SubprocessTranscriber {
     /// Path to the config file (if any)
     config_path: Option<std::path::PathBuf>,
     /// Pre-spawned worker (from prepare())
-    prepared_worker: Mutex<Option<PreparedWorker>>,
+    prepared: Arc<PreparedSlot>,
+    /// Audio streamed to the pre-spawned worker while recording
+    stream: Mutex<Option<AudioStream>>,
+    /// Model load time on the critical path of the last transcription
+    last_load_time: Mutex<Option<std::time::Duration>>,
+    /// Workers transcribing right now, so `cancel()` can stop them
+    running: Mutex<Vec<RunningWorker>>,
+    /// Limits the workers transcribing at once to `max_workers`
+    slots: WorkerSlots,
 }
 
 impl SubprocessTranscriber {
@@ -68,7 +415,11 @@ impl SubprocessTranscriber {
         Ok(Self {
             config: config.clone(),
             config_path,
-            prepared_worker: Mutex::new(None),
+            prepared: Arc::new(PreparedSlot::new()),
+            stream: Mutex::new(None),
+            last_load_time: Mutex::new(None),
+            running: Mutex::new(Vec::new()),
+            slots: WorkerSlots::new(config.max_workers),
         })
     }
 
@@ -90,11 +441,15 @@ impl SubprocessTranscriber {
         if let Some(ref config_path) = self.config_path {
             cmd.arg("--config").arg(config_path);
         }
+        if let Some(instance) = crate::config::Config::instance() {
+            cmd.arg("--instance").arg(instance);
+        }
 
+        // Stdout and stderr are only for logging, so they go to our log
         cmd.arg("transcribe-worker")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
+            .stdin(Stdio::null())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
 
         // Pass essential config via command-line arguments
         cmd.arg("--model").arg(&self.config.model);
@@ -108,126 +463,113 @@ impl SubprocessTranscriber {
         if let Some(threads) = self.config.threads {
             cmd.arg("--threads").arg(threads.to_string());
         }
+        if let Some(threshold) = self.config.no_speech_threshold {
+            cmd.arg("--no-speech-threshold").arg(threshold.to_string());
+        }
+        if !self.config.suppress_non_speech_tokens {
+            cmd.arg("--keep-non-speech-tokens");
+        }
+        if self.config.gpu_backend == GpuBackend::Cpu {
+            cmd.arg("--cpu");
+        }
 
         Ok(cmd)
     }
 
-    /// Spawn a worker process and wait for it to be ready
-    fn spawn_and_wait_ready(&self) -> Result<PreparedWorker, TranscribeError> {
-        let mut cmd = self.build_worker_command()?;
+    /// Spawn a worker process, with the stream for its audio
+    ///
+    /// The worker gets its end of a socket pair as [`worker::SOCKET_FD`].
+    fn spawn_worker(
+        mut cmd: Command,
+    ) -> Result<(Child, AudioStream, BufReader<UnixStream>), TranscribeError> {
+        let socket_err =
+            |e: std::io::Error| TranscribeError::InitFailed(format!("Worker socket failed: {}", e));
+        let (socket, worker_end) = UnixStream::pair().map_err(socket_err)?;
+        let fd = worker_end.as_raw_fd();
+        cmd.arg("--socket-fd").arg(worker::SOCKET_FD.to_string());
+        // Safety: only async-signal-safe calls between fork and exec
+        unsafe {
+            cmd.pre_exec(move || {
+                // The copy dup2 makes is kept open across exec, unlike the
+                // original; if it already is the right number, unmark that
+                let ok = if fd == worker::SOCKET_FD {
+                    libc::fcntl(fd, libc::F_SETFD, 0) != -1
+                } else {
+                    libc::dup2(fd, worker::SOCKET_FD) != -1
+                };
+                if ok {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::last_os_error())
+                }
+            });
+        }
 
-        let mut child = cmd.spawn().map_err(|e| {
+        let child = cmd.spawn().map_err(|e| {
             TranscribeError::InitFailed(format!("Failed to spawn transcribe-worker: {}", e))
         })?;
+        drop(worker_end);
 
-        // Get handles
-        let stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| TranscribeError::InitFailed("Worker stdin not available".to_string()))?;
-
-        let stdout = child.stdout.take().ok_or_else(|| {
-            TranscribeError::InitFailed("Worker stdout not available".to_string())
-        })?;
-
-        let mut stdout = BufReader::new(stdout);
-
-        // Wait for READY signal (model loaded)
-        let mut ready_line = String::new();
-        stdout.read_line(&mut ready_line).map_err(|e| {
-            TranscribeError::InitFailed(format!("Failed to read READY signal: {}", e))
-        })?;
+        let writer = socket
+            .try_clone()
+            .and_then(SocketWriter::new)
+            .map_err(socket_err)?;
+        Ok((child, AudioStream::new(writer), BufReader::new(socket)))
+    }
 
-        if ready_line.trim() != READY_SIGNAL {
-            // Worker failed during model load - try to get error from JSON
-            if let Ok(response) = serde_json::from_str::<WorkerResponse>(&ready_line) {
-                if let Some(error) = response.error {
-                    return Err(TranscribeError::InitFailed(error));
-                }
-            }
-            return Err(TranscribeError::InitFailed(format!(
-                "Worker failed to load model (got: {:?})",
-                ready_line.trim()
-            )));
+    /// Wait for a spawned worker to be ready
+    fn wait_ready(
+        mut child: Child,
+        mut socket: BufReader<UnixStream>,
+    ) -> Result<PreparedWorker, TranscribeError> {
+        if let Err(e) = Self::read_ready(&mut socket) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
         }
 
         tracing::debug!("Worker ready (model loaded)");
 
-        Ok(PreparedWorker {
-            child,
-            stdin,
-            stdout,
-        })
-    }
-
-    /// Write audio samples to the worker's stdin
-    fn write_audio_to_worker(
-        stdin: &mut ChildStdin,
-        samples: &[f32],
-    ) -> Result<(), TranscribeError> {
-        // Write sample count (u32 little-endian)
-        let count = samples.len() as u32;
-        stdin.write_all(&count.to_le_bytes()).map_err(|e| {
-            TranscribeError::InferenceFailed(format!("Failed to write sample count: {}", e))
-        })?;
-
-        // Write samples (f32 little-endian)
-        let samples_bytes = unsafe {
-            std::slice::from_raw_parts(
-                samples.as_ptr() as *const u8,
-                std::mem::size_of_val(samples),
-            )
-        };
-        stdin.write_all(samples_bytes).map_err(|e| {
-            TranscribeError::InferenceFailed(format!("Failed to write audio samples: {}", e))
-        })?;
-
-        stdin.flush().map_err(|e| {
-            TranscribeError::InferenceFailed(format!("Failed to flush stdin: {}", e))
-        })?;
-
-        Ok(())
+        Ok(PreparedWorker { child, socket })
     }
 
-    /// Read the JSON response from the worker's stdout
-    fn read_worker_response(
-        stdout: &mut BufReader<ChildStdout>,
-    ) -> Result<WorkerResponse, TranscribeError> {
-        let mut line = String::new();
-        stdout.read_line(&mut line).map_err(|e| {
-            TranscribeError::InferenceFailed(format!("Failed to read worker output: {}", e))
+    /// Read the ready message (model loaded)
+    fn read_ready(socket: &mut BufReader<UnixStream>) -> Result<(), TranscribeError> {
+        let message = read_message(socket).map_err(|e| {
+            TranscribeError::InitFailed(format!("Failed to read ready message: {}", e))
         })?;
 
-        serde_json::from_str(&line).map_err(|e| {
-            TranscribeError::InferenceFailed(format!(
-                "Failed to parse worker response: {} (output: {:?})",
-                e, line
-            ))
-        })
-    }
-}
-
-impl Transcriber for SubprocessTranscriber {
-    fn prepare(&self) {
-        tracing::debug!("Preparing subprocess transcriber (spawning worker)...");
-        let start = std::time::Instant::now();
-
-        match self.spawn_and_wait_ready() {
-            Ok(worker) => {
-                let mut guard = self.prepared_worker.lock().unwrap();
-                *guard = Some(worker);
-                tracing::info!(
-                    "Worker prepared in {:.2}s (model loaded while recording)",
-                    start.elapsed().as_secs_f32()
-                );
-            }
-            Err(e) => {
-                tracing::warn!("Failed to prepare worker: {} (will retry on transcribe)", e);
+        match message {
+            Some(WorkerMessage::Ready { version }) if version == worker::PROTOCOL_VERSION => Ok(()),
+            Some(WorkerMessage::Ready { version }) => Err(TranscribeError::InitFailed(format!(
+                "Worker speaks a different protocol (version {}, expected {}). \
+                 Restart voxtype after upgrading.",
+                version,
+                worker::PROTOCOL_VERSION
+            ))),
+            // Worker failed during model load
+            Some(WorkerMessage::Result(response)) => {
+                Err(TranscribeError::InitFailed(response.error.unwrap_or_else(
+                    || "Worker failed to load model".to_string(),
+                )))
             }
+            Some(other) => Err(TranscribeError::InitFailed(format!(
+                "Worker failed to load model (got: {:?})",
+                other
+            ))),
+            None => Err(TranscribeError::InitFailed(
+                "Worker exited before loading the model (see the log for its output)".to_string(),
+            )),
         }
     }
 
-    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+    /// Transcribe with a worker, prepared or new
+    fn run(
+        &self,
+        samples: &[f32],
+        progress: Option<&Progress>,
+        context: Option<&str>,
+    ) -> Result<String, TranscribeError> {
         if samples.is_empty() {
             return Err(TranscribeError::AudioFormat(
                 "Empty audio buffer".to_string(),
@@ -235,50 +577,96 @@ impl Transcriber for SubprocessTranscriber {
         }
 
         let duration_secs = samples.len() as f32 / 16000.0;
+        let _slot = self.slots.acquire();
+
+        // Send the audio the prepared worker doesn't have yet, even before
+        // it's ready, so it starts transcribing as soon as the model loads
+        let streamed = match self.stream.lock().unwrap().take() {
+            Some(mut stream) if stream.is_prefix_of(samples) => {
+                let sent = stream.sent;
+                stream.send(&samples[sent..]);
+                Some((stream, sent))
+            }
+            Some(_) => {
+                tracing::warn!("Audio streamed to the worker is incomplete, starting a new worker");
+                None
+            }
+            None => None,
+        };
 
-        // Try to use prepared worker, or spawn a new one
-        let mut prepared = self.prepared_worker.lock().unwrap();
-        let mut worker = match prepared.take() {
-            Some(w) => {
+        // Use the prepared worker, or spawn a new one
+        let (prepared, waited) = self.prepared.take();
+        let (mut worker, stream, load_time) = match (prepared, streamed) {
+            (Some(w), Some((stream, sent))) => {
                 tracing::debug!(
-                    "Using pre-spawned worker for {:.2}s of audio",
-                    duration_secs
+                    "Using pre-spawned worker for {:.2}s of audio ({:.2}s streamed while recording)",
+                    duration_secs,
+                    sent as f32 / 16000.0
                 );
-                w
+                if let Some(waited) = waited {
+                    tracing::debug!(
+                        "Waited {:.2}s for the worker to load the model",
+                        waited.as_secs_f32()
+                    );
+                }
+                (w, stream, waited)
             }
-            None => {
+            (prepared, _) => {
+                if let Some(mut stale) = prepared {
+                    let _ = stale.child.kill();
+                    let _ = stale.child.wait();
+                }
                 tracing::debug!(
                     "No prepared worker, spawning new one for {:.2}s of audio",
                     duration_secs
                 );
-                self.spawn_and_wait_ready()?
+                let load_start = std::time::Instant::now();
+                let (child, mut stream, socket) = Self::spawn_worker(self.build_worker_command()?)?;
+                stream.send(samples);
+                let worker = Self::wait_ready(child, socket)?;
+                (worker, stream, Some(load_start.elapsed()))
             }
         };
-        drop(prepared); // Release lock
+        *self.last_load_time.lock().unwrap() = load_time;
 
         let start = std::time::Instant::now();
 
-        // Write audio to worker
-        Self::write_audio_to_worker(&mut worker.stdin, samples)?;
-        drop(worker.stdin); // Close stdin to signal EOF
-
-        // Read response
-        let response = Self::read_worker_response(&mut worker.stdout)?;
+        // Finish writing the audio
+        stream.finish(context)?;
+
+        // Read response, with the worker where cancel() can stop it
+        let pid = worker.child.id();
+        let control = worker
+            .socket
+            .get_ref()
+            .try_clone()
+            .and_then(SocketWriter::new)
+            .map_err(|e| {
+                TranscribeError::InferenceFailed(format!("Worker socket failed: {}", e))
+            })?;
+        self.running.lock().unwrap().push(RunningWorker {
+            child: worker.child,
+            control,
+        });
+        let response = read_worker_response(&mut worker.socket, progress);
+        let running = {
+            let mut running = self.running.lock().unwrap();
+            running
+                .iter()
+                .position(|w| w.child.id() == pid)
+                .map(|i| running.swap_remove(i))
+        };
+        let Some(mut running) = running else {
+            return Err(TranscribeError::Cancelled);
+        };
+        let response = response?;
 
-        // Wait for process to exit
-        let status = worker.child.wait().map_err(|e| {
+        // Wait for process to exit; its output is already in our log
+        let status = running.child.wait().map_err(|e| {
             TranscribeError::InferenceFailed(format!("Failed to wait for worker: {}", e))
         })?;
-
         if !status.success() {
-            // Try to get stderr for error details
-            if let Some(mut stderr) = worker.child.stderr.take() {
-                let mut err_output = String::new();
-                let _ = stderr.read_to_string(&mut err_output);
-                if !err_output.is_empty() {
-                    tracing::warn!("Worker stderr: {}", err_output.trim());
-                }
-            }
+            tracing::warn!("Transcription worker exited with {}", status);
         }
 
         tracing::debug!(
@@ -286,18 +674,116 @@ impl Transcriber for SubprocessTranscriber {
             start.elapsed().as_secs_f32()
         );
 
-        // Handle response
-        if response.ok {
-            response.text.ok_or_else(|| {
-                TranscribeError::InferenceFailed("Worker returned ok but no text".to_string())
-            })
-        } else {
-            Err(TranscribeError::InferenceFailed(
-                response
-                    .error
-                    .unwrap_or_else(|| "Unknown worker error".to_string()),
-            ))
+        response.into_text()
+    }
+}
+
+impl Transcriber for SubprocessTranscriber {
+    fn last_model_load_time(&self) -> Option<std::time::Duration> {
+        *self.last_load_time.lock().unwrap()
+    }
+
+    fn cancel(&self) {
+        let mut workers: Vec<RunningWorker> = self.running.lock().unwrap().drain(..).collect();
+        if workers.is_empty() {
+            return;
+        }
+        for worker in &mut workers {
+            tracing::info!(
+                "Cancelling transcription worker (pid {})",
+                worker.child.id()
+            );
+            let _ = worker::write_control(&mut worker.control, &Control::Cancel);
+        }
+
+        // Give them a moment to stop on their own, without holding up the caller
+        std::thread::spawn(move || {
+            let deadline = std::time::Instant::now() + CANCEL_GRACE;
+            for mut worker in workers {
+                while matches!(worker.child.try_wait(), Ok(None))
+                    && std::time::Instant::now() < deadline
+                {
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                if matches!(worker.child.try_wait(), Ok(None)) {
+                    tracing::info!("Killing transcription worker (pid {})", worker.child.id());
+                    let _ = worker.child.kill();
+                }
+                let _ = worker.child.wait();
+            }
+        });
+    }
+
+    fn max_concurrent(&self) -> usize {
+        self.config.max_workers.max(1)
+    }
+
+    fn prepare(&self) {
+        {
+            let mut prepared = self.prepared.worker.lock().unwrap();
+            if !matches!(*prepared, Prepared::None) {
+                tracing::debug!("Worker already prepared");
+                return;
+            }
+            *prepared = Prepared::Loading;
         }
+        tracing::debug!("Preparing subprocess transcriber (spawning worker)...");
+
+        let start = std::time::Instant::now();
+        let (child, stream, socket) = match self.build_worker_command().and_then(Self::spawn_worker)
+        {
+            Ok(spawned) => spawned,
+            Err(e) => {
+                tracing::warn!("Failed to prepare worker: {} (will retry on transcribe)", e);
+                self.prepared.set(Prepared::None);
+                return;
+            }
+        };
+        *self.stream.lock().unwrap() = Some(stream);
+
+        // Load in the background so the recording starts at once;
+        // transcribe() waits for the worker if it's still loading
+        let prepared = Arc::clone(&self.prepared);
+        std::thread::spawn(move || match Self::wait_ready(child, socket) {
+            Ok(worker) => {
+                tracing::info!(
+                    "Worker prepared in {:.2}s (model loaded while recording)",
+                    start.elapsed().as_secs_f32()
+                );
+                prepared.set(Prepared::Ready(worker));
+            }
+            Err(e) => {
+                tracing::warn!("Failed to prepare worker: {} (will retry on transcribe)", e);
+                prepared.set(Prepared::None);
+            }
+        });
+    }
+
+    fn feed(&self, samples: &[f32]) {
+        if let Some(stream) = self.stream.lock().unwrap().as_mut() {
+            stream.send(samples);
+        }
+    }
+
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        self.run(samples, None, None)
+    }
+
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, Some(&progress), None)
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, progress.as_ref(), Some(context))
     }
 }
 
@@ -316,5 +802,177 @@ mod tests {
             serde_json::from_str(r#"{"ok": false, "error": "Model not found"}"#).unwrap();
         assert!(!error.ok);
         assert_eq!(error.error, Some("Model not found".to_string()));
+        assert_eq!(error.confidence, None);
+
+        let rejected: WorkerResponse =
+            serde_json::from_str(r#"{"ok": false, "error": "Low confidence", "confidence": 0.25}"#)
+                .unwrap();
+        assert_eq!(rejected.confidence, Some(0.25));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cancel_kills_running_workers() {
+        let transcriber = SubprocessTranscriber::new(&WhisperConfig::default(), None).unwrap();
+        // A worker that ignores the cancel request
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id() as libc::pid_t;
+        let (control, mut worker_end) = UnixStream::pair().unwrap();
+        let control = SocketWriter::new(control).unwrap();
+        transcriber
+            .running
+            .lock()
+            .unwrap()
+            .push(RunningWorker { child, control });
+
+        let start = std::time::Instant::now();
+        transcriber.cancel();
+        assert!(transcriber.running.lock().unwrap().is_empty());
+        assert!(start.elapsed() < CANCEL_GRACE);
+
+        let mut request = [0u8; 5];
+        std::io::Read::read_exact(&mut worker_end, &mut request).unwrap();
+        assert_eq!(request[0], 3);
+
+        // Killed after the grace period, and reaped
+        while unsafe { libc::kill(pid, 0) } == 0 {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(start.elapsed() >= CANCEL_GRACE);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_worker_socket() {
+        let fake_worker = |script: String| {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(script);
+            SubprocessTranscriber::spawn_worker(cmd).unwrap()
+        };
+
+        let (child, _stream, socket) = fake_worker(format!(
+            "printf '{{\"type\":\"ready\",\"version\":{}}}\\n' >&3; \
+             printf '{{\"type\":\"progress\",\"percent\":50}}\\n' >&3; \
+             printf '{{\"type\":\"future\"}}\\n' >&3; \
+             printf '{{\"type\":\"result\",\"ok\":true,\"text\":\"hi\"}}\\n' >&3",
+            worker::PROTOCOL_VERSION
+        ));
+        let mut worker = SubprocessTranscriber::wait_ready(child, socket).unwrap();
+        let reported = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let progress: Progress = Arc::new(move |percent| sink.lock().unwrap().push(percent));
+        let response = read_worker_response(&mut worker.socket, Some(&progress)).unwrap();
+        assert_eq!(response.text.as_deref(), Some("hi"));
+        assert_eq!(*reported.lock().unwrap(), vec![50]);
+        worker.child.wait().unwrap();
+
+        let (child, _stream, socket) =
+            fake_worker("printf '{\"type\":\"ready\",\"version\":1}\\n' >&3".to_string());
+        let Err(err) = SubprocessTranscriber::wait_ready(child, socket) else {
+            panic!("worker of another protocol version accepted");
+        };
+        assert!(err.to_string().contains("different protocol"), "{}", err);
+
+        let (child, _stream, socket) = fake_worker("exit 1".to_string());
+        let Err(err) = SubprocessTranscriber::wait_ready(child, socket) else {
+            panic!("worker that exited accepted");
+        };
+        assert!(err.to_string().contains("exited"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_take_waits_for_loading_worker() {
+        let slot = Arc::new(PreparedSlot::new());
+        assert!(slot.take().0.is_none());
+
+        slot.set(Prepared::Loading);
+        let loader = Arc::clone(&slot);
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            let child = Command::new("true").spawn().unwrap();
+            let socket = BufReader::new(UnixStream::pair().unwrap().0);
+            loader.set(Prepared::Ready(PreparedWorker { child, socket }));
+        });
+
+        let (worker, waited) = slot.take();
+        handle.join().unwrap();
+        let mut worker = worker.expect("prepared worker");
+        assert!(waited.unwrap() >= std::time::Duration::from_millis(40));
+        assert!(matches!(*slot.worker.lock().unwrap(), Prepared::None));
+        worker.child.wait().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_audio_stream_frames() {
+        let (socket, mut worker_end) = UnixStream::pair().unwrap();
+        let mut stream = AudioStream::new(SocketWriter::new(socket).unwrap());
+
+        let recording = [0.1, 0.2, 0.3, 0.4];
+        stream.send(&recording[..2]);
+        stream.send(&[]);
+        assert_eq!(stream.sent, 2);
+        assert!(stream.is_prefix_of(&recording));
+        assert!(!stream.is_prefix_of(&recording[1..]));
+        assert!(!stream.is_prefix_of(&recording[..1]));
+
+        stream.send(&recording[2..]);
+        stream.finish(Some("tokio")).unwrap();
+
+        let mut written = Vec::new();
+        std::io::Read::read_to_end(&mut worker_end, &mut written).unwrap();
+        // Header, two Audio messages of two samples, the Prompt, then End
+        let prompt = br#"{"type":"prompt","text":"tokio"}"#;
+        assert_eq!(written.len(), 14 + (5 + 8) * 2 + 5 + prompt.len() + 5);
+        assert!(written.windows(prompt.len()).any(|w| w == prompt));
+        assert_eq!(&written[..4], b"VOXW");
+        assert_eq!(&written[written.len() - 5..], &[2, 0, 0, 0, 0]);
+
+        // An abandoned stream ends without the End message
+        let (socket, mut worker_end) = UnixStream::pair().unwrap();
+        let mut stream = AudioStream::new(SocketWriter::new(socket).unwrap());
+        stream.send(&recording);
+        drop(stream);
+        let mut written = Vec::new();
+        std::io::Read::read_to_end(&mut worker_end, &mut written).unwrap();
+        assert_eq!(written.len(), 14 + 5 + 16);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_socket_writer_to_exited_worker() {
+        let (socket, worker_end) = UnixStream::pair().unwrap();
+        let mut writer = SocketWriter::new(socket).unwrap();
+        writer.write_all(b"VOXW").unwrap();
+        drop(worker_end);
+        let err = writer.write_all(b"VOXW").unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn test_worker_slots_limit_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let slots = WorkerSlots::new(2);
+        let active = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _slot = slots.acquire();
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(*slots.free.lock().unwrap(), 2);
+
+        // Zero is treated as one
+        assert_eq!(*WorkerSlots::new(0).free.lock().unwrap(), 1);
     }
 }
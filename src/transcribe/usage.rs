@@ -0,0 +1,294 @@
+//! Audio sent to the remote backend, and an optional budget for it
+//!
+//! Paid APIs bill by the minute of audio. Every recording the remote backend
+//! transcribes is added to a per-day and per-month total, kept in
+//! `remote_usage.json` in the state directory so it survives restarts and is
+//! shared with `voxtype transcribe`. `voxtype status --detail` shows it.
+//!
+//! With `remote_daily_limit_mins` or `remote_monthly_limit_mins` set, a
+//! recording that would go over the limit is transcribed with the local
+//! whisper model instead, until the day or month is over.
+
+use super::{Progress, Segment, Transcriber};
+use crate::config::{Config, WhisperConfig, WhisperMode};
+use crate::error::TranscribeError;
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Serializes updates of the usage file within this process
+static UPDATE: Mutex<()> = Mutex::new(());
+
+/// Seconds of audio sent so far
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Usage {
+    /// Day of the last recording counted
+    pub date: Option<NaiveDate>,
+    /// Seconds sent on that day
+    pub day_secs: f64,
+    /// Seconds sent in that month
+    pub month_secs: f64,
+}
+
+impl Usage {
+    /// Path of the usage file
+    pub fn path() -> PathBuf {
+        Config::state_dir().join("remote_usage.json")
+    }
+
+    /// Load the usage file, or no usage if there is none
+    pub fn read() -> Self {
+        std::fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the usage file, replacing the previous one atomically
+    pub fn write(&self) -> std::io::Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// Seconds sent on `today`
+    pub fn today_secs(&self, today: NaiveDate) -> f64 {
+        if self.date == Some(today) {
+            self.day_secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Seconds sent in the month of `today`
+    pub fn month_secs(&self, today: NaiveDate) -> f64 {
+        match self.date {
+            Some(date) if (date.year(), date.month()) == (today.year(), today.month()) => {
+                self.month_secs
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Count `secs` more sent on `today`
+    pub fn add(&mut self, secs: f64, today: NaiveDate) {
+        self.day_secs = self.today_secs(today) + secs;
+        self.month_secs = self.month_secs(today) + secs;
+        self.date = Some(today);
+    }
+}
+
+/// The remote backend's limits, in seconds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    pub daily_secs: Option<f64>,
+    pub monthly_secs: Option<f64>,
+}
+
+impl Budget {
+    /// The limits configured in `[whisper]`
+    pub fn from_config(config: &WhisperConfig) -> Self {
+        let secs = |mins: Option<u32>| mins.map(|mins| mins as f64 * 60.0);
+        Self {
+            daily_secs: secs(config.remote_daily_limit_mins),
+            monthly_secs: secs(config.remote_monthly_limit_mins),
+        }
+    }
+
+    /// Which limit sending `secs` more on `today` would go over, if any
+    pub fn exceeded_by(&self, usage: &Usage, secs: f64, today: NaiveDate) -> Option<&'static str> {
+        if self
+            .daily_secs
+            .is_some_and(|limit| usage.today_secs(today) + secs > limit)
+        {
+            return Some("daily");
+        }
+        if self
+            .monthly_secs
+            .is_some_and(|limit| usage.month_secs(today) + secs > limit)
+        {
+            return Some("monthly");
+        }
+        None
+    }
+}
+
+/// Creates the local transcriber to use over budget
+type Fallback = Box<dyn Fn() -> Result<Box<dyn Transcriber>, TranscribeError> + Send + Sync>;
+
+/// The remote transcriber, counting the audio it sends and handing
+/// recordings to a local one over budget
+pub struct MeteredTranscriber {
+    remote: Box<dyn Transcriber>,
+    budget: Budget,
+    fallback: Fallback,
+    /// The local transcriber, created the first time it's needed
+    local: Mutex<Option<Arc<dyn Transcriber>>>,
+}
+
+impl MeteredTranscriber {
+    pub fn new(
+        remote: Box<dyn Transcriber>,
+        budget: Budget,
+        fallback: impl Fn() -> Result<Box<dyn Transcriber>, TranscribeError> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            remote,
+            budget,
+            fallback: Box::new(fallback),
+            local: Mutex::new(None),
+        }
+    }
+
+    /// The remote transcriber for `config`, falling back to local whisper
+    /// with the same settings
+    pub fn from_config(
+        config: &WhisperConfig,
+        config_path: Option<PathBuf>,
+    ) -> Result<Self, TranscribeError> {
+        let remote = super::remote::RemoteTranscriber::new(config)?;
+        let mut local = config.clone();
+        local.mode = Some(WhisperMode::Local);
+        local.backend = None;
+        Ok(Self::new(
+            Box::new(remote),
+            Budget::from_config(config),
+            move || super::create_transcriber_with_config_path(&local, config_path.clone()),
+        ))
+    }
+
+    /// Run `f` with the remote transcriber, counting the audio, or with the
+    /// local one when that would go over budget
+    fn run<T>(
+        &self,
+        samples: &[f32],
+        f: impl FnOnce(&dyn Transcriber) -> Result<T, TranscribeError>,
+    ) -> Result<T, TranscribeError> {
+        let secs = samples.len() as f64 / 16000.0;
+        let today = Local::now().date_naive();
+
+        if let Some(limit) = self.budget.exceeded_by(&Usage::read(), secs, today) {
+            tracing::warn!(
+                "Remote {} limit reached, transcribing with the local model",
+                limit
+            );
+            let cached = self.local.lock().unwrap().clone();
+            let local = match cached {
+                Some(local) => local,
+                None => {
+                    let local: Arc<dyn Transcriber> =
+                        Arc::from((self.fallback)().map_err(|e| {
+                            TranscribeError::InitFailed(format!(
+                                "Remote {} limit reached and the local model failed to load: {}",
+                                limit, e
+                            ))
+                        })?);
+                    *self.local.lock().unwrap() = Some(local.clone());
+                    local
+                }
+            };
+            return f(local.as_ref());
+        }
+
+        let result = f(self.remote.as_ref())?;
+
+        let _guard = UPDATE.lock().unwrap();
+        let mut usage = Usage::read();
+        usage.add(secs, today);
+        if let Err(e) = usage.write() {
+            tracing::warn!("Failed to save remote usage: {}", e);
+        }
+        Ok(result)
+    }
+}
+
+impl Transcriber for MeteredTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        self.run(samples, |t| t.transcribe(samples))
+    }
+
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, |t| t.transcribe_with_progress(samples, progress))
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        self.run(samples, |t| {
+            t.transcribe_with_context(samples, progress, context)
+        })
+    }
+
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        self.run(samples, |t| t.transcribe_segments(samples))
+    }
+
+    fn cancel(&self) {
+        self.remote.cancel();
+        if let Some(local) = self.local.lock().unwrap().as_ref() {
+            local.cancel();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_usage_rolls_over() {
+        let mut usage = Usage::default();
+        usage.add(30.0, date("2026-03-30"));
+        usage.add(15.0, date("2026-03-30"));
+        assert_eq!(usage.today_secs(date("2026-03-30")), 45.0);
+
+        // A new day starts at zero, the month keeps counting
+        usage.add(10.0, date("2026-03-31"));
+        assert_eq!(usage.today_secs(date("2026-03-31")), 10.0);
+        assert_eq!(usage.month_secs(date("2026-03-31")), 55.0);
+
+        // A new month starts at zero too
+        assert_eq!(usage.month_secs(date("2026-04-01")), 0.0);
+        usage.add(5.0, date("2026-04-01"));
+        assert_eq!(usage.month_secs(date("2026-04-01")), 5.0);
+        assert_eq!(usage.month_secs(date("2027-04-01")), 0.0);
+    }
+
+    #[test]
+    fn test_budget() {
+        let today = date("2026-03-30");
+        let mut usage = Usage::default();
+        usage.add(3000.0, date("2026-03-29"));
+        usage.add(540.0, today);
+
+        let budget = Budget {
+            daily_secs: Some(600.0),
+            monthly_secs: Some(3600.0),
+        };
+        assert_eq!(budget.exceeded_by(&usage, 60.0, today), None);
+        assert_eq!(budget.exceeded_by(&usage, 61.0, today), Some("daily"));
+        // Tomorrow the daily limit starts over, the monthly one doesn't
+        let tomorrow = date("2026-03-31");
+        assert_eq!(budget.exceeded_by(&usage, 60.0, tomorrow), None);
+        assert_eq!(budget.exceeded_by(&usage, 61.0, tomorrow), Some("monthly"));
+
+        assert_eq!(Budget::default().exceeded_by(&usage, 1e9, today), None);
+    }
+}
@@ -0,0 +1,281 @@
+//! GPU memory budget for whisper models
+//!
+//! With `whisper.vram_budget_mb` set, the model's VRAM need is estimated from
+//! its file size before every load. If it exceeds the budget or the VRAM
+//! currently free, the largest downloaded model that fits is loaded instead
+//! (a smaller or quantized one, never English-only in place of
+//! multilingual), and when none fits the model runs on the CPU. Free VRAM
+//! comes from nvidia-smi (NVIDIA) or sysfs (AMD); on other GPUs only the
+//! budget applies.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MB: u64 = 1024 * 1024;
+
+/// Model file and device to load it on
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub model_path: PathBuf,
+    pub use_gpu: bool,
+}
+
+/// Estimated VRAM need of a model: weights plus whisper.cpp's compute
+/// buffers, which grow with model size
+pub fn estimate_mb(file_mb: u64) -> u64 {
+    file_mb * 13 / 10 + 200
+}
+
+/// Free VRAM on the first GPU, if the driver reports it
+pub fn free_vram_mb() -> Option<u64> {
+    nvidia_free_mb().or_else(amd_free_mb)
+}
+
+fn nvidia_free_mb() -> Option<u64> {
+    let output = Command::new("nvidia-smi")
+        .args(["--query-gpu=memory.free", "--format=csv,noheader,nounits"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn amd_free_mb() -> Option<u64> {
+    let mut cards: Vec<PathBuf> = fs::read_dir("/sys/class/drm")
+        .ok()?
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("card") && !name.contains('-')
+        })
+        .map(|entry| entry.path().join("device"))
+        .collect();
+    cards.sort();
+    cards.iter().find_map(|device| {
+        let read = |name: &str| -> Option<u64> {
+            fs::read_to_string(device.join(name))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+        let total = read("mem_info_vram_total")?;
+        let used = read("mem_info_vram_used")?;
+        Some(total.saturating_sub(used) / MB)
+    })
+}
+
+/// Choose where to load `model_path` within `budget_mb`
+///
+/// `gpu` is whether this build runs whisper on the GPU at all; without it,
+/// or without a budget, the model is loaded as configured.
+pub fn place_model(model_path: &Path, budget_mb: Option<u64>, gpu: bool) -> Placement {
+    let requested = Placement {
+        model_path: model_path.to_path_buf(),
+        use_gpu: gpu,
+    };
+    let Some(budget_mb) = budget_mb.filter(|_| gpu) else {
+        return requested;
+    };
+    let Some(file_mb) = file_mb(model_path) else {
+        return requested;
+    };
+
+    let free_mb = free_vram_mb();
+    let available_mb = free_mb.map_or(budget_mb, |free| free.min(budget_mb));
+    let candidates = model_path
+        .parent()
+        .map(downloaded_models)
+        .unwrap_or_default();
+    let placement = choose(model_path, file_mb, &candidates, available_mb);
+
+    if placement != requested {
+        let limit = match free_mb {
+            Some(free) => format!("budget {} MB, {} MB free", budget_mb, free),
+            None => format!("budget {} MB", budget_mb),
+        };
+        if placement.use_gpu {
+            tracing::warn!(
+                "VRAM: {} needs ~{} MB ({}), loading {} (~{} MB) instead",
+                model_name(model_path),
+                estimate_mb(file_mb),
+                limit,
+                model_name(&placement.model_path),
+                file_mb_of(&candidates, &placement.model_path).map_or(0, estimate_mb)
+            );
+        } else {
+            tracing::warn!(
+                "VRAM: {} needs ~{} MB ({}) and no smaller model fits, running on CPU",
+                model_name(model_path),
+                estimate_mb(file_mb),
+                limit
+            );
+        }
+    } else {
+        tracing::debug!(
+            "VRAM: {} needs ~{} MB of {} MB available",
+            model_name(model_path),
+            estimate_mb(file_mb),
+            available_mb
+        );
+    }
+    placement
+}
+
+/// The requested model if it fits, else the largest smaller candidate that
+/// does, else the requested model on the CPU
+fn choose(
+    requested: &Path,
+    requested_mb: u64,
+    candidates: &[(PathBuf, u64)],
+    available_mb: u64,
+) -> Placement {
+    if estimate_mb(requested_mb) <= available_mb {
+        return Placement {
+            model_path: requested.to_path_buf(),
+            use_gpu: true,
+        };
+    }
+    let english_only = is_english_only(requested);
+    candidates
+        .iter()
+        .filter(|(path, mb)| {
+            path != requested
+                && *mb < requested_mb
+                && estimate_mb(*mb) <= available_mb
+                && (english_only || !is_english_only(path))
+        })
+        .max_by_key(|(_, mb)| *mb)
+        .map(|(path, _)| Placement {
+            model_path: path.clone(),
+            use_gpu: true,
+        })
+        .unwrap_or_else(|| Placement {
+            model_path: requested.to_path_buf(),
+            use_gpu: false,
+        })
+}
+
+/// Whisper models (`ggml-*.bin`, except the VAD model) and their sizes in MB
+fn downloaded_models(dir: &Path) -> Vec<(PathBuf, u64)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            name.starts_with("ggml-") && name.ends_with(".bin") && !name.contains("silero")
+        })
+        .filter_map(|path| file_mb(&path).map(|mb| (path, mb)))
+        .collect()
+}
+
+fn file_mb(path: &Path) -> Option<u64> {
+    fs::metadata(path).ok().map(|m| m.len() / MB)
+}
+
+fn file_mb_of(candidates: &[(PathBuf, u64)], path: &Path) -> Option<u64> {
+    candidates
+        .iter()
+        .find(|(candidate, _)| candidate == path)
+        .map(|(_, mb)| *mb)
+}
+
+/// Model name from its file name ("ggml-small.en.bin" -> "small.en")
+fn model_name(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    name.strip_prefix("ggml-")
+        .unwrap_or(&name)
+        .trim_end_matches(".bin")
+        .to_string()
+}
+
+fn is_english_only(path: &Path) -> bool {
+    super::models::is_english_only(&path.to_string_lossy())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn models() -> Vec<(PathBuf, u64)> {
+        [
+            ("ggml-large-v3.bin", 2950),
+            ("ggml-large-v3-turbo-q5_0.bin", 547),
+            ("ggml-small.bin", 465),
+            ("ggml-base.en.bin", 141),
+        ]
+        .into_iter()
+        .map(|(name, mb)| (PathBuf::from("/models").join(name), mb))
+        .collect()
+    }
+
+    #[test]
+    fn test_model_name() {
+        assert_eq!(model_name(Path::new("/m/ggml-small.en.bin")), "small.en");
+        assert!(is_english_only(Path::new("/m/ggml-small.en.bin")));
+        assert!(is_english_only(Path::new("/m/ggml-small.en-q5_1.bin")));
+        assert!(!is_english_only(Path::new("/m/ggml-large-v3-turbo.bin")));
+        assert!(is_english_only(Path::new("/m/ggml-distil-large-v3.bin")));
+    }
+
+    #[test]
+    fn test_choose() {
+        let large = Path::new("/models/ggml-large-v3.bin");
+        let gpu = |name: &str| Placement {
+            model_path: PathBuf::from("/models").join(name),
+            use_gpu: true,
+        };
+
+        // Fits: unchanged
+        assert_eq!(
+            choose(large, 2950, &models(), 8000),
+            gpu("ggml-large-v3.bin")
+        );
+
+        // Largest model that fits, quantized ones included
+        assert_eq!(
+            choose(large, 2950, &models(), 1000),
+            gpu("ggml-large-v3-turbo-q5_0.bin")
+        );
+
+        // Never an English-only model in place of a multilingual one
+        assert_eq!(
+            choose(large, 2950, &models(), 500),
+            Placement {
+                model_path: large.to_path_buf(),
+                use_gpu: false,
+            }
+        );
+
+        // English-only models may fall back to multilingual ones
+        let base_en = Path::new("/models/ggml-base.en.bin");
+        assert!(!choose(base_en, 141, &models(), 100).use_gpu);
+    }
+
+    #[test]
+    fn test_place_model_without_budget() {
+        let path = Path::new("/models/ggml-large-v3.bin");
+        let placement = place_model(path, None, true);
+        assert_eq!(placement.model_path, path);
+        assert!(placement.use_gpu);
+        assert!(!place_model(path, Some(100), false).use_gpu);
+    }
+}
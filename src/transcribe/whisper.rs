@@ -6,17 +6,41 @@
 //! - Single language: Use a specific language for transcription
 //! - Auto-detect: Let Whisper detect from all ~99 supported languages
 //! - Constrained auto-detect: Detect from a user-specified subset of languages
-
-use super::Transcriber;
-use crate::config::{Config, LanguageConfig, WhisperConfig};
+//!
+//! With `unload_after_secs`, the model is freed after that long without use
+//! and reloaded when the next recording starts (or on the next transcription).
+//! `gpu_backend` can keep a GPU build on the CPU (see `gpu`), and with
+//! `vram_budget_mb` each load may pick a smaller model or the CPU (see `vram`).
+//! The gpu_isolation worker loads the model from a memory mapping (see `mmap`).
+
+use super::mmap::MappedFile;
+use super::{gpu, prompt_with_context, vram, Progress, Segment, Transcriber, Word};
+use crate::config::{GpuBackend, LanguageConfig, WhisperConfig};
 use crate::error::TranscribeError;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
 /// Whisper-based transcriber
 pub struct WhisperTranscriber {
-    /// Whisper context (holds the model)
-    ctx: WhisperContext,
+    /// Whisper context (holds the model), None while unloaded
+    ctx: Mutex<Option<Arc<WhisperContext>>>,
+    /// Model file, to reload the context after an idle unload
+    model_path: PathBuf,
+    /// Preferred GPU backend
+    gpu_backend: GpuBackend,
+    /// GPU memory the model may use, checked on every load
+    vram_budget_mb: Option<u64>,
+    /// Load the model from a memory mapping of the file
+    mmap: bool,
+    /// Free the context after this long unused (None = keep loaded)
+    unload_after: Option<Duration>,
+    /// When the context was last used
+    last_used: Mutex<Instant>,
+    /// Reload time on the critical path of the last transcription
+    last_load_time: Mutex<Option<Duration>>,
     /// Language configuration (single, auto, or array)
     language: LanguageConfig,
     /// Whether to translate to English
@@ -27,38 +51,83 @@ pub struct WhisperTranscriber {
     context_window_optimization: bool,
     /// Initial prompt to provide context for transcription
     initial_prompt: Option<String>,
+    /// Minimum confidence to accept a transcription (0.0 = disabled)
+    min_confidence: f32,
+    /// Drop segments more likely than this to contain no speech
+    no_speech_threshold: Option<f32>,
+    /// Suppress non-speech tokens ("(laughs)", "[music]")
+    suppress_non_speech_tokens: bool,
+    /// Bumped by `cancel()` to abort the runs started before it
+    cancel_epoch: Arc<AtomicU64>,
 }
 
 impl WhisperTranscriber {
     /// Create a new whisper transcriber
     pub fn new(config: &WhisperConfig) -> Result<Self, TranscribeError> {
-        let model_path = resolve_model_path(&config.model)?;
-
-        tracing::info!("Loading whisper model from {:?}", model_path);
-        let start = std::time::Instant::now();
+        Self::create(config, false)
+    }
 
-        let ctx = WhisperContext::new_with_params(
-            model_path
-                .to_str()
-                .ok_or_else(|| TranscribeError::ModelNotFound("Invalid path".to_string()))?,
-            WhisperContextParameters::default(),
-        )
-        .map_err(|e| TranscribeError::InitFailed(e.to_string()))?;
+    /// Create a new whisper transcriber, loading the model from a memory
+    /// mapping of the model file
+    ///
+    /// Faster for a process that loads the model once and exits, like the
+    /// gpu_isolation worker: the file comes straight from the page cache.
+    pub fn new_mapped(config: &WhisperConfig) -> Result<Self, TranscribeError> {
+        Self::create(config, true)
+    }
 
-        tracing::info!("Model loaded in {:.2}s", start.elapsed().as_secs_f32());
+    fn create(config: &WhisperConfig, mmap: bool) -> Result<Self, TranscribeError> {
+        let model_path = super::models::resolve(&config.model, config.auto_download)?;
+        crate::model_cache::mark_used(&model_path);
+        let ctx = load_context(&model_path, config.gpu_backend, config.vram_budget_mb, mmap)?;
 
         let threads = config.threads.unwrap_or_else(|| num_cpus::get().min(4));
+        let language = super::models::model_language(&model_path, &config.language);
 
         Ok(Self {
-            ctx,
-            language: config.language.clone(),
+            ctx: Mutex::new(Some(Arc::new(ctx))),
+            model_path,
+            gpu_backend: config.gpu_backend,
+            vram_budget_mb: config.vram_budget_mb,
+            mmap,
+            unload_after: (config.unload_after_secs > 0)
+                .then(|| Duration::from_secs(config.unload_after_secs)),
+            last_used: Mutex::new(Instant::now()),
+            last_load_time: Mutex::new(None),
+            language,
             translate: config.translate,
             threads,
             context_window_optimization: config.context_window_optimization,
             initial_prompt: config.initial_prompt.clone(),
+            min_confidence: config.min_confidence,
+            no_speech_threshold: config.no_speech_threshold,
+            suppress_non_speech_tokens: config.suppress_non_speech_tokens,
+            cancel_epoch: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    /// The loaded context, reloading it if it was unloaded while idle
+    ///
+    /// Also returns how long the reload took, if one was needed.
+    fn context(&self) -> Result<(Arc<WhisperContext>, Option<Duration>), TranscribeError> {
+        let mut ctx = self.ctx.lock().unwrap();
+        *self.last_used.lock().unwrap() = Instant::now();
+        if let Some(ref ctx) = *ctx {
+            return Ok((Arc::clone(ctx), None));
+        }
+
+        tracing::info!("Reloading whisper model (unloaded while idle)");
+        let start = Instant::now();
+        let loaded = Arc::new(load_context(
+            &self.model_path,
+            self.gpu_backend,
+            self.vram_budget_mb,
+            self.mmap,
+        )?);
+        *ctx = Some(Arc::clone(&loaded));
+        Ok((loaded, Some(start.elapsed())))
+    }
+
     /// Select the best language from allowed languages using Whisper's language detection.
     ///
     /// This runs the mel spectrogram computation and language detection head to get
@@ -118,10 +187,20 @@ impl WhisperTranscriber {
 
         Ok(selected)
     }
-}
 
-impl Transcriber for WhisperTranscriber {
-    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+    /// Run inference and collect raw (untrimmed) segments and their confidence
+    ///
+    /// `timestamps` disables single-segment mode so Whisper splits the
+    /// output into timed segments even for short clips. Whisper reports
+    /// `progress` as it moves through the audio in 30s windows. `context` is
+    /// added to the initial prompt.
+    fn run(
+        &self,
+        samples: &[f32],
+        timestamps: bool,
+        progress: Option<Progress>,
+        context: Option<&str>,
+    ) -> Result<(Vec<Segment>, f32), TranscribeError> {
         if samples.is_empty() {
             return Err(TranscribeError::AudioFormat(
                 "Empty audio buffer".to_string(),
@@ -137,9 +216,11 @@ impl Transcriber for WhisperTranscriber {
 
         let start = std::time::Instant::now();
 
+        let (ctx, load_time) = self.context()?;
+        *self.last_load_time.lock().unwrap() = load_time;
+
         // Create state for this transcription
-        let mut state = self
-            .ctx
+        let mut state = ctx
             .create_state()
             .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?;
 
@@ -178,20 +259,43 @@ impl Transcriber for WhisperTranscriber {
         params.set_print_realtime(false);
         params.set_print_timestamps(false);
 
+        // whisper-rs never frees the callbacks, so they only hold weak
+        // references (the caller's progress channel closes when we return)
+        let started_epoch = self.cancel_epoch.load(Ordering::SeqCst);
+        let epoch = Arc::downgrade(&self.cancel_epoch);
+        params.set_abort_callback_safe(move || {
+            epoch
+                .upgrade()
+                .is_some_and(|epoch| epoch.load(Ordering::SeqCst) != started_epoch)
+        });
+        if let Some(ref progress) = progress {
+            let progress = Arc::downgrade(progress);
+            params.set_progress_callback_safe(move |percent: i32| {
+                if let Some(progress) = progress.upgrade() {
+                    progress(percent.clamp(0, 100) as u8);
+                }
+            });
+        }
+
         // Improve transcription quality
         params.set_suppress_blank(true);
-        params.set_suppress_nst(true);
+        params.set_suppress_nst(self.suppress_non_speech_tokens);
+        if let Some(threshold) = self.no_speech_threshold {
+            params.set_no_speech_thold(threshold);
+        }
 
-        // Set initial prompt if configured
-        if let Some(prompt) = &self.initial_prompt {
-            params.set_initial_prompt(prompt);
+        // Set initial prompt if configured, with this recording's context
+        if let Some(prompt) = prompt_with_context(self.initial_prompt.as_deref(), context) {
+            params.set_initial_prompt(&prompt);
             tracing::debug!("Using initial prompt: {:?}", prompt);
         }
 
         // For short recordings, use single segment mode
-        if duration_secs < 30.0 {
+        if duration_secs < 30.0 && !timestamps {
             params.set_single_segment(true);
         }
+        // Token-level timing, grouped into words below
+        params.set_token_timestamps(timestamps);
 
         // Optimize context window for short clips
         if self.context_window_optimization {
@@ -210,21 +314,70 @@ impl Transcriber for WhisperTranscriber {
         }
 
         // Run inference
-        state
-            .full(params, samples)
-            .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?;
+        let inference = state.full(params, samples);
+        if self.cancel_epoch.load(Ordering::SeqCst) != started_epoch {
+            tracing::info!(
+                "Transcription cancelled after {:.2}s",
+                start.elapsed().as_secs_f32()
+            );
+            return Err(TranscribeError::Cancelled);
+        }
+        inference.map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?;
 
-        // Collect all segments using iterator API
-        let mut text = String::new();
+        // Collect all segments using iterator API (timestamps are in centiseconds)
+        let mut segments = Vec::new();
+        let token_eot = ctx.token_eot();
+        let mut token_probs = Vec::new();
+        let mut no_speech_prob = 0.0f32;
         for segment in state.as_iter() {
-            text.push_str(
-                segment
-                    .to_str()
-                    .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?,
-            );
+            let text = segment
+                .to_str()
+                .map_err(|e| TranscribeError::InferenceFailed(e.to_string()))?;
+            let segment_no_speech = segment.no_speech_probability();
+            if self
+                .no_speech_threshold
+                .is_some_and(|threshold| segment_no_speech > threshold)
+            {
+                tracing::debug!(
+                    "Dropping segment (no-speech {:.2}): {:?}",
+                    segment_no_speech,
+                    text
+                );
+                continue;
+            }
+            // Special tokens (timestamps, EOT, ...) all sort after EOT
+            let tokens: Vec<_> = (0..segment.n_tokens())
+                .filter_map(|i| segment.get_token(i))
+                .filter(|token| token.token_id() < token_eot)
+                .collect();
+            token_probs.extend(tokens.iter().map(|token| token.token_probability()));
+            let words = if timestamps {
+                group_words(tokens.iter().filter_map(|token| {
+                    let data = token.token_data();
+                    let text = token.to_bytes().ok()?;
+                    Some((text, data.t0.max(0) as u64 * 10, data.t1.max(0) as u64 * 10))
+                }))
+            } else {
+                Vec::new()
+            };
+            no_speech_prob = no_speech_prob.max(segment_no_speech);
+            segments.push(Segment {
+                start_ms: segment.start_timestamp().max(0) as u64 * 10,
+                end_ms: segment.end_timestamp().max(0) as u64 * 10,
+                text: text.to_string(),
+                speaker: None,
+                words,
+            });
         }
 
-        let result = text.trim().to_string();
+        let result = join_segments(&segments);
+        let confidence = transcription_confidence(&token_probs, no_speech_prob);
+        tracing::debug!(
+            "Transcription confidence: {:.2} ({} tokens, no-speech {:.2})",
+            confidence,
+            token_probs.len(),
+            no_speech_prob
+        );
 
         tracing::info!(
             "Transcription completed in {:.2}s: {:?}",
@@ -236,70 +389,197 @@ impl Transcriber for WhisperTranscriber {
             }
         );
 
-        Ok(result)
+        *self.last_used.lock().unwrap() = Instant::now();
+        Ok((segments, confidence))
+    }
+
+    /// Transcribe to text, discarding low-confidence results
+    fn transcribe_text(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: Option<&str>,
+    ) -> Result<String, TranscribeError> {
+        let (segments, confidence) = self.run(samples, false, progress, context)?;
+        let text = join_segments(&segments);
+        if !text.is_empty() && confidence < self.min_confidence {
+            tracing::info!(
+                "Discarding low-confidence transcription ({:.2} < {:.2}): {:?}",
+                confidence,
+                self.min_confidence,
+                text
+            );
+            return Err(TranscribeError::LowConfidence(confidence));
+        }
+        Ok(text)
     }
 }
 
-/// Resolve model name to file path
-fn resolve_model_path(model: &str) -> Result<PathBuf, TranscribeError> {
-    // If it's already an absolute path, use it directly
-    let path = PathBuf::from(model);
-    if path.is_absolute() && path.exists() {
-        return Ok(path);
+impl Transcriber for WhisperTranscriber {
+    fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+        self.transcribe_text(samples, None, None)
     }
 
-    // Map model names to file names
-    let model_filename = match model {
-        "tiny" => "ggml-tiny.bin",
-        "tiny.en" => "ggml-tiny.en.bin",
-        "base" => "ggml-base.bin",
-        "base.en" => "ggml-base.en.bin",
-        "small" => "ggml-small.bin",
-        "small.en" => "ggml-small.en.bin",
-        "medium" => "ggml-medium.bin",
-        "medium.en" => "ggml-medium.en.bin",
-        "large" | "large-v1" => "ggml-large-v1.bin",
-        "large-v2" => "ggml-large-v2.bin",
-        "large-v3" => "ggml-large-v3.bin",
-        "large-v3-turbo" => "ggml-large-v3-turbo.bin",
-        // If it looks like a filename, use it as-is
-        other if other.ends_with(".bin") => other,
-        // Otherwise, assume it's a model name and add prefix/suffix
-        other => {
-            return Err(TranscribeError::ModelNotFound(format!(
-                "Unknown model: '{}'. Valid models: tiny, base, small, medium, large-v3, large-v3-turbo",
-                other
-            )));
+    fn transcribe_with_progress(
+        &self,
+        samples: &[f32],
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        self.transcribe_text(samples, Some(progress), None)
+    }
+
+    fn transcribe_with_context(
+        &self,
+        samples: &[f32],
+        progress: Option<Progress>,
+        context: &str,
+    ) -> Result<String, TranscribeError> {
+        self.transcribe_text(samples, progress, Some(context))
+    }
+
+    fn transcribe_segments(&self, samples: &[f32]) -> Result<Vec<Segment>, TranscribeError> {
+        Ok(self
+            .run(samples, true, None, None)?
+            .0
+            .into_iter()
+            .filter_map(|mut seg| {
+                seg.text = seg.text.trim().to_string();
+                (!seg.text.is_empty()).then_some(seg)
+            })
+            .collect())
+    }
+
+    fn cancel(&self) {
+        self.cancel_epoch.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn prepare(&self) {
+        // Reload while the user is still speaking
+        if self.unload_after.is_some() {
+            if let Err(e) = self.context() {
+                tracing::warn!("Failed to reload whisper model: {}", e);
+            }
         }
-    };
+    }
+
+    fn last_model_load_time(&self) -> Option<Duration> {
+        *self.last_load_time.lock().unwrap()
+    }
 
-    // Look in the data directory
-    let models_dir = Config::models_dir();
-    let model_path = models_dir.join(model_filename);
+    fn unload_if_idle(&self) {
+        let Some(unload_after) = self.unload_after else {
+            return;
+        };
+        let mut ctx = self.ctx.lock().unwrap();
+        if ctx.is_some() && self.last_used.lock().unwrap().elapsed() >= unload_after {
+            tracing::info!(
+                "Unloading whisper model after {}s idle",
+                unload_after.as_secs()
+            );
+            *ctx = None;
+        }
+    }
+}
 
-    if model_path.exists() {
-        return Ok(model_path);
+/// Group token text and timing (bytes, start ms, end ms) into words
+///
+/// A token starting with a space starts a new word; the others (word
+/// pieces, punctuation) continue the previous one. Bytes are joined before
+/// decoding, since a token can end in the middle of a UTF-8 character.
+fn group_words<'a>(tokens: impl IntoIterator<Item = (&'a [u8], u64, u64)>) -> Vec<Word> {
+    let mut words: Vec<(Vec<u8>, u64, u64)> = Vec::new();
+    for (text, start_ms, end_ms) in tokens {
+        match words.last_mut() {
+            Some(word) if !text.starts_with(b" ") => {
+                word.0.extend_from_slice(text);
+                word.2 = end_ms;
+            }
+            _ => words.push((text.to_vec(), start_ms, end_ms)),
+        }
     }
+    words
+        .into_iter()
+        .filter_map(|(text, start_ms, end_ms)| {
+            let text = String::from_utf8_lossy(&text).trim().to_string();
+            (!text.is_empty()).then_some(Word {
+                start_ms,
+                end_ms,
+                text,
+            })
+        })
+        .collect()
+}
 
-    // Also check current directory
-    let cwd_path = PathBuf::from(model_filename);
-    if cwd_path.exists() {
-        return Ok(cwd_path);
+/// Load a whisper model file, on the GPU unless `gpu_backend` says
+/// otherwise, or a smaller one / on the CPU when it doesn't fit in
+/// `vram_budget_mb`
+///
+/// With `mmap`, the model is read from a memory mapping of the file, or
+/// from the file as usual when it can't be mapped.
+fn load_context(
+    model_path: &Path,
+    gpu_backend: GpuBackend,
+    vram_budget_mb: Option<u64>,
+    mmap: bool,
+) -> Result<WhisperContext, TranscribeError> {
+    let mut params = WhisperContextParameters::default();
+    let use_gpu = gpu::use_gpu(gpu_backend, params.use_gpu);
+    let placement = vram::place_model(model_path, vram_budget_mb, use_gpu);
+    params.use_gpu(placement.use_gpu);
+
+    tracing::info!("Loading whisper model from {:?}", placement.model_path);
+    let start = Instant::now();
+
+    let mapped = if mmap {
+        MappedFile::open(&placement.model_path)
+            .map_err(|e| {
+                tracing::warn!(
+                    "Failed to map {:?}, reading it instead: {}",
+                    placement.model_path,
+                    e
+                )
+            })
+            .ok()
+    } else {
+        None
+    };
+    let ctx = match mapped {
+        Some(mapped) => WhisperContext::new_from_buffer_with_params(mapped.as_slice(), params),
+        None => WhisperContext::new_with_params(
+            placement
+                .model_path
+                .to_str()
+                .ok_or_else(|| TranscribeError::ModelNotFound("Invalid path".to_string()))?,
+            params,
+        ),
     }
+    .map_err(|e| TranscribeError::InitFailed(e.to_string()))?;
+
+    tracing::info!("Model loaded in {:.2}s", start.elapsed().as_secs_f32());
+    Ok(ctx)
+}
 
-    // Also check ./models/
-    let local_models_path = PathBuf::from("models").join(model_filename);
-    if local_models_path.exists() {
-        return Ok(local_models_path);
+/// Overall confidence of a transcription
+///
+/// Average probability of the text tokens, scaled by how likely the audio
+/// is to contain speech at all. Returns 1.0 for empty output so silence is
+/// left to the empty-transcription path.
+fn transcription_confidence(token_probs: &[f32], no_speech_prob: f32) -> f32 {
+    if token_probs.is_empty() {
+        return 1.0;
     }
+    let avg = token_probs.iter().sum::<f32>() / token_probs.len() as f32;
+    avg * (1.0 - no_speech_prob.clamp(0.0, 1.0))
+}
 
-    Err(TranscribeError::ModelNotFound(format!(
-        "Model '{}' not found. Looked in:\n  - {}\n  - {}\n  - {}\n\nDownload from: https://huggingface.co/ggerganov/whisper.cpp/tree/main",
-        model,
-        model_path.display(),
-        cwd_path.display(),
-        local_models_path.display()
-    )))
+/// Join raw segment text the way Whisper emits it (leading spaces included)
+fn join_segments(segments: &[Segment]) -> String {
+    segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<String>()
+        .trim()
+        .to_string()
 }
 
 /// Calculate audio_ctx parameter for short clips (≤22.5s).
@@ -327,43 +607,54 @@ fn calculate_audio_ctx(duration_secs: f32) -> Option<i32> {
     }
 }
 
-/// Get the filename for a model
-pub fn get_model_filename(model: &str) -> String {
-    match model {
-        "tiny" => "ggml-tiny.bin",
-        "tiny.en" => "ggml-tiny.en.bin",
-        "base" => "ggml-base.bin",
-        "base.en" => "ggml-base.en.bin",
-        "small" => "ggml-small.bin",
-        "small.en" => "ggml-small.en.bin",
-        "medium" => "ggml-medium.bin",
-        "medium.en" => "ggml-medium.en.bin",
-        "large-v3" => "ggml-large-v3.bin",
-        "large-v3-turbo" => "ggml-large-v3-turbo.bin",
-        other => other,
-    }
-    .to_string()
-}
-
-/// Get the download URL for a model
-pub fn get_model_url(model: &str) -> String {
-    let filename = get_model_filename(model);
-
-    format!(
-        "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/{}",
-        filename
-    )
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_model_url() {
-        let url = get_model_url("base.en");
-        assert!(url.contains("ggml-base.en.bin"));
-        assert!(url.contains("huggingface.co"));
+    fn test_join_segments_trims_whisper_spacing() {
+        let segments = vec![
+            Segment {
+                start_ms: 0,
+                end_ms: 1200,
+                text: " Hello there.".to_string(),
+                speaker: None,
+                words: Vec::new(),
+            },
+            Segment {
+                start_ms: 1200,
+                end_ms: 2500,
+                text: " How are you?".to_string(),
+                speaker: None,
+                words: Vec::new(),
+            },
+        ];
+        assert_eq!(join_segments(&segments), "Hello there. How are you?");
+    }
+
+    #[test]
+    fn test_group_words() {
+        let tokens: [(&[u8], u64, u64); 6] = [
+            (b" Hello", 0, 300),
+            (b",", 300, 320),
+            (b" wor", 400, 550),
+            (b"ld", 550, 700),
+            (b" caf\xc3", 800, 900),
+            (b"\xa9.", 900, 1000),
+        ];
+        let words = group_words(tokens);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["Hello,", "world", "café."]);
+        assert_eq!((words[1].start_ms, words[1].end_ms), (400, 700));
+        assert!(group_words([(&b" "[..], 0, 10)]).is_empty());
+    }
+
+    #[test]
+    fn test_transcription_confidence() {
+        assert!((transcription_confidence(&[0.9, 0.7], 0.0) - 0.8).abs() < 1e-6);
+        // Likely silence drags confidence down even with confident tokens
+        assert!((transcription_confidence(&[0.9, 0.9], 0.8) - 0.18).abs() < 1e-6);
+        assert_eq!(transcription_confidence(&[], 0.9), 1.0);
     }
 
     #[test]
@@ -5,30 +5,333 @@
 //! transcription, ensuring the GPU is fully released after transcription
 //! completes (the process exits, releasing all GPU resources).
 //!
+//! The daemon and worker talk over a Unix socket pair, the worker's end
+//! passed as file descriptor 3 (`--socket-fd`). Stdout and stderr are left
+//! for logging and end up in the daemon's log.
+//!
 //! Protocol (eager mode - subprocess spawned when recording starts):
 //! 1. Worker starts, loads model
-//! 2. Worker writes "READY\n" to stdout (signals model is loaded)
-//! 3. Parent sends audio via stdin: [u32 sample_count (LE)][f32 samples (LE)...]
-//! 4. Worker transcribes and writes JSON response to stdout
-//! 5. Worker exits
+//! 2. Worker sends `{"type":"ready","version":N}` (signals model is loaded)
+//! 3. Daemon sends a header: magic "VOXW", u16 protocol version, u32 sample
+//!    rate, u32 flags
+//! 4. Daemon sends messages: [u8 kind][u32 payload length][payload], with
+//!    the audio in Audio messages (f32 samples) and an End message after it
+//! 5. Worker transcribes, sending `{"type":"progress","percent":N}` as it
+//!    goes, and then `{"type":"result",...}` with the text or the error
+//! 6. Worker exits
+//!
+//! The worker's messages are JSON, one per line. All integers and samples
+//! the daemon sends are little-endian. The key benefit: model loading happens
+//! while the user is speaking, so perceived latency is just the transcription
+//! time. The daemon streams Audio messages as the audio is captured, before
+//! the worker is ready if the model is still loading, so only the last
+//! moments of audio are sent after recording stops. If the stream ends before
+//! the End message, the recording was abandoned and nothing is transcribed.
 //!
-//! The key benefit: model loading happens while the user is speaking,
-//! so perceived latency is just the transcription time.
+//! Control messages carry JSON, like `{"type":"cancel"}`, which stops the
+//! transcription in progress, or `{"type":"prompt","text":...}` before the
+//! End message, adding to the initial prompt for this recording. The worker
+//! also stops when the daemon closes the socket.
+//!
+//! A daemon and worker from different versions of voxtype refuse each other
+//! with an error instead of misreading the stream. Message kinds the worker
+//! doesn't know are skipped, so new ones can be added in the same version.
 
 use crate::config::WhisperConfig;
-use crate::transcribe::Transcriber;
-use std::io::{self, Read, Write};
+use crate::error::TranscribeError;
+use crate::transcribe::{Progress, Transcriber};
+use std::io::{self, BufReader, Read, Write};
+use std::os::unix::io::FromRawFd;
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
 use ureq::serde_json;
 
-/// Ready signal sent after model is loaded
-pub const READY_SIGNAL: &str = "READY";
+/// File descriptor the worker's end of the socket is passed on
+pub const SOCKET_FD: i32 = 3;
+
+/// Magic bytes that start the stream from the daemon
+const MAGIC: [u8; 4] = *b"VOXW";
+
+/// Version of the protocol, bumped on incompatible changes
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Sample rate of the audio (what Whisper expects)
+const SAMPLE_RATE: u32 = 16000;
+
+/// Message carrying f32 samples
+const MSG_AUDIO: u8 = 1;
+/// Message ending the audio (empty payload)
+const MSG_END: u8 = 2;
+/// Message carrying a JSON [`Control`]
+const MSG_CONTROL: u8 = 3;
+
+/// Most audio accepted from the daemon, to prevent OOM from malformed input
+/// Max 10 minutes at 16kHz = 9,600,000 samples = ~38MB
+const MAX_SAMPLES: usize = 16000 * 60 * 10;
+
+/// Stream header, written once before any message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Header {
+    pub version: u16,
+    pub sample_rate: u32,
+    /// Reserved for options; none are defined yet
+    pub flags: u32,
+}
+
+impl Header {
+    /// Header for the protocol this binary speaks
+    pub fn current() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            sample_rate: SAMPLE_RATE,
+            flags: 0,
+        }
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&self.flags.to_le_bytes())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> Result<Self, String> {
+        let mut buf = [0u8; 14];
+        reader
+            .read_exact(&mut buf)
+            .map_err(|e| format!("Failed to read stream header: {}", e))?;
+        if buf[..4] != MAGIC {
+            return Err(
+                "Unrecognized audio stream from the daemon. Restart voxtype after upgrading."
+                    .to_string(),
+            );
+        }
+        Ok(Self {
+            version: u16::from_le_bytes([buf[4], buf[5]]),
+            sample_rate: u32::from_le_bytes([buf[6], buf[7], buf[8], buf[9]]),
+            flags: u32::from_le_bytes([buf[10], buf[11], buf[12], buf[13]]),
+        })
+    }
+}
+
+/// Request from the daemon while the worker runs
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Control {
+    /// Stop transcribing and exit
+    Cancel,
+    /// First message to a `voxtype serve` worker, before the audio
+    Auth { token: Option<String> },
+    /// Context for the initial prompt of this recording, before End
+    Prompt { text: String },
+}
+
+/// A recording received from the daemon
+#[derive(Debug, Default, PartialEq)]
+pub struct Recording {
+    pub samples: Vec<f32>,
+    /// Initial prompt context ([`Control::Prompt`])
+    pub prompt: Option<String>,
+}
+
+impl Recording {
+    /// Transcribe it, with its prompt context if any
+    pub fn transcribe(
+        &self,
+        transcriber: &dyn Transcriber,
+        progress: Progress,
+    ) -> Result<String, TranscribeError> {
+        match &self.prompt {
+            Some(prompt) => {
+                transcriber.transcribe_with_context(&self.samples, Some(progress), prompt)
+            }
+            None => transcriber.transcribe_with_progress(&self.samples, progress),
+        }
+    }
+}
+
+/// Message from the worker to the daemon
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WorkerMessage {
+    /// Model loaded, waiting for audio
+    Ready { version: u16 },
+    /// Percent of the audio transcribed so far
+    Progress { percent: u8 },
+    /// The transcription, or why there is none
+    Result(WorkerResponse),
+}
+
+/// Write a message
+fn write_message<W: Write>(writer: &mut W, kind: u8, payload: &[u8]) -> io::Result<()> {
+    writer.write_all(&[kind])?;
+    writer.write_all(&(payload.len() as u32).to_le_bytes())?;
+    writer.write_all(payload)?;
+    writer.flush()
+}
+
+/// Write an Audio message
+pub fn write_audio<W: Write>(writer: &mut W, samples: &[f32]) -> io::Result<()> {
+    let samples_bytes = unsafe {
+        std::slice::from_raw_parts(
+            samples.as_ptr() as *const u8,
+            std::mem::size_of_val(samples),
+        )
+    };
+    write_message(writer, MSG_AUDIO, samples_bytes)
+}
+
+/// Write the End message
+pub fn write_end<W: Write>(writer: &mut W) -> io::Result<()> {
+    write_message(writer, MSG_END, &[])
+}
+
+/// Write a Control message
+pub fn write_control<W: Write>(writer: &mut W, control: &Control) -> io::Result<()> {
+    let json = serde_json::to_vec(control).map_err(io::Error::other)?;
+    write_message(writer, MSG_CONTROL, &json)
+}
+
+/// Read a message, returning its kind and payload
+fn read_message<R: Read>(reader: &mut R) -> Result<(u8, Vec<u8>), String> {
+    let mut prefix = [0u8; 5];
+    reader
+        .read_exact(&mut prefix)
+        .map_err(|e| format!("Failed to read message: {}", e))?;
+    let kind = prefix[0];
+    let len = u32::from_le_bytes([prefix[1], prefix[2], prefix[3], prefix[4]]) as usize;
+    if len > MAX_SAMPLES * 4 {
+        return Err(format!("Message too large: {} bytes", len));
+    }
+
+    let mut payload = vec![0u8; len];
+    reader
+        .read_exact(&mut payload)
+        .map_err(|e| format!("Failed to read message: {}", e))?;
+    Ok((kind, payload))
+}
+
+/// Parse a Control message, None for ones this worker doesn't know
+fn parse_control(payload: &[u8]) -> Option<Control> {
+    match serde_json::from_slice(payload) {
+        Ok(control) => Some(control),
+        Err(e) => {
+            eprintln!("[worker] Ignoring unknown control message: {}", e);
+            None
+        }
+    }
+}
+
+/// Read the header and messages up to End, returning the recording
+fn read_audio<R: Read>(reader: &mut R) -> Result<Recording, String> {
+    read_header(reader)?;
+    read_samples(reader)
+}
+
+/// Read the header, checking the daemon speaks our protocol
+pub fn read_header<R: Read>(reader: &mut R) -> Result<Header, String> {
+    let header = Header::read(reader)?;
+    if header.version != PROTOCOL_VERSION {
+        return Err(format!(
+            "Protocol mismatch: the daemon speaks version {}, this worker version {}. \
+             Restart voxtype after upgrading.",
+            header.version, PROTOCOL_VERSION
+        ));
+    }
+    if header.sample_rate != SAMPLE_RATE {
+        return Err(format!(
+            "Unsupported sample rate: {} Hz (expected {} Hz)",
+            header.sample_rate, SAMPLE_RATE
+        ));
+    }
+    Ok(header)
+}
+
+/// Read the next message, which must be a Control message
+pub fn read_control<R: Read>(reader: &mut R) -> Result<Control, String> {
+    match read_message(reader)? {
+        (MSG_CONTROL, payload) => serde_json::from_slice(&payload)
+            .map_err(|e| format!("Malformed control message: {}", e)),
+        (kind, _) => Err(format!("Expected a control message, got kind {}", kind)),
+    }
+}
+
+/// Read messages up to End, returning the recording
+pub fn read_samples<R: Read>(reader: &mut R) -> Result<Recording, String> {
+    let mut samples: Vec<f32> = Vec::new();
+    let mut prompt = None;
+    loop {
+        let (kind, payload) = read_message(reader)?;
+        match kind {
+            MSG_END => return Ok(Recording { samples, prompt }),
+            MSG_AUDIO => {
+                if !payload.len().is_multiple_of(4) {
+                    return Err(format!("Malformed audio message ({} bytes)", payload.len()));
+                }
+                let total = samples.len() + payload.len() / 4;
+                if total > MAX_SAMPLES {
+                    return Err(format!(
+                        "Sample count too large: {} (max {})",
+                        total, MAX_SAMPLES
+                    ));
+                }
+                samples.extend(
+                    payload
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                );
+            }
+            MSG_CONTROL => match parse_control(&payload) {
+                Some(Control::Cancel) => return Err(TranscribeError::Cancelled.to_string()),
+                Some(Control::Prompt { text }) => prompt = Some(text),
+                _ => {}
+            },
+            other => {
+                // Added by a newer daemon of the same protocol version
+                eprintln!("[worker] Ignoring unknown message kind {}", other);
+            }
+        }
+    }
+}
+
+/// Wait for a cancel request, or the daemon going away
+///
+/// Runs while transcribing, after the End message.
+fn wait_for_cancel<R: Read>(reader: &mut R) {
+    loop {
+        match read_message(reader) {
+            Ok((MSG_CONTROL, payload)) => {
+                if parse_control(&payload) == Some(Control::Cancel) {
+                    eprintln!("[worker] Cancel requested");
+                    return;
+                }
+            }
+            Ok(_) => {}
+            Err(_) => {
+                eprintln!("[worker] Daemon closed the connection");
+                return;
+            }
+        }
+    }
+}
 
 /// JSON response from the worker
 #[derive(Debug, serde::Serialize)]
 #[serde(untagged)]
 pub enum WorkerResponse {
-    Success { ok: bool, text: String },
-    Error { ok: bool, error: String },
+    Success {
+        ok: bool,
+        text: String,
+    },
+    LowConfidence {
+        ok: bool,
+        error: String,
+        confidence: f32,
+    },
+    Error {
+        ok: bool,
+        error: String,
+    },
 }
 
 impl WorkerResponse {
@@ -36,32 +339,57 @@ impl WorkerResponse {
         WorkerResponse::Success { ok: true, text }
     }
 
+    pub fn low_confidence(confidence: f32) -> Self {
+        WorkerResponse::LowConfidence {
+            ok: false,
+            error: TranscribeError::LowConfidence(confidence).to_string(),
+            confidence,
+        }
+    }
+
     pub fn error(msg: impl Into<String>) -> Self {
         WorkerResponse::Error {
             ok: false,
             error: msg.into(),
         }
     }
+
+    /// Response for the outcome of a transcription
+    pub fn from_result(result: Result<String, TranscribeError>) -> Self {
+        match result {
+            Ok(text) => WorkerResponse::success(text),
+            Err(TranscribeError::LowConfidence(confidence)) => {
+                WorkerResponse::low_confidence(confidence)
+            }
+            Err(e) => WorkerResponse::error(e.to_string()),
+        }
+    }
 }
 
 /// Run the transcription worker
 ///
 /// This is the main entry point called from `voxtype transcribe-worker`.
-/// It loads the model FIRST, signals ready, then waits for audio.
-pub fn run_worker(config: &WhisperConfig) -> anyhow::Result<()> {
-    let stdout = io::stdout();
-    let mut stdout_lock = stdout.lock();
+/// It loads the model FIRST, signals ready, then waits for audio on the
+/// socket passed as `socket_fd`.
+pub fn run_worker(config: &WhisperConfig, socket_fd: i32) -> anyhow::Result<()> {
+    // Safety: the daemon passes the socket on this descriptor, and nothing
+    // else in this process owns it
+    let socket = unsafe { UnixStream::from_raw_fd(socket_fd) };
+    let mut reader = BufReader::new(socket.try_clone()?);
+    let writer = Arc::new(Mutex::new(socket));
 
     // Step 1: Load model first (while user is speaking)
     eprintln!("[worker] Loading model: {}", config.model);
     let load_start = std::time::Instant::now();
 
-    let transcriber = match super::whisper::WhisperTranscriber::new(config) {
-        Ok(t) => t,
+    // A new worker loads the model for every recording; mapped, it comes
+    // straight from the page cache
+    let transcriber = match super::whisper::WhisperTranscriber::new_mapped(config) {
+        Ok(t) => Arc::new(t),
         Err(e) => {
-            // Write error and exit - parent will see no READY signal
-            write_response_to(
-                &mut stdout_lock,
+            // Send the error instead of Ready and exit
+            send_response(
+                &writer,
                 WorkerResponse::error(format!("Failed to load model: {}", e)),
             );
             return Ok(());
@@ -74,61 +402,26 @@ pub fn run_worker(config: &WhisperConfig) -> anyhow::Result<()> {
     );
 
     // Step 2: Signal ready (model is loaded, waiting for audio)
-    writeln!(stdout_lock, "{}", READY_SIGNAL)?;
-    stdout_lock.flush()?;
-    eprintln!("[worker] Signaled READY, waiting for audio...");
-
-    // Step 3: Read audio from stdin
-    let stdin = io::stdin();
-    let mut stdin = stdin.lock();
-
-    // Read sample count (u32 little-endian)
-    let mut count_buf = [0u8; 4];
-    if let Err(e) = stdin.read_exact(&mut count_buf) {
-        write_response_to(
-            &mut stdout_lock,
-            WorkerResponse::error(format!("Failed to read sample count: {}", e)),
-        );
-        return Ok(());
-    }
-    let sample_count = u32::from_le_bytes(count_buf) as usize;
-
-    // Validate sample count (prevent OOM from malformed input)
-    // Max 10 minutes at 16kHz = 9,600,000 samples = ~38MB
-    const MAX_SAMPLES: usize = 16000 * 60 * 10;
-    if sample_count > MAX_SAMPLES {
-        write_response_to(
-            &mut stdout_lock,
-            WorkerResponse::error(format!(
-                "Sample count too large: {} (max {})",
-                sample_count, MAX_SAMPLES
-            )),
-        );
-        return Ok(());
-    }
-
-    if sample_count == 0 {
-        write_response_to(
-            &mut stdout_lock,
-            WorkerResponse::error("Empty audio buffer"),
-        );
-        return Ok(());
-    }
+    send_message(
+        &writer,
+        &WorkerMessage::Ready {
+            version: PROTOCOL_VERSION,
+        },
+    );
+    eprintln!("[worker] Signaled ready, waiting for audio...");
 
-    // Read samples (f32 little-endian)
-    let mut samples = vec![0f32; sample_count];
-    let samples_bytes = unsafe {
-        std::slice::from_raw_parts_mut(
-            samples.as_mut_ptr() as *mut u8,
-            sample_count * std::mem::size_of::<f32>(),
-        )
+    // Step 3: Read audio from the daemon
+    let recording = match read_audio(&mut reader) {
+        Ok(recording) => recording,
+        Err(e) => {
+            send_response(&writer, WorkerResponse::error(e));
+            return Ok(());
+        }
     };
+    let sample_count = recording.samples.len();
 
-    if let Err(e) = stdin.read_exact(samples_bytes) {
-        write_response_to(
-            &mut stdout_lock,
-            WorkerResponse::error(format!("Failed to read audio samples: {}", e)),
-        );
+    if sample_count == 0 {
+        send_response(&writer, WorkerResponse::error("Empty audio buffer"));
         return Ok(());
     }
 
@@ -138,35 +431,59 @@ pub fn run_worker(config: &WhisperConfig) -> anyhow::Result<()> {
         sample_count as f32 / 16000.0
     );
 
-    // Step 4: Transcribe
+    // Stop early when the daemon cancels; the thread ends with the process
+    let cancel_target = Arc::clone(&transcriber);
+    std::thread::spawn(move || {
+        wait_for_cancel(&mut reader);
+        cancel_target.cancel();
+    });
+
+    // Step 4: Transcribe, reporting progress
     eprintln!("[worker] Starting transcription...");
     let transcribe_start = std::time::Instant::now();
-    let result = transcriber.transcribe(&samples);
+    let progress_writer = Arc::clone(&writer);
+    let result = recording.transcribe(
+        transcriber.as_ref(),
+        Arc::new(move |percent| {
+            send_message(&progress_writer, &WorkerMessage::Progress { percent })
+        }),
+    );
 
-    match result {
+    match &result {
         Ok(text) => {
             eprintln!(
                 "[worker] Transcription complete in {:.2}s: {} chars",
                 transcribe_start.elapsed().as_secs_f32(),
                 text.len()
             );
-            write_response_to(&mut stdout_lock, WorkerResponse::success(text));
+        }
+        Err(TranscribeError::LowConfidence(confidence)) => {
+            eprintln!("[worker] Low confidence transcription: {:.2}", confidence);
         }
         Err(e) => {
             eprintln!("[worker] Transcription failed: {}", e);
-            write_response_to(&mut stdout_lock, WorkerResponse::error(e.to_string()));
         }
     }
+    send_response(&writer, WorkerResponse::from_result(result));
 
     Ok(())
 }
 
-/// Write a JSON response to the given writer
-fn write_response_to<W: Write>(writer: &mut W, response: WorkerResponse) {
-    if let Ok(json) = serde_json::to_string(&response) {
-        let _ = writeln!(writer, "{}", json);
-        let _ = writer.flush();
-    }
+/// Write a message to the daemon as a JSON line
+pub fn write_worker_message<W: Write>(writer: &mut W, message: &WorkerMessage) -> io::Result<()> {
+    let json = serde_json::to_string(message).map_err(io::Error::other)?;
+    writeln!(writer, "{}", json)?;
+    writer.flush()
+}
+
+/// Send a message on the socket shared by the worker's threads
+fn send_message(socket: &Mutex<UnixStream>, message: &WorkerMessage) {
+    let _ = write_worker_message(&mut *socket.lock().unwrap(), message);
+}
+
+/// Send the result
+fn send_response(socket: &Mutex<UnixStream>, response: WorkerResponse) {
+    send_message(socket, &WorkerMessage::Result(response));
 }
 
 #[cfg(test)]
@@ -184,10 +501,113 @@ mod tests {
         let json = serde_json::to_string(&error).unwrap();
         assert!(json.contains(r#""ok":false"#));
         assert!(json.contains(r#""error":"Something went wrong""#));
+
+        let rejected = WorkerResponse::low_confidence(0.25);
+        let json = serde_json::to_string(&rejected).unwrap();
+        assert!(json.contains(r#""ok":false"#));
+        assert!(json.contains(r#""confidence":0.25"#));
     }
 
     #[test]
-    fn test_ready_signal() {
-        assert_eq!(READY_SIGNAL, "READY");
+    fn test_audio_stream_round_trip() {
+        let mut stream = Vec::new();
+        Header::current().write(&mut stream).unwrap();
+        write_audio(&mut stream, &[0.5, -0.25]).unwrap();
+        // Message kinds from a newer daemon are skipped
+        write_message(&mut stream, 9, b"prompt").unwrap();
+        write_audio(&mut stream, &[1.0]).unwrap();
+        write_end(&mut stream).unwrap();
+
+        let recording = read_audio(&mut stream.as_slice()).unwrap();
+        assert_eq!(recording.samples, vec![0.5, -0.25, 1.0]);
+        assert_eq!(recording.prompt, None);
+
+        // Without the End message the recording was abandoned
+        let truncated = &stream[..stream.len() - 5];
+        assert!(read_audio(&mut &truncated[..]).is_err());
+    }
+
+    #[test]
+    fn test_audio_stream_rejects_mismatch() {
+        // The bare sample count older daemons sent
+        let mut legacy = Vec::new();
+        legacy.extend_from_slice(&16000u32.to_le_bytes());
+        legacy.extend_from_slice(&[0u8; 16]);
+        let err = read_audio(&mut legacy.as_slice()).unwrap_err();
+        assert!(err.contains("Unrecognized"), "{}", err);
+
+        let mut newer = Vec::new();
+        Header {
+            version: PROTOCOL_VERSION + 1,
+            ..Header::current()
+        }
+        .write(&mut newer)
+        .unwrap();
+        let err = read_audio(&mut newer.as_slice()).unwrap_err();
+        assert!(err.contains("Protocol mismatch"), "{}", err);
+
+        let mut huge = Vec::new();
+        Header::current().write(&mut huge).unwrap();
+        huge.push(MSG_AUDIO);
+        huge.extend_from_slice(&((MAX_SAMPLES as u32 + 1) * 4).to_le_bytes());
+        let err = read_audio(&mut huge.as_slice()).unwrap_err();
+        assert!(err.contains("too large"), "{}", err);
+    }
+
+    #[test]
+    fn test_worker_messages() {
+        let mut lines = Vec::new();
+        write_worker_message(&mut lines, &WorkerMessage::Ready { version: 2 }).unwrap();
+        write_worker_message(&mut lines, &WorkerMessage::Progress { percent: 40 }).unwrap();
+        write_worker_message(
+            &mut lines,
+            &WorkerMessage::Result(WorkerResponse::success("Hi".to_string())),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(lines).unwrap(),
+            "{\"type\":\"ready\",\"version\":2}\n\
+             {\"type\":\"progress\",\"percent\":40}\n\
+             {\"type\":\"result\",\"ok\":true,\"text\":\"Hi\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_control_messages() {
+        let mut stream = Vec::new();
+        Header::current().write(&mut stream).unwrap();
+        write_audio(&mut stream, &[0.5]).unwrap();
+        // Control messages from a newer daemon are ignored
+        write_message(&mut stream, MSG_CONTROL, br#"{"type":"pause"}"#).unwrap();
+        write_control(&mut stream, &Control::Cancel).unwrap();
+        let err = read_audio(&mut stream.as_slice()).unwrap_err();
+        assert_eq!(err, TranscribeError::Cancelled.to_string());
+
+        // A prompt before End goes with the recording
+        let mut prompted = Vec::new();
+        Header::current().write(&mut prompted).unwrap();
+        write_audio(&mut prompted, &[0.5]).unwrap();
+        let prompt = Control::Prompt {
+            text: "tokio, serde".to_string(),
+        };
+        write_control(&mut prompted, &prompt).unwrap();
+        write_end(&mut prompted).unwrap();
+        assert_eq!(
+            read_audio(&mut prompted.as_slice()).unwrap(),
+            Recording {
+                samples: vec![0.5],
+                prompt: Some("tokio, serde".to_string()),
+            }
+        );
+
+        // After the audio, a cancel or a closed socket stops the transcription
+        let mut after = Vec::new();
+        write_audio(&mut after, &[0.5]).unwrap();
+        write_control(&mut after, &Control::Cancel).unwrap();
+        write_audio(&mut after, &[0.5]).unwrap();
+        let mut reader = after.as_slice();
+        wait_for_cancel(&mut reader);
+        assert_eq!(reader.len(), 5 + 4);
+        wait_for_cancel(&mut &[][..]);
     }
 }
@@ -0,0 +1,412 @@
+//! Wyoming protocol server for Home Assistant
+//!
+//! `voxtype serve --wyoming` offers the worker's loaded model as a
+//! speech-to-text provider for Home Assistant's Assist pipelines. Add it in
+//! Home Assistant with the Wyoming Protocol integration, giving this
+//! machine's address and port (10300 by default).
+//!
+//! Each event is a JSON header line, followed by `data_length` bytes of JSON
+//! data and `payload_length` bytes of payload. Transcribing goes:
+//! 1. Client sends `transcribe`, then `audio-start`
+//! 2. Client sends `audio-chunk` events with raw PCM payloads, then `audio-stop`
+//! 3. Server sends `transcript` with the text, or `error`
+//!
+//! A `describe` event is answered with `info`, listing the model and the
+//! languages it transcribes. Language requests from the client are ignored;
+//! the worker's own config decides.
+
+use super::remote_worker;
+use super::Transcriber;
+use crate::config::WhisperConfig;
+use serde::Deserialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use ureq::serde_json::{self, json, Map, Value};
+
+/// Wyoming protocol version sent in event headers
+const WYOMING_VERSION: &str = "1.5.3";
+
+/// Largest data or payload accepted in one event
+const MAX_EVENT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Languages multilingual Whisper models transcribe
+const WHISPER_LANGUAGES: &[&str] = &[
+    "en", "zh", "de", "es", "ru", "ko", "fr", "ja", "pt", "tr", "pl", "ca", "nl", "ar", "sv", "it",
+    "id", "hi", "fi", "vi", "he", "uk", "el", "ms", "cs", "ro", "da", "hu", "ta", "no", "th", "ur",
+    "hr", "bg", "lt", "la", "mi", "ml", "cy", "sk", "te", "fa", "lv", "bn", "sr", "az", "sl", "kn",
+    "et", "mk", "br", "eu", "is", "hy", "ne", "mn", "bs", "kk", "sq", "sw", "gl", "mr", "pa", "si",
+    "km", "sn", "yo", "so", "af", "oc", "ka", "be", "tg", "sd", "gu", "am", "yi", "lo", "uz", "fo",
+    "ht", "ps", "tk", "nn", "mt", "sa", "lb", "my", "bo", "tl", "mg", "as", "tt", "haw", "ln",
+    "ha", "ba", "jw", "su", "yue",
+];
+
+/// Header line of an event
+#[derive(Deserialize)]
+struct Header {
+    #[serde(rename = "type")]
+    kind: String,
+    /// Data sent in the header by older clients
+    #[serde(default)]
+    data: Option<Map<String, Value>>,
+    #[serde(default)]
+    data_length: usize,
+    #[serde(default)]
+    payload_length: usize,
+}
+
+/// A Wyoming event
+#[derive(Debug, PartialEq)]
+struct Event {
+    kind: String,
+    data: Map<String, Value>,
+    payload: Vec<u8>,
+}
+
+/// Read the next event, or `None` when the client has closed the connection
+fn read_event<R: BufRead>(reader: &mut R) -> io::Result<Option<Event>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let header: Header =
+        serde_json::from_str(&line).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    if header.data_length > MAX_EVENT_BYTES || header.payload_length > MAX_EVENT_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Event too large",
+        ));
+    }
+
+    let mut data = header.data.unwrap_or_default();
+    if header.data_length > 0 {
+        let mut bytes = vec![0u8; header.data_length];
+        reader.read_exact(&mut bytes)?;
+        let extra: Map<String, Value> = serde_json::from_slice(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        data.extend(extra);
+    }
+    let mut payload = vec![0u8; header.payload_length];
+    reader.read_exact(&mut payload)?;
+
+    Ok(Some(Event {
+        kind: header.kind,
+        data,
+        payload,
+    }))
+}
+
+/// Write an event with its data and no payload
+fn write_event<W: Write>(writer: &mut W, kind: &str, data: Value) -> io::Result<()> {
+    let data = data.to_string();
+    let header = json!({
+        "type": kind,
+        "version": WYOMING_VERSION,
+        "data_length": data.len(),
+    });
+    writeln!(writer, "{}", header)?;
+    writer.write_all(data.as_bytes())?;
+    writer.flush()
+}
+
+/// The `info` event's data, describing the model
+fn info(config: &WhisperConfig) -> Value {
+    let attribution = json!({
+        "name": "voxtype",
+        "url": "https://github.com/peteonrails/voxtype",
+    });
+    let languages: Vec<String> = if super::models::is_english_only(&config.model) {
+        vec!["en".to_string()]
+    } else if config.language.is_auto() {
+        WHISPER_LANGUAGES.iter().map(|l| l.to_string()).collect()
+    } else {
+        config.language.as_vec()
+    };
+    json!({
+        "asr": [{
+            "name": "voxtype",
+            "description": "Whisper speech-to-text by voxtype",
+            "attribution": attribution,
+            "installed": true,
+            "version": env!("CARGO_PKG_VERSION"),
+            "models": [{
+                "name": config.model,
+                "description": format!("Whisper {}", config.model),
+                "attribution": attribution,
+                "installed": true,
+                "version": null,
+                "languages": languages,
+            }],
+        }],
+    })
+}
+
+/// Audio received between `audio-start` and `audio-stop`
+struct Audio {
+    /// Sample rate of the chunks
+    rate: u32,
+    /// Mono samples at `rate`
+    samples: Vec<f32>,
+}
+
+impl Audio {
+    /// Start collecting audio in the format of `data`
+    fn new(data: &Map<String, Value>) -> Self {
+        Self {
+            rate: field(data, "rate").unwrap_or(16000) as u32,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Add a chunk of raw PCM audio
+    fn add(&mut self, chunk: &Event) -> Result<(), String> {
+        let width = field(&chunk.data, "width").unwrap_or(2);
+        let channels = field(&chunk.data, "channels").unwrap_or(1).max(1) as usize;
+        if let Some(rate) = field(&chunk.data, "rate") {
+            self.rate = rate as u32;
+        }
+
+        let samples: Vec<f32> = match width {
+            1 => chunk
+                .payload
+                .iter()
+                .map(|&b| (b as f32 - 128.0) / 128.0)
+                .collect(),
+            2 => chunk
+                .payload
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0)
+                .collect(),
+            4 => chunk
+                .payload
+                .chunks_exact(4)
+                .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0)
+                .collect(),
+            _ => return Err(format!("Unsupported sample width {}", width)),
+        };
+
+        // Mix to mono if stereo
+        if channels > 1 {
+            self.samples.extend(
+                samples
+                    .chunks(channels)
+                    .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32),
+            );
+        } else {
+            self.samples.extend(samples);
+        }
+        Ok(())
+    }
+
+    /// The audio as mono 16kHz samples
+    fn finish(self) -> Vec<f32> {
+        crate::audio::cpal_capture::resample(&self.samples, self.rate, 16000)
+    }
+}
+
+/// An unsigned integer field of event data
+fn field(data: &Map<String, Value>, name: &str) -> Option<u64> {
+    data.get(name).and_then(Value::as_u64)
+}
+
+/// Serve Wyoming clients until the process exits
+///
+/// Each connection gets its own thread, as Home Assistant may keep one open
+/// between pipeline runs.
+pub(super) fn serve(
+    listener: TcpListener,
+    config: &WhisperConfig,
+    transcriber: Arc<dyn Transcriber>,
+) {
+    let info = Arc::new(info(config));
+    for tcp in listener.incoming() {
+        let tcp = match tcp {
+            Ok(tcp) => tcp,
+            Err(e) => {
+                tracing::warn!("Failed to accept Wyoming connection: {}", e);
+                continue;
+            }
+        };
+        let (info, transcriber) = (Arc::clone(&info), Arc::clone(&transcriber));
+        std::thread::spawn(move || {
+            let result = remote_worker::accept(tcp, None)
+                .and_then(|conn| serve_connection(conn, transcriber.as_ref(), &info));
+            if let Err(e) = result {
+                tracing::debug!("Wyoming connection ended: {}", e);
+            }
+        });
+    }
+}
+
+/// Answer the events sent over one connection
+fn serve_connection<C: Read + Write>(
+    conn: C,
+    transcriber: &dyn Transcriber,
+    info: &Value,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(conn);
+    let mut audio: Option<Audio> = None;
+    while let Some(event) = read_event(&mut reader)? {
+        match event.kind.as_str() {
+            "describe" => write_event(reader.get_mut(), "info", info.clone())?,
+            "transcribe" => {
+                tracing::debug!("Wyoming transcription requested: {:?}", event.data);
+            }
+            "audio-start" => audio = Some(Audio::new(&event.data)),
+            "audio-chunk" => {
+                let added = audio
+                    .get_or_insert_with(|| Audio::new(&event.data))
+                    .add(&event);
+                if let Err(e) = added {
+                    audio = None;
+                    write_event(reader.get_mut(), "error", json!({ "text": e }))?;
+                }
+            }
+            "audio-stop" => {
+                let Some(audio) = audio.take() else {
+                    continue;
+                };
+                let samples = audio.finish();
+                tracing::info!(
+                    "Transcribing {:.2}s of audio for a Wyoming client",
+                    samples.len() as f32 / 16000.0
+                );
+                let reply = match transcriber.transcribe(&samples) {
+                    Ok(text) => ("transcript", json!({ "text": text })),
+                    Err(e) => {
+                        tracing::warn!("Transcription failed: {}", e);
+                        (
+                            "error",
+                            json!({ "text": format!("Transcription failed: {}", e) }),
+                        )
+                    }
+                };
+                write_event(reader.get_mut(), reply.0, reply.1)?;
+            }
+            other => tracing::debug!("Ignoring Wyoming event {}", other),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::TranscribeError;
+    use std::io::Cursor;
+
+    /// Transcriber that returns the sample count
+    struct Counting;
+
+    impl Transcriber for Counting {
+        fn transcribe(&self, samples: &[f32]) -> Result<String, TranscribeError> {
+            Ok(format!("{} samples", samples.len()))
+        }
+    }
+
+    /// Encode an event the way the Python wyoming library does
+    fn event(kind: &str, data: Value, payload: &[u8]) -> Vec<u8> {
+        let data = data.to_string();
+        let mut bytes = json!({
+            "type": kind,
+            "data_length": data.len(),
+            "payload_length": payload.len(),
+        })
+        .to_string()
+        .into_bytes();
+        bytes.push(b'\n');
+        bytes.extend_from_slice(data.as_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+
+    #[test]
+    fn test_read_event() {
+        let mut input = event("audio-chunk", json!({ "rate": 16000 }), &[1, 2]);
+        // Older clients send the data in the header
+        input.extend_from_slice(b"{\"type\": \"transcribe\", \"data\": {\"language\": \"en\"}}\n");
+        let mut reader = Cursor::new(input);
+
+        let chunk = read_event(&mut reader).unwrap().unwrap();
+        assert_eq!(chunk.kind, "audio-chunk");
+        assert_eq!(field(&chunk.data, "rate"), Some(16000));
+        assert_eq!(chunk.payload, vec![1, 2]);
+
+        let transcribe = read_event(&mut reader).unwrap().unwrap();
+        assert_eq!(transcribe.data["language"], "en");
+        assert!(read_event(&mut reader).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_info_languages() {
+        let config = WhisperConfig {
+            model: "base.en".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            info(&config)["asr"][0]["models"][0]["languages"],
+            json!(["en"])
+        );
+
+        let config = WhisperConfig {
+            model: "large-v3".to_string(),
+            language: crate::config::LanguageConfig::Single("auto".to_string()),
+            ..Default::default()
+        };
+        let languages = &info(&config)["asr"][0]["models"][0]["languages"];
+        assert_eq!(languages.as_array().unwrap().len(), WHISPER_LANGUAGES.len());
+    }
+
+    #[test]
+    fn test_transcribe_session() {
+        // One second of stereo 48kHz audio, in two chunks
+        let format = json!({ "rate": 48000, "width": 2, "channels": 2 });
+        let chunk = vec![0u8; 48000 * 2 * 2 / 2];
+        let mut input = event("describe", json!({}), &[]);
+        input.extend(event("transcribe", json!({ "language": "en" }), &[]));
+        input.extend(event("audio-start", format.clone(), &[]));
+        input.extend(event("audio-chunk", format.clone(), &chunk));
+        input.extend(event("audio-chunk", format, &chunk));
+        input.extend(event("audio-stop", json!({}), &[]));
+
+        struct Session {
+            input: Cursor<Vec<u8>>,
+            output: Vec<u8>,
+        }
+        impl Read for Session {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.input.read(buf)
+            }
+        }
+        impl Write for Session {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.output.write(buf)
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut session = Session {
+            input: Cursor::new(input),
+            output: Vec::new(),
+        };
+        let info = json!({ "asr": [] });
+        serve_connection(&mut session, &Counting, &info).unwrap();
+
+        let mut output = Cursor::new(session.output);
+        let reply = read_event(&mut output).unwrap().unwrap();
+        assert_eq!(reply.kind, "info");
+        let reply = read_event(&mut output).unwrap().unwrap();
+        assert_eq!(reply.kind, "transcript");
+        assert_eq!(reply.data["text"], "16000 samples");
+        assert!(read_event(&mut output).unwrap().is_none());
+
+        let bad = Event {
+            kind: "audio-chunk".to_string(),
+            data: json!({ "width": 3 }).as_object().unwrap().clone(),
+            payload: vec![0; 6],
+        };
+        assert!(Audio::new(&Map::new()).add(&bad).is_err());
+    }
+}
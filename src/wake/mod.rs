@@ -0,0 +1,188 @@
+//! Wake-word activation
+//!
+//! An always-listening detector on its own audio stream, separate from the
+//! recording capture: saying the wake phrase ("hey jarvis") starts a
+//! recording, and a pause ends it. Hands-free dictation for RSI and
+//! accessibility users.
+//!
+//! Detection uses openWakeWord ONNX models: a shared mel spectrogram and
+//! speech embedding model, plus a small model per phrase. Together they take
+//! a few percent of one CPU core. Needs the `wake-word` build feature.
+
+#[cfg(feature = "wake-word")]
+mod openwakeword;
+
+use crate::config::{Config, WakeWordConfig};
+use crate::error::WakeWordError;
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+/// Release the openWakeWord models are downloaded from
+const MODELS_URL: &str = "https://github.com/dscripka/openWakeWord/releases/download/v0.5.1";
+
+/// Feature models shared by all wake phrases
+pub const FEATURE_MODELS: [&str; 2] = ["melspectrogram.onnx", "embedding_model.onnx"];
+
+/// Frame length for the energy check (20ms at 16kHz)
+pub(crate) const FRAME_SIZE: usize = 320;
+
+/// RMS energy above which a frame counts as speech
+const SPEECH_ENERGY: f32 = 0.01;
+
+/// Seconds to wait for speech after the wake phrase before giving up
+const NO_SPEECH_SECS: f32 = 5.0;
+
+/// Directory holding the openWakeWord models
+pub fn models_dir() -> PathBuf {
+    Config::models_dir().join("openwakeword")
+}
+
+/// Path of the wake phrase model: a .onnx path, or a model name from the
+/// openWakeWord release
+pub fn phrase_model_path(model: &str) -> PathBuf {
+    if model.ends_with(".onnx") {
+        PathBuf::from(model)
+    } else {
+        models_dir().join(phrase_model_filename(model))
+    }
+}
+
+/// File name of a released wake phrase model
+pub fn phrase_model_filename(model: &str) -> String {
+    format!("{}_v0.1.onnx", model)
+}
+
+/// Download URL of a model file from the openWakeWord release
+pub fn model_url(filename: &str) -> String {
+    format!("{}/{}", MODELS_URL, filename)
+}
+
+/// All model files the detector needs, feature models first
+pub fn model_paths(config: &WakeWordConfig) -> Vec<PathBuf> {
+    let dir = models_dir();
+    let mut paths: Vec<PathBuf> = FEATURE_MODELS.iter().map(|name| dir.join(name)).collect();
+    paths.push(phrase_model_path(&config.model));
+    paths
+}
+
+/// Start listening for the wake phrase
+///
+/// Returns a channel that receives a message each time the phrase is heard.
+/// The detector runs until the daemon exits.
+pub async fn listen(config: &Config) -> Result<mpsc::Receiver<()>, WakeWordError> {
+    #[cfg(feature = "wake-word")]
+    {
+        openwakeword::listen(config).await
+    }
+    #[cfg(not(feature = "wake-word"))]
+    {
+        let _ = config;
+        Err(WakeWordError::NotCompiled)
+    }
+}
+
+/// Ends a wake-word recording after the speaker stops
+///
+/// Fed the recording's audio as it is captured. Ends the recording once
+/// speech was heard and then `silence_secs` passed without any, or when no
+/// speech follows the wake phrase at all.
+pub struct SilenceStop {
+    /// Silent samples that end the recording after speech
+    silence_samples: usize,
+    /// Silent samples that end the recording when nobody spoke
+    no_speech_samples: usize,
+    /// Whether speech was heard since the recording started
+    heard_speech: bool,
+    /// Silent samples since the last speech (or the start)
+    silent: usize,
+    /// Samples not yet checked, less than a frame
+    pending: Vec<f32>,
+}
+
+impl SilenceStop {
+    /// Track a recording sampled at `sample_rate`
+    pub fn new(silence_secs: f32, sample_rate: u32) -> Self {
+        Self {
+            silence_samples: (silence_secs.max(0.1) * sample_rate as f32) as usize,
+            no_speech_samples: (NO_SPEECH_SECS * sample_rate as f32) as usize,
+            heard_speech: false,
+            silent: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add captured audio; true when the recording should end
+    pub fn feed(&mut self, samples: &[f32]) -> bool {
+        self.pending.extend_from_slice(samples);
+        let frames = self.pending.len() / FRAME_SIZE;
+        for frame in self.pending.chunks_exact(FRAME_SIZE) {
+            if is_speech(frame) {
+                self.heard_speech = true;
+                self.silent = 0;
+            } else {
+                self.silent += FRAME_SIZE;
+            }
+        }
+        self.pending.drain(..frames * FRAME_SIZE);
+
+        let limit = if self.heard_speech {
+            self.silence_samples
+        } else {
+            self.no_speech_samples
+        };
+        self.silent >= limit
+    }
+}
+
+/// Whether a frame is loud enough to be speech
+pub(crate) fn is_speech(frame: &[f32]) -> bool {
+    let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame.len().max(1) as f32).sqrt();
+    rms >= SPEECH_ENERGY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phrase_model_path() {
+        assert_eq!(
+            phrase_model_path("/opt/models/hey_computer.onnx"),
+            PathBuf::from("/opt/models/hey_computer.onnx")
+        );
+        assert_eq!(
+            phrase_model_path("hey_jarvis"),
+            models_dir().join("hey_jarvis_v0.1.onnx")
+        );
+        assert!(model_url("alexa_v0.1.onnx").ends_with("/v0.5.1/alexa_v0.1.onnx"));
+        assert_eq!(model_paths(&WakeWordConfig::default()).len(), 3);
+    }
+
+    #[test]
+    fn test_silence_stop() {
+        let speech = [0.1; 1600];
+        let silence = [0.0; 1600];
+        let mut stop = SilenceStop::new(0.5, 16000);
+
+        // Speech, then a short pause
+        assert!(!stop.feed(&speech));
+        for _ in 0..4 {
+            assert!(!stop.feed(&silence));
+        }
+        // Speaking again resets the pause
+        assert!(!stop.feed(&speech));
+        for _ in 0..4 {
+            assert!(!stop.feed(&silence));
+        }
+        assert!(stop.feed(&silence));
+    }
+
+    #[test]
+    fn test_silence_stop_without_speech() {
+        let mut stop = SilenceStop::new(0.5, 16000);
+        // Silence right after the wake phrase waits longer than a pause
+        assert!(!stop.feed(&[0.0; 16000]));
+        assert!(!stop.feed(&[0.0; 3 * 16000]));
+        assert!(stop.feed(&[0.0; 16000]));
+    }
+}
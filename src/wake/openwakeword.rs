@@ -0,0 +1,245 @@
+//! openWakeWord detector
+//!
+//! Streams 80ms chunks through three ONNX models: a mel spectrogram, a
+//! speech embedding of the last 76 mel frames, and the wake phrase model,
+//! which scores the last 16 embeddings from 0.0 to 1.0.
+
+use super::model_paths;
+use crate::audio::cpal_capture::CpalCapture;
+use crate::audio::AudioCapture;
+use crate::config::Config;
+use crate::error::WakeWordError;
+use ort::session::Session;
+use ort::value::Tensor;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Samples per detector step (80ms at 16kHz)
+const CHUNK: usize = 1280;
+
+/// Samples before each chunk that the mel spectrogram window overlaps
+const MEL_CONTEXT: usize = 480;
+
+/// Mel bands per frame
+const MEL_BANDS: usize = 32;
+
+/// Mel frames per embedding
+const EMBEDDING_WINDOW: usize = 76;
+
+/// Values per embedding
+const EMBEDDING_SIZE: usize = 96;
+
+/// Embeddings scored by the wake phrase model
+const PHRASE_WINDOW: usize = 16;
+
+/// The three models and their rolling buffers
+struct Detector {
+    mel: Session,
+    embedding: Session,
+    phrase: Session,
+    threshold: f32,
+    /// Audio not yet processed, after MEL_CONTEXT samples of the previous chunk
+    audio: Vec<f32>,
+    mel_frames: VecDeque<[f32; MEL_BANDS]>,
+    embeddings: VecDeque<[f32; EMBEDDING_SIZE]>,
+}
+
+impl Detector {
+    /// Load the feature models and the wake phrase model
+    fn load(paths: &[std::path::PathBuf], threshold: f32) -> Result<Self, WakeWordError> {
+        let [mel, embedding, phrase] = paths else {
+            return Err(WakeWordError::InitFailed(
+                "expected three model files".to_string(),
+            ));
+        };
+        let detector = Self {
+            mel: load_session(mel)?,
+            embedding: load_session(embedding)?,
+            phrase: load_session(phrase)?,
+            threshold,
+            audio: vec![0.0; MEL_CONTEXT],
+            // openWakeWord starts from a buffer of ones
+            mel_frames: VecDeque::from(vec![[1.0; MEL_BANDS]; EMBEDDING_WINDOW]),
+            embeddings: VecDeque::new(),
+        };
+        tracing::info!("Wake word model loaded from {:?}", phrase);
+        Ok(detector)
+    }
+
+    /// Add 16kHz audio; true when the wake phrase was heard
+    fn push(&mut self, samples: &[f32]) -> Result<bool, String> {
+        // The models expect 16-bit sample values
+        self.audio.extend(samples.iter().map(|s| s * 32767.0));
+        let mut detected = false;
+
+        while self.audio.len() >= MEL_CONTEXT + CHUNK {
+            let window = self.audio[..MEL_CONTEXT + CHUNK].to_vec();
+            self.audio.drain(..CHUNK);
+
+            for frame in self.run_mel(window)? {
+                self.mel_frames.push_back(frame);
+            }
+            while self.mel_frames.len() > EMBEDDING_WINDOW {
+                self.mel_frames.pop_front();
+            }
+
+            let embedding = self.run_embedding()?;
+            self.embeddings.push_back(embedding);
+            if self.embeddings.len() > PHRASE_WINDOW {
+                self.embeddings.pop_front();
+            }
+            if self.embeddings.len() < PHRASE_WINDOW {
+                continue;
+            }
+
+            let score = self.run_phrase()?;
+            if score >= self.threshold {
+                tracing::debug!("Wake word score {:.2}", score);
+                // Start over so one utterance triggers once
+                self.embeddings.clear();
+                detected = true;
+            }
+        }
+
+        Ok(detected)
+    }
+
+    /// Mel spectrogram frames of a window, scaled like openWakeWord does
+    fn run_mel(&mut self, window: Vec<f32>) -> Result<Vec<[f32; MEL_BANDS]>, String> {
+        let input = Tensor::<f32>::from_array(([1usize, window.len()], window))
+            .map_err(|e| format!("Failed to create mel input tensor: {}", e))?;
+        let outputs = self
+            .mel
+            .run(ort::inputs![input])
+            .map_err(|e| format!("Mel spectrogram failed: {}", e))?;
+        let (_, data) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| format!("Failed to extract mel frames: {}", e))?;
+
+        Ok(data
+            .chunks_exact(MEL_BANDS)
+            .map(|frame| {
+                let mut scaled = [0.0; MEL_BANDS];
+                for (out, value) in scaled.iter_mut().zip(frame) {
+                    *out = value / 10.0 + 2.0;
+                }
+                scaled
+            })
+            .collect())
+    }
+
+    /// Speech embedding of the last EMBEDDING_WINDOW mel frames
+    fn run_embedding(&mut self) -> Result<[f32; EMBEDDING_SIZE], String> {
+        let frames: Vec<f32> = self.mel_frames.iter().flatten().copied().collect();
+        let input = Tensor::<f32>::from_array(([1usize, EMBEDDING_WINDOW, MEL_BANDS, 1], frames))
+            .map_err(|e| format!("Failed to create embedding input tensor: {}", e))?;
+        let outputs = self
+            .embedding
+            .run(ort::inputs![input])
+            .map_err(|e| format!("Embedding failed: {}", e))?;
+        let (_, data) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| format!("Failed to extract embedding: {}", e))?;
+
+        let mut embedding = [0.0; EMBEDDING_SIZE];
+        if data.len() < EMBEDDING_SIZE {
+            return Err(format!("Embedding has {} values", data.len()));
+        }
+        embedding.copy_from_slice(&data[..EMBEDDING_SIZE]);
+        Ok(embedding)
+    }
+
+    /// Wake phrase score of the last PHRASE_WINDOW embeddings
+    fn run_phrase(&mut self) -> Result<f32, String> {
+        let embeddings: Vec<f32> = self.embeddings.iter().flatten().copied().collect();
+        let input =
+            Tensor::<f32>::from_array(([1usize, PHRASE_WINDOW, EMBEDDING_SIZE], embeddings))
+                .map_err(|e| format!("Failed to create wake phrase input tensor: {}", e))?;
+        let outputs = self
+            .phrase
+            .run(ort::inputs![input])
+            .map_err(|e| format!("Wake phrase model failed: {}", e))?;
+        let (_, data) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| format!("Failed to extract wake phrase score: {}", e))?;
+
+        data.first()
+            .copied()
+            .ok_or_else(|| "Wake phrase model returned no score".to_string())
+    }
+}
+
+/// Load one model, single-threaded to keep the footprint small
+fn load_session(path: &Path) -> Result<Session, WakeWordError> {
+    Session::builder()
+        .map_err(|e| WakeWordError::InitFailed(format!("ONNX session builder failed: {}", e)))?
+        .with_intra_threads(1)
+        .map_err(|e| WakeWordError::InitFailed(format!("Failed to set threads: {}", e)))?
+        .commit_from_file(path)
+        .map_err(|e| WakeWordError::InitFailed(format!("Failed to load {:?}: {}", path, e)))
+}
+
+/// Load the models and start the audio stream and detector thread
+pub(super) async fn listen(config: &Config) -> Result<mpsc::Receiver<()>, WakeWordError> {
+    let paths = model_paths(&config.wake_word);
+    if let Some(missing) = paths.iter().find(|path| !path.exists()) {
+        return Err(WakeWordError::ModelNotFound(missing.display().to_string()));
+    }
+    let mut detector = Detector::load(&paths, config.wake_word.threshold)?;
+
+    // Own stream, so detection never touches the recording capture
+    let mut audio_config = config.audio.clone();
+    if let Some(ref device) = config.wake_word.device {
+        audio_config.device = device.clone();
+    }
+    audio_config.sample_rate = 16000;
+    let mut capture =
+        CpalCapture::new(&audio_config).map_err(|e| WakeWordError::InitFailed(e.to_string()))?;
+    let mut chunks = capture
+        .start()
+        .await
+        .map_err(|e| WakeWordError::InitFailed(e.to_string()))?;
+
+    let (detected_tx, detected_rx) = mpsc::channel(4);
+    let (chunk_tx, chunk_rx) = std::sync::mpsc::channel::<Vec<f32>>();
+
+    std::thread::spawn(move || {
+        while let Ok(chunk) = chunk_rx.recv() {
+            match detector.push(&chunk) {
+                Ok(true) => {
+                    if detected_tx.blocking_send(()).is_err() {
+                        break;
+                    }
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::warn!("Wake word detection stopped: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        // The capture also keeps everything it recorded; drop that regularly
+        let mut drain = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                chunk = chunks.recv() => {
+                    let Some(chunk) = chunk else { break };
+                    if chunk_tx.send(chunk).is_err() {
+                        break;
+                    }
+                }
+                _ = drain.tick() => {
+                    capture.get_samples().await;
+                }
+            }
+        }
+        let _ = capture.stop().await;
+    });
+
+    Ok(detected_rx)
+}
@@ -0,0 +1,313 @@
+//! Minimal Wayland wire protocol client
+//!
+//! Just enough of the protocol to talk to the compositor socket without
+//! libwayland: encoding requests, decoding events, passing file descriptors
+//! and binding globals. Used by the virtual-keyboard output and the caption
+//! overlay.
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// The wl_display singleton always has object id 1
+pub(crate) const WL_DISPLAY: u32 = 1;
+
+/// How long to wait for the compositor to answer
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Encoder for request arguments
+#[derive(Default)]
+pub(crate) struct Args(Vec<u8>);
+
+impl Args {
+    pub(crate) fn uint(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_ne_bytes());
+        self
+    }
+
+    pub(crate) fn int(self, value: i32) -> Self {
+        self.uint(value as u32)
+    }
+
+    pub(crate) fn string(mut self, value: &str) -> Self {
+        self = self.uint(value.len() as u32 + 1);
+        self.0.extend_from_slice(value.as_bytes());
+        self.0.push(0);
+        while !self.0.len().is_multiple_of(4) {
+            self.0.push(0);
+        }
+        self
+    }
+}
+
+/// Decoder for event arguments
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn uint(&mut self) -> Option<u32> {
+        let bytes = self.data.get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        Some(u32::from_ne_bytes(bytes.try_into().ok()?))
+    }
+
+    pub(crate) fn string(&mut self) -> Option<String> {
+        let len = self.uint()? as usize;
+        let bytes = self.data.get(self.pos..self.pos + len)?;
+        self.pos += len.div_ceil(4) * 4;
+        let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+        Some(String::from_utf8_lossy(bytes).into_owned())
+    }
+}
+
+/// Encode a message header plus arguments
+pub(crate) fn encode_message(object: u32, opcode: u16, args: Args) -> Vec<u8> {
+    let size = (8 + args.0.len()) as u32;
+    let mut message = Vec::with_capacity(size as usize);
+    message.extend_from_slice(&object.to_ne_bytes());
+    message.extend_from_slice(&((size << 16) | opcode as u32).to_ne_bytes());
+    message.extend_from_slice(&args.0);
+    message
+}
+
+/// Minimal blocking Wayland client connection
+pub(crate) struct Connection {
+    stream: UnixStream,
+    next_id: u32,
+    buffer: Vec<u8>,
+}
+
+impl Connection {
+    /// Connect to the compositor socket from WAYLAND_DISPLAY
+    pub(crate) fn connect() -> io::Result<Self> {
+        let display = std::env::var_os("WAYLAND_DISPLAY")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "WAYLAND_DISPLAY not set"))?;
+        let mut path = PathBuf::from(display);
+        if path.is_relative() {
+            let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "XDG_RUNTIME_DIR not set")
+            })?;
+            path = PathBuf::from(runtime_dir).join(path);
+        }
+        Self::connect_to(UnixStream::connect(path)?)
+    }
+
+    pub(crate) fn connect_to(stream: UnixStream) -> io::Result<Self> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        Ok(Self {
+            stream,
+            next_id: 2,
+            buffer: Vec::new(),
+        })
+    }
+
+    pub(crate) fn new_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub(crate) fn send(&mut self, object: u32, opcode: u16, args: Args) -> io::Result<()> {
+        self.stream.write_all(&encode_message(object, opcode, args))
+    }
+
+    /// Send a request carrying a file descriptor (SCM_RIGHTS)
+    pub(crate) fn send_with_fd(
+        &mut self,
+        object: u32,
+        opcode: u16,
+        args: Args,
+        fd: RawFd,
+    ) -> io::Result<()> {
+        let message = encode_message(object, opcode, args);
+        let fd_len = std::mem::size_of::<RawFd>();
+        let space = unsafe { libc::CMSG_SPACE(fd_len as u32) } as usize;
+        let mut control = vec![0u8; space];
+        let mut iov = libc::iovec {
+            iov_base: message.as_ptr() as *mut libc::c_void,
+            iov_len: message.len(),
+        };
+
+        let sent = unsafe {
+            let mut msg: libc::msghdr = std::mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+            msg.msg_controllen = space as _;
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(fd_len as u32) as _;
+            std::ptr::copy_nonoverlapping(
+                &fd as *const RawFd as *const u8,
+                libc::CMSG_DATA(cmsg),
+                fd_len,
+            );
+            libc::sendmsg(self.stream.as_raw_fd(), &msg, libc::MSG_NOSIGNAL)
+        };
+
+        if sent < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if sent as usize != message.len() {
+            return Err(io::Error::new(io::ErrorKind::WriteZero, "short write"));
+        }
+        Ok(())
+    }
+
+    /// Read the next event as (object, opcode, payload)
+    pub(crate) fn read_event(&mut self) -> io::Result<(u32, u16, Vec<u8>)> {
+        loop {
+            if self.buffer.len() >= 8 {
+                let object = u32::from_ne_bytes(self.buffer[0..4].try_into().unwrap());
+                let header = u32::from_ne_bytes(self.buffer[4..8].try_into().unwrap());
+                let size = (header >> 16) as usize;
+                if size < 8 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "malformed Wayland message",
+                    ));
+                }
+                if self.buffer.len() >= size {
+                    let payload = self.buffer[8..size].to_vec();
+                    self.buffer.drain(..size);
+                    return Ok((object, (header & 0xffff) as u16, payload));
+                }
+            }
+
+            let mut chunk = [0u8; 4096];
+            let n = self.stream.read(&mut chunk)?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "compositor closed the connection",
+                ));
+            }
+            self.buffer.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// Wait until the compositor has processed all requests so far
+    ///
+    /// Other events are passed to `on_event`. Protocol errors are returned.
+    pub(crate) fn roundtrip(
+        &mut self,
+        mut on_event: impl FnMut(u32, u16, &[u8]),
+    ) -> io::Result<()> {
+        let callback = self.new_id();
+        // wl_display.sync
+        self.send(WL_DISPLAY, 0, Args::default().uint(callback))?;
+        loop {
+            let (object, opcode, payload) = self.read_event()?;
+            if object == callback {
+                return Ok(());
+            }
+            if object == WL_DISPLAY && opcode == 0 {
+                // wl_display.error(object, code, message)
+                let mut reader = Reader::new(&payload);
+                let (_, code) = (reader.uint(), reader.uint());
+                let message = reader.string().unwrap_or_default();
+                return Err(io::Error::other(format!(
+                    "Wayland protocol error {}: {}",
+                    code.unwrap_or(0),
+                    message
+                )));
+            }
+            on_event(object, opcode, &payload);
+        }
+    }
+
+    /// Fetch the registry and the globals it advertises as (name, interface)
+    pub(crate) fn globals(&mut self) -> io::Result<(u32, Vec<(u32, String)>)> {
+        let registry = self.new_id();
+        // wl_display.get_registry
+        self.send(WL_DISPLAY, 1, Args::default().uint(registry))?;
+
+        let mut globals = Vec::new();
+        self.roundtrip(|object, opcode, payload| {
+            // wl_registry.global(name, interface, version)
+            if object != registry || opcode != 0 {
+                return;
+            }
+            let mut reader = Reader::new(payload);
+            if let (Some(name), Some(interface)) = (reader.uint(), reader.string()) {
+                globals.push((name, interface));
+            }
+        })?;
+        Ok((registry, globals))
+    }
+
+    /// Bind a global from `globals()` and return the new object id
+    pub(crate) fn bind(
+        &mut self,
+        registry: u32,
+        name: u32,
+        interface: &str,
+        version: u32,
+    ) -> io::Result<u32> {
+        let id = self.new_id();
+        // wl_registry.bind(name, interface, version, new_id)
+        self.send(
+            registry,
+            0,
+            Args::default()
+                .uint(name)
+                .string(interface)
+                .uint(version)
+                .uint(id),
+        )?;
+        Ok(id)
+    }
+}
+
+/// Put `data` in an anonymous file to hand to the compositor
+pub(crate) fn memfd(name: &CStr, data: &[u8]) -> io::Result<File> {
+    let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut file = unsafe { File::from_raw_fd(fd) };
+    file.write_all(data)?;
+    Ok(file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Seek;
+
+    #[test]
+    fn test_message_encoding() {
+        let message = encode_message(2, 0, Args::default().uint(7).string("wl_seat"));
+        // header (8) + uint (4) + string length (4) + "wl_seat\0" (8)
+        assert_eq!(message.len(), 24);
+        assert_eq!(
+            u32::from_ne_bytes(message[4..8].try_into().unwrap()),
+            24 << 16
+        );
+
+        let mut reader = Reader::new(&message[8..]);
+        assert_eq!(reader.uint(), Some(7));
+        assert_eq!(reader.string().as_deref(), Some("wl_seat"));
+        assert_eq!(reader.uint(), None);
+    }
+
+    #[test]
+    fn test_memfd() {
+        let mut file = memfd(c"voxtype-test", b"hello").unwrap();
+        let mut contents = String::new();
+        file.rewind().unwrap();
+        file.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, "hello");
+    }
+}